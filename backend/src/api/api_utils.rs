@@ -11,15 +11,19 @@ use crate::api::model::streams::provider_stream::{create_channel_unavailable_str
 use crate::api::model::streams::provider_stream_factory::{create_provider_stream, ProviderStreamFactoryOptions};
 use crate::api::model::streams::shared_stream_manager::SharedStreamManager;
 use crate::api::model::streams::throttled_stream::ThrottledStream;
-use crate::auth::Claims;
-use crate::model::{ConfigTarget, ProxyUserCredentials};
+use crate::auth::{Claims, ClientIp};
+use crate::model::{ChannelFailover, ConfigTarget, ProxyUserCredentials};
 use crate::model::{ConfigInput, InputFetchMethod};
+use crate::repository::failover_repository::load_channel_failover;
+use crate::repository::xtream_repository;
 use shared::model::{PlaylistEntry, PlaylistItemType, TargetType, UserConnectionPermission, XtreamCluster};
 use crate::tools::atomic_once_flag::AtomicOnceFlag;
 use crate::tools::lru_cache::LRUResourceCache;
 use shared::utils::{DASH_EXT, HLS_EXT};
-use shared::utils::{default_grace_period_millis, human_readable_byte_size};
+use shared::utils::{default_failover_timeout_secs, default_grace_period_millis, human_readable_byte_size};
 use crate::utils::create_new_file_for_write;
+use crate::utils::rate_limiter::{acquire_provider_permit, ProviderRateLimiter};
+use crate::utils::cookie_jar::ProviderCookieJar;
 use crate::utils::request;
 use crate::utils::request::{extract_extension_from_url, replace_url_extension, sanitize_sensitive_info};
 use crate::utils::{debug_if_enabled, trace_if_enabled};
@@ -30,13 +34,15 @@ use axum::response::IntoResponse;
 use chrono::{DateTime, Utc};
 use futures::{StreamExt, TryStreamExt};
 use jsonwebtoken::{decode, Algorithm, DecodingKey, Validation};
-use log::{debug, error, log_enabled, trace};
+use log::{debug, error, log_enabled, trace, warn};
 use reqwest::StatusCode;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::io::BufWriter;
 use std::path::Path;
+use std::sync::atomic::AtomicU32;
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
 use url::Url;
 
@@ -146,6 +152,31 @@ pub fn get_user_target<'a>(api_req: &'a UserApiRequest, app_state: &'a AppState)
     get_user_target_by_credentials(username, password, api_req, app_state)
 }
 
+/// Cause of a denied playlist or guide request.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ClientErrorKind {
+    Unauthorized,
+    Expired,
+}
+
+/// Returns the `status` with the configured `client_error` message, or the bare `status` if it is not enabled.
+pub fn client_error_response(app_state: &AppState, status: StatusCode, kind: ClientErrorKind) -> axum::response::Response {
+    match app_state.config.client_error.as_ref().filter(|client_error| client_error.enabled) {
+        Some(client_error) => {
+            let message = match kind {
+                ClientErrorKind::Unauthorized => &client_error.unauthorized,
+                ClientErrorKind::Expired => &client_error.expired,
+            };
+            axum::response::Response::builder()
+                .status(status)
+                .header(axum::http::header::CONTENT_TYPE, client_error.content_type.as_str())
+                .body(Body::from(message.clone()))
+                .unwrap_or_else(|_| status.into_response())
+        }
+        None => status.into_response(),
+    }
+}
+
 pub struct StreamOptions {
     pub stream_retry: bool,
     pub stream_force_retry_secs: u32,
@@ -242,6 +273,7 @@ pub struct StreamDetails {
     pub input_name: Option<String>,
     pub grace_period_millis: u64,
     pub reconnect_flag: Option<Arc<AtomicOnceFlag>>,
+    pub reconnect_count: Option<Arc<AtomicU32>>,
     pub provider_connection_guard: Option<ProviderConnectionGuard>,
 }
 
@@ -253,6 +285,7 @@ impl StreamDetails {
             input_name: None,
             grace_period_millis: default_grace_period_millis(),
             reconnect_flag: None,
+            reconnect_count: None,
             provider_connection_guard: None,
         }
     }
@@ -271,6 +304,8 @@ struct StreamingStrategy {
     provider_connection_guard: Option<ProviderConnectionGuard>,
     provider_stream_state: ProviderStreamState,
     input_headers: Option<HashMap<String, String>>,
+    rate_limiter: Option<Arc<ProviderRateLimiter>>,
+    cookie_jar: Option<Arc<ProviderCookieJar>>,
 }
 
 /// Determines the appropriate streaming strategy for the given input and stream URL.
@@ -286,7 +321,8 @@ struct StreamingStrategy {
 /// The function returns:
 /// - an optional `ProviderConnectionGuard` to manage the connection's lifecycle,
 /// - a `ProviderStreamState` describing how the stream state is,
-/// - and optional HTTP headers to include in the request.
+/// - optional HTTP headers to include in the request,
+/// - and the cookie jar of the provider connection.
 ///
 /// This logic helps abstract the decision-making behind provider selection and stream URL resolution.
 async fn resolve_streaming_strategy(app_state: &AppState, stream_url: &str, input: &ConfigInput, force_provider: Option<&str>)
@@ -319,10 +355,13 @@ async fn resolve_streaming_strategy(app_state: &AppState, stream_url: &str, inpu
             }
         }
     };
+    let cookie_jar = provider_connection_guard.get_provider_config().map(|provider| Arc::clone(&provider.cookie_jar));
     StreamingStrategy {
         provider_connection_guard: Some(provider_connection_guard),
         provider_stream_state: stream_response_params,
-        input_headers: Some(input.headers.clone())
+        input_headers: Some(input.headers.clone()),
+        rate_limiter: input.t_rate_limiter.clone(),
+        cookie_jar,
     }
 }
 
@@ -335,15 +374,15 @@ fn get_grace_period_millis(connection_permission: UserConnectionPermission, stre
 }
 
 #[allow(clippy::too_many_arguments)]
-async fn create_stream_response_details(app_state: &AppState,
-                                        stream_options: &StreamOptions,
-                                        stream_url: &str,
-                                        req_headers: &HeaderMap,
-                                        input: &ConfigInput,
-                                        item_type: PlaylistItemType,
-                                        share_stream: bool,
-                                        connection_permission: UserConnectionPermission,
-                                        force_provider: Option<&str>) -> StreamDetails {
+async fn open_stream_response_details(app_state: &AppState,
+                                      stream_options: &StreamOptions,
+                                      stream_url: &str,
+                                      req_headers: &HeaderMap,
+                                      input: &ConfigInput,
+                                      item_type: PlaylistItemType,
+                                      share_stream: bool,
+                                      connection_permission: UserConnectionPermission,
+                                      force_provider: Option<&str>) -> StreamDetails {
     let mut streaming_strategy =
         resolve_streaming_strategy(app_state, stream_url, input, force_provider).await;
     let config_grace_period_millis = app_state.config.reverse_proxy.as_ref()
@@ -359,22 +398,25 @@ async fn create_stream_response_details(app_state: &AppState,
                 input_name: None,
                 grace_period_millis,
                 reconnect_flag: None,
+                reconnect_count: None,
                 provider_connection_guard: streaming_strategy.provider_connection_guard.take(),
             }
         }
         ProviderStreamState::Available(provider_name, request_url) |
         ProviderStreamState::GracePeriod(provider_name, request_url) => {
-            let parsed_url = Url::parse(&request_url);
-            let ((stream, stream_info), reconnect_flag) = if let Ok(url) = parsed_url {
-                let provider_stream_factory_options = ProviderStreamFactoryOptions::new(item_type, share_stream, stream_options, &url, req_headers, streaming_strategy.input_headers.as_ref());
+            let parsed_url = Url::parse(&request_url).map(|url| input.get_tls_url(&url));
+            let ((stream, stream_info), reconnect) = if let Ok(url) = parsed_url {
+                let provider_stream_factory_options = ProviderStreamFactoryOptions::new(item_type, share_stream, stream_options, &url, req_headers, streaming_strategy.input_headers.as_ref(), streaming_strategy.rate_limiter.take())
+                    .with_cookie_jar(streaming_strategy.cookie_jar.take());
                 let reconnect_flag = provider_stream_factory_options.get_reconnect_flag_clone();
-                let provider_stream = match create_provider_stream(Arc::clone(&app_state.config), Arc::clone(&app_state.http_client), provider_stream_factory_options).await {
+                let reconnect_count = provider_stream_factory_options.get_reconnect_count_clone();
+                let provider_stream = match create_provider_stream(Arc::clone(&app_state.config), input.get_client(&app_state.http_client), provider_stream_factory_options).await {
                     None => (None, None),
                     Some((stream, info)) => {
                         (Some(stream), info)
                     }
                 };
-                (provider_stream, Some(reconnect_flag))
+                (provider_stream, Some((reconnect_flag, reconnect_count)))
             } else {
                 ((None, None), None)
             };
@@ -398,18 +440,106 @@ async fn create_stream_response_details(app_state: &AppState,
                 }
             }
 
+            let (reconnect_flag, reconnect_count) = reconnect.unzip();
             StreamDetails {
                 stream,
                 stream_info,
                 input_name: provider_name,
                 grace_period_millis,
                 reconnect_flag,
+                reconnect_count,
                 provider_connection_guard: streaming_strategy.provider_connection_guard.take(),
             }
         }
     }
 }
 
+enum FailoverProbe {
+    Streaming(StreamDetails),
+    // a custom stream like channel unavailable, served when all urls fail
+    Failed(Option<StreamDetails>),
+}
+
+// A provider stream is only accepted, when the provider answered with success and sends data within the timeout.
+async fn probe_failover_stream(mut stream_details: StreamDetails, timeout: Duration) -> FailoverProbe {
+    // custom streams have no response url
+    let is_provider_stream = stream_details.stream_info.as_ref().is_some_and(|(_, status, url)| status.is_success() && url.is_some());
+    let Some(mut stream) = stream_details.stream.take().filter(|_| is_provider_stream) else {
+        return FailoverProbe::Failed(Some(stream_details).filter(|_| !is_provider_stream));
+    };
+    if timeout.is_zero() {
+        stream_details.stream = Some(stream);
+        return FailoverProbe::Streaming(stream_details);
+    }
+    match tokio::time::timeout(timeout, stream.next()).await {
+        Ok(Some(Ok(chunk))) => {
+            stream_details.stream = Some(futures::stream::once(async move { Ok(chunk) }).chain(stream).boxed());
+            FailoverProbe::Streaming(stream_details)
+        }
+        _ => FailoverProbe::Failed(None),
+    }
+}
+
+/// Opens the stream of a channel with failover urls. When the provider answers with an error or sends no data,
+/// the next url is tried. If all fail, the custom stream of the first url or the channel unavailable stream is served.
+#[allow(clippy::too_many_arguments)]
+async fn create_stream_response_details(app_state: &AppState,
+                                        stream_options: &StreamOptions,
+                                        stream_url: &str,
+                                        req_headers: &HeaderMap,
+                                        input: &ConfigInput,
+                                        item_type: PlaylistItemType,
+                                        share_stream: bool,
+                                        connection_permission: UserConnectionPermission,
+                                        force_provider: Option<&str>,
+                                        failover: &[ChannelFailover]) -> StreamDetails {
+    let stream_details = open_stream_response_details(app_state, stream_options, stream_url, req_headers, input, item_type, share_stream, connection_permission, force_provider).await;
+    if failover.is_empty() {
+        return stream_details;
+    }
+    let timeout = Duration::from_secs(app_state.config.reverse_proxy.as_ref()
+        .and_then(|reverse_proxy| reverse_proxy.stream.as_ref())
+        .map_or_else(default_failover_timeout_secs, |stream| stream.failover_timeout_secs));
+    let mut fallback = match probe_failover_stream(stream_details, timeout).await {
+        FailoverProbe::Streaming(stream_details) => return stream_details,
+        FailoverProbe::Failed(custom) => custom,
+    };
+    let candidates = failover.iter().filter_map(|channel| app_state.config.get_input_by_name(&channel.input_name).map(|input| (input, channel.url.as_str())));
+    for (failover_input, failover_url) in candidates {
+        // the connection of the failed provider is released before the next one is allocated
+        if let Some(custom) = fallback.as_mut() {
+            drop(custom.provider_connection_guard.take());
+        }
+        debug!("Stream {} failed, failover to input {}", sanitize_sensitive_info(stream_url), failover_input.name);
+        let stream_details = open_stream_response_details(app_state, stream_options, failover_url, req_headers, failover_input, item_type, share_stream, connection_permission, None).await;
+        match probe_failover_stream(stream_details, timeout).await {
+            FailoverProbe::Streaming(stream_details) => return stream_details,
+            FailoverProbe::Failed(custom) => {
+                if fallback.is_none() {
+                    fallback = custom;
+                }
+            }
+        }
+    }
+    warn!("All failover urls of stream {} failed", sanitize_sensitive_info(stream_url));
+    fallback.map_or_else(|| {
+        let (stream, stream_info) = create_channel_unavailable_stream(&app_state.config, &[], StatusCode::BAD_GATEWAY);
+        StreamDetails { stream, stream_info, input_name: None, grace_period_millis: 0, reconnect_flag: None, reconnect_count: None, provider_connection_guard: None }
+    }, |mut custom| {
+        drop(custom.provider_connection_guard.take());
+        custom
+    })
+}
+
+/// Opens the provider stream of a recording, like a client stream it holds a provider connection.
+/// Returns `None` if the provider has no free connection or the stream can't be opened.
+pub(in crate::api) async fn create_recording_stream(app_state: &AppState, stream_url: &str, input: &ConfigInput, item_type: PlaylistItemType) -> Option<StreamDetails> {
+    let stream_options = get_stream_options(app_state);
+    let stream_details = create_stream_response_details(app_state, &stream_options, stream_url, &HeaderMap::new(), input, item_type, false, UserConnectionPermission::Allowed, None, &[]).await;
+    // custom streams like the provider connections exhausted video have no reconnect flag
+    (stream_details.has_stream() && stream_details.reconnect_flag.is_some()).then_some(stream_details)
+}
+
 pub struct RedirectParams<'a, P>
 where
     P: PlaylistEntry,
@@ -516,10 +646,10 @@ fn is_throttled_stream(item_type: PlaylistItemType, throttle_kbps: usize) -> boo
     throttle_kbps > 0 && matches!(item_type, PlaylistItemType::Video | PlaylistItemType::Series  | PlaylistItemType::SeriesInfo | PlaylistItemType::Catchup)
 }
 
-fn prepare_body_stream(app_state: &AppState, item_type: PlaylistItemType, stream: ActiveClientStream) -> Body {
-    let throttle_kbps = usize::try_from(get_stream_throttle(app_state)).unwrap_or_default();
+fn prepare_body_stream(item_type: PlaylistItemType, stream: ActiveClientStream, (throttle_kbps, burst_bytes): (u64, u64)) -> Body {
+    let throttle_kbps = usize::try_from(throttle_kbps).unwrap_or_default();
     let body_stream = if is_throttled_stream(item_type, throttle_kbps) {
-        axum::body::Body::from_stream(ThrottledStream::new(stream.boxed(), throttle_kbps))
+        axum::body::Body::from_stream(ThrottledStream::new(stream.boxed(), throttle_kbps, usize::try_from(burst_bytes).unwrap_or(usize::MAX)))
     } else {
         axum::body::Body::from_stream(stream)
     };
@@ -532,17 +662,19 @@ pub async fn force_provider_stream_response(app_state: &AppState,
                                             item_type: PlaylistItemType,
                                             req_headers: &HeaderMap,
                                             input: &ConfigInput,
+                                            target: &ConfigTarget,
                                             user: &ProxyUserCredentials) -> impl axum::response::IntoResponse + Send {
     let stream_options = get_stream_options(app_state);
     let share_stream = false;
     let connection_permission = UserConnectionPermission::Allowed;
 
     let mut stream_details =
-        create_stream_response_details(app_state, &stream_options, &user_session.stream_url, req_headers, input, item_type, share_stream, connection_permission, Some(&user_session.provider)).await;
+        create_stream_response_details(app_state, &stream_options, &user_session.stream_url, req_headers, input, item_type, share_stream, connection_permission, Some(&user_session.provider), &[]).await;
 
     if stream_details.has_stream() {
         let provider_response = stream_details.stream_info.as_ref().map(|(h, sc,url)| (h.clone(), *sc, url.clone()));
-        let stream = ActiveClientStream::new(stream_details, app_state, user, connection_permission).await;
+        let throttle = get_stream_throttle(app_state, target, user_session.virtual_id, item_type, provider_response.as_ref().map(|(h, _, _)| h.as_slice()));
+        let stream = ActiveClientStream::new(stream_details, app_state, user, Some(&user_session.token), connection_permission).await;
 
         let (status_code, header_map) = get_stream_response_with_headers(provider_response.map(|(h,s,_)| (h, s)));
         let mut response = axum::response::Response::builder().status(status_code);
@@ -550,7 +682,7 @@ pub async fn force_provider_stream_response(app_state: &AppState,
             response = response.header(key, value);
         }
 
-        let body_stream = prepare_body_stream(app_state, item_type, stream);
+        let body_stream = prepare_body_stream(item_type, stream, throttle);
         debug_if_enabled!("Streaming provider forced stream request from {}", sanitize_sensitive_info(&user_session.stream_url));
         return response.body(body_stream).unwrap().into_response();
     }
@@ -583,6 +715,13 @@ pub async fn stream_response(app_state: &AppState,
         return create_custom_video_stream_response(&app_state.config, CustomVideoStreamType::UserConnectionsExhausted).into_response();
     }
 
+    if matches!(item_type, PlaylistItemType::Live | PlaylistItemType::LiveUnknown)
+        && app_state.config.t_custom_stream_response.as_ref().is_some_and(|c| c.maintenance.is_some())
+        && app_state.maintenance.is_active_for(&target.name, virtual_id) {
+        debug!("Streaming maintenance announcement for {}", target.name);
+        return create_custom_video_stream_response(&app_state.config, CustomVideoStreamType::Maintenance).into_response();
+    }
+
     let share_stream = is_stream_share_enabled(item_type, target);
     if share_stream {
         if let Some(value) = shared_stream_response(app_state, stream_url, user, connection_permission).await {
@@ -591,14 +730,20 @@ pub async fn stream_response(app_state: &AppState,
     }
 
     let stream_options = get_stream_options(app_state);
+    let failover = if target.is_channel_failover() && matches!(item_type, PlaylistItemType::Live | PlaylistItemType::LiveUnknown) {
+        load_channel_failover(&app_state.config, &target.name, virtual_id).await
+    } else {
+        vec![]
+    };
     let mut stream_details =
-        create_stream_response_details(app_state, &stream_options, stream_url, req_headers, input, item_type, share_stream, connection_permission, None).await;
+        create_stream_response_details(app_state, &stream_options, stream_url, req_headers, input, item_type, share_stream, connection_permission, None, &failover).await;
     if stream_details.has_stream() {
         // let content_length = get_stream_content_length(provider_response.as_ref());
         let provider_response = stream_details.stream_info.as_ref().map(|(h, sc, response_url)| (h.clone(), *sc, response_url.clone()));
         let provider_name = stream_details.provider_connection_guard.as_ref().and_then(ProviderConnectionGuard::get_provider_name);
+        let throttle = get_stream_throttle(app_state, target, virtual_id, item_type, provider_response.as_ref().map(|(h, _, _)| h.as_slice()));
 
-        let stream = ActiveClientStream::new(stream_details, app_state, user, connection_permission).await;
+        let stream = ActiveClientStream::new(stream_details, app_state, user, Some(session_token), connection_permission).await;
         let stream_resp = if share_stream {
             debug_if_enabled!("Streaming shared stream request from {}", sanitize_sensitive_info(stream_url));
             // Shared Stream response
@@ -636,7 +781,7 @@ pub async fn stream_response(app_state: &AppState,
                 }
             }
 
-            let body_stream = prepare_body_stream(app_state, item_type, stream);
+            let body_stream = prepare_body_stream(item_type, stream, throttle);
             response.body(body_stream).unwrap().into_response()
         };
 
@@ -646,12 +791,48 @@ pub async fn stream_response(app_state: &AppState,
     axum::http::StatusCode::BAD_REQUEST.into_response()
 }
 
-fn get_stream_throttle(app_state: &AppState) -> u64 {
-    app_state.config
-        .reverse_proxy
-        .as_ref()
-        .and_then(|reverse_proxy| reverse_proxy.stream.as_ref())
-        .map(|stream| stream.throttle_kbps).unwrap_or_default()
+fn get_content_length(headers: &[(String, String)]) -> Option<u64> {
+    // a range response has the full size in `content-range: bytes 0-1023/146515`
+    let get_header = |name: &str| headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.trim());
+    get_header("content-range")
+        .and_then(|range| range.rsplit_once('/'))
+        .and_then(|(_, total)| total.parse().ok())
+        .or_else(|| get_header("content-length").and_then(|length| length.parse().ok()))
+}
+
+// The bitrate reported in the vod info, or the file size divided by the duration of the movie.
+fn get_vod_bitrate_kbps(app_state: &AppState, target: &ConfigTarget, virtual_id: u32, provider_headers: Option<&[(String, String)]>) -> Option<u64> {
+    let content = xtream_repository::xtream_load_vod_info(&app_state.config, &target.name, virtual_id)?;
+    let doc: serde_json::Value = serde_json::from_str(&content).ok()?;
+    let info = doc.get(crate::model::XC_TAG_INFO_DATA)?;
+    let get_field = |field: &str| match info.get(field)? {
+        serde_json::Value::Number(value) => value.as_u64(),
+        serde_json::Value::String(value) => value.trim().parse::<u64>().ok(),
+        _ => None,
+    }.filter(|value| *value > 0);
+    get_field("bitrate").or_else(|| {
+        let duration_secs = get_field("duration_secs")?;
+        let content_length = provider_headers.and_then(get_content_length)?;
+        Some(content_length * 8 / 1000 / duration_secs).filter(|kbps| *kbps > 0)
+    })
+}
+
+// Returns the throttle kbps and burst bytes for the stream.
+#[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+fn get_stream_throttle(app_state: &AppState, target: &ConfigTarget, virtual_id: u32, item_type: PlaylistItemType, provider_headers: Option<&[(String, String)]>) -> (u64, u64) {
+    let Some(stream) = app_state.config.reverse_proxy.as_ref().and_then(|reverse_proxy| reverse_proxy.stream.as_ref()) else {
+        return (0, 0);
+    };
+    let throttle_kbps = if stream.throttle_bitrate_factor > 0.0 && item_type == PlaylistItemType::Video {
+        get_vod_bitrate_kbps(app_state, target, virtual_id, provider_headers)
+            .map(|bitrate| (bitrate as f64 * stream.throttle_bitrate_factor).round() as u64)
+            .filter(|kbps| *kbps > 0)
+            .inspect(|kbps| debug_if_enabled!("Throttle vod stream {} to {} kbps", virtual_id, kbps))
+            .unwrap_or(stream.throttle_kbps)
+    } else {
+        stream.throttle_kbps
+    };
+    (throttle_kbps, stream.throttle_burst_bytes)
 }
 
 async fn shared_stream_response(app_state: &AppState, stream_url: &str, user: &ProxyUserCredentials, connect_permission: UserConnectionPermission) -> Option<impl IntoResponse> {
@@ -660,7 +841,7 @@ async fn shared_stream_response(app_state: &AppState, stream_url: &str, user: &P
         if let Some(headers) = app_state.shared_stream_manager.get_shared_state_headers(stream_url).await {
             let (status_code, header_map) = get_stream_response_with_headers(Some((headers.clone(), StatusCode::OK)));
             let stream_details = StreamDetails::from_stream(stream);
-            let stream = ActiveClientStream::new(stream_details, app_state, user, connect_permission).await.boxed();
+            let stream = ActiveClientStream::new(stream_details, app_state, user, None, connect_permission).await.boxed();
             let mut response = axum::response::Response::builder()
                 .status(status_code);
             for (key, value) in &header_map {
@@ -673,7 +854,7 @@ async fn shared_stream_response(app_state: &AppState, stream_url: &str, user: &P
 }
 
 pub fn is_stream_share_enabled(item_type: PlaylistItemType, target: &ConfigTarget) -> bool {
-    (item_type == PlaylistItemType::Live  /* || item_type == PlaylistItemType::LiveHls */) && target.options.as_ref().is_some_and(|opt| opt.share_live_streams)
+    matches!(item_type, PlaylistItemType::Live | PlaylistItemType::LiveHls) && target.options.as_ref().is_some_and(|opt| opt.share_live_streams)
 }
 
 pub type HeaderFilter = Option<Box<dyn Fn(&str) -> bool + Send>>;
@@ -719,7 +900,9 @@ pub async fn resource_response(app_state: &AppState, resource_url: &str, req_hea
     }
     trace_if_enabled!("Try to fetch resource {}", sanitize_sensitive_info(resource_url));
     if let Ok(url) = Url::parse(resource_url) {
-        let client = request::get_client_request(&app_state.http_client, input.map_or(InputFetchMethod::GET, |i| i.method), input.map(|i| &i.headers), &url, Some(&req_headers));
+        let (http_client, url) = input.map_or_else(|| (Arc::clone(&app_state.http_client), url.clone()), |i| (i.get_client(&app_state.http_client), i.get_tls_url(&url)));
+        let client = request::get_client_request(&http_client, input.map_or(InputFetchMethod::GET, |i| i.method), input.map(|i| &i.headers), &url, Some(&req_headers));
+        let _permit = acquire_provider_permit(input.and_then(|i| i.t_rate_limiter.as_ref())).await;
         match client.send().await {
             Ok(response) => {
                 let status = response.status();
@@ -798,6 +981,22 @@ pub fn redirect(url: &str) -> impl IntoResponse {
         .unwrap()
 }
 
+/// Records the activity of the user with the client ip and the user agent of the request.
+pub async fn update_user_activity(app_state: &AppState, user: &ProxyUserCredentials, client_ip: ClientIp, req_headers: &HeaderMap) {
+    let user_agent = req_headers.get(axum::http::header::USER_AGENT).and_then(|value| value.to_str().ok());
+    app_state.active_users.update_user_activity(&user.username, client_ip.0, user_agent).await;
+}
+
+/// Returns the start offset of a `Range: bytes=<start>-` request header.
+pub fn get_range_start_bytes(req_headers: &HeaderMap) -> Option<u64> {
+    req_headers
+        .get(axum::http::header::RANGE)
+        .and_then(|h| h.to_str().ok())
+        .and_then(|range| range.strip_prefix("bytes="))
+        .and_then(|range| range.split('-').next())
+        .and_then(|start| start.trim().parse::<u64>().ok())
+}
+
 pub async fn is_seek_request(
     cluster: XtreamCluster,
     req_headers: &HeaderMap,
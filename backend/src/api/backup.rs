@@ -0,0 +1,192 @@
+use crate::api::model::app_state::AppState;
+use crate::api::scheduler::datetime_to_instant;
+use crate::model::{BackupConfig, BackupTarget, BackupUpload, Config};
+use crate::repository::user_repository::get_api_user_db_path;
+use crate::utils::tar_archive::TarGzWriter;
+use crate::utils::request::sanitize_sensitive_info;
+use crate::utils::s3::{s3_delete, s3_put};
+use crate::utils::encrypt_with_password;
+use chrono::Local;
+use cron::Schedule;
+use log::{debug, error, info};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+const BACKUP_FILE_PREFIX: &str = "tuliprox_backup_";
+
+fn get_file_name(path: &Path) -> String {
+    path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+fn collect_dir_files(archive_dir: &str, root: &Path, dir: &Path, files: &mut Vec<(String, PathBuf)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            collect_dir_files(archive_dir, root, &path, files);
+        } else if let Ok(relative) = path.strip_prefix(root) {
+            let relative = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+            files.push((format!("{archive_dir}/{relative}"), path));
+        }
+    }
+}
+
+// the archive name and the local path of the config files, the user db and the user configs
+fn collect_backup_files(cfg: &Config) -> Vec<(String, PathBuf)> {
+    let mut files = vec![];
+    for file in [&cfg.t_config_file_path, &cfg.t_sources_file_path, &cfg.t_api_proxy_file_path, &cfg.t_epg_aliases_file_path, &cfg.t_epg_schedules_file_path] {
+        let path = PathBuf::from(file);
+        if path.is_file() {
+            files.push((format!("config/{}", get_file_name(&path)), path));
+        }
+    }
+    // the mapping can be a single file or a directory with mapping files
+    let mapping_path = PathBuf::from(&cfg.t_mapping_file_path);
+    if mapping_path.is_dir() {
+        collect_dir_files("config/mapping", &mapping_path, &mapping_path, &mut files);
+    } else if mapping_path.is_file() {
+        files.push((format!("config/{}", get_file_name(&mapping_path)), mapping_path));
+    }
+    let user_db_path = get_api_user_db_path(cfg);
+    if user_db_path.is_file() {
+        files.push((format!("data/{}", get_file_name(&user_db_path)), user_db_path));
+    }
+    if let Some(user_config_dir) = cfg.user_config_dir.as_ref() {
+        let user_config_path = PathBuf::from(user_config_dir);
+        collect_dir_files("user_config", &user_config_path, &user_config_path, &mut files);
+    }
+    files
+}
+
+async fn create_backup_archive(cfg: &Config) -> Result<Vec<u8>, String> {
+    let mut archive = TarGzWriter::new(Vec::new());
+    for (name, path) in collect_backup_files(cfg) {
+        let content = {
+            let _file_lock = cfg.file_locks.read_lock(&path).await;
+            tokio::fs::read(&path).await.map_err(|err| format!("can't read {}: {err}", path.display()))?
+        };
+        archive.append(&name, &content).map_err(|err| err.to_string())?;
+    }
+    archive.finish().map_err(|err| err.to_string())
+}
+
+// removes the oldest archives of the backup_dir, returns the names of the removed archives
+fn apply_retention(backup_dir: &Path, retention: usize) -> Vec<String> {
+    if retention == 0 {
+        return vec![];
+    }
+    let Ok(entries) = std::fs::read_dir(backup_dir) else { return vec![] };
+    let mut names: Vec<String> = entries.flatten()
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| name.starts_with(BACKUP_FILE_PREFIX))
+        .collect();
+    // the names contain the timestamp, the oldest archives are sorted first
+    names.sort();
+    let remove_count = names.len().saturating_sub(retention);
+    names.into_iter().take(remove_count)
+        .filter(|name| match std::fs::remove_file(backup_dir.join(name)) {
+            Ok(()) => true,
+            Err(err) => {
+                error!("Failed to remove backup {name}: {err}");
+                false
+            }
+        })
+        .collect()
+}
+
+async fn send_upload_request(client: &reqwest::Client, upload: &BackupUpload, name: &str, content: Option<Vec<u8>>) -> Result<(), String> {
+    let url = upload.get_file_url(name)?;
+    match upload.t_target.as_ref() {
+        Some(BackupTarget::S3 { .. }) => {
+            let credentials = upload.get_s3_credentials();
+            match content {
+                Some(content) => s3_put(client, &url, &credentials, content).await,
+                None => s3_delete(client, &url, &credentials).await,
+            }
+        }
+        Some(BackupTarget::Http(_)) => {
+            let mut request = match content {
+                Some(content) => client.put(url).body(content),
+                None => client.delete(url),
+            };
+            let (username, password) = upload.get_credentials();
+            if let Some(username) = username {
+                request = request.basic_auth(username, password);
+            }
+            let response = request.send().await.map_err(|err| err.to_string())?;
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(format!("status {}", response.status()))
+            }
+        }
+        None => Err("backup upload is not prepared".to_string()),
+    }
+}
+
+/// Writes a backup archive to the `backup_dir`, uploads it and removes the archives beyond the retention.
+pub(in crate::api) async fn exec_backup(client: &reqwest::Client, cfg: &Config, backup: &BackupConfig) -> Result<PathBuf, String> {
+    let Some(backup_dir) = cfg.backup_dir.as_ref().map(PathBuf::from) else {
+        return Err("backup_dir is not set".to_string());
+    };
+    let mut content = create_backup_archive(cfg).await?;
+    let mut name = format!("{BACKUP_FILE_PREFIX}{}.tar.gz", Local::now().format("%Y%m%d_%H%M%S"));
+    if let Some(password) = backup.encryption_password.as_ref() {
+        content = encrypt_with_password(password, &content).map_err(|err| err.message)?;
+        name.push_str(".enc");
+    }
+    tokio::fs::create_dir_all(&backup_dir).await.map_err(|err| err.to_string())?;
+    let path = backup_dir.join(&name);
+    tokio::fs::write(&path, &content).await.map_err(|err| format!("can't write {}: {err}", path.display()))?;
+
+    let removed = apply_retention(&backup_dir, backup.retention);
+    for upload in backup.upload.iter().flatten() {
+        let destination = sanitize_sensitive_info(&upload.url);
+        match send_upload_request(client, upload, &name, Some(content.clone())).await {
+            Ok(()) => info!("Uploaded backup {name} to {destination}"),
+            Err(err) => error!("Failed to upload backup {name} to {destination}: {err}"),
+        }
+        for removed_name in &removed {
+            match send_upload_request(client, upload, removed_name, None).await {
+                Ok(()) => debug!("Removed backup {removed_name} from {destination}"),
+                Err(err) => error!("Failed to remove backup {removed_name} from {destination}: {err}"),
+            }
+        }
+    }
+    Ok(path)
+}
+
+pub(in crate::api) fn exec_backup_scheduler(cfg: &Config, app_state: &Arc<AppState>) {
+    let Some(backup) = cfg.backup.as_ref().filter(|backup| backup.enabled).cloned() else { return };
+    let Ok(schedule) = Schedule::from_str(&backup.schedule) else { return };
+    info!("Backup scheduled with {}", backup.schedule);
+    let app_state = Arc::clone(app_state);
+    tokio::spawn(async move {
+        let offset = *Local::now().offset();
+        while let Some(datetime) = schedule.upcoming(offset).next() {
+            tokio::time::sleep_until(tokio::time::Instant::from(datetime_to_instant(datetime))).await;
+            match exec_backup(&app_state.http_client, &app_state.config, &backup).await {
+                Ok(path) => info!("Backup written to {}", path.display()),
+                Err(err) => error!("Backup failed: {err}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_retention, BACKUP_FILE_PREFIX};
+
+    #[test]
+    fn test_apply_retention() {
+        let dir = tempfile::tempdir().unwrap();
+        for day in 1..=4 {
+            std::fs::write(dir.path().join(format!("{BACKUP_FILE_PREFIX}2025010{day}_030000.tar.gz")), b"").unwrap();
+        }
+        std::fs::write(dir.path().join("config.yml_20250101_030000"), b"").unwrap();
+        let removed = apply_retention(dir.path(), 2);
+        assert_eq!(removed, vec![format!("{BACKUP_FILE_PREFIX}20250101_030000.tar.gz"), format!("{BACKUP_FILE_PREFIX}20250102_030000.tar.gz")]);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 3);
+        assert!(apply_retention(dir.path(), 0).is_empty());
+    }
+}
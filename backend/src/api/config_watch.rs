@@ -3,13 +3,13 @@ use shared::error::{TuliproxError, TuliproxErrorKind};
 use crate::utils;
 use crate::utils::is_directory;
 use log::{debug, error, info};
-use notify::event::{AccessKind, AccessMode};
+use notify::event::{AccessKind, AccessMode, ModifyKind, RenameMode};
 use notify::{recommended_watcher, Event, EventKind, RecursiveMode, Watcher};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::{mpsc, Arc};
 
-enum ConfigFile {
+pub(in crate::api) enum ConfigFile {
     Config,
     ApiProxy,
     Mapping,
@@ -17,7 +17,7 @@ enum ConfigFile {
 }
 
 impl ConfigFile {
-    fn load_mappping(app_state: &Arc<AppState>) -> Result<(), TuliproxError> {
+    pub(in crate::api) fn load_mappping(app_state: &Arc<AppState>) -> Result<(), TuliproxError> {
         match utils::read_mappings(app_state.config.t_mapping_file_path.as_str(), true) {
             Ok(Some(mappings_cfg)) => {
                 app_state.config.set_mappings(&mappings_cfg);
@@ -27,7 +27,8 @@ impl ConfigFile {
                 info!("No mapping file loaded {}", app_state.config.t_mapping_file_path.as_str());
             }
             Err(err) => {
-                error!("Failed to load mapping file {err}");
+                // the mappings are only replaced if all scripts are valid
+                error!("Failed to load mapping file, the previous mappings are kept: {}", err.message);
                 return Err(err);
             }
         }
@@ -35,7 +36,7 @@ impl ConfigFile {
         Ok(())
     }
 
-    fn load_api_proxy(app_state: &Arc<AppState>) -> Result<(), TuliproxError> {
+    pub(in crate::api) fn load_api_proxy(app_state: &Arc<AppState>) -> Result<(), TuliproxError> {
         match utils::read_api_proxy_config(&app_state.config) {
             Ok(()) => {
                 info!("Api Proxy File: {:?}", &app_state.config.t_api_proxy_file_path);
@@ -57,6 +58,32 @@ impl ConfigFile {
     }
 }
 
+fn canonical_path(path: &Path) -> PathBuf {
+    std::fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+// the mapping needs its own watch if it is not covered by the watch of the config directory
+fn get_mapping_watch(config_path: &Path, mapping_path: &Path, mapping_is_dir: bool) -> Option<(PathBuf, RecursiveMode)> {
+    if mapping_path.as_os_str().is_empty() {
+        return None;
+    }
+    let config_dir = canonical_path(config_path);
+    if mapping_is_dir {
+        if canonical_path(mapping_path).starts_with(&config_dir) {
+            None
+        } else {
+            Some((mapping_path.to_path_buf(), RecursiveMode::Recursive))
+        }
+    } else {
+        let parent = mapping_path.parent().filter(|parent| !parent.as_os_str().is_empty())?;
+        if canonical_path(parent) == config_dir {
+            None
+        } else {
+            Some((parent.to_path_buf(), RecursiveMode::NonRecursive))
+        }
+    }
+}
+
 pub async fn exec_config_watch(app_state: &Arc<AppState>) -> Result<(), TuliproxError> {
     let (tx, rx) = mpsc::channel::<notify::Result<Event>>();
 
@@ -78,9 +105,15 @@ pub async fn exec_config_watch(app_state: &Arc<AppState>) -> Result<(), Tuliprox
     // Add a path to be watched. All files and directories at that path and
     // below will be monitored for changes.
     let path = Path::new(app_state.config.t_config_path.as_str());
-    let recursive_mode = if utils::is_directory(&app_state.config.t_mapping_file_path) { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
+    let mapping_is_dir = utils::is_directory(&app_state.config.t_mapping_file_path);
+    let mapping_watch = get_mapping_watch(path, Path::new(app_state.config.t_mapping_file_path.as_str()), mapping_is_dir);
+    let recursive_mode = if mapping_is_dir && mapping_watch.is_none() { RecursiveMode::Recursive } else { RecursiveMode::NonRecursive };
     watcher.watch(path, recursive_mode).map_err(|err| TuliproxError::new(TuliproxErrorKind::Info, format!("Failed to start config file watcher {err}")))?;
     info!("Watching config file changes {}", path.display());
+    if let Some((mapping_path, mapping_mode)) = mapping_watch {
+        watcher.watch(&mapping_path, mapping_mode).map_err(|err| TuliproxError::new(TuliproxErrorKind::Info, format!("Failed to start mapping file watcher {err}")))?;
+        info!("Watching mapping file changes {}", mapping_path.display());
+    }
 
     let watcher_app_state = Arc::clone(app_state);
     tokio::spawn(async move {
@@ -88,13 +121,15 @@ pub async fn exec_config_watch(app_state: &Arc<AppState>) -> Result<(), Tuliprox
         for res in rx {
             match res {
                 Ok(event) => {
-                    if let EventKind::Access(AccessKind::Close(AccessMode::Write)) = event.kind {
+                    // editors with atomic saves rename a temporary file to the config file
+                    if let EventKind::Access(AccessKind::Close(AccessMode::Write))
+                    | EventKind::Modify(ModifyKind::Name(RenameMode::To)) = event.kind {
                         for path in event.paths {
                             if let Some((config_file, _is_dir)) = files.get(&path) {
                                 if let Err(err) = config_file.reload(&path, &watcher_app_state) {
                                     error!("Failed to reload config file {}: {err}", path.display());
                                 }
-                            } else if recursive_mode == RecursiveMode::Recursive && path.extension().is_some_and(|ext| ext == "yml") {
+                            } else if mapping_is_dir && path.extension().is_some_and(|ext| ext == "yml" || ext == "map") {
                                 for (key, (config_file, is_dir)) in &files {
                                     if *is_dir && path.starts_with(key) {
                                         if let Err(err) = config_file.reload(&path, &watcher_app_state) {
@@ -1,8 +1,9 @@
 use crate::model::{Config, ConfigInput, ConfigTarget, InputType};
-use crate::model::{M3uPlaylistItem, PlaylistGroup};
+use crate::model::{M3uPlaylistItem, PlaylistComparison, PlaylistGroup};
 use shared::model::{PlaylistItemType, TargetType, XtreamCluster};
 use crate::repository::{m3u_repository, xtream_repository};
 use crate::utils::{m3u, xtream};
+use crate::utils::request::sanitize_sensitive_info;
 use axum::response::IntoResponse;
 use serde::Serialize;
 use serde_json::{json, Value};
@@ -166,27 +167,55 @@ pub(in crate::api::endpoints) async fn get_playlist_for_target(cfg_target: Optio
     (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": "Invalid Arguments"}))).into_response()
 }
 
+pub(in crate::api::endpoints) async fn fetch_input_playlist(client: Arc<reqwest::Client>, input: &ConfigInput, cfg: &Config) -> Result<Vec<PlaylistGroup>, String> {
+    let (result, errors) =
+        match input.input_type {
+            InputType::M3u | InputType::M3uBatch => m3u::get_m3u_playlist(client, cfg, input, &cfg.working_dir).await,
+            InputType::Xtream | InputType::XtreamBatch => xtream::get_xtream_playlist(cfg, client, input, &cfg.working_dir).await,
+        };
+    if result.is_empty() {
+        let error_strings: Vec<String> = errors.iter().map(std::string::ToString::to_string).collect();
+        Err(error_strings.join(", "))
+    } else {
+        Ok(result)
+    }
+}
+
 pub(in crate::api::endpoints) async fn get_playlist(client: Arc<reqwest::Client>, cfg_input: Option<&ConfigInput>, cfg: &Config) -> impl IntoResponse + Send {
     match cfg_input {
         Some(input) => {
-            let (result, errors) =
-                match input.input_type {
-                    InputType::M3u | InputType::M3uBatch => m3u::get_m3u_playlist(client, cfg, input, &cfg.working_dir).await,
-                    InputType::Xtream | InputType::XtreamBatch => xtream::get_xtream_playlist(cfg, client, input, &cfg.working_dir).await,
-                };
-            if result.is_empty() {
-                let error_strings: Vec<String> = errors.iter().map(std::string::ToString::to_string).collect();
-                (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": error_strings.join(", ")}))).into_response()
-            } else {
-                let (live, vod, series) = group_playlist_groups_by_cluster(result, input.input_type);
-                let response = PlaylistResponse {
-                    live: Some(live),
-                    vod: Some(vod),
-                    series: Some(series),
-                };
-                (axum::http::StatusCode::OK, axum::Json(response)).into_response()
+            match fetch_input_playlist(client, input, cfg).await {
+                Err(err) => (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": sanitize_sensitive_info(&err)}))).into_response(),
+                Ok(result) => {
+                    let (live, vod, series) = group_playlist_groups_by_cluster(result, input.input_type);
+                    let response = PlaylistResponse {
+                        live: Some(live),
+                        vod: Some(vod),
+                        series: Some(series),
+                    };
+                    (axum::http::StatusCode::OK, axum::Json(response)).into_response()
+                }
             }
         }
         None => (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": "Invalid Arguments"}))).into_response(),
     }
 }
+
+pub(in crate::api::endpoints) async fn get_playlist_comparison(client: Arc<reqwest::Client>, left_input: Option<&ConfigInput>, right_input: Option<&ConfigInput>, cfg: &Config) -> impl IntoResponse + Send {
+    match (left_input, right_input) {
+        (Some(left), Some(right)) => {
+            let (left_result, right_result) = tokio::join!(
+                fetch_input_playlist(Arc::clone(&client), left, cfg),
+                fetch_input_playlist(client, right, cfg)
+            );
+            match (left_result, right_result) {
+                (Ok(left_playlist), Ok(right_playlist)) => {
+                    let report = PlaylistComparison::new(&left.name, &left_playlist, &right.name, &right_playlist);
+                    (axum::http::StatusCode::OK, axum::Json(report)).into_response()
+                }
+                (Err(err), _) | (_, Err(err)) => (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": sanitize_sensitive_info(&err)}))).into_response(),
+            }
+        }
+        _ => (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": "Invalid input"}))).into_response(),
+    }
+}
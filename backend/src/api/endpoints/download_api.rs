@@ -126,7 +126,7 @@ pub async fn queue_download_file(
                 if app_state.downloads.active.read().await.is_none() {
                     match run_download_queue(&app_state.config, download_cfg, &app_state.downloads).await {
                         Ok(()) => {}
-                        Err(err) => return (axum::http::StatusCode::INTERNAL_SERVER_ERROR, axum::Json(json!({"error": err}))).into_response(),
+                        Err(err) => return (axum::http::StatusCode::INTERNAL_SERVER_ERROR, axum::Json(json!({"error": request::sanitize_sensitive_info(&err)}))).into_response(),
                     }
                 }
                 axum::Json(download_info!(&file_download)).into_response()
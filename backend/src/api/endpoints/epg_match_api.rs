@@ -0,0 +1,32 @@
+use crate::api::model::app_state::AppState;
+use crate::model::EpgMatchKind;
+use crate::repository::epg_match_report_repository::load_epg_match_report;
+use axum::response::IntoResponse;
+use serde_json::json;
+use std::str::FromStr;
+use std::sync::Arc;
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub(in crate::api) struct EpgMatchQuery {
+    // comma separated match kinds like `fuzzy,unmatched`
+    #[serde(default)]
+    kind: Option<String>,
+}
+
+pub(in crate::api) async fn epg_match_report(
+    axum::extract::Path(target_id): axum::extract::Path<u16>,
+    axum::extract::Query(query): axum::extract::Query<EpgMatchQuery>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    let Some(target_name) = app_state.config.get_target_by_id(target_id).map(|target| target.name.clone()) else {
+        return (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": "Invalid target"}))).into_response();
+    };
+    let kinds: Result<Vec<EpgMatchKind>, _> = query.kind.as_deref().unwrap_or_default()
+        .split(',').filter(|kind| !kind.trim().is_empty()).map(EpgMatchKind::from_str).collect();
+    let kinds = match kinds {
+        Ok(kinds) => kinds,
+        Err(err) => return (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": err.to_string()}))).into_response(),
+    };
+    let report = load_epg_match_report(&app_state.config, &target_name).await;
+    axum::Json(json!({"target": target_name, "ts": report.ts, "summary": report.summary(), "channels": report.query(&kinds)})).into_response()
+}
@@ -0,0 +1,65 @@
+use crate::api::model::app_state::AppState;
+use crate::model::{GroupBulkOperation, GroupOverrides};
+use crate::repository::group_override_repository::{reset_group_overrides, try_load_group_overrides, update_group_overrides};
+use axum::response::IntoResponse;
+use log::error;
+use serde_json::json;
+use std::sync::Arc;
+
+fn get_target_name(app_state: &AppState, target_id: u16) -> Option<String> {
+    app_state.config.get_target_by_id(target_id).map(|target| target.name.clone())
+}
+
+fn group_overrides_response(target_name: &str, result: Result<GroupOverrides, std::io::Error>) -> axum::response::Response {
+    match result {
+        Ok(overrides) => axum::Json(overrides).into_response(),
+        Err(err) => {
+            error!("Failed to access group overrides for target {target_name}: {err}");
+            (axum::http::StatusCode::INTERNAL_SERVER_ERROR, axum::Json(json!({"error": err.to_string()}))).into_response()
+        }
+    }
+}
+
+async fn group_overrides_get(
+    axum::extract::Path(target_id): axum::extract::Path<u16>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    let Some(target_name) = get_target_name(&app_state, target_id) else {
+        return (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": "Invalid target"}))).into_response();
+    };
+    let result = try_load_group_overrides(&app_state.config, &target_name).await.map(Option::unwrap_or_default);
+    group_overrides_response(&target_name, result)
+}
+
+async fn group_overrides_update(
+    axum::extract::Path(target_id): axum::extract::Path<u16>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+    axum::extract::Json(operations): axum::extract::Json<Vec<GroupBulkOperation>>,
+) -> impl axum::response::IntoResponse + Send {
+    let Some(target_name) = get_target_name(&app_state, target_id) else {
+        return (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": "Invalid target"}))).into_response();
+    };
+    let result = update_group_overrides(&app_state.config, &target_name, |overrides| {
+        for operation in operations {
+            overrides.apply_operation(operation);
+        }
+    }).await;
+    group_overrides_response(&target_name, result)
+}
+
+async fn group_overrides_reset(
+    axum::extract::Path(target_id): axum::extract::Path<u16>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    let Some(target_name) = get_target_name(&app_state, target_id) else {
+        return (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": "Invalid target"}))).into_response();
+    };
+    let result = reset_group_overrides(&app_state.config, &target_name).await;
+    group_overrides_response(&target_name, result)
+}
+
+pub(in crate::api::endpoints) fn group_api_register(router: axum::Router<Arc<AppState>>) -> axum::Router<Arc<AppState>> {
+    router.route("/playlist/groups/{target_id}", axum::routing::get(group_overrides_get)
+        .post(group_overrides_update)
+        .delete(group_overrides_reset))
+}
@@ -1,5 +1,5 @@
 use crate::api::model::app_state::HdHomerunAppState;
-use crate::auth::AuthBasic;
+use crate::auth::{AuthBasic, ClientIp};
 use crate::model::{ConfigTarget, ProxyUserCredentials};
 use crate::model::{Config};
 use crate::model::{M3uPlaylistItem, XtreamPlaylistItem};
@@ -159,11 +159,12 @@ where
     }
 }
 
-fn create_device(app_state: &Arc<HdHomerunAppState>) -> Option<Device> {
-    if let Some(credentials) = app_state.app_state.config.get_user_credentials(&app_state.device.t_username) {
+fn create_device(app_state: &Arc<HdHomerunAppState>, client_ip: ClientIp) -> Option<Device> {
+    if let Some(mut credentials) = app_state.app_state.config.get_user_credentials(&app_state.device.t_username) {
+        credentials.t_ipv6_client = client_ip.is_ipv6();
         let server_info = app_state.app_state.config.get_user_server_info(&credentials);
         let device = &app_state.device;
-        let device_url = format!("{}://{}:{}", server_info.protocol, server_info.host, device.port);
+        let device_url = format!("{}://{}:{}", server_info.protocol, server_info.get_url_host(), device.port);
         Some(Device {
             friendly_name: device.friendly_name.to_string(),
             manufacturer: device.manufacturer.to_string(),
@@ -185,8 +186,8 @@ fn create_device(app_state: &Arc<HdHomerunAppState>) -> Option<Device> {
     }
 }
 
-async fn device_xml(axum::extract::State(app_state): axum::extract::State<Arc<HdHomerunAppState>>) -> impl IntoResponse {
-    if let Some(device) = create_device(&app_state) {
+async fn device_xml(client_ip: ClientIp, axum::extract::State(app_state): axum::extract::State<Arc<HdHomerunAppState>>) -> impl IntoResponse {
+    if let Some(device) = create_device(&app_state, client_ip) {
         axum::response::Response::builder()
             .status(axum::http::StatusCode::OK)
             .header(axum::http::header::CONTENT_TYPE, "application/xml")
@@ -198,16 +199,16 @@ async fn device_xml(axum::extract::State(app_state): axum::extract::State<Arc<Hd
     }
 }
 
-async fn device_json(axum::extract::State(app_state): axum::extract::State<Arc<HdHomerunAppState>>) -> impl IntoResponse {
-    if let Some(device) = create_device(&app_state) {
+async fn device_json(client_ip: ClientIp, axum::extract::State(app_state): axum::extract::State<Arc<HdHomerunAppState>>) -> impl IntoResponse {
+    if let Some(device) = create_device(&app_state, client_ip) {
         axum::Json(device).into_response()
     } else {
         axum::http::StatusCode::INTERNAL_SERVER_ERROR.into_response()
     }
 }
 
-async fn discover_json(axum::extract::State(app_state): axum::extract::State<Arc<HdHomerunAppState>>) -> impl IntoResponse {
-    if let Some(device) = create_device(&app_state) {
+async fn discover_json(client_ip: ClientIp, axum::extract::State(app_state): axum::extract::State<Arc<HdHomerunAppState>>) -> impl IntoResponse {
+    if let Some(device) = create_device(&app_state, client_ip) {
         axum::Json(device).into_response()
     } else {
         axum::http::StatusCode::INTERNAL_SERVER_ERROR.into_response()
@@ -275,21 +276,23 @@ async fn lineup(app_state: &Arc<HdHomerunAppState>, cfg: &Arc<Config>, credentia
     axum::http::StatusCode::NOT_FOUND.into_response()
 }
 
-async fn auth_lineup_json(AuthBasic((username, password)): AuthBasic, axum::extract::State(app_state): axum::extract::State<Arc<HdHomerunAppState>>) -> impl IntoResponse {
+async fn auth_lineup_json(AuthBasic((username, password)): AuthBasic, client_ip: ClientIp, axum::extract::State(app_state): axum::extract::State<Arc<HdHomerunAppState>>) -> impl IntoResponse {
     let cfg = Arc::clone(&app_state.app_state.config);
-    if let Some((credentials, target)) = cfg.get_target_for_username(&app_state.device.t_username) {
+    if let Some((mut credentials, target)) = cfg.get_target_for_username(&app_state.device.t_username) {
         if !username.eq(&credentials.username) || !password.eq(&credentials.password) {
             return axum::http::StatusCode::UNAUTHORIZED.into_response();
         }
+        credentials.t_ipv6_client = client_ip.is_ipv6();
         let user_credentials = Arc::new(credentials);
         return lineup(&app_state, &cfg, &user_credentials, target).await.into_response();
     }
     axum::http::StatusCode::NOT_FOUND.into_response()
 }
 
-async fn lineup_json(axum::extract::State(app_state): axum::extract::State<Arc<HdHomerunAppState>>) -> impl IntoResponse {
+async fn lineup_json(client_ip: ClientIp, axum::extract::State(app_state): axum::extract::State<Arc<HdHomerunAppState>>) -> impl IntoResponse {
     let cfg = Arc::clone(&app_state.app_state.config);
-    if let Some((credentials, target)) = cfg.get_target_for_username(&app_state.device.t_username) {
+    if let Some((mut credentials, target)) = cfg.get_target_for_username(&app_state.device.t_username) {
+        credentials.t_ipv6_client = client_ip.is_ipv6();
         let user_credentials = Arc::new(credentials);
         return lineup(&app_state, &cfg, &user_credentials, target).await.into_response();
     }
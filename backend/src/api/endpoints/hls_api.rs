@@ -1,7 +1,12 @@
-use crate::api::api_utils::{force_provider_stream_response, get_stream_alternative_url, is_seek_request};
-use crate::api::api_utils::{try_option_bad_request};
+use crate::api::api_utils::{force_provider_stream_response, get_stream_alternative_url, is_seek_request, is_stream_share_enabled, StreamDetails};
+use crate::api::api_utils::{try_option_bad_request, update_user_activity};
 use crate::api::model::app_state::AppState;
+use crate::api::model::model_utils::get_stream_response_with_headers;
+use crate::api::model::stream_error::StreamError;
+use crate::api::model::streams::active_client_stream::ActiveClientStream;
 use crate::api::model::streams::provider_stream::{create_custom_video_stream_response, CustomVideoStreamType};
+use crate::api::model::streams::shared_hls_cache::{SharedHlsResource, HLS_PLAYLIST_TTL_MILLIS, HLS_SEGMENT_TTL_MILLIS};
+use crate::utils::rate_limiter::acquire_provider_permit;
 use crate::model::{ProxyUserCredentials};
 use crate::model::ConfigInput;
 use shared::model::{PlaylistItemType, UserConnectionPermission, XtreamCluster};
@@ -10,11 +15,13 @@ use shared::utils::HLS_EXT;
 use crate::utils::request;
 use crate::utils::request::{is_hls_url, replace_url_extension, sanitize_sensitive_info};
 use axum::response::IntoResponse;
+use bytes::Bytes;
+use futures::StreamExt;
 use log::{debug, error};
 use serde::Deserialize;
 use std::sync::Arc;
 use crate::api::model::active_user_manager::UserSession;
-use crate::auth::Fingerprint;
+use crate::auth::{ClientIp, Fingerprint};
 
 #[derive(Debug, Deserialize)]
 struct HlsApiPathParams {
@@ -34,6 +41,66 @@ fn hls_response(hls_content: String) -> impl IntoResponse + Send {
         .into_response()
 }
 
+// Only the content type is kept for shared resources, the other provider headers belong to the first request.
+async fn fetch_shared_hls_resource(app_state: &AppState, input: &ConfigInput, url: &str) -> Option<SharedHlsResource> {
+    let parsed_url = input.get_tls_url(&url.parse::<url::Url>().ok()?);
+    let request = request::get_client_request(&input.get_client(&app_state.http_client), input.method, Some(&input.headers), &parsed_url, None);
+    let _permit = acquire_provider_permit(input.t_rate_limiter.as_ref()).await;
+    match request.send().await {
+        Ok(response) if response.status().is_success() => {
+            let response_url = response.url().to_string();
+            let headers = response.headers().get(axum::http::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(|value| vec![(axum::http::header::CONTENT_TYPE.to_string(), value.to_string())])
+                .unwrap_or_default();
+            match response.bytes().await {
+                Ok(content) => Some(SharedHlsResource { headers, content, response_url }),
+                Err(err) => {
+                    error!("Failed to read hls resource {} {err}", sanitize_sensitive_info(url));
+                    None
+                }
+            }
+        }
+        Ok(response) => {
+            debug!("Failed to download hls resource {} {}", sanitize_sensitive_info(url), response.status());
+            None
+        }
+        Err(err) => {
+            error!("Failed to download hls resource {} {err}", sanitize_sensitive_info(url));
+            None
+        }
+    }
+}
+
+async fn download_hls_playlist(app_state: &AppState, input: &ConfigInput, url: &str, share_stream: bool) -> Result<(String, String), String> {
+    if share_stream {
+        let resource = app_state.shared_hls_cache.get_or_fetch(url, HLS_PLAYLIST_TTL_MILLIS,
+                                                               || fetch_shared_hls_resource(app_state, input, url)).await;
+        return resource.map(|resource| (String::from_utf8_lossy(&resource.content).to_string(), resource.response_url))
+            .ok_or_else(|| format!("Failed to download shared m3u8 {url}"));
+    }
+    request::download_text_content(Arc::clone(&app_state.http_client), input, url, None).await.map_err(|err| err.to_string())
+}
+
+/// Serves a segment of a shared hls channel, the clients of the channel share one provider request per segment.
+async fn shared_hls_segment_response(app_state: &AppState, session: &UserSession, input: &ConfigInput, user: &ProxyUserCredentials) -> impl IntoResponse + Send {
+    let segment_url = session.stream_url.as_str();
+    let Some(resource) = app_state.shared_hls_cache.get_or_fetch(segment_url, HLS_SEGMENT_TTL_MILLIS,
+                                                                  || fetch_shared_hls_resource(app_state, input, segment_url)).await else {
+        return create_custom_video_stream_response(&app_state.config, CustomVideoStreamType::ChannelUnavailable).into_response();
+    };
+    debug!("Streaming shared hls segment {}", sanitize_sensitive_info(segment_url));
+    let content: Bytes = resource.content;
+    let stream = futures::stream::once(async move { Ok::<Bytes, StreamError>(content) }).boxed();
+    let stream = ActiveClientStream::new(StreamDetails::from_stream(stream), app_state, user, Some(&session.token), UserConnectionPermission::Allowed).await;
+    let (status_code, header_map) = get_stream_response_with_headers(Some((resource.headers, axum::http::StatusCode::OK)));
+    let mut response = axum::response::Response::builder().status(status_code);
+    for (key, value) in &header_map {
+        response = response.header(key, value);
+    }
+    response.body(axum::body::Body::from_stream(stream)).unwrap().into_response()
+}
+
 #[allow(clippy::too_many_arguments)]
 pub(in crate::api) async fn handle_hls_stream_request(
         fingerprint: &str,
@@ -43,7 +110,8 @@ pub(in crate::api) async fn handle_hls_stream_request(
         hls_url: &str,
         virtual_id: u32,
         input: &ConfigInput,
-        connection_permission: UserConnectionPermission) -> impl IntoResponse + Send {
+        connection_permission: UserConnectionPermission,
+        share_stream: bool) -> impl IntoResponse + Send {
     let url = replace_url_extension(hls_url, HLS_EXT);
     let server_info = app_state.config.get_user_server_info(user);
 
@@ -70,7 +138,7 @@ pub(in crate::api) async fn handle_hls_stream_request(
         }
     };
 
-    match request::download_text_content(Arc::clone(&app_state.http_client), input, &request_url, None).await {
+    match download_hls_playlist(app_state, input, &request_url, share_stream).await {
         Ok((content, response_url)) => {
             let rewrite_hls_props = RewriteHlsProps {
                 secret: &app_state.config.t_encrypt_secret,
@@ -85,7 +153,7 @@ pub(in crate::api) async fn handle_hls_stream_request(
             hls_response(hls_content).into_response()
         }
         Err(err) => {
-            error!("Failed to download m3u8 {}", sanitize_sensitive_info(err.to_string().as_str()));
+            error!("Failed to download m3u8 {}", sanitize_sensitive_info(&err));
             create_custom_video_stream_response(&app_state.config, CustomVideoStreamType::ChannelUnavailable).into_response()
         }
     }
@@ -93,13 +161,16 @@ pub(in crate::api) async fn handle_hls_stream_request(
 
 async fn hls_api_stream(
     Fingerprint(fingerprint): Fingerprint,
+    client_ip: ClientIp,
     req_headers: axum::http::HeaderMap,
     axum::extract::Path(params): axum::extract::Path<HlsApiPathParams>,
     axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
 ) -> impl axum::response::IntoResponse + Send {
-    let (user, target) = try_option_bad_request!(
+    let (mut user, target) = try_option_bad_request!(
         app_state.config.get_target_for_user(&params.username, &params.password), false,
         format!("Could not find any user {}", params.username));
+    user.t_ipv6_client = client_ip.is_ipv6();
+    update_user_activity(&app_state, &user, client_ip, &req_headers).await;
     if user.permission_denied(&app_state) {
         return create_custom_video_stream_response(&app_state.config, CustomVideoStreamType::UserAccountExpired).into_response();
     }
@@ -129,22 +200,27 @@ async fn hls_api_stream(
         if session.virtual_id == virtual_id {
             if is_seek_request(XtreamCluster::Live, &req_headers).await {
                 // partial request means we are in reverse proxy mode, seek happened
-                return force_provider_stream_response(&app_state, session, PlaylistItemType::LiveHls, &req_headers, input, &user).await.into_response()
+                return force_provider_stream_response(&app_state, session, PlaylistItemType::LiveHls, &req_headers, input, target, &user).await.into_response()
             }
         } else {
             return axum::http::StatusCode::BAD_REQUEST.into_response();
         }
 
-        let connection_permission = user.connection_permission(&app_state).await;
+        let connection_permission = user.connection_permission(&app_state, &fingerprint).await;
         if connection_permission == UserConnectionPermission::Exhausted {
             return create_custom_video_stream_response(&app_state.config, CustomVideoStreamType::UserConnectionsExhausted).into_response();
         }
 
+        let share_stream = is_stream_share_enabled(PlaylistItemType::LiveHls, target);
         if is_hls_url(&session.stream_url) {
-            return handle_hls_stream_request(&fingerprint, &app_state, &user, Some(session), &session.stream_url, virtual_id, input, connection_permission).await.into_response();
+            return handle_hls_stream_request(&fingerprint, &app_state, &user, Some(session), &session.stream_url, virtual_id, input, connection_permission, share_stream).await.into_response();
+        }
+
+        if share_stream {
+            return shared_hls_segment_response(&app_state, session, input, &user).await.into_response();
         }
 
-        force_provider_stream_response(&app_state, session, PlaylistItemType::LiveHls, &req_headers, input, &user).await.into_response()
+        force_provider_stream_response(&app_state, session, PlaylistItemType::LiveHls, &req_headers, input, target, &user).await.into_response()
     } else {
         axum::http::StatusCode::BAD_REQUEST.into_response()
     }
@@ -0,0 +1,111 @@
+use crate::utils::{LogEntry, LOG_BUFFER};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::IntoResponse;
+use futures::StreamExt;
+use log::LevelFilter;
+use std::str::FromStr;
+use tokio_stream::wrappers::BroadcastStream;
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub(in crate::api) struct LogQuery {
+    // minimum level, `warn` returns warnings and errors
+    #[serde(default)]
+    level: Option<String>,
+    // module prefix like `tuliprox::api`
+    #[serde(default)]
+    module: Option<String>,
+    // entries after this unix timestamp in milliseconds
+    #[serde(default)]
+    since: Option<i64>,
+    // the last n entries
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+struct LogFilter {
+    level: LevelFilter,
+    module: Option<String>,
+    since: Option<i64>,
+}
+
+impl LogFilter {
+    fn from_query(query: &LogQuery) -> Option<Self> {
+        let level = match query.level.as_deref().map(str::trim).filter(|level| !level.is_empty()) {
+            Some(level) => LevelFilter::from_str(level).ok()?,
+            None => LevelFilter::Trace,
+        };
+        Some(Self {
+            level,
+            module: query.module.as_deref().map(str::trim).filter(|module| !module.is_empty()).map(ToString::to_string),
+            since: query.since,
+        })
+    }
+
+    fn matches(&self, entry: &LogEntry) -> bool {
+        entry.level <= self.level
+            && self.module.as_ref().is_none_or(|module| entry.module.starts_with(module.as_str()))
+            && self.since.is_none_or(|since| entry.ts > since)
+    }
+}
+
+fn take_last(mut entries: Vec<LogEntry>, limit: Option<usize>) -> Vec<LogEntry> {
+    if let Some(limit) = limit {
+        if entries.len() > limit {
+            entries.drain(..entries.len() - limit);
+        }
+    }
+    entries
+}
+
+pub(in crate::api) async fn logs(
+    axum::extract::Query(query): axum::extract::Query<LogQuery>,
+) -> impl axum::response::IntoResponse + Send {
+    let Some(filter) = LogFilter::from_query(&query) else {
+        return axum::http::StatusCode::BAD_REQUEST.into_response();
+    };
+    axum::Json(take_last(LOG_BUFFER.get_entries(|entry| filter.matches(entry)), query.limit)).into_response()
+}
+
+// Sends the buffered entries and then every new entry as server-sent events.
+pub(in crate::api) async fn log_stream(
+    axum::extract::Query(query): axum::extract::Query<LogQuery>,
+) -> impl axum::response::IntoResponse + Send {
+    let Some(filter) = LogFilter::from_query(&query) else {
+        return axum::http::StatusCode::BAD_REQUEST.into_response();
+    };
+    let (entries, receiver) = LOG_BUFFER.subscribe(|entry| filter.matches(entry));
+    let history = futures::stream::iter(take_last(entries, query.limit));
+    // lagging receivers skip the missed entries
+    let live = BroadcastStream::new(receiver)
+        .filter_map(move |entry| futures::future::ready(entry.ok().filter(|entry| filter.matches(entry))));
+    let events = history.chain(live).map(|entry| Event::default().event("log").json_data(entry));
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::endpoints::log_api::{take_last, LogFilter, LogQuery};
+    use crate::utils::LogEntry;
+    use log::Level;
+
+    #[test]
+    fn test_log_filter() {
+        let entry = |ts: i64, level: Level, module: &str| LogEntry { ts, level, module: module.to_string(), message: String::new() };
+        let entries = vec![
+            entry(1, Level::Error, "tuliprox::api::main_api"),
+            entry(2, Level::Debug, "tuliprox::api::main_api"),
+            entry(3, Level::Warn, "tuliprox::processing"),
+            entry(4, Level::Info, "tuliprox::api::v1_api"),
+        ];
+        let query = LogQuery { level: Some("info".to_string()), module: Some("tuliprox::api".to_string()), ..LogQuery::default() };
+        let filter = LogFilter::from_query(&query).unwrap();
+        let matched: Vec<i64> = entries.iter().filter(|e| filter.matches(e)).map(|e| e.ts).collect();
+        assert_eq!(matched, vec![1, 4]);
+
+        let filter = LogFilter::from_query(&LogQuery { since: Some(2), ..LogQuery::default() }).unwrap();
+        assert_eq!(entries.iter().filter(|e| filter.matches(e)).count(), 2);
+        assert!(LogFilter::from_query(&LogQuery { level: Some("loud".to_string()), ..LogQuery::default() }).is_none());
+        assert_eq!(take_last(entries, Some(1))[0].ts, 4);
+    }
+}
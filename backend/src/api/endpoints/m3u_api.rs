@@ -1,9 +1,10 @@
-use crate::api::api_utils::{force_provider_stream_response, get_user_target, get_user_target_by_credentials, is_seek_request, redirect, redirect_response, resource_response, separate_number_and_remainder, stream_response, try_option_bad_request, try_result_bad_request, RedirectParams};
+use crate::api::api_utils::{client_error_response, force_provider_stream_response, is_stream_share_enabled, get_user_target, get_user_target_by_credentials, is_seek_request, redirect, redirect_response, resource_response, separate_number_and_remainder, stream_response, try_option_bad_request, try_result_bad_request, update_user_activity, ClientErrorKind, RedirectParams};
 use crate::api::endpoints::hls_api::handle_hls_stream_request;
 use crate::api::endpoints::xtream_api::{ApiStreamContext, ApiStreamRequest};
 use crate::api::model::app_state::AppState;
 use crate::api::model::request::UserApiRequest;
 use crate::api::model::streams::provider_stream::{create_custom_video_stream_response, CustomVideoStreamType};
+use crate::model::ProxyUserCredentials;
 use shared::model::{FieldGetAccessor, PlaylistEntry, PlaylistItemType, TargetType, UserConnectionPermission, XtreamCluster};
 use crate::repository::m3u_repository::{m3u_get_item_for_stream_id, m3u_load_rewrite_playlist};
 use crate::repository::storage_const;
@@ -15,14 +16,38 @@ use bytes::Bytes;
 use futures::stream;
 use log::{debug, error};
 use std::sync::Arc;
-use crate::auth::Fingerprint;
+use crate::auth::{ClientIp, Fingerprint};
+
+/// Returns a playlist with a single channel named like the `client_error` `expired_channel`,
+/// players show why the list is empty. The channel streams the `user_account_expired` video.
+fn m3u_account_expired_response(app_state: &AppState, user: &ProxyUserCredentials) -> axum::response::Response {
+    let Some(client_error) = app_state.config.client_error.as_ref().filter(|client_error| client_error.enabled) else {
+        return StatusCode::FORBIDDEN.into_response();
+    };
+    let base_url = app_state.config.get_user_server_info(user).get_base_url();
+    let channel_name = client_error.expired_channel.replace(['\r', '\n', '"'], " ");
+    let playlist = format!("#EXTM3U\n#EXTINF:-1 tvg-id=\"\" tvg-name=\"{channel_name}\" group-title=\"{channel_name}\",{channel_name}\n{base_url}/{}/live/{}/{}/0\n",
+                           storage_const::M3U_STREAM_PATH, user.username, user.password);
+    axum::response::Response::builder()
+        .status(StatusCode::OK)
+        .header(axum::http::header::CONTENT_TYPE, mime::TEXT_PLAIN_UTF_8.to_string())
+        .body(axum::body::Body::from(playlist))
+        .unwrap_or_else(|_| StatusCode::FORBIDDEN.into_response())
+}
 
 async fn m3u_api(
+    client_ip: ClientIp,
+    req_headers: &HeaderMap,
     api_req: &UserApiRequest,
     app_state: &AppState,
 ) -> impl axum::response::IntoResponse + Send {
     match get_user_target(api_req, app_state) {
-        Some((user, target)) => {
+        Some((mut user, target)) => {
+            user.t_ipv6_client = client_ip.is_ipv6();
+            update_user_activity(app_state, &user, client_ip, req_headers).await;
+            if user.permission_denied(app_state) {
+                return m3u_account_expired_response(app_state, &user);
+            }
             match m3u_load_rewrite_playlist(&app_state.config, target, &user).await {
                 Ok(m3u_iter) => {
                     // Convert the iterator into a stream of `Bytes`
@@ -42,33 +67,40 @@ async fn m3u_api(
                 }
             }
         }
-        None => axum::http::StatusCode::BAD_REQUEST.into_response(),
+        None => client_error_response(app_state, StatusCode::BAD_REQUEST, ClientErrorKind::Unauthorized),
     }
 }
 
 
-async fn m3u_api_get(axum::extract::Query(api_req): axum::extract::Query<UserApiRequest>,
+async fn m3u_api_get(client_ip: ClientIp,
+                     req_headers: HeaderMap,
+                     axum::extract::Query(api_req): axum::extract::Query<UserApiRequest>,
                      axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
 ) -> impl axum::response::IntoResponse + Send {
-    m3u_api(&api_req, &app_state).await
+    m3u_api(client_ip, &req_headers, &api_req, &app_state).await
 }
 
 async fn m3u_api_post(
+    client_ip: ClientIp,
+    req_headers: HeaderMap,
     axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
     axum::extract::Form(api_req): axum::extract::Form<UserApiRequest>,
 ) -> impl axum::response::IntoResponse + Send {
-    m3u_api(&api_req, &app_state).await.into_response()
+    m3u_api(client_ip, &req_headers, &api_req, &app_state).await.into_response()
 }
 
-async fn m3u_api_stream(
+pub(in crate::api) async fn m3u_api_stream(
     fingerprint: &str,
+    client_ip: ClientIp,
     req_headers: &HeaderMap,
     app_state: &Arc<AppState>,
     api_req: &UserApiRequest,
     stream_req: ApiStreamRequest<'_>,
     // _addr: &std::net::SocketAddr,
 ) -> impl axum::response::IntoResponse + Send {
-    let (user, target) = try_option_bad_request!(get_user_target_by_credentials(stream_req.username, stream_req.password, api_req, app_state), false, format!("Could not find any user {}", stream_req.username));
+    let (mut user, target) = try_option_bad_request!(get_user_target_by_credentials(stream_req.username, stream_req.password, api_req, app_state), false, format!("Could not find any user {}", stream_req.username));
+    user.t_ipv6_client = client_ip.is_ipv6();
+    update_user_activity(app_state, &user, client_ip, req_headers).await;
     if user.permission_denied(app_state) {
         return create_custom_video_stream_response(&app_state.config, CustomVideoStreamType::UserAccountExpired).into_response();
     }
@@ -99,14 +131,14 @@ async fn m3u_api_stream(
         }
         if session.virtual_id == virtual_id && is_seek_request(cluster, req_headers).await {
             // partial request means we are in reverse proxy mode, seek happened
-            return force_provider_stream_response(app_state, session, pli.item_type, req_headers, input, &user).await.into_response();
+            return force_provider_stream_response(app_state, session, pli.item_type, req_headers, input, target, &user).await.into_response();
         }
         session.stream_url.as_str()
     } else {
         pli.url.as_str()
     };
 
-    let connection_permission = user.connection_permission(app_state).await;
+    let connection_permission = user.connection_permission(app_state, fingerprint).await;
     if connection_permission == UserConnectionPermission::Exhausted {
         return create_custom_video_stream_response(&app_state.config, CustomVideoStreamType::UserConnectionsExhausted).into_response();
     }
@@ -136,7 +168,7 @@ async fn m3u_api_stream(
     let is_hls_request = pli.item_type == PlaylistItemType::LiveHls || pli.item_type == PlaylistItemType::LiveDash || extension == HLS_EXT;
     // Reverse proxy mode
     if is_hls_request {
-        return handle_hls_stream_request(fingerprint, app_state, &user, user_session.as_ref(), &pli.url, pli.virtual_id, input, connection_permission).await.into_response();
+        return handle_hls_stream_request(fingerprint, app_state, &user, user_session.as_ref(), &pli.url, pli.virtual_id, input, connection_permission, is_stream_share_enabled(PlaylistItemType::LiveHls, target)).await.into_response();
     }
 
     stream_response(app_state, &session_key, pli.virtual_id, pli.item_type, session_url, req_headers, input, target, &user, connection_permission).await.into_response()
@@ -186,6 +218,7 @@ macro_rules! create_m3u_api_stream {
     ($fn_name:ident, $context:expr) => {
         async fn $fn_name(
             Fingerprint(fingerprint): Fingerprint,
+            client_ip: ClientIp,
             req_headers: axum::http::HeaderMap,
             axum::extract::Query(api_req): axum::extract::Query<UserApiRequest>,
             axum::extract::Path((username, password, stream_id)): axum::extract::Path<(String, String, String)>,
@@ -194,6 +227,7 @@ macro_rules! create_m3u_api_stream {
         ) ->  impl IntoResponse + Send {
             m3u_api_stream(
                 &fingerprint,
+                client_ip,
                 &req_headers,
                 &app_state,
                 &api_req,
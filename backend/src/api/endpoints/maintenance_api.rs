@@ -0,0 +1,51 @@
+use crate::api::model::app_state::AppState;
+use crate::api::model::maintenance_state::MaintenanceBanner;
+use axum::response::IntoResponse;
+use log::info;
+use serde_json::json;
+use std::sync::Arc;
+
+#[derive(Debug, serde::Deserialize)]
+pub(in crate::api) struct MaintenanceRequest {
+    minutes: u32,
+    // target names, all targets if not set
+    #[serde(default)]
+    targets: Option<Vec<String>>,
+    // virtual ids of the channels, all channels if not set
+    #[serde(default)]
+    channels: Option<Vec<u32>>,
+}
+
+fn maintenance_response(app_state: &AppState) -> axum::response::Response {
+    axum::Json(json!({"active": app_state.maintenance.get()})).into_response()
+}
+
+pub(in crate::api) async fn maintenance_status(
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    maintenance_response(&app_state)
+}
+
+pub(in crate::api) async fn maintenance_start(
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+    axum::extract::Json(request): axum::extract::Json<MaintenanceRequest>,
+) -> impl axum::response::IntoResponse + Send {
+    if app_state.config.t_custom_stream_response.as_ref().is_none_or(|c| c.maintenance.is_none()) {
+        return (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": "No maintenance.ts in custom_stream_response_path"}))).into_response();
+    }
+    if request.minutes == 0 {
+        return (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": "Invalid minutes"}))).into_response();
+    }
+    let until = chrono::Utc::now().timestamp() + i64::from(request.minutes) * 60;
+    info!("Maintenance announcement started for {} minutes", request.minutes);
+    app_state.maintenance.set(Some(MaintenanceBanner { until, targets: request.targets, channels: request.channels }));
+    maintenance_response(&app_state)
+}
+
+pub(in crate::api) async fn maintenance_stop(
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    info!("Maintenance announcement stopped");
+    app_state.maintenance.set(None);
+    maintenance_response(&app_state)
+}
@@ -0,0 +1,51 @@
+use crate::api::model::app_state::AppState;
+use crate::foundation::filter::{apply_templates_to_pattern_single, PatternTemplate};
+use crate::foundation::mapper::MapperScript;
+use crate::model::PlaylistItemHeader;
+use crate::utils;
+use axum::response::IntoResponse;
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const MAX_DRY_RUN_ITEMS: usize = 100;
+
+#[derive(Debug, serde::Deserialize)]
+pub(in crate::api) struct MapperDryRunRequest {
+    script: String,
+    // missing fields of the samples are empty
+    #[serde(default)]
+    items: Vec<PlaylistItemHeader>,
+}
+
+// includes are resolved like for the mapping files, the templates of the mapping files can be used in the script
+fn get_mapping_context(mapping_file_path: &str) -> (Option<PathBuf>, Option<Vec<PatternTemplate>>) {
+    if mapping_file_path.is_empty() {
+        return (None, None);
+    }
+    let include_dir = if utils::is_directory(mapping_file_path) {
+        Some(PathBuf::from(mapping_file_path))
+    } else {
+        Path::new(mapping_file_path).parent().map(Path::to_path_buf)
+    };
+    let templates = utils::read_mappings(mapping_file_path, true).ok().flatten().and_then(|mappings| mappings.mappings.templates);
+    (include_dir, templates)
+}
+
+pub(in crate::api) async fn mapper_dry_run(
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+    axum::extract::Json(request): axum::extract::Json<MapperDryRunRequest>,
+) -> impl axum::response::IntoResponse + Send {
+    if request.items.len() > MAX_DRY_RUN_ITEMS {
+        return (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": format!("Too many items, max {MAX_DRY_RUN_ITEMS}")}))).into_response();
+    }
+    let (include_dir, templates) = get_mapping_context(&app_state.config.t_mapping_file_path);
+    let script = match templates.as_ref() {
+        Some(_) => apply_templates_to_pattern_single(&request.script, templates.as_ref()),
+        None => Ok(request.script),
+    };
+    match script.and_then(|script| MapperScript::parse_with_includes(&script, templates.as_ref(), include_dir.as_deref())) {
+        Ok(mapper) => axum::Json(json!({"results": mapper.dry_run(request.items, templates.as_ref())})).into_response(),
+        Err(err) => (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": err.to_string()}))).into_response(),
+    }
+}
@@ -5,6 +5,21 @@ pub(in crate::api) mod m3u_api;
 pub(in crate::api) mod xmltv_api;
 pub(in crate::api) mod web_index;
 pub(in crate::api) mod hls_api;
+pub(in crate::api) mod portal_api;
+pub(in crate::api) mod qr_code_api;
+pub(in crate::api) mod share_link_api;
+pub(in crate::api) mod provisioning_api;
+pub(in crate::api) mod log_api;
+pub(in crate::api) mod mapper_api;
+pub(in crate::api) mod refresh_history_api;
+pub(in crate::api) mod refresh_progress_api;
+pub(in crate::api) mod user_activity_api;
+pub(in crate::api) mod epg_match_api;
+pub(in crate::api) mod replication_api;
+pub(in crate::api) mod maintenance_api;
+pub(in crate::api) mod search_api;
+pub(in crate::api) mod recording_api;
 mod user_api;
+mod group_api;
 pub(in crate::api) mod hdhomerun_api;
 mod api_playlist_utils;
\ No newline at end of file
@@ -0,0 +1,181 @@
+use crate::api::model::app_state::AppState;
+use crate::foundation::template::Template;
+use crate::model::{Config, PortalConfig};
+use crate::utils::qr_code_svg;
+use axum::response::IntoResponse;
+use std::str::FromStr;
+use std::sync::{Arc, LazyLock};
+
+const DEFAULT_LOGIN_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8"><meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{title}}</title>
+<style>
+body{font-family:sans-serif;background:#1e1e2e;color:#e0e0e0;display:flex;justify-content:center;padding:2em}
+main{background:#2a2a3c;border-radius:8px;padding:2em;width:100%;max-width:22em}
+input,button{display:block;width:100%;box-sizing:border-box;margin:.5em 0;padding:.6em;border-radius:4px;border:1px solid #555}
+button{background:#5a7bd8;color:#fff;border:none;cursor:pointer}.error{color:#ff7b7b}
+</style>
+</head>
+<body>
+<main>
+<h1>{{title}}</h1>
+{{#if server.message}}<p>{{server.message}}</p>{{/if}}
+{{#if error}}<p class="error">{{error}}</p>{{/if}}
+<form method="post" action="{{path}}">
+<input name="username" placeholder="Username" autocomplete="username" required>
+<input name="password" type="password" placeholder="Password" autocomplete="current-password" required>
+<button type="submit">Login</button>
+</form>
+</main>
+</body>
+</html>
+"#;
+
+const DEFAULT_ACCOUNT_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8"><meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{{title}}</title>
+<style>
+body{font-family:sans-serif;background:#1e1e2e;color:#e0e0e0;display:flex;justify-content:center;padding:2em}
+main{background:#2a2a3c;border-radius:8px;padding:2em;width:100%;max-width:40em}
+a{color:#8fb0ff;word-break:break-all}.qr svg{width:12em;height:12em}.expired{color:#ff7b7b}
+td{padding:.3em 1em .3em 0;vertical-align:top}
+</style>
+</head>
+<body>
+<main>
+<h1>{{title}}</h1>
+<table>
+<tr><td>Username</td><td>{{user.username}}</td></tr>
+{{#if user.status}}<tr><td>Status</td><td>{{user.status}}</td></tr>{{/if}}
+<tr><td>Expires</td><td{{#if user.expired}} class="expired"{{/if}}>{{#if user.exp_date}}{{user.exp_date}}{{#unless user.expired}} ({{user.expires_in_days}} days){{/unless}}{{else}}unlimited{{/if}}</td></tr>
+<tr><td>Max connections</td><td>{{#if user.max_connections}}{{user.max_connections}}{{else}}unlimited{{/if}}</td></tr>
+<tr><td>Xtream server</td><td>{{server.url}}</td></tr>
+</table>
+<h2>Playlist</h2>
+<p><a href="{{m3u_url}}">{{m3u_url}}</a></p>
+<div class="qr">{{{qr.m3u}}}</div>
+<h2>EPG</h2>
+<p><a href="{{epg_url}}">{{epg_url}}</a></p>
+<div class="qr">{{{qr.epg}}}</div>
+</main>
+</body>
+</html>
+"#;
+
+static DEFAULT_LOGIN: LazyLock<Template> = LazyLock::new(|| Template::parse(DEFAULT_LOGIN_TEMPLATE).unwrap());
+static DEFAULT_ACCOUNT: LazyLock<Template> = LazyLock::new(|| Template::parse(DEFAULT_ACCOUNT_TEMPLATE).unwrap());
+
+#[derive(Debug, serde::Deserialize)]
+struct PortalLoginRequest {
+    #[serde(default)]
+    username: String,
+    #[serde(default)]
+    password: String,
+}
+
+// the server info with the host of the request selects the portal profile
+fn get_request_server_name(cfg: &Config, headers: &axum::http::HeaderMap) -> Option<String> {
+    let host = headers.get(axum::http::header::HOST)?.to_str().ok()?;
+    let authority = axum::http::uri::Authority::from_str(host).ok()?;
+    let guard = cfg.t_api_proxy.load();
+    guard.as_ref()?.server.iter()
+        .find(|server| server.host.eq_ignore_ascii_case(authority.host()))
+        .map(|server| server.name.clone())
+}
+
+fn get_title<'a>(portal: &'a PortalConfig, server_name: Option<&str>) -> &'a str {
+    portal.get_profile(server_name).and_then(|profile| profile.title.as_deref()).unwrap_or(&portal.title)
+}
+
+fn html_response(status: axum::http::StatusCode, content: String) -> axum::response::Response {
+    (status, [(axum::http::header::CACHE_CONTROL, "no-store")], axum::response::Html(content)).into_response()
+}
+
+fn render_login(cfg: &Config, portal: &PortalConfig, server_name: Option<&str>, error: Option<&str>) -> axum::response::Response {
+    let server = server_name.map(|name| cfg.get_server_info(name));
+    let context = serde_json::json!({
+        "title": get_title(portal, server_name),
+        "path": format!("/{}", portal.path),
+        "error": error,
+        "server": server.map(|server| serde_json::json!({"name": server.name, "url": server.get_base_url(), "message": server.message})),
+    });
+    let template = portal.get_profile(server_name).and_then(|profile| profile.t_login_template.as_ref())
+        .or(portal.t_login_template.as_ref())
+        .unwrap_or(&DEFAULT_LOGIN);
+    let status = if error.is_some() { axum::http::StatusCode::UNAUTHORIZED } else { axum::http::StatusCode::OK };
+    html_response(status, template.render_html(&context))
+}
+
+async fn portal_login_page(
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+) -> impl axum::response::IntoResponse + Send {
+    let Some(portal) = app_state.config.portal.as_ref() else { return axum::http::StatusCode::NOT_FOUND.into_response() };
+    let server_name = get_request_server_name(&app_state.config, &headers);
+    render_login(&app_state.config, portal, server_name.as_deref(), None)
+}
+
+async fn portal_login(
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+    headers: axum::http::HeaderMap,
+    axum::extract::Form(req): axum::extract::Form<PortalLoginRequest>,
+) -> impl axum::response::IntoResponse + Send {
+    let Some(portal) = app_state.config.portal.as_ref() else { return axum::http::StatusCode::NOT_FOUND.into_response() };
+    let server_name = get_request_server_name(&app_state.config, &headers);
+    let username = req.username.trim();
+    let password = req.password.trim();
+    let credentials = if username.is_empty() || password.is_empty() {
+        None
+    } else {
+        app_state.config.get_target_for_user(username, password).map(|(user, _target)| user)
+    };
+    let Some(user) = credentials else {
+        return render_login(&app_state.config, portal, server_name.as_deref(), Some("Invalid username or password"));
+    };
+
+    let mut context = app_state.config.get_user_template_context(&user);
+    let qr = serde_json::json!({
+        "m3u": context.get("m3u_url").and_then(serde_json::Value::as_str).and_then(qr_code_svg),
+        "epg": context.get("epg_url").and_then(serde_json::Value::as_str).and_then(qr_code_svg),
+    });
+    if let Some(values) = context.as_object_mut() {
+        values.insert("title".to_string(), get_title(portal, server_name.as_deref()).into());
+        values.insert("path".to_string(), format!("/{}", portal.path).into());
+        values.insert("qr".to_string(), qr);
+    }
+    let template = portal.get_profile(server_name.as_deref()).and_then(|profile| profile.t_account_template.as_ref())
+        .or(portal.t_account_template.as_ref())
+        .unwrap_or(&DEFAULT_ACCOUNT);
+    html_response(axum::http::StatusCode::OK, template.render_html(&context))
+}
+
+pub fn portal_api_register(path: &str) -> axum::Router<Arc<AppState>> {
+    axum::Router::new()
+        .route(&format!("/{path}"), axum::routing::get(portal_login_page).post(portal_login))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::endpoints::portal_api::{DEFAULT_ACCOUNT, DEFAULT_LOGIN};
+
+    #[test]
+    fn test_default_templates() {
+        let login = DEFAULT_LOGIN.render_html(&serde_json::json!({"title": "TV", "path": "/portal", "error": "Invalid <user>"}));
+        assert!(login.contains(r#"action="/portal""#));
+        assert!(login.contains("Invalid &lt;user&gt;"));
+        let account = DEFAULT_ACCOUNT.render_html(&serde_json::json!({
+            "title": "TV",
+            "user": {"username": "bob", "exp_date": "2030-01-01", "expires_in_days": 10, "expired": false, "max_connections": 0},
+            "m3u_url": "http://tv.local/get.php?username=bob&password=pw",
+            "qr": {"m3u": "<svg/>"},
+        }));
+        assert!(account.contains("2030-01-01 (10 days)"));
+        assert!(account.contains("unlimited"));
+        assert!(account.contains(r#"href="http://tv.local/get.php?username=bob&amp;password=pw""#));
+        assert!(account.contains(r#"<div class="qr"><svg/></div>"#));
+    }
+}
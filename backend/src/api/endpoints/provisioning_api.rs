@@ -0,0 +1,148 @@
+use crate::api::model::app_state::AppState;
+use crate::foundation::template::Template;
+use crate::model::ProxyUserCredentials;
+use axum::response::IntoResponse;
+use serde_json::{json, Value};
+use std::sync::{Arc, LazyLock};
+
+const KODI_SETTINGS_TEMPLATE: &str = r#"<settings version="2">
+    <setting id="kodi_addon_instance_name">{{server.name}} {{user.username}}</setting>
+    <setting id="kodi_addon_instance_enabled">true</setting>
+    <setting id="m3uPathType">1</setting>
+    <setting id="m3uUrl">{{m3u_url}}</setting>
+    <setting id="m3uCache">true</setting>
+    <setting id="epgPathType">1</setting>
+    <setting id="epgUrl">{{epg_url}}</setting>
+    <setting id="epgCache">true</setting>
+    <setting id="logoFromEpg">1</setting>
+    <setting id="catchupEnabled">true</setting>
+</settings>
+"#;
+
+static KODI_SETTINGS: LazyLock<Template> = LazyLock::new(|| Template::parse(KODI_SETTINGS_TEMPLATE).unwrap());
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum ProvisioningProfile {
+    TiviMate,
+    Smarters,
+    Kodi,
+}
+
+impl ProvisioningProfile {
+    fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "tivimate" => Some(Self::TiviMate),
+            "smarters" => Some(Self::Smarters),
+            "kodi" => Some(Self::Kodi),
+            _ => None,
+        }
+    }
+
+    const fn file_name(self) -> &'static str {
+        match self {
+            Self::TiviMate => "tivimate.json",
+            Self::Smarters => "smarters.json",
+            Self::Kodi => "instance-settings-1.xml",
+        }
+    }
+
+    const fn content_type(self) -> &'static str {
+        match self {
+            Self::TiviMate | Self::Smarters => "application/json",
+            Self::Kodi => "application/xml",
+        }
+    }
+
+    // the context is the user template context from `Config::get_user_template_context`
+    fn render(self, context: &Value) -> String {
+        let field = |path: &str| path.split('.').fold(context, |value, key| value.get(key).unwrap_or(&Value::Null)).clone();
+        let name = format!("{} {}", field("server.name").as_str().unwrap_or_default(), field("user.username").as_str().unwrap_or_default());
+        match self {
+            Self::TiviMate => json!({
+                "playlists": [{
+                    "name": name,
+                    "type": "xtream",
+                    "server": field("server.url"),
+                    "username": field("user.username"),
+                    "password": field("user.password"),
+                    "epg": field("epg_url"),
+                }, {
+                    "name": format!("{name} M3U"),
+                    "type": "m3u",
+                    "url": field("m3u_url"),
+                    "epg": field("epg_url"),
+                }],
+            }).to_string(),
+            Self::Smarters => json!({
+                "playlist": {
+                    "any_name": name,
+                    "login_type": "xtream",
+                    "url": field("server.url"),
+                    "username": field("user.username"),
+                    "password": field("user.password"),
+                    "m3u_url": field("m3u_url"),
+                    "epg_url": field("epg_url"),
+                },
+            }).to_string(),
+            // xml needs the same escaping as html
+            Self::Kodi => KODI_SETTINGS.render_html(context),
+        }
+    }
+}
+
+pub(in crate::api) fn provisioning_response(app_state: &AppState, user: &ProxyUserCredentials, profile: &str) -> axum::response::Response {
+    let Some(profile) = ProvisioningProfile::from_name(profile) else {
+        return axum::http::StatusCode::NOT_FOUND.into_response();
+    };
+    let content = profile.render(&app_state.config.get_user_template_context(user));
+    (
+        [
+            (axum::http::header::CONTENT_TYPE, profile.content_type().to_string()),
+            (axum::http::header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", profile.file_name())),
+            (axum::http::header::CACHE_CONTROL, "no-store".to_string()),
+        ],
+        content,
+    ).into_response()
+}
+
+#[derive(Debug, serde::Deserialize)]
+pub(in crate::api) struct ProvisioningQuery {
+    username: String,
+}
+
+pub(in crate::api) async fn provisioning(
+    axum::extract::Path(profile): axum::extract::Path<String>,
+    axum::extract::Query(query): axum::extract::Query<ProvisioningQuery>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    match app_state.config.get_user_credentials(&query.username) {
+        Some(user) => provisioning_response(&app_state, &user, &profile),
+        None => axum::http::StatusCode::BAD_REQUEST.into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::endpoints::provisioning_api::ProvisioningProfile;
+    use serde_json::json;
+
+    #[test]
+    fn test_provisioning_profiles() {
+        let context = json!({
+            "server": {"name": "default", "url": "http://tv.local:8901"},
+            "user": {"username": "bob", "password": "a&b"},
+            "m3u_url": "http://tv.local:8901/get.php?username=bob&password=a&b&type=m3u_plus",
+            "epg_url": "http://tv.local:8901/xmltv.php?username=bob&password=a&b",
+        });
+        let tivimate: serde_json::Value = serde_json::from_str(&ProvisioningProfile::TiviMate.render(&context)).unwrap();
+        assert_eq!(tivimate["playlists"][0]["server"], "http://tv.local:8901");
+        assert_eq!(tivimate["playlists"][0]["password"], "a&b");
+        assert_eq!(tivimate["playlists"][1]["name"], "default bob M3U");
+        let smarters: serde_json::Value = serde_json::from_str(&ProvisioningProfile::Smarters.render(&context)).unwrap();
+        assert_eq!(smarters["playlist"]["username"], "bob");
+        let kodi = ProvisioningProfile::Kodi.render(&context);
+        assert!(kodi.contains(r#"<setting id="epgUrl">http://tv.local:8901/xmltv.php?username=bob&amp;password=a&amp;b</setting>"#));
+        assert_eq!(ProvisioningProfile::from_name("TiviMate"), Some(ProvisioningProfile::TiviMate));
+        assert_eq!(ProvisioningProfile::from_name("vlc"), None);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::api::model::app_state::AppState;
+use crate::auth::{create_signature, verify_signature};
+use crate::utils::{qr_code_png, qr_code_svg};
+use axum::response::IntoResponse;
+use std::sync::Arc;
+
+const QR_CODE_PNG_SCALE: usize = 8;
+const QR_CODE_PATH: &str = "qrcode";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum QrCodeKind {
+    M3u,
+    Xtream,
+}
+
+impl QrCodeKind {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::M3u => "m3u",
+            Self::Xtream => "xtream",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "m3u" => Some(Self::M3u),
+            "xtream" => Some(Self::Xtream),
+            _ => None,
+        }
+    }
+
+    const fn url_field(self) -> &'static str {
+        match self {
+            Self::M3u => "m3u_url",
+            Self::Xtream => "xtream_url",
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum QrCodeFormat {
+    #[default]
+    Svg,
+    Png,
+}
+
+impl QrCodeFormat {
+    const fn as_str(self) -> &'static str {
+        match self {
+            Self::Svg => "svg",
+            Self::Png => "png",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "svg" => Some(Self::Svg),
+            "png" => Some(Self::Png),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(in crate::api) struct QrCodeRequest {
+    #[serde(default)]
+    pub username: String,
+    kind: QrCodeKind,
+    #[serde(default)]
+    format: QrCodeFormat,
+    // validity of the url in minutes, unlimited if not set
+    #[serde(default)]
+    ttl_mins: Option<u32>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct QrCodeQuery {
+    username: String,
+    #[serde(default)]
+    expires: Option<i64>,
+    signature: String,
+}
+
+fn signature_payload(username: &str, kind: QrCodeKind) -> String {
+    format!("{QR_CODE_PATH}:{username}:{}", kind.as_str())
+}
+
+// creates the signed image url on the server info of the user
+fn create_qr_code_url(app_state: &AppState, request: &QrCodeRequest) -> Option<String> {
+    let user = app_state.config.get_user_credentials(&request.username)?;
+    let expires = request.ttl_mins.map(|mins| chrono::Utc::now().timestamp() + i64::from(mins) * 60);
+    let signature = create_signature(&app_state.config.get_url_signing_secret(), &signature_payload(&user.username, request.kind), expires);
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    query.append_pair("username", &user.username);
+    if let Some(ts) = expires {
+        query.append_pair("expires", &ts.to_string());
+    }
+    query.append_pair("signature", &signature);
+    let base_url = app_state.config.get_user_server_info(&user).get_base_url();
+    Some(format!("{base_url}/{QR_CODE_PATH}/{}.{}?{}", request.kind.as_str(), request.format.as_str(), query.finish()))
+}
+
+pub(in crate::api) fn qr_code_url_response(app_state: &AppState, request: &QrCodeRequest) -> axum::response::Response {
+    create_qr_code_url(app_state, request).map_or_else(|| axum::http::StatusCode::BAD_REQUEST.into_response(), IntoResponse::into_response)
+}
+
+pub(in crate::api) async fn qr_code_url(
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+    axum::extract::Json(request): axum::extract::Json<QrCodeRequest>,
+) -> impl axum::response::IntoResponse + Send {
+    qr_code_url_response(&app_state, &request)
+}
+
+async fn qr_code_image(
+    axum::extract::Path(file): axum::extract::Path<String>,
+    axum::extract::Query(query): axum::extract::Query<QrCodeQuery>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    let Some((kind, format)) = file.split_once('.')
+        .and_then(|(kind, format)| QrCodeKind::from_name(kind).zip(QrCodeFormat::from_name(format))) else {
+        return axum::http::StatusCode::NOT_FOUND.into_response();
+    };
+    if !verify_signature(&app_state.config.get_url_signing_secret(), &signature_payload(&query.username, kind), query.expires, &query.signature) {
+        return axum::http::StatusCode::FORBIDDEN.into_response();
+    }
+    let Some(user) = app_state.config.get_user_credentials(&query.username) else {
+        return axum::http::StatusCode::FORBIDDEN.into_response();
+    };
+    if user.permission_denied(&app_state) {
+        return axum::http::StatusCode::FORBIDDEN.into_response();
+    }
+    let context = app_state.config.get_user_template_context(&user);
+    let Some(text) = context.get(kind.url_field()).and_then(serde_json::Value::as_str) else {
+        return axum::http::StatusCode::INTERNAL_SERVER_ERROR.into_response();
+    };
+    let headers = [(axum::http::header::CACHE_CONTROL, "no-store")];
+    match format {
+        QrCodeFormat::Svg => qr_code_svg(text).map(|svg| (headers, [(axum::http::header::CONTENT_TYPE, "image/svg+xml")], svg).into_response()),
+        QrCodeFormat::Png => qr_code_png(text, QR_CODE_PNG_SCALE).map(|png| (headers, [(axum::http::header::CONTENT_TYPE, mime::IMAGE_PNG.as_ref())], png).into_response()),
+    }.unwrap_or_else(|| axum::http::StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+pub fn qr_code_api_register() -> axum::Router<Arc<AppState>> {
+    axum::Router::new()
+        .route(&format!("/{QR_CODE_PATH}/{{file}}"), axum::routing::get(qr_code_image))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::endpoints::qr_code_api::{signature_payload, QrCodeKind, QrCodeRequest};
+
+    #[test]
+    fn test_qr_code_request() {
+        let request: QrCodeRequest = serde_json::from_str(r#"{"username": "bob", "kind": "xtream", "ttl_mins": 10}"#).unwrap();
+        assert_eq!(request.kind, QrCodeKind::Xtream);
+        assert_eq!(request.format.as_str(), "svg");
+        assert_ne!(signature_payload("bob", QrCodeKind::M3u), signature_payload("bob", QrCodeKind::Xtream));
+        assert!(serde_json::from_str::<QrCodeRequest>(r#"{"kind": "epg"}"#).is_err());
+    }
+}
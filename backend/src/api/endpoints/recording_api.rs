@@ -0,0 +1,88 @@
+use crate::api::model::app_state::AppState;
+use crate::api::model::recording::Recording;
+use crate::api::recording::{exec_recording, get_recording_channel, get_recording_programme};
+use axum::response::IntoResponse;
+use log::info;
+use serde_json::json;
+use std::sync::Arc;
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(in crate::api) struct RecordingRequest {
+    target: String,
+    // virtual id of the live channel
+    virtual_id: u32,
+    // unix timestamps, not needed when the programme is given
+    #[serde(default)]
+    start: Option<i64>,
+    #[serde(default)]
+    stop: Option<i64>,
+    // the start of the programme in the json guide of the target identifies the programme of the channel
+    #[serde(default)]
+    programme_start: Option<i64>,
+}
+
+fn bad_request(error: &str) -> axum::response::Response {
+    (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": error}))).into_response()
+}
+
+pub(in crate::api) async fn recording_list(
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    match app_state.recordings.as_ref() {
+        Some(manager) => axum::Json(manager.list().await).into_response(),
+        None => bad_request("Recording is not enabled"),
+    }
+}
+
+pub(in crate::api) async fn recording_schedule(
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+    axum::extract::Json(request): axum::extract::Json<RecordingRequest>,
+) -> impl axum::response::IntoResponse + Send {
+    let Some(manager) = app_state.recordings.as_ref() else {
+        return bad_request("Recording is not enabled");
+    };
+    let Some(target) = app_state.config.sources.get_target_by_name(&request.target) else {
+        return bad_request("Target not found");
+    };
+    let channel = match get_recording_channel(&app_state.config, target, request.virtual_id).await {
+        Ok(channel) => channel,
+        Err(err) => return bad_request(err),
+    };
+    let (start, stop) = match (request.programme_start, request.start, request.stop) {
+        (Some(programme_start), None, None) => match get_recording_programme(&app_state.config, target, &channel, programme_start) {
+            Ok(times) => times,
+            Err(err) => return bad_request(err),
+        },
+        (None, Some(start), Some(stop)) => (start, stop),
+        _ => return bad_request("Either start and stop or programme_start is required"),
+    };
+    if stop <= start || stop <= chrono::Utc::now().timestamp() {
+        return bad_request("Invalid start or stop");
+    }
+    let recording = Recording::new(&target.name, request.virtual_id, &channel.name, start, stop);
+    match manager.add(recording.clone()).await {
+        Ok(cancel) => {
+            info!("Recording of {} scheduled", recording.name);
+            exec_recording(&app_state, recording.clone(), cancel);
+            axum::Json(recording).into_response()
+        }
+        Err(err) => bad_request(err),
+    }
+}
+
+pub(in crate::api) async fn recording_delete(
+    axum::extract::Path(id): axum::extract::Path<String>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    let Some(manager) = app_state.recordings.as_ref() else {
+        return bad_request("Recording is not enabled");
+    };
+    match manager.delete(&id).await {
+        Some(recording) => {
+            info!("Recording of {} deleted", recording.name);
+            axum::Json(recording).into_response()
+        }
+        None => axum::http::StatusCode::NOT_FOUND.into_response(),
+    }
+}
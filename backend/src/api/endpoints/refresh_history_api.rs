@@ -0,0 +1,29 @@
+use crate::api::model::app_state::AppState;
+use crate::model::RefreshTrend;
+use crate::repository::refresh_history_repository::load_refresh_history;
+use axum::response::IntoResponse;
+use serde_json::json;
+use std::sync::Arc;
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub(in crate::api) struct RefreshHistoryQuery {
+    // entries after this unix timestamp in seconds
+    #[serde(default)]
+    since: Option<i64>,
+    // the last n entries
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+pub(in crate::api) async fn refresh_history(
+    axum::extract::Path(target_id): axum::extract::Path<u16>,
+    axum::extract::Query(query): axum::extract::Query<RefreshHistoryQuery>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    let Some(target_name) = app_state.config.get_target_by_id(target_id).map(|target| target.name.clone()) else {
+        return (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": "Invalid target"}))).into_response();
+    };
+    let entries = load_refresh_history(&app_state.config, &target_name).await.query(query.since, query.limit);
+    let trend = RefreshTrend::new(&entries);
+    axum::Json(json!({"target": target_name, "entries": entries, "trend": trend})).into_response()
+}
@@ -0,0 +1,18 @@
+use crate::utils::REFRESH_PROGRESS;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::IntoResponse;
+use futures::StreamExt;
+use tokio_stream::wrappers::BroadcastStream;
+
+pub(in crate::api) async fn refresh_progress() -> impl axum::response::IntoResponse + Send {
+    axum::Json(REFRESH_PROGRESS.get_running()).into_response()
+}
+
+// Sends the progress of the running target refreshes and then every update as server-sent events.
+pub(in crate::api) async fn refresh_progress_stream() -> impl axum::response::IntoResponse + Send {
+    let (running, receiver) = REFRESH_PROGRESS.subscribe();
+    // lagging receivers skip the missed updates, the next update of a target contains its current state
+    let live = BroadcastStream::new(receiver).filter_map(|progress| futures::future::ready(progress.ok()));
+    let events = futures::stream::iter(running).chain(live).map(|progress| Event::default().event("progress").json_data(progress));
+    Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+}
@@ -0,0 +1,111 @@
+use crate::api::model::app_state::AppState;
+use crate::api::replication::{create_replication_manifest, resolve_replication_file, REPLICATION_API_PATH};
+use crate::auth::{constant_time_eq, AuthBearer};
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::json;
+use std::sync::Arc;
+
+#[derive(Debug, Deserialize)]
+struct ReplicationFileRequest {
+    root: String,
+    #[serde(default)]
+    path: String,
+}
+
+fn is_authorized(app_state: &AppState, token: &str) -> bool {
+    app_state.config.replication.as_ref().is_some_and(|replication| replication.enabled && constant_time_eq(replication.token.as_bytes(), token.as_bytes()))
+}
+
+fn is_standby(app_state: &AppState) -> bool {
+    app_state.config.replication.as_ref().is_some_and(crate::model::ReplicationConfig::is_standby)
+}
+
+async fn replication_manifest(
+    AuthBearer(token): AuthBearer,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl IntoResponse {
+    if !is_authorized(&app_state, &token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let cfg = Arc::clone(&app_state.config);
+    match tokio::task::spawn_blocking(move || create_replication_manifest(&cfg)).await {
+        Ok(manifest) => axum::Json(manifest).into_response(),
+        Err(_) => StatusCode::INTERNAL_SERVER_ERROR.into_response(),
+    }
+}
+
+async fn replication_file(
+    AuthBearer(token): AuthBearer,
+    axum::extract::Query(request): axum::extract::Query<ReplicationFileRequest>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl IntoResponse {
+    if !is_authorized(&app_state, &token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    let Some(path) = resolve_replication_file(&app_state.config, &request.root, &request.path) else {
+        return StatusCode::BAD_REQUEST.into_response();
+    };
+    let file_lock = app_state.config.file_locks.read_lock(&path).await;
+    match tokio::fs::File::open(&path).await {
+        Ok(file) => {
+            // the read lock is held until the file is sent
+            let stream = tokio_util::io::ReaderStream::new(tokio::io::BufReader::new(file))
+                .map(move |chunk| {
+                    let _ = &file_lock;
+                    chunk
+                });
+            axum::response::Response::builder()
+                .status(StatusCode::OK)
+                .header(axum::http::header::CONTENT_TYPE, mime::APPLICATION_OCTET_STREAM.to_string())
+                .body(axum::body::Body::from_stream(stream))
+                .unwrap()
+                .into_response()
+        }
+        Err(_) => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+fn replication_status_response(app_state: &AppState) -> axum::response::Response {
+    axum::Json(json!({
+        "standby": is_standby(app_state),
+        "promoted": app_state.replication.is_promoted(),
+        "last_sync": app_state.replication.last_sync(),
+    })).into_response()
+}
+
+async fn replication_status(
+    AuthBearer(token): AuthBearer,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl IntoResponse {
+    if !is_authorized(&app_state, &token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    replication_status_response(&app_state)
+}
+
+async fn replication_promote(
+    AuthBearer(token): AuthBearer,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl IntoResponse {
+    if !is_authorized(&app_state, &token) {
+        return StatusCode::UNAUTHORIZED.into_response();
+    }
+    if !is_standby(&app_state) {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    if !app_state.replication.is_promoted() {
+        app_state.replication.request_promotion();
+    }
+    replication_status_response(&app_state)
+}
+
+pub fn replication_api_register() -> axum::Router<Arc<AppState>> {
+    axum::Router::new()
+        .route(&format!("{REPLICATION_API_PATH}/manifest"), axum::routing::get(replication_manifest))
+        .route(&format!("{REPLICATION_API_PATH}/file"), axum::routing::get(replication_file))
+        .route(&format!("{REPLICATION_API_PATH}/status"), axum::routing::get(replication_status))
+        .route(&format!("{REPLICATION_API_PATH}/promote"), axum::routing::post(replication_promote))
+}
@@ -0,0 +1,155 @@
+use crate::api::model::app_state::AppState;
+use crate::model::{Config, ConfigTarget};
+use crate::repository::{m3u_repository, xtream_repository};
+use crate::utils::request::sanitize_sensitive_info;
+use axum::response::IntoResponse;
+use serde_json::json;
+use shared::model::{PlaylistItemType, TargetType, XtreamCluster};
+use std::sync::Arc;
+
+const DEFAULT_SEARCH_LIMIT: usize = 100;
+// minimum jaro winkler similarity in percent for fuzzy matches
+const FUZZY_MATCH_THRESHOLD: u16 = 85;
+
+#[derive(Debug, serde::Deserialize)]
+pub(in crate::api) struct ChannelSearchQuery {
+    q: String,
+    #[serde(default)]
+    fuzzy: bool,
+    #[serde(default)]
+    limit: Option<usize>,
+}
+
+#[derive(Debug, serde::Serialize)]
+struct ChannelSearchResult {
+    target: String,
+    users: Vec<String>,
+    xtream_cluster: XtreamCluster,
+    item_type: PlaylistItemType,
+    virtual_id: u32,
+    name: String,
+    title: String,
+    group: String,
+    input_name: String,
+    provider_id: String,
+    url: String,
+    score: u16,
+}
+
+struct ChannelMatcher {
+    query: String,
+    fuzzy: bool,
+}
+
+impl ChannelMatcher {
+    fn new(query: &str, fuzzy: bool) -> Self {
+        Self { query: query.trim().to_lowercase(), fuzzy }
+    }
+
+    // 100 for a substring match, otherwise the fuzzy similarity above the threshold
+    fn score(&self, values: &[&str]) -> Option<u16> {
+        values.iter().filter_map(|value| {
+            let value = value.to_lowercase();
+            if value.contains(&self.query) {
+                Some(100)
+            } else if self.fuzzy {
+                #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+                let score = (strsim::jaro_winkler(&value, &self.query) * 100.0).round() as u16;
+                Some(score).filter(|score| *score >= FUZZY_MATCH_THRESHOLD)
+            } else {
+                None
+            }
+        }).max()
+    }
+}
+
+fn get_target_users(cfg: &Config, target_name: &str) -> Vec<String> {
+    cfg.t_api_proxy.load().as_ref().map(|api_proxy| api_proxy.user.iter()
+        .filter(|target_user| target_user.target == target_name)
+        .flat_map(|target_user| target_user.credentials.iter().map(|credentials| credentials.username.clone()))
+        .collect()).unwrap_or_default()
+}
+
+async fn search_target(cfg: &Arc<Config>, target: &ConfigTarget, matcher: &ChannelMatcher, results: &mut Vec<ChannelSearchResult>) {
+    let users = get_target_users(cfg, &target.name);
+    if target.has_output(&TargetType::Xtream) {
+        for cluster in [XtreamCluster::Live, XtreamCluster::Video, XtreamCluster::Series] {
+            if let Some((_guard, iter)) = xtream_repository::iter_raw_xtream_playlist(cfg, target, cluster).await {
+                for (item, _) in iter {
+                    if let Some(score) = matcher.score(&[&item.name, &item.title]) {
+                        results.push(ChannelSearchResult {
+                            target: target.name.clone(),
+                            users: users.clone(),
+                            xtream_cluster: item.xtream_cluster,
+                            item_type: item.item_type,
+                            virtual_id: item.virtual_id,
+                            provider_id: item.provider_id.to_string(),
+                            url: sanitize_sensitive_info(&item.url).to_string(),
+                            name: item.name,
+                            title: item.title,
+                            group: item.group,
+                            input_name: item.input_name,
+                            score,
+                        });
+                    }
+                }
+            }
+        }
+    } else if target.has_output(&TargetType::M3u) {
+        if let Some((_guard, iter)) = m3u_repository::iter_raw_m3u_playlist(cfg, target).await {
+            for (item, _) in iter {
+                if let Some(score) = matcher.score(&[&item.name, &item.title]) {
+                    results.push(ChannelSearchResult {
+                        target: target.name.clone(),
+                        users: users.clone(),
+                        xtream_cluster: XtreamCluster::try_from(item.item_type).unwrap_or(XtreamCluster::Live),
+                        item_type: item.item_type,
+                        virtual_id: item.virtual_id,
+                        url: sanitize_sensitive_info(&item.url).to_string(),
+                        name: item.name,
+                        title: item.title,
+                        group: item.group,
+                        input_name: item.input_name,
+                        provider_id: item.provider_id,
+                        score,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Searches the processed playlists of all targets by name or title.
+pub(in crate::api) async fn channel_search(
+    axum::extract::Query(query): axum::extract::Query<ChannelSearchQuery>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    if query.q.trim().is_empty() {
+        return (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": "Invalid query"}))).into_response();
+    }
+    let matcher = ChannelMatcher::new(&query.q, query.fuzzy);
+    let mut results = vec![];
+    for target in app_state.config.sources.sources.iter().flat_map(|source| &source.targets) {
+        search_target(&app_state.config, target, &matcher, &mut results).await;
+    }
+    // stable sort, the best matches first in target order
+    results.sort_by_key(|result| std::cmp::Reverse(result.score));
+    results.truncate(query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT));
+    axum::Json(results).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChannelMatcher;
+
+    #[test]
+    fn test_channel_matcher() {
+        let matcher = ChannelMatcher::new("Sky Sport", false);
+        assert_eq!(matcher.score(&["DE: SKY SPORT 1 HD", ""]), Some(100));
+        assert_eq!(matcher.score(&["Sky Sprt 1"]), None);
+
+        let matcher = ChannelMatcher::new("sky sport", true);
+        assert!(matcher.score(&["Sky Sprt"]).is_some_and(|score| (85..100).contains(&score)));
+        assert_eq!(matcher.score(&["Discovery"]), None);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::api::api_utils::get_user_target_by_username;
+use crate::api::endpoints::m3u_api::m3u_api_stream;
+use crate::api::endpoints::xtream_api::{xtream_player_api_stream, ApiStreamContext, ApiStreamRequest};
+use crate::api::model::app_state::AppState;
+use crate::api::model::request::UserApiRequest;
+use crate::auth::{create_signature, verify_signature, ClientIp, Fingerprint};
+use crate::model::{ConfigTarget, ProxyUserCredentials};
+use crate::repository::m3u_repository::m3u_get_item_for_stream_id;
+use crate::repository::xtream_repository::xtream_get_item_for_stream_id;
+use axum::response::IntoResponse;
+use serde_json::json;
+use shared::model::{PlaylistItemType, TargetType};
+use std::sync::Arc;
+
+const SHARE_LINK_PATH: &str = "share";
+const DEFAULT_SHARE_LINK_TTL_MINS: u32 = 60;
+
+#[derive(Debug, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(in crate::api) struct ShareLinkRequest {
+    #[serde(default)]
+    pub username: String,
+    // virtual id of the live channel
+    virtual_id: u32,
+    // validity of the url in minutes, default 60
+    #[serde(default)]
+    ttl_mins: Option<u32>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct ShareLinkQuery {
+    username: String,
+    expires: i64,
+    signature: String,
+}
+
+fn signature_payload(username: &str, virtual_id: u32) -> String {
+    format!("{SHARE_LINK_PATH}:{username}:{virtual_id}")
+}
+
+fn bad_request(error: &str) -> axum::response::Response {
+    (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": error}))).into_response()
+}
+
+// Only plain live channels can be shared, redirects and hls playlists would reveal the credentials of the issuer.
+async fn get_share_link_item_type(app_state: &AppState, user: &ProxyUserCredentials, target: &ConfigTarget, virtual_id: u32) -> Result<PlaylistItemType, &'static str> {
+    let item_type = if target.has_output(&TargetType::Xtream) {
+        xtream_get_item_for_stream_id(virtual_id, &app_state.config, target, None).map(|(item, _)| item.item_type)
+    } else if target.has_output(&TargetType::M3u) {
+        m3u_get_item_for_stream_id(virtual_id, &app_state.config, target).await.map(|item| item.item_type)
+    } else {
+        return Err("Target has no xtream or m3u output");
+    }.map_err(|_| "Channel not found")?;
+    if !matches!(item_type, PlaylistItemType::Live | PlaylistItemType::LiveUnknown) {
+        return Err("Only live channels can be shared");
+    }
+    if user.proxy.is_redirect(item_type) || target.is_force_redirect(item_type) {
+        return Err("Channels of users in redirect mode can't be shared");
+    }
+    Ok(item_type)
+}
+
+pub(in crate::api) async fn share_link_url_response(app_state: &AppState, request: &ShareLinkRequest) -> axum::response::Response {
+    let Some((user, target)) = get_user_target_by_username(&request.username, app_state) else {
+        return bad_request("User not found");
+    };
+    if user.permission_denied(app_state) {
+        return bad_request("User has no permission");
+    }
+    let ttl_mins = request.ttl_mins.unwrap_or(DEFAULT_SHARE_LINK_TTL_MINS);
+    if ttl_mins == 0 {
+        return bad_request("Invalid ttl_mins");
+    }
+    if let Err(err) = get_share_link_item_type(app_state, &user, target, request.virtual_id).await {
+        return bad_request(err);
+    }
+    let expires = chrono::Utc::now().timestamp() + i64::from(ttl_mins) * 60;
+    let signature = create_signature(&app_state.config.get_url_signing_secret(), &signature_payload(&user.username, request.virtual_id), Some(expires));
+    let mut query = url::form_urlencoded::Serializer::new(String::new());
+    query.append_pair("username", &user.username);
+    query.append_pair("expires", &expires.to_string());
+    query.append_pair("signature", &signature);
+    let base_url = app_state.config.get_user_server_info(&user).get_base_url();
+    let url = format!("{base_url}/{SHARE_LINK_PATH}/{}.ts?{}", request.virtual_id, query.finish());
+    axum::Json(json!({"url": url, "expires": expires})).into_response()
+}
+
+pub(in crate::api) async fn share_link_url(
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+    axum::extract::Json(request): axum::extract::Json<ShareLinkRequest>,
+) -> impl axum::response::IntoResponse + Send {
+    share_link_url_response(&app_state, &request).await
+}
+
+/// Streams the shared channel with the credentials of the issuer, the stream counts against the connections of the issuer.
+async fn share_link_stream(
+    Fingerprint(fingerprint): Fingerprint,
+    client_ip: ClientIp,
+    req_headers: axum::http::HeaderMap,
+    axum::extract::Path(stream_id): axum::extract::Path<String>,
+    axum::extract::Query(query): axum::extract::Query<ShareLinkQuery>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    let Some(virtual_id) = stream_id.split('.').next().and_then(|id| id.parse::<u32>().ok()) else {
+        return axum::http::StatusCode::NOT_FOUND.into_response();
+    };
+    if !verify_signature(&app_state.config.get_url_signing_secret(), &signature_payload(&query.username, virtual_id), Some(query.expires), &query.signature) {
+        return axum::http::StatusCode::FORBIDDEN.into_response();
+    }
+    let Some((user, target)) = get_user_target_by_username(&query.username, &app_state) else {
+        return axum::http::StatusCode::FORBIDDEN.into_response();
+    };
+    // the channel could have changed since the link was created
+    if get_share_link_item_type(&app_state, &user, target, virtual_id).await.is_err() {
+        return axum::http::StatusCode::NOT_FOUND.into_response();
+    }
+    let api_req = UserApiRequest::default();
+    let virtual_id = virtual_id.to_string();
+    let stream_req = ApiStreamRequest::from(ApiStreamContext::Live, &user.username, &user.password, &virtual_id, "");
+    if target.has_output(&TargetType::Xtream) {
+        xtream_player_api_stream(&fingerprint, client_ip, &req_headers, &app_state, &api_req, stream_req).await.into_response()
+    } else {
+        m3u_api_stream(&fingerprint, client_ip, &req_headers, &app_state, &api_req, stream_req).await.into_response()
+    }
+}
+
+pub fn share_link_api_register() -> axum::Router<Arc<AppState>> {
+    axum::Router::new()
+        .route(&format!("/{SHARE_LINK_PATH}/{{stream_id}}"), axum::routing::get(share_link_stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::endpoints::share_link_api::signature_payload;
+    use crate::auth::{create_signature, verify_signature};
+
+    #[test]
+    fn test_share_link_signature() {
+        let secret = b"37c30f739e83ba27b4c17b174c31f3a9";
+        let expires = chrono::Utc::now().timestamp() + 60;
+        let signature = create_signature(secret, &signature_payload("bob", 42), Some(expires));
+        assert!(verify_signature(secret, &signature_payload("bob", 42), Some(expires), &signature));
+        // the link is only valid for the signed channel and user
+        assert!(!verify_signature(secret, &signature_payload("bob", 43), Some(expires), &signature));
+        assert!(!verify_signature(secret, &signature_payload("alice", 42), Some(expires), &signature));
+        let past_expires = chrono::Utc::now().timestamp() - 1;
+        let signature = create_signature(secret, &signature_payload("bob", 42), Some(past_expires));
+        assert!(!verify_signature(secret, &signature_payload("bob", 42), Some(past_expires), &signature));
+    }
+}
@@ -0,0 +1,41 @@
+use crate::api::model::app_state::AppState;
+use crate::model::{UserActivityInfo, DEFAULT_DORMANT_DAYS};
+use axum::response::IntoResponse;
+use shared::utils::current_time_secs;
+use std::sync::Arc;
+
+#[derive(Debug, Default, serde::Deserialize)]
+pub(in crate::api) struct UserActivityQuery {
+    // users without activity for this number of days are dormant
+    #[serde(default)]
+    dormant_days: Option<u64>,
+    // only dormant users
+    #[serde(default)]
+    dormant: bool,
+}
+
+/// Returns the last activity and the device inventory of all configured users, sorted by username.
+pub(in crate::api) async fn user_activity(
+    axum::extract::Query(query): axum::extract::Query<UserActivityQuery>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    let now = current_time_secs();
+    let dormant_secs = query.dormant_days.unwrap_or(DEFAULT_DORMANT_DAYS).saturating_mul(86_400);
+    let mut activity = app_state.active_users.get_all_user_activity().await;
+    let mut result: Vec<UserActivityInfo> = app_state.config.t_api_proxy.load().as_ref()
+        .map(|api_proxy| api_proxy.user.iter()
+            .flat_map(|target_user| target_user.credentials.iter().map(|credentials| {
+                let user_activity = activity.remove(&credentials.username);
+                UserActivityInfo {
+                    username: credentials.username.clone(),
+                    target: target_user.target.clone(),
+                    dormant: user_activity.as_ref().is_none_or(|a| a.is_dormant(now, dormant_secs)),
+                    activity: user_activity,
+                }
+            }).collect::<Vec<_>>())
+            .filter(|info| !query.dormant || info.dormant)
+            .collect())
+        .unwrap_or_default();
+    result.sort_by(|a, b| a.username.cmp(&b.username));
+    axum::Json(result).into_response()
+}
@@ -1,4 +1,7 @@
 use crate::api::api_utils::{get_user_target_by_username, get_username_from_auth_header};
+use crate::api::endpoints::provisioning_api::provisioning_response;
+use crate::api::endpoints::qr_code_api::{qr_code_url_response, QrCodeRequest};
+use crate::api::endpoints::share_link_api::{share_link_url_response, ShareLinkRequest};
 use crate::api::model::app_state::AppState;
 use crate::auth::validator_user;
 use crate::model::{Config, ConfigTarget};
@@ -148,6 +151,51 @@ async fn playlist_bouquet(
         .into_response()
 }
 
+async fn user_qr_code_url(
+    AuthBearer(token): AuthBearer,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+    axum::extract::Json(mut request): axum::extract::Json<QrCodeRequest>,
+) -> impl axum::response::IntoResponse + Send {
+    if let Some(username) = get_username_from_auth_header(&token, &app_state) {
+        if let Some((user, _target)) = get_user_target_by_username(username.as_str(), &app_state) {
+            if user.permission_denied(&app_state) {
+                return axum::http::StatusCode::FORBIDDEN.into_response();
+            }
+            request.username = username;
+            return qr_code_url_response(&app_state, &request);
+        }
+    }
+    axum::http::StatusCode::BAD_REQUEST.into_response()
+}
+
+async fn user_provisioning(
+    AuthBearer(token): AuthBearer,
+    axum::extract::Path(profile): axum::extract::Path<String>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    if let Some(username) = get_username_from_auth_header(&token, &app_state) {
+        if let Some((user, _target)) = get_user_target_by_username(username.as_str(), &app_state) {
+            if user.permission_denied(&app_state) {
+                return axum::http::StatusCode::FORBIDDEN.into_response();
+            }
+            return provisioning_response(&app_state, &user, &profile);
+        }
+    }
+    axum::http::StatusCode::BAD_REQUEST.into_response()
+}
+
+async fn user_share_link_url(
+    AuthBearer(token): AuthBearer,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+    axum::extract::Json(mut request): axum::extract::Json<ShareLinkRequest>,
+) -> impl axum::response::IntoResponse + Send {
+    if let Some(username) = get_username_from_auth_header(&token, &app_state) {
+        request.username = username;
+        return share_link_url_response(&app_state, &request).await;
+    }
+    axum::http::StatusCode::BAD_REQUEST.into_response()
+}
+
 pub fn user_api_register(app_state: Arc<AppState>) -> axum::Router<Arc<AppState>> {
     axum::Router::new()
         .nest(
@@ -156,6 +204,9 @@ pub fn user_api_register(app_state: Arc<AppState>) -> axum::Router<Arc<AppState>
                 .route("/playlist/categories", axum::routing::get(playlist_categories))
                 .route("/playlist/bouquet", axum::routing::get(playlist_bouquet))
                 .route("/playlist/bouquet", axum::routing::post(save_playlist_bouquet))
+                .route("/qrcode", axum::routing::post(user_qr_code_url))
+                .route("/share_link", axum::routing::post(user_share_link_url))
+                .route("/provisioning/{profile}", axum::routing::get(user_provisioning))
                 .route_layer(axum::middleware::from_fn_with_state(app_state, validator_user))
         )
 
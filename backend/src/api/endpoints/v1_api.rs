@@ -1,9 +1,23 @@
-use crate::api::endpoints::api_playlist_utils::{get_playlist, get_playlist_for_target};
+use crate::api::endpoints::api_playlist_utils::{get_playlist, get_playlist_comparison, get_playlist_for_target};
 use crate::api::endpoints::download_api;
+use crate::api::endpoints::group_api::group_api_register;
+use crate::api::endpoints::log_api;
+use crate::api::endpoints::maintenance_api;
+use crate::api::endpoints::mapper_api;
+use crate::api::endpoints::refresh_history_api;
+use crate::api::endpoints::refresh_progress_api;
+use crate::api::endpoints::epg_match_api;
+use crate::api::endpoints::search_api;
+use crate::api::endpoints::recording_api;
+use crate::api::endpoints::user_activity_api;
+use crate::api::endpoints::provisioning_api;
+use crate::api::endpoints::qr_code_api;
+use crate::api::endpoints::share_link_api;
 use crate::api::endpoints::user_api::user_api_register;
 use crate::api::model::app_state::AppState;
 use crate::api::model::config::{ServerConfig, ServerInputConfig, ServerSourceConfig, ServerTargetConfig};
-use crate::api::model::request::{PlaylistRequest, PlaylistRequestType};
+use crate::api::model::request::{PlaylistComparisonRequest, PlaylistRequest, PlaylistRequestType};
+use crate::messaging::send_welcome_email;
 use crate::auth::create_access_token;
 use crate::auth::validator_admin;
 use shared::error::TuliproxError;
@@ -12,12 +26,14 @@ use crate::model::XtreamPlaylistItem;
 use crate::model::{Config, ConfigInput, ConfigInputOptions, ConfigSource,  InputType};
 use crate::model::{ApiProxyConfig, ApiProxyServerInfo, ProxyUserCredentials, TargetUser};
 use crate::processing::processor::playlist;
+use crate::repository::refresh_history_repository::load_target_freshness;
+use crate::repository::store_version::get_reprocess_required_stores;
 use crate::repository::user_repository::store_api_user;
 use crate::utils::ip_checker::get_ips;
 use crate::utils::request::sanitize_sensitive_info;
 use crate::{utils, VERSION};
 use axum::response::IntoResponse;
-use log::error;
+use log::{error, info};
 use serde_json::json;
 use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
@@ -71,6 +87,9 @@ async fn save_config_api_proxy_user(
     }
 
     if let Some(old_api_proxy) =  app_state.config.t_api_proxy.load().clone() {
+        let existing_usernames: HashSet<String> = old_api_proxy.user.iter()
+            .flat_map(|target_user| &target_user.credentials)
+            .map(|credential| credential.username.clone()).collect();
         let mut api_proxy = (*old_api_proxy).clone();
         api_proxy.user = users;
         api_proxy.user.iter_mut().flat_map(|t| &mut t.credentials).for_each(ProxyUserCredentials::prepare);
@@ -87,6 +106,11 @@ async fn save_config_api_proxy_user(
                 return (axum::http::StatusCode::INTERNAL_SERVER_ERROR, axum::Json(json!({"error": err.to_string()}))).into_response();
             }
         }
+
+        new_api_proxy.user.iter()
+            .flat_map(|target_user| &target_user.credentials)
+            .filter(|credential| !existing_usernames.contains(&credential.username))
+            .for_each(|credential| send_welcome_email(&app_state.config, credential));
     }
     axum::http::StatusCode::OK.into_response()
 }
@@ -143,8 +167,9 @@ async fn playlist_update(
             axum::http::StatusCode::OK.into_response()
         }
         Err(err) => {
-            error!("Failed playlist update {}", sanitize_sensitive_info(err.to_string().as_str()));
-            (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": err.to_string()}))).into_response()
+            let message = sanitize_sensitive_info(err.to_string().as_str()).into_owned();
+            error!("Failed playlist update {message}");
+            (axum::http::StatusCode::BAD_REQUEST, axum::Json(json!({"error": message}))).into_response()
         }
     }
 }
@@ -162,6 +187,7 @@ fn create_config_input_for_m3u(url: &str) -> ConfigInput {
             xtream_skip_series: false,
             xtream_live_stream_without_extension: false,
             xtream_live_stream_use_prefix: true,
+            xtream_epg_fallback: false,
         }),
         ..Default::default()
     }
@@ -182,6 +208,7 @@ fn create_config_input_for_xtream(username: &str, password: &str, host: &str) ->
             xtream_skip_series: false,
             xtream_live_stream_without_extension: false,
             xtream_live_stream_use_prefix: true,
+            xtream_epg_fallback: false,
         }),
         ..Default::default()
     }
@@ -226,6 +253,16 @@ async fn playlist_content(
     }
 }
 
+async fn playlist_compare(
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+    axum::extract::Json(compare_req): axum::extract::Json<PlaylistComparisonRequest>,
+) -> impl IntoResponse + Send {
+    get_playlist_comparison(Arc::clone(&app_state.http_client),
+                            app_state.config.get_input_by_id(compare_req.left),
+                            app_state.config.get_input_by_id(compare_req.right),
+                            &app_state.config).await.into_response()
+}
+
 async fn playlist_webplayer(
     axum::extract::Path(target_id): axum::extract::Path<u32>,
     axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
@@ -238,6 +275,31 @@ async fn playlist_webplayer(
     format!("{base_url}/token/{access_token}/{target_id}/{}/{}", playlist_item.xtream_cluster.as_stream_type(), playlist_item.virtual_id).into_response()
 }
 
+async fn sessions(
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    axum::Json(app_state.active_users.get_active_user_info().await).into_response()
+}
+
+async fn sessions_health(
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    axum::Json(app_state.stream_health.get_stream_health_info()).into_response()
+}
+
+pub(in crate::api) async fn sessions_close(
+    axum::extract::Path((username, token)): axum::extract::Path<(String, String)>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl axum::response::IntoResponse + Send {
+    let streams = app_state.stream_health.close_session(&username, &token);
+    let removed = app_state.active_users.remove_user_session(&username, &token).await;
+    if streams == 0 && !removed {
+        return axum::http::StatusCode::NOT_FOUND.into_response();
+    }
+    info!("Closed session of user {username} with {streams} streams");
+    axum::Json(json!({"streams": streams})).into_response()
+}
+
 async fn config(
     axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
 ) -> impl axum::response::IntoResponse + Send {
@@ -287,6 +349,15 @@ async fn config(
         sources: config.sources.sources.iter().map(map_source).collect(),
         proxy: config.proxy.clone(),
         ipcheck: config.ipcheck.clone(),
+        portal: config.portal.clone(),
+        provider_cache: config.provider_cache.clone(),
+        metrics_export: config.metrics_export.clone(),
+        replication: config.replication.clone(),
+        backup: config.backup.clone(),
+        user_cleanup: config.user_cleanup.clone(),
+        client_error: config.client_error.clone(),
+        recording: config.recording.clone(),
+        crash_report: config.crash_report.clone(),
         api_proxy: utils::read_api_proxy(&app_state.config, false),
     };
 
@@ -344,6 +415,16 @@ async fn create_status_check(app_state: &Arc<AppState>) -> StatusCheck {
 
     let active_provider_connections = app_state.active_provider.active_connections().await.map(|c| c.into_iter().collect::<BTreeMap<_, _>>());
 
+    let mut stale_targets = BTreeMap::new();
+    for target in app_state.config.sources.sources.iter().flat_map(|source| source.targets.iter()) {
+        let freshness = load_target_freshness(&app_state.config, &target.name).await;
+        if freshness.is_stale() {
+            stale_targets.insert(target.name.clone(), freshness);
+        }
+    }
+
+    let reprocess_required = get_reprocess_required_stores();
+
     StatusCheck {
         status: "ok".to_string(),
         version: VERSION.to_string(),
@@ -353,6 +434,8 @@ async fn create_status_check(app_state: &Arc<AppState>) -> StatusCheck {
         active_users,
         active_user_connections,
         active_provider_connections,
+        stale_targets: (!stale_targets.is_empty()).then_some(stale_targets),
+        reprocess_required: (!reprocess_required.is_empty()).then_some(reprocess_required),
         cache,
     }
 }
@@ -385,15 +468,38 @@ pub fn v1_api_register(web_auth_enabled: bool, app_state: Arc<AppState>, web_ui_
     let mut router = axum::Router::new();
     router = router
         .route("/status", axum::routing::get(status))
+        .route("/sessions", axum::routing::get(sessions))
+        .route("/sessions/health", axum::routing::get(sessions_health))
+        .route("/sessions/{username}/{token}", axum::routing::delete(sessions_close))
+        .route("/sessions/activity", axum::routing::get(user_activity_api::user_activity))
+        .route("/logs", axum::routing::get(log_api::logs))
+        .route("/logs/stream", axum::routing::get(log_api::log_stream))
         .route("/config", axum::routing::get(config))
         .route("/config/main", axum::routing::post(save_config_main))
         .route("/config/user", axum::routing::post(save_config_api_proxy_user))
         .route("/config/apiproxy", axum::routing::post(save_config_api_proxy_config))
         .route("/playlist/webplayer/{target_id}", axum::routing::post(playlist_webplayer))
+        .route("/qrcode", axum::routing::post(qr_code_api::qr_code_url))
+        .route("/share_link", axum::routing::post(share_link_api::share_link_url))
+        .route("/provisioning/{profile}", axum::routing::get(provisioning_api::provisioning))
         .route("/playlist/update", axum::routing::post(playlist_update))
         .route("/playlist", axum::routing::post(playlist_content))
+        .route("/playlist/compare", axum::routing::post(playlist_compare))
+        .route("/playlist/history/{target_id}", axum::routing::get(refresh_history_api::refresh_history))
+        .route("/playlist/progress", axum::routing::get(refresh_progress_api::refresh_progress))
+        .route("/playlist/progress/stream", axum::routing::get(refresh_progress_api::refresh_progress_stream))
+        .route("/playlist/epg_match/{target_id}", axum::routing::get(epg_match_api::epg_match_report))
+        .route("/mapper/dryrun", axum::routing::post(mapper_api::mapper_dry_run))
+        .route("/search", axum::routing::get(search_api::channel_search))
+        .route("/maintenance", axum::routing::get(maintenance_api::maintenance_status)
+            .post(maintenance_api::maintenance_start)
+            .delete(maintenance_api::maintenance_stop))
+        .route("/recordings", axum::routing::get(recording_api::recording_list)
+            .post(recording_api::recording_schedule))
+        .route("/recordings/{id}", axum::routing::delete(recording_api::recording_delete))
         .route("/file/download", axum::routing::post(download_api::queue_download_file))
         .route("/file/download/info", axum::routing::get(download_api::download_file_info));
+    router = group_api_register(router);
     if app_state.config.ipcheck.is_some() {
         router = router.route("/ipinfo", axum::routing::get(ipinfo));
     }
@@ -1,23 +1,29 @@
 use axum::response::IntoResponse;
-use chrono::{Duration, NaiveDateTime, TimeDelta};
+use chrono::{DateTime, Duration, FixedOffset};
 use flate2::write::GzEncoder;
 use flate2::Compression;
 use log::{error, trace};
+use quick_xml::encoding::Decoder;
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::{Reader, Writer};
+use shared::model::PlaylistItemType;
+use std::collections::HashMap;
 use std::fs::File;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use crate::api::api_utils::{get_user_target, serve_file};
+use crate::api::api_utils::{client_error_response, get_user_target, get_user_target_by_credentials, resource_response, serve_file, ClientErrorKind};
 use crate::api::model::app_state::AppState;
 use crate::api::model::request::UserApiRequest;
-use crate::model::{ConfigTarget, ProxyUserCredentials, TargetOutput};
+use crate::model::{ConfigTarget, EpgJson, ProxyUserCredentials, TargetOutput};
 use crate::model::{Config};
+use crate::repository::epg_repository::{epg_get_json_file_path, load_epg_icons};
+use crate::repository::storage_const;
 use crate::repository::m3u_repository::m3u_get_epg_file_path;
 use crate::repository::storage::get_target_storage_path;
 use crate::repository::xtream_repository::{xtream_get_epg_file_path, xtream_get_storage_path};
 use crate::utils;
+use crate::utils::{parse_timeshift, time_correct};
 
 pub fn get_empty_epg_response() -> impl axum::response::IntoResponse + Send {
     axum::response::Response::builder()
@@ -27,21 +33,10 @@ pub fn get_empty_epg_response() -> impl axum::response::IntoResponse + Send {
         .unwrap()
 }
 
-fn time_correct(date_time: &str, correction: &TimeDelta) -> String {
-    // Split the dateTime string into date and time parts
-    let date_time_split: Vec<&str> = date_time.split(' ').collect();
-    if date_time_split.len() != 2 {
-        return date_time.to_string();
-    }
-
-    // Parse the datetime string
-    NaiveDateTime::parse_from_str(date_time_split[0], "%Y%m%d%H%M%S").map_or_else(|_| date_time.to_string(), |native_dt| {
-        let corrected_dt = native_dt + *correction;
-        // Format the corrected datetime back to string
-        let formatted_dt = corrected_dt.format("%Y%m%d%H%M%S").to_string();
-        let result = format!("{} {}", formatted_dt, date_time_split[1]);
-        result
-    })
+// Converts `20250601203000 +0000` into the user timezone, the absolute time is kept.
+fn time_convert(date_time: &str, offset: FixedOffset) -> String {
+    DateTime::parse_from_str(date_time.trim(), "%Y%m%d%H%M%S %z")
+        .map_or_else(|_| date_time.to_string(), |dt| dt.with_timezone(&offset).format("%Y%m%d%H%M%S %z").to_string())
 }
 
 fn get_epg_path_for_target_of_type(target_name: &str, epg_path: PathBuf) -> Option<PathBuf> {
@@ -75,29 +70,74 @@ fn get_epg_path_for_target(config: &Config, target: &ConfigTarget) -> Option<Pat
     None
 }
 
-// `-2:30`(-2h30m), `1:45` (1h45m), `+0:15` (15m), `2` (2h), `:30` (30m), `:3` (3m), `2:` (3h)
-fn parse_timeshift(time_shift: Option<&String>) -> Option<i32> {
-    time_shift.and_then(|offset| {
-        let sign_factor = if offset.starts_with('-') { -1 } else { 1 };
-        let offset = offset.trim_start_matches(&['-', '+'][..]);
+// The override icons are served through the resource cache for users in reverse proxy mode.
+async fn get_epg_icon_urls(config: &Config, user: &ProxyUserCredentials, target: &ConfigTarget) -> HashMap<String, String> {
+    if !user.proxy.is_reverse(PlaylistItemType::Live) || target.is_force_redirect(PlaylistItemType::Live)
+        || !config.is_reverse_proxy_resource_rewrite_enabled() {
+        return HashMap::new();
+    }
+    let base_url = config.get_user_server_info(user).get_base_url();
+    load_epg_icons(config, &target.name).await.into_iter()
+        .map(|(hash, url)| (url, format!("{base_url}/{}/{}/{}/{hash}", storage_const::EPG_RESOURCE_PATH, user.username, user.password)))
+        .collect()
+}
 
-        let parts: Vec<&str> = offset.split(':').collect();
-        let hours: i32 = parts.first().and_then(|h| h.parse().ok()).unwrap_or(0);
-        let minutes: i32 = parts.get(1).and_then(|m| m.parse().ok()).unwrap_or(0);
+pub(in crate::api) fn get_epg_json_path_for_target(config: &Config, target: &ConfigTarget) -> Option<PathBuf> {
+    // the first output with the option epg_json serves
+    for output in &target.output {
+        let epg_path = match output {
+            TargetOutput::Xtream(xtream_output) if xtream_output.epg_json => {
+                xtream_get_storage_path(config, &target.name).map(|storage_path| xtream_get_epg_file_path(&storage_path))
+            }
+            TargetOutput::M3u(m3u_output) if m3u_output.epg_json => {
+                get_target_storage_path(config, &target.name).map(|target_path| m3u_get_epg_file_path(&target_path))
+            }
+            _ => None,
+        };
+        if let Some(epg_path) = epg_path {
+            return get_epg_path_for_target_of_type(&target.name, epg_get_json_file_path(&epg_path));
+        }
+    }
+    None
+}
 
-        let total_minutes = hours * 60 + minutes;
-        (total_minutes > 0).then_some(sign_factor * total_minutes)
-    })
+async fn serve_epg_json(epg_path: &Path, user: &ProxyUserCredentials, icon_urls: &HashMap<String, String>) -> impl axum::response::IntoResponse + Send {
+    // the times are unix timestamps, only the user timeshift changes them
+    let timeshift = parse_timeshift(user.epg_timeshift.as_ref());
+    if timeshift.is_none() && icon_urls.is_empty() {
+        return serve_file(epg_path, mime::APPLICATION_JSON).await.into_response();
+    }
+    match File::open(epg_path).map_err(|err| err.to_string())
+        .and_then(|file| serde_json::from_reader::<_, EpgJson>(utils::file_reader(file)).map_err(|err| err.to_string())) {
+        Ok(mut epg) => {
+            for channel in &mut epg.channels {
+                if let Some(minutes) = timeshift {
+                    channel.shift(i64::from(minutes) * 60);
+                }
+                if let Some(icon_url) = channel.icon.as_ref().and_then(|icon| icon_urls.get(icon)) {
+                    channel.icon = Some(icon_url.clone());
+                }
+            }
+            axum::Json(epg).into_response()
+        }
+        Err(err) => {
+            error!("Failed to read json epg {}: {err}", epg_path.to_str().unwrap_or("?"));
+            axum::Json(EpgJson::default()).into_response()
+        }
+    }
 }
 
-async fn serve_epg(epg_path: &Path, user: &ProxyUserCredentials) -> impl axum::response::IntoResponse + Send {
+async fn serve_epg(epg_path: &Path, user: &ProxyUserCredentials, icon_urls: &HashMap<String, String>) -> impl axum::response::IntoResponse + Send {
     match File::open(epg_path) {
         Ok(epg_file) => {
-            match parse_timeshift(user.epg_timeshift.as_ref()) {
-                None => serve_file(epg_path, mime::TEXT_XML).await.into_response(),
-                Some(duration) => {
-                    serve_epg_with_timeshift(epg_file, duration).into_response()
+            match (parse_timeshift(user.epg_timeshift.as_ref()), user.get_utc_offset()) {
+                (Some(duration), _) => {
+                    let duration = Duration::minutes(i64::from(duration));
+                    serve_epg_with_rewrite(epg_file, |value| time_correct(value, &duration), icon_urls).into_response()
                 }
+                (None, Some(offset)) => serve_epg_with_rewrite(epg_file, |value| time_convert(value, offset), icon_urls).into_response(),
+                (None, None) if icon_urls.is_empty() => serve_file(epg_path, mime::TEXT_XML).await.into_response(),
+                (None, None) => serve_epg_with_rewrite(epg_file, ToString::to_string, icon_urls).into_response(),
             }
         }
         Err(_) => {
@@ -106,13 +146,27 @@ async fn serve_epg(epg_path: &Path, user: &ProxyUserCredentials) -> impl axum::r
     }
 }
 
-fn serve_epg_with_timeshift(epg_file: File, offset_minutes: i32) -> impl axum::response::IntoResponse + Send {
+fn rewrite_icon_src(elem: &BytesStart, decoder: Decoder, icon_urls: &HashMap<String, String>) -> BytesStart<'static> {
+    let mut icon = BytesStart::new(String::from_utf8_lossy(elem.name().as_ref()).into_owned());
+    for attr in elem.attributes().flatten() {
+        if attr.key.as_ref() == b"src" {
+            if let Some(icon_url) = attr.decode_and_unescape_value(decoder).ok().and_then(|src| icon_urls.get(src.as_ref())) {
+                icon.push_attribute(("src", icon_url.as_str()));
+                continue;
+            }
+        }
+        icon.push_attribute(attr);
+    }
+    icon
+}
+
+// Corrects the programme times and replaces the icons of `icon_urls`.
+fn serve_epg_with_rewrite(epg_file: File, correct: impl Fn(&str) -> String, icon_urls: &HashMap<String, String>) -> impl axum::response::IntoResponse + Send {
     let reader = utils::file_reader(epg_file);
     let encoder = GzEncoder::new(Vec::with_capacity(4096), Compression::default());
     let mut xml_reader = Reader::from_reader(reader);
     let mut xml_writer = Writer::new(encoder);
     let mut buf = Vec::with_capacity(1024);
-    let duration = Duration::minutes(i64::from(offset_minutes));
 
     loop {
         match xml_reader.read_event_into(&mut buf) {
@@ -125,13 +179,13 @@ fn serve_epg_with_timeshift(epg_file: File, offset_minutes: i32) -> impl axum::r
                             let start_value = attr.decode_and_unescape_value(xml_reader.decoder())
                                 .expect("Failed to decode start attribute");
                             // Modify the start attribute value as needed
-                            elem.push_attribute(("start", time_correct(&start_value, &duration).as_str()));
+                            elem.push_attribute(("start", correct(&start_value).as_str()));
                         }
                         Ok(attr) if attr.key.as_ref() == b"stop" => {
                             let stop_value = attr.decode_and_unescape_value(xml_reader.decoder())
                                 .expect("Failed to decode stop attribute");
                             // Modify the stop attribute value as needed
-                            elem.push_attribute(("stop", time_correct(&stop_value, &duration).as_str()));
+                            elem.push_attribute(("stop", correct(&stop_value).as_str()));
                         }
                         Ok(attr) => {
                             // Copy any other attributes as they are
@@ -146,6 +200,12 @@ fn serve_epg_with_timeshift(epg_file: File, offset_minutes: i32) -> impl axum::r
                 // Write the modified start event
                 xml_writer.write_event(Event::Start(elem)).expect("Failed to write event");
             }
+            Ok(Event::Start(ref e)) if e.name().as_ref() == b"icon" && !icon_urls.is_empty() => {
+                xml_writer.write_event(Event::Start(rewrite_icon_src(e, xml_reader.decoder(), icon_urls))).expect("Failed to write event");
+            }
+            Ok(Event::Empty(ref e)) if e.name().as_ref() == b"icon" && !icon_urls.is_empty() => {
+                xml_writer.write_event(Event::Empty(rewrite_icon_src(e, xml_reader.decoder(), icon_urls))).expect("Failed to write event");
+            }
             Ok(Event::Eof) => break, // End of file
             Ok(event) => {
                 // Write any other event as is
@@ -185,11 +245,11 @@ async fn xmltv_api(
     axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
 ) -> impl IntoResponse + Send {
     let Some((user, target)) = get_user_target(&api_req, &app_state) else {
-        return axum::http::StatusCode::FORBIDDEN.into_response();
+        return client_error_response(&app_state, axum::http::StatusCode::FORBIDDEN, ClientErrorKind::Unauthorized);
     };
 
     if user.permission_denied(&app_state) {
-        return axum::http::StatusCode::FORBIDDEN.into_response();
+        return client_error_response(&app_state, axum::http::StatusCode::FORBIDDEN, ClientErrorKind::Expired);
     }
 
     let Some(epg_path) = get_epg_path_for_target(&app_state.config, target) else {
@@ -198,12 +258,57 @@ async fn xmltv_api(
         return get_empty_epg_response().into_response();
     };
 
-    serve_epg(&epg_path, &user).await.into_response()
+    let icon_urls = get_epg_icon_urls(&app_state.config, &user, target).await;
+    serve_epg(&epg_path, &user, &icon_urls).await.into_response()
+}
+
+/// Serves the json guide of the user target, the programme times are shifted by the user `epg_timeshift`.
+///
+/// Returns an empty guide if no output of the target has the option `epg_json`.
+async fn epg_json_api(
+    axum::extract::Query(api_req): axum::extract::Query<UserApiRequest>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl IntoResponse + Send {
+    let Some((user, target)) = get_user_target(&api_req, &app_state) else {
+        return client_error_response(&app_state, axum::http::StatusCode::FORBIDDEN, ClientErrorKind::Unauthorized);
+    };
+
+    if user.permission_denied(&app_state) {
+        return client_error_response(&app_state, axum::http::StatusCode::FORBIDDEN, ClientErrorKind::Expired);
+    }
+
+    match get_epg_json_path_for_target(&app_state.config, target) {
+        Some(epg_path) => {
+            let icon_urls = get_epg_icon_urls(&app_state.config, &user, target).await;
+            serve_epg_json(&epg_path, &user, &icon_urls).await.into_response()
+        }
+        None => axum::Json(EpgJson::default()).into_response(),
+    }
+}
+
+/// Serves an override icon of the guide through the resource cache, the icon is the hash of the original url.
+async fn epg_icon_resource(
+    req_headers: axum::http::HeaderMap,
+    axum::extract::Path((username, password, icon)): axum::extract::Path<(String, String, String)>,
+    axum::extract::Query(api_req): axum::extract::Query<UserApiRequest>,
+    axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
+) -> impl IntoResponse + Send {
+    let Some((user, target)) = get_user_target_by_credentials(&username, &password, &api_req, &app_state) else {
+        return axum::http::StatusCode::FORBIDDEN.into_response();
+    };
+    if user.permission_denied(&app_state) {
+        return axum::http::StatusCode::FORBIDDEN.into_response();
+    }
+    match load_epg_icons(&app_state.config, &target.name).await.get(icon.trim()) {
+        Some(url) => resource_response(&app_state, url, &req_headers, None).await.into_response(),
+        None => axum::http::StatusCode::NOT_FOUND.into_response(),
+    }
 }
 
 /// Registers the XMLTV EPG API routes for handling HTTP GET requests.
 ///
 /// The returned router maps the `/xmltv.php`, `/update/epg.php`, and `/epg` endpoints to the `xmltv_api` handler, enabling XMLTV EPG data retrieval with optional time-shifting and compression.
+/// The `/epg.json` endpoint serves the json guide, `/resource/epg` the override icons of the guide.
 ///
 /// # Examples
 ///
@@ -216,6 +321,8 @@ pub fn xmltv_api_register() -> axum::Router<Arc<AppState>> {
         .route("/xmltv.php", axum::routing::get(xmltv_api))
         .route("/update/epg.php", axum::routing::get(xmltv_api))
         .route("/epg", axum::routing::get(xmltv_api))
+        .route("/epg.json", axum::routing::get(epg_json_api))
+        .route(&format!("/{}/{{username}}/{{password}}/{{icon}}", storage_const::EPG_RESOURCE_PATH), axum::routing::get(epg_icon_resource))
 }
 
 #[cfg(test)]
@@ -240,4 +347,11 @@ mod tests {
         assert_eq!(parse_timeshift(Some(&String::new())), None);
         assert_eq!(parse_timeshift(None), None);
     }
+
+    #[test]
+    fn test_time_convert() {
+        let offset = FixedOffset::east_opt(2 * 3600).unwrap();
+        assert_eq!(time_convert("20250601203000 +0000", offset), "20250601223000 +0200");
+        assert_eq!(time_convert("20250601203000", offset), "20250601203000");
+    }
 }
\ No newline at end of file
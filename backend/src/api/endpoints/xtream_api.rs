@@ -1,7 +1,7 @@
 // https://github.com/tellytv/go.xtream-codes/blob/master/structs.go
 
 use crate::api::api_utils;
-use crate::api::api_utils::{force_provider_stream_response, get_user_target, get_user_target_by_credentials, is_seek_request, redirect_response, resource_response, separate_number_and_remainder, serve_file, stream_response, RedirectParams};
+use crate::api::api_utils::{force_provider_stream_response, get_range_start_bytes, get_user_target, get_user_target_by_credentials, is_seek_request, is_stream_share_enabled, redirect_response, resource_response, separate_number_and_remainder, serve_file, stream_response, update_user_activity, RedirectParams};
 use crate::api::api_utils::{redirect, try_option_bad_request, try_result_bad_request};
 use crate::api::endpoints::hls_api::handle_hls_stream_request;
 use crate::api::endpoints::xmltv_api::get_empty_epg_response;
@@ -9,23 +9,24 @@ use crate::api::model::app_state::AppState;
 use crate::api::model::request::UserApiRequest;
 use crate::api::model::streams::provider_stream::{create_custom_video_stream_response, CustomVideoStreamType};
 use crate::api::model::xtream::XtreamAuthorizationResponse;
-use crate::model::{get_backdrop_path_value, ConfigTarget, XtreamPlaylistItem};
+use crate::model::{get_backdrop_path_value, ConfigTarget, XtreamMappingOptions, XtreamPlaylistItem};
 use crate::model::{Config, ConfigInput};
 use crate::model::{ProxyUserCredentials};
+use crate::repository::group_override_repository::load_group_overrides;
 use crate::repository::playlist_repository::get_target_id_mapping;
 use crate::repository::storage::{get_target_storage_path};
 use crate::repository::{storage_const, user_repository, xtream_repository};
 use shared::error::create_tuliprox_error_result;
 use shared::error::info_err;
 use shared::error::{str_to_io_error, TuliproxError, TuliproxErrorKind};
-use crate::utils::{generate_playlist_uuid, hex_encode};
+use crate::utils::{convert_timeshift_start, format_catchup_time, generate_playlist_uuid, get_server_utc_offset, hex_encode};
 use crate::utils::get_u32_from_serde_value;
 use crate::utils::request::{extract_extension_from_url, sanitize_sensitive_info};
 use crate::utils::trace_if_enabled;
 use crate::utils::xtream::create_vod_info_from_item;
 use shared::utils::HLS_EXT;
 use crate::utils::{request, xtream};
-use crate::auth::Fingerprint;
+use crate::auth::{ClientIp, Fingerprint};
 use axum::http::{HeaderMap, StatusCode};
 use axum::response::IntoResponse;
 use bytes::Bytes;
@@ -33,7 +34,7 @@ use futures::stream::{self, StreamExt};
 use futures::Stream;
 use log::{debug, error, warn};
 use serde::{Deserialize, Serialize};
-use serde_json::{Map, Value};
+use serde_json::{json, Map, Value};
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Display, Formatter};
@@ -174,14 +175,26 @@ async fn get_user_info(user: &ProxyUserCredentials, app_state: &AppState) -> Xtr
     XtreamAuthorizationResponse::new(&server_info, user, active_connections, app_state.config.user_access_control)
 }
 
-async fn xtream_player_api_stream(
+// The timeshift start is sent in the user timezone, the provider expects the server timezone.
+fn get_user_timeshift_action_path(config: &Config, user: &ProxyUserCredentials, action_path: &str) -> Option<String> {
+    let user_offset = user.get_utc_offset()?;
+    let server_offset = get_server_utc_offset(&config.get_user_server_info(user).timezone);
+    let (duration, start) = action_path.split_once('/')?;
+    let start = convert_timeshift_start(start, user_offset, server_offset)?;
+    Some(format!("{duration}/{start}"))
+}
+
+pub(in crate::api) async fn xtream_player_api_stream(
     fingerprint: &str,
+    client_ip: ClientIp,
     req_headers: &HeaderMap,
     app_state: &Arc<AppState>,
     api_req: &UserApiRequest,
     stream_req: ApiStreamRequest<'_>,
 ) -> impl IntoResponse + Send {
-    let (user, target) = try_option_bad_request!(get_user_target_by_credentials(stream_req.username, stream_req.password, api_req, app_state), false, format!("Could not find any user {}", stream_req.username));
+    let (mut user, target) = try_option_bad_request!(get_user_target_by_credentials(stream_req.username, stream_req.password, api_req, app_state), false, format!("Could not find any user {}", stream_req.username));
+    user.t_ipv6_client = client_ip.is_ipv6();
+    update_user_activity(app_state, &user, client_ip, req_headers).await;
     if user.permission_denied(app_state) {
         return create_custom_video_stream_response(&app_state.config, CustomVideoStreamType::UserAccountExpired).into_response();
     }
@@ -199,6 +212,13 @@ async fn xtream_player_api_stream(
     let cluster = pli.xtream_cluster;
 
     let item_type = if stream_req.context == ApiStreamContext::Timeshift { PlaylistItemType::Catchup } else  { pli.item_type };
+    let timeshift_action_path = if stream_req.context == ApiStreamContext::Timeshift { get_user_timeshift_action_path(&app_state.config, &user, stream_req.action_path) } else { None };
+    let action_path = timeshift_action_path.as_deref().unwrap_or(stream_req.action_path);
+
+    if cluster != XtreamCluster::Live && stream_req.context != ApiStreamContext::Timeshift {
+        // the byte offset of a range request is the playback position for clients which don't report it
+        app_state.active_users.update_playback_position(&user.username, virtual_id, cluster, None, get_range_start_bytes(req_headers)).await;
+    }
 
     let session_key = format!("{fingerprint}{virtual_id}");
     let user_session = app_state.active_users.get_user_session(&user.username, &session_key).await;
@@ -214,7 +234,7 @@ async fn xtream_player_api_stream(
 
         if session.virtual_id == virtual_id && is_seek_request(cluster, req_headers).await {
             // partial request means we are in reverse proxy mode, seek happened
-            return force_provider_stream_response(app_state, session, item_type, req_headers, input, &user).await.into_response();
+            return force_provider_stream_response(app_state, session, item_type, req_headers, input, target, &user).await.into_response();
         }
 
         session.stream_url.as_str()
@@ -222,7 +242,7 @@ async fn xtream_player_api_stream(
         pli.url.as_str()
     };
 
-    let connection_permission = user.connection_permission(app_state).await;
+    let connection_permission = user.connection_permission(app_state, fingerprint).await;
     if connection_permission == UserConnectionPermission::Exhausted {
         return create_custom_video_stream_response(&app_state.config, CustomVideoStreamType::UserConnectionsExhausted).into_response();
     }
@@ -239,7 +259,7 @@ async fn xtream_player_api_stream(
         user: &user,
         stream_ext: stream_ext.as_deref(),
         req_context: context,
-        action_path: stream_req.action_path,
+        action_path,
     };
     if let Some(response) = redirect_response(app_state, &redirect_params).await {
         return response.into_response();
@@ -248,10 +268,10 @@ async fn xtream_player_api_stream(
     let extension = stream_ext.unwrap_or_else(
         || extract_extension_from_url(&pli.url).map_or_else(String::new, std::string::ToString::to_string));
 
-    let query_path = if stream_req.action_path.is_empty() {
+    let query_path = if action_path.is_empty() {
         format!("{}{extension}", pli.provider_id)
     } else {
-        format!("{}/{}{extension}", stream_req.action_path, pli.provider_id)
+        format!("{action_path}/{}{extension}", pli.provider_id)
     };
 
     let stream_url = try_option_bad_request!(get_xtream_player_api_stream_url(input, stream_req.context, &query_path, session_url),
@@ -260,7 +280,7 @@ async fn xtream_player_api_stream(
     let is_hls_request = item_type == PlaylistItemType::LiveHls || item_type == PlaylistItemType::LiveDash || extension == HLS_EXT;
     // Reverse proxy mode
     if is_hls_request {
-        return handle_hls_stream_request(fingerprint, app_state, &user, user_session.as_ref(), &stream_url, pli.virtual_id, input, connection_permission).await.into_response();
+        return handle_hls_stream_request(fingerprint, app_state, &user, user_session.as_ref(), &stream_url, pli.virtual_id, input, connection_permission, is_stream_share_enabled(PlaylistItemType::LiveHls, target)).await.into_response();
     }
 
     stream_response(app_state, session_key.as_str(), pli.virtual_id, item_type, &stream_url, req_headers, input, target, &user, connection_permission).await.into_response()
@@ -304,13 +324,20 @@ async fn xtream_player_api_stream_with_token(
             status: None,
             ui_enabled: false,
             comment: None,
+            locale: None,
+            timezone: None,
+            email: None,
+            devices: None,
+            package: None,
+            t_device: None,
+            t_ipv6_client: false,
         };
 
         // TODO how should we use fixed provider for hls in multi provider config?
 
         // Reverse proxy mode
         if is_hls_request {
-            return handle_hls_stream_request(fingerprint, app_state, &user, None, &pli.url, pli.virtual_id, input, UserConnectionPermission::Allowed).await.into_response();
+            return handle_hls_stream_request(fingerprint, app_state, &user, None, &pli.url, pli.virtual_id, input, UserConnectionPermission::Allowed, is_stream_share_enabled(PlaylistItemType::LiveHls, target)).await.into_response();
         }
 
         let extension = stream_ext.unwrap_or_else(
@@ -504,6 +531,7 @@ macro_rules! create_xtream_player_api_stream {
     ($fn_name:ident, $context:expr) => {
         async fn $fn_name(
             Fingerprint(fingerprint): Fingerprint,
+            client_ip: ClientIp,
             req_headers: HeaderMap,
             axum::extract::Path((username, password, stream_id)): axum::extract::Path<(String, String, String)>,
             axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
@@ -511,6 +539,7 @@ macro_rules! create_xtream_player_api_stream {
         ) ->  impl IntoResponse + Send {
             xtream_player_api_stream(
                 &fingerprint,
+                client_ip,
                 &req_headers,
                 &app_state,
                 &api_req,
@@ -563,6 +592,7 @@ struct XtreamApiTimeShiftRequest {
 
 async fn xtream_player_api_timeshift_stream(
     Fingerprint(fingerprint): Fingerprint,
+    client_ip: ClientIp,
     req_headers: HeaderMap,
     axum::extract::Query(mut api_req): axum::extract::Query<UserApiRequest>,
     axum::extract::Path(timeshift_request): axum::extract::Path<XtreamApiTimeShiftRequest>,
@@ -580,11 +610,12 @@ async fn xtream_player_api_timeshift_stream(
     api_req.password = password.to_string();
     api_req.stream_id = stream_id.to_string();
 
-    xtream_player_api_stream(&fingerprint, &req_headers, &app_state, &api_req,  ApiStreamRequest::from(ApiStreamContext::Timeshift, &username, &password, &stream_id, &action_path), /*&addr*/).await.into_response()
+    xtream_player_api_stream(&fingerprint, client_ip, &req_headers, &app_state, &api_req,  ApiStreamRequest::from(ApiStreamContext::Timeshift, &username, &password, &stream_id, &action_path), /*&addr*/).await.into_response()
 }
 
 async fn xtream_player_api_timeshift_query_stream(
     Fingerprint(fingerprint): Fingerprint,
+    client_ip: ClientIp,
     req_headers: HeaderMap,
     axum::extract::Query(api_query_req): axum::extract::Query<UserApiRequest>,
     axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
@@ -602,7 +633,7 @@ async fn xtream_player_api_timeshift_query_stream(
         // }
         // xtream_player_api_stream(&req_headers, &api_query_req, &app_state, ApiStreamRequest::from_access_token(ApiStreamContext::Timeshift, token, stream_id, &action_path)/*, &addr*/).await.into_response()
     }
-    xtream_player_api_stream(&fingerprint, &req_headers, &app_state, &api_query_req, ApiStreamRequest::from(ApiStreamContext::Timeshift, username, password, stream_id, &action_path)).await.into_response()
+    xtream_player_api_stream(&fingerprint, client_ip, &req_headers, &app_state, &api_query_req, ApiStreamRequest::from(ApiStreamContext::Timeshift, username, password, stream_id, &action_path)).await.into_response()
 }
 
 
@@ -698,7 +729,17 @@ async fn xtream_get_short_epg(app_state: &AppState, user: &ProxyUserCredentials,
     get_empty_epg_response().into_response()
 }
 
-async fn xtream_player_api_handle_content_action(config: &Config, target_name: &str, action: &str, category_id: Option<u32>, user: &ProxyUserCredentials) -> Option<impl IntoResponse> {
+fn translate_categories(dictionary: &HashMap<String, String>, categories: &mut [Value]) {
+    for category in categories {
+        let translated = category.get(crate::model::XC_TAG_CATEGORY_NAME).and_then(Value::as_str).and_then(|name| dictionary.get(name));
+        if let Some(name) = translated {
+            category[crate::model::XC_TAG_CATEGORY_NAME] = Value::String(name.clone());
+        }
+    }
+}
+
+async fn xtream_player_api_handle_content_action(config: &Config, target: &ConfigTarget, action: &str, category_id: Option<u32>, user: &ProxyUserCredentials) -> Option<impl IntoResponse> {
+    let target_name = target.name.as_str();
     if let Ok((path, content)) = match action {
         crate::model::XC_ACTION_GET_LIVE_CATEGORIES => xtream_repository::xtream_get_collection_path(config, target_name, storage_const::COL_CAT_LIVE),
         crate::model::XC_ACTION_GET_VOD_CATEGORIES => xtream_repository::xtream_get_collection_path(config, target_name, storage_const::COL_CAT_VOD),
@@ -713,6 +754,39 @@ async fn xtream_player_api_handle_content_action(config: &Config, target_name: &
                 crate::model::XC_ACTION_GET_SERIES_CATEGORIES => user_repository::user_get_bouquet_filter(config, &user.username, category_id, TargetType::Xtream, XtreamCluster::Series).await,
                 _ => None
             };
+            let cluster = match action {
+                crate::model::XC_ACTION_GET_VOD_CATEGORIES => XtreamCluster::Video,
+                crate::model::XC_ACTION_GET_SERIES_CATEGORIES => XtreamCluster::Series,
+                _ => XtreamCluster::Live,
+            };
+            let overrides = load_group_overrides(config, target_name).await.filter(|o| o.has_cluster(cluster));
+            let continue_watching = cluster == XtreamCluster::Video && target.get_xtream_output().is_some_and(|o| o.continue_watching);
+            let dictionary = target.get_locale_dictionary(user.locale.as_deref());
+            let api_proxy = config.t_api_proxy.load();
+            let package = user.package.as_deref()
+                .and_then(|name| api_proxy.as_ref().and_then(|api_proxy| api_proxy.get_user_package(name)))
+                .filter(|package| package.get_category_order(cluster).is_some());
+            if overrides.is_some() || continue_watching || dictionary.is_some() || package.is_some() {
+                let query = filter.map_or_else(HashMap::new, |flt| HashMap::from([(crate::model::XC_TAG_CATEGORY_ID, flt)]));
+                let mut categories = crate::utils::json_filter_file(&file_path, &query);
+                if let Some(overrides) = overrides {
+                    categories = overrides.apply_to_categories(cluster, categories);
+                }
+                if let Some(package) = package {
+                    package.sort_categories(cluster, &mut categories);
+                }
+                if continue_watching {
+                    categories.insert(0, json!({
+                        crate::model::XC_TAG_CATEGORY_ID: crate::model::XC_CONTINUE_WATCHING_CATEGORY_ID.to_string(),
+                        crate::model::XC_TAG_CATEGORY_NAME: crate::model::XC_CONTINUE_WATCHING_CATEGORY_NAME,
+                        crate::model::XC_TAG_PARENT_ID: 0,
+                    }));
+                }
+                if let Some(dictionary) = dictionary {
+                    translate_categories(dictionary, &mut categories);
+                }
+                return Some(axum::Json(categories).into_response());
+            }
             if let Some(flt) = filter {
                 return Some(serve_query(&file_path, &HashMap::from([(crate::model::XC_TAG_CATEGORY_ID, flt)])).into_response());
             }
@@ -727,7 +801,15 @@ async fn xtream_player_api_handle_content_action(config: &Config, target_name: &
     None
 }
 
-async fn xtream_get_catchup_response(app_state: &AppState, target: &ConfigTarget, stream_id: &str, start: &str, end: &str) -> impl IntoResponse + Send {
+fn get_timestamp_field(item: &Map<String, Value>, field: &str) -> Option<i64> {
+    match item.get(field)? {
+        Value::Number(value) => value.as_i64(),
+        Value::String(value) => value.parse().ok(),
+        _ => None,
+    }
+}
+
+async fn xtream_get_catchup_response(app_state: &AppState, user: &ProxyUserCredentials, target: &ConfigTarget, stream_id: &str, start: &str, end: &str) -> impl IntoResponse + Send {
     let virtual_id: u32 = try_result_bad_request!(FromStr::from_str(stream_id));
     let (pli, _) = try_result_bad_request!(xtream_repository::xtream_get_item_for_stream_id(virtual_id, &app_state.config, target, Some(XtreamCluster::Live)));
     let input = try_option_bad_request!(app_state.config.get_input_by_name(pli.input_name.as_str()));
@@ -738,7 +820,15 @@ async fn xtream_get_catchup_response(app_state: &AppState, target: &ConfigTarget
     let epg_listings = try_option_bad_request!(doc.get_mut(crate::model::XC_TAG_EPG_LISTINGS).and_then(Value::as_array_mut));
     let target_path = try_option_bad_request!(get_target_storage_path(&app_state.config, target.name.as_str()));
     let (mut target_id_mapping, file_lock) = get_target_id_mapping(&app_state.config, &target_path).await;
+    let user_offset = user.get_utc_offset();
     for epg_list_item in epg_listings.iter_mut().filter_map(Value::as_object_mut) {
+        if let Some(offset) = user_offset {
+            for (field, timestamp_field) in [(crate::model::XC_TAG_EPG_START, crate::model::XC_TAG_EPG_START_TIMESTAMP), (crate::model::XC_TAG_EPG_END, crate::model::XC_TAG_EPG_STOP_TIMESTAMP)] {
+                if let Some(time) = get_timestamp_field(epg_list_item, timestamp_field).and_then(|ts| format_catchup_time(ts, offset)) {
+                    epg_list_item.insert(field.to_string(), Value::String(time));
+                }
+            }
+        }
         // TODO epg_id
         if let Some(catchup_provider_id) = epg_list_item.get(crate::model::XC_TAG_ID).and_then(Value::as_str).and_then(|id| id.parse::<u32>().ok()) {
             let uuid = generate_playlist_uuid(&hex_encode(&pli.get_uuid()), &catchup_provider_id.to_string(), pli.item_type, &pli.url);
@@ -760,6 +850,44 @@ async fn xtream_get_catchup_response(app_state: &AppState, target: &ConfigTarget
                 .body(result).unwrap().into_response())
 }
 
+// Xtream api extension for clients which report the playback position.
+async fn xtream_update_playback_position(app_state: &AppState, user: &ProxyUserCredentials, target: &ConfigTarget,
+                                         action: &str, api_req: &UserApiRequest) -> impl IntoResponse + Send {
+    let stream_id = get_non_empty(&api_req.stream_id, &api_req.vod_id, &api_req.series_id);
+    let virtual_id: u32 = try_result_bad_request!(stream_id.trim().parse());
+    if action == crate::model::XC_ACTION_REMOVE_PLAYBACK_POSITION {
+        app_state.active_users.remove_playback_position(&user.username, virtual_id).await;
+        return StatusCode::OK.into_response();
+    }
+    let position_secs: u64 = try_result_bad_request!(api_req.position.trim().parse());
+    let (pli, _) = try_result_bad_request!(xtream_repository::xtream_get_item_for_stream_id(virtual_id, &app_state.config, target, None));
+    if pli.xtream_cluster == XtreamCluster::Live {
+        return StatusCode::BAD_REQUEST.into_response();
+    }
+    app_state.active_users.update_playback_position(&user.username, virtual_id, pli.xtream_cluster, Some(position_secs), None).await;
+    StatusCode::OK.into_response()
+}
+
+// Synthetic vod category with the recently played movies of the user.
+async fn xtream_get_continue_watching_response(app_state: &AppState, user: &ProxyUserCredentials, target: &ConfigTarget) -> impl IntoResponse + Send {
+    let xtream_output = try_option_bad_request!(target.get_xtream_output());
+    let options = XtreamMappingOptions::from_target_options(target, xtream_output, &app_state.config);
+    let base_url = app_state.config.get_user_server_info(user).get_base_url();
+    let category_id = crate::model::XC_CONTINUE_WATCHING_CATEGORY_ID.to_string();
+    let positions = app_state.active_users.get_playback_positions(&user.username, Some(XtreamCluster::Video)).await;
+    let docs: Vec<Value> = positions.iter().filter_map(|position| {
+        let (pli, _) = xtream_repository::xtream_get_item_for_stream_id(position.virtual_id, &app_state.config, target, Some(XtreamCluster::Video)).ok()?;
+        let mut doc = pli.to_doc(&base_url, &options, user);
+        if let Some(fields) = doc.as_object_mut() {
+            fields.insert(crate::model::XC_TAG_CATEGORY_ID.to_string(), Value::String(category_id.clone()));
+            fields.insert(crate::model::XC_TAG_CATEGORY_IDS.to_string(), json!([crate::model::XC_CONTINUE_WATCHING_CATEGORY_ID]));
+            fields.insert(crate::model::XC_TAG_PLAYBACK_POSITION.to_string(), json!(position));
+        }
+        Some(doc)
+    }).collect();
+    axum::Json(docs).into_response()
+}
+
 macro_rules! skip_json_response_if_flag_set {
     ($flag:expr, $stmt:expr) => {
         if $flag {
@@ -780,11 +908,15 @@ macro_rules! skip_flag_optional {
 }
 
 async fn xtream_player_api(
+    client_ip: ClientIp,
+    req_headers: &HeaderMap,
     api_req: UserApiRequest,
     app_state: &Arc<AppState>,
 ) -> impl IntoResponse + Send {
     let user_target = get_user_target(&api_req, app_state);
-    if let Some((user, target)) = user_target {
+    if let Some((mut user, target)) = user_target {
+        user.t_ipv6_client = client_ip.is_ipv6();
+        update_user_activity(app_state, &user, client_ip, req_headers).await;
         if !target.has_output(&TargetType::Xtream) {
             return axum::response::Json(get_user_info(&user, app_state).await).into_response();
         }
@@ -824,15 +956,26 @@ async fn xtream_player_api(
                 ).await.into_response();
             }
             crate::model::XC_ACTION_GET_CATCHUP_TABLE => {
-                skip_json_response_if_flag_set!(skip_live, xtream_get_catchup_response(app_state, target, api_req.stream_id.trim(), api_req.start.trim(), api_req.end.trim()).await);
+                skip_json_response_if_flag_set!(skip_live, xtream_get_catchup_response(app_state, &user, target, api_req.stream_id.trim(), api_req.start.trim(), api_req.end.trim()).await);
+            }
+            crate::model::XC_ACTION_SET_PLAYBACK_POSITION | crate::model::XC_ACTION_REMOVE_PLAYBACK_POSITION => {
+                return xtream_update_playback_position(app_state, &user, target, action, &api_req).await.into_response();
+            }
+            crate::model::XC_ACTION_GET_PLAYBACK_POSITIONS => {
+                return axum::Json(app_state.active_users.get_playback_positions(&user.username, None).await).into_response();
             }
             _ => {}
         }
 
         let category_id = api_req.category_id.trim().parse::<u32>().ok();
+        if action == crate::model::XC_ACTION_GET_VOD_STREAMS
+            && category_id == Some(crate::model::XC_CONTINUE_WATCHING_CATEGORY_ID)
+            && target.get_xtream_output().is_some_and(|o| o.continue_watching) {
+            skip_json_response_if_flag_set!(skip_vod, xtream_get_continue_watching_response(app_state, &user, target).await);
+        }
         // Handle general content actions
         if let Some(response) = xtream_player_api_handle_content_action(
-            &app_state.config, &target.name, action, category_id, &user,
+            &app_state.config, target, action, category_id, &user,
         ).await {
             return response.into_response();
         }
@@ -893,18 +1036,22 @@ fn xtream_create_content_stream(xtream_iter: impl Iterator<Item=(String, bool)>)
 }
 
 async fn xtream_player_api_get(
+    client_ip: ClientIp,
+    req_headers: HeaderMap,
     axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
     axum::extract::Query(api_req): axum::extract::Query<UserApiRequest>,
 ) -> impl IntoResponse + Send {
-    xtream_player_api(api_req, &app_state).await
+    xtream_player_api(client_ip, &req_headers, api_req, &app_state).await
 }
 
 
 async fn xtream_player_api_post(
+    client_ip: ClientIp,
+    req_headers: HeaderMap,
     axum::extract::State(app_state): axum::extract::State<Arc<AppState>>,
     axum::extract::Form(api_req): axum::extract::Form<UserApiRequest>,
 ) -> impl IntoResponse + Send {
-    xtream_player_api(api_req, &app_state).await
+    xtream_player_api(client_ip, &req_headers, api_req, &app_state).await
 }
 
 macro_rules! register_xtream_api {
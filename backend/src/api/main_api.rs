@@ -1,6 +1,9 @@
 use crate::api::endpoints::hdhomerun_api::hdhr_api_register;
 use crate::api::endpoints::hls_api::hls_api_register;
 use crate::api::endpoints::m3u_api::m3u_api_register;
+use crate::api::endpoints::portal_api::portal_api_register;
+use crate::api::endpoints::qr_code_api::qr_code_api_register;
+use crate::api::endpoints::share_link_api::share_link_api_register;
 use crate::api::endpoints::v1_api::v1_api_register;
 use crate::api::endpoints::web_index::{index_register_with_path, index_register_without_path};
 use crate::api::endpoints::xmltv_api::xmltv_api_register;
@@ -9,12 +12,26 @@ use crate::api::model::active_provider_manager::ActiveProviderManager;
 use crate::api::model::active_user_manager::ActiveUserManager;
 use crate::api::model::app_state::{AppState, HdHomerunAppState};
 use crate::api::model::download::DownloadQueue;
+use crate::api::model::streams::shared_hls_cache::SharedHlsCache;
 use crate::api::model::streams::shared_stream_manager::SharedStreamManager;
+use crate::api::model::traffic_counter::TrafficCounter;
+use crate::api::model::stream_health::StreamHealthRegistry;
+use crate::api::model::replication_state::ReplicationState;
+use crate::api::model::maintenance_state::MaintenanceState;
+use crate::api::model::recording::RecordingManager;
+use crate::api::metrics_export::exec_metrics_export;
+use crate::api::replication::exec_standby_replication;
+use crate::api::backup::exec_backup_scheduler;
+use crate::api::user_cleanup::exec_user_cleanup_scheduler;
+use crate::api::recording::exec_recording_scheduler;
+use crate::api::endpoints::replication_api::replication_api_register;
 use crate::api::scheduler::start_scheduler;
 use crate::model::{Config, ProcessTargets, RateLimitConfig, ScheduleConfig};
+use crate::repository::m3u_repository::m3u_store_needs_reprocess;
 use crate::model::{Healthcheck};
 use crate::processing::processor::playlist;
 use crate::tools::lru_cache::LRUResourceCache;
+use crate::utils::request::format_url_host;
 use log::{error, info};
 use reqwest::Client;
 use std::io::ErrorKind;
@@ -54,7 +71,7 @@ async fn healthcheck() -> impl axum::response::IntoResponse {
     axum::Json(create_healthcheck())
 }
 
-async fn create_shared_data(cfg: &Arc<Config>) -> AppState {
+pub(in crate::api) async fn create_shared_data(cfg: &Arc<Config>) -> AppState {
     let lru_cache = cfg.reverse_proxy.as_ref().and_then(|r| r.cache.as_ref()).and_then(|c| if c.enabled {
         Some(Mutex::new(LRUResourceCache::new(c.t_size, &PathBuf::from(c.dir.as_ref().unwrap()))))
     } else { None });
@@ -85,18 +102,49 @@ async fn create_shared_data(cfg: &Arc<Config>) -> AppState {
         downloads: Arc::new(DownloadQueue::new()),
         cache,
         shared_stream_manager: Arc::new(SharedStreamManager::new()),
+        shared_hls_cache: Arc::new(SharedHlsCache::new()),
         active_users,
         active_provider,
+        traffic: Arc::new(TrafficCounter::default()),
+        stream_health: Arc::new(StreamHealthRegistry::default()),
+        replication: Arc::new(ReplicationState::default()),
+        maintenance: Arc::new(MaintenanceState::default()),
+        recordings: Arc::new(cfg.recording.as_ref().filter(|recording| recording.enabled).map(RecordingManager::new)),
     }
 }
 
 fn exec_update_on_boot(client: Arc<reqwest::Client>, cfg: &Arc<Config>, targets: &Arc<ProcessTargets>) {
+    let cfg_clone = Arc::clone(cfg);
+    let targets_clone = Arc::clone(targets);
     if cfg.update_on_boot {
-        let cfg_clone = Arc::clone(cfg);
-        let targets_clone = Arc::clone(targets);
         tokio::spawn(
             async move { playlist::exec_processing(client, cfg_clone, targets_clone).await }
         );
+    } else {
+        // without an update the m3u output of the targets would be empty
+        tokio::spawn(async move {
+            if let Some(outdated_targets) = get_outdated_store_targets(&cfg_clone, &targets_clone).await {
+                info!("Processing targets with playlist stores of an older release");
+                playlist::exec_processing(client, cfg_clone, Arc::new(outdated_targets)).await;
+            }
+        });
+    }
+}
+
+// the targets with m3u stores of an older release, they can't be read until they are processed again
+async fn get_outdated_store_targets(cfg: &Config, targets: &ProcessTargets) -> Option<ProcessTargets> {
+    let mut target_names = vec![];
+    for source in &cfg.sources.sources {
+        for target in source.targets.iter().filter(|target| targets.has_target(target.id)) {
+            if m3u_store_needs_reprocess(cfg, target).await {
+                target_names.push(target.name.clone());
+            }
+        }
+    }
+    if target_names.is_empty() {
+        None
+    } else {
+        cfg.sources.validate_targets(Some(&target_names)).ok()
     }
 }
 
@@ -169,7 +217,7 @@ fn create_compression_layer() -> tower_http::compression::CompressionLayer {
 }
 
 fn start_hdhomerun(cfg: &Arc<Config>, app_state: &Arc<AppState>, infos: &mut Vec<String>) {
-    let host = cfg.api.host.to_string();
+    let host = format_url_host(&cfg.api.host);
     let guard = cfg.t_hdhomerun.load();
     if let Some(hdhomerun) = &*guard {
         if hdhomerun.enabled {
@@ -216,7 +264,7 @@ fn start_hdhomerun(cfg: &Arc<Config>, app_state: &Arc<AppState>, infos: &mut Vec
 
 pub async fn start_server(cfg: Arc<Config>, targets: Arc<ProcessTargets>) -> futures::io::Result<()> {
     let mut infos = Vec::new();
-    let host = cfg.api.host.to_string();
+    let host = format_url_host(&cfg.api.host);
     let port = cfg.api.port;
     let web_ui_enabled = cfg.web_ui.as_ref().is_some_and(|c| c.enabled);
     let web_dir_path = match get_web_dir_path(web_ui_enabled, cfg.api.web_root.as_str()) {
@@ -230,8 +278,19 @@ pub async fn start_server(cfg: Arc<Config>, targets: Arc<ProcessTargets>) -> fut
     let app_state = Arc::new(app_shared_data);
     let shared_data = Arc::clone(&app_state);
 
-    exec_scheduler(&Arc::clone(&shared_data.http_client), &cfg, &targets);
-    exec_update_on_boot(Arc::clone(&shared_data.http_client), &cfg, &targets);
+    // a standby starts the updates when it is promoted
+    let standby = exec_standby_replication(&cfg, &app_state, &targets, |app_state, targets| {
+        exec_scheduler(&app_state.http_client, &app_state.config, targets);
+        exec_update_on_boot(Arc::clone(&app_state.http_client), &app_state.config, targets);
+    });
+    if !standby {
+        exec_scheduler(&Arc::clone(&shared_data.http_client), &cfg, &targets);
+        exec_update_on_boot(Arc::clone(&shared_data.http_client), &cfg, &targets);
+    }
+    exec_metrics_export(&cfg, &app_state);
+    exec_backup_scheduler(&cfg, &app_state);
+    exec_user_cleanup_scheduler(&cfg, &app_state);
+    exec_recording_scheduler(&app_state).await;
 
     if cfg.config_hot_reload {
         if let Err(err) = exec_config_watch(&app_state).await {
@@ -247,7 +306,10 @@ pub async fn start_server(cfg: Arc<Config>, targets: Arc<ProcessTargets>) -> fut
 
 
     let web_ui_path = cfg.web_ui.as_ref().and_then(|c| c.path.as_ref()).map(|p| format!("/{p}")).unwrap_or_default();
-    infos.push(format!("Server running: http://{}:{}", &cfg.api.host, &cfg.api.port));
+    if let Some(portal) = cfg.portal.as_ref().filter(|portal| portal.enabled) {
+        infos.push(format!("Portal running: http://{host}:{port}/{}", portal.path));
+    }
+    infos.push(format!("Server running: http://{host}:{port}"));
     for info in &infos {
         info!("{info}");
     }
@@ -255,6 +317,9 @@ pub async fn start_server(cfg: Arc<Config>, targets: Arc<ProcessTargets>) -> fut
     // Web Server
     let mut router = axum::Router::new()
         .route("/healthcheck", axum::routing::get(healthcheck));
+    if cfg.replication.as_ref().is_some_and(|replication| replication.enabled) {
+        router = router.merge(replication_api_register());
+    }
     if web_ui_enabled {
         router = router
             .nest_service(&format!("{web_ui_path}/static"), tower_http::services::ServeDir::new(web_dir_path.join("static")))
@@ -269,7 +334,12 @@ pub async fn start_server(cfg: Arc<Config>, targets: Arc<ProcessTargets>) -> fut
         .merge(xtream_api_register())
         .merge(m3u_api_register())
         .merge(xmltv_api_register())
-        .merge(hls_api_register());
+        .merge(hls_api_register())
+        .merge(qr_code_api_register())
+        .merge(share_link_api_register());
+    if let Some(portal) = cfg.portal.as_ref().filter(|portal| portal.enabled) {
+        api_router = api_router.merge(portal_api_register(&portal.path));
+    }
     // let mut rate_limiting = false;
     if let Some(rate_limiter) = app_state.config.reverse_proxy.as_ref().and_then(|r| r.rate_limit.clone()) {
         // rate_limiting = rate_limiter.enabled;
@@ -0,0 +1,91 @@
+use crate::api::model::app_state::AppState;
+use crate::model::{Config, MetricsExportConfig, RefreshResult};
+use crate::repository::refresh_history_repository::load_refresh_history;
+use crate::utils::request::sanitize_sensitive_info;
+use log::{debug, error, info};
+use reqwest::header;
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const NANOS_PER_SEC: i64 = 1_000_000_000;
+
+// commas, equal signs and spaces have to be escaped in tag values
+fn escape_tag(value: &str) -> String {
+    value.replace(',', "\\,").replace('=', "\\=").replace(' ', "\\ ")
+}
+
+fn write_refresh_line(lines: &mut String, target_name: &str, result: &RefreshResult) {
+    let _ = writeln!(lines, "tuliprox_refresh,target={} success={},duration={}i,groups={}i,live={}i,vod={}i,series={}i,errors={}i {}",
+                     escape_tag(target_name), result.success, result.duration, result.groups, result.live, result.vod, result.series,
+                     result.errors, result.ts * NANOS_PER_SEC);
+}
+
+#[allow(clippy::cast_precision_loss)]
+async fn collect_metrics(app_state: &AppState, last_bytes_sent: &mut (u64, Instant)) -> String {
+    let ts = chrono::Utc::now().timestamp() * NANOS_PER_SEC;
+    let mut lines = String::new();
+    let _ = writeln!(lines, "tuliprox_connections active_users={}i,active_connections={}i {ts}",
+                     app_state.active_users.active_users().await, app_state.active_users.active_connections().await);
+    if let Some(provider_connections) = app_state.active_provider.active_connections().await {
+        for (provider, connections) in provider_connections {
+            let _ = writeln!(lines, "tuliprox_provider_connections,provider={} connections={connections}i {ts}", escape_tag(&provider));
+        }
+    }
+
+    let bytes_sent = app_state.traffic.bytes_sent();
+    let elapsed = last_bytes_sent.1.elapsed().as_secs_f64();
+    let bytes_per_second = if elapsed > 0.0 { bytes_sent.saturating_sub(last_bytes_sent.0) as f64 / elapsed } else { 0.0 };
+    *last_bytes_sent = (bytes_sent, Instant::now());
+    let _ = writeln!(lines, "tuliprox_bandwidth bytes_sent={bytes_sent}i,bytes_per_second={bytes_per_second:.0} {ts}");
+
+    // the last refresh keeps its own timestamp, sending it again overwrites the same point
+    for target in app_state.config.sources.sources.iter().flat_map(|source| &source.targets) {
+        if let Some(result) = load_refresh_history(&app_state.config, &target.name).await.entries.last() {
+            write_refresh_line(&mut lines, &target.name, result);
+        }
+    }
+    lines
+}
+
+async fn send_metrics(client: &reqwest::Client, export: &MetricsExportConfig, lines: String) {
+    let mut request = client.post(&export.url)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(lines);
+    if let Some(token) = export.token.as_ref() {
+        request = request.header(header::AUTHORIZATION, format!("Token {token}"));
+    }
+    match request.send().await {
+        Ok(response) if response.status().is_success() => debug!("Metrics exported"),
+        Ok(response) => error!("Metrics export failed with status {} {}", response.status(), sanitize_sensitive_info(&export.url)),
+        Err(err) => error!("Metrics export failed {}", sanitize_sensitive_info(&err.to_string())),
+    }
+}
+
+pub(in crate::api) fn exec_metrics_export(cfg: &Config, app_state: &Arc<AppState>) {
+    let Some(export) = cfg.metrics_export.as_ref().filter(|export| export.enabled).cloned() else { return };
+    info!("Metrics export every {}s to {}", export.interval_secs, sanitize_sensitive_info(&export.url));
+    let app_state = Arc::clone(app_state);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(export.interval_secs));
+        let mut last_bytes_sent = (app_state.traffic.bytes_sent(), Instant::now());
+        loop {
+            interval.tick().await;
+            let lines = collect_metrics(&app_state, &mut last_bytes_sent).await;
+            send_metrics(&app_state.http_client, &export, lines).await;
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_refresh_line() {
+        let result = RefreshResult { ts: 1_700_000_000, success: true, duration: 12, groups: 3, live: 10, vod: 5, series: 2, errors: 0 };
+        let mut lines = String::new();
+        write_refresh_line(&mut lines, "my target,all=1", &result);
+        assert_eq!(lines, "tuliprox_refresh,target=my\\ target\\,all\\=1 success=true,duration=12i,groups=3i,live=10i,vod=5i,series=2i,errors=0i 1700000000000000000\n");
+    }
+}
@@ -4,4 +4,9 @@ mod scheduler;
 mod endpoints;
 pub mod main_api;
 mod config_watch;
+mod metrics_export;
+mod replication;
+mod backup;
+mod user_cleanup;
+mod recording;
 mod serve;
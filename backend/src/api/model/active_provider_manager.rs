@@ -629,7 +629,13 @@ mod tests {
             headers: HashMap::default(),
             options: None,
             method: InputFetchMethod::default(),
+            rate_limit: None,
+            tls: None,
+            auth: None,
             t_base_url: String::default(),
+            t_rate_limiter: None,
+            t_client: None,
+            t_auth_token: None,
         }
     }
 
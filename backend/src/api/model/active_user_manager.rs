@@ -1,28 +1,37 @@
 use crate::model::Config;
-use crate::model::{ProxyUserCredentials};
+use crate::model::{ProxyUserCredentials, UserActivityMap};
+use crate::repository::user_repository::{get_user_activity_file_path, load_user_activity, save_user_activity};
 use crate::utils::request::sanitize_sensitive_info;
 use shared::utils::{current_time_secs, default_grace_period_millis, default_grace_period_timeout_secs};
 use jsonwebtoken::get_current_timestamp;
-use log::{debug, info};
-use std::collections::HashMap;
+use log::{debug, error, info};
+use std::collections::{BTreeMap, HashMap};
+use std::net::IpAddr;
+use std::path::PathBuf;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::sync::RwLock;
-use shared::model::UserConnectionPermission;
+use std::time::Duration;
+use tokio::sync::{Notify, RwLock};
+use shared::model::{UserConnectionPermission, XtreamCluster};
 
 const USER_CON_TTL: u64 = 10_800;  // 3 hours
 const USER_SESSION_LIMIT: usize = 50;
+const USER_ACTIVITY_SAVE_INTERVAL_SECS: u64 = 300;
+// a slot granted to a waiter is reserved until the connection is added
+const SLOT_RESERVATION_TTL_MILLIS: u64 = 5_000;
 
 pub struct UserConnectionGuard {
     manager: Arc<ActiveUserManager>,
     username: String,
+    device: Option<String>,
 }
 impl Drop for UserConnectionGuard {
     fn drop(&mut self) {
         let manager = self.manager.clone();
         let username = self.username.clone();
+        let device = self.device.take();
         tokio::spawn(async move {
-            manager.remove_connection(&username).await;
+            manager.remove_connection(&username, device.as_deref()).await;
         });
     }
 }
@@ -30,6 +39,7 @@ impl Drop for UserConnectionGuard {
 #[derive(Clone, Debug)]
 pub struct UserSession {
     pub token: String,
+    pub device: Option<String>,
     pub virtual_id: u32,
     pub provider: String,
     pub stream_url: String,
@@ -37,12 +47,59 @@ pub struct UserSession {
     pub permission: UserConnectionPermission,
 }
 
+/// Last known playback position of a vod or series episode.
+/// The position is reported by the client or inferred from the byte range of a stream request.
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct PlaybackPosition {
+    pub virtual_id: u32,
+    pub cluster: XtreamCluster,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub position_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub byte_offset: Option<u64>,
+    pub ts: u64,
+}
+
+/// Session info of an active user for the web ui.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct ActiveUserSessionInfo {
+    pub token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    pub virtual_id: u32,
+    pub provider: String,
+    pub ts: u64,
+    pub permission: UserConnectionPermission,
+}
+
+/// Connections and sessions of an active user, the connections of the devices share the `max_connections` of the user.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct ActiveUserInfo {
+    pub username: String,
+    pub connections: u32,
+    pub max_connections: u32,
+    pub devices: BTreeMap<String, u32>,
+    pub sessions: Vec<ActiveUserSessionInfo>,
+}
+
+struct SlotWaiter {
+    id: u64,
+    // the client has a session, like a zapping client reconnecting
+    known: bool,
+}
+
 struct UserConnectionData {
     max_connections: u32,
     connections: u32,
+    // connections per device name
+    device_connections: HashMap<String, u32>,
     granted_grace: bool,
     grace_ts: u64,
     sessions: Vec<UserSession>,
+    // clients waiting for a freed slot, in arrival order
+    waiters: Vec<SlotWaiter>,
+    // timestamps in millis of the slots granted to waiters
+    reservations: Vec<u64>,
 }
 
 impl UserConnectionData {
@@ -50,12 +107,38 @@ impl UserConnectionData {
         Self {
             max_connections,
             connections,
+            device_connections: HashMap::new(),
             granted_grace: false,
             grace_ts: 0,
             sessions: Vec::new(),
+            waiters: Vec::new(),
+            reservations: Vec::new(),
         }
     }
 
+    fn reserved_connections(&mut self) -> u32 {
+        let now = get_current_timestamp_millis();
+        self.reservations.retain(|ts| now.saturating_sub(*ts) < SLOT_RESERVATION_TTL_MILLIS);
+        u32::try_from(self.reservations.len()).unwrap_or(u32::MAX)
+    }
+
+    fn is_known_client(&self, fingerprint: &str) -> bool {
+        !fingerprint.is_empty() && self.sessions.iter().any(|session| session.token.starts_with(fingerprint))
+    }
+
+    // Known clients are served before new ones, otherwise first come first served.
+    fn take_slot(&mut self, waiter_id: u64) -> bool {
+        let reserved = self.reserved_connections();
+        let head = self.waiters.iter().position(|waiter| waiter.known).unwrap_or(0);
+        if self.waiters.get(head).is_some_and(|waiter| waiter.id == waiter_id)
+            && self.connections + reserved < self.max_connections {
+            self.waiters.remove(head);
+            self.reservations.push(get_current_timestamp_millis());
+            return true;
+        }
+        false
+    }
+
     fn add_session(&mut self, session: UserSession) {
         self.gc();
         self.sessions.push(session);
@@ -68,27 +151,55 @@ impl UserConnectionData {
     }
 }
 
+fn get_current_timestamp_millis() -> u64 {
+    u64::try_from(chrono::Utc::now().timestamp_millis()).unwrap_or_default()
+}
+
 pub struct ActiveUserManager {
     grace_period_millis: u64,
     grace_period_timeout_secs: u64,
+    slot_wait_millis: u64,
+    // notified when a connection is removed
+    slot_notify: Arc<Notify>,
+    slot_waiter_id: Arc<AtomicU64>,
     log_active_user: bool,
     user: Arc<RwLock<HashMap<String, UserConnectionData>>>,
+    // playback positions are kept when the user disconnects
+    playback_positions: Arc<RwLock<HashMap<String, Vec<PlaybackPosition>>>>,
+    // user activity is kept when the user disconnects and persisted to spot dormant accounts
+    user_activity: Arc<RwLock<UserActivityMap>>,
+    user_activity_file: Option<PathBuf>,
+    user_activity_save_ts: Arc<AtomicU64>,
     gc_ts: Option<AtomicU64>,
 }
 
 impl ActiveUserManager {
     pub fn new(config: &Config) -> Self {
         let log_active_user = config.log.as_ref().is_some_and(|l| l.log_active_user);
-        let (grace_period_millis, grace_period_timeout_secs) = config.reverse_proxy.as_ref()
-            .and_then(|r| r.stream.as_ref())
+        let stream_config = config.reverse_proxy.as_ref().and_then(|r| r.stream.as_ref());
+        let (grace_period_millis, grace_period_timeout_secs) = stream_config
             .map_or_else(|| (default_grace_period_millis(), default_grace_period_timeout_secs()), |s| (s.grace_period_millis, s.grace_period_timeout_secs));
+        let slot_wait_millis = stream_config.map_or(0, |s| s.slot_wait_millis);
+        let user_activity_file = get_user_activity_file_path(config);
+        let mut user_activity = user_activity_file.as_deref().map(load_user_activity).unwrap_or_default();
+        let now = current_time_secs();
+        for activity in user_activity.values_mut() {
+            activity.prune(now);
+        }
 
         Self {
             grace_period_millis,
             grace_period_timeout_secs,
+            slot_wait_millis,
+            slot_notify: Arc::new(Notify::new()),
+            slot_waiter_id: Arc::new(AtomicU64::new(0)),
             log_active_user,
             user: Arc::new(RwLock::new(HashMap::new())),
-            gc_ts: Some(AtomicU64::new(current_time_secs())),
+            playback_positions: Arc::new(RwLock::new(HashMap::new())),
+            user_activity: Arc::new(RwLock::new(user_activity)),
+            user_activity_file,
+            user_activity_save_ts: Arc::new(AtomicU64::new(now)),
+            gc_ts: Some(AtomicU64::new(now)),
         }
     }
 
@@ -96,8 +207,15 @@ impl ActiveUserManager {
         Self {
             grace_period_millis: self.grace_period_millis,
             grace_period_timeout_secs: self.grace_period_timeout_secs,
+            slot_wait_millis: self.slot_wait_millis,
+            slot_notify: Arc::clone(&self.slot_notify),
+            slot_waiter_id: Arc::clone(&self.slot_waiter_id),
             log_active_user: self.log_active_user,
             user: Arc::clone(&self.user),
+            playback_positions: Arc::clone(&self.playback_positions),
+            user_activity: Arc::clone(&self.user_activity),
+            user_activity_file: self.user_activity_file.clone(),
+            user_activity_save_ts: Arc::clone(&self.user_activity_save_ts),
             gc_ts: None,
         }
     }
@@ -110,9 +228,9 @@ impl ActiveUserManager {
     }
 
     fn check_connection_permission(&self, username: &str, connection_data: &mut UserConnectionData) -> UserConnectionPermission {
-        let current_connections = connection_data.connections;
+        let current_connections = connection_data.connections + connection_data.reserved_connections();
 
-        if current_connections < connection_data.max_connections {
+        if current_connections < connection_data.max_connections && connection_data.waiters.is_empty() {
             // Reset grace period because user is back under max_connections
             connection_data.granted_grace = false;
             connection_data.grace_ts = 0;
@@ -132,7 +250,7 @@ impl ActiveUserManager {
             connection_data.grace_ts = 0;
         }
 
-        if self.grace_period_millis > 0 && current_connections == connection_data.max_connections {
+        if self.grace_period_millis > 0 && current_connections == connection_data.max_connections && connection_data.waiters.is_empty() {
             // Allow grace period once
             connection_data.granted_grace = true;
             connection_data.grace_ts = now;
@@ -149,15 +267,60 @@ impl ActiveUserManager {
         &self,
         username: &str,
         max_connections: u32,
+        fingerprint: &str,
     ) -> UserConnectionPermission {
         if max_connections > 0 {
-            if let Some(connection_data) = self.user.write().await.get_mut(username) {
-                return self.check_connection_permission(username, connection_data);
+            let permission = match self.user.write().await.get_mut(username) {
+                Some(connection_data) => self.check_connection_permission(username, connection_data),
+                None => return UserConnectionPermission::Allowed,
+            };
+            if permission == UserConnectionPermission::Exhausted && self.slot_wait_millis > 0 {
+                return self.wait_for_slot(username, fingerprint).await;
             }
+            return permission;
         }
         UserConnectionPermission::Allowed
     }
 
+    /// Waits up to `slot_wait_millis` for a freed slot.
+    /// Clients with a session of the user are served first, a zapping client wins over a new device.
+    async fn wait_for_slot(&self, username: &str, fingerprint: &str) -> UserConnectionPermission {
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(self.slot_wait_millis);
+        let waiter_id = self.slot_waiter_id.fetch_add(1, Ordering::Relaxed);
+        {
+            let mut lock = self.user.write().await;
+            let Some(connection_data) = lock.get_mut(username) else { return UserConnectionPermission::Allowed };
+            let known = connection_data.is_known_client(fingerprint);
+            connection_data.waiters.push(SlotWaiter { id: waiter_id, known });
+        }
+        debug!("User {username} waits for a free slot");
+        loop {
+            let notified = self.slot_notify.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+            {
+                let mut lock = self.user.write().await;
+                let Some(connection_data) = lock.get_mut(username) else { return UserConnectionPermission::Allowed };
+                if connection_data.take_slot(waiter_id) {
+                    drop(lock);
+                    debug!("User {username} got a freed slot");
+                    // more than one slot could be free
+                    self.slot_notify.notify_waiters();
+                    return UserConnectionPermission::Allowed;
+                }
+            }
+            if tokio::time::timeout_at(deadline, notified).await.is_err() {
+                if let Some(connection_data) = self.user.write().await.get_mut(username) {
+                    connection_data.waiters.retain(|waiter| waiter.id != waiter_id);
+                }
+                // the next waiter could be the head now
+                self.slot_notify.notify_waiters();
+                debug!("User access denied, no slot freed in time: {username}");
+                return UserConnectionPermission::Exhausted;
+            }
+        }
+    }
+
 
     pub async fn active_users(&self) -> usize {
         self.user.read().await.len()
@@ -172,13 +335,17 @@ impl ActiveUserManager {
         user.read().await.values().map(|c| c.connections as usize).sum()
     }
 
-    pub async fn add_connection(&self, username: &str, max_connections: u32) -> UserConnectionGuard {
+    pub async fn add_connection(&self, user: &ProxyUserCredentials) -> UserConnectionGuard {
+        let username = user.username.as_str();
         let mut lock = self.user.write().await;
-        if let Some(connection_data) = lock.get_mut(username) {
-            connection_data.connections += 1;
-            connection_data.max_connections = max_connections;
-        } else {
-            lock.insert(username.to_string(), UserConnectionData::new(1, max_connections));
+        let connection_data = lock.entry(username.to_string()).or_insert_with(|| UserConnectionData::new(0, user.max_connections));
+        connection_data.connections += 1;
+        connection_data.max_connections = user.max_connections;
+        if !connection_data.reservations.is_empty() {
+            connection_data.reservations.remove(0);
+        }
+        if let Some(device) = user.t_device.as_ref() {
+            *connection_data.device_connections.entry(device.clone()).or_default() += 1;
         }
         drop(lock);
 
@@ -187,17 +354,26 @@ impl ActiveUserManager {
         UserConnectionGuard {
             manager: Arc::new(self.clone_inner()),
             username: username.to_string(),
+            device: user.t_device.clone(),
         }
     }
 
-    async fn remove_connection(&self, username: &str) {
+    async fn remove_connection(&self, username: &str, device: Option<&str>) {
         let mut lock = self.user.write().await;
         if let Some(connection_data) = lock.get_mut(username) {
             if connection_data.connections > 0 {
                 connection_data.connections -= 1;
             }
+            if let Some(device) = device {
+                if let Some(count) = connection_data.device_connections.get_mut(device) {
+                    *count = count.saturating_sub(1);
+                    if *count == 0 {
+                        connection_data.device_connections.remove(device);
+                    }
+                }
+            }
 
-            if connection_data.connections == 0 {
+            if connection_data.connections == 0 && connection_data.waiters.is_empty() {
                 lock.remove(username);
             } else if connection_data.connections < connection_data.max_connections {
                 // Grace timeout expired, reset grace counters
@@ -206,6 +382,7 @@ impl ActiveUserManager {
             }
         }
         drop(lock);
+        self.slot_notify.notify_waiters();
 
         self.log_active_user();
     }
@@ -214,9 +391,10 @@ impl ActiveUserManager {
         sessions.iter().find(|&session| session.token.eq(token))
     }
 
-    fn new_user_session(session_token: &str, virtual_id: u32, provider: &str, stream_url: &str, connection_permission: UserConnectionPermission) -> UserSession {
+    fn new_user_session(session_token: &str, device: Option<&String>, virtual_id: u32, provider: &str, stream_url: &str, connection_permission: UserConnectionPermission) -> UserSession {
         UserSession {
             token: session_token.to_string(),
+            device: device.cloned(),
             virtual_id,
             provider: provider.to_string(),
             stream_url: stream_url.to_string(),
@@ -248,14 +426,14 @@ impl ActiveUserManager {
 
             // no session create new one
             debug!("Creating session for user {} with token {session_token} {}", user.username, sanitize_sensitive_info(stream_url));
-            let session = Self::new_user_session(session_token, virtual_id, provider, stream_url, connection_permission);
+            let session = Self::new_user_session(session_token, user.t_device.as_ref(), virtual_id, provider, stream_url, connection_permission);
             let token = session.token.clone();
             connection_data.add_session(session);
             Some(token)
         } else {
             debug!("Creating session for user {} with token {session_token} {}", user.username, sanitize_sensitive_info(stream_url));
             let mut connection_data = UserConnectionData::new(0, user.max_connections);
-            let session = Self::new_user_session(session_token, virtual_id, provider, stream_url, connection_permission);
+            let session = Self::new_user_session(session_token, user.t_device.as_ref(), virtual_id, provider, stream_url, connection_permission);
             let token = session.token.clone();
             connection_data.add_session(session);
             lock.insert(user.username.to_string(), connection_data);
@@ -295,6 +473,121 @@ impl ActiveUserManager {
         None
     }
 
+    /// Removes the session of the user, a client using the session token gets a new session.
+    pub async fn remove_user_session(&self, username: &str, token: &str) -> bool {
+        let mut lock = self.user.write().await;
+        lock.get_mut(username).is_some_and(|connection_data| {
+            let count = connection_data.sessions.len();
+            connection_data.sessions.retain(|session| session.token != token);
+            connection_data.sessions.len() < count
+        })
+    }
+
+    /// Returns the connections and sessions of all active users, sorted by username.
+    pub async fn get_active_user_info(&self) -> Vec<ActiveUserInfo> {
+        let lock = self.user.read().await;
+        let mut result: Vec<ActiveUserInfo> = lock.iter().map(|(username, connection_data)| ActiveUserInfo {
+            username: username.clone(),
+            connections: connection_data.connections,
+            max_connections: connection_data.max_connections,
+            devices: connection_data.device_connections.iter().map(|(device, count)| (device.clone(), *count)).collect(),
+            sessions: connection_data.sessions.iter().map(|session| ActiveUserSessionInfo {
+                token: session.token.clone(),
+                device: session.device.clone(),
+                virtual_id: session.virtual_id,
+                provider: session.provider.clone(),
+                ts: session.ts,
+                permission: session.permission,
+            }).collect(),
+        }).collect();
+        drop(lock);
+        result.sort_by(|a, b| a.username.cmp(&b.username));
+        result
+    }
+
+    /// Updates the playback position, values which are not given are kept.
+    pub async fn update_playback_position(&self, username: &str, virtual_id: u32, cluster: XtreamCluster,
+                                          position_secs: Option<u64>, byte_offset: Option<u64>) {
+        let mut lock = self.playback_positions.write().await;
+        let positions = lock.entry(username.to_string()).or_default();
+        let ts = current_time_secs();
+        if let Some(position) = positions.iter_mut().find(|p| p.virtual_id == virtual_id) {
+            position.cluster = cluster;
+            position.ts = ts;
+            if position_secs.is_some() {
+                position.position_secs = position_secs;
+            }
+            if byte_offset.is_some() {
+                position.byte_offset = byte_offset;
+            }
+        } else {
+            positions.push(PlaybackPosition { virtual_id, cluster, position_secs, byte_offset, ts });
+        }
+        if positions.len() > USER_SESSION_LIMIT {
+            positions.sort_by_key(|p| std::cmp::Reverse(p.ts));
+            positions.truncate(USER_SESSION_LIMIT);
+        }
+    }
+
+    pub async fn remove_playback_position(&self, username: &str, virtual_id: u32) {
+        let mut lock = self.playback_positions.write().await;
+        if let Some(positions) = lock.get_mut(username) {
+            positions.retain(|p| p.virtual_id != virtual_id);
+            if positions.is_empty() {
+                lock.remove(username);
+            }
+        }
+    }
+
+    /// Returns the playback positions of the user, most recent first.
+    pub async fn get_playback_positions(&self, username: &str, cluster: Option<XtreamCluster>) -> Vec<PlaybackPosition> {
+        let mut positions: Vec<PlaybackPosition> = self.playback_positions.read().await.get(username)
+            .map(|positions| positions.iter().filter(|p| cluster.is_none_or(|c| c == p.cluster)).cloned().collect())
+            .unwrap_or_default();
+        positions.sort_by_key(|p| std::cmp::Reverse(p.ts));
+        positions
+    }
+
+    /// Records the request of a user, the activity is saved at most every `USER_ACTIVITY_SAVE_INTERVAL_SECS`.
+    pub async fn update_user_activity(&self, username: &str, client_ip: IpAddr, user_agent: Option<&str>) {
+        let now = current_time_secs();
+        let client_ip = client_ip.to_canonical().to_string();
+        self.user_activity.write().await.entry(username.to_string()).or_default().update(now, &client_ip, user_agent);
+
+        let save_ts = self.user_activity_save_ts.load(Ordering::Acquire);
+        if now.saturating_sub(save_ts) >= USER_ACTIVITY_SAVE_INTERVAL_SECS
+            && self.user_activity_save_ts.compare_exchange(save_ts, now, Ordering::AcqRel, Ordering::Acquire).is_ok() {
+            self.save_user_activity().await;
+        }
+    }
+
+    pub async fn save_user_activity(&self) {
+        if let Some(path) = self.user_activity_file.as_ref() {
+            let activity = self.user_activity.read().await.clone();
+            if let Err(err) = save_user_activity(path, &activity) {
+                error!("Failed to save user activity {}: {err}", path.display());
+            }
+        }
+    }
+
+    /// Applies the `update` to the activity of all users and saves it.
+    pub async fn modify_user_activity<R>(&self, update: impl FnOnce(&mut UserActivityMap) -> R) -> R {
+        let result = update(&mut *self.user_activity.write().await);
+        self.user_activity_save_ts.store(current_time_secs(), Ordering::Release);
+        self.save_user_activity().await;
+        result
+    }
+
+    /// Returns the activity of all users within the rolling window of `USER_ACTIVITY_WINDOW_SECS`.
+    pub async fn get_all_user_activity(&self) -> UserActivityMap {
+        let now = current_time_secs();
+        let mut result = self.user_activity.read().await.clone();
+        for activity in result.values_mut() {
+            activity.prune(now);
+        }
+        result
+    }
+
     fn log_active_user(&self) {
         if self.log_active_user {
             let user = Arc::clone(&self.user);
@@ -321,6 +614,44 @@ impl ActiveUserManager {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::{SlotWaiter, UserConnectionData, UserSession};
+    use shared::model::UserConnectionPermission;
+
+    #[test]
+    fn test_take_slot_prefers_known_clients() {
+        let mut connection_data = UserConnectionData::new(2, 2);
+        connection_data.sessions.push(UserSession {
+            token: "fp_zapping42".to_string(),
+            device: None,
+            virtual_id: 42,
+            provider: "provider".to_string(),
+            stream_url: String::new(),
+            ts: 0,
+            permission: UserConnectionPermission::Allowed,
+        });
+        let known = connection_data.is_known_client("fp_zapping");
+        assert!(known);
+        assert!(!connection_data.is_known_client("fp_new"));
+        assert!(!connection_data.is_known_client(""));
+        connection_data.waiters.push(SlotWaiter { id: 1, known: false });
+        connection_data.waiters.push(SlotWaiter { id: 2, known });
+
+        // no slot free
+        assert!(!connection_data.take_slot(2));
+        connection_data.connections = 1;
+        // the new device arrived first, but the zapping client wins
+        assert!(!connection_data.take_slot(1));
+        assert!(connection_data.take_slot(2));
+        // the freed slot is reserved for the zapping client
+        assert!(!connection_data.take_slot(1));
+        connection_data.connections = 0;
+        assert!(connection_data.take_slot(1));
+        assert!(connection_data.waiters.is_empty());
+    }
+}
+
 //
 // mod tests {
 //     use std::sync::{Arc, atomic::{AtomicUsize, Ordering}};
@@ -4,7 +4,13 @@ use shared::model::UserConnectionPermission;
 use crate::api::model::active_provider_manager::ActiveProviderManager;
 use crate::api::model::active_user_manager::ActiveUserManager;
 use crate::api::model::download::DownloadQueue;
+use crate::api::model::streams::shared_hls_cache::SharedHlsCache;
 use crate::api::model::streams::shared_stream_manager::SharedStreamManager;
+use crate::api::model::traffic_counter::TrafficCounter;
+use crate::api::model::stream_health::StreamHealthRegistry;
+use crate::api::model::replication_state::ReplicationState;
+use crate::api::model::maintenance_state::MaintenanceState;
+use crate::api::model::recording::RecordingManager;
 use crate::model::{Config, HdHomeRunDeviceConfig};
 use crate::tools::lru_cache::LRUResourceCache;
 
@@ -15,8 +21,14 @@ pub struct AppState {
     pub downloads: Arc<DownloadQueue>,
     pub cache: Arc<Option<Mutex<LRUResourceCache>>>,
     pub shared_stream_manager: Arc<SharedStreamManager>,
+    pub shared_hls_cache: Arc<SharedHlsCache>,
     pub active_users: Arc<ActiveUserManager>,
     pub active_provider: Arc<ActiveProviderManager>,
+    pub traffic: Arc<TrafficCounter>,
+    pub stream_health: Arc<StreamHealthRegistry>,
+    pub replication: Arc<ReplicationState>,
+    pub maintenance: Arc<MaintenanceState>,
+    pub recordings: Arc<Option<RecordingManager>>,
 }
 
 impl AppState {
@@ -24,8 +36,8 @@ impl AppState {
         self.active_users.user_connections(username).await
     }
 
-    pub async fn get_connection_permission(&self, username: &str, max_connections: u32) -> UserConnectionPermission {
-        self.active_users.connection_permission(username, max_connections).await
+    pub async fn get_connection_permission(&self, username: &str, max_connections: u32, fingerprint: &str) -> UserConnectionPermission {
+        self.active_users.connection_permission(username, max_connections, fingerprint).await
     }
 }
 
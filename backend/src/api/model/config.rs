@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 use shared::model::ProcessingOrder;
-use crate::model::{ApiProxyConfig, ConfigApi, InputType, LogConfig, MessagingConfig, ReverseProxyConfig, ScheduleConfig, VideoConfig, ConfigSort, WebUiConfig, ProxyConfig, IpCheckConfig, ConfigTargetOptions, TargetOutput, ConfigRename};
+use crate::model::{ApiProxyConfig, ConfigApi, InputType, LogConfig, MessagingConfig, ReverseProxyConfig, ScheduleConfig, VideoConfig, ConfigSort, WebUiConfig, ProxyConfig, IpCheckConfig, PortalConfig, ProviderCacheConfig, MetricsExportConfig, ReplicationConfig, BackupConfig, UserCleanupConfig, ClientErrorConfig, RecordingConfig, CrashReportConfig, ConfigTargetOptions, TargetOutput, ConfigRename};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct ServerInputConfig {
@@ -54,5 +54,14 @@ pub struct ServerConfig {
     pub web_ui: Option<WebUiConfig>,
     pub proxy: Option<ProxyConfig>,
     pub ipcheck: Option<IpCheckConfig>,
+    pub portal: Option<PortalConfig>,
+    pub provider_cache: Option<ProviderCacheConfig>,
+    pub metrics_export: Option<MetricsExportConfig>,
+    pub replication: Option<ReplicationConfig>,
+    pub backup: Option<BackupConfig>,
+    pub user_cleanup: Option<UserCleanupConfig>,
+    pub client_error: Option<ClientErrorConfig>,
+    pub recording: Option<RecordingConfig>,
+    pub crash_report: Option<CrashReportConfig>,
 }
 
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+/// An announcement served instead of the live streams until `until`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceBanner {
+    // unix timestamp
+    pub until: i64,
+    // target names, all targets if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub targets: Option<Vec<String>>,
+    // virtual ids of the channels, all channels if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channels: Option<Vec<u32>>,
+}
+
+impl MaintenanceBanner {
+    pub fn is_active_for(&self, now: i64, target_name: &str, virtual_id: u32) -> bool {
+        now < self.until
+            && self.targets.as_ref().is_none_or(|targets| targets.iter().any(|name| name == target_name))
+            && self.channels.as_ref().is_none_or(|channels| channels.contains(&virtual_id))
+    }
+}
+
+/// The maintenance banner set by the web ui api.
+#[derive(Debug, Default)]
+pub struct MaintenanceState {
+    banner: RwLock<Option<MaintenanceBanner>>,
+}
+
+impl MaintenanceState {
+    /// Returns the banner while it is active.
+    pub fn get(&self) -> Option<MaintenanceBanner> {
+        let now = chrono::Utc::now().timestamp();
+        self.banner.read().ok()?.as_ref().filter(|banner| now < banner.until).cloned()
+    }
+
+    pub fn set(&self, banner: Option<MaintenanceBanner>) {
+        if let Ok(mut guard) = self.banner.write() {
+            *guard = banner;
+        }
+    }
+
+    pub fn is_active_for(&self, target_name: &str, virtual_id: u32) -> bool {
+        let now = chrono::Utc::now().timestamp();
+        self.banner.read().is_ok_and(|guard| guard.as_ref().is_some_and(|banner| banner.is_active_for(now, target_name, virtual_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MaintenanceBanner;
+
+    #[test]
+    fn test_maintenance_banner_is_active() {
+        let banner = MaintenanceBanner { until: 100, targets: None, channels: None };
+        assert!(banner.is_active_for(99, "any", 1));
+        assert!(!banner.is_active_for(100, "any", 1));
+
+        let banner = MaintenanceBanner { until: 100, targets: Some(vec!["m3u".to_string()]), channels: Some(vec![5, 7]) };
+        assert!(banner.is_active_for(0, "m3u", 7));
+        assert!(!banner.is_active_for(0, "xtream", 7));
+        assert!(!banner.is_active_for(0, "m3u", 6));
+    }
+}
@@ -9,4 +9,9 @@ pub(crate) mod streams;
 pub(in crate::api) mod active_user_manager;
 pub(in crate::api) mod active_provider_manager;
 pub(in crate::api) mod stream;
-pub(in crate::api) mod provider_config;
\ No newline at end of file
+pub(in crate::api) mod provider_config;
+pub(in crate::api) mod traffic_counter;
+pub(in crate::api) mod stream_health;
+pub(in crate::api) mod replication_state;
+pub(in crate::api) mod maintenance_state;
+pub(in crate::api) mod recording;
\ No newline at end of file
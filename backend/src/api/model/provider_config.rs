@@ -1,5 +1,6 @@
 use crate::api::model::active_provider_manager::ProviderAllocation;
 use crate::model::{ConfigInput, ConfigInputAlias, InputType, InputUserInfo};
+use crate::utils::cookie_jar::ProviderCookieJar;
 use jsonwebtoken::get_current_timestamp;
 use log::debug;
 use std::ops::Deref;
@@ -27,6 +28,7 @@ struct ProviderConfigConnection {
 /// `max_connections`: Maximum allowed concurrent connections.
 /// `priority`: Priority level for selecting providers.
 /// `current_connections`: A `RwLock` to safely track the number of active connections.
+/// `cookie_jar`: The session cookies of the provider stream responses.
 #[derive(Debug)]
 pub struct ProviderConfig {
     pub id: u16,
@@ -38,6 +40,7 @@ pub struct ProviderConfig {
     max_connections: usize,
     priority: i16,
     connection: RwLock<ProviderConfigConnection>,
+    pub cookie_jar: Arc<ProviderCookieJar>,
 }
 
 impl ProviderConfig {
@@ -52,6 +55,7 @@ impl ProviderConfig {
             max_connections: cfg.max_connections as usize,
             priority: cfg.priority,
             connection: RwLock::new(ProviderConfigConnection::default()),
+            cookie_jar: Arc::new(ProviderCookieJar::default()),
         }
     }
 
@@ -66,6 +70,7 @@ impl ProviderConfig {
             max_connections: alias.max_connections as usize,
             priority: alias.priority,
             connection: RwLock::new(ProviderConfigConnection::default()),
+            cookie_jar: Arc::new(ProviderCookieJar::default()),
         }
     }
 
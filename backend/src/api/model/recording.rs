@@ -0,0 +1,220 @@
+use crate::model::RecordingConfig;
+use crate::utils::hash_string_as_hex;
+use chrono::TimeZone;
+use deunicode::deunicode;
+use log::error;
+use serde::{Deserialize, Serialize};
+use shared::utils::CONSTANTS;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
+
+const RECORDINGS_FILE: &str = "recordings.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecordingState {
+    Scheduled,
+    Recording,
+    Finished,
+    Failed,
+}
+
+/// A recording of a live channel, the times are unix timestamps in seconds.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Recording {
+    pub id: String,
+    pub target: String,
+    pub virtual_id: u32,
+    pub name: String,
+    pub start: i64,
+    pub stop: i64,
+    pub state: RecordingState,
+    // the file in the recordings directory
+    pub filename: String,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Recording {
+    pub fn new(target: &str, virtual_id: u32, name: &str, start: i64, stop: i64) -> Self {
+        let started = chrono::Utc.timestamp_opt(start, 0).single().map_or_else(|| start.to_string(), |dt| dt.format("%Y%m%d_%H%M").to_string());
+        let channel_name = CONSTANTS.re_filename.replace_all(&deunicode(name).replace(' ', "_"), "").to_string();
+        Self {
+            id: hash_string_as_hex(&format!("{target}:{virtual_id}:{start}")),
+            target: target.to_string(),
+            virtual_id,
+            name: name.to_string(),
+            start,
+            stop,
+            state: RecordingState::Scheduled,
+            filename: format!("{channel_name}_{virtual_id}_{started}.ts"),
+            size: 0,
+            error: None,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, RecordingState::Scheduled | RecordingState::Recording)
+    }
+}
+
+/// The recordings are persisted as json in the recordings directory.
+pub struct RecordingManager {
+    dir: PathBuf,
+    quota_bytes: Option<u64>,
+    recordings: RwLock<Vec<Recording>>,
+    // cancels the waiting or running recordings when they are deleted
+    cancel_tokens: RwLock<HashMap<String, CancellationToken>>,
+}
+
+impl RecordingManager {
+    pub fn new(recording_cfg: &RecordingConfig) -> Self {
+        let dir = PathBuf::from(recording_cfg.dir.as_deref().unwrap_or("recordings"));
+        let mut recordings = load_recordings(&dir);
+        // recordings running while the server stopped are incomplete
+        for recording in recordings.iter_mut().filter(|recording| recording.state == RecordingState::Recording) {
+            recording.state = RecordingState::Failed;
+            recording.error = Some("Interrupted by server restart".to_string());
+        }
+        Self {
+            dir,
+            quota_bytes: recording_cfg.t_quota_bytes,
+            recordings: RwLock::new(recordings),
+            cancel_tokens: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn get_file_path(&self, recording: &Recording) -> PathBuf {
+        self.dir.join(&recording.filename)
+    }
+
+    pub async fn list(&self) -> Vec<Recording> {
+        self.recordings.read().await.clone()
+    }
+
+    /// Adds the recording, the returned token cancels it.
+    pub async fn add(&self, recording: Recording) -> Result<CancellationToken, &'static str> {
+        {
+            let mut recordings = self.recordings.write().await;
+            if recordings.iter().any(|existing| existing.id == recording.id) {
+                return Err("Recording already scheduled");
+            }
+            if recordings.iter().any(|existing| existing.filename == recording.filename) {
+                return Err("Recording file already exists");
+            }
+            recordings.push(recording.clone());
+            self.persist(&recordings);
+        }
+        Ok(self.register(&recording.id).await)
+    }
+
+    /// Returns the cancel token of the recording, used for the scheduled recordings after a restart.
+    pub async fn register(&self, id: &str) -> CancellationToken {
+        let token = CancellationToken::new();
+        self.cancel_tokens.write().await.insert(id.to_string(), token.clone());
+        token
+    }
+
+    /// Cancels the recording and removes it with its file.
+    pub async fn delete(&self, id: &str) -> Option<Recording> {
+        if let Some(token) = self.cancel_tokens.write().await.remove(id) {
+            token.cancel();
+        }
+        let recording = {
+            let mut recordings = self.recordings.write().await;
+            let index = recordings.iter().position(|recording| recording.id == id)?;
+            let recording = recordings.remove(index);
+            self.persist(&recordings);
+            recording
+        };
+        let file_path = self.get_file_path(&recording);
+        if file_path.exists() {
+            if let Err(err) = std::fs::remove_file(&file_path) {
+                error!("Failed to delete recording {}: {err}", file_path.display());
+            }
+        }
+        Some(recording)
+    }
+
+    pub async fn update<F: FnOnce(&mut Recording)>(&self, id: &str, update: F) {
+        let active = {
+            let mut recordings = self.recordings.write().await;
+            let Some(recording) = recordings.iter_mut().find(|recording| recording.id == id) else { return };
+            update(recording);
+            let active = recording.is_active();
+            self.persist(&recordings);
+            active
+        };
+        if !active {
+            self.cancel_tokens.write().await.remove(id);
+        }
+    }
+
+    /// The size is updated while recording, it is persisted with the next state change.
+    pub async fn set_size(&self, id: &str, size: u64) {
+        if let Some(recording) = self.recordings.write().await.iter_mut().find(|recording| recording.id == id) {
+            recording.size = size;
+        }
+    }
+
+    /// Returns `true` if the recordings exceed the quota.
+    pub async fn is_quota_exceeded(&self) -> bool {
+        match self.quota_bytes {
+            Some(quota) => used_bytes(&self.recordings.read().await) >= quota,
+            None => false,
+        }
+    }
+
+    fn persist(&self, recordings: &[Recording]) {
+        if let Err(err) = store_recordings(&self.dir, recordings) {
+            error!("Failed to store recordings in {}: {err}", self.dir.display());
+        }
+    }
+}
+
+fn used_bytes(recordings: &[Recording]) -> u64 {
+    recordings.iter().map(|recording| recording.size).sum()
+}
+
+fn load_recordings(dir: &Path) -> Vec<Recording> {
+    let path = dir.join(RECORDINGS_FILE);
+    if !path.exists() {
+        return Vec::new();
+    }
+    match std::fs::read(&path).map_err(|err| err.to_string())
+        .and_then(|content| serde_json::from_slice(&content).map_err(|err| err.to_string())) {
+        Ok(recordings) => recordings,
+        Err(err) => {
+            error!("Failed to read recordings {}: {err}", path.display());
+            Vec::new()
+        }
+    }
+}
+
+fn store_recordings(dir: &Path, recordings: &[Recording]) -> Result<(), String> {
+    std::fs::create_dir_all(dir).map_err(|err| err.to_string())?;
+    let content = serde_json::to_vec_pretty(recordings).map_err(|err| err.to_string())?;
+    std::fs::write(dir.join(RECORDINGS_FILE), content).map_err(|err| err.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{used_bytes, Recording, RecordingState};
+
+    #[test]
+    fn test_recording_new() {
+        let recording = Recording::new("live", 42, "Das Erste HD", 1_750_000_000, 1_750_003_600);
+        assert_eq!(recording.state, RecordingState::Scheduled);
+        assert_eq!(recording.filename, "Das_Erste_HD_42_20250615_1506.ts");
+        assert_eq!(recording.id, Recording::new("live", 42, "Other", 1_750_000_000, 1_750_000_001).id);
+        assert_ne!(recording.id, Recording::new("live", 43, "Das Erste HD", 1_750_000_000, 1_750_003_600).id);
+
+        let mut finished = recording.clone();
+        finished.size = 5;
+        assert_eq!(used_bytes(&[recording, finished]), 5);
+    }
+}
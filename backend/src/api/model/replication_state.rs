@@ -0,0 +1,38 @@
+use std::sync::atomic::{AtomicBool, AtomicI64, Ordering};
+use tokio::sync::Notify;
+
+/// State of a standby instance, shared by the replication task and the replication api.
+#[derive(Debug, Default)]
+pub struct ReplicationState {
+    promoted: AtomicBool,
+    // unix timestamp of the last successful sync, 0 before the first one
+    last_sync: AtomicI64,
+    promote_request: Notify,
+}
+
+impl ReplicationState {
+    pub fn is_promoted(&self) -> bool {
+        self.promoted.load(Ordering::Relaxed)
+    }
+
+    pub fn set_promoted(&self) {
+        self.promoted.store(true, Ordering::Relaxed);
+    }
+
+    pub fn last_sync(&self) -> i64 {
+        self.last_sync.load(Ordering::Relaxed)
+    }
+
+    pub fn set_last_sync(&self, ts: i64) {
+        self.last_sync.store(ts, Ordering::Relaxed);
+    }
+
+    /// Asks the replication task to promote the standby.
+    pub fn request_promotion(&self) {
+        self.promote_request.notify_one();
+    }
+
+    pub async fn promotion_requested(&self) {
+        self.promote_request.notified().await;
+    }
+}
@@ -42,6 +42,12 @@ pub struct PlaylistRequest {
     pub source_name: Option<String>,
 }
 
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct PlaylistComparisonRequest {
+    pub left: u16,
+    pub right: u16,
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize, Default)]
 pub struct UserApiRequest {
     #[serde(default)]
@@ -70,6 +76,8 @@ pub struct UserApiRequest {
     pub stream: String,
     #[serde(default)]
     pub duration: String,
+    #[serde(default)]
+    pub position: String,
     #[serde(default, alias = "type")]
     pub content_type: String,
 }
\ No newline at end of file
@@ -0,0 +1,328 @@
+use crate::api::model::streams::active_client_stream::ClientStreamControl;
+use shared::utils::current_time_secs;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const TS_PACKET_SIZE: usize = 188;
+const TS_SYNC_BYTE: u8 = 0x47;
+const TS_NULL_PID: u16 = 0x1FFF;
+// the current rate is measured over windows of this duration
+const RATE_WINDOW: Duration = Duration::from_secs(5);
+
+/// Counts the continuity counter errors of a mpeg-ts stream, other streams are not checked.
+#[derive(Default)]
+struct TsContinuityCheck {
+    // none until the first chunk decided if the stream is mpeg-ts
+    is_ts: Option<bool>,
+    remainder: Vec<u8>,
+    counters: HashMap<u16, u8>,
+}
+
+impl TsContinuityCheck {
+    fn is_synced(data: &[u8], offset: usize) -> bool {
+        data[offset] == TS_SYNC_BYTE && data.get(offset + TS_PACKET_SIZE).is_none_or(|byte| *byte == TS_SYNC_BYTE)
+    }
+
+    // Returns the discontinuities of the chunk.
+    fn check(&mut self, chunk: &[u8]) -> u64 {
+        if self.is_ts == Some(false) || chunk.is_empty() {
+            return 0;
+        }
+        let mut data = std::mem::take(&mut self.remainder);
+        data.extend_from_slice(chunk);
+        if self.is_ts.is_none() {
+            if data.len() < TS_PACKET_SIZE + 1 {
+                self.remainder = data;
+                return 0;
+            }
+            self.is_ts = Some(Self::is_synced(&data, 0));
+            if self.is_ts == Some(false) {
+                return 0;
+            }
+        }
+        let mut discontinuities = 0;
+        let mut offset = 0;
+        while offset + TS_PACKET_SIZE <= data.len() {
+            if !Self::is_synced(&data, offset) {
+                // lost sync, the packets until the next sync byte are lost too
+                offset += 1;
+                continue;
+            }
+            if self.check_packet(&data[offset..offset + TS_PACKET_SIZE]) {
+                discontinuities += 1;
+            }
+            offset += TS_PACKET_SIZE;
+        }
+        self.remainder = data.split_off(offset);
+        discontinuities
+    }
+
+    // Returns true if the continuity counter of the packet does not follow the previous packet of the pid.
+    fn check_packet(&mut self, packet: &[u8]) -> bool {
+        let pid = (u16::from(packet[1] & 0x1F) << 8) | u16::from(packet[2]);
+        if pid == TS_NULL_PID {
+            return false;
+        }
+        let adaptation_field_control = (packet[3] >> 4) & 0x03;
+        let counter = packet[3] & 0x0F;
+        // the discontinuity indicator announces a counter reset, like after a provider reconnect
+        if adaptation_field_control & 0x02 != 0 && packet[4] > 0 && packet[5] & 0x80 != 0 {
+            self.counters.insert(pid, counter);
+            return false;
+        }
+        // the counter only increments for packets with payload
+        if adaptation_field_control & 0x01 == 0 {
+            return false;
+        }
+        match self.counters.insert(pid, counter) {
+            // a duplicate packet has the same counter
+            Some(last) => counter != last && counter != (last + 1) & 0x0F,
+            None => false,
+        }
+    }
+}
+
+struct StreamRate {
+    window_start: Instant,
+    window_bytes: u64,
+    bytes_per_sec: u64,
+}
+
+/// Health of a client stream, shows which providers deliver choppy streams.
+pub struct StreamHealth {
+    started: Instant,
+    bytes: AtomicU64,
+    discontinuities: AtomicU64,
+    reconnects: Option<Arc<AtomicU32>>,
+    rate: Mutex<StreamRate>,
+    continuity: Mutex<TsContinuityCheck>,
+}
+
+impl StreamHealth {
+    pub fn new(reconnects: Option<Arc<AtomicU32>>) -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            bytes: AtomicU64::new(0),
+            discontinuities: AtomicU64::new(0),
+            reconnects,
+            rate: Mutex::new(StreamRate { window_start: now, window_bytes: 0, bytes_per_sec: 0 }),
+            continuity: Mutex::new(TsContinuityCheck::default()),
+        }
+    }
+
+    pub fn add(&self, chunk: &[u8]) {
+        let len = chunk.len() as u64;
+        self.bytes.fetch_add(len, Ordering::Relaxed);
+        if let Ok(mut rate) = self.rate.lock() {
+            rate.window_bytes += len;
+            let elapsed = rate.window_start.elapsed();
+            if elapsed >= RATE_WINDOW {
+                rate.bytes_per_sec = rate.window_bytes * 1000 / u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX).max(1);
+                rate.window_start = Instant::now();
+                rate.window_bytes = 0;
+            }
+        }
+        if let Ok(mut continuity) = self.continuity.lock() {
+            let discontinuities = continuity.check(chunk);
+            if discontinuities > 0 {
+                self.discontinuities.fetch_add(discontinuities, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn get_bytes_per_sec(&self) -> u64 {
+        let rate = self.rate.lock().map_or(0, |rate| {
+            // a stalled stream has no new chunk which closes the window
+            if rate.window_start.elapsed() >= RATE_WINDOW * 2 { 0 } else { rate.bytes_per_sec }
+        });
+        if rate > 0 || self.started.elapsed() >= RATE_WINDOW {
+            rate
+        } else {
+            self.bytes.load(Ordering::Relaxed) * 1000 / u64::try_from(self.started.elapsed().as_millis()).unwrap_or(u64::MAX).max(1)
+        }
+    }
+}
+
+/// Health of an active stream for the status api.
+#[derive(Clone, Debug, serde::Serialize)]
+pub struct StreamHealthInfo {
+    pub username: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub provider: Option<String>,
+    pub ts: u64,
+    pub duration_secs: u64,
+    pub bytes: u64,
+    pub bytes_per_sec: u64,
+    pub discontinuities: u64,
+    pub reconnects: u32,
+}
+
+struct StreamHealthEntry {
+    username: String,
+    session: Option<String>,
+    provider: Option<String>,
+    ts: u64,
+    health: Arc<StreamHealth>,
+    control: Arc<ClientStreamControl>,
+}
+
+/// The health of all active client streams, the streams can be closed by user session.
+#[derive(Default)]
+pub struct StreamHealthRegistry {
+    next_id: AtomicU64,
+    streams: Mutex<HashMap<u64, StreamHealthEntry>>,
+}
+
+/// Removes the stream from the registry when the client stream is dropped.
+pub struct StreamHealthGuard {
+    registry: Arc<StreamHealthRegistry>,
+    id: u64,
+}
+
+impl Drop for StreamHealthGuard {
+    fn drop(&mut self) {
+        if let Ok(mut streams) = self.registry.streams.lock() {
+            streams.remove(&self.id);
+        }
+    }
+}
+
+impl StreamHealthRegistry {
+    pub(in crate::api) fn register(registry: &Arc<Self>, username: &str, session: Option<&str>, provider: Option<&str>,
+                                       health: &Arc<StreamHealth>, control: &Arc<ClientStreamControl>) -> StreamHealthGuard {
+        let id = registry.next_id.fetch_add(1, Ordering::Relaxed);
+        if let Ok(mut streams) = registry.streams.lock() {
+            streams.insert(id, StreamHealthEntry {
+                username: username.to_string(),
+                session: session.map(ToString::to_string),
+                provider: provider.map(ToString::to_string),
+                ts: current_time_secs(),
+                health: Arc::clone(health),
+                control: Arc::clone(control),
+            });
+        }
+        StreamHealthGuard { registry: Arc::clone(registry), id }
+    }
+
+    /// Returns the health of the active streams, sorted by username and start time.
+    pub fn get_stream_health_info(&self) -> Vec<StreamHealthInfo> {
+        let mut result: Vec<StreamHealthInfo> = self.streams.lock().map(|streams| streams.values()
+            .filter(|entry| !entry.control.is_closed())
+            .map(|entry| StreamHealthInfo {
+                username: entry.username.clone(),
+                session: entry.session.clone(),
+                provider: entry.provider.clone(),
+                ts: entry.ts,
+                duration_secs: entry.health.started.elapsed().as_secs(),
+                bytes: entry.health.bytes.load(Ordering::Relaxed),
+                bytes_per_sec: entry.health.get_bytes_per_sec(),
+                discontinuities: entry.health.discontinuities.load(Ordering::Relaxed),
+                reconnects: entry.health.reconnects.as_ref().map_or(0, |reconnects| reconnects.load(Ordering::Relaxed)),
+            }).collect()).unwrap_or_default();
+        result.sort_by(|a, b| a.username.cmp(&b.username).then(a.ts.cmp(&b.ts)));
+        result
+    }
+
+    /// Closes the streams of the user session and releases their connections, returns the number of closed streams.
+    pub fn close_session(&self, username: &str, session: &str) -> usize {
+        let controls: Vec<Arc<ClientStreamControl>> = self.streams.lock().map(|streams| streams.values()
+            .filter(|entry| entry.username == username && entry.session.as_deref() == Some(session) && !entry.control.is_closed())
+            .map(|entry| Arc::clone(&entry.control))
+            .collect()).unwrap_or_default();
+        // the lock is released, the connection guards are dropped while closing
+        for control in &controls {
+            control.close();
+        }
+        controls.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::api_utils::StreamDetails;
+    use crate::api::endpoints::v1_api::sessions_close;
+    use crate::api::main_api::create_shared_data;
+    use crate::api::model::stream_health::{TsContinuityCheck, TS_PACKET_SIZE, TS_SYNC_BYTE};
+    use crate::api::model::streams::active_client_stream::ActiveClientStream;
+    use crate::model::{Config, ProxyUserCredentials, SourcesConfig};
+    use axum::response::IntoResponse;
+    use futures::StreamExt;
+    use shared::model::UserConnectionPermission;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn create_packet(pid: u16, counter: u8) -> Vec<u8> {
+        let mut packet = vec![0xFF; TS_PACKET_SIZE];
+        packet[0] = TS_SYNC_BYTE;
+        packet[1] = u8::try_from(pid >> 8).unwrap_or_default() & 0x1F;
+        packet[2] = u8::try_from(pid & 0xFF).unwrap_or_default();
+        packet[3] = 0x10 | (counter & 0x0F);
+        packet
+    }
+
+    #[test]
+    fn test_ts_continuity_check() {
+        let mut data = vec![];
+        for counter in [14, 15, 0, 0, 1, 3] {
+            data.extend(create_packet(0x100, counter));
+            data.extend(create_packet(0x101, counter));
+        }
+        let mut check = TsContinuityCheck::default();
+        // chunks are not aligned to the packets
+        let discontinuities: u64 = data.chunks(100).map(|chunk| check.check(chunk)).sum();
+        assert_eq!(discontinuities, 2);
+
+        let mut check = TsContinuityCheck::default();
+        assert_eq!(check.check(&[0x00; 1000]), 0);
+        assert_eq!(check.is_ts, Some(false));
+    }
+
+    #[tokio::test]
+    async fn test_close_session() {
+        let mut sources: SourcesConfig = serde_yaml::from_str(r#"
+sources:
+  - inputs:
+      - { name: provider, type: m3u, url: 'http://127.0.0.1/playlist.m3u', max_connections: 1 }
+    targets:
+      - { name: target, filter: 'Group ~ ".*"', output: [{ type: m3u }] }
+"#).unwrap();
+        sources.prepare(true).unwrap();
+        let cfg = Arc::new(Config { sources, ..Default::default() });
+        let app_state = Arc::new(create_shared_data(&cfg).await);
+        let user: ProxyUserCredentials = serde_json::from_value(serde_json::json!({"username": "user", "password": "secret", "max_connections": 1})).unwrap();
+        app_state.active_users.create_user_session(&user, "token", 1, "provider", "http://127.0.0.1/1", UserConnectionPermission::Allowed).await;
+
+        let mut stream_details = StreamDetails::from_stream(futures::stream::pending().boxed());
+        stream_details.provider_connection_guard = Some(app_state.active_provider.acquire_connection("provider").await);
+        let mut stream = ActiveClientStream::new(stream_details, &app_state, &user, Some("token"), UserConnectionPermission::Allowed).await;
+        assert_eq!(app_state.active_users.user_connections("user").await, 1);
+        assert!(app_state.active_provider.active_connections().await.is_some_and(|connections| connections.get("provider") == Some(&1)));
+        assert_eq!(app_state.stream_health.get_stream_health_info().len(), 1);
+
+        // the client waits for the next chunk of the provider
+        let client = tokio::spawn(async move { stream.next().await.is_none() });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!client.is_finished());
+
+        let response = sessions_close(axum::extract::Path(("user".to_string(), "token".to_string())), axum::extract::State(Arc::clone(&app_state))).await.into_response();
+        assert_eq!(response.status(), axum::http::StatusCode::OK);
+        // the stream is closed but not yet dropped
+        assert!(app_state.stream_health.get_stream_health_info().is_empty());
+        let end_of_stream = tokio::time::timeout(Duration::from_secs(1), client).await.unwrap().unwrap();
+        assert!(end_of_stream, "the pending read ends with the end of the stream");
+
+        // the guards are released by spawned tasks
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(app_state.active_users.user_connections("user").await, 0);
+        assert!(app_state.active_provider.active_connections().await.is_none());
+        assert!(app_state.active_users.get_active_user_info().await.iter().all(|info| info.sessions.is_empty()));
+
+        let response = sessions_close(axum::extract::Path(("user".to_string(), "unknown".to_string())), axum::extract::State(Arc::clone(&app_state))).await.into_response();
+        assert_eq!(response.status(), axum::http::StatusCode::NOT_FOUND);
+    }
+}
@@ -6,38 +6,117 @@ use crate::api::model::app_state::AppState;
 use crate::api::model::stream::BoxedProviderStream;
 use crate::api::model::stream_error::StreamError;
 use crate::api::model::streams::transport_stream_buffer::TransportStreamBuffer;
+use crate::api::model::traffic_counter::TrafficCounter;
+use crate::api::model::stream_health::{StreamHealth, StreamHealthGuard, StreamHealthRegistry};
 use crate::model::{ProxyUserCredentials};
 use bytes::Bytes;
 use futures::Stream;
 use log::{error, info};
 use std::pin::Pin;
-use std::sync::atomic::AtomicU8;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
 use std::task::{Poll, Waker};
+use std::time::{Duration, Instant};
 use crate::api::model::streams::timed_client_stream::TimedClientStream;
 use futures::{StreamExt};
 use shared::model::UserConnectionPermission;
+use crate::tools::atomic_once_flag::AtomicOnceFlag;
 
 const INNER_STREAM: u8 = 0_u8;
 const GRACE_BLOCK_STREAM: u8 = 1_u8;
 const USER_EXHAUSTED_STREAM: u8 = 2_u8;
 const PROVIDER_EXHAUSTED_STREAM: u8 = 3_u8;
+const IDLE_CHECK_INTERVAL_MILLIS: u64 = 5_000;
 
-pub(in crate::api) struct ActiveClientStream {
-    inner: BoxedProviderStream,
-    send_custom_stream_flag: Option<Arc<AtomicU8>>,
+// The connection guards are released when the stream is dropped or the client is idle.
+struct ConnectionGuards {
     #[allow(unused)]
     user_connection_guard: Option<UserConnectionGuard>,
     #[allow(dead_code)]
     provider_connection_guard: Option<ProviderConnectionGuard>,
+}
+
+/// Closes a client stream from outside, like for an idle client or by the admin api.
+/// The connections are released at once, the client gets the end of the stream with its next read.
+pub(in crate::api) struct ClientStreamControl {
+    closed: AtomicBool,
+    connection_guards: Mutex<Option<ConnectionGuards>>,
+    reconnect_flag: Option<Arc<AtomicOnceFlag>>,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl ClientStreamControl {
+    pub fn close(&self) {
+        if self.closed.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        if let Some(flag) = self.reconnect_flag.as_ref() {
+            flag.notify();
+        }
+        let guards = self.connection_guards.lock().ok().and_then(|mut guards| guards.take());
+        drop(guards);
+        let waker = self.waker.lock().ok().and_then(|mut waker| waker.take());
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    fn set_waker(&self, waker: &Waker) {
+        if let Ok(mut waker_lock) = self.waker.lock() {
+            if waker_lock.as_ref().is_none_or(|current| !current.will_wake(waker)) {
+                *waker_lock = Some(waker.clone());
+            }
+        }
+    }
+}
+
+// A client which does not read is detected by a delivered chunk which is not followed by the next poll,
+// a stream waiting for the provider is not idle.
+struct ClientIdleWatch {
+    started: Instant,
+    // millis since start of the last delivered chunk
+    last_delivery_millis: AtomicU64,
+    waiting_for_client: AtomicBool,
+}
+
+impl ClientIdleWatch {
+    fn elapsed_millis(&self) -> u64 {
+        u64::try_from(self.started.elapsed().as_millis()).unwrap_or(u64::MAX)
+    }
+
+    fn delivered(&self) {
+        self.last_delivery_millis.store(self.elapsed_millis(), Ordering::Relaxed);
+        self.waiting_for_client.store(true, Ordering::Relaxed);
+    }
+
+    fn is_idle(&self, timeout_millis: u64) -> bool {
+        self.waiting_for_client.load(Ordering::Relaxed)
+            && self.elapsed_millis().saturating_sub(self.last_delivery_millis.load(Ordering::Relaxed)) >= timeout_millis
+    }
+}
+
+pub(in crate::api) struct ActiveClientStream {
+    inner: BoxedProviderStream,
+    send_custom_stream_flag: Option<Arc<AtomicU8>>,
+    control: Arc<ClientStreamControl>,
+    idle_watch: Option<Arc<ClientIdleWatch>>,
     custom_video: (Option<TransportStreamBuffer>, Option<TransportStreamBuffer>),
     waker: Arc<Mutex<Option<Waker>>>,
+    traffic: Arc<TrafficCounter>,
+    health: Arc<StreamHealth>,
+    #[allow(dead_code)]
+    health_guard: StreamHealthGuard,
 }
 
 impl ActiveClientStream {
     pub(crate) async fn new(mut stream_details: StreamDetails,
                             app_state: &AppState,
                             user: &ProxyUserCredentials,
+                            session_token: Option<&str>,
                             connection_permission: UserConnectionPermission) -> Self {
         let active_user = app_state.active_users.clone();
         let active_provider = app_state.active_provider.clone();
@@ -45,8 +124,7 @@ impl ActiveClientStream {
             error!("Something is wrong this should not happen");
         }
         let grant_user_grace_period = connection_permission == UserConnectionPermission::GracePeriod;
-        let username = user.username.as_str();
-        let user_connection_guard = Some(active_user.add_connection(username, user.max_connections).await);
+        let user_connection_guard = Some(active_user.add_connection(user).await);
         let cfg = &app_state.config;
         let waker = Arc::new(Mutex::new(None));
         let waker_clone = Arc::clone(&waker);
@@ -71,16 +149,68 @@ impl ActiveClientStream {
             }
         };
 
+        let health = Arc::new(StreamHealth::new(stream_details.reconnect_count.take()));
+        let provider_name = stream_details.provider_connection_guard.as_ref().and_then(ProviderConnectionGuard::get_provider_name)
+            .or_else(|| stream_details.input_name.clone());
+        let control = Arc::new(ClientStreamControl {
+            closed: AtomicBool::new(false),
+            connection_guards: Mutex::new(Some(ConnectionGuards {
+                user_connection_guard,
+                provider_connection_guard: stream_details.provider_connection_guard,
+            })),
+            reconnect_flag: stream_details.reconnect_flag.clone(),
+            waker: Mutex::new(None),
+        });
+        let health_guard = StreamHealthRegistry::register(&app_state.stream_health, &user.username, session_token, provider_name.as_deref(), &health, &control);
+        let idle_timeout_secs = cfg.reverse_proxy.as_ref().and_then(|reverse_proxy| reverse_proxy.stream.as_ref())
+            .map_or(0, |stream| stream.idle_timeout_secs);
+        let idle_watch = (idle_timeout_secs > 0).then(|| Self::watch_idle_client(idle_timeout_secs, &user.username, &control));
+
         Self {
             inner: stream,
-            user_connection_guard,
-            provider_connection_guard: stream_details.provider_connection_guard,
+            control,
+            idle_watch,
             send_custom_stream_flag: grace_stop_flag,
             custom_video,
             waker,
+            traffic: Arc::clone(&app_state.traffic),
+            health,
+            health_guard,
         }
     }
 
+    // Closes the stream and releases the connections when the client read nothing within the timeout.
+    fn watch_idle_client(idle_timeout_secs: u64, username: &str, control: &Arc<ClientStreamControl>) -> Arc<ClientIdleWatch> {
+        let idle_watch = Arc::new(ClientIdleWatch {
+            started: Instant::now(),
+            last_delivery_millis: AtomicU64::new(0),
+            waiting_for_client: AtomicBool::new(false),
+        });
+        let timeout_millis = idle_timeout_secs * 1000;
+        let check_interval = Duration::from_millis((timeout_millis / 2).clamp(100, IDLE_CHECK_INTERVAL_MILLIS));
+        let idle_watch_weak = Arc::downgrade(&idle_watch);
+        let control_weak = Arc::downgrade(control);
+        let username = username.to_string();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(check_interval).await;
+                // the stream is dropped
+                let (Some(idle_watch), Some(control)) = (idle_watch_weak.upgrade(), control_weak.upgrade()) else {
+                    break;
+                };
+                if control.is_closed() {
+                    break;
+                }
+                if idle_watch.is_idle(timeout_millis) {
+                    info!("Closing stream of user {username}, the client read nothing for {idle_timeout_secs} seconds");
+                    control.close();
+                    break;
+                }
+            }
+        });
+        idle_watch
+    }
+
     fn stream_grace_period(stream_details: &StreamDetails,
                            user_grace_period: bool,
                            user: &ProxyUserCredentials,
@@ -163,13 +293,40 @@ impl Stream for ActiveClientStream {
     type Item = Result<Bytes, StreamError>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut std::task::Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.control.is_closed() {
+            return Poll::Ready(None);
+        }
+        if let Some(idle_watch) = self.idle_watch.as_ref() {
+            idle_watch.waiting_for_client.store(false, Ordering::Relaxed);
+        }
+        let poll = self.poll_stream(cx);
+        match &poll {
+            Poll::Ready(Some(_)) => {
+                if let Some(idle_watch) = self.idle_watch.as_ref() {
+                    idle_watch.delivered();
+                }
+            }
+            Poll::Pending => self.control.set_waker(cx.waker()),
+            Poll::Ready(None) => {}
+        }
+        poll
+    }
+}
+
+impl ActiveClientStream {
+    fn poll_stream(&mut self, cx: &mut std::task::Context<'_>) -> Poll<Option<Result<Bytes, StreamError>>> {
         let flag = match &self.send_custom_stream_flag {
             Some(flag) => flag.load(std::sync::atomic::Ordering::SeqCst),
             None => INNER_STREAM,
         };
 
         if flag == INNER_STREAM {
-            return Pin::new(&mut self.inner).poll_next(cx);
+            let poll = Pin::new(&mut self.inner).poll_next(cx);
+            if let Poll::Ready(Some(Ok(bytes))) = &poll {
+                self.traffic.add(bytes.len());
+                self.health.add(bytes);
+            }
+            return poll;
         }
 
         if flag == GRACE_BLOCK_STREAM {
@@ -2,11 +2,13 @@ pub(in crate::api) mod provider_stream;
 pub(in crate::api) mod persist_pipe_stream;
 pub(in crate::api) mod provider_stream_factory;
 pub(in crate::api) mod shared_stream_manager;
+pub(in crate::api) mod shared_hls_cache;
 pub(in crate::api) mod active_client_stream;
 pub(in crate::api) mod throttled_stream;
 mod timed_client_stream;
 mod buffered_stream;
 mod client_stream;
 mod custom_video_stream;
+mod rtsp_stream;
 pub(in crate) mod transport_stream_buffer;
 // mod chunked_buffer;
@@ -13,7 +13,8 @@ pub enum CustomVideoStreamType {
     ChannelUnavailable,
     UserConnectionsExhausted,
     ProviderConnectionsExhausted,
-    UserAccountExpired
+    UserAccountExpired,
+    Maintenance,
 }
 
 fn create_video_stream(video_buffer: Option<&TransportStreamBuffer>, headers: &[(String, String)], log_message: &str) -> ProviderStreamResponse {
@@ -49,12 +50,18 @@ pub fn create_user_account_expired_stream(cfg: &Config, headers: &[(String, Stri
     create_video_stream(video, headers, "Streaming response user account expired")
 }
 
+pub fn create_maintenance_stream(cfg: &Config, headers: &[(String, String)]) -> ProviderStreamResponse {
+    let video = cfg.t_custom_stream_response.as_ref().and_then(|c| c.maintenance.as_ref());
+    create_video_stream(video, headers, "Streaming response maintenance")
+}
+
 pub fn create_custom_video_stream_response(config: &Config, video_response: CustomVideoStreamType) -> impl axum::response::IntoResponse + Send {
     if let (Some(stream), Some((headers, status_code, _))) = match video_response {
         CustomVideoStreamType::ChannelUnavailable => create_channel_unavailable_stream(config, &[], StatusCode::BAD_REQUEST),
         CustomVideoStreamType::UserConnectionsExhausted => create_user_connections_exhausted_stream(config, &[]),
         CustomVideoStreamType::ProviderConnectionsExhausted => create_provider_connections_exhausted_stream(config, &[]),
         CustomVideoStreamType::UserAccountExpired => create_user_account_expired_stream(config, &[]),
+        CustomVideoStreamType::Maintenance => create_maintenance_stream(config, &[]),
     } {
         let mut builder = axum::response::Response::builder()
             .status(status_code);
@@ -5,20 +5,23 @@ use crate::api::model::stream_error::StreamError;
 use crate::api::model::streams::buffered_stream::BufferedStream;
 use crate::api::model::streams::client_stream::ClientStream;
 use crate::api::model::streams::provider_stream::{create_channel_unavailable_stream, get_header_filter_for_item_type};
+use crate::api::model::streams::rtsp_stream::create_rtsp_stream;
 use crate::api::model::streams::timed_client_stream::TimedClientStream;
 use shared::model::PlaylistItemType;
-use crate::model::{Config, DEFAULT_USER_AGENT};
+use crate::model::{Config, StreamChaosConfig, DEFAULT_USER_AGENT};
 use crate::tools::atomic_once_flag::AtomicOnceFlag;
+use crate::utils::rate_limiter::{acquire_provider_permit, ProviderRateLimiter};
+use crate::utils::cookie_jar::ProviderCookieJar;
 use crate::utils::request::{classify_content_type, get_request_headers, sanitize_sensitive_info, MimeCategory};
 use crate::utils::{debug_if_enabled};
 use shared::utils::{filter_request_header};
 use futures::stream::{self};
 use futures::{StreamExt, TryStreamExt};
 use log::{debug, log_enabled, warn};
-use reqwest::header::{HeaderMap, RANGE};
+use reqwest::header::{HeaderMap, HeaderValue, COOKIE, RANGE};
 use reqwest::StatusCode;
 use std::collections::HashMap;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use url::Url;
@@ -27,6 +30,7 @@ use url::Url;
 pub const STREAM_QUEUE_SIZE: usize = 4096; // mpsc channel holding messages. with possible 8192byte chunks
 const RETRY_SECONDS: u64 = 5;
 const ERR_MAX_RETRY_COUNT: u32 = 5;
+const RTSP_SCHEME: &str = "rtsp";
 
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone)]
@@ -42,6 +46,10 @@ pub struct ProviderStreamFactoryOptions {
     headers: HeaderMap,
     range_bytes: Arc<Option<AtomicUsize>>,
     reconnect_flag: Arc<AtomicOnceFlag>,
+    // provider reconnects of the stream, shown in the stream health
+    reconnect_count: Arc<AtomicU32>,
+    rate_limiter: Option<Arc<ProviderRateLimiter>>,
+    cookie_jar: Option<Arc<ProviderCookieJar>>,
 }
 
 impl ProviderStreamFactoryOptions {
@@ -52,6 +60,7 @@ impl ProviderStreamFactoryOptions {
         stream_url: &Url,
         req_headers: &HeaderMap,
         input_headers: Option<&HashMap<String, String>>,
+        rate_limiter: Option<Arc<ProviderRateLimiter>>,
     ) -> Self {
         let buffer_size = if stream_options.buffer_enabled { stream_options.buffer_size } else { STREAM_QUEUE_SIZE };
         let filter_header = get_header_filter_for_item_type(item_type);
@@ -75,12 +84,21 @@ impl ProviderStreamFactoryOptions {
             buffer_size,
             share_stream,
             reconnect_flag: Arc::new(AtomicOnceFlag::new()),
+            reconnect_count: Arc::new(AtomicU32::new(0)),
             url,
             headers,
             range_bytes,
+            rate_limiter,
+            cookie_jar: None,
         }
     }
 
+    /// The session cookies of the provider connection are sent with the stream requests.
+    pub(crate) fn with_cookie_jar(mut self, cookie_jar: Option<Arc<ProviderCookieJar>>) -> Self {
+        self.cookie_jar = cookie_jar;
+        self
+    }
+
     #[inline]
     fn is_piped(&self) -> bool {
         self.pipe_stream
@@ -106,6 +124,11 @@ impl ProviderStreamFactoryOptions {
         Arc::clone(&self.reconnect_flag)
     }
 
+    #[inline]
+    pub fn get_reconnect_count_clone(&self) -> Arc<AtomicU32> {
+        Arc::clone(&self.reconnect_count)
+    }
+
     #[inline]
     pub fn cancel_reconnect(&self) {
         self.reconnect_flag.notify();
@@ -182,7 +205,7 @@ fn get_request_range_start_bytes(req_headers: &HashMap<String, Vec<u8>>) -> Opti
 // }
 
 
-fn prepare_client(request_client: &Arc<reqwest::Client>, stream_options: &ProviderStreamFactoryOptions) -> (reqwest::RequestBuilder, bool) {
+fn prepare_client(request_client: &Arc<reqwest::Client>, stream_options: &ProviderStreamFactoryOptions) -> (reqwest::RequestBuilder, bool, bool) {
     let url = stream_options.get_url();
     let range_start = stream_options.get_total_bytes_send();
     let original_headers = stream_options.get_headers();
@@ -216,6 +239,17 @@ fn prepare_client(request_client: &Arc<reqwest::Client>, stream_options: &Provid
         headers.insert(axum::http::header::USER_AGENT, axum::http::header::HeaderValue::from_static(DEFAULT_USER_AGENT));
     }
 
+    // the session cookies are appended to configured cookies
+    let session_cookies = stream_options.cookie_jar.as_ref().and_then(|jar| jar.get_cookie_header(url));
+    let with_cookies = session_cookies.is_some();
+    if let Some(cookies) = session_cookies {
+        let cookie_header = match headers.get(COOKIE) {
+            Some(existing) => HeaderValue::from_bytes(&[existing.as_bytes(), b"; ", cookies.as_bytes()].concat()).unwrap_or(cookies),
+            None => cookies,
+        };
+        headers.insert(COOKIE, cookie_header);
+    }
+
     let partial = if let Some(range) = range_start {
         let range_header = format!("bytes={range}-");
         if let Ok(header_value) = axum::http::header::HeaderValue::from_str(&range_header) {
@@ -233,14 +267,119 @@ fn prepare_client(request_client: &Arc<reqwest::Client>, stream_options: &Provid
 
     let request_builder = request_client.get(url.clone()).headers(headers);
 
-    (request_builder, partial)
+    (request_builder, partial, with_cookies)
+}
+
+fn get_chaos_config(cfg: &Config) -> Option<&StreamChaosConfig> {
+    cfg.reverse_proxy.as_ref()
+        .and_then(|reverse_proxy| reverse_proxy.stream.as_ref())
+        .and_then(|stream| stream.chaos.as_ref())
+        .filter(|chaos| chaos.enabled)
+}
+
+fn provider_error_status_response(cfg: &Config, stream_options: &ProviderStreamFactoryOptions, status: StatusCode) -> Result<Option<ProviderStreamFactoryResponse>, StatusCode> {
+    if status.is_client_error() {
+        debug!("Client error status response : {status}");
+        return match status {
+            StatusCode::NOT_FOUND
+            | StatusCode::FORBIDDEN
+            | StatusCode::UNAUTHORIZED
+            | StatusCode::METHOD_NOT_ALLOWED
+            | StatusCode::BAD_REQUEST => {
+                if let (Some(boxed_provider_stream), response_info) =
+                    create_channel_unavailable_stream(cfg, &get_response_headers(stream_options.get_headers()), StatusCode::BAD_GATEWAY)
+                {
+                    Ok(Some((boxed_provider_stream, response_info)))
+                } else {
+                    Err(StatusCode::SERVICE_UNAVAILABLE)
+                }
+            }
+            _ => Err(status)
+        };
+    }
+    if status.is_server_error() {
+        debug!("Server error status response : {status}");
+        return match status {
+            StatusCode::INTERNAL_SERVER_ERROR |
+            StatusCode::BAD_GATEWAY |
+            StatusCode::SERVICE_UNAVAILABLE |
+            StatusCode::GATEWAY_TIMEOUT => {
+                if let (Some(boxed_provider_stream), response_info) =
+                    create_channel_unavailable_stream(cfg, &get_response_headers(stream_options.get_headers()), StatusCode::BAD_GATEWAY)
+                {
+                    Ok(Some((boxed_provider_stream, response_info)))
+                } else {
+                    Err(StatusCode::SERVICE_UNAVAILABLE)
+                }
+            }
+            _ => Err(status)
+        }
+    }
+    Err(status)
+}
+
+// rtsp streams are muxed to MPEG-TS, they are handled like a provider response with content type `video/mp2t`
+async fn rtsp_stream_request(cfg: &Config, stream_options: &ProviderStreamFactoryOptions) -> Result<Option<ProviderStreamFactoryResponse>, StatusCode> {
+    let url = stream_options.get_url();
+    let user_agent = stream_options.get_headers().get(axum::http::header::USER_AGENT)
+        .and_then(|value| value.to_str().ok()).unwrap_or(DEFAULT_USER_AGENT);
+    let permit = acquire_provider_permit(stream_options.rate_limiter.as_ref()).await;
+    let result = create_rtsp_stream(url, user_agent).await;
+    drop(permit);
+    match result {
+        Ok(rtsp_stream) => {
+            let rtsp_stream = if stream_options.get_reconnect_force_secs() > 0 {
+                TimedClientStream::new(rtsp_stream, stream_options.get_reconnect_force_secs()).boxed()
+            } else {
+                rtsp_stream
+            };
+            let response_headers = vec![(axum::http::header::CONTENT_TYPE.to_string(), "video/mp2t".to_string())];
+            Ok(Some((rtsp_stream, Some((response_headers, StatusCode::OK, Some(url.clone()))))))
+        }
+        Err(err) => {
+            warn!("Failed to open rtsp stream {}: {err}", sanitize_sensitive_info(url.as_str()));
+            if let (Some(boxed_provider_stream), response_info) =
+                create_channel_unavailable_stream(cfg, &get_response_headers(stream_options.get_headers()), StatusCode::BAD_GATEWAY)
+            {
+                Ok(Some((boxed_provider_stream, response_info)))
+            } else {
+                Err(StatusCode::SERVICE_UNAVAILABLE)
+            }
+        }
+    }
 }
 
 async fn provider_stream_request(cfg: &Config, request_client: Arc<reqwest::Client>, stream_options: &ProviderStreamFactoryOptions) -> Result<Option<ProviderStreamFactoryResponse>, StatusCode> {
-    let (client, _partial_content) = prepare_client(&request_client, stream_options);
-    match client.send().await {
+    if stream_options.get_url().scheme() == RTSP_SCHEME {
+        return rtsp_stream_request(cfg, stream_options).await;
+    }
+    let chaos = get_chaos_config(cfg);
+    if let Some(chaos_config) = chaos {
+        if chaos_config.latency_millis > 0 {
+            tokio::time::sleep(Duration::from_millis(chaos_config.latency_millis)).await;
+        }
+        if let Some(status) = chaos_config.next_request_error_status().and_then(|status| StatusCode::from_u16(status).ok()) {
+            debug!("Chaos testing: provider stream request answered with {status}");
+            return provider_error_status_response(cfg, stream_options, status);
+        }
+    }
+    let (client, _partial_content, with_cookies) = prepare_client(&request_client, stream_options);
+    // the permit is held until the provider answered, reconnects are limited too
+    let permit = acquire_provider_permit(stream_options.rate_limiter.as_ref()).await;
+    let response = client.send().await;
+    drop(permit);
+    match response {
         Ok(mut response) => {
             let status = response.status();
+            if let Some(jar) = stream_options.cookie_jar.as_ref() {
+                // the session expired, the next request gets a new session from the provider
+                let session_expired = with_cookies && (status == StatusCode::UNAUTHORIZED || status == StatusCode::FORBIDDEN) && jar.clear();
+                jar.store(response.url(), response.headers());
+                if session_expired {
+                    debug!("Provider session expired ({status}), requesting a new session");
+                    return Ok(None);
+                }
+            }
             if status.is_success() {
                 let response_info = {
                     // Unfortunately, the HEAD request does not work, so we need this workaround.
@@ -265,47 +404,14 @@ async fn provider_stream_request(cfg: &Config, request_client: Arc<reqwest::Clie
                 } else {
                     provider_stream
                 };
+                let boxed_provider_stream = match chaos.map(|chaos_config| chaos_config.drop_after_secs) {
+                    Some(drop_after_secs) if drop_after_secs > 0 => TimedClientStream::new(boxed_provider_stream, drop_after_secs).boxed(),
+                    _ => boxed_provider_stream,
+                };
                 return Ok(Some((boxed_provider_stream, response_info)));
             }
 
-            if status.is_client_error() {
-                debug!("Client error status response : {status}");
-                return match status {
-                    StatusCode::NOT_FOUND
-                    | StatusCode::FORBIDDEN
-                    | StatusCode::UNAUTHORIZED
-                    | StatusCode::METHOD_NOT_ALLOWED
-                    | StatusCode::BAD_REQUEST => {
-                        if let (Some(boxed_provider_stream), response_info) =
-                            create_channel_unavailable_stream(cfg, &get_response_headers(stream_options.get_headers()), StatusCode::BAD_GATEWAY)
-                        {
-                            Ok(Some((boxed_provider_stream, response_info)))
-                        } else {
-                            Err(StatusCode::SERVICE_UNAVAILABLE)
-                        }
-                    }
-                    _ => Err(status)
-                };
-            }
-            if status.is_server_error() {
-                debug!("Server error status response : {status}");
-                return match status {
-                    StatusCode::INTERNAL_SERVER_ERROR |
-                    StatusCode::BAD_GATEWAY |
-                    StatusCode::SERVICE_UNAVAILABLE |
-                    StatusCode::GATEWAY_TIMEOUT => {
-                        if let (Some(boxed_provider_stream), response_info) =
-                            create_channel_unavailable_stream(cfg, &get_response_headers(stream_options.get_headers()), StatusCode::BAD_GATEWAY)
-                        {
-                            Ok(Some((boxed_provider_stream, response_info)))
-                        } else {
-                            Err(StatusCode::SERVICE_UNAVAILABLE)
-                        }
-                    }
-                    _ => Err(status)
-                }
-            }
-            Err(status)
+            provider_error_status_response(cfg, stream_options, status)
         }
         Err(_err) => {
             if let (Some(boxed_provider_stream), response_info) =
@@ -401,6 +507,7 @@ pub async fn create_provider_stream(cfg: Arc<Config>,
                     let config_clone = Arc::clone(&config);
                     async move {
                         if continue_streaming.is_active() {
+                            stream_opts.reconnect_count.fetch_add(1, Ordering::Relaxed);
                             match get_provider_stream(&config_clone, client, &stream_opts).await {
                                 Ok(Some((stream, _info))) => Some((stream, ())),
                                 Ok(None) => None,
@@ -0,0 +1,203 @@
+use crate::api::model::stream::BoxedProviderStream;
+use crate::api::model::stream_error::StreamError;
+use crate::utils::mpegts_muxer::{TsMuxer, TsStreamType};
+use crate::utils::rtsp::{get_track_url, parse_rtp, parse_sdp, AacDepacketizer, RtspConnection, RtspMessage, SdpMedia, VideoCodec, VideoDepacketizer};
+use crate::utils::request::sanitize_sensitive_info;
+use bytes::Bytes;
+use futures::StreamExt;
+use log::debug;
+use std::io::{Error, ErrorKind};
+use std::time::{Duration, Instant};
+use tokio_stream::wrappers::ReceiverStream;
+use url::Url;
+
+const RTSP_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+// the camera stopped sending
+const RTSP_READ_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_SESSION_TIMEOUT_SECS: u64 = 60;
+const RTSP_CHANNEL_SIZE: usize = 64;
+const TS_CLOCK_RATE: u64 = 90_000;
+// the first frame starts at one second, the pcr runs ahead of the first frame
+const TS_START_PTS: u64 = TS_CLOCK_RATE;
+
+/// Converts the 32 bit rtp timestamps of a track to the 90kHz clock of the transport stream.
+struct TimestampMapper {
+    clock_rate: u64,
+    last: Option<u32>,
+    elapsed: u64,
+}
+
+impl TimestampMapper {
+    fn new(clock_rate: u32) -> Self {
+        Self { clock_rate: u64::from(clock_rate.max(1)), last: None, elapsed: 0 }
+    }
+
+    #[allow(clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+    fn map_to_pts(&mut self, timestamp: u32) -> u64 {
+        if let Some(last) = self.last {
+            // frames can be slightly out of order, the elapsed time never goes back before the start
+            let diff = i64::from(timestamp.wrapping_sub(last) as i32);
+            self.elapsed = (self.elapsed as i64 + diff).max(0) as u64;
+        }
+        self.last = Some(timestamp);
+        TS_START_PTS + self.elapsed * TS_CLOCK_RATE / self.clock_rate
+    }
+}
+
+struct RtspTrack {
+    channel: u8,
+    payload_type: u8,
+    timestamps: TimestampMapper,
+}
+
+struct RtspSession {
+    connection: RtspConnection,
+    base_url: String,
+    session_timeout: Duration,
+    video: (RtspTrack, VideoDepacketizer),
+    audio: Option<(RtspTrack, AacDepacketizer)>,
+    muxer: TsMuxer,
+}
+
+fn get_video_codec(media: &SdpMedia) -> Option<VideoCodec> {
+    match media.codec.as_str() {
+        "H264" => Some(VideoCodec::H264),
+        "H265" => Some(VideoCodec::H265),
+        _ => None,
+    }
+}
+
+async fn setup_track(connection: &mut RtspConnection, base_url: &str, media: &SdpMedia, channel: u8) -> Result<Option<u64>, Error> {
+    let transport = format!("RTP/AVP/TCP;unicast;interleaved={channel}-{}", channel + 1);
+    let track_url = get_track_url(base_url, media.control.as_deref());
+    let response = connection.request("SETUP", &track_url, &[("Transport", &transport)]).await?;
+    // `Session: 12345678;timeout=60`
+    Ok(response.header("Session")
+        .and_then(|session| session.split(';').find_map(|param| param.trim().strip_prefix("timeout=")))
+        .and_then(|timeout| timeout.trim().parse::<u64>().ok()))
+}
+
+async fn open_session(url: &Url, user_agent: &str) -> Result<RtspSession, Error> {
+    let mut connection = RtspConnection::connect(url, user_agent).await?;
+    let request_url = connection.url().to_string();
+    let response = connection.request("DESCRIBE", &request_url, &[("Accept", "application/sdp")]).await?;
+    let base_url = response.header("Content-Base").or_else(|| response.header("Content-Location"))
+        .map_or(request_url.as_str(), |base| base).to_string();
+    let medias = parse_sdp(&String::from_utf8_lossy(&response.body));
+
+    let (video_media, video_codec) = medias.iter()
+        .filter(|media| media.media == "video")
+        .find_map(|media| get_video_codec(media).map(|codec| (media, codec)))
+        .ok_or_else(|| Error::new(ErrorKind::Unsupported, "rtsp stream has no H.264 or H.265 video"))?;
+    // audio codecs which can't be muxed into the transport stream, like G.711, are skipped
+    let audio_media = medias.iter()
+        .filter(|media| media.media == "audio" && media.codec == "MPEG4-GENERIC")
+        .find_map(|media| AacDepacketizer::new(media).map(|depacketizer| (media, depacketizer)));
+
+    let mut session_timeout = setup_track(&mut connection, &base_url, video_media, 0).await?;
+    let video = (RtspTrack { channel: 0, payload_type: video_media.payload_type, timestamps: TimestampMapper::new(video_media.clock_rate) },
+                 VideoDepacketizer::new(video_codec, video_media));
+    let audio = match audio_media {
+        Some((media, depacketizer)) => {
+            session_timeout = setup_track(&mut connection, &base_url, media, 2).await?.or(session_timeout);
+            Some((RtspTrack { channel: 2, payload_type: media.payload_type, timestamps: TimestampMapper::new(media.clock_rate) }, depacketizer))
+        }
+        None => None,
+    };
+    connection.request("PLAY", &base_url, &[("Range", "npt=0.000-")]).await?;
+
+    let video_type = if video_codec == VideoCodec::H265 { TsStreamType::H265 } else { TsStreamType::H264 };
+    let audio_type = audio.as_ref().map(|_| TsStreamType::Aac);
+    Ok(RtspSession {
+        connection,
+        base_url,
+        session_timeout: Duration::from_secs(session_timeout.unwrap_or(DEFAULT_SESSION_TIMEOUT_SECS).max(2)),
+        video,
+        audio,
+        muxer: TsMuxer::new(video_type, audio_type),
+    })
+}
+
+impl RtspSession {
+    // Muxes the interleaved packet, the output starts with the first key frame.
+    fn mux_packet(&mut self, channel: u8, data: &[u8], started: &mut bool, out: &mut Vec<u8>) {
+        let Some(packet) = parse_rtp(data) else { return };
+        if channel == self.video.0.channel && packet.payload_type == self.video.0.payload_type {
+            for unit in self.video.1.push(&packet) {
+                let pts = self.video.0.timestamps.map_to_pts(unit.timestamp);
+                *started |= unit.key_frame;
+                if *started {
+                    self.muxer.write_video(out, pts, unit.key_frame, &unit.data);
+                }
+            }
+        } else if let Some((track, depacketizer)) = self.audio.as_mut() {
+            if channel == track.channel && packet.payload_type == track.payload_type {
+                for (timestamp, frame) in depacketizer.push(&packet) {
+                    let pts = track.timestamps.map_to_pts(timestamp);
+                    if *started {
+                        self.muxer.write_audio(out, pts, &frame);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn run(mut self, sender: tokio::sync::mpsc::Sender<Result<Bytes, StreamError>>) {
+        let keep_alive_interval = self.session_timeout / 2;
+        let mut last_keep_alive = Instant::now();
+        let mut started = false;
+        let mut out = Vec::new();
+        loop {
+            let message = match tokio::time::timeout(RTSP_READ_TIMEOUT, self.connection.read_message()).await {
+                Ok(Ok(message)) => message,
+                Ok(Err(err)) => {
+                    debug!("Rtsp stream ended {err}");
+                    break;
+                }
+                Err(_) => {
+                    debug!("Rtsp stream timed out");
+                    break;
+                }
+            };
+            if let RtspMessage::Interleaved(channel, data) = message {
+                self.mux_packet(channel, &data, &mut started, &mut out);
+                if !out.is_empty() && sender.send(Ok(Bytes::from(std::mem::take(&mut out)))).await.is_err() {
+                    // the client disconnected
+                    break;
+                }
+            }
+            if last_keep_alive.elapsed() >= keep_alive_interval {
+                last_keep_alive = Instant::now();
+                if self.connection.send("GET_PARAMETER", &self.base_url, &[]).await.is_err() {
+                    break;
+                }
+            }
+        }
+        let _ = self.connection.send("TEARDOWN", &self.base_url, &[]).await;
+    }
+}
+
+/// Opens the rtsp url with TCP interleaved transport and muxes H.264/H.265 video and AAC audio to MPEG-TS.
+pub(in crate::api) async fn create_rtsp_stream(url: &Url, user_agent: &str) -> Result<BoxedProviderStream, Error> {
+    let session = tokio::time::timeout(RTSP_CONNECT_TIMEOUT, open_session(url, user_agent)).await
+        .map_err(|_| Error::new(ErrorKind::TimedOut, format!("rtsp connect timed out {}", sanitize_sensitive_info(url.as_str()))))??;
+    let (sender, receiver) = tokio::sync::mpsc::channel(RTSP_CHANNEL_SIZE);
+    tokio::spawn(session.run(sender));
+    Ok(ReceiverStream::new(receiver).boxed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TimestampMapper, TS_START_PTS};
+
+    #[test]
+    fn test_timestamp_mapper() {
+        let mut video = TimestampMapper::new(90_000);
+        assert_eq!(video.map_to_pts(u32::MAX - 1000), TS_START_PTS);
+        // the rtp timestamp wraps around
+        assert_eq!(video.map_to_pts(2000), TS_START_PTS + 3001);
+        let mut audio = TimestampMapper::new(48_000);
+        assert_eq!(audio.map_to_pts(5000), TS_START_PTS);
+        assert_eq!(audio.map_to_pts(5000 + 48_000), TS_START_PTS + 90_000);
+    }
+}
@@ -0,0 +1,109 @@
+use bytes::Bytes;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::{Mutex, OnceCell};
+
+// playlists change with every segment, they are only shared between concurrent requests
+pub const HLS_PLAYLIST_TTL_MILLIS: u64 = 1_000;
+pub const HLS_SEGMENT_TTL_MILLIS: u64 = 30_000;
+// the oldest entries are evicted when the cached content exceeds this size
+const HLS_CACHE_MAX_BYTES: usize = 128 * 1024 * 1024;
+
+/// A provider playlist or segment shared by the clients watching the same hls channel.
+#[derive(Debug, Clone)]
+pub struct SharedHlsResource {
+    pub headers: Vec<(String, String)>,
+    pub content: Bytes,
+    // the url after redirects, relative segment urls are resolved against it
+    pub response_url: String,
+}
+
+struct CacheSlot {
+    ts: u64,
+    ttl_millis: u64,
+    cell: Arc<OnceCell<Option<SharedHlsResource>>>,
+}
+
+impl CacheSlot {
+    fn size(&self) -> usize {
+        self.cell.get().and_then(Option::as_ref).map_or(0, |resource| resource.content.len())
+    }
+}
+
+fn current_time_millis() -> u64 {
+    u64::try_from(chrono::Utc::now().timestamp_millis()).unwrap_or_default()
+}
+
+/// Shares the provider playlists and segments of hls channels, each resource is fetched once
+/// for all clients of a channel, concurrent requests wait for the running fetch.
+#[derive(Default)]
+pub struct SharedHlsCache {
+    entries: Mutex<HashMap<String, CacheSlot>>,
+}
+
+impl SharedHlsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn get_or_fetch<F, Fut>(&self, url: &str, ttl_millis: u64, fetch: F) -> Option<SharedHlsResource>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output=Option<SharedHlsResource>>,
+    {
+        let cell = {
+            let mut lock = self.entries.lock().await;
+            let now = current_time_millis();
+            lock.retain(|_, slot| now.saturating_sub(slot.ts) < slot.ttl_millis);
+            Self::evict(&mut lock);
+            Arc::clone(&lock.entry(url.to_string())
+                .or_insert_with(|| CacheSlot { ts: now, ttl_millis, cell: Arc::new(OnceCell::new()) })
+                .cell)
+        };
+        let result = cell.get_or_init(fetch).await.clone();
+        if result.is_none() {
+            // failed fetches are not cached
+            let mut lock = self.entries.lock().await;
+            if lock.get(url).is_some_and(|slot| Arc::ptr_eq(&slot.cell, &cell)) {
+                lock.remove(url);
+            }
+        }
+        result
+    }
+
+    fn evict(entries: &mut HashMap<String, CacheSlot>) {
+        let mut size: usize = entries.values().map(CacheSlot::size).sum();
+        while size > HLS_CACHE_MAX_BYTES {
+            let Some(oldest) = entries.iter().min_by_key(|(_, slot)| slot.ts).map(|(url, _)| url.clone()) else { break };
+            size = size.saturating_sub(entries.remove(&oldest).map_or(0, |slot| slot.size()));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SharedHlsCache, SharedHlsResource};
+    use bytes::Bytes;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn resource(content: &'static str) -> SharedHlsResource {
+        SharedHlsResource { headers: vec![], content: Bytes::from_static(content.as_bytes()), response_url: "http://provider/seg.ts".to_string() }
+    }
+
+    #[tokio::test]
+    async fn test_shared_hls_cache() {
+        let cache = SharedHlsCache::new();
+        let fetches = AtomicUsize::new(0);
+        let fetch = || async { fetches.fetch_add(1, Ordering::SeqCst); Some(resource("segment")) };
+        let (first, second) = tokio::join!(
+            cache.get_or_fetch("http://provider/seg.ts", 10_000, fetch),
+            cache.get_or_fetch("http://provider/seg.ts", 10_000, fetch));
+        assert_eq!(first.unwrap().content, second.unwrap().content);
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+
+        // failed fetches are retried
+        assert!(cache.get_or_fetch("http://provider/other.ts", 10_000, || async { None }).await.is_none());
+        assert!(cache.get_or_fetch("http://provider/other.ts", 10_000, || async { Some(resource("other")) }).await.is_some());
+    }
+}
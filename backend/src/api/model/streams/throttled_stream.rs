@@ -12,17 +12,20 @@ use tokio::time::{sleep, Sleep};
 pub struct ThrottledStream<S> {
     inner: S,
     rate_bytes_per_sec: f64,
+    burst_bytes_remaining: usize,
     next_delay: Option<Pin<Box<Sleep>>>,
 }
 
 impl<S> ThrottledStream<S> {
     #[allow(clippy::cast_precision_loss)]
-    pub fn new(inner: S, throttle_kbps: usize) -> Self {
+    /// The first `burst_bytes` are sent unthrottled for a fast start, then the rate is capped at `throttle_kbps`.
+    pub fn new(inner: S, throttle_kbps: usize, burst_bytes: usize) -> Self {
         assert!(throttle_kbps > 0, "Rate must be greater than 0");
         let rate_bytes_per_sec = (throttle_kbps as f64) *  1000.0 / 8.0;
         Self {
             inner,
             rate_bytes_per_sec,
+            burst_bytes_remaining: burst_bytes,
             next_delay: None,
         }
     }
@@ -55,11 +58,15 @@ where
         // Poll the inner stream
         match Pin::new(&mut this.inner).poll_next(cx) {
             Poll::Ready(Some(Ok(bytes))) => {
-                let len = bytes.len() as f64;
-                let delay_duration = Duration::from_secs_f64(len / this.rate_bytes_per_sec);
+                let burst_len = bytes.len().min(this.burst_bytes_remaining);
+                this.burst_bytes_remaining -= burst_len;
+                let len = (bytes.len() - burst_len) as f64;
+                if len > 0.0 {
+                    let delay_duration = Duration::from_secs_f64(len / this.rate_bytes_per_sec);
 
-                // Schedule the next delay
-                this.next_delay = Some(Box::pin(sleep(delay_duration)));
+                    // Schedule the next delay
+                    this.next_delay = Some(Box::pin(sleep(delay_duration)));
+                }
 
                 Poll::Ready(Some(Ok(bytes)))
             }
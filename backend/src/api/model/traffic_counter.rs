@@ -0,0 +1,17 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counts the bytes streamed to the clients since the start.
+#[derive(Debug, Default)]
+pub struct TrafficCounter {
+    bytes_sent: AtomicU64,
+}
+
+impl TrafficCounter {
+    pub fn add(&self, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent.load(Ordering::Relaxed)
+    }
+}
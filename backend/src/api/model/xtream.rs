@@ -51,6 +51,7 @@ pub struct XtreamAuthorizationResponse {
 impl XtreamAuthorizationResponse {
     pub fn new(server_info: &ApiProxyServerInfo, user: &ProxyUserCredentials, active_connections: u32, access_control: bool) -> Self {
         let now = Local::now();
+        let user_offset = user.get_utc_offset();
         let created_default = (now - Duration::days(365)).timestamp();
         let expired_default = (now + Duration::days(365)).timestamp();
 
@@ -93,14 +94,15 @@ impl XtreamAuthorizationResponse {
                 status: user_status.to_string(),
             },
             server_info: XtreamServerInfoResponse {
-                url: server_info.host.clone(),
+                url: server_info.get_url_host(),
                 port: if server_info.protocol == "http" { server_info.port.as_ref().map_or("80", |v| v.as_str()).to_string() } else { String::from("80") },
                 https_port: if server_info.protocol == "https" { server_info.port.as_ref().map_or("443", |v| v.as_str()).to_string() } else { String::from("443") },
                 server_protocol: server_info.protocol.clone(),
                 rtmp_port: String::new(),
-                timezone: server_info.timezone.to_string(),
+                timezone: user.timezone.as_ref().filter(|_| user_offset.is_some()).unwrap_or(&server_info.timezone).to_string(),
                 timestamp_now: now.timestamp(),
-                time_now: now.format("%Y-%m-%d %H:%M:%S").to_string(),
+                time_now: user_offset.map_or_else(|| now.format("%Y-%m-%d %H:%M:%S").to_string(),
+                                                  |offset| now.with_timezone(&offset).format("%Y-%m-%d %H:%M:%S").to_string()),
             },
         }
     }
@@ -0,0 +1,162 @@
+use crate::api::api_utils::create_recording_stream;
+use crate::api::endpoints::xmltv_api::get_epg_json_path_for_target;
+use crate::api::model::app_state::AppState;
+use crate::api::model::recording::{Recording, RecordingManager, RecordingState};
+use crate::model::{Config, ConfigTarget, EpgJson};
+use crate::repository::m3u_repository::m3u_get_item_for_stream_id;
+use crate::repository::xtream_repository::xtream_get_item_for_stream_id;
+use crate::utils;
+use crate::utils::request::sanitize_sensitive_info;
+use futures::StreamExt;
+use log::{error, info};
+use shared::model::{PlaylistItemType, TargetType};
+use std::fs::File;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
+
+/// A live channel of a target which can be recorded.
+pub(in crate::api) struct RecordingChannel {
+    pub name: String,
+    pub url: String,
+    pub input_name: String,
+    pub item_type: PlaylistItemType,
+    pub epg_channel_id: Option<String>,
+}
+
+// Only plain live streams can be recorded, hls and dash playlists are not supported.
+pub(in crate::api) async fn get_recording_channel(config: &Config, target: &ConfigTarget, virtual_id: u32) -> Result<RecordingChannel, &'static str> {
+    let channel = if target.has_output(&TargetType::Xtream) {
+        xtream_get_item_for_stream_id(virtual_id, config, target, None).map(|(item, _)| RecordingChannel {
+            name: item.title, url: item.url, input_name: item.input_name, item_type: item.item_type, epg_channel_id: item.epg_channel_id,
+        })
+    } else if target.has_output(&TargetType::M3u) {
+        m3u_get_item_for_stream_id(virtual_id, config, target).await.map(|item| RecordingChannel {
+            name: item.title, url: item.url, input_name: item.input_name, item_type: item.item_type, epg_channel_id: item.epg_channel_id,
+        })
+    } else {
+        return Err("Target has no xtream or m3u output");
+    }.map_err(|_| "Channel not found")?;
+    if !matches!(channel.item_type, PlaylistItemType::Live | PlaylistItemType::LiveUnknown) {
+        return Err("Only live channels can be recorded");
+    }
+    Ok(channel)
+}
+
+/// Returns the start and stop of the programme starting at `programme_start` from the json guide of the target.
+pub(in crate::api) fn get_recording_programme(config: &Config, target: &ConfigTarget, channel: &RecordingChannel, programme_start: i64) -> Result<(i64, i64), &'static str> {
+    let epg_channel_id = channel.epg_channel_id.as_deref().ok_or("Channel has no epg id")?;
+    let epg_path = get_epg_json_path_for_target(config, target).ok_or("Target has no json guide, enable the output option epg_json")?;
+    let epg: EpgJson = File::open(&epg_path).map_err(|err| err.to_string())
+        .and_then(|file| serde_json::from_reader(utils::file_reader(file)).map_err(|err| err.to_string()))
+        .map_err(|err| {
+            error!("Failed to read json epg {}: {err}", epg_path.display());
+            "Failed to read json guide"
+        })?;
+    let programme = epg.channels.iter()
+        .filter(|epg_channel| epg_channel.id == epg_channel_id)
+        .flat_map(|epg_channel| &epg_channel.programmes)
+        .find(|programme| programme.start == programme_start)
+        .ok_or("Programme not found")?;
+    let stop = programme.stop.ok_or("Programme has no stop time")?;
+    Ok((programme.start, stop))
+}
+
+enum RecordingEnd {
+    Stopped,
+    Cancelled,
+    QuotaExceeded,
+    StreamEnded,
+}
+
+async fn record(app_state: &AppState, manager: &RecordingManager, recording: &Recording, cancel: &CancellationToken) -> Result<RecordingEnd, String> {
+    let target = app_state.config.sources.get_target_by_name(&recording.target).ok_or("Target not found")?;
+    let channel = get_recording_channel(&app_state.config, target, recording.virtual_id).await?;
+    let input = app_state.config.get_input_by_name(&channel.input_name).ok_or("Input not found")?;
+    if manager.is_quota_exceeded().await {
+        return Err("Recording quota exceeded".to_string());
+    }
+    let Some(mut stream_details) = create_recording_stream(app_state, &channel.url, input, channel.item_type).await else {
+        return Err(format!("Can't open stream {}", sanitize_sensitive_info(&channel.url)));
+    };
+    let Some(mut stream) = stream_details.stream.take() else { return Err("Can't open stream".to_string()) };
+
+    let file_path = manager.get_file_path(recording);
+    if let Some(parent) = file_path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|err| format!("Failed to create recordings directory: {err}"))?;
+    }
+    let mut file = tokio::fs::File::create(&file_path).await.map_err(|err| format!("Failed to create {}: {err}", file_path.display()))?;
+    manager.update(&recording.id, |rec| rec.state = RecordingState::Recording).await;
+    info!("Recording {} to {}", recording.name, file_path.display());
+
+    let remaining_secs = u64::try_from(recording.stop - chrono::Utc::now().timestamp()).unwrap_or(0);
+    let stop_timer = tokio::time::sleep(Duration::from_secs(remaining_secs));
+    tokio::pin!(stop_timer);
+    let mut size: u64 = 0;
+    let end = loop {
+        tokio::select! {
+            () = cancel.cancelled() => break RecordingEnd::Cancelled,
+            () = &mut stop_timer => break RecordingEnd::Stopped,
+            chunk = stream.next() => match chunk {
+                Some(Ok(bytes)) => {
+                    file.write_all(&bytes).await.map_err(|err| format!("Failed to write {}: {err}", file_path.display()))?;
+                    size += bytes.len() as u64;
+                    manager.set_size(&recording.id, size).await;
+                    if manager.is_quota_exceeded().await {
+                        break RecordingEnd::QuotaExceeded;
+                    }
+                }
+                Some(Err(err)) => return Err(format!("Stream error: {err}")),
+                None => break RecordingEnd::StreamEnded,
+            }
+        }
+    };
+    file.flush().await.map_err(|err| format!("Failed to write {}: {err}", file_path.display()))?;
+    // the provider connection is released with the stream details
+    drop(stream);
+    drop(stream_details);
+    Ok(end)
+}
+
+/// Waits until the start of the recording and records the channel until the stop time.
+pub(in crate::api) fn exec_recording(app_state: &Arc<AppState>, recording: Recording, cancel: CancellationToken) {
+    let app_state = Arc::clone(app_state);
+    tokio::spawn(async move {
+        let Some(manager) = app_state.recordings.as_ref().as_ref() else { return };
+        let wait_secs = u64::try_from(recording.start - chrono::Utc::now().timestamp()).unwrap_or(0);
+        tokio::select! {
+            () = cancel.cancelled() => return,
+            () = tokio::time::sleep(Duration::from_secs(wait_secs)) => {}
+        }
+        let (state, error) = if recording.stop <= chrono::Utc::now().timestamp() {
+            (RecordingState::Failed, Some("Stop time passed before the recording started".to_string()))
+        } else {
+            match record(&app_state, manager, &recording, &cancel).await {
+                Ok(RecordingEnd::Stopped) => (RecordingState::Finished, None),
+                // a deleted recording is already removed
+                Ok(RecordingEnd::Cancelled) => return,
+                Ok(RecordingEnd::QuotaExceeded) => (RecordingState::Finished, Some("Recording quota exceeded, recording stopped".to_string())),
+                Ok(RecordingEnd::StreamEnded) => (RecordingState::Finished, Some("Stream ended before the stop time".to_string())),
+                Err(err) => (RecordingState::Failed, Some(err)),
+            }
+        };
+        match error.as_ref() {
+            Some(err) => error!("Recording {} {}: {err}", recording.name, if state == RecordingState::Failed { "failed" } else { "finished" }),
+            None => info!("Recording {} finished", recording.name),
+        }
+        manager.update(&recording.id, |rec| {
+            rec.state = state;
+            rec.error = error;
+        }).await;
+    });
+}
+
+/// Restarts the scheduled recordings after a server start.
+pub(in crate::api) async fn exec_recording_scheduler(app_state: &Arc<AppState>) {
+    let Some(manager) = app_state.recordings.as_ref().as_ref() else { return };
+    for recording in manager.list().await.into_iter().filter(|recording| recording.state == RecordingState::Scheduled) {
+        let cancel = manager.register(&recording.id).await;
+        exec_recording(app_state, recording, cancel);
+    }
+}
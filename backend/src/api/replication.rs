@@ -0,0 +1,260 @@
+use crate::api::config_watch::ConfigFile;
+use crate::api::model::app_state::AppState;
+use crate::model::{Config, ProcessTargets, ReplicationConfig};
+use crate::repository::storage::get_target_storage_path;
+use crate::repository::user_repository::get_api_user_db_path;
+use crate::utils::request::sanitize_sensitive_info;
+use filetime::FileTime;
+use futures::StreamExt;
+use log::{debug, error, info, warn};
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+
+pub(in crate::api) const REPLICATION_API_PATH: &str = "/api/v1/replication";
+
+const ROOT_SOURCES: &str = "sources";
+const ROOT_API_PROXY: &str = "api_proxy";
+const ROOT_MAPPING: &str = "mapping";
+const ROOT_USER_DB: &str = "user_db";
+const ROOT_USER_CONFIG: &str = "user_config";
+const ROOT_TARGET_PREFIX: &str = "target/";
+
+/// A replicated file, the `path` is relative to the root and empty if the root itself is a file.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub(in crate::api) struct ReplicationFile {
+    pub root: String,
+    pub path: String,
+    pub size: u64,
+    pub modified: i64,
+}
+
+// only plain relative paths are accepted, they can't leave the root
+fn is_safe_relative_path(path: &str) -> bool {
+    Path::new(path).components().all(|component| matches!(component, Component::Normal(_)))
+}
+
+// the roots are resolved on both sides to their local paths
+fn resolve_root(cfg: &Config, root: &str) -> Option<PathBuf> {
+    let path = match root {
+        ROOT_SOURCES => PathBuf::from(&cfg.t_sources_file_path),
+        ROOT_API_PROXY => PathBuf::from(&cfg.t_api_proxy_file_path),
+        ROOT_MAPPING => PathBuf::from(&cfg.t_mapping_file_path),
+        ROOT_USER_DB => get_api_user_db_path(cfg),
+        ROOT_USER_CONFIG => PathBuf::from(cfg.user_config_dir.as_ref()?),
+        _ => {
+            let target_name = root.strip_prefix(ROOT_TARGET_PREFIX)?;
+            if target_name.is_empty() || target_name.contains(['/', '\\']) || !is_safe_relative_path(target_name) {
+                return None;
+            }
+            get_target_storage_path(cfg, target_name)?
+        }
+    };
+    if path.as_os_str().is_empty() { None } else { Some(path) }
+}
+
+pub(in crate::api) fn resolve_replication_file(cfg: &Config, root: &str, path: &str) -> Option<PathBuf> {
+    let root_path = resolve_root(cfg, root)?;
+    if path.is_empty() {
+        Some(root_path)
+    } else if is_safe_relative_path(path) {
+        Some(root_path.join(path))
+    } else {
+        None
+    }
+}
+
+fn get_modified_secs(metadata: &std::fs::Metadata) -> i64 {
+    metadata.modified().ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .and_then(|duration| i64::try_from(duration.as_secs()).ok())
+        .unwrap_or(0)
+}
+
+fn collect_files(root: &str, root_path: &Path, dir: &Path, files: &mut Vec<ReplicationFile>) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let Ok(metadata) = entry.metadata() else { continue };
+        if metadata.is_dir() {
+            collect_files(root, root_path, &path, files);
+        } else if let Ok(relative) = path.strip_prefix(root_path) {
+            let relative = relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/");
+            files.push(ReplicationFile { root: root.to_string(), path: relative, size: metadata.len(), modified: get_modified_secs(&metadata) });
+        }
+    }
+}
+
+fn collect_root_files(cfg: &Config, root: &str) -> Vec<ReplicationFile> {
+    let mut files = Vec::new();
+    if let Some(root_path) = resolve_root(cfg, root) {
+        if let Ok(metadata) = std::fs::metadata(&root_path) {
+            if metadata.is_dir() {
+                collect_files(root, &root_path, &root_path, &mut files);
+            } else {
+                files.push(ReplicationFile { root: root.to_string(), path: String::new(), size: metadata.len(), modified: get_modified_secs(&metadata) });
+            }
+        }
+    }
+    files
+}
+
+/// Lists the config files, the user db, the user config files and the processed target stores.
+pub(in crate::api) fn create_replication_manifest(cfg: &Config) -> Vec<ReplicationFile> {
+    let mut roots: Vec<String> = [ROOT_SOURCES, ROOT_API_PROXY, ROOT_MAPPING, ROOT_USER_DB, ROOT_USER_CONFIG].iter().map(ToString::to_string).collect();
+    roots.extend(cfg.sources.sources.iter().flat_map(|source| &source.targets).map(|target| format!("{ROOT_TARGET_PREFIX}{}", target.name)));
+    roots.iter().flat_map(|root| collect_root_files(cfg, root)).collect()
+}
+
+fn is_up_to_date(path: &Path, file: &ReplicationFile) -> bool {
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.len() == file.size && get_modified_secs(&metadata) == file.modified)
+}
+
+async fn fetch_manifest(app_state: &AppState, replication: &ReplicationConfig, primary_url: &str) -> Result<Vec<ReplicationFile>, String> {
+    app_state.http_client.get(format!("{primary_url}{REPLICATION_API_PATH}/manifest"))
+        .bearer_auth(&replication.token)
+        .send().await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|err| sanitize_sensitive_info(&err.to_string()).to_string())?
+        .json::<Vec<ReplicationFile>>().await
+        .map_err(|err| err.to_string())
+}
+
+// the file is downloaded next to the local file and replaced under the file lock
+async fn download_file(app_state: &AppState, replication: &ReplicationConfig, primary_url: &str,
+                       file: &ReplicationFile, local_path: &Path) -> Result<(), String> {
+    let response = app_state.http_client.get(format!("{primary_url}{REPLICATION_API_PATH}/file"))
+        .query(&[("root", file.root.as_str()), ("path", file.path.as_str())])
+        .bearer_auth(&replication.token)
+        .send().await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(|err| sanitize_sensitive_info(&err.to_string()).to_string())?;
+    if let Some(parent) = local_path.parent() {
+        tokio::fs::create_dir_all(parent).await.map_err(|err| err.to_string())?;
+    }
+    let mut tmp_name = OsString::from(local_path.as_os_str());
+    tmp_name.push(".replication");
+    let tmp_path = PathBuf::from(tmp_name);
+    let mut tmp_file = tokio::fs::File::create(&tmp_path).await.map_err(|err| err.to_string())?;
+    let mut stream = response.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.map_err(|err| err.to_string())?;
+        tmp_file.write_all(&chunk).await.map_err(|err| err.to_string())?;
+    }
+    tmp_file.flush().await.map_err(|err| err.to_string())?;
+    drop(tmp_file);
+
+    let _file_lock = app_state.config.file_locks.write_lock(local_path).await;
+    tokio::fs::rename(&tmp_path, local_path).await.map_err(|err| err.to_string())?;
+    filetime::set_file_mtime(local_path, FileTime::from_unix_time(file.modified, 0)).map_err(|err| err.to_string())
+}
+
+// files of directory roots which were removed on the primary
+async fn remove_deleted_files(app_state: &AppState, manifest: &[ReplicationFile]) {
+    let roots: HashSet<&str> = manifest.iter().filter(|file| !file.path.is_empty()).map(|file| file.root.as_str()).collect();
+    for root in roots {
+        let local_files = collect_root_files(&app_state.config, root);
+        for local_file in local_files.iter().filter(|local| !manifest.iter().any(|file| file.root == local.root && file.path == local.path)) {
+            if let Some(path) = resolve_replication_file(&app_state.config, &local_file.root, &local_file.path) {
+                let _file_lock = app_state.config.file_locks.write_lock(&path).await;
+                match tokio::fs::remove_file(&path).await {
+                    Ok(()) => debug!("Replication removed {}", path.display()),
+                    Err(err) => error!("Replication failed to remove {}: {err}", path.display()),
+                }
+            }
+        }
+    }
+}
+
+/// Pulls the changed files from the primary, fails only if the primary is not reachable.
+async fn sync_with_primary(app_state: &Arc<AppState>, replication: &ReplicationConfig, primary_url: &str) -> Result<(), String> {
+    let manifest = fetch_manifest(app_state, replication, primary_url).await?;
+    let mut changed_roots = HashSet::new();
+    for file in &manifest {
+        let Some(local_path) = resolve_replication_file(&app_state.config, &file.root, &file.path) else {
+            warn!("Replication skipped invalid file {} {}", file.root, file.path);
+            continue;
+        };
+        if is_up_to_date(&local_path, file) {
+            continue;
+        }
+        match download_file(app_state, replication, primary_url, file, &local_path).await {
+            Ok(()) => {
+                debug!("Replicated {}", local_path.display());
+                changed_roots.insert(file.root.as_str());
+            }
+            Err(err) => error!("Replication failed for {}: {err}", local_path.display()),
+        }
+    }
+    remove_deleted_files(app_state, &manifest).await;
+
+    if changed_roots.contains(ROOT_API_PROXY) || changed_roots.contains(ROOT_USER_DB) {
+        let _ = ConfigFile::load_api_proxy(app_state);
+    }
+    if changed_roots.contains(ROOT_MAPPING) {
+        let _ = ConfigFile::load_mappping(app_state);
+    }
+    if changed_roots.contains(ROOT_SOURCES) {
+        info!("Replicated sources file, the changes are applied after a restart");
+    }
+    Ok(())
+}
+
+/// Starts the replication task if the instance is a standby.
+/// The scheduled updates of a standby are started when it is promoted, returns `true` for a standby.
+pub(in crate::api) fn exec_standby_replication<F>(cfg: &Config, app_state: &Arc<AppState>, targets: &Arc<ProcessTargets>, on_promote: F) -> bool
+where
+    F: FnOnce(&Arc<AppState>, &Arc<ProcessTargets>) + Send + 'static,
+{
+    let Some(replication) = cfg.replication.as_ref().filter(|replication| replication.is_standby()).cloned() else { return false };
+    let Some(primary_url) = replication.primary_url.clone() else { return false };
+    info!("Standby replication every {}s from {}", replication.interval_secs, sanitize_sensitive_info(&primary_url));
+    let app_state = Arc::clone(app_state);
+    let targets = Arc::clone(targets);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(replication.interval_secs));
+        let mut last_success = Instant::now();
+        loop {
+            tokio::select! {
+                _ = interval.tick() => {}
+                () = app_state.replication.promotion_requested() => {
+                    info!("Standby promoted to primary on request");
+                    break;
+                }
+            }
+            match sync_with_primary(&app_state, &replication, &primary_url).await {
+                Ok(()) => {
+                    last_success = Instant::now();
+                    app_state.replication.set_last_sync(chrono::Utc::now().timestamp());
+                }
+                Err(err) => {
+                    error!("Replication from primary failed: {err}");
+                    if replication.promote_after_secs > 0 && last_success.elapsed() >= Duration::from_secs(replication.promote_after_secs) {
+                        warn!("Primary unreachable for {}s, standby promoted to primary", replication.promote_after_secs);
+                        break;
+                    }
+                }
+            }
+        }
+        app_state.replication.set_promoted();
+        on_promote(&app_state, &targets);
+    });
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_safe_relative_path;
+
+    #[test]
+    fn test_safe_relative_path() {
+        assert!(is_safe_relative_path("live.db"));
+        assert!(is_safe_relative_path("bob/bouquet.json"));
+        assert!(!is_safe_relative_path("../config.yml"));
+        assert!(!is_safe_relative_path("bob/../../config.yml"));
+        assert!(!is_safe_relative_path("/etc/passwd"));
+    }
+}
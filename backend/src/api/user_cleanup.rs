@@ -0,0 +1,182 @@
+use crate::api::model::app_state::AppState;
+use crate::api::scheduler::datetime_to_instant;
+use crate::messaging::send_message;
+use crate::model::{ApiProxyConfig, Config, UserActivityMap, UserCleanupConfig};
+use crate::repository::user_repository::store_api_user;
+use crate::utils;
+use chrono::Local;
+use cron::Schedule;
+use log::{error, info};
+use shared::model::{MsgKind, ProxyUserStatus, UserCleanupAction};
+use shared::utils::current_time_secs;
+use std::str::FromStr;
+use std::sync::Arc;
+
+const SECS_PER_DAY: u64 = 86_400;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(in crate::api) struct UserCleanupPlan {
+    // users notified about the upcoming cleanup
+    pub notify: Vec<String>,
+    // users disabled or deleted
+    pub cleanup: Vec<String>,
+}
+
+// Users are inactive when neither their last activity nor their creation is within `inactive_days`.
+// Inactive users are notified first and cleaned up when they are still inactive after `grace_days`.
+fn plan_user_cleanup(cleanup: &UserCleanupConfig, api_proxy: &ApiProxyConfig, activity: &mut UserActivityMap, now: u64) -> UserCleanupPlan {
+    let inactive_secs = u64::from(cleanup.inactive_days) * SECS_PER_DAY;
+    let grace_secs = u64::from(cleanup.grace_days) * SECS_PER_DAY;
+    let mut plan = UserCleanupPlan::default();
+    for credentials in api_proxy.user.iter().flat_map(|target_user| &target_user.credentials) {
+        let username = &credentials.username;
+        if cleanup.is_excluded(username)
+            || (cleanup.action == UserCleanupAction::Disable && credentials.status == Some(ProxyUserStatus::Disabled)) {
+            continue;
+        }
+        let created_at = credentials.created_at.and_then(|ts| u64::try_from(ts).ok()).unwrap_or(0);
+        let last_seen = activity.get(username).map_or(0, |user_activity| user_activity.last_seen);
+        if now.saturating_sub(last_seen.max(created_at)) < inactive_secs {
+            if let Some(user_activity) = activity.get_mut(username) {
+                user_activity.cleanup_notified = None;
+            }
+            continue;
+        }
+        let user_activity = activity.entry(username.clone()).or_default();
+        match user_activity.cleanup_notified {
+            None => {
+                user_activity.cleanup_notified = Some(now);
+                plan.notify.push(username.clone());
+                if grace_secs == 0 {
+                    plan.cleanup.push(username.clone());
+                }
+            }
+            Some(ts) if now.saturating_sub(ts) >= grace_secs => plan.cleanup.push(username.clone()),
+            Some(_) => {}
+        }
+    }
+    plan
+}
+
+fn apply_user_cleanup(api_proxy: &mut ApiProxyConfig, action: UserCleanupAction, usernames: &[String]) {
+    for target_user in &mut api_proxy.user {
+        match action {
+            UserCleanupAction::Disable => target_user.credentials.iter_mut()
+                .filter(|credentials| usernames.contains(&credentials.username))
+                .for_each(|credentials| credentials.status = Some(ProxyUserStatus::Disabled)),
+            UserCleanupAction::Delete => target_user.credentials.retain(|credentials| !usernames.contains(&credentials.username)),
+        }
+    }
+}
+
+fn store_api_proxy(cfg: &Config, api_proxy: &ApiProxyConfig) -> Result<(), String> {
+    if api_proxy.use_user_db {
+        store_api_user(cfg, &api_proxy.user).map(|_| ()).map_err(|err| err.to_string())
+    } else {
+        let backup_dir = cfg.backup_dir.as_deref().unwrap_or_default();
+        utils::save_api_proxy(&cfg.t_api_proxy_file_path, backup_dir, api_proxy).map_err(|err| err.to_string())
+    }
+}
+
+const fn action_name(action: UserCleanupAction) -> &'static str {
+    match action {
+        UserCleanupAction::Disable => "disabled",
+        UserCleanupAction::Delete => "deleted",
+    }
+}
+
+pub(in crate::api) async fn exec_user_cleanup(app_state: &AppState, cleanup: &UserCleanupConfig) -> Result<UserCleanupPlan, String> {
+    let Some(old_api_proxy) = app_state.config.t_api_proxy.load().clone() else { return Ok(UserCleanupPlan::default()) };
+    let now = current_time_secs();
+    let plan = app_state.active_users.modify_user_activity(|activity| plan_user_cleanup(cleanup, &old_api_proxy, activity, now)).await;
+    if !plan.cleanup.is_empty() {
+        let mut api_proxy = (*old_api_proxy).clone();
+        apply_user_cleanup(&mut api_proxy, cleanup.action, &plan.cleanup);
+        store_api_proxy(&app_state.config, &api_proxy)?;
+        app_state.config.t_api_proxy.store(Some(Arc::new(api_proxy)));
+        if cleanup.action == UserCleanupAction::Delete {
+            app_state.active_users.modify_user_activity(|activity| plan.cleanup.iter().for_each(|username| { activity.remove(username); })).await;
+        }
+    }
+    Ok(plan)
+}
+
+fn notify_user_cleanup(app_state: &AppState, cleanup: &UserCleanupConfig, plan: &UserCleanupPlan) {
+    let action = action_name(cleanup.action);
+    let messaging = app_state.config.messaging.as_ref();
+    if !plan.notify.is_empty() && cleanup.grace_days > 0 {
+        let msg = format!("Users inactive for {} days will be {action} in {} days: {}", cleanup.inactive_days, cleanup.grace_days, plan.notify.join(", "));
+        info!("{msg}");
+        send_message(&app_state.http_client, &MsgKind::Info, messaging, &msg);
+    }
+    if !plan.cleanup.is_empty() {
+        let msg = format!("Inactive users {action}: {}", plan.cleanup.join(", "));
+        info!("{msg}");
+        send_message(&app_state.http_client, &MsgKind::Info, messaging, &msg);
+    }
+}
+
+pub(in crate::api) fn exec_user_cleanup_scheduler(cfg: &Config, app_state: &Arc<AppState>) {
+    let Some(cleanup) = cfg.user_cleanup.as_ref().filter(|cleanup| cleanup.enabled).cloned() else { return };
+    let Ok(schedule) = Schedule::from_str(&cleanup.schedule) else { return };
+    info!("User cleanup scheduled with {}", cleanup.schedule);
+    let app_state = Arc::clone(app_state);
+    tokio::spawn(async move {
+        let offset = *Local::now().offset();
+        while let Some(datetime) = schedule.upcoming(offset).next() {
+            tokio::time::sleep_until(tokio::time::Instant::from(datetime_to_instant(datetime))).await;
+            match exec_user_cleanup(&app_state, &cleanup).await {
+                Ok(plan) => notify_user_cleanup(&app_state, &cleanup, &plan),
+                Err(err) => error!("User cleanup failed: {err}"),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_user_cleanup, plan_user_cleanup, UserCleanupPlan, SECS_PER_DAY};
+    use crate::model::{ApiProxyConfig, UserActivity, UserActivityMap, UserCleanupConfig};
+    use shared::model::{ProxyUserStatus, UserCleanupAction};
+
+    fn api_proxy() -> ApiProxyConfig {
+        serde_yaml::from_str(r"
+server: []
+user:
+  - target: main
+    credentials:
+      - {username: active, password: p1}
+      - {username: dormant, password: p2}
+      - {username: fresh, password: p3, created_at: 9000000}
+      - {username: admin, password: p4}
+").unwrap()
+    }
+
+    #[test]
+    fn test_plan_user_cleanup() {
+        let cleanup: UserCleanupConfig = serde_yaml::from_str("{enabled: true, schedule: '0 0 4 * * *', inactive_days: 30, grace_days: 7, exclude: [admin]}").unwrap();
+        let mut api_proxy = api_proxy();
+        let now = 10_000_000;
+        let mut activity = UserActivityMap::new();
+        activity.insert("active".to_string(), UserActivity { last_seen: now - SECS_PER_DAY, ..UserActivity::default() });
+        activity.insert("dormant".to_string(), UserActivity { last_seen: now - 40 * SECS_PER_DAY, ..UserActivity::default() });
+
+        let plan = plan_user_cleanup(&cleanup, &api_proxy, &mut activity, now);
+        assert_eq!(plan, UserCleanupPlan { notify: vec!["dormant".to_string()], cleanup: vec![] });
+        assert_eq!(activity.get("dormant").unwrap().cleanup_notified, Some(now));
+
+        // still within the grace period
+        let plan = plan_user_cleanup(&cleanup, &api_proxy, &mut activity, now + 3 * SECS_PER_DAY);
+        assert_eq!(plan, UserCleanupPlan::default());
+
+        let plan = plan_user_cleanup(&cleanup, &api_proxy, &mut activity, now + 7 * SECS_PER_DAY);
+        assert_eq!(plan.cleanup, vec!["dormant".to_string()]);
+
+        apply_user_cleanup(&mut api_proxy, UserCleanupAction::Disable, &plan.cleanup);
+        let status: Vec<Option<ProxyUserStatus>> = api_proxy.user[0].credentials.iter().map(|c| c.status).collect();
+        assert_eq!(status, vec![None, Some(ProxyUserStatus::Disabled), None, None]);
+
+        apply_user_cleanup(&mut api_proxy, UserCleanupAction::Delete, &plan.cleanup);
+        assert_eq!(api_proxy.user[0].credentials.len(), 3);
+    }
+}
@@ -2,7 +2,7 @@ use crate::utils::{hex_decode, hex_encode};
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
 
-fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+pub fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
     a.len() == b.len() && a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
 }
 
@@ -54,9 +54,25 @@ pub fn verify_access_token(token_str: &str, secret: &[u8; 32]) -> bool {
     constant_time_eq(expected.as_bytes(), &signature)
 }
 
+/// Signs the payload with an optional expiry as unix timestamp, the signature is hex encoded.
+pub fn create_signature(secret: &[u8; 32], payload: &str, expires: Option<i64>) -> String {
+    let mut hasher = blake3::Hasher::new_keyed(secret);
+    hasher.update(payload.as_bytes());
+    hasher.update(&expires.unwrap_or_default().to_le_bytes());
+    hex_encode(hasher.finalize().as_bytes())
+}
+
+pub fn verify_signature(secret: &[u8; 32], payload: &str, expires: Option<i64>, signature: &str) -> bool {
+    if expires.is_some_and(|ts| ts < Utc::now().timestamp()) {
+        return false;
+    }
+    let expected = create_signature(secret, payload, expires);
+    constant_time_eq(expected.as_bytes(), signature.to_uppercase().as_bytes())
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::auth::access_token::{create_access_token, verify_access_token};
+    use crate::auth::access_token::{create_access_token, create_signature, verify_access_token, verify_signature};
     use std::thread;
 
     #[test]
@@ -67,4 +83,20 @@ mod tests {
         thread::sleep(std::time::Duration::from_secs(2));
         assert!(!verify_access_token(token.as_str(), secret));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_signature() {
+        let secret = b"37c30f739e83ba27b4c17b174c31f3a9";
+        let signature = create_signature(secret, "bob:m3u", None);
+        assert!(verify_signature(secret, "bob:m3u", None, &signature));
+        assert!(verify_signature(secret, "bob:m3u", None, &signature.to_lowercase()));
+        assert!(!verify_signature(secret, "bob:xtream", None, &signature));
+        assert!(!verify_signature(secret, "bob:m3u", Some(i64::MAX), &signature));
+        let expires = chrono::Utc::now().timestamp() + 60;
+        let signature = create_signature(secret, "bob:m3u", Some(expires));
+        assert!(verify_signature(secret, "bob:m3u", Some(expires), &signature));
+        let past = chrono::Utc::now().timestamp() - 1;
+        let signature = create_signature(secret, "bob:m3u", Some(past));
+        assert!(!verify_signature(secret, "bob:m3u", Some(past), &signature));
+    }
+}
@@ -0,0 +1,41 @@
+use std::net::{IpAddr, SocketAddr};
+use axum::extract::{ConnectInfo, FromRequestParts};
+use axum::http::request::Parts;
+use axum::http::StatusCode;
+use crate::auth::Rejection;
+
+/// The ip address of the client, the `x-real-ip` and `x-forwarded-for` headers of a reverse proxy take precedence.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct ClientIp(pub IpAddr);
+
+impl ClientIp {
+    /// Ipv4 clients connected to a dual stack socket are reported as mapped ipv6 addresses, they are no ipv6 clients.
+    pub fn is_ipv6(&self) -> bool {
+        self.0.to_canonical().is_ipv6()
+    }
+}
+
+fn parse_header_ip(parts: &Parts, name: &str) -> Option<IpAddr> {
+    parts.headers.get(name)
+        .and_then(|value| value.to_str().ok())
+        // the first entry of x-forwarded-for is the client
+        .and_then(|value| value.split(',').next())
+        .and_then(|value| value.trim().trim_start_matches('[').trim_end_matches(']').parse::<IpAddr>().ok())
+}
+
+impl<B> FromRequestParts<B> for ClientIp
+where
+    B: Send + Sync,
+{
+    type Rejection = Rejection;
+
+    async fn from_request_parts(req: &mut Parts, state: &B) -> Result<Self, Self::Rejection> {
+        if let Some(ip) = parse_header_ip(req, "x-real-ip").or_else(|| parse_header_ip(req, "x-forwarded-for")) {
+            return Ok(ClientIp(ip));
+        }
+        let ConnectInfo(addr) = ConnectInfo::<SocketAddr>::from_request_parts(req, state)
+            .await
+            .map_err(|_| (StatusCode::BAD_REQUEST, "IP-Addr is missing"))?;
+        Ok(ClientIp(addr.ip()))
+    }
+}
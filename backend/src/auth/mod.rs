@@ -7,6 +7,7 @@ mod auth_bearer;
 mod auth_basic;
 mod access_token;
 mod fingerprint;
+mod client_ip;
 type Rejection = (StatusCode, &'static str);
 
 pub use self::authenticator::*;
@@ -14,5 +15,6 @@ pub use self::access_token::*;
 pub use self::user::*;
 pub use self::password::*;
 pub use self::fingerprint::*;
+pub use self::client_ip::*;
 pub use self::auth_basic::*;
 pub use self::auth_bearer::*;
\ No newline at end of file
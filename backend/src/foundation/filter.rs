@@ -25,6 +25,7 @@ pub fn get_field_value(pli: &PlaylistItem, field: ItemField) -> String {
         ItemField::Input => header.input_name.to_string(),
         ItemField::Type => header.item_type.to_string(),
         ItemField::Caption => if header.title.is_empty() { header.name.to_string() } else { header.title.to_string() },
+        ItemField::Country | ItemField::Language => header.get_field(field.as_str()).map(|value| value.to_string()).unwrap_or_default(),
     };
     value.to_string()
 }
@@ -41,7 +42,7 @@ pub fn set_field_value(pli: &mut PlaylistItem, field: ItemField, value: String)
             header.title.clone_from(&value);
             header.name = value;
         }
-        ItemField::Type => {},
+        ItemField::Type | ItemField::Country | ItemField::Language => {},
     }
     true
 }
@@ -69,7 +70,7 @@ impl ValueAccessor<'_> {
         if self.pli.header.set_field(field, value) {
             trace!("Property {field} set to {value}");
         } else {
-            error!("Can't set unknown field {field} set to {value}");
+            error!("Can't set read only field {field} to {value}");
         }
     }
 }
@@ -109,7 +110,7 @@ pub struct CompiledRegex {
 #[derive(Parser)]
 #[grammar_inline = r#"
 WHITESPACE = _{ " " | "\t" | "\r" | "\n"}
-field = { ^"group" | ^"title" | ^"name" | ^"url" | ^"input" | ^"caption"}
+field = { ^"group" | ^"title" | ^"name" | ^"url" | ^"input" | ^"caption" | ^"country" | ^"language"}
 and = { ^"and" }
 or = { ^"or" }
 not = { ^"not" }
@@ -741,7 +742,7 @@ pub fn apply_templates_to_pattern_single(pattern: &str, templates: Option<&Vec<P
 mod tests {
     use crate::foundation::filter::{get_filter, ValueProvider};
     use crate::model::{PlaylistItem, PlaylistItemHeader};
-    use crate::utils::CONSTANTS;
+    use shared::utils::CONSTANTS;
 
     fn create_mock_pli(name: &str, group: &str) -> PlaylistItem {
         PlaylistItem {
@@ -939,4 +940,28 @@ mod tests {
             }
         }
     }
+    #[test]
+    fn test_filter_country() {
+        let flt = r#"Country ~ "^(DE|AT|CH)$" OR Language ~ "^EN$""#;
+        match get_filter(flt, None) {
+            Ok(filter) => {
+                assert_eq!(format!("{filter}"), flt);
+                let channels = [
+                    create_mock_pli("DE: Das Erste", "News"),
+                    create_mock_pli("ORF 1", "|AT| Austria"),
+                    create_mock_pli("BBC One", "UK: Entertainment"),
+                    create_mock_pli("FR: TF1", "France"),
+                    create_mock_pli("Sky Cinema", "Movies"),
+                ];
+                let filtered: Vec<&str> = channels.iter()
+                    .filter(|&chan| filter.filter(&ValueProvider { pli: chan }))
+                    .map(|chan| chan.header.name.as_str())
+                    .collect();
+                assert_eq!(filtered, vec!["DE: Das Erste", "ORF 1", "BBC One"]);
+            }
+            Err(e) => {
+                panic!("{}", e)
+            }
+        }
+    }
 }
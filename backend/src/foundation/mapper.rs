@@ -1,16 +1,21 @@
 #![allow(clippy::empty_docs)]
 
 use crate::foundation::filter::{PatternTemplate, TemplateValue, ValueAccessor};
-use crate::foundation::mapper::EvalResult::{AnyValue, Failure, Named, Number, Undefined, Value};
+use crate::foundation::mapper::EvalResult::{AnyValue, Failure, List, Named, Number, Undefined, Value};
+use crate::model::{join_group_path, PlaylistItem, PlaylistItemHeader, MAPPER_FIELDS};
+use crate::utils::{hex_encode, md5, sha1, short_hash, uuid_v5};
 use shared::error::{create_tuliprox_error_result, info_err, TuliproxError, TuliproxErrorKind};
 use shared::utils::Capitalize;
 use log::{debug, trace};
+use pest::error::LineColLocation;
 use pest::iterators::{Pair, Pairs};
 use pest::Parser;
+use chrono::format::{Item, StrftimeItems};
 use regex::Regex;
 use std::borrow::Cow;
 use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
 
 #[derive(Parser)]
@@ -21,37 +26,52 @@ null = { "null" }
 identifier = @{ !null ~ (ASCII_ALPHANUMERIC | "_")+ }
 var_access = { identifier ~ ("." ~ identifier)? }
 string_literal = @{ "\"" ~ ( "\\\\" | "\\\"" | "\\n" | "\\t" | "\\r" | (!"\"" ~ ANY) )* ~ "\"" }
+template_text = @{ ( "\\\\" | "\\`" | "\\$" | (!("`" | "${") ~ ANY) )+ }
+template_expr = !{ "${" ~ (function_call | user_function_call | index_access | var_access | field_access) ~ "}" }
+template_string = ${ "`" ~ (template_expr | template_text)* ~ "`" }
 number = @{ "-"? ~ ASCII_DIGIT+ ~ ("." ~ ASCII_DIGIT+)? }
 number_range_from = { number ~ ".." }
 number_range_to = { ".." ~ number }
 number_range_full = { number ~ ".." ~ number }
 number_range_eq = { number }
 number_range = _{ number_range_full | number_range_from | number_range_to | number_range_eq}
-field = { ^"name" | ^"title" | ^"caption" | ^"group" | ^"id" | ^"chno" | ^"logo" | ^"logo_small" | ^"parent_code" | ^"audio_track" | ^"time_shift" | ^"rec" | ^"url" | ^"epg_channel_id" | ^"epg_id" }
+field = @{ "\"" ~ (ASCII_ALPHANUMERIC | "_" | "-")+ ~ "\"" | ASCII_ALPHANUMERIC ~ (ASCII_ALPHANUMERIC | "_")* }
 field_access = _{ "@" ~ field }
 regex_source = _{ field_access | identifier }
 regex_expr = { regex_source ~ regex_op ~ string_literal }
 block_expr = { "{" ~ statements ~ "}" }
 condition = { function_call | var_access | field_access }
 assignment = { (field_access | identifier) ~ "=" ~ expression }
-expression = { assignment | map_block | match_block | function_call | regex_expr | string_literal | number | var_access | field_access | null | block_expr }
-function_name = { "concat" | "uppercase" | "lowercase" | "capitalize" | "trim" | "print" | "number" | "first" | "template" }
+arithmetic_op = { "+" | "-" | "*" | "/" | "%" }
+index_access = { identifier ~ "[" ~ number ~ "]" }
+arithmetic_term = _{ "(" ~ arithmetic ~ ")" | function_call | user_function_call | number | index_access | var_access | field_access }
+arithmetic = { arithmetic_term ~ (arithmetic_op ~ arithmetic_term)+ }
+expression = { assignment | map_block | match_block | arithmetic | function_call | user_function_call | regex_expr | template_string | string_literal | number | index_access | var_access | field_access | null | block_expr }
+function_name = { "concat" | "uppercase" | "lowercase" | "capitalize" | "trim" | "print" | "number" | "first" | "template" | "replace" | "substring" | "split" | "regex_replace" | "now" | "format_date" | "parse_date" | "join" | "last" | "list" | "drop" | "counter" | "hash" | "pad_left" | "pad_right" | "format_number" | "env" | "var" | "group_path" | "url_host" | "url_path" | "url_param" | "url_set_param" | "md5" | "sha1" | "uuid_v5" }
 function_call = { function_name ~ "(" ~ (expression ~ ("," ~ expression)*)? ~ ")" }
+user_function_call = { identifier ~ "(" ~ (expression ~ ("," ~ expression)*)? ~ ")" }
+fn_params = { (identifier ~ ("," ~ identifier)*)? }
+fn_definition = { "fn" ~ identifier ~ "(" ~ fn_params ~ ")" ~ block_expr }
 any_match = { "_" }
 match_case_key = { any_match | identifier }
 match_case_key_list = { match_case_key ~ ("," ~ match_case_key)* }
 match_case = { match_case_key_list ~ "=>" ~ expression | "(" ~ match_case_key_list ~ ")" ~ "=>" ~ expression }
 match_block = { "match" ~  "{" ~ NEWLINE* ~ (match_case ~ ("," ~ NEWLINE* ~ match_case)*)? ~ ","? ~ NEWLINE* ~ "}" }
-map_case_key_list = { string_literal ~ ("|" ~ string_literal)* }
+map_case_text_ignore_case = ${ "i" ~ string_literal }
+map_case_regex = { "~" ~ string_literal }
+map_case_key_item = _{ map_case_text_ignore_case | map_case_regex | string_literal }
+map_case_key_list = { map_case_key_item ~ ("|" ~ map_case_key_item)* }
 map_case_key = { any_match | number_range | map_case_key_list }
 map_case = { map_case_key ~ "=>" ~ expression }
 map_key = { var_access | field_access  }
 map_block = { "map" ~ map_key ~ "{" ~ NEWLINE* ~ (map_case ~ ("," ~ NEWLINE* ~ map_case)*)? ~ ","? ~ NEWLINE* ~ "}" }
-statement = _{ expression }
+statement = _{ fn_definition | expression }
+include_directive = { "include" ~ string_literal }
 comment = _{ "#" ~ (!NEWLINE ~ ANY)* }
 statement_reparator = _{ ";" | NEWLINE }
 statements = _{ (statement_reparator* ~ (statement | comment))* ~ statement_reparator* }
-main = { SOI ~ statements? ~ EOI }
+includes = _{ (statement_reparator* ~ (include_directive | comment))* }
+main = { SOI ~ includes ~ statements? ~ EOI }
 "##]
 struct MapperParser;
 
@@ -64,6 +84,13 @@ enum MatchCaseKey {
     AnyMatch,
 }
 
+fn format_match_case_keys(keys: &[MatchCaseKey]) -> String {
+    keys.iter().map(|key| match key {
+        MatchCaseKey::Identifier(name) => name.as_str(),
+        MatchCaseKey::AnyMatch => "_",
+    }).collect::<Vec<&str>>().join(", ")
+}
+
 #[derive(Debug, Clone)]
 struct MatchCase {
     pub keys: Vec<MatchCaseKey>,
@@ -73,6 +100,9 @@ struct MatchCase {
 #[derive(Debug, Clone)]
 enum MapCaseKey {
     Text(String),
+    // the text is stored lowercase
+    TextIgnoreCase(String),
+    Regex(Regex),
     RangeFrom(f64),
     RangeTo(f64),
     RangeFull(f64, f64),
@@ -80,6 +110,19 @@ enum MapCaseKey {
     AnyMatch,
 }
 
+fn format_map_case_keys(keys: &[MapCaseKey]) -> String {
+    keys.iter().map(|key| match key {
+        MapCaseKey::Text(text) => format!("\"{text}\""),
+        MapCaseKey::TextIgnoreCase(text) => format!("i\"{text}\""),
+        MapCaseKey::Regex(re) => format!("~\"{}\"", re.as_str()),
+        MapCaseKey::RangeFrom(from) => format!("{}..", format_number(*from)),
+        MapCaseKey::RangeTo(to) => format!("..{}", format_number(*to)),
+        MapCaseKey::RangeFull(from, to) => format!("{}..{}", format_number(*from), format_number(*to)),
+        MapCaseKey::RangeEq(num) => format_number(*num),
+        MapCaseKey::AnyMatch => "_".to_string(),
+    }).collect::<Vec<String>>().join(" | ")
+}
+
 #[derive(Debug, Clone)]
 struct MapCase {
     pub keys: Vec<MapCaseKey>,
@@ -105,6 +148,74 @@ enum BuiltInFunction {
     ToNumber,
     First,
     Template,
+    Replace,
+    Substring,
+    Split,
+    RegexReplace,
+    Now,
+    FormatDate,
+    ParseDate,
+    Join,
+    Last,
+    List,
+    Drop,
+    Counter,
+    Hash,
+    PadLeft,
+    PadRight,
+    FormatNumber,
+    Env,
+    Var,
+    GroupPath,
+    UrlHost,
+    UrlPath,
+    UrlParam,
+    UrlSetParam,
+    Md5,
+    Sha1,
+    UuidV5,
+}
+
+impl BuiltInFunction {
+    const fn name(&self) -> &'static str {
+        match self {
+            Self::Concat => "concat",
+            Self::Uppercase => "uppercase",
+            Self::Lowercase => "lowercase",
+            Self::Capitalize => "capitalize",
+            Self::Trim => "trim",
+            Self::Print => "print",
+            Self::ToNumber => "number",
+            Self::First => "first",
+            Self::Template => "template",
+            Self::Replace => "replace",
+            Self::Substring => "substring",
+            Self::Split => "split",
+            Self::RegexReplace => "regex_replace",
+            Self::Now => "now",
+            Self::FormatDate => "format_date",
+            Self::ParseDate => "parse_date",
+            Self::Join => "join",
+            Self::Last => "last",
+            Self::List => "list",
+            Self::Drop => "drop",
+            Self::Counter => "counter",
+            Self::Hash => "hash",
+            Self::PadLeft => "pad_left",
+            Self::PadRight => "pad_right",
+            Self::FormatNumber => "format_number",
+            Self::Env => "env",
+            Self::Var => "var",
+            Self::GroupPath => "group_path",
+            Self::UrlHost => "url_host",
+            Self::UrlPath => "url_path",
+            Self::UrlParam => "url_param",
+            Self::UrlSetParam => "url_set_param",
+            Self::Md5 => "md5",
+            Self::Sha1 => "sha1",
+            Self::UuidV5 => "uuid_v5",
+        }
+    }
 }
 
 impl FromStr for BuiltInFunction {
@@ -121,11 +232,78 @@ impl FromStr for BuiltInFunction {
             "number" => Ok(Self::ToNumber),
             "first" => Ok(Self::First),
             "template" => Ok(Self::Template),
+            "replace" => Ok(Self::Replace),
+            "substring" => Ok(Self::Substring),
+            "split" => Ok(Self::Split),
+            "regex_replace" => Ok(Self::RegexReplace),
+            "now" => Ok(Self::Now),
+            "format_date" => Ok(Self::FormatDate),
+            "parse_date" => Ok(Self::ParseDate),
+            "join" => Ok(Self::Join),
+            "last" => Ok(Self::Last),
+            "list" => Ok(Self::List),
+            "drop" => Ok(Self::Drop),
+            "counter" => Ok(Self::Counter),
+            "hash" => Ok(Self::Hash),
+            "pad_left" => Ok(Self::PadLeft),
+            "pad_right" => Ok(Self::PadRight),
+            "format_number" => Ok(Self::FormatNumber),
+            "env" => Ok(Self::Env),
+            "var" => Ok(Self::Var),
+            "group_path" => Ok(Self::GroupPath),
+            "url_host" => Ok(Self::UrlHost),
+            "url_path" => Ok(Self::UrlPath),
+            "url_param" => Ok(Self::UrlParam),
+            "url_set_param" => Ok(Self::UrlSetParam),
+            "md5" => Ok(Self::Md5),
+            "sha1" => Ok(Self::Sha1),
+            "uuid_v5" => Ok(Self::UuidV5),
             _ => create_tuliprox_error_result!(TuliproxErrorKind::Info, "Unknown function {}", s),
         }
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+enum ArithmeticOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+}
+
+impl ArithmeticOperator {
+    fn is_multiplicative(self) -> bool {
+        matches!(self, Self::Multiply | Self::Divide | Self::Modulo)
+    }
+
+    fn apply(self, left: f64, right: f64) -> EvalResult {
+        match self {
+            Self::Add => Number(left + right),
+            Self::Subtract => Number(left - right),
+            Self::Multiply => Number(left * right),
+            Self::Divide | Self::Modulo if right == 0.0 => Failure(format!("Division by zero: {} {self:?} 0", format_number(left))),
+            Self::Divide => Number(left / right),
+            Self::Modulo => Number(left % right),
+        }
+    }
+}
+
+impl FromStr for ArithmeticOperator {
+    type Err = TuliproxError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "+" => Ok(Self::Add),
+            "-" => Ok(Self::Subtract),
+            "*" => Ok(Self::Multiply),
+            "/" => Ok(Self::Divide),
+            "%" => Ok(Self::Modulo),
+            _ => create_tuliprox_error_result!(TuliproxErrorKind::Info, "Unknown operator {}", s),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 enum RegexSource {
     Identifier(String),
@@ -139,13 +317,56 @@ enum Expression {
     NumberLiteral(f64),
     FieldAccess(String),
     VarAccess(String, String),
+    IndexAccess(String, i64),
     RegexExpr { field: RegexSource, pattern: String, re_pattern: Regex },
-    FunctionCall { name: BuiltInFunction, args: Vec<ExprId> },
+    // a literal regex_replace pattern is compiled at parse time
+    FunctionCall { name: BuiltInFunction, args: Vec<ExprId>, re_pattern: Option<Regex> },
+    UserFunctionCall { name: String, args: Vec<ExprId> },
+    Arithmetic { op: ArithmeticOperator, left: ExprId, right: ExprId },
     Assignment { target: AssignmentTarget, expr: ExprId },
     MatchBlock(Vec<MatchCase>),
     MapBlock { key: MapKey, cases: Vec<MapCase> },
     NullValue,
     Block(Vec<ExprId>),
+    TemplateString(Vec<TemplatePart>),
+}
+
+impl Expression {
+    // the description in the trace, literals are not traced
+    fn trace_label(&self) -> Option<String> {
+        match self {
+            Expression::StringLiteral(_) | Expression::NumberLiteral(_) | Expression::NullValue => None,
+            Expression::Identifier(name) => Some(name.clone()),
+            Expression::FieldAccess(field) => Some(format!("@{field}")),
+            Expression::VarAccess(name, field) => Some(format!("{name}.{field}")),
+            Expression::IndexAccess(name, index) => Some(format!("{name}[{index}]")),
+            Expression::RegexExpr { field, pattern, .. } => Some(match field {
+                RegexSource::Identifier(name) => format!("{name} ~ \"{pattern}\""),
+                RegexSource::Field(field) => format!("@{field} ~ \"{pattern}\""),
+            }),
+            Expression::FunctionCall { name, .. } => Some(format!("{}(..)", name.name())),
+            Expression::UserFunctionCall { name, .. } => Some(format!("{name}(..)")),
+            Expression::Arithmetic { op, .. } => Some(format!("{op:?}")),
+            Expression::Assignment { target, .. } => Some(match target {
+                AssignmentTarget::Identifier(name) => format!("{name} ="),
+                AssignmentTarget::Field(field) => format!("@{field} ="),
+            }),
+            Expression::MatchBlock(_) => Some("match".to_string()),
+            Expression::MapBlock { key, .. } => Some(match key {
+                MapKey::Identifier(name) => format!("map {name}"),
+                MapKey::FieldAccess(field) => format!("map @{field}"),
+                MapKey::VarAccess(name, field) => format!("map {name}.{field}"),
+            }),
+            Expression::Block(_) => Some("block".to_string()),
+            Expression::TemplateString(_) => Some("template string".to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum TemplatePart {
+    Text(String),
+    Expr(ExprId),
 }
 
 #[derive(Debug, Clone)]
@@ -160,21 +381,303 @@ enum Statement {
     Comment, //(String),
 }
 
+// functions are defined at the top of the script with `fn name(params) { ... }`,
+// the body is evaluated with its own variables which are initialized with the parameters
+#[derive(Debug, Clone)]
+struct UserFunction {
+    name: String,
+    params: Vec<String>,
+    body: ExprId,
+    // the line and column of the definition for the validation messages
+    location: String,
+}
+
+// the fields which can be read by the mapper but not set
+const MAPPER_READ_ONLY_FIELDS: &[&str] = &["input", "type", "clean_title", "year", "season", "episode", "quality", "country", "language"];
+
+// fields which are no playlist fields are read from and written to the extended attributes
+fn check_field(field: &str, location: &str, warnings: &mut Vec<String>) {
+    let name = field.to_lowercase();
+    if !MAPPER_FIELDS.contains(&name.as_str()) && !MAPPER_READ_ONLY_FIELDS.contains(&name.as_str()) {
+        warnings.push(format!("{location}: Unknown field @{field}, it is handled as extended attribute"));
+    }
+}
+
+// names with a `-` like `@"tvg-shift"` are quoted, otherwise `@chno-offset` would be a subtraction
+fn field_name(pair: &Pair<Rule>) -> String {
+    pair.as_str().trim().trim_matches('"').to_string()
+}
+
+// an unknown identifier is often a field without @
+fn unknown_identifier(ident: &str) -> TuliproxError {
+    let name = ident.to_lowercase();
+    if MAPPER_FIELDS.contains(&name.as_str()) || MAPPER_READ_ONLY_FIELDS.contains(&name.as_str()) {
+        info_err!(format!("Identifier unknown {ident}, did you mean the field @{ident}? Fields are prefixed with @"))
+    } else {
+        info_err!(format!("Identifier unknown {ident}"))
+    }
+}
+
+// the names of the grammar rules in the parse errors
+fn get_rule_label(rule: Rule) -> String {
+    match rule {
+        Rule::EOI => "end of script".to_string(),
+        Rule::arithmetic_op => "operator".to_string(),
+        Rule::identifier => "variable".to_string(),
+        Rule::string_literal => "string".to_string(),
+        Rule::match_case_key => "match case".to_string(),
+        Rule::map_case_key => "map case".to_string(),
+        Rule::fn_params => "parameters".to_string(),
+        _ => format!("{rule:?}").replace('_', " "),
+    }
+}
+
+// the brackets which are not closed and if a string is not closed, the content of strings is skipped
+fn scan_unclosed(text: &str) -> (Vec<char>, bool) {
+    let mut brackets = vec![];
+    let mut in_string = false;
+    let mut escaped = false;
+    for c in text.chars() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' | '{' | '[' => brackets.push(c),
+            ')' | '}' | ']' => { brackets.pop(); }
+            _ => {}
+        }
+    }
+    (brackets, in_string)
+}
+
+// a hint for common mistakes at the error position
+fn get_parse_error_hint(input: &str, line_text: &str, col: usize) -> Option<String> {
+    let before: String = line_text.chars().take(col.saturating_sub(1)).collect();
+    let rest: String = line_text.chars().skip(col.saturating_sub(1)).collect();
+    let rest = rest.trim();
+    if rest.starts_with('\'') {
+        return Some("Strings are enclosed in double quotes".to_string());
+    }
+    if rest.starts_with("==") || (before.trim_end().ends_with('=') && rest.starts_with('=')) {
+        return Some("Values are compared with map or match blocks, = is an assignment".to_string());
+    }
+    if rest.starts_with('$') {
+        return Some("${...} can only be used inside template strings `...`".to_string());
+    }
+    if before.trim_end().ends_with('~') {
+        return Some("Regular expressions are enclosed in double quotes".to_string());
+    }
+    if rest.starts_with('"') && scan_unclosed(rest).1 {
+        return Some("The string is not closed with \"".to_string());
+    }
+    if rest.is_empty() && before.contains("=>") {
+        return Some("The cases of map and match blocks are separated by ,".to_string());
+    }
+    match scan_unclosed(input).0.last() {
+        Some('(') => Some("A ( is not closed with )".to_string()),
+        Some('{') => Some("A { is not closed with }".to_string()),
+        Some('[') => Some("A [ is not closed with ]".to_string()),
+        _ => None,
+    }
+}
+
+// the location and message of the error, the script line with a pointer to the column and a hint
+fn format_parse_error(err: pest::error::Error<Rule>, input: &str, file: Option<&Path>) -> String {
+    let (line, col) = match err.line_col {
+        LineColLocation::Pos(pos) | LineColLocation::Span(pos, _) => pos,
+    };
+    let location = file.map_or_else(|| format!("line {line}:{col}"), |path| format!("{}:{line}:{col}", path.display()));
+    let err = err.renamed_rules(|rule| get_rule_label(*rule));
+    let line_text = input.lines().nth(line.saturating_sub(1)).unwrap_or_default().replace('\t', " ");
+    let mut message = format!("{location}: {}\n  {}\n  {}^", err.variant.message(), line_text.trim_end(), " ".repeat(col.saturating_sub(1)));
+    if let Some(hint) = get_parse_error_hint(input, &line_text, col) {
+        message.push_str("\n  Hint: ");
+        message.push_str(&hint);
+    }
+    message
+}
+
+// the state while a script and its includes are parsed
+struct ScriptUnit {
+    include_dir: PathBuf,
+    include_stack: Vec<PathBuf>,
+    included: HashSet<PathBuf>,
+    expressions: Vec<Expression>,
+    functions: Vec<UserFunction>,
+    statements: Vec<Statement>,
+    sources: Vec<String>,
+    locations: Vec<String>,
+}
+
+/// The values of the `counter` function, shared by all items mapped for a target,
+/// and the `mapper_vars` of the target read by the `var` function.
+#[derive(Debug, Default)]
+pub struct MapperCounters {
+    values: HashMap<String, f64>,
+    vars: HashMap<String, String>,
+}
+
+impl MapperCounters {
+    pub fn with_vars(vars: HashMap<String, String>) -> Self {
+        Self { values: HashMap::new(), vars }
+    }
+
+    fn var(&self, name: &str) -> Option<&String> {
+        self.vars.get(name)
+    }
+
+    // the first call returns start, every further call adds step
+    fn next(&mut self, name: &str, start: f64, step: f64) -> f64 {
+        let value = self.values.get(name).map_or(start, |value| value + step);
+        self.values.insert(name.to_string(), value);
+        value
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct MapperScript {
     expressions: Vec<Expression>,
+    functions: HashMap<String, UserFunction>,
     statements: Vec<Statement>,
+    // the source text of the statements for the trace
+    sources: Vec<String>,
+    // the line and column of the statements for the validation messages
+    locations: Vec<String>,
 }
 
 impl MapperScript {
-    pub fn eval(&self, setter: &mut ValueAccessor, templates: Option<&Vec<PatternTemplate>>) {
-        let ctx = &mut MapperContext::new(&self.expressions, templates);
+    /// Returns `true` if the script called `drop()` and the item should be removed.
+    pub fn eval(&self, setter: &mut ValueAccessor, templates: Option<&Vec<PatternTemplate>>, counters: &mut MapperCounters) -> bool {
+        let ctx = &mut MapperContext::new(&self.expressions, &self.functions, templates);
+        ctx.counters = std::mem::take(counters);
+        let _ = self.eval_with_context(ctx, setter);
+        *counters = std::mem::take(&mut ctx.counters);
+        ctx.dropped
+    }
+
+    /// Evaluates the script like `eval` and returns every statement, matched case and intermediate result.
+    pub fn eval_with_trace(&self, setter: &mut ValueAccessor, templates: Option<&Vec<PatternTemplate>>, counters: &mut MapperCounters) -> (Vec<String>, bool) {
+        let ctx = &mut MapperContext::new(&self.expressions, &self.functions, templates);
+        ctx.trace = Some(vec![]);
+        ctx.counters = std::mem::take(counters);
         self.eval_with_context(ctx, setter);
+        *counters = std::mem::take(&mut ctx.counters);
+        (ctx.trace.take().unwrap_or_default(), ctx.dropped)
+    }
+
+    // returns the failures of the statements, the remaining statements are skipped after drop()
+    fn eval_with_context(&self, ctx: &mut MapperContext, setter: &mut ValueAccessor) -> Vec<String> {
+        let mut failures = vec![];
+        for (stmt, source) in self.statements.iter().zip(&self.sources) {
+            if matches!(stmt, Statement::Comment) {
+                continue;
+            }
+            ctx.add_trace(|| source.clone());
+            if let Some(failure) = stmt.eval(ctx, setter) {
+                failures.push(failure);
+            }
+            if ctx.dropped {
+                ctx.add_trace(|| "dropped".to_string());
+                break;
+            }
+        }
+        failures
+    }
+
+    /// Evaluates the script for each sample entry and returns the values before and after the evaluation
+    /// with the failures of the statements, the samples are not part of any playlist.
+    pub fn dry_run(&self, items: Vec<PlaylistItemHeader>, templates: Option<&Vec<PatternTemplate>>) -> Vec<MapperDryRunResult> {
+        // the counters continue over the samples like over the items of a target
+        let mut counters = MapperCounters::default();
+        items.into_iter().map(|header| {
+            let before = header.clone();
+            let mut pli = PlaylistItem { header };
+            let ctx = &mut MapperContext::new(&self.expressions, &self.functions, templates);
+            ctx.trace = Some(vec![]);
+            ctx.counters = std::mem::take(&mut counters);
+            let failures = self.eval_with_context(ctx, &mut ValueAccessor { pli: &mut pli });
+            counters = std::mem::take(&mut ctx.counters);
+            let changed = get_changed_fields(&before, &pli.header);
+            MapperDryRunResult { before, after: pli.header, changed, failures, trace: ctx.trace.take().unwrap_or_default(), dropped: ctx.dropped }
+        }).collect()
     }
 
-    fn eval_with_context(&self, ctx: &mut MapperContext, setter: &mut ValueAccessor) {
-        for stmt in &self.statements {
-            stmt.eval(ctx, setter);
+    /// Returns the warnings for fields which are not playlist fields, assignments to read only fields
+    /// and map or match cases which can't be reached because they follow a `_` case.
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = vec![];
+        let mut functions: Vec<&UserFunction> = self.functions.values().collect();
+        functions.sort_by(|a, b| a.name.cmp(&b.name));
+        for function in functions {
+            self.collect_warnings(function.body, &function.location, &mut warnings);
+        }
+        for (stmt, location) in self.statements.iter().zip(&self.locations) {
+            if let Statement::Expression(expr_id) = stmt {
+                self.collect_warnings(*expr_id, location, &mut warnings);
+            }
+        }
+        warnings
+    }
+
+    fn collect_warnings(&self, expr_id: ExprId, location: &str, warnings: &mut Vec<String>) {
+        let Some(expr) = self.expressions.get(expr_id.0) else { return };
+        match expr {
+            Expression::FieldAccess(field)
+            | Expression::RegexExpr { field: RegexSource::Field(field), .. }
+            | Expression::MapBlock { key: MapKey::FieldAccess(field), .. } => check_field(field, location, warnings),
+            Expression::Assignment { target: AssignmentTarget::Field(field), .. } => {
+                if MAPPER_READ_ONLY_FIELDS.contains(&field.to_lowercase().as_str()) {
+                    warnings.push(format!("{location}: Field @{field} is read only"));
+                } else {
+                    check_field(field, location, warnings);
+                }
+            }
+            _ => {}
+        }
+        match expr {
+            Expression::Assignment { expr, .. } => self.collect_warnings(*expr, location, warnings),
+            Expression::FunctionCall { args, .. }
+            | Expression::UserFunctionCall { args, .. }
+            | Expression::Block(args) => args.iter().for_each(|arg| self.collect_warnings(*arg, location, warnings)),
+            Expression::Arithmetic { left, right, .. } => {
+                self.collect_warnings(*left, location, warnings);
+                self.collect_warnings(*right, location, warnings);
+            }
+            Expression::TemplateString(parts) => {
+                for part in parts {
+                    if let TemplatePart::Expr(expr_id) = part {
+                        self.collect_warnings(*expr_id, location, warnings);
+                    }
+                }
+            }
+            Expression::MatchBlock(cases) => {
+                let mut any_match = false;
+                for case in cases {
+                    if any_match {
+                        warnings.push(format!("{location}: Unreachable match case {} after _", format_match_case_keys(&case.keys)));
+                    }
+                    any_match |= matches!(case.keys.as_slice(), [MatchCaseKey::AnyMatch]);
+                    self.collect_warnings(case.expression, location, warnings);
+                }
+            }
+            Expression::MapBlock { cases, .. } => {
+                let mut any_match = false;
+                for case in cases {
+                    if any_match {
+                        warnings.push(format!("{location}: Unreachable map case {} after _", format_map_case_keys(&case.keys)));
+                    }
+                    any_match |= case.keys.iter().any(|key| matches!(key, MapCaseKey::AnyMatch));
+                    self.collect_warnings(case.expression, location, warnings);
+                }
+            }
+            _ => {}
         }
     }
 }
@@ -187,30 +690,75 @@ impl ExprId {
 }
 
 impl Statement {
-    pub fn eval(&self, ctx: &mut MapperContext, setter: &mut ValueAccessor) {
+    // returns the failure of a top level expression
+    pub fn eval(&self, ctx: &mut MapperContext, setter: &mut ValueAccessor) -> Option<String> {
         match self {
             Statement::Expression(expr_id) => {
-                let result = expr_id.eval(ctx, setter);
-                if let Failure(err) = &result {
+                if let Failure(err) = expr_id.eval(ctx, setter) {
                     debug!("{err}");
-                    // } else {
-                    //     trace!("Ignoring result {result:?}");
+                    return Some(err);
                 }
+                None
             }
-            Statement::Comment => {}
+            Statement::Comment => None,
+        }
+    }
+}
+
+/// The result of a dry run for a sample entry.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct MapperDryRunResult {
+    pub before: PlaylistItemHeader,
+    pub after: PlaylistItemHeader,
+    // the names of the fields which have different values after the evaluation
+    pub changed: Vec<String>,
+    pub failures: Vec<String>,
+    pub trace: Vec<String>,
+    pub dropped: bool,
+}
+
+fn get_changed_fields(before: &PlaylistItemHeader, after: &PlaylistItemHeader) -> Vec<String> {
+    match (serde_json::to_value(before), serde_json::to_value(after)) {
+        (Ok(serde_json::Value::Object(before)), Ok(serde_json::Value::Object(after))) => {
+            after.iter().filter(|(key, value)| before.get(key.as_str()) != Some(value)).map(|(key, _)| key.clone()).collect()
         }
+        _ => vec![],
     }
 }
 
 impl MapperScript {
-    fn validate(expressions: &Vec<Expression>, statements: &Vec<Statement>, templates: Option<&Vec<PatternTemplate>>) -> Result<(), TuliproxError> {
-        let ctx = &mut MapperContext::new(expressions, templates);
+    // A function can only call functions which are defined before, which rules out recursion.
+    fn validate_functions(expressions: &Vec<Expression>, functions: Vec<UserFunction>, templates: Option<&Vec<PatternTemplate>>) -> Result<HashMap<String, UserFunction>, TuliproxError> {
+        let mut defined: HashMap<String, UserFunction> = HashMap::new();
+        for function in functions {
+            if BuiltInFunction::from_str(&function.name).is_ok() {
+                return create_tuliprox_error_result!(TuliproxErrorKind::Info, "{}: Function {} is a built-in function", function.location, function.name);
+            }
+            if defined.contains_key(&function.name) {
+                return create_tuliprox_error_result!(TuliproxErrorKind::Info, "{}: Function {} is already defined", function.location, function.name);
+            }
+            let mut identifiers: HashSet<String> = HashSet::new();
+            for param in &function.params {
+                if !identifiers.insert(param.clone()) {
+                    return create_tuliprox_error_result!(TuliproxErrorKind::Info, "{}: Function {} has duplicate parameter {}", function.location, function.name, param);
+                }
+            }
+            MapperContext::new(expressions, &defined, templates).validate_expr(function.body, &mut identifiers)
+                .map_err(|err| info_err!(format!("{}: {}", function.location, err.message)))?;
+            defined.insert(function.name.clone(), function);
+        }
+        Ok(defined)
+    }
+
+    fn validate(expressions: &Vec<Expression>, functions: &HashMap<String, UserFunction>, statements: &[Statement], locations: &[String], templates: Option<&Vec<PatternTemplate>>) -> Result<(), TuliproxError> {
+        let ctx = &mut MapperContext::new(expressions, functions, templates);
 
         let mut identifiers: HashSet<String> = HashSet::new();
-        for stmt in statements {
+        for (stmt, location) in statements.iter().zip(locations) {
             match stmt {
                 Statement::Expression(expr) => {
-                    ctx.validate_expr(*expr, &mut identifiers)?;
+                    ctx.validate_expr(*expr, &mut identifiers)
+                        .map_err(|err| info_err!(format!("{location}: {}", err.message)))?;
                 }
                 Statement::Comment => {}
             }
@@ -219,18 +767,98 @@ impl MapperScript {
     }
 
     pub fn parse(input: &str, templates: Option<&Vec<PatternTemplate>>) -> Result<Self, TuliproxError> {
-        let mut parsed = MapperParser::parse(Rule::main, input).map_err(|e| info_err!(e.to_string()))?;
+        Self::parse_with_includes(input, templates, None)
+    }
+
+    /// Parses the script, `include "file"` directives are resolved relative to `include_dir`.
+    pub fn parse_with_includes(input: &str, templates: Option<&Vec<PatternTemplate>>, include_dir: Option<&Path>) -> Result<Self, TuliproxError> {
+        let mut unit = ScriptUnit {
+            include_dir: include_dir.map_or_else(PathBuf::new, Path::to_path_buf),
+            include_stack: vec![],
+            included: HashSet::new(),
+            expressions: Vec::new(),
+            functions: Vec::new(),
+            statements: Vec::new(),
+            sources: Vec::new(),
+            locations: Vec::new(),
+        };
+        Self::parse_unit(input, None, &mut unit)?;
+        let ScriptUnit { expressions, functions, statements, sources, locations, .. } = unit;
+
+        let functions = MapperScript::validate_functions(&expressions, functions, templates)?;
+        MapperScript::validate(&expressions, &functions, &statements, &locations, templates)?;
+        Ok(Self { expressions, functions, statements, sources, locations })
+    }
+
+    // included files are parsed into the same expressions, their functions and statements come before the ones of the including script
+    fn parse_unit(input: &str, file: Option<&Path>, unit: &mut ScriptUnit) -> Result<(), TuliproxError> {
+        let mut parsed = MapperParser::parse(Rule::main, input).map_err(|err| info_err!(format_parse_error(err, input, file)))?;
         let program_pair = parsed.next().unwrap();
-        let mut statements = Vec::new();
-        let mut expressions = Vec::new();
+        let mut has_statements = false;
         for stmt_pair in program_pair.into_inner() {
-            if let Some(stmt) = Self::parse_statement(stmt_pair, &mut expressions)? {
-                statements.push(stmt);
+            let (line, col) = stmt_pair.line_col();
+            let location = file.map_or_else(|| format!("line {line}:{col}"), |path| format!("{}:{line}:{col}", path.display()));
+            match stmt_pair.as_rule() {
+                Rule::include_directive => Self::parse_include(stmt_pair, file, unit)?,
+                Rule::fn_definition => {
+                    if has_statements {
+                        return create_tuliprox_error_result!(TuliproxErrorKind::Info, "{location}: Functions have to be defined at the top of the script");
+                    }
+                    let function = Self::parse_fn_definition(stmt_pair, location.clone(), &mut unit.expressions)
+                        .map_err(|err| info_err!(format!("{location}: {}", err.message)))?;
+                    unit.functions.push(function);
+                }
+                _ => {
+                    let source = stmt_pair.as_str().split_whitespace().collect::<Vec<&str>>().join(" ");
+                    let statement = Self::parse_statement(stmt_pair, &mut unit.expressions)
+                        .map_err(|err| info_err!(format!("{location}: {}", err.message)))?;
+                    if let Some(stmt) = statement {
+                        has_statements = true;
+                        unit.statements.push(stmt);
+                        unit.sources.push(source);
+                        unit.locations.push(location);
+                    }
+                }
             }
         }
+        Ok(())
+    }
+
+    fn parse_include(pair: Pair<Rule>, file: Option<&Path>, unit: &mut ScriptUnit) -> Result<(), TuliproxError> {
+        let (line, _) = pair.line_col();
+        let location = file.map_or_else(|| format!("line {line}"), |path| format!("{}:{line}", path.display()));
+        let raw = pair.into_inner().next().unwrap().as_str();
+        // remove quotes
+        let include_path = unit.include_dir.join(&raw[1..raw.len() - 1]);
+        let canonical = std::fs::canonicalize(&include_path)
+            .map_err(|err| info_err!(format!("{location}: Failed to include {}: {err}", include_path.display())))?;
+        if unit.include_stack.contains(&canonical) {
+            return create_tuliprox_error_result!(TuliproxErrorKind::Info, "{location}: Include cycle detected for {}", include_path.display());
+        }
+        // a file included by multiple scripts is only added once
+        if !unit.included.insert(canonical.clone()) {
+            return Ok(());
+        }
+        let content = std::fs::read_to_string(&canonical)
+            .map_err(|err| info_err!(format!("{location}: Failed to include {}: {err}", include_path.display())))?;
+        unit.include_stack.push(canonical);
+        Self::parse_unit(&content, Some(&include_path), unit)?;
+        unit.include_stack.pop();
+        Ok(())
+    }
 
-        MapperScript::validate(&expressions, &statements, templates)?;
-        Ok(Self { expressions, statements })
+    fn parse_fn_definition(pair: Pair<Rule>, location: String, expressions: &mut Vec<Expression>) -> Result<UserFunction, TuliproxError> {
+        let mut inner = pair.into_inner();
+        let name = inner.next().unwrap().as_str().to_string();
+        let params = inner.next().unwrap().into_inner().map(|param| param.as_str().to_string()).collect();
+        let body = match MapperScript::parse_expression(inner.next().unwrap(), expressions)? {
+            Some(expr) => {
+                expressions.push(expr);
+                ExprId(expressions.len() - 1)
+            }
+            None => return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Function {} has no body", name),
+        };
+        Ok(UserFunction { name, params, body, location })
     }
     fn parse_statement(pair: Pair<Rule>, expressions: &mut Vec<Expression>) -> Result<Option<Statement>, TuliproxError> {
         match pair.as_rule() {
@@ -257,7 +885,7 @@ impl MapperScript {
         let name = inner.next().unwrap();
         let target = match name.as_rule() {
             Rule::identifier => AssignmentTarget::Identifier(name.as_str().to_string()),
-            Rule::field => AssignmentTarget::Field(name.as_str().to_string()),
+            Rule::field => AssignmentTarget::Field(field_name(&name)),
             _ => return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Assignment target isn't supported {}", name.as_str()),
         };
         let next = inner.next().unwrap();
@@ -332,6 +960,18 @@ impl MapperScript {
                             let content = &raw[1..raw.len() - 1];
                             matches.push(MapCaseKey::Text(content.to_string()));
                         }
+                        Rule::map_case_text_ignore_case => {
+                            let raw = arm.into_inner().next().unwrap().as_str();
+                            matches.push(MapCaseKey::TextIgnoreCase(raw[1..raw.len() - 1].to_lowercase()));
+                        }
+                        Rule::map_case_regex => {
+                            let raw = arm.into_inner().next().unwrap().as_str();
+                            let pattern = &raw[1..raw.len() - 1];
+                            match Regex::new(pattern) {
+                                Ok(re) => matches.push(MapCaseKey::Regex(re)),
+                                Err(_) => return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Invalid regex {}", pattern),
+                            }
+                        }
                         _ => return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Unexpected map key: {:?}", arm.as_rule()),
                     }
                 }
@@ -387,6 +1027,7 @@ impl MapperScript {
         }
     }
 
+    #[allow(clippy::too_many_lines)]
     fn parse_expression(pair: Pair<Rule>, expressions: &mut Vec<Expression>) -> Result<Option<Expression>, TuliproxError> {
         match pair.as_rule() {
             Rule::assignment => {
@@ -397,7 +1038,7 @@ impl MapperScript {
                 }
             }
             Rule::field => {
-                Ok(Some(Expression::FieldAccess(pair.as_str().trim().to_string())))
+                Ok(Some(Expression::FieldAccess(field_name(&pair))))
             }
             Rule::var_access => {
                 let text = pair.as_str();
@@ -409,6 +1050,16 @@ impl MapperScript {
                 }
             }
 
+            Rule::index_access => {
+                let mut inner = pair.into_inner();
+                let name = inner.next().unwrap().as_str().to_string();
+                let raw = inner.next().unwrap().as_str();
+                match raw.parse::<i64>() {
+                    Ok(index) => Ok(Some(Expression::IndexAccess(name, index))),
+                    Err(_) => create_tuliprox_error_result!(TuliproxErrorKind::Info, "Invalid index {raw}, only integers are allowed"),
+                }
+            }
+
             Rule::string_literal => {
                 let raw = pair.as_str();
                 // remove quotes
@@ -430,7 +1081,7 @@ impl MapperScript {
                 let first = inner.next().unwrap();
                 let field = match first.as_rule() {
                     Rule::identifier => RegexSource::Identifier(first.as_str().to_string()),
-                    Rule::field => RegexSource::Field(first.as_str().to_string()),
+                    Rule::field => RegexSource::Field(field_name(&first)),
                     _ => return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Invalid regex source {}", first.as_str().to_string()),
                 };
                 let pattern_raw = inner.next().unwrap().as_str();
@@ -453,8 +1104,28 @@ impl MapperScript {
                     }
                 }
                 let name = BuiltInFunction::from_str(&fn_name)?;
-                Ok(Some(Expression::FunctionCall { name, args }))
+                let re_pattern = match (&name, args.get(1).and_then(|id| expressions.get(id.0))) {
+                    (BuiltInFunction::RegexReplace, Some(Expression::StringLiteral(pattern))) => match Regex::new(pattern) {
+                        Ok(re) => Some(re),
+                        Err(_) => return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Invalid regex {}", pattern),
+                    },
+                    _ => None,
+                };
+                Ok(Some(Expression::FunctionCall { name, args, re_pattern }))
             }
+            Rule::user_function_call => {
+                let mut inner = pair.into_inner();
+                let name = inner.next().unwrap().as_str().to_string();
+                let mut args = vec![];
+                for arg in inner {
+                    if let Some(expr) = MapperScript::parse_expression(arg, expressions)? {
+                        expressions.push(expr);
+                        args.push(ExprId(expressions.len() - 1));
+                    }
+                }
+                Ok(Some(Expression::UserFunctionCall { name, args }))
+            }
+            Rule::fn_definition => create_tuliprox_error_result!(TuliproxErrorKind::Info, "Functions have to be defined at the top of the script"),
 
             Rule::match_block => {
                 let case_pairs = pair.into_inner();
@@ -478,6 +1149,7 @@ impl MapperScript {
                 Ok(Some(Expression::NullValue))
             }
 
+            Rule::arithmetic => MapperScript::parse_arithmetic(pair.into_inner(), expressions),
             Rule::expression => {
                 let inner = pair.into_inner().next().unwrap();
                 MapperScript::parse_expression(inner, expressions)
@@ -494,10 +1166,65 @@ impl MapperScript {
                 }
                 Ok(Some(Expression::Block(block_expressions)))
             }
+            Rule::template_string => {
+                let mut parts = vec![];
+                for part in pair.into_inner() {
+                    match part.as_rule() {
+                        Rule::template_text => parts.push(TemplatePart::Text(part.as_str().replace("\\`", "`").replace("\\$", "$"))),
+                        Rule::template_expr => {
+                            let inner = part.into_inner().next().unwrap();
+                            if let Some(expr) = MapperScript::parse_expression(inner, expressions)? {
+                                expressions.push(expr);
+                                parts.push(TemplatePart::Expr(ExprId(expressions.len() - 1)));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(Some(Expression::TemplateString(parts)))
+            }
             _ => create_tuliprox_error_result!(TuliproxErrorKind::Info, "Unknown expression rule: {:?}", pair.as_rule()),
         }
     }
 
+    // multiplicative operators bind stronger, operators with the same precedence are evaluated from left to right
+    fn parse_arithmetic(pairs: Pairs<Rule>, expressions: &mut Vec<Expression>) -> Result<Option<Expression>, TuliproxError> {
+        let mut terms = vec![];
+        let mut operators = vec![];
+        for pair in pairs {
+            if pair.as_rule() == Rule::arithmetic_op {
+                operators.push(ArithmeticOperator::from_str(pair.as_str())?);
+            } else {
+                let rule = pair.as_rule();
+                let Some(expr) = MapperScript::parse_expression(pair, expressions)? else {
+                    return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Invalid arithmetic operand: {:?}", rule);
+                };
+                expressions.push(expr);
+                terms.push(ExprId(expressions.len() - 1));
+            }
+        }
+
+        let mut sum_terms = vec![terms[0]];
+        let mut sum_operators = vec![];
+        for (op, right) in operators.into_iter().zip(terms.into_iter().skip(1)) {
+            if op.is_multiplicative() {
+                let left = sum_terms.pop().unwrap();
+                expressions.push(Expression::Arithmetic { op, left, right });
+                sum_terms.push(ExprId(expressions.len() - 1));
+            } else {
+                sum_operators.push(op);
+                sum_terms.push(right);
+            }
+        }
+        let mut left = sum_terms[0];
+        for (op, right) in sum_operators.into_iter().zip(sum_terms.into_iter().skip(1)) {
+            expressions.push(Expression::Arithmetic { op, left, right });
+            left = ExprId(expressions.len() - 1);
+        }
+        // the caller pushes the returned expression again at the same index
+        Ok(expressions.pop())
+    }
+
     fn parse_map_block(mut pairs: Pairs<Rule>, expressions: &mut Vec<Expression>) -> Result<Option<Expression>, TuliproxError> {
         let first = pairs.next().unwrap();
         let key = match first.as_rule() {
@@ -505,7 +1232,7 @@ impl MapperScript {
                 let map_key = first.into_inner().next().unwrap();
                 match map_key.as_rule() {
                     Rule::field => {
-                        MapKey::FieldAccess(map_key.as_str().trim().to_string())
+                        MapKey::FieldAccess(field_name(&map_key))
                     }
                     Rule::var_access => {
                         let text = map_key.as_str();
@@ -537,15 +1264,27 @@ impl MapperScript {
 
 pub struct MapperContext<'a> {
     expressions: &'a Vec<Expression>,
+    functions: &'a HashMap<String, UserFunction>,
     variables: HashMap<String, EvalResult>,
     templates: Option<HashMap<String, &'a PatternTemplate>>,
+    // the recorded evaluation steps if the trace is enabled
+    trace: Option<Vec<String>>,
+    trace_depth: usize,
+    // set by drop(), the item is removed from the playlist
+    dropped: bool,
+    counters: MapperCounters,
 }
 
 impl<'a> MapperContext<'a> {
-    fn new(expressions: &'a Vec<Expression>, templates: Option<&'a Vec<PatternTemplate>>) -> Self {
+    fn new(expressions: &'a Vec<Expression>, functions: &'a HashMap<String, UserFunction>, templates: Option<&'a Vec<PatternTemplate>>) -> Self {
         Self {
             expressions,
+            functions,
             variables: HashMap::new(),
+            trace: None,
+            trace_depth: 0,
+            dropped: false,
+            counters: MapperCounters::default(),
             templates: templates.and_then(|vec_templates| {
                 if vec_templates.is_empty() {
                     None
@@ -584,18 +1323,36 @@ impl<'a> MapperContext<'a> {
         self.variables.get(name).unwrap_or(&Undefined)
     }
 
+    fn add_trace<F: FnOnce() -> String>(&mut self, entry: F) {
+        if let Some(trace) = self.trace.as_mut() {
+            trace.push(format!("{}{}", "  ".repeat(self.trace_depth), entry()));
+        }
+    }
+
+    // nested expressions are recorded before the enclosing expression with a deeper indentation
     fn eval_expr_by_id(&mut self, id: usize, accessor: &mut ValueAccessor) -> EvalResult {
         let Some(expr) = self.expressions.get(id) else { return Undefined };
-        expr.eval(self, accessor)
+        if self.trace.is_none() {
+            return expr.eval(self, accessor);
+        }
+        self.trace_depth += 1;
+        let result = expr.eval(self, accessor);
+        if let Some(label) = expr.trace_label() {
+            self.add_trace(|| format!("{label} => {result:?}"));
+        }
+        self.trace_depth -= 1;
+        result
     }
 
+    #[allow(clippy::too_many_lines)]
     fn validate_expr(&mut self, expr_id: ExprId, identifiers: &mut HashSet<String>) -> Result<(), TuliproxError> {
         let Some(expr) = self.expressions.get(expr_id.0) else { return create_tuliprox_error_result!(TuliproxErrorKind::Info, "No matching expression found at index {}", expr_id.0) };
         match expr {
             Expression::Identifier(ident)
-            | Expression::VarAccess(ident, _) => {
+            | Expression::VarAccess(ident, _)
+            | Expression::IndexAccess(ident, _) => {
                 if !identifiers.contains(ident.as_str()) {
-                    return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Identifier unknown {}", ident);
+                    return Err(unknown_identifier(ident));
                 }
             }
             Expression::NullValue
@@ -606,7 +1363,7 @@ impl<'a> MapperContext<'a> {
                 match field {
                     RegexSource::Identifier(ident) => {
                         if !identifiers.contains(ident.as_str()) {
-                            return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Identifier unknown {}", ident);
+                            return Err(unknown_identifier(ident));
                         }
                     }
                     RegexSource::Field(_) => {}
@@ -621,20 +1378,72 @@ impl<'a> MapperContext<'a> {
                 }
                 self.validate_expr(*expr, identifiers)?;
             }
-            Expression::FunctionCall { name, args } => {
-                if args.is_empty() {
+            Expression::FunctionCall { name, args, .. } => {
+                if matches!(name, BuiltInFunction::Now | BuiltInFunction::Drop | BuiltInFunction::UrlHost | BuiltInFunction::UrlPath) {
+                    if !args.is_empty() {
+                        return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Function accepts no arguments {:?}, {} given", name, args.len());
+                    }
+                } else if args.is_empty() {
                     return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Function needs at least one argument {:?}", name);
                 }
                 match name {
                     BuiltInFunction::ToNumber
                     | BuiltInFunction::Template
-                    | BuiltInFunction::First => {
+                    | BuiltInFunction::First
+                    | BuiltInFunction::Last
+                    | BuiltInFunction::Hash
+                    | BuiltInFunction::Md5
+                    | BuiltInFunction::Sha1
+                    | BuiltInFunction::Env
+                    | BuiltInFunction::Var
+                    | BuiltInFunction::UrlParam => {
                         if args.len() > 1 {
                             return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Function accepts only one argument {:?}, {} given", name, args.len());
                         }
                     }
+                    BuiltInFunction::Replace
+                    | BuiltInFunction::RegexReplace if args.len() != 3 => {
+                        return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Function accepts exactly three arguments {:?}, {} given", name, args.len());
+                    }
+                    BuiltInFunction::FormatDate
+                    | BuiltInFunction::ParseDate
+                    | BuiltInFunction::Join
+                    | BuiltInFunction::UrlSetParam
+                    | BuiltInFunction::UuidV5 if args.len() != 2 => {
+                        return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Function accepts exactly two arguments {:?}, {} given", name, args.len());
+                    }
+                    BuiltInFunction::Substring
+                    | BuiltInFunction::Split
+                    | BuiltInFunction::PadLeft
+                    | BuiltInFunction::PadRight if !(2..=3).contains(&args.len()) => {
+                        return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Function accepts two or three arguments {:?}, {} given", name, args.len());
+                    }
+                    BuiltInFunction::Counter if args.len() > 3 => {
+                        return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Function accepts one to three arguments {:?}, {} given", name, args.len());
+                    }
+                    BuiltInFunction::FormatNumber if args.len() > 2 => {
+                        return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Function accepts one or two arguments {:?}, {} given", name, args.len());
+                    }
                     _ => {}
                 }
+                if matches!(name, BuiltInFunction::FormatDate | BuiltInFunction::ParseDate) {
+                    if let Some(Expression::StringLiteral(format)) = self.expressions.get(args[1].0) {
+                        if !is_valid_date_format(format) {
+                            return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Invalid date format {}", format);
+                        }
+                    }
+                }
+                for expr_id in args {
+                    self.validate_expr(*expr_id, identifiers)?;
+                }
+            }
+            Expression::UserFunctionCall { name, args } => {
+                let Some(function) = self.functions.get(name) else {
+                    return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Function unknown {}", name);
+                };
+                if function.params.len() != args.len() {
+                    return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Function {} accepts {} arguments, {} given", name, function.params.len(), args.len());
+                }
                 for expr_id in args {
                     self.validate_expr(*expr_id, identifiers)?;
                 }
@@ -645,11 +1454,22 @@ impl<'a> MapperContext<'a> {
             Expression::MapBlock { key, cases } => {
                 self.validate_map_block(identifiers, key, cases)?;
             }
+            Expression::Arithmetic { op: _op, left, right } => {
+                self.validate_expr(*left, identifiers)?;
+                self.validate_expr(*right, identifiers)?;
+            }
             Expression::Block(expressions) => {
                 for expr_id in expressions {
                     self.validate_expr(*expr_id, identifiers)?;
                 }
             }
+            Expression::TemplateString(parts) => {
+                for part in parts {
+                    if let TemplatePart::Expr(expr_id) = part {
+                        self.validate_expr(*expr_id, identifiers)?;
+                    }
+                }
+            }
         }
         Ok(())
     }
@@ -663,7 +1483,7 @@ impl<'a> MapperContext<'a> {
                 match identifier {
                     MatchCaseKey::Identifier(ident) => {
                         if !identifiers.contains(ident.as_str()) {
-                            return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Identifier unknown {}", ident);
+                            return Err(unknown_identifier(ident));
                         }
                         identifier_key.push_str(ident.as_str());
                         identifier_key.push_str(", ");
@@ -691,12 +1511,13 @@ impl<'a> MapperContext<'a> {
             MapKey::Identifier(ident)
             | MapKey::VarAccess(ident, _) => {
                 if !identifiers.contains(ident.as_str()) {
-                    return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Identifier unknown {}", ident);
+                    return Err(unknown_identifier(ident));
                 }
             }
             MapKey::FieldAccess(_) => {}
         }
         let mut case_keys = HashSet::new();
+        let mut ignore_case_keys = HashSet::new();
         let mut any_match_count = 0;
         for map_case in cases {
             for key in &map_case.keys {
@@ -707,7 +1528,13 @@ impl<'a> MapperContext<'a> {
                         }
                         case_keys.insert(value.as_str());
                     }
-                    MapCaseKey::RangeEq(_)
+                    MapCaseKey::TextIgnoreCase(value) => {
+                        if !ignore_case_keys.insert(value.as_str()) {
+                            return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Duplicate case i\"{}\"", value);
+                        }
+                    }
+                    MapCaseKey::Regex(_)
+                    | MapCaseKey::RangeEq(_)
                     | MapCaseKey::RangeTo(_)
                     | MapCaseKey::RangeFrom(_) => {}
                     MapCaseKey::RangeFull(from, to) => {
@@ -735,6 +1562,7 @@ enum EvalResult {
     Value(String),
     Number(f64),
     Named(Vec<(String, String)>),
+    List(Vec<String>),
     AnyValue,
     Failure(String),
 }
@@ -806,6 +1634,7 @@ impl EvalResult {
             (Value(a), Number(b)) => match_number(*b, a),
             (Number(a), Number(b)) => compare_number(*a, *b) == Ordering::Equal,
             (Named(a), Named(b)) => compare_tuple_vec(a, b),
+            (List(a), List(b)) => a == b,
             _ => false,
         }
     }
@@ -827,6 +1656,7 @@ impl EvalResult {
             },
             (Number(a), Number(b)) => Some(compare_number(*a, *b)),
             (Named(a), Named(b)) => if compare_tuple_vec(a, b) { Some(Ordering::Equal) } else { None },
+            (List(a), List(b)) => if a == b { Some(Ordering::Equal) } else { None },
             _ => None,
         }
     }
@@ -853,6 +1683,7 @@ fn concat_args(args: &Vec<EvalResult>) -> Vec<Cow<str>> {
                     }
                 }
             }
+            List(items) => result.push(Cow::Owned(items.join(", "))),
             Undefined | AnyValue | Failure(_) => {}
         }
     }
@@ -860,6 +1691,235 @@ fn concat_args(args: &Vec<EvalResult>) -> Vec<Cow<str>> {
     result
 }
 
+fn arg_to_string(arg: Option<&EvalResult>) -> Option<String> {
+    match arg? {
+        Value(value) => Some(value.clone()),
+        Number(value) => Some(format_number(*value)),
+        Named(values) => values.first().map(|(_key, val)| val.clone()),
+        List(items) => items.first().cloned(),
+        Undefined | AnyValue | Failure(_) => None,
+    }
+}
+
+// regex captures are converted to the list of their positional groups
+fn to_list(value: &EvalResult) -> Vec<String> {
+    match value {
+        Value(value) => vec![value.clone()],
+        Number(value) => vec![format_number(*value)],
+        Named(values) => values.iter().filter(|(key, _)| key.parse::<usize>().is_ok()).map(|(_, val)| val.clone()).collect(),
+        List(items) => items.clone(),
+        Undefined | AnyValue | Failure(_) => vec![],
+    }
+}
+
+// negative indices count from the end
+fn get_list_item(items: &[String], index: i64) -> Option<&String> {
+    let position = if index < 0 { usize::try_from(-index).ok().and_then(|i| items.len().checked_sub(i)) } else { usize::try_from(index).ok() };
+    position.and_then(|i| items.get(i))
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn arg_to_index(arg: Option<&EvalResult>) -> Result<Option<i64>, String> {
+    match arg_to_string(arg) {
+        None => Ok(None),
+        Some(value) => value.trim().parse::<f64>().map(|num| Some(num as i64)).map_err(|_| format!("Invalid number: {value}")),
+    }
+}
+
+fn arg_to_number(arg: Option<&EvalResult>, default: f64) -> Result<f64, String> {
+    match arg_to_string(arg) {
+        None => Ok(default),
+        Some(value) => value.trim().parse::<f64>().map_err(|_| format!("Invalid number: {value}")),
+    }
+}
+
+// counter(name, start, step), start and step default to 1, an undefined name has no counter
+fn eval_counter(counters: &mut MapperCounters, args: &[EvalResult]) -> EvalResult {
+    let Some(name) = arg_to_string(args.first()) else { return Undefined };
+    match (arg_to_number(args.get(1), 1.0), arg_to_number(args.get(2), 1.0)) {
+        (Ok(start), Ok(step)) => Number(counters.next(&name, start, step)),
+        (Err(err), _) | (_, Err(err)) => Failure(format!("Function counter failed: {err}")),
+    }
+}
+
+// negative indices count from the end
+#[allow(clippy::cast_possible_truncation, clippy::cast_possible_wrap, clippy::cast_sign_loss)]
+fn resolve_index(index: i64, len: usize) -> usize {
+    if index < 0 {
+        (len as i64 + index).max(0) as usize
+    } else {
+        (index as usize).min(len)
+    }
+}
+
+// text values like field contents are converted to numbers, undefined values result in `None`
+fn arithmetic_operand(value: &EvalResult) -> Result<Option<f64>, String> {
+    match value {
+        Number(num) => Ok(Some(*num)),
+        Value(_) | Named(_) | List(_) => match arg_to_string(Some(value)) {
+            Some(text) => text.trim().parse::<f64>().map(Some).map_err(|_| format!("Invalid number: {text}")),
+            None => Ok(None),
+        },
+        Undefined | AnyValue => Ok(None),
+        Failure(err) => Err(err.clone()),
+    }
+}
+
+// chrono fails on display when the format contains invalid specifiers
+fn is_valid_date_format(format: &str) -> bool {
+    !StrftimeItems::new(format).any(|item| matches!(item, Item::Error))
+}
+
+fn format_date(ts: i64, format: &str) -> EvalResult {
+    if !is_valid_date_format(format) {
+        return Failure(format!("Invalid date format {format}"));
+    }
+    match chrono::DateTime::from_timestamp(ts, 0) {
+        Some(datetime) => Value(datetime.with_timezone(&chrono::Local).format(format).to_string()),
+        None => Failure(format!("Invalid timestamp {ts}")),
+    }
+}
+
+// dates without timezone are local dates, dates without time start at midnight
+#[allow(clippy::cast_precision_loss)]
+fn parse_date(value: &str, format: &str) -> EvalResult {
+    let ts = chrono::DateTime::parse_from_str(value, format).map(|datetime| datetime.timestamp()).ok()
+        .or_else(|| chrono::NaiveDateTime::parse_from_str(value, format).ok()
+            .or_else(|| chrono::NaiveDate::parse_from_str(value, format).ok().and_then(|date| date.and_hms_opt(0, 0, 0)))
+            .and_then(|datetime| datetime.and_local_timezone(chrono::Local).earliest())
+            .map(|datetime| datetime.timestamp()));
+    ts.map_or_else(|| Failure(format!("Invalid date {value} for format {format}")), |ts| Number(ts as f64))
+}
+
+// The url functions operate on the `url` field of the entry.
+fn eval_url_function(name: &BuiltInFunction, args: &[EvalResult], accessor: &ValueAccessor) -> Option<EvalResult> {
+    if !matches!(name, BuiltInFunction::UrlHost | BuiltInFunction::UrlPath | BuiltInFunction::UrlParam | BuiltInFunction::UrlSetParam) {
+        return None;
+    }
+    let Some(mut url) = accessor.get("url").and_then(|url| url::Url::parse(url.trim()).ok()) else { return Some(Undefined) };
+    let result = match name {
+        BuiltInFunction::UrlHost => url.host_str().map_or(Undefined, |host| Value(host.to_string())),
+        BuiltInFunction::UrlPath => Value(url.path().to_string()),
+        BuiltInFunction::UrlParam => {
+            let param = arg_to_string(args.first()).unwrap_or_default();
+            url.query_pairs().find(|(key, _)| *key == param).map_or(Undefined, |(_, value)| Value(value.into_owned()))
+        }
+        BuiltInFunction::UrlSetParam => {
+            let Some(param) = arg_to_string(args.first()).filter(|param| !param.is_empty()) else {
+                return Some(Failure(format!("Function '{name:?}' failed: Missing parameter name")));
+            };
+            let value = arg_to_string(args.get(1)).unwrap_or_default();
+            // an existing parameter keeps its position, otherwise it is appended
+            let mut pairs: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+            match pairs.iter_mut().find(|(key, _)| *key == param) {
+                Some(pair) => pair.1 = value,
+                None => pairs.push((param, value)),
+            }
+            url.query_pairs_mut().clear().extend_pairs(pairs);
+            Value(url.to_string())
+        }
+        _ => return None,
+    };
+    Some(result)
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn eval_positional_function(name: &BuiltInFunction, args: &[EvalResult], re_pattern: Option<&Regex>) -> Option<EvalResult> {
+    if !matches!(name, BuiltInFunction::Replace | BuiltInFunction::Substring | BuiltInFunction::Split | BuiltInFunction::RegexReplace
+        | BuiltInFunction::Now | BuiltInFunction::FormatDate | BuiltInFunction::ParseDate | BuiltInFunction::Join
+        | BuiltInFunction::PadLeft | BuiltInFunction::PadRight | BuiltInFunction::FormatNumber | BuiltInFunction::UuidV5) {
+        return None;
+    }
+    if matches!(name, BuiltInFunction::Now) {
+        return Some(Number(chrono::Utc::now().timestamp() as f64));
+    }
+    if matches!(name, BuiltInFunction::Join) {
+        let items = args.first().map(to_list).unwrap_or_default();
+        let separator = arg_to_string(args.get(1)).unwrap_or_default();
+        return Some(if items.is_empty() { Undefined } else { Value(items.join(&separator)) });
+    }
+    let Some(value) = arg_to_string(args.first()) else { return Some(Undefined) };
+    let result = match name {
+        BuiltInFunction::UuidV5 => {
+            let text = arg_to_string(args.get(1)).unwrap_or_default();
+            uuid_v5(&value, &text).map_or_else(|| Failure(format!("Function '{name:?}' failed: Invalid namespace {value}")), Value)
+        }
+        BuiltInFunction::Replace => {
+            let from = arg_to_string(args.get(1)).unwrap_or_default();
+            let to = arg_to_string(args.get(2)).unwrap_or_default();
+            if from.is_empty() { Value(value) } else { Value(value.replace(&from, &to)) }
+        }
+        BuiltInFunction::Substring => {
+            match (arg_to_index(args.get(1)), arg_to_index(args.get(2))) {
+                (Ok(start), Ok(len)) => {
+                    let chars: Vec<char> = value.chars().collect();
+                    let start = resolve_index(start.unwrap_or(0), chars.len());
+                    let end = len.map_or(chars.len(), |len| start.saturating_add(usize::try_from(len).unwrap_or(0)).min(chars.len()));
+                    Value(chars[start..end].iter().collect())
+                }
+                (Err(err), _) | (_, Err(err)) => Failure(format!("Function '{name:?}' failed: {err}")),
+            }
+        }
+        BuiltInFunction::Split => {
+            let separator = arg_to_string(args.get(1)).unwrap_or_default();
+            let parts: Vec<String> = if separator.is_empty() { vec![value] } else { value.split(separator.as_str()).map(ToString::to_string).collect() };
+            // without index all parts are returned as list
+            if args.len() == 2 {
+                return Some(List(parts));
+            }
+            match arg_to_index(args.get(2)) {
+                Ok(index) => get_list_item(&parts, index.unwrap_or(0)).map_or(Undefined, |part| Value(part.clone())),
+                Err(err) => Failure(format!("Function '{name:?}' failed: {err}")),
+            }
+        }
+        BuiltInFunction::RegexReplace => {
+            let pattern = arg_to_string(args.get(1)).unwrap_or_default();
+            let replacement = arg_to_string(args.get(2)).unwrap_or_default();
+            let re = re_pattern.map_or_else(|| Regex::new(&pattern).map(Cow::Owned), |re| Ok(Cow::Borrowed(re)));
+            match re {
+                Ok(re) => Value(re.replace_all(&value, replacement.as_str()).to_string()),
+                Err(_) => Failure(format!("Function '{name:?}' failed: Invalid regex {pattern}")),
+            }
+        }
+        BuiltInFunction::FormatDate => {
+            let format = arg_to_string(args.get(1)).unwrap_or_default();
+            match arg_to_index(Some(&Value(value))) {
+                Ok(Some(ts)) => format_date(ts, &format),
+                Ok(None) => Undefined,
+                Err(err) => Failure(format!("Function '{name:?}' failed: {err}")),
+            }
+        }
+        BuiltInFunction::ParseDate => {
+            let format = arg_to_string(args.get(1)).unwrap_or_default();
+            parse_date(value.trim(), &format)
+        }
+        BuiltInFunction::PadLeft | BuiltInFunction::PadRight => {
+            // the first character of the third argument is used, the default is a space
+            let fill = arg_to_string(args.get(2)).and_then(|fill| fill.chars().next()).unwrap_or(' ');
+            match arg_to_index(args.get(1)) {
+                Ok(width) => {
+                    let missing = usize::try_from(width.unwrap_or(0)).unwrap_or(0).saturating_sub(value.chars().count());
+                    let padding: String = std::iter::repeat_n(fill, missing).collect();
+                    if matches!(name, BuiltInFunction::PadLeft) { Value(padding + &value) } else { Value(value + &padding) }
+                }
+                Err(err) => Failure(format!("Function '{name:?}' failed: {err}")),
+            }
+        }
+        BuiltInFunction::FormatNumber => {
+            match (value.trim().parse::<f64>(), arg_to_index(args.get(1))) {
+                (Ok(num), Ok(decimals)) => {
+                    let decimals = usize::try_from(decimals.unwrap_or(0)).unwrap_or(0);
+                    Value(format!("{num:.decimals$}"))
+                }
+                (Err(_), _) => Failure(format!("Function '{name:?}' failed: Invalid number: {value}")),
+                (_, Err(err)) => Failure(format!("Function '{name:?}' failed: {err}")),
+            }
+        }
+        _ => return None,
+    };
+    Some(result)
+}
+
 impl Expression {
     #[allow(clippy::too_many_lines)]
     pub fn eval(&self, ctx: &mut MapperContext, accessor: &mut ValueAccessor) -> EvalResult {
@@ -884,7 +1944,7 @@ impl Expression {
                     None => Failure(format!("Variable with name {name} not found.")),
                     Some(value) => match value {
                         Undefined => Undefined,
-                        Number(_) | Value(_) => Failure(format!("Variable with name {name} has no fields.")),
+                        Number(_) | Value(_) | List(_) => Failure(format!("Variable with name {name} has no fields.")),
                         Named(values) => {
                             for (key, val) in values {
                                 if key == field {
@@ -897,6 +1957,13 @@ impl Expression {
                     },
                 }
             }
+            Expression::IndexAccess(name, index) => {
+                match ctx.variables.get(name) {
+                    None => Failure(format!("Variable with name {name} not found.")),
+                    Some(value @ (AnyValue | Failure(_))) => value.clone(),
+                    Some(value) => get_list_item(&to_list(value), *index).map_or(Undefined, |item| Value(item.clone())),
+                }
+            }
             Expression::StringLiteral(s) => Value(s.clone()),
             Expression::NumberLiteral(num) => Number(*num),
             Expression::RegexExpr { field, pattern: _pattern, re_pattern } => {
@@ -962,6 +2029,9 @@ impl Expression {
                                 }
                                 accessor.set(name, &result);
                             }
+                            List(items) => {
+                                accessor.set(name, &items.join(", "));
+                            }
                             Undefined | AnyValue => {}
                             Failure(err) => {
                                 return Failure(format!("Failed to set field {name} value: {err}"));
@@ -971,13 +2041,27 @@ impl Expression {
                     }
                 }
             }
-            Expression::FunctionCall { name, args } => {
+            Expression::FunctionCall { name, args, re_pattern } => {
+                if matches!(name, BuiltInFunction::Drop) {
+                    ctx.dropped = true;
+                    return Undefined;
+                }
                 let mut evaluated_args: Vec<EvalResult> = args.iter().map(|a| a.eval(ctx, accessor)).collect();
                 for arg in &evaluated_args {
                     if arg.is_error() {
                         return Failure(format!("Function '{name:?}' failed: {}", if let Failure(msg) = arg { msg } else { "Unknown error" }));
                     }
                 }
+                if matches!(name, BuiltInFunction::Counter) {
+                    return eval_counter(&mut ctx.counters, &evaluated_args);
+                }
+                if let Some(result) = eval_url_function(name, &evaluated_args, accessor) {
+                    return result;
+                }
+                // positional arguments are evaluated before undefined arguments are removed
+                if let Some(result) = eval_positional_function(name, &evaluated_args, re_pattern.as_ref()) {
+                    return result;
+                }
                 evaluated_args.retain(|er| !matches!(er, Undefined | Failure(_) | AnyValue));
                 if evaluated_args.is_empty() {
                     if matches!(name, BuiltInFunction::Print) {
@@ -1014,12 +2098,26 @@ impl Expression {
                                                 Some((_key, val)) => Value(val.to_string()),
                                             }
                                         }
+                                        List(items) => items.first().map_or(Undefined, |item| Value(item.clone())),
                                         _ => value.clone()
                                     }
                                 }
                                 None => Undefined,
                             }
                         }
+                        BuiltInFunction::Last => to_list(&evaluated_args[0]).pop().map_or(Undefined, Value),
+                        BuiltInFunction::Hash => Value(short_hash(&concat_args(&evaluated_args).join(""))),
+                        BuiltInFunction::Md5 => Value(hex_encode(&md5(concat_args(&evaluated_args).join("").as_bytes())).to_lowercase()),
+                        BuiltInFunction::Sha1 => Value(hex_encode(&sha1(concat_args(&evaluated_args).join("").as_bytes())).to_lowercase()),
+                        BuiltInFunction::Env => arg_to_string(evaluated_args.first())
+                            .and_then(|name| std::env::var(name).ok()).map_or(Undefined, Value),
+                        BuiltInFunction::Var => arg_to_string(evaluated_args.first())
+                            .and_then(|name| ctx.counters.var(&name).cloned()).map_or(Undefined, Value),
+                        BuiltInFunction::List => List(evaluated_args.iter().flat_map(to_list).collect()),
+                        BuiltInFunction::GroupPath => {
+                            let path = join_group_path(evaluated_args.iter().flat_map(to_list));
+                            if path.is_empty() { Undefined } else { Value(path) }
+                        }
                         BuiltInFunction::Template => {
                             let evaluated_arg = &evaluated_args[0];
                             let value = match evaluated_arg {
@@ -1027,6 +2125,7 @@ impl Expression {
                                     Some(value)
                                 }
                                 Named(values) => values.first().map(|(_key, val)| val),
+                                List(items) => items.first(),
                                 _ => None
                             };
                             if let Some(val) = value {
@@ -1038,6 +2137,25 @@ impl Expression {
                                 Undefined
                             }
                         }
+                        // evaluated by eval_positional_function, drop() before the arguments
+                        BuiltInFunction::Replace
+                        | BuiltInFunction::Substring
+                        | BuiltInFunction::Split
+                        | BuiltInFunction::RegexReplace
+                        | BuiltInFunction::Now
+                        | BuiltInFunction::FormatDate
+                        | BuiltInFunction::ParseDate
+                        | BuiltInFunction::Join
+                        | BuiltInFunction::PadLeft
+                        | BuiltInFunction::PadRight
+                        | BuiltInFunction::FormatNumber
+                        | BuiltInFunction::Drop
+                        | BuiltInFunction::Counter
+                        | BuiltInFunction::UrlHost
+                        | BuiltInFunction::UrlPath
+                        | BuiltInFunction::UrlParam
+                        | BuiltInFunction::UrlSetParam
+                        | BuiltInFunction::UuidV5 => Undefined,
                     }
                 }
             }
@@ -1064,10 +2182,12 @@ impl Expression {
                             | Number(_)
                             | Named(_)
                             | AnyValue => match_count += 1,
+                            List(items) => if !items.is_empty() { match_count += 1 },
                             Undefined | Failure(_) => {}
                         }
                     }
                     if match_count == case_keys_len {
+                        ctx.add_trace(|| format!("  match case {} matched", format_match_case_keys(&match_case.keys)));
                         return match_case.expression.eval(ctx, accessor);
                     }
                 }
@@ -1093,7 +2213,7 @@ impl Expression {
                             None => Failure(format!("Variable with name {name} not found.")),
                             Some(value) => match value {
                                 Undefined => Undefined,
-                                Number(_) | Value(_) => Failure(format!("Variable with name {name} has no fields.")),
+                                Number(_) | Value(_) | List(_) => Failure(format!("Variable with name {name} has no fields.")),
                                 Named(values) => {
                                     for (key, val) in values {
                                         if key == field {
@@ -1113,6 +2233,15 @@ impl Expression {
                     for key in &map_case.keys {
                         if match key {
                             MapCaseKey::Text(value) => key_value.matches(&Value(value.to_string())),
+                            MapCaseKey::TextIgnoreCase(value) => match &key_value {
+                                Value(text) => text.to_lowercase() == *value,
+                                _ => key_value.matches(&Value(value.clone())),
+                            },
+                            MapCaseKey::Regex(re) => match &key_value {
+                                Value(text) => re.is_match(text),
+                                Number(num) => re.is_match(&format_number(*num)),
+                                _ => false,
+                            },
                             MapCaseKey::AnyMatch => true,
                             MapCaseKey::RangeFrom(num) => {
                                 match key_value.compare(&Number(*num)) {
@@ -1165,11 +2294,39 @@ impl Expression {
                     }
 
                     if matches {
+                        ctx.add_trace(|| format!("  map case {:?} matched for {key_value:?}", map_case.keys));
                         return map_case.expression.eval(ctx, accessor);
                     }
                 }
                 Undefined
             }
+            Expression::Arithmetic { op, left, right } => {
+                let left_value = left.eval(ctx, accessor);
+                let right_value = right.eval(ctx, accessor);
+                match (arithmetic_operand(&left_value), arithmetic_operand(&right_value)) {
+                    (Ok(Some(left_num)), Ok(Some(right_num))) => op.apply(left_num, right_num),
+                    (Err(err), _) | (_, Err(err)) => Failure(format!("Arithmetic {op:?} failed: {err}")),
+                    _ => Undefined,
+                }
+            }
+            Expression::UserFunctionCall { name, args } => {
+                let functions = ctx.functions;
+                let Some(function) = functions.get(name) else {
+                    return Failure(format!("Function '{name}' not found."));
+                };
+                let mut variables = HashMap::new();
+                for (param, arg) in function.params.iter().zip(args) {
+                    let value = arg.eval(ctx, accessor);
+                    if let Failure(msg) = &value {
+                        return Failure(format!("Function '{name}' failed: {msg}"));
+                    }
+                    variables.insert(param.clone(), value);
+                }
+                let outer_variables = std::mem::replace(&mut ctx.variables, variables);
+                let result = function.body.eval(ctx, accessor);
+                ctx.variables = outer_variables;
+                result
+            }
             Expression::Block(expressions) => {
                 let mut result = Undefined;
                 for expr in expressions {
@@ -1177,6 +2334,23 @@ impl Expression {
                 }
                 result
             }
+            // undefined values are interpolated as empty text
+            Expression::TemplateString(parts) => {
+                let mut text = String::new();
+                for part in parts {
+                    match part {
+                        TemplatePart::Text(value) => text.push_str(value),
+                        TemplatePart::Expr(expr_id) => {
+                            let value = expr_id.eval(ctx, accessor);
+                            if let Failure(msg) = value {
+                                return Failure(format!("Template string failed: {msg}"));
+                            }
+                            text.push_str(&concat_args(&vec![value]).join(""));
+                        }
+                    }
+                }
+                Value(text)
+            }
         }
     }
 }
@@ -1184,7 +2358,6 @@ impl Expression {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::{PlaylistItem, PlaylistItemHeader};
 
     #[test]
     fn test_mapper_dsl_eval() {
@@ -1221,7 +2394,7 @@ mod tests {
             let mut accessor = ValueAccessor {
                 pli,
             };
-            mapper.eval(&mut accessor, None);
+            mapper.eval(&mut accessor, None, &mut MapperCounters::default());
             println!("Result: {pli:?}");
         }
 
@@ -1299,4 +2472,467 @@ mod tests {
         let mapper = MapperScript::parse(script, None).expect("Parsing failed");
         println!("Program: {mapper:?}");
     }
+
+    #[test]
+    fn test_string_functions() {
+        let script = r#"
+            name = regex_replace(@Caption, "^([A-Z]{2}): (.*?) (FHD|HD|SD)$", "$2 ($1)")
+            country = split(@Caption, ":", 0)
+            quality = substring(@Caption, -3)
+            @Caption = concat(replace(name, "Sport", "Sports"), " ", substring(country, 0, 1), " ", quality, split(@Caption, ":", 5))
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let mut pli = PlaylistItem { header: PlaylistItemHeader { title: "DE: Sport 1 FHD".to_string(), ..Default::default() } };
+        mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut MapperCounters::default());
+        assert_eq!(pli.header.title, "Sports 1 (DE) D FHD");
+        // the literal pattern is compiled once at parse time
+        assert!(mapper.expressions.iter().any(|expr| matches!(expr, Expression::FunctionCall { name: BuiltInFunction::RegexReplace, re_pattern: Some(_), .. })));
+
+        // a pattern from a variable is compiled on evaluation
+        let mapper = MapperScript::parse(r#"
+            pattern = "^[A-Z]{2}: "
+            @Caption = regex_replace(@Caption, pattern, "")
+        "#, None).expect("Parsing failed");
+        let mut pli = PlaylistItem { header: PlaylistItemHeader { title: "DE: Sport 1".to_string(), ..Default::default() } };
+        mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut MapperCounters::default());
+        assert_eq!(pli.header.title, "Sport 1");
+
+        assert!(MapperScript::parse(r#"a = replace(@Caption, "a")"#, None).is_err());
+        assert!(MapperScript::parse(r#"a = regex_replace(@Caption, "(", "")"#, None).is_err());
+    }
+
+    #[test]
+    fn test_template_string() {
+        let script = r#"
+            match_name = @Caption ~ "^(?P<name>.*?) (?P<quality>HD|SD)$"
+            quality = lowercase(match_name.quality)
+            @Group = `${ @Group }: ${uppercase(match_name.name)} ${quality}${missing_value}`
+            @Caption = `\`${quality}\` costs \${number(@Chno) * 2}`
+        "#;
+        let mapper = MapperScript::parse(script, None);
+        assert!(mapper.is_err(), "unknown identifiers are rejected");
+        let mapper = MapperScript::parse(&script.replace("${missing_value}", ""), None).expect("Parsing failed");
+        let mut pli = PlaylistItem { header: PlaylistItemHeader { title: "Sport 1 HD".to_string(), group: "US".to_string(), chno: "4".to_string(), ..Default::default() } };
+        mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut MapperCounters::default());
+        assert_eq!(pli.header.group, "US: SPORT 1 hd");
+        assert_eq!(pli.header.title, "`hd` costs ${number(@Chno) * 2}");
+
+        let mapper = MapperScript::parse("@Caption = `${@Caption}${@Chno}`", None).expect("Parsing failed");
+        let mut pli = PlaylistItem { header: PlaylistItemHeader { title: "Sport".to_string(), ..Default::default() } };
+        mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut MapperCounters::default());
+        assert_eq!(pli.header.title, "Sport");
+    }
+
+    #[test]
+    fn test_list_functions() {
+        let script = r#"
+            parts = @Caption ~ "^(\w+) (\w+) (\w+)$"
+            reordered = list(parts[2], parts[0], parts[-2])
+            @Caption = join(reordered, " | ")
+            @Group = concat(first(parts), "/", last(parts), "/", parts[5])
+            words = split(@Chno, "-")
+            next = number(words[0]) + 1
+            @Chno = `${words[-1]}${next}`
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let mut pli = PlaylistItem { header: PlaylistItemHeader { title: "Sport DE HD".to_string(), chno: "4-a-b".to_string(), ..Default::default() } };
+        mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut MapperCounters::default());
+        assert_eq!(pli.header.title, "HD | Sport | DE");
+        assert_eq!(pli.header.group, "Sport/HD/");
+        assert_eq!(pli.header.chno, "b5");
+
+        assert!(MapperScript::parse("a = join(@Caption)", None).is_err());
+        assert!(MapperScript::parse("a = b[0]", None).is_err());
+        assert!(MapperScript::parse("a = @Caption\nb = a[0.5]", None).is_err());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let script = r#"
+            group_offset = 100
+            index = number(@Chno)
+            @Chno = group_offset + index * 2 - (10 - 4) / 3
+            rest = index % 4
+            missing = null
+            @Caption = concat(@Caption, " ", rest, " ", 7 / 2, " ", missing + 1)
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let mut pli = PlaylistItem { header: PlaylistItemHeader { title: "Sport".to_string(), chno: "5".to_string(), ..Default::default() } };
+        mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut MapperCounters::default());
+        assert_eq!(pli.header.chno, "108");
+        assert_eq!(pli.header.title, "Sport 1 3.5 ");
+
+        let mut pli = PlaylistItem { header: PlaylistItemHeader { title: "Sport".to_string(), chno: "5".to_string(), ..Default::default() } };
+        let mapper = MapperScript::parse("@Chno = @Chno / 0", None).expect("Parsing failed");
+        mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut MapperCounters::default());
+        assert_eq!(pli.header.chno, "5");
+    }
+
+    #[test]
+    fn test_user_functions() {
+        let script = r#"
+            # strips the country prefix and the quality suffix
+            fn normalize(title, suffix) {
+                name = regex_replace(title, "^[A-Z]{2}: ", "")
+                name = regex_replace(name, " (FHD|HD|SD)$", "")
+                concat(trim(name), suffix)
+            }
+            fn label(title) { uppercase(normalize(title, " *")) }
+            name = "kept"
+            @Caption = label(@Caption)
+            @Group = concat(normalize(@Group, ""), " ", name)
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let mut pli = PlaylistItem { header: PlaylistItemHeader { title: "DE: Sport 1 FHD".to_string(), group: "DE: Sports HD".to_string(), ..Default::default() } };
+        mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut MapperCounters::default());
+        assert_eq!(pli.header.title, "SPORT 1 *");
+        assert_eq!(pli.header.group, "Sports kept");
+
+        assert!(MapperScript::parse("fn f(a) { a }\n@Caption = f(@Caption, 1)", None).is_err());
+        assert!(MapperScript::parse("fn f(a) { b }", None).is_err());
+        assert!(MapperScript::parse("fn f(a) { f(a) }", None).is_err());
+        assert!(MapperScript::parse("fn trim(a) { a }", None).is_err());
+        assert!(MapperScript::parse("@Caption = g(@Caption)", None).is_err());
+        assert!(MapperScript::parse("a = 1\nfn f(a) { a }", None).is_err());
+    }
+
+    #[test]
+    fn test_date_functions() {
+        let script = r#"
+            start = parse_date(@Caption, "%Y-%m-%d %H:%M")
+            @Caption = format_date(start + 3600, "%d.%m.%Y %H:%M")
+            @Group = concat(@Group, " ", format_date(now(), "%Y"))
+            @Chno = parse_date("20250304050600 +0000", "%Y%m%d%H%M%S %z")
+            @Id = parse_date("2025-03-04", "%Y-%m-%d") - parse_date("2025-03-03", "%Y-%m-%d")
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let mut pli = PlaylistItem { header: PlaylistItemHeader { title: "2025-03-04 05:06".to_string(), group: "News".to_string(), ..Default::default() } };
+        mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut MapperCounters::default());
+        assert_eq!(pli.header.title, "04.03.2025 06:06");
+        assert_eq!(pli.header.group, format!("News {}", chrono::Local::now().format("%Y")));
+        assert_eq!(pli.header.chno, "1741064760");
+        assert_eq!(pli.header.id, "86400");
+
+        assert!(MapperScript::parse("a = now(1)", None).is_err());
+        assert!(MapperScript::parse("a = format_date(now())", None).is_err());
+        assert!(MapperScript::parse(r#"a = format_date(now(), "%Q")"#, None).is_err());
+    }
+
+    #[test]
+    fn test_attribute_fields() {
+        let script = r#"
+            days = number(@"catchup-days")
+            @"catchup-days" = days * 2
+            @"Tvg-Shift" = "+1"
+            @Chno = @Chno-1
+            @Year = "1999"
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let mut pli = PlaylistItem { header: PlaylistItemHeader { title: "Sport".to_string(), chno: "5".to_string(), ..Default::default() } };
+        pli.header.attributes = Some([("catchup-days".to_string(), "3".to_string())].into_iter().collect());
+        mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut MapperCounters::default());
+        let attributes = pli.header.attributes.as_ref().unwrap();
+        assert_eq!(attributes.get("catchup-days").map(String::as_str), Some("6"));
+        assert_eq!(attributes.get("tvg-shift").map(String::as_str), Some("+1"));
+        assert!(!attributes.contains_key("year"));
+        assert_eq!(pli.header.chno, "4");
+    }
+
+    #[test]
+    fn test_field_subtraction() {
+        // without quotes a `-` after a field name is a subtraction
+        let script = r#"
+            offset = 2
+            @Chno = @chno-offset
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let mut pli = PlaylistItem { header: PlaylistItemHeader { chno: "5".to_string(), ..Default::default() } };
+        mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut MapperCounters::default());
+        assert_eq!(pli.header.chno, "3");
+        assert!(pli.header.attributes.as_ref().is_none_or(|attributes| !attributes.contains_key("chno-offset")));
+    }
+
+    #[test]
+    fn test_include() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("common.map"), "# shared definitions\nfn tag(a) { concat(\"[\", a, \"]\") }\n").unwrap();
+        std::fs::write(dir.path().join("quality.map"), "include \"common.map\"\nfn hd(a) { concat(a, \" \", tag(\"HD\")) }\n").unwrap();
+        std::fs::write(dir.path().join("a.map"), "include \"b.map\"\n").unwrap();
+        std::fs::write(dir.path().join("b.map"), "include \"a.map\"\n").unwrap();
+        std::fs::write(dir.path().join("broken.map"), "a = 1\nb = concat(a\n").unwrap();
+
+        let script = "include \"common.map\"\ninclude \"quality.map\"\n@Caption = hd(@Caption)\n";
+        let mapper = MapperScript::parse_with_includes(script, None, Some(dir.path())).expect("Parsing failed");
+        let mut pli = PlaylistItem { header: PlaylistItemHeader { title: "Sport".to_string(), ..Default::default() } };
+        mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut MapperCounters::default());
+        assert_eq!(pli.header.title, "Sport [HD]");
+
+        let err = MapperScript::parse_with_includes("include \"a.map\"", None, Some(dir.path())).unwrap_err();
+        assert!(err.to_string().contains("b.map:1: Include cycle detected"));
+        let err = MapperScript::parse_with_includes("include \"broken.map\"", None, Some(dir.path())).unwrap_err();
+        assert!(err.to_string().contains("broken.map:2:"));
+        let err = MapperScript::parse_with_includes("\ninclude \"missing.map\"", None, Some(dir.path())).unwrap_err();
+        assert!(err.to_string().contains("line 2: Failed to include"));
+        assert!(MapperScript::parse_with_includes("a = 1\ninclude \"common.map\"", None, Some(dir.path())).is_err());
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let script = r#"
+            @Caption = uppercase(@Caption)
+            @Group = concat(@Group, " HD")
+            @Chno = number(@Chno) / 0
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let items: Vec<PlaylistItemHeader> = serde_json::from_str(r#"[{"title": "sport", "group": "DE", "chno": "1"}, {"title": "NEWS"}]"#).unwrap();
+        let results = mapper.dry_run(items, None);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].before.title, "sport");
+        assert_eq!(results[0].after.title, "SPORT");
+        assert_eq!(results[0].after.group, "DE HD");
+        assert_eq!(results[0].changed, vec!["group".to_string(), "name".to_string(), "title".to_string()]);
+        assert_eq!(results[0].failures.len(), 1);
+        assert!(results[0].failures[0].contains("Division by zero"));
+        assert_eq!(results[1].changed, vec!["group".to_string(), "name".to_string()]);
+    }
+
+    #[test]
+    fn test_trace() {
+        let script = r#"
+            quality = @Caption ~ "(HD|SD)"
+            quality = map quality {
+              "HD" => "High",
+              _ => "Low",
+            }
+            @Group = match {
+              quality => concat(@Group, " ", quality),
+              _ => @Group,
+            }
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let items: Vec<PlaylistItemHeader> = serde_json::from_str(r#"[{"title": "Sport HD", "group": "DE"}]"#).unwrap();
+        let results = mapper.dry_run(items, None);
+        assert_eq!(results[0].after.group, "DE High");
+        assert_eq!(results[0].trace, vec![
+            r#"quality = @Caption ~ "(HD|SD)""#,
+            r#"    @Caption ~ "(HD|SD)" => Value("HD")"#,
+            "  quality = => Undefined",
+            r#"quality = map quality { "HD" => "High", _ => "Low", }"#,
+            r#"      map case [Text("HD")] matched for Value("HD")"#,
+            r#"    map quality => Value("High")"#,
+            "  quality = => Undefined",
+            r#"@Group = match { quality => concat(@Group, " ", quality), _ => @Group, }"#,
+            "      match case quality matched",
+            r#"        @Group => Value("DE")"#,
+            r#"        quality => Value("High")"#,
+            r#"      concat(..) => Value("DE High")"#,
+            r#"    match => Value("DE High")"#,
+            "  @Group = => Undefined",
+        ]);
+    }
+
+    #[test]
+    fn test_map_ignore_case_and_regex() {
+        let script = r#"
+            quality = @Caption ~ "(\w+)$"
+            @Group = map quality {
+              "HD" => "exact",
+              i"fhd" | i"uhd" => "ignore case",
+              ~"^\d{3,4}p$" => "regex",
+              _ => "none",
+            }
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let items: Vec<PlaylistItemHeader> = serde_json::from_str(r#"[{"title": "A HD"}, {"title": "B Fhd"}, {"title": "C UHD"},
+            {"title": "D 1080p"}, {"title": "E hd"}]"#).unwrap();
+        let groups: Vec<String> = mapper.dry_run(items, None).into_iter().map(|r| r.after.group).collect();
+        assert_eq!(groups, vec!["exact", "ignore case", "ignore case", "regex", "none"]);
+        assert!(MapperScript::parse("a = map @Caption {\n ~\"(\" => 1,\n }", None).is_err());
+        assert!(MapperScript::parse("a = map @Caption {\n i\"HD\" => 1,\n i\"hd\" => 2,\n }", None).is_err());
+    }
+
+    #[test]
+    fn test_drop() {
+        let script = r#"
+            adult = @Caption ~ "(?i)\b(XXX|Adult)\b"
+            match {
+              adult => drop(),
+            }
+            @Group = "Checked"
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let items: Vec<PlaylistItemHeader> = serde_json::from_str(r#"[{"title": "XXX Movie", "group": "DE"}, {"title": "Sport", "group": "DE"}]"#).unwrap();
+        let results = mapper.dry_run(items, None);
+        assert!(results[0].dropped);
+        assert_eq!(results[0].after.group, "DE");
+        assert_eq!(results[0].trace.last().map(String::as_str), Some("dropped"));
+        assert!(!results[1].dropped);
+        assert_eq!(results[1].after.group, "Checked");
+        assert!(MapperScript::parse("drop(@Caption)", None).is_err());
+    }
+
+    #[test]
+    fn test_pad_and_format_number() {
+        let script = r#"
+            season = "1"
+            rating = "7.46"
+            @Caption = concat(@Caption, " S", pad_left(season, 2, "0"), "E", pad_left(2 + 3, 2, "0"))
+            @Chno = pad_left(@Chno, 4, "0")
+            @Group = concat(pad_right(@Group, 6, "."), format_number(rating, 1), " ", format_number(rating))
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let items: Vec<PlaylistItemHeader> = serde_json::from_str(r#"[{"title": "Show", "group": "DE", "chno": "42"}]"#).unwrap();
+        let results = mapper.dry_run(items, None);
+        assert_eq!(results[0].after.title, "Show S01E05");
+        assert_eq!(results[0].after.chno, "0042");
+        assert_eq!(results[0].after.group, "DE....7.5 7");
+        assert!(MapperScript::parse("@Chno = pad_left(@Chno)", None).is_err());
+        assert!(MapperScript::parse("@Chno = format_number(@Chno, 1, 2)", None).is_err());
+    }
+
+    #[test]
+    fn test_hash() {
+        let script = r#"
+            @epg_channel_id = concat("id-", hash(concat(@Group, "/", @Caption)))
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let items: Vec<PlaylistItemHeader> = serde_json::from_str(r#"[{"title": "Sport", "group": "DE", "id": "1"},
+            {"title": "Sport", "group": "DE", "id": "2"}, {"title": "News", "group": "DE"}]"#).unwrap();
+        let results = mapper.dry_run(items, None);
+        assert_eq!(results[0].after.epg_channel_id, Some(format!("id-{}", short_hash("DE/Sport"))));
+        assert_eq!(results[0].after.epg_channel_id, results[1].after.epg_channel_id);
+        assert_ne!(results[0].after.epg_channel_id, results[2].after.epg_channel_id);
+        assert!(MapperScript::parse("@Chno = hash(@Group, @Caption)", None).is_err());
+    }
+
+    #[test]
+    fn test_counter() {
+        let script = r#"
+            @Chno = counter(@Group, 100, 10)
+            @Caption = concat(@Caption, " ", counter("all"))
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let items: Vec<PlaylistItemHeader> = serde_json::from_str(r#"[{"title": "A", "group": "DE"}, {"title": "B", "group": "DE"},
+            {"title": "C", "group": "FR"}, {"title": "D", "group": "DE"}]"#).unwrap();
+        let results = mapper.dry_run(items, None);
+        let chnos: Vec<&str> = results.iter().map(|r| r.after.chno.as_str()).collect();
+        assert_eq!(chnos, vec!["100", "110", "100", "120"]);
+        assert_eq!(results[3].after.title, "D 4");
+        assert!(MapperScript::parse("@Chno = counter(@Group, 1, 1, 1)", None).is_err());
+
+        let mut counters = MapperCounters::default();
+        let mapper = MapperScript::parse(r#"@Chno = counter("chno")"#, None).expect("Parsing failed");
+        for expected in ["1", "2"] {
+            let mut pli = PlaylistItem { header: PlaylistItemHeader::default() };
+            mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut counters);
+            assert_eq!(pli.header.chno, expected);
+        }
+    }
+
+    #[test]
+    fn test_env_and_var() {
+        let script = r#"
+            @Caption = concat(var("prefix"), ": ", @Caption, var("unknown"))
+            @Group = env("PATH")
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let mut counters = MapperCounters::with_vars(HashMap::from([("prefix".to_string(), "DE".to_string())]));
+        let mut pli = PlaylistItem { header: PlaylistItemHeader { title: "Sport".to_string(), ..Default::default() } };
+        mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut counters);
+        assert_eq!(pli.header.title, "DE: Sport");
+        assert_eq!(pli.header.group, std::env::var("PATH").unwrap());
+        assert!(MapperScript::parse(r#"@Caption = var("a", "b")"#, None).is_err());
+    }
+
+    #[test]
+    fn test_group_path() {
+        let script = r#"
+            @Group = group_path("Sports ", @Group, "", split("England/Premier League", "/"))
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let mut pli = PlaylistItem { header: PlaylistItemHeader { group: "Football".to_string(), ..Default::default() } };
+        mapper.eval(&mut ValueAccessor { pli: &mut pli }, None, &mut MapperCounters::default());
+        assert_eq!(pli.header.group, "Sports / Football / England / Premier League");
+    }
+
+    #[test]
+    fn test_url_functions() {
+        let script = r#"
+            @Group = concat(url_host(), " ", url_path(), " ", url_param("token"))
+            @Logo = concat("https://logos.example.com", url_path())
+            @url = url_set_param("token", "new")
+            @url = url_set_param("user", "me")
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let items: Vec<PlaylistItemHeader> = serde_json::from_str(r#"[{"url": "http://cdn.provider.tv/live/1.ts?token=abc&q=1", "group": "DE"},
+            {"url": "not a url", "group": "DE"}]"#).unwrap();
+        let results = mapper.dry_run(items, None);
+        assert_eq!(results[0].after.group, "cdn.provider.tv /live/1.ts abc");
+        assert_eq!(results[0].after.logo, "https://logos.example.com/live/1.ts");
+        assert_eq!(results[0].after.url, "http://cdn.provider.tv/live/1.ts?token=new&q=1&user=me");
+        assert_eq!(results[1].after.url, "not a url");
+        assert!(MapperScript::parse("@Group = url_host(@url)", None).is_err());
+        assert!(MapperScript::parse("@url = url_set_param(\"token\")", None).is_err());
+    }
+
+    #[test]
+    fn test_digest_functions() {
+        let script = r#"
+            name = lowercase(@Caption)
+            @epg_channel_id = uuid_v5("dns", name)
+            @Group = concat(md5(name), " ", sha1(name))
+            @Chno = uuid_v5("unknown", name)
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        let items: Vec<PlaylistItemHeader> = serde_json::from_str(r#"[{"title": "Python.org", "chno": "1"}]"#).unwrap();
+        let results = mapper.dry_run(items, None);
+        assert_eq!(results[0].after.epg_channel_id.as_deref(), Some("886313e1-3b8a-5372-9b90-0c9aee199e5d"));
+        assert_eq!(results[0].after.group, "db0fe87d2a4445fb20f06fa792ac61ea f008728016e4ebd6744956070bb83edb635a7907");
+        assert_eq!(results[0].after.chno, "1");
+        assert!(MapperScript::parse("@Group = uuid_v5(@Caption)", None).is_err());
+        assert!(MapperScript::parse("@Group = md5(@Caption, @Group)", None).is_err());
+    }
+
+    #[test]
+    fn test_warnings() {
+        let script = r#"
+            @"tvg-foo" = "x"
+            @Country = "DE"
+            q = map @Group {
+                "a" => 1,
+                _ => 2,
+                "b" => 3,
+            }
+            w = @Group
+            r = match {
+                q => "x",
+                _ => "y",
+                w => "z",
+            }
+        "#;
+        let mapper = MapperScript::parse(script, None).expect("Parsing failed");
+        assert_eq!(mapper.warnings(), vec![
+            "line 2:13: Unknown field @tvg-foo, it is handled as extended attribute",
+            "line 3:13: Field @Country is read only",
+            "line 4:13: Unreachable map case \"b\" after _",
+            "line 10:13: Unreachable match case w after _",
+        ]);
+        assert!(MapperScript::parse("@Caption = trim(@Caption)", None).unwrap().warnings().is_empty());
+        let err = MapperScript::parse("@Caption = trim(@Caption)\n@Group = concat(x, \"a\")", None).unwrap_err();
+        assert_eq!(err.message, "line 2:1: Identifier unknown x");
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = MapperScript::parse("@Caption = concat(@Caption", None).unwrap_err();
+        assert_eq!(err.message, "line 1:27: expected operator\n  @Caption = concat(@Caption\n                            ^\n  Hint: A ( is not closed with )");
+        let hint = |script: &str| MapperScript::parse(script, None).unwrap_err().message.lines().last().unwrap().to_string();
+        assert_eq!(hint("@Caption = 'abc'"), "  Hint: Strings are enclosed in double quotes");
+        assert_eq!(hint("@Caption = \"abc"), "  Hint: The string is not closed with \"");
+        assert_eq!(hint("a = map @Group {\n \"x\" => 1\n \"y\" => 2\n}"), "  Hint: The cases of map and match blocks are separated by ,");
+        assert_eq!(hint("x = @Caption ~ abc"), "  Hint: Regular expressions are enclosed in double quotes");
+        assert_eq!(hint("a = match {\n x => 1,\n"), "  Hint: A { is not closed with }");
+        let err = MapperScript::parse("@Caption = uppercase(Title)", None).unwrap_err();
+        assert_eq!(err.message, "line 1:1: Identifier unknown Title, did you mean the field @Title? Fields are prefixed with @");
+    }
 }
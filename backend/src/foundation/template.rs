@@ -0,0 +1,186 @@
+#![allow(clippy::empty_docs)]
+
+use pest::iterators::Pair;
+use pest::Parser;
+use serde_json::Value;
+use shared::error::{info_err, TuliproxError, TuliproxErrorKind};
+
+#[derive(Parser)]
+#[grammar_inline = r##"
+ws = _{ (" " | "\t")* }
+identifier = _{ (ASCII_ALPHA | "_") ~ (ASCII_ALPHANUMERIC | "_")* }
+path = @{ identifier ~ ("." ~ identifier)* }
+text = @{ (!"{{" ~ ANY)+ }
+comment = _{ "{{!" ~ (!"}}" ~ ANY)* ~ "}}" }
+raw_variable = { "{{{" ~ ws ~ path ~ ws ~ "}}}" }
+variable = { "{{" ~ ws ~ !("else" ~ ws ~ "}}") ~ path ~ ws ~ "}}" }
+condition_kind = { "if" | "unless" }
+block_open = _{ "{{" ~ ws ~ "#" ~ PUSH(condition_kind) ~ (" " | "\t")+ ~ path ~ ws ~ "}}" }
+block_else = _{ "{{" ~ ws ~ "else" ~ ws ~ "}}" }
+block_close = _{ "{{" ~ ws ~ "/" ~ POP ~ ws ~ "}}" }
+nodes = { (comment | condition | raw_variable | variable | text)* }
+condition = { block_open ~ nodes ~ (block_else ~ nodes)? ~ block_close }
+main = { SOI ~ nodes ~ EOI }
+"##]
+struct TemplateParser;
+
+#[derive(Debug, Clone)]
+enum TemplateNode {
+    Text(String),
+    Variable(Vec<String>, bool),
+    Condition {
+        negate: bool,
+        path: Vec<String>,
+        then: Vec<TemplateNode>,
+        otherwise: Vec<TemplateNode>,
+    },
+}
+
+/// A handlebars like template for user facing texts.
+///
+/// Supported are `{{variable}}` with dotted paths like `{{server.url}}`,
+/// `{{#if variable}}...{{else}}...{{/if}}`, `{{#unless variable}}...{{/unless}}` and `{{! comments }}`.
+/// Unknown variables render as empty text. For html output `{{variable}}` is escaped and `{{{variable}}}` is inserted raw.
+#[derive(Debug, Clone)]
+pub struct Template {
+    nodes: Vec<TemplateNode>,
+}
+
+fn parse_path(pair: &Pair<Rule>) -> Vec<String> {
+    pair.as_str().split('.').map(ToString::to_string).collect()
+}
+
+fn parse_nodes(pair: Pair<Rule>) -> Vec<TemplateNode> {
+    pair.into_inner().filter_map(|node| match node.as_rule() {
+        Rule::text => Some(TemplateNode::Text(node.as_str().to_string())),
+        Rule::variable => node.into_inner().next().map(|path| TemplateNode::Variable(parse_path(&path), true)),
+        Rule::raw_variable => node.into_inner().next().map(|path| TemplateNode::Variable(parse_path(&path), false)),
+        Rule::condition => {
+            let mut inner = node.into_inner();
+            let negate = inner.next().is_some_and(|kind| kind.as_str() == "unless");
+            let path = inner.next().map(|path| parse_path(&path)).unwrap_or_default();
+            let then = inner.next().map(parse_nodes).unwrap_or_default();
+            let otherwise = inner.next().map(parse_nodes).unwrap_or_default();
+            Some(TemplateNode::Condition { negate, path, then, otherwise })
+        }
+        _ => None,
+    }).collect()
+}
+
+fn lookup<'a>(context: &'a Value, path: &[String]) -> &'a Value {
+    path.iter().fold(context, |value, key| value.get(key).unwrap_or(&Value::Null))
+}
+
+fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(flag) => *flag,
+        Value::Number(number) => number.as_f64().is_some_and(|n| n != 0.0),
+        Value::String(text) => !text.is_empty(),
+        Value::Array(list) => !list.is_empty(),
+        Value::Object(_) => true,
+    }
+}
+
+fn push_html_escaped(output: &mut String, text: &str) {
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\'' => output.push_str("&#39;"),
+            _ => output.push(c),
+        }
+    }
+}
+
+fn render_nodes(nodes: &[TemplateNode], context: &Value, html: bool, output: &mut String) {
+    for node in nodes {
+        match node {
+            TemplateNode::Text(text) => output.push_str(text),
+            TemplateNode::Variable(path, escape) => {
+                let value = match lookup(context, path) {
+                    Value::Null => continue,
+                    Value::String(text) => text.clone(),
+                    value => value.to_string(),
+                };
+                if html && *escape {
+                    push_html_escaped(output, &value);
+                } else {
+                    output.push_str(&value);
+                }
+            }
+            TemplateNode::Condition { negate, path, then, otherwise } => {
+                if is_truthy(lookup(context, path)) == *negate {
+                    render_nodes(otherwise, context, html, output);
+                } else {
+                    render_nodes(then, context, html, output);
+                }
+            }
+        }
+    }
+}
+
+impl Template {
+    pub fn parse(input: &str) -> Result<Self, TuliproxError> {
+        let mut parsed = TemplateParser::parse(Rule::main, input).map_err(|e| info_err!(format!("Invalid template: {e}")))?;
+        let nodes = parsed.next()
+            .and_then(|main| main.into_inner().next())
+            .map(parse_nodes)
+            .unwrap_or_default();
+        Ok(Self { nodes })
+    }
+
+    pub fn render(&self, context: &Value) -> String {
+        let mut output = String::new();
+        render_nodes(&self.nodes, context, false, &mut output);
+        output
+    }
+
+    pub fn render_html(&self, context: &Value) -> String {
+        let mut output = String::new();
+        render_nodes(&self.nodes, context, true, &mut output);
+        output
+    }
+}
+
+/// Parses and renders the template in one step.
+pub fn render_template(input: &str, context: &Value) -> Result<String, TuliproxError> {
+    Ok(Template::parse(input)?.render(context))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::foundation::template::{render_template, Template};
+    use serde_json::json;
+
+    #[test]
+    fn test_render_template() {
+        let context = json!({
+            "username": "bob",
+            "max_connections": 2,
+            "trial": false,
+            "server": {"url": "http://tv.local:8901"},
+        });
+        let template = "Hi {{ username }}{{! greeting }}, visit {{server.url}}/get.php{{#if trial}} (trial){{/if}}\
+            {{#unless missing}}.{{else}}!{{/unless}} {{#if max_connections}}max {{max_connections}}{{else}}unlimited{{/if}}{{unknown}}";
+        assert_eq!(render_template(template, &context).unwrap(), "Hi bob, visit http://tv.local:8901/get.php. max 2");
+    }
+
+    #[test]
+    fn test_invalid_template() {
+        assert!(Template::parse("{{#if a}}missing close").is_err());
+        assert!(Template::parse("{{#if a}}wrong close{{/unless}}").is_err());
+        assert!(Template::parse("{{ not valid }}").is_err());
+        assert!(Template::parse("single {braces} are text").is_ok());
+    }
+
+    #[test]
+    fn test_render_html() {
+        let context = json!({"url": "http://tv.local/get.php?a=1&b=2", "svg": "<svg/>"});
+        let template = Template::parse("<a href=\"{{url}}\">{{{ svg }}}</a>").unwrap();
+        assert_eq!(template.render_html(&context), "<a href=\"http://tv.local/get.php?a=1&amp;b=2\"><svg/></a>");
+        assert_eq!(template.render(&context), "<a href=\"http://tv.local/get.php?a=1&b=2\"><svg/></a>");
+    }
+}
@@ -13,7 +13,7 @@ use crate::auth::generate_password;
 use crate::model::{Config, Healthcheck, HealthcheckConfig, ProcessTargets};
 use crate::processing::processor::playlist;
 use crate::utils::{config_file_reader, resolve_env_var};
-use crate::utils::request::{create_client, set_sanitize_sensitive_info};
+use crate::utils::request::{create_client, set_sanitize_rules, set_sanitize_sensitive_info};
 use chrono::{DateTime, Utc};
 use clap::Parser;
 use log::{error, info};
@@ -66,6 +66,20 @@ struct Args {
     #[arg(short = None, long = "healthcheck", default_value_t = false, default_missing_value = "true"
     )]
     healthcheck: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Create a redacted archive with configs, logs, status and version info for bug reports
+    DebugBundle(utils::DebugBundleArgs),
+    /// Parse the mapper scripts and filters referenced by the targets and report errors and warnings
+    ValidateMappings,
+    #[cfg(feature = "mock-provider")]
+    /// Run a synthetic xtream/m3u provider for testing
+    MockProvider(mock::MockProviderConfig),
 }
 
 
@@ -83,6 +97,16 @@ const BUILD_TIMESTAMP: &str = env!("VERGEN_BUILD_TIMESTAMP");
 fn main() {
     let args = Args::parse();
 
+    #[cfg(feature = "mock-provider")]
+    if let Some(Command::MockProvider(mock_config)) = &args.command {
+        init_logger(args.log_level.as_ref(), "");
+        let rt = tokio::runtime::Runtime::new().unwrap();
+        if let Err(err) = rt.block_on(mock::run_mock_provider(mock_config.clone())) {
+            exit!("Mock provider failed: {err}");
+        }
+        return;
+    }
+
     if args.genpwd {
         match generate_password() {
             Ok(pwd) => println!("{pwd}"),
@@ -95,6 +119,26 @@ fn main() {
     let config_file: String = resolve_env_var(&args.config_file.unwrap_or_else(|| utils::get_default_config_file_path(&config_path)));
     let api_proxy_file = resolve_env_var(&args.api_proxy.unwrap_or_else(|| utils::get_default_api_proxy_config_path(config_path.as_str())));
     let mappings_file = args.mapping_file.as_ref();
+    let sources_file: String = args.source_file.unwrap_or_else(|| utils::get_default_sources_file_path(&config_path));
+
+    if let Some(Command::DebugBundle(bundle_args)) = &args.command {
+        let mappings_path = mappings_file.cloned().unwrap_or_else(|| utils::get_default_mappings_path(&config_path));
+        let files = [config_file.as_str(), sources_file.as_str(), api_proxy_file.as_str(), mappings_path.as_str()];
+        match utils::create_debug_bundle(bundle_args, &config_file, &files) {
+            Ok(path) => println!("Debug bundle created: {}", path.display()),
+            Err(err) => {
+                eprintln!("Failed to create debug bundle: {err}");
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(Command::ValidateMappings) = &args.command {
+        let mappings_path = utils::get_validation_mapping_path(&config_file, mappings_file.cloned().unwrap_or_else(|| utils::get_default_mappings_path(&config_path)));
+        validate_mappings(&sources_file, &mappings_path);
+        return;
+    }
 
     init_logger(args.log_level.as_ref(), config_file.as_str());
 
@@ -107,12 +151,13 @@ fn main() {
         healthcheck(config_file.as_str());
     }
 
-    let sources_file: String = args.source_file.unwrap_or_else(|| utils::get_default_sources_file_path(&config_path));
     let cfg = utils::read_config(config_path.as_str(), config_file.as_str(),
                                              sources_file.as_str(), api_proxy_file.as_str(),
                                              mappings_file.cloned(), true).unwrap_or_else(|err| exit!("{}", err));
 
     set_sanitize_sensitive_info(cfg.log.as_ref().is_none_or(|l| l.sanitize_sensitive_info));
+    set_sanitize_rules(cfg.log.as_ref().map(|l| l.sanitize_rules.clone()).unwrap_or_default());
+    utils::init_crash_report(&cfg);
 
     let temp_path = PathBuf::from(&cfg.working_dir).join("tmp");
     create_directories(&cfg, &temp_path);
@@ -205,6 +250,23 @@ async fn start_in_server_mode(cfg: Arc<Config>, targets: Arc<ProcessTargets>) {
     }
 }
 
+fn validate_mappings(sources_file: &str, mappings_file: &str) {
+    match utils::validate_mappings(sources_file, mappings_file) {
+        Ok(validation) => {
+            validation.errors.iter().for_each(|err| println!("error: {err}"));
+            validation.warnings.iter().for_each(|warning| println!("warning: {warning}"));
+            println!("{} errors, {} warnings", validation.errors.len(), validation.warnings.len());
+            if !validation.is_valid() {
+                std::process::exit(1);
+            }
+        }
+        Err(err) => {
+            eprintln!("Failed to validate mappings: {err}");
+            std::process::exit(1);
+        }
+    }
+}
+
 fn healthcheck(config_file: &str) {
     let path = std::path::PathBuf::from(config_file);
     let file = File::open(path).expect("Failed to open config file");
@@ -1,9 +1,27 @@
 use std::sync::Arc;
-use crate::model::{MessagingConfig};
+use crate::foundation::template::render_template;
+use crate::model::{Config, EmailMessagingConfig, MessagingConfig, ProxyUserCredentials};
+use crate::utils::smtp::smtp_send;
 use log::{debug, error};
 use reqwest::{header};
 use shared::model::MsgKind;
 
+const DEFAULT_WELCOME_TEMPLATE: &str = "Hello {{user.username}},
+
+your IPTV account is ready.
+
+Xtream
+  Server: {{server.url}}
+  Username: {{user.username}}
+  Password: {{user.password}}
+
+M3U: {{m3u_url}}
+EPG: {{epg_url}}
+
+Expires: {{#if user.exp_date}}{{user.exp_date}}{{else}}unlimited{{/if}}
+Max connections: {{#if user.max_connections}}{{user.max_connections}}{{else}}unlimited{{/if}}
+";
+
 fn is_enabled(kind: MsgKind, cfg: &MessagingConfig) -> bool {
     cfg.notify_on.contains(&kind)
 }
@@ -71,12 +89,58 @@ fn send_pushover_message(client: &Arc<reqwest::Client>, msg: &str, messaging: &M
     }
 }
 
+fn send_email(email: &EmailMessagingConfig, to: Vec<String>, subject: String, msg: String) {
+    let email = email.clone();
+    tokio::spawn(async move {
+        match smtp_send(&email, &to, &subject, &msg).await {
+            Ok(()) => debug!("Email sent successfully to {}", to.join(", ")),
+            Err(e) => error!("Email wasn't sent to {} because of: {e}", to.join(", ")),
+        }
+    });
+}
+
+fn send_email_message(kind: MsgKind, msg: &str, messaging: &MessagingConfig) {
+    if let Some(email) = &messaging.email {
+        if !email.to.is_empty() {
+            let subject = format!("tuliprox {}", serde_json::to_value(kind).ok().and_then(|v| v.as_str().map(ToString::to_string)).unwrap_or_default());
+            send_email(email, email.to.clone(), subject, msg.to_owned());
+        }
+    }
+}
+
+fn render(template: &str, context: &serde_json::Value) -> String {
+    render_template(template, context).unwrap_or_else(|err| {
+        error!("Failed to render message template: {err}");
+        template.to_string()
+    })
+}
+
+fn create_message(kind: MsgKind, msg: &str, messaging: &MessagingConfig) -> String {
+    match messaging.templates.as_ref().and_then(|templates| templates.get(&kind)) {
+        Some(template) => render(template, &serde_json::json!({"kind": kind, "message": msg})),
+        None => msg.to_string(),
+    }
+}
+
+/// Sends the account details to a new user with an email address, if welcome emails are enabled.
+pub fn send_welcome_email(cfg: &Config, user: &ProxyUserCredentials) {
+    let Some(to) = user.email.as_ref().filter(|email| !email.trim().is_empty()) else { return };
+    let Some(email) = cfg.messaging.as_ref().and_then(|m| m.email.as_ref()) else { return };
+    let Some(welcome) = email.welcome.as_ref().filter(|w| w.enabled) else { return };
+    let context = cfg.get_user_template_context(user);
+    let subject = render(&welcome.subject, &context);
+    let msg = render(welcome.template.as_deref().unwrap_or(DEFAULT_WELCOME_TEMPLATE), &context);
+    send_email(email, vec![to.trim().to_string()], subject, msg);
+}
+
 pub fn send_message(client: &Arc<reqwest::Client>, kind: &MsgKind, cfg: Option<&MessagingConfig>, msg: &str) {
     if let Some(messaging) = cfg {
         if is_enabled(*kind, messaging) {
-            send_telegram_message(msg, messaging);
-            send_http_post_request(client, msg, messaging);
-            send_pushover_message(client, msg, messaging);
+            let msg = create_message(*kind, msg, messaging);
+            send_telegram_message(&msg, messaging);
+            send_http_post_request(client, &msg, messaging);
+            send_pushover_message(client, &msg, messaging);
+            send_email_message(*kind, &msg, messaging);
         }
     }
 }
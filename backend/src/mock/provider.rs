@@ -0,0 +1,635 @@
+use crate::model::{XC_ACTION_GET_ACCOUNT_INFO, XC_ACTION_GET_LIVE_CATEGORIES, XC_ACTION_GET_LIVE_STREAMS,
+                   XC_ACTION_GET_SERIES, XC_ACTION_GET_SERIES_CATEGORIES, XC_ACTION_GET_SERIES_INFO,
+                   XC_ACTION_GET_VOD_CATEGORIES, XC_ACTION_GET_VOD_INFO, XC_ACTION_GET_VOD_STREAMS};
+use axum::body::Body;
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use bytes::Bytes;
+use log::{debug, info};
+use serde_json::{json, Value};
+use shared::model::XtreamCluster;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+const DEFAULT_HOST: &str = "127.0.0.1";
+const DEFAULT_PORT: u16 = 8901;
+const DEFAULT_CREDENTIAL: &str = "mock";
+const DEFAULT_LIVE_COUNT: u32 = 10;
+const DEFAULT_VOD_COUNT: u32 = 10;
+const DEFAULT_SERIES_COUNT: u32 = 5;
+const DEFAULT_EPISODE_COUNT: u32 = 3;
+const DEFAULT_CATEGORY_COUNT: u32 = 2;
+const DEFAULT_VOD_SIZE: u64 = 10 * 1024 * 1024;
+const DEFAULT_VOD_DURATION_SECS: u64 = 600;
+const DEFAULT_LIVE_BITRATE_KBPS: u64 = 2000;
+
+const VOD_ID_BASE: u32 = 100_000;
+const SERIES_ID_BASE: u32 = 200_000;
+const EPISODE_ID_FACTOR: u32 = 100;
+
+const TS_PACKET_SIZE: usize = 188;
+const TS_PACKETS_PER_CHUNK: usize = 7;
+const TS_CHUNK_SIZE: usize = TS_PACKET_SIZE * TS_PACKETS_PER_CHUNK;
+// MPEG-TS null packet (PID 0x1FFF), decoders skip it, so it is a valid but empty stream.
+const TS_NULL_PACKET_HEADER: [u8; 4] = [0x47, 0x1F, 0xFF, 0x10];
+
+// 509 Bandwidth Limit Exceeded is what most xtream providers answer when all connections are in use.
+const STATUS_CONNECTION_LIMIT: u16 = 509;
+
+/// Settings of the mock provider, they are also the arguments of the `mock-provider` command.
+#[derive(Debug, Clone, clap::Args)]
+pub struct MockProviderConfig {
+    /// The address to listen on
+    #[arg(long, default_value = DEFAULT_HOST)]
+    pub host: String,
+
+    /// The port to listen on, 0 picks a free port
+    #[arg(long, default_value_t = DEFAULT_PORT)]
+    pub port: u16,
+
+    /// The provider username
+    #[arg(long, default_value = DEFAULT_CREDENTIAL)]
+    pub username: String,
+
+    /// The provider password
+    #[arg(long, default_value = DEFAULT_CREDENTIAL)]
+    pub password: String,
+
+    /// Number of live channels
+    #[arg(long, default_value_t = DEFAULT_LIVE_COUNT)]
+    pub live_count: u32,
+
+    /// Number of movies
+    #[arg(long, default_value_t = DEFAULT_VOD_COUNT)]
+    pub vod_count: u32,
+
+    /// Number of series
+    #[arg(long, default_value_t = DEFAULT_SERIES_COUNT)]
+    pub series_count: u32,
+
+    /// Number of episodes for each series
+    #[arg(long, default_value_t = DEFAULT_EPISODE_COUNT)]
+    pub episode_count: u32,
+
+    /// Number of categories for each cluster
+    #[arg(long, default_value_t = DEFAULT_CATEGORY_COUNT)]
+    pub category_count: u32,
+
+    /// Maximum number of parallel streams, 0 is unlimited
+    #[arg(long, default_value_t = 0)]
+    pub max_connections: usize,
+
+    /// Answer stream requests with a redirect to the real stream location
+    #[arg(long, default_value_t = false)]
+    pub redirect: bool,
+
+    /// Size of a movie or episode in bytes
+    #[arg(long, default_value_t = DEFAULT_VOD_SIZE)]
+    pub vod_size: u64,
+
+    /// Duration of a movie or episode in seconds
+    #[arg(long, default_value_t = DEFAULT_VOD_DURATION_SECS)]
+    pub vod_duration_secs: u64,
+
+    /// Bitrate of the endless live streams in kbit/s
+    #[arg(long, default_value_t = DEFAULT_LIVE_BITRATE_KBPS)]
+    pub live_bitrate_kbps: u64,
+}
+
+impl Default for MockProviderConfig {
+    fn default() -> Self {
+        Self {
+            host: DEFAULT_HOST.to_string(),
+            port: DEFAULT_PORT,
+            username: DEFAULT_CREDENTIAL.to_string(),
+            password: DEFAULT_CREDENTIAL.to_string(),
+            live_count: DEFAULT_LIVE_COUNT,
+            vod_count: DEFAULT_VOD_COUNT,
+            series_count: DEFAULT_SERIES_COUNT,
+            episode_count: DEFAULT_EPISODE_COUNT,
+            category_count: DEFAULT_CATEGORY_COUNT,
+            max_connections: 0,
+            redirect: false,
+            vod_size: DEFAULT_VOD_SIZE,
+            vod_duration_secs: DEFAULT_VOD_DURATION_SECS,
+            live_bitrate_kbps: DEFAULT_LIVE_BITRATE_KBPS,
+        }
+    }
+}
+
+struct MockProviderState {
+    config: MockProviderConfig,
+    connections: Arc<AtomicUsize>,
+}
+
+impl MockProviderState {
+    fn is_authorized(&self, username: &str, password: &str) -> bool {
+        self.config.username == username && self.config.password == password
+    }
+
+    fn is_authorized_query(&self, params: &HashMap<String, String>) -> bool {
+        match (params.get("username"), params.get("password")) {
+            (Some(username), Some(password)) => self.is_authorized(username, password),
+            _ => false,
+        }
+    }
+
+    fn acquire_connection(&self) -> Option<ConnectionGuard> {
+        let count = self.connections.fetch_add(1, Ordering::SeqCst) + 1;
+        let guard = ConnectionGuard { connections: Arc::clone(&self.connections) };
+        if self.config.max_connections > 0 && count > self.config.max_connections {
+            drop(guard);
+            return None;
+        }
+        Some(guard)
+    }
+
+    fn category_id(&self, cluster: XtreamCluster, index: u32) -> u32 {
+        let base = match cluster {
+            XtreamCluster::Live => 100,
+            XtreamCluster::Video => 200,
+            XtreamCluster::Series => 300,
+        };
+        base + (index % self.config.category_count.max(1)) + 1
+    }
+
+    fn vod_bitrate_kbps(&self) -> u64 {
+        self.config.vod_size * 8 / 1000 / self.config.vod_duration_secs.max(1)
+    }
+}
+
+/// Decrements the active connection count when the stream is dropped.
+struct ConnectionGuard {
+    connections: Arc<AtomicUsize>,
+}
+
+impl Drop for ConnectionGuard {
+    fn drop(&mut self) {
+        self.connections.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn get_base_url(headers: &HeaderMap, state: &MockProviderState) -> String {
+    let host = headers.get(header::HOST).and_then(|h| h.to_str().ok())
+        .map_or_else(|| format!("{}:{}", state.config.host, state.config.port), ToString::to_string);
+    format!("http://{host}")
+}
+
+fn now_secs() -> i64 {
+    chrono::Utc::now().timestamp()
+}
+
+fn json_response(value: &Value) -> Response {
+    axum::Json(value).into_response()
+}
+
+fn unauthorized_response() -> Response {
+    json_response(&json!({"user_info": {"auth": 0}}))
+}
+
+fn create_categories(state: &MockProviderState, cluster: XtreamCluster) -> Value {
+    let name = match cluster {
+        XtreamCluster::Live => "Live",
+        XtreamCluster::Video => "Movies",
+        XtreamCluster::Series => "Series",
+    };
+    Value::Array((0..state.config.category_count.max(1)).map(|index| {
+        let category_id = state.category_id(cluster, index);
+        json!({"category_id": category_id.to_string(), "category_name": format!("{name} {}", index + 1), "parent_id": 0})
+    }).collect())
+}
+
+fn create_live_streams(state: &MockProviderState) -> Value {
+    Value::Array((1..=state.config.live_count).map(|stream_id| {
+        let category_id = state.category_id(XtreamCluster::Live, stream_id - 1);
+        json!({
+            "num": stream_id,
+            "name": format!("Live {stream_id}"),
+            "stream_type": "live",
+            "stream_id": stream_id,
+            "stream_icon": "",
+            "epg_channel_id": format!("mock.live.{stream_id}"),
+            "added": "0",
+            "category_id": category_id.to_string(),
+            "category_ids": [category_id],
+            "custom_sid": "",
+            "tv_archive": 0,
+            "direct_source": "",
+            "tv_archive_duration": 0
+        })
+    }).collect())
+}
+
+fn create_vod_streams(state: &MockProviderState) -> Value {
+    Value::Array((1..=state.config.vod_count).map(|index| {
+        let category_id = state.category_id(XtreamCluster::Video, index - 1);
+        json!({
+            "num": index,
+            "name": format!("Movie {index}"),
+            "stream_type": "movie",
+            "stream_id": VOD_ID_BASE + index,
+            "stream_icon": "",
+            "rating": "",
+            "added": "0",
+            "category_id": category_id.to_string(),
+            "category_ids": [category_id],
+            "container_extension": "ts",
+            "custom_sid": "",
+            "direct_source": ""
+        })
+    }).collect())
+}
+
+fn create_series(state: &MockProviderState) -> Value {
+    Value::Array((1..=state.config.series_count).map(|index| {
+        let category_id = state.category_id(XtreamCluster::Series, index - 1);
+        json!({
+            "num": index,
+            "name": format!("Series {index}"),
+            "series_id": SERIES_ID_BASE + index,
+            "cover": "",
+            "plot": "",
+            "cast": "",
+            "director": "",
+            "genre": "",
+            "releaseDate": "",
+            "last_modified": "0",
+            "rating": "",
+            "category_id": category_id.to_string(),
+            "category_ids": [category_id],
+            "backdrop_path": [],
+            "youtube_trailer": "",
+            "episode_run_time": "",
+        })
+    }).collect())
+}
+
+fn create_vod_info(state: &MockProviderState, stream_id: u32) -> Value {
+    let duration_secs = state.config.vod_duration_secs;
+    json!({
+        "info": {
+            "name": format!("Movie {}", stream_id.saturating_sub(VOD_ID_BASE)),
+            "duration_secs": duration_secs,
+            "duration": format!("{:02}:{:02}:{:02}", duration_secs / 3600, (duration_secs % 3600) / 60, duration_secs % 60),
+            "bitrate": state.vod_bitrate_kbps(),
+        },
+        "movie_data": {
+            "stream_id": stream_id,
+            "name": format!("Movie {}", stream_id.saturating_sub(VOD_ID_BASE)),
+            "added": "0",
+            "category_id": state.category_id(XtreamCluster::Video, stream_id.saturating_sub(VOD_ID_BASE + 1)).to_string(),
+            "container_extension": "ts",
+            "custom_sid": "",
+            "direct_source": ""
+        }
+    })
+}
+
+fn create_series_info(state: &MockProviderState, series_id: u32) -> Value {
+    let episodes: Vec<Value> = (1..=state.config.episode_count).map(|episode| json!({
+        "id": (series_id * EPISODE_ID_FACTOR + episode).to_string(),
+        "episode_num": episode,
+        "title": format!("Series {} - S01E{episode:02}", series_id.saturating_sub(SERIES_ID_BASE)),
+        "container_extension": "ts",
+        "info": {"duration_secs": state.config.vod_duration_secs, "bitrate": state.vod_bitrate_kbps()},
+        "custom_sid": "",
+        "added": "0",
+        "season": 1,
+        "direct_source": ""
+    })).collect();
+    json!({
+        "seasons": [{"season_number": 1, "name": "Season 1", "episode_count": state.config.episode_count}],
+        "info": {
+            "name": format!("Series {}", series_id.saturating_sub(SERIES_ID_BASE)),
+            "category_id": state.category_id(XtreamCluster::Series, series_id.saturating_sub(SERIES_ID_BASE + 1)).to_string(),
+        },
+        "episodes": {"1": episodes}
+    })
+}
+
+fn create_login_info(state: &MockProviderState, headers: &HeaderMap) -> Value {
+    let now = now_secs();
+    let base_url = get_base_url(headers, state);
+    let (url, port) = base_url.trim_start_matches("http://").rsplit_once(':')
+        .map_or_else(|| (base_url.clone(), "80".to_string()), |(url, port)| (url.to_string(), port.to_string()));
+    json!({
+        "user_info": {
+            "username": state.config.username,
+            "password": state.config.password,
+            "message": "",
+            "auth": 1,
+            "status": "Active",
+            "exp_date": (now + 30 * 24 * 60 * 60).to_string(),
+            "is_trial": "0",
+            "active_cons": state.connections.load(Ordering::SeqCst).to_string(),
+            "created_at": now.to_string(),
+            "max_connections": state.config.max_connections.to_string(),
+            "allowed_output_formats": ["m3u8", "ts"]
+        },
+        "server_info": {
+            "url": url,
+            "port": port,
+            "https_port": "",
+            "server_protocol": "http",
+            "rtmp_port": "",
+            "timezone": "UTC",
+            "timestamp_now": now,
+            "time_now": chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()
+        }
+    })
+}
+
+async fn player_api(State(state): State<Arc<MockProviderState>>, headers: HeaderMap,
+                    Query(params): Query<HashMap<String, String>>) -> Response {
+    if !state.is_authorized_query(&params) {
+        return unauthorized_response();
+    }
+    let get_id = |key: &str| params.get(key).and_then(|id| id.parse::<u32>().ok()).unwrap_or_default();
+    let response = match params.get("action").map_or("", String::as_str) {
+        "" | XC_ACTION_GET_ACCOUNT_INFO => create_login_info(&state, &headers),
+        XC_ACTION_GET_LIVE_CATEGORIES => create_categories(&state, XtreamCluster::Live),
+        XC_ACTION_GET_VOD_CATEGORIES => create_categories(&state, XtreamCluster::Video),
+        XC_ACTION_GET_SERIES_CATEGORIES => create_categories(&state, XtreamCluster::Series),
+        XC_ACTION_GET_LIVE_STREAMS => create_live_streams(&state),
+        XC_ACTION_GET_VOD_STREAMS => create_vod_streams(&state),
+        XC_ACTION_GET_SERIES => create_series(&state),
+        XC_ACTION_GET_VOD_INFO => create_vod_info(&state, get_id("vod_id")),
+        XC_ACTION_GET_SERIES_INFO => create_series_info(&state, get_id("series_id")),
+        _ => Value::Array(vec![]),
+    };
+    json_response(&response)
+}
+
+async fn get_m3u(State(state): State<Arc<MockProviderState>>, headers: HeaderMap,
+                 Query(params): Query<HashMap<String, String>>) -> Response {
+    if !state.is_authorized_query(&params) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let base_url = get_base_url(&headers, &state);
+    let username = &state.config.username;
+    let password = &state.config.password;
+    let mut content = String::from("#EXTM3U\n");
+    for stream_id in 1..=state.config.live_count {
+        let group = state.category_id(XtreamCluster::Live, stream_id - 1);
+        let _ = write!(content, "#EXTINF:-1 tvg-id=\"mock.live.{stream_id}\" tvg-name=\"Live {stream_id}\" group-title=\"Live {group}\",Live {stream_id}\n{base_url}/live/{username}/{password}/{stream_id}.ts\n");
+    }
+    for index in 1..=state.config.vod_count {
+        let group = state.category_id(XtreamCluster::Video, index - 1);
+        let stream_id = VOD_ID_BASE + index;
+        let _ = write!(content, "#EXTINF:-1 tvg-name=\"Movie {index}\" group-title=\"Movies {group}\",Movie {index}\n{base_url}/movie/{username}/{password}/{stream_id}.ts\n");
+    }
+    for index in 1..=state.config.series_count {
+        let group = state.category_id(XtreamCluster::Series, index - 1);
+        let series_id = SERIES_ID_BASE + index;
+        for episode in 1..=state.config.episode_count {
+            let episode_id = series_id * EPISODE_ID_FACTOR + episode;
+            let _ = write!(content, "#EXTINF:-1 tvg-name=\"Series {index} S01E{episode:02}\" group-title=\"Series {group}\",Series {index} S01E{episode:02}\n{base_url}/series/{username}/{password}/{episode_id}.ts\n");
+        }
+    }
+    ([(header::CONTENT_TYPE, "audio/x-mpegurl")], content).into_response()
+}
+
+async fn get_xmltv(State(state): State<Arc<MockProviderState>>, Query(params): Query<HashMap<String, String>>, headers: HeaderMap) -> Response {
+    if !state.is_authorized_query(&params) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let hour = 60 * 60;
+    let now = now_secs();
+    let start = now - (now % hour) - 2 * hour;
+    // the guide changes every hour
+    let etag = format!("\"{start}\"");
+    if headers.get(header::IF_NONE_MATCH).is_some_and(|value| value.as_bytes() == etag.as_bytes()) {
+        return StatusCode::NOT_MODIFIED.into_response();
+    }
+    let format_time = |timestamp: i64| chrono::DateTime::from_timestamp(timestamp, 0)
+        .map_or_else(String::new, |dt| dt.format("%Y%m%d%H%M%S +0000").to_string());
+    let mut content = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<tv generator-info-name=\"tuliprox-mock\">\n");
+    for stream_id in 1..=state.config.live_count {
+        let _ = writeln!(content, "  <channel id=\"mock.live.{stream_id}\"><display-name>Live {stream_id}</display-name></channel>");
+    }
+    for stream_id in 1..=state.config.live_count {
+        for slot in 0..6 {
+            let programme_start = start + slot * hour;
+            let _ = writeln!(content, "  <programme start=\"{}\" stop=\"{}\" channel=\"mock.live.{stream_id}\"><title>Live {stream_id} Show {}</title></programme>",
+                             format_time(programme_start), format_time(programme_start + hour), slot + 1);
+        }
+    }
+    content.push_str("</tv>\n");
+    ([(header::CONTENT_TYPE, "application/xml".to_string()), (header::ETAG, etag)], content).into_response()
+}
+
+/// Returns `size` bytes of the endless null packet stream starting at `offset`.
+fn create_ts_chunk(offset: u64, size: usize) -> Bytes {
+    let mut chunk = Vec::with_capacity(size);
+    #[allow(clippy::cast_possible_truncation)]
+    let mut position = (offset % TS_PACKET_SIZE as u64) as usize;
+    while chunk.len() < size {
+        chunk.push(if position < TS_NULL_PACKET_HEADER.len() { TS_NULL_PACKET_HEADER[position] } else { 0xFF });
+        position = (position + 1) % TS_PACKET_SIZE;
+    }
+    Bytes::from(chunk)
+}
+
+fn parse_range(headers: &HeaderMap, size: u64) -> Option<(u64, u64)> {
+    let range = headers.get(header::RANGE)?.to_str().ok()?.strip_prefix("bytes=")?;
+    let (start, end) = range.split_once('-')?;
+    let start = start.trim().parse::<u64>().ok()?;
+    let end = end.trim().parse::<u64>().ok().map_or(size - 1, |end| end.min(size - 1));
+    (start <= end).then_some((start, end))
+}
+
+fn live_stream_response(state: &MockProviderState, guard: ConnectionGuard) -> Response {
+    let interval = Duration::from_micros((TS_CHUNK_SIZE as u64 * 8 * 1000) / state.config.live_bitrate_kbps.max(1));
+    let stream = futures::stream::unfold((guard, 0_u64), move |(guard, offset)| async move {
+        tokio::time::sleep(interval).await;
+        let chunk = create_ts_chunk(offset, TS_CHUNK_SIZE);
+        Some((Ok::<Bytes, std::io::Error>(chunk), (guard, offset + TS_CHUNK_SIZE as u64)))
+    });
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "video/mp2t")
+        .body(Body::from_stream(stream))
+        .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+fn file_stream_response(state: &MockProviderState, headers: &HeaderMap, guard: ConnectionGuard) -> Response {
+    let size = state.config.vod_size.max(1);
+    let range = parse_range(headers, size);
+    if headers.contains_key(header::RANGE) && range.is_none() {
+        return Response::builder()
+            .status(StatusCode::RANGE_NOT_SATISFIABLE)
+            .header(header::CONTENT_RANGE, format!("bytes */{size}"))
+            .body(Body::empty())
+            .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response());
+    }
+    let (start, end) = range.unwrap_or((0, size - 1));
+    let stream = futures::stream::unfold((guard, start), move |(guard, offset)| async move {
+        if offset > end {
+            return None;
+        }
+        #[allow(clippy::cast_possible_truncation)]
+        let chunk_size = (end - offset + 1).min(TS_CHUNK_SIZE as u64) as usize;
+        Some((Ok::<Bytes, std::io::Error>(create_ts_chunk(offset, chunk_size)), (guard, offset + chunk_size as u64)))
+    });
+    let mut builder = Response::builder()
+        .header(header::CONTENT_TYPE, "video/mp2t")
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::CONTENT_LENGTH, (end - start + 1).to_string());
+    builder = if range.is_some() {
+        builder.status(StatusCode::PARTIAL_CONTENT).header(header::CONTENT_RANGE, format!("bytes {start}-{end}/{size}"))
+    } else {
+        builder.status(StatusCode::OK)
+    };
+    builder.body(Body::from_stream(stream)).unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+fn serve_stream(state: &MockProviderState, headers: &HeaderMap, kind: &str, username: &str, password: &str, file: &str) -> Response {
+    if !state.is_authorized(username, password) {
+        return StatusCode::FORBIDDEN.into_response();
+    }
+    let stream_id = file.split_once('.').map_or(file, |(id, _)| id);
+    if stream_id.parse::<u32>().is_err() {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+    let Some(guard) = state.acquire_connection() else {
+        debug!("Mock provider connection limit exhausted for {kind}/{file}");
+        return StatusCode::from_u16(STATUS_CONNECTION_LIMIT).unwrap_or(StatusCode::SERVICE_UNAVAILABLE).into_response();
+    };
+    match kind {
+        "live" => live_stream_response(state, guard),
+        "movie" | "series" => file_stream_response(state, headers, guard),
+        _ => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+async fn stream(State(state): State<Arc<MockProviderState>>, headers: HeaderMap,
+                Path((kind, username, password, file)): Path<(String, String, String, String)>) -> Response {
+    if state.config.redirect {
+        if !state.is_authorized(&username, &password) {
+            return StatusCode::FORBIDDEN.into_response();
+        }
+        let location = format!("{}/stream/{kind}/{username}/{password}/{file}", get_base_url(&headers, &state));
+        return Response::builder()
+            .status(StatusCode::FOUND)
+            .header(header::LOCATION, location)
+            .body(Body::empty())
+            .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response());
+    }
+    serve_stream(&state, &headers, &kind, &username, &password, &file)
+}
+
+async fn redirected_stream(State(state): State<Arc<MockProviderState>>, headers: HeaderMap,
+                           Path((kind, username, password, file)): Path<(String, String, String, String)>) -> Response {
+    serve_stream(&state, &headers, &kind, &username, &password, &file)
+}
+
+fn create_router(config: MockProviderConfig) -> axum::Router {
+    let state = Arc::new(MockProviderState { config, connections: Arc::new(AtomicUsize::new(0)) });
+    axum::Router::new()
+        .route("/player_api.php", axum::routing::get(player_api))
+        .route("/get.php", axum::routing::get(get_m3u))
+        .route("/xmltv.php", axum::routing::get(get_xmltv))
+        .route("/{kind}/{username}/{password}/{file}", axum::routing::get(stream))
+        .route("/stream/{kind}/{username}/{password}/{file}", axum::routing::get(redirected_stream))
+        .with_state(state)
+}
+
+/// Starts the mock provider in the background and returns the bound address.
+/// With port `0` a free port is picked, which is what the integration tests use.
+pub async fn spawn_mock_provider(mut config: MockProviderConfig) -> std::io::Result<SocketAddr> {
+    let listener = tokio::net::TcpListener::bind(format!("{}:{}", config.host, config.port)).await?;
+    let addr = listener.local_addr()?;
+    config.port = addr.port();
+    let router = create_router(config);
+    tokio::spawn(async move {
+        let _ = axum::serve(listener, router).await;
+    });
+    Ok(addr)
+}
+
+/// Runs the mock provider until the process is stopped.
+pub async fn run_mock_provider(config: MockProviderConfig) -> std::io::Result<()> {
+    let listener = tokio::net::TcpListener::bind(format!("{}:{}", config.host, config.port)).await?;
+    let addr = listener.local_addr()?;
+    info!("Mock provider listening on http://{addr} (username: {}, password: {})", config.username, config.password);
+    info!("Xtream: http://{addr}/player_api.php?username={}&password={}", config.username, config.password);
+    info!("M3U: http://{addr}/get.php?username={}&password={}", config.username, config.password);
+    axum::serve(listener, create_router(config)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::mock::{spawn_mock_provider, MockProviderConfig};
+    use crate::model::{Config, ConfigInput};
+    use crate::utils::request::{get_download_validators_path, get_input_epg_content_as_file};
+    use crate::utils::xtream::get_xtream_playlist;
+    use reqwest::StatusCode;
+    use std::sync::Arc;
+
+    fn test_config() -> MockProviderConfig {
+        MockProviderConfig { port: 0, ..MockProviderConfig::default() }
+    }
+
+    #[tokio::test]
+    async fn test_mock_provider_xtream_playlist() {
+        let addr = spawn_mock_provider(test_config()).await.unwrap();
+        let mut input: ConfigInput = serde_yaml::from_str(&format!(
+            "{{name: mock, type: xtream, url: 'http://{addr}', username: mock, password: mock}}")).unwrap();
+        input.prepare(0, false).unwrap();
+        let working_dir = tempfile::tempdir().unwrap();
+        let (groups, errors) = get_xtream_playlist(&Config::default(), Arc::new(reqwest::Client::new()), &input,
+                                                   working_dir.path().to_str().unwrap()).await;
+        assert!(errors.is_empty(), "{errors:?}");
+        let count: usize = groups.iter().map(|g| g.channels.len()).sum();
+        assert_eq!(count, 25);
+    }
+
+    #[tokio::test]
+    async fn test_mock_provider_xmltv_not_modified() {
+        let addr = spawn_mock_provider(test_config()).await.unwrap();
+        let mut input: ConfigInput = serde_yaml::from_str(&format!(
+            "{{name: mock, type: xtream, url: 'http://{addr}', username: mock, password: mock}}")).unwrap();
+        input.prepare(0, false).unwrap();
+        let working_dir = tempfile::tempdir().unwrap();
+        let file_path = working_dir.path().join("mock_epg.xml");
+        let url = format!("http://{addr}/xmltv.php?username=mock&password=mock");
+        let client = Arc::new(reqwest::Client::new());
+
+        get_input_epg_content_as_file(Arc::clone(&client), &input, "", &url, Some(file_path.clone())).await.unwrap();
+        assert!(std::fs::read_to_string(&file_path).unwrap().contains("mock.live.1"));
+        assert!(get_download_validators_path(&file_path).exists());
+
+        // the provider answers with 304, the stored file is kept
+        std::fs::write(&file_path, "cached").unwrap();
+        get_input_epg_content_as_file(Arc::clone(&client), &input, "", &url, Some(file_path.clone())).await.unwrap();
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "cached");
+    }
+
+    #[tokio::test]
+    async fn test_mock_provider_streams() {
+        let config = MockProviderConfig { max_connections: 1, redirect: true, vod_size: 1000, ..test_config() };
+        let addr = spawn_mock_provider(config).await.unwrap();
+        let client = reqwest::Client::new();
+
+        let response = client.get(format!("http://{addr}/movie/mock/mock/100001.ts"))
+            .header("Range", "bytes=100-").send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::PARTIAL_CONTENT);
+        assert_eq!(response.headers().get("content-range").unwrap(), "bytes 100-999/1000");
+        assert_eq!(response.bytes().await.unwrap().len(), 900);
+
+        let live = client.get(format!("http://{addr}/live/mock/mock/1.ts")).send().await.unwrap();
+        assert_eq!(live.status(), StatusCode::OK);
+        assert!(live.url().path().starts_with("/stream/"));
+        let second = client.get(format!("http://{addr}/live/mock/mock/2.ts")).send().await.unwrap();
+        assert_eq!(second.status().as_u16(), 509);
+        drop(live);
+
+        let forbidden = client.get(format!("http://{addr}/get.php?username=mock&password=wrong")).send().await.unwrap();
+        assert_eq!(forbidden.status(), StatusCode::FORBIDDEN);
+    }
+}
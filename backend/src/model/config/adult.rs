@@ -0,0 +1,38 @@
+use crate::foundation::filter::{apply_templates_to_pattern_single, PatternTemplate};
+use shared::error::{create_tuliprox_error_result, TuliproxError, TuliproxErrorKind};
+use shared::utils::default_as_true;
+
+pub const DEFAULT_ADULT_PATTERN: &str = r"(?i)(?:\b(?:xxx|adults?|porn\w*|erotic\w*|playboy|hustler|brazzers|redlight)\b|(?:^|[^0-9])18\s*\+)";
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigAdultDetection {
+    // match against channel name, title and group title, default detects common adult keywords and `18+`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    // trust the `is_adult` flag of xtream providers
+    #[serde(default = "default_as_true")]
+    pub provider_flag: bool,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub t_re: Option<regex::Regex>,
+}
+
+impl ConfigAdultDetection {
+    pub fn prepare(&mut self, templates: Option<&Vec<PatternTemplate>>) -> Result<(), TuliproxError> {
+        let pattern = match self.pattern.as_ref() {
+            Some(pattern) => apply_templates_to_pattern_single(pattern, templates)?,
+            None => DEFAULT_ADULT_PATTERN.to_string(),
+        };
+        match regex::Regex::new(&pattern) {
+            Ok(re) => {
+                self.t_re = Some(re);
+                Ok(())
+            }
+            Err(err) => create_tuliprox_error_result!(TuliproxErrorKind::Info, "cant parse regex: {} {err}", &pattern),
+        }
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.t_re.as_ref().is_some_and(|re| re.is_match(text))
+    }
+}
@@ -2,15 +2,27 @@ use crate::api::model::app_state::AppState;
 use shared::error::{info_err, TuliproxError, TuliproxErrorKind};
 use crate::model::{Config};
 use crate::repository::user_repository::{backup_api_user_db_file, get_api_user_db_path, load_api_user, merge_api_user};
-use crate::utils::{save_api_proxy};
+use crate::utils::{parse_utc_offset, save_api_proxy};
 use shared::utils::{default_as_true};
-use chrono::Local;
+use chrono::{FixedOffset, Local};
 use log::debug;
 use std::cmp::PartialEq;
-use std::collections::HashSet;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use shared::model::{ProxyType, ProxyUserStatus, UserConnectionPermission};
+use shared::model::{ProxyType, ProxyUserStatus, UserConnectionPermission, XtreamCluster};
 use crate::utils;
+use crate::utils::request::format_url_host;
+
+/// A named device of a user with its own token, the devices share the `max_connections` of the user.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProxyUserDevice {
+    pub name: String,
+    pub token: String,
+    #[serde(default = "default_as_true")]
+    pub enabled: bool,
+}
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -37,6 +49,22 @@ pub struct ProxyUserCredentials {
     pub ui_enabled: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub comment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub devices: Option<Vec<ProxyUserDevice>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    // the device name when the user was authenticated with a device token
+    #[serde(skip)]
+    pub t_device: Option<String>,
+    // set when the request came from an ipv6 client, selects the `host_v6` of the server info
+    #[serde(skip)]
+    pub t_ipv6_client: bool,
 }
 
 impl ProxyUserCredentials {
@@ -46,9 +74,16 @@ impl ProxyUserCredentials {
 
     pub fn matches_token(&self, token: &str) -> bool {
         if let Some(tkn) = &self.token {
-            return tkn.eq(token);
+            if tkn.eq(token) {
+                return true;
+            }
         }
-        false
+        self.get_device_by_token(token).is_some()
+    }
+
+    /// Returns the enabled device with the token.
+    pub fn get_device_by_token(&self, token: &str) -> Option<&ProxyUserDevice> {
+        self.devices.as_ref()?.iter().find(|device| device.enabled && device.token == token)
     }
 
     pub fn matches(&self, username: &str, password: &str) -> bool {
@@ -64,6 +99,13 @@ impl ProxyUserCredentials {
                 self.token = Some(tkn.trim().to_string());
             }
         }
+        if let Some(devices) = self.devices.as_mut() {
+            for device in devices {
+                device.name = device.name.trim().to_string();
+                device.token = device.token.trim().to_string();
+            }
+        }
+        self.package = self.package.as_ref().map(|package| package.trim().to_string()).filter(|package| !package.is_empty());
     }
 
     pub fn validate(&self) -> Result<(), TuliproxError> {
@@ -73,9 +115,16 @@ impl ProxyUserCredentials {
         if self.password.is_empty() {
             return Err(TuliproxError::new(TuliproxErrorKind::Info, "Password required".to_string()));
         }
+        if self.timezone.as_ref().is_some_and(|tz| parse_utc_offset(tz).is_none()) {
+            return Err(TuliproxError::new(TuliproxErrorKind::Info, "Invalid timezone, expected utc offset like +02:00".to_string()));
+        }
         Ok(())
     }
 
+    pub fn get_utc_offset(&self) -> Option<FixedOffset> {
+        self.timezone.as_deref().and_then(parse_utc_offset)
+    }
+
     pub fn has_permissions(&self, app_state: &AppState) -> bool {
         if app_state.config.user_access_control {
             if let Some(exp_date) = self.exp_date.as_ref() {
@@ -101,10 +150,10 @@ impl ProxyUserCredentials {
         !self.has_permissions(app_state)
     }
 
-    pub async fn connection_permission(&self, app_state: &AppState) -> UserConnectionPermission {
+    pub async fn connection_permission(&self, app_state: &AppState, fingerprint: &str) -> UserConnectionPermission {
         if self.max_connections > 0 && app_state.config.user_access_control {
             // we allow requests with max connection reached, but we should block streaming after grace period
-            return app_state.get_connection_permission(&self.username, self.max_connections).await;
+            return app_state.get_connection_permission(&self.username, self.max_connections, fingerprint).await;
         }
         UserConnectionPermission::Allowed
     }
@@ -141,6 +190,9 @@ pub struct ApiProxyServerInfo {
     pub name: String,
     pub protocol: String,
     pub host: String,
+    // advertised to ipv6 clients instead of `host`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_v6: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub port: Option<String>,
     pub timezone: String,
@@ -160,10 +212,11 @@ impl ApiProxyServerInfo {
        if self.protocol.is_empty() {
            return Err(info_err!("protocol cant be empty for api server config".to_string()));
        }
-       self.host = self.host.trim().to_string();
+       self.host = trim_host(&self.host);
        if self.host.is_empty() {
            return Err(info_err!("host cant be empty for api server config".to_string()));
        }
+       self.host_v6 = self.host_v6.as_deref().map(trim_host).filter(|host| !host.is_empty());
        if let Some(port)= self.port.as_ref() {
            let port = port.trim().to_string();
            if port.is_empty() {
@@ -203,11 +256,27 @@ impl ApiProxyServerInfo {
         self.prepare().is_ok()
     }
 
+    /// Uses the `host_v6` for ipv6 clients if configured.
+    pub fn for_client(mut self, ipv6_client: bool) -> Self {
+        if ipv6_client {
+            if let Some(host) = self.host_v6.take() {
+                self.host = host;
+            }
+        }
+        self
+    }
+
+    /// The host for urls, ipv6 literals are enclosed in brackets.
+    pub fn get_url_host(&self) -> String {
+        format_url_host(&self.host)
+    }
+
     pub fn get_base_url(&self) -> String {
+        let host = self.get_url_host();
         let base_url = if let Some(port) = self.port.as_ref() {
-            format!("{}://{}:{port}", self.protocol, self.host)
+            format!("{}://{host}:{port}", self.protocol)
         } else {
-            format!("{}://{}", self.protocol, self.host)
+            format!("{}://{host}", self.protocol)
         };
 
         match &self.path {
@@ -217,6 +286,50 @@ impl ApiProxyServerInfo {
     }
 }
 
+// ipv6 literals can be configured with or without brackets
+fn trim_host(host: &str) -> String {
+    let host = host.trim();
+    host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host).to_string()
+}
+
+/// A user package defines the order of the categories and the adult content visibility for all users assigned to it.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiProxyUserPackage {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub live: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vod: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub series: Vec<String>,
+    // skips the channels tagged by the target `adult_detection`
+    #[serde(default)]
+    pub hide_adult: bool,
+}
+
+impl ApiProxyUserPackage {
+    pub fn get_category_order(&self, cluster: XtreamCluster) -> Option<&Vec<String>> {
+        let order = match cluster {
+            XtreamCluster::Live => &self.live,
+            XtreamCluster::Video => &self.vod,
+            XtreamCluster::Series => &self.series,
+        };
+        if order.is_empty() { None } else { Some(order) }
+    }
+
+    /// The listed categories come first in the order of the package, the others keep the provider order.
+    pub fn sort_categories(&self, cluster: XtreamCluster, categories: &mut [Value]) {
+        if let Some(order) = self.get_category_order(cluster) {
+            let positions: HashMap<&str, usize> = order.iter().enumerate().map(|(idx, name)| (name.as_str(), idx)).collect();
+            categories.sort_by_key(|category| category.get(crate::model::XC_TAG_CATEGORY_NAME)
+                .and_then(Value::as_str)
+                .and_then(|name| positions.get(name).copied())
+                .unwrap_or(usize::MAX));
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ApiProxyConfig {
@@ -224,6 +337,8 @@ pub struct ApiProxyConfig {
     pub user: Vec<TargetUser>,
     #[serde(default)]
     pub use_user_db: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub packages: Option<Vec<ApiProxyUserPackage>>,
 }
 
 impl ApiProxyConfig {
@@ -299,6 +414,28 @@ impl ApiProxyConfig {
         }
     }
 
+    fn prepare_packages(&mut self, errors: &mut Vec<String>) {
+        if let Some(packages) = self.packages.as_mut() {
+            let mut names = HashSet::new();
+            for package in packages {
+                package.name = package.name.trim().to_string();
+                if package.name.is_empty() {
+                    errors.push("Package name is empty".to_string());
+                } else if !names.insert(package.name.clone()) {
+                    errors.push(format!("Non-unique package name found {}", &package.name));
+                }
+            }
+        }
+    }
+
+    pub fn get_user_package(&self, name: &str) -> Option<&ApiProxyUserPackage> {
+        self.packages.as_ref().and_then(|packages| packages.iter().find(|package| package.name == name))
+    }
+
+    pub fn is_adult_hidden(&self, user: &ProxyUserCredentials) -> bool {
+        user.package.as_deref().and_then(|name| self.get_user_package(name)).is_some_and(|package| package.hide_adult)
+    }
+
     fn prepare_target_user(&mut self, errors: &mut Vec<String>) {
         let mut usernames = HashSet::new();
         let mut tokens = HashSet::new();
@@ -320,6 +457,25 @@ impl ApiProxyConfig {
                     }
                 }
 
+                if let Some(devices) = &user.devices {
+                    let mut device_names = HashSet::new();
+                    for device in devices {
+                        if device.name.is_empty() || device.token.is_empty() {
+                            errors.push(format!("Device name and token required for user {}", &user.username));
+                        } else if !device_names.insert(device.name.as_str()) {
+                            errors.push(format!("Non unique device name {} found for user {}", &device.name, &user.username));
+                        } else if !tokens.insert(device.token.clone()) {
+                            errors.push(format!("Non unique token found {} device {}", &user.username, &device.name));
+                        }
+                    }
+                }
+
+                if let Some(package) = &user.package {
+                    if !self.packages.as_ref().is_some_and(|packages| packages.iter().any(|p| p.name.eq(package))) {
+                        errors.push(format!("No package with name {package} found for user {}", &user.username));
+                    }
+                }
+
                 if let Some(server_info_name) = &user.server {
                     if !&self.server.iter()
                         .any(|server_info| server_info.name.eq(server_info_name))
@@ -341,6 +497,7 @@ impl ApiProxyConfig {
         } else {
             self.prepare_server_config(&mut errors);
         }
+        self.prepare_packages(&mut errors);
         self.prepare_target_user(&mut errors);
         if errors.is_empty() {
             Ok(())
@@ -370,7 +527,9 @@ impl ApiProxyConfig {
     pub fn get_target_name_by_token(&self, token: &str) -> Option<(ProxyUserCredentials, String)> {
         for target_user in &self.user {
             if let Some((credentials, target_name)) = target_user.get_target_name_by_token(token) {
-                return Some((credentials.clone(), target_name.to_string()));
+                let mut credentials = credentials.clone();
+                credentials.t_device = credentials.get_device_by_token(token).map(|device| device.name.clone());
+                return Some((credentials, target_name.to_string()));
             }
         }
         None
@@ -387,3 +546,71 @@ impl ApiProxyConfig {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{ApiProxyConfig, ApiProxyServerInfo};
+    use serde_json::json;
+    use shared::model::XtreamCluster;
+
+    const API_PROXY: &str = r"
+server:
+  - {name: default, protocol: http, host: localhost, port: '8901', timezone: UTC, message: hello}
+user:
+  - target: all
+    credentials:
+      - username: bob
+        password: secret
+        token: bob_token
+        max_connections: 2
+        devices:
+          - {name: Living Room, token: living_token}
+          - {name: Bedroom, token: bedroom_token, enabled: false}
+";
+
+    #[test]
+    fn test_user_devices() {
+        let mut api_proxy: ApiProxyConfig = serde_yaml::from_str(API_PROXY).unwrap();
+        api_proxy.prepare().unwrap();
+        let (user, target) = api_proxy.get_target_name_by_token("living_token").unwrap();
+        assert_eq!(target, "all");
+        assert_eq!(user.username, "bob");
+        assert_eq!(user.t_device.as_deref(), Some("Living Room"));
+        let (user, _) = api_proxy.get_target_name_by_token("bob_token").unwrap();
+        assert_eq!(user.t_device, None);
+        assert!(api_proxy.get_target_name_by_token("bedroom_token").is_none());
+
+        let mut api_proxy: ApiProxyConfig = serde_yaml::from_str(&API_PROXY.replace("bedroom_token", "bob_token")).unwrap();
+        assert!(api_proxy.prepare().is_err());
+    }
+
+    #[test]
+    fn test_user_package() {
+        let config = format!("{API_PROXY}        package: sports\npackages:\n  - {{name: sports, live: [Sport, News]}}\n");
+        let mut api_proxy: ApiProxyConfig = serde_yaml::from_str(&config).unwrap();
+        api_proxy.prepare().unwrap();
+        let user = api_proxy.get_user_credentials("bob").unwrap();
+        let package = api_proxy.get_user_package(user.package.as_deref().unwrap()).unwrap();
+        let mut categories = vec![json!({"category_name": "Movies"}), json!({"category_name": "News"}),
+                                  json!({"category_name": "Kids"}), json!({"category_name": "Sport"})];
+        package.sort_categories(XtreamCluster::Live, &mut categories);
+        let names: Vec<&str> = categories.iter().filter_map(|c| c["category_name"].as_str()).collect();
+        assert_eq!(names, vec!["Sport", "News", "Movies", "Kids"]);
+        package.sort_categories(XtreamCluster::Video, &mut categories[2..]);
+        assert_eq!(categories[2]["category_name"], "Movies");
+
+        let mut api_proxy: ApiProxyConfig = serde_yaml::from_str(&config.replace("package: sports", "package: kids")).unwrap();
+        assert!(api_proxy.prepare().is_err());
+    }
+
+    #[test]
+    fn test_server_info_ipv6() {
+        let mut server_info: ApiProxyServerInfo = serde_yaml::from_str(
+            "{name: default, protocol: http, host: '[::1]', host_v6: ' v6.example.tv ', port: '8901', timezone: UTC, message: hello}").unwrap();
+        server_info.prepare().unwrap();
+        assert_eq!(server_info.host, "::1");
+        assert_eq!(server_info.get_base_url(), "http://[::1]:8901");
+        assert_eq!(server_info.clone().for_client(false).get_base_url(), "http://[::1]:8901");
+        assert_eq!(server_info.for_client(true).get_base_url(), "http://v6.example.tv:8901");
+    }
+}
@@ -4,13 +4,14 @@ use std::fs::File;
 use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
 use log::{debug, error};
 use path_clean::PathClean;
 use rand::Rng;
 
 use crate::model::{ApiProxyConfig, ApiProxyServerInfo, CustomStreamResponse, Mappings, ProxyUserCredentials, ReverseProxyConfig, ScheduleConfig, SourcesConfig};
-use crate::model::{ConfigInput, ConfigInputOptions, ConfigTarget, HdHomeRunConfig, IpCheckConfig, LogConfig, MessagingConfig, ProxyConfig, TargetOutput, VideoConfig, WebUiConfig};
+use crate::model::{DEFAULT_REFRESH_HISTORY_SIZE, ConfigInput, ConfigInputOptions, ConfigTarget, HdHomeRunConfig, IpCheckConfig, LogConfig, MessagingConfig, PortalConfig, ProviderCacheConfig, MetricsExportConfig, ReplicationConfig, BackupConfig, UserCleanupConfig, ClientErrorConfig, RecordingConfig, CrashReportConfig, ProxyConfig, TargetOutput, VideoConfig, WebUiConfig};
 use shared::error::{create_tuliprox_error_result, TuliproxError, TuliproxErrorKind};
 use shared::utils::{default_connect_timeout_secs};
 
@@ -18,6 +19,7 @@ const CHANNEL_UNAVAILABLE: &str = "channel_unavailable.ts";
 const USER_CONNECTIONS_EXHAUSTED: &str = "user_connections_exhausted.ts";
 const PROVIDER_CONNECTIONS_EXHAUSTED: &str = "provider_connections_exhausted.ts";
 const USER_ACCOUNT_EXPIRED: &str = "user_account_expired.ts";
+const MAINTENANCE: &str = "maintenance.ts";
 
 fn generate_secret() -> [u8; 32] {
     let mut rng = rand::rng();
@@ -35,6 +37,7 @@ macro_rules! valid_property {
 pub use valid_property;
 use crate::api::model::streams::transport_stream_buffer::TransportStreamBuffer;
 use crate::utils;
+use crate::utils::request;
 
 
 #[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize)]
@@ -76,6 +79,10 @@ pub struct Config {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub mapping_path: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_aliases_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_schedules_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub custom_stream_response_path: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub video: Option<VideoConfig>,
@@ -89,6 +96,15 @@ pub struct Config {
     pub connect_timeout_secs: u32,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sleep_timer_mins: Option<u32>,
+    // the number of refresh results kept per target, 0 disables the history
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_history_size: Option<usize>,
+    // notify when a target serves its last playlist longer than the given minutes, because inputs fail
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale_notify_mins: Option<u32>,
+    // a refresh of a source running longer than the given minutes is aborted and its targets are marked failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_timeout_mins: Option<u32>,
     #[serde(default)]
     pub update_on_boot: bool,
     #[serde(default)]
@@ -105,6 +121,24 @@ pub struct Config {
     pub proxy: Option<ProxyConfig>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub ipcheck: Option<IpCheckConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub portal: Option<PortalConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_cache: Option<ProviderCacheConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics_export: Option<MetricsExportConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replication: Option<ReplicationConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup: Option<BackupConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_cleanup: Option<UserCleanupConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_error: Option<ClientErrorConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recording: Option<RecordingConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crash_report: Option<CrashReportConfig>,
     #[serde(skip)]
     pub sources: SourcesConfig,
     #[serde(skip)]
@@ -120,6 +154,10 @@ pub struct Config {
     #[serde(skip)]
     pub t_mapping_file_path: String,
     #[serde(skip)]
+    pub t_epg_aliases_file_path: String,
+    #[serde(skip)]
+    pub t_epg_schedules_file_path: String,
+    #[serde(skip)]
     pub t_api_proxy_file_path: String,
     #[serde(skip)]
     pub t_custom_stream_response_path: Option<String>,
@@ -216,6 +254,14 @@ impl Config {
     }
 
     pub fn get_inputs_for_target(&self, target_name: &str) -> Option<Vec<&ConfigInput>> {
+        // a dependent target uses the inputs of the targets it depends on
+        if let Some(depends_on) = self.sources.get_target_by_name(target_name).and_then(|target| target.depends_on.as_ref()) {
+            let inputs: Vec<&ConfigInput> = depends_on.iter()
+                .filter_map(|name| self.get_inputs_for_target(name))
+                .flatten()
+                .collect();
+            return if inputs.is_empty() { None } else { Some(inputs) };
+        }
         for source in &self.sources.sources {
             if let Some(cfg) = source.get_inputs_for_target(target_name) {
                 return Some(cfg);
@@ -277,11 +323,27 @@ impl Config {
         None
     }
 
+    pub fn get_refresh_history_size(&self) -> usize {
+        self.refresh_history_size.unwrap_or(DEFAULT_REFRESH_HISTORY_SIZE)
+    }
+
+    pub fn get_stale_notify_secs(&self) -> Option<i64> {
+        self.stale_notify_mins.map(|mins| i64::from(mins) * 60)
+    }
+
+    pub fn get_refresh_timeout(&self) -> Option<Duration> {
+        self.refresh_timeout_mins.filter(|mins| *mins > 0).map(|mins| Duration::from_secs(u64::from(mins) * 60))
+    }
+
     pub fn get_target_by_id(&self, target_id: u16) -> Option<&ConfigTarget> {
         self.sources.get_target_by_id(target_id)
     }
 
     pub fn set_mappings(&self, mappings_cfg: &Mappings) {
+        if let Some(global) = self.sources.global.as_ref() {
+            let global_mappings: Vec<_> = global.mapping.iter().flatten().filter_map(|mapping_id| mappings_cfg.get_mapping(mapping_id)).collect();
+            global.t_mapping.store(if global_mappings.is_empty() { None } else { Some(Arc::new(global_mappings)) });
+        }
         for source in &self.sources.sources {
             for target in &source.targets {
                 if let Some(mapping_ids) = &target.mapping {
@@ -298,6 +360,20 @@ impl Config {
         }
     }
 
+    // inputs with tls settings get their own client
+    fn prepare_input_clients(&mut self) -> Result<(), TuliproxError> {
+        let mut clients = Vec::new();
+        for input in self.sources.sources.iter().flat_map(|source| &source.inputs) {
+            if let Some(client) = request::create_input_client(self, input)? {
+                clients.push((input.id, Arc::new(client)));
+            }
+        }
+        for input in self.sources.sources.iter_mut().flat_map(|source| &mut source.inputs) {
+            input.t_client = clients.iter().find(|(id, _)| *id == input.id).map(|(_, client)| Arc::clone(client));
+        }
+        Ok(())
+    }
+
     fn check_unique_input_names(&mut self) -> Result<(), TuliproxError> {
         let mut seen_names = HashSet::new();
         for source in &mut self.sources.sources {
@@ -352,6 +428,12 @@ impl Config {
         if let Some(mapping_path) = &self.mapping_path {
             self.t_mapping_file_path = mapping_path.to_string();
         }
+        if let Some(epg_aliases_path) = &self.epg_aliases_path {
+            self.t_epg_aliases_file_path.clone_from(epg_aliases_path);
+        }
+        if let Some(epg_schedules_path) = &self.epg_schedules_path {
+            self.t_epg_schedules_file_path.clone_from(epg_schedules_path);
+        }
 
         if let Some(mins) = self.sleep_timer_mins {
             if mins == 0 {
@@ -371,9 +453,39 @@ impl Config {
         if let Some(proxy) = &mut self.proxy {
             proxy.prepare()?;
         }
+        if let Some(log) = self.log.as_mut() {
+            log.prepare()?;
+        }
         if let Some(ipcheck) = self.ipcheck.as_mut() {
             ipcheck.prepare()?;
         }
+        if let Some(messaging) = self.messaging.as_ref() {
+            messaging.prepare()?;
+        }
+        if let Some(portal) = self.portal.as_mut() {
+            portal.prepare(&self.working_dir)?;
+        }
+        if let Some(metrics_export) = self.metrics_export.as_mut() {
+            metrics_export.prepare()?;
+        }
+        if let Some(replication) = self.replication.as_mut() {
+            replication.prepare()?;
+        }
+        if let Some(backup) = self.backup.as_mut() {
+            backup.prepare()?;
+        }
+        if let Some(user_cleanup) = self.user_cleanup.as_mut() {
+            user_cleanup.prepare()?;
+        }
+        if let Some(client_error) = self.client_error.as_mut() {
+            client_error.prepare()?;
+        }
+        if let Some(recording) = self.recording.as_mut() {
+            recording.prepare(&self.working_dir)?;
+        }
+        if let Some(crash_report) = self.crash_report.as_mut() {
+            crash_report.prepare(&self.working_dir)?;
+        }
         self.prepare_hdhomerun()?;
         self.api.prepare();
         self.prepare_api_web_root();
@@ -381,6 +493,9 @@ impl Config {
         let target_names = self.sources.check_unique_target_names()?;
         self.check_scheduled_targets(&target_names)?;
         self.check_unique_input_names()?;
+        if include_computed {
+            self.prepare_input_clients()?;
+        }
         self.prepare_video_config()?;
         self.prepare_web()?;
 
@@ -477,11 +592,13 @@ impl Config {
             let user_connections_exhausted = load_and_set_file(&path.join(USER_CONNECTIONS_EXHAUSTED));
             let provider_connections_exhausted = load_and_set_file(&path.join(PROVIDER_CONNECTIONS_EXHAUSTED));
             let user_account_expired = load_and_set_file(&path.join(USER_ACCOUNT_EXPIRED));
+            let maintenance = load_and_set_file(&path.join(MAINTENANCE));
             self.t_custom_stream_response = Some(CustomStreamResponse {
                 channel_unavailable,
                 user_connections_exhausted,
                 provider_connections_exhausted,
                 user_account_expired,
+                maintenance,
             });
         }
     }
@@ -509,9 +626,46 @@ impl Config {
 
     pub fn get_user_server_info(&self, user: &ProxyUserCredentials) -> ApiProxyServerInfo {
         let server_info_name = user.server.as_ref().map_or("default", |server_name| server_name.as_str());
-        self.get_server_info(server_info_name)
+        self.get_server_info(server_info_name).for_client(user.t_ipv6_client)
     }
 
+    /// Creates the variables for user facing templates like welcome emails.
+    pub fn get_user_template_context(&self, user: &ProxyUserCredentials) -> serde_json::Value {
+        let server_info = self.get_user_server_info(user);
+        let server_url = server_info.get_base_url();
+        let credentials = format!("username={}&password={}", user.username, user.password);
+        let expires = user.exp_date.and_then(|ts| chrono::DateTime::from_timestamp(ts, 0));
+        serde_json::json!({
+            "server": {
+                "name": server_info.name,
+                "url": server_url,
+                "protocol": server_info.protocol,
+                "host": server_info.host,
+                "port": server_info.port,
+                "timezone": server_info.timezone,
+                "message": server_info.message,
+            },
+            "user": {
+                "username": user.username,
+                "password": user.password,
+                "email": user.email,
+                "status": user.status.map(|status| status.to_string()),
+                "max_connections": user.max_connections,
+                "exp_date": expires.map(|dt| dt.format("%Y-%m-%d").to_string()),
+                "expires_in_days": expires.map(|dt| (dt - chrono::Utc::now()).num_days().max(0)),
+                "expired": expires.is_some_and(|dt| dt < chrono::Utc::now()),
+            },
+            "m3u_url": format!("{server_url}/get.php?{credentials}&type=m3u_plus"),
+            "epg_url": format!("{server_url}/xmltv.php?{credentials}"),
+            "xtream_url": format!("{server_url}/player_api.php?{credentials}"),
+        })
+    }
+
+    /// The secret for signed urls, it survives restarts when the web ui authentication is configured.
+    pub fn get_url_signing_secret(&self) -> [u8; 32] {
+        self.web_ui.as_ref().and_then(|web_ui| web_ui.auth.as_ref())
+            .map_or(self.t_access_token_secret, |auth| blake3::derive_key("tuliprox signed url", auth.secret.as_bytes()))
+    }
 }
 
 
@@ -0,0 +1,70 @@
+use shared::model::{ClusterFlags, XtreamCluster};
+use crate::foundation::filter::{apply_templates_to_pattern_single, PatternTemplate};
+use shared::error::{TuliproxError, TuliproxErrorKind, create_tuliprox_error_result};
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigCategoryRule {
+    // match against group title
+    pub pattern: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cluster: Option<ClusterFlags>,
+    // new category name, can contain capture group variables $1, $2, ...
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub priority: i16,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub t_re: Option<regex::Regex>,
+}
+
+impl ConfigCategoryRule {
+    pub fn prepare(&mut self, templates: Option<&Vec<PatternTemplate>>) -> Result<(), TuliproxError> {
+        self.pattern = apply_templates_to_pattern_single(&self.pattern, templates)?;
+        if let Some(name) = self.name.as_mut() {
+            *name = name.trim().to_string();
+            if name.is_empty() {
+                return create_tuliprox_error_result!(TuliproxErrorKind::Info, "category rule name can't be empty for pattern: {}", &self.pattern);
+            }
+        }
+        self.icon = self.icon.as_ref().map(|s| s.trim().to_string()).filter(|s| !s.is_empty());
+        match regex::Regex::new(&self.pattern) {
+            Ok(pattern) => {
+                self.t_re = Some(pattern);
+                Ok(())
+            }
+            Err(err) => create_tuliprox_error_result!(TuliproxErrorKind::Info, "cant parse regex: {} {err}", &self.pattern),
+        }
+    }
+
+    fn has_cluster(&self, cluster: XtreamCluster) -> bool {
+        self.cluster.as_ref().is_none_or(|flags| flags.has_cluster(cluster.into()))
+    }
+
+    /// Returns true if the rule applies to the provider group.
+    pub fn is_match(&self, cluster: XtreamCluster, title: &str) -> bool {
+        self.has_cluster(cluster) && self.t_re.as_ref().is_some_and(|re| re.is_match(title))
+    }
+
+    /// Returns the new category name for a matching provider group.
+    pub fn apply(&self, title: &str) -> Option<String> {
+        match (self.name.as_ref(), self.t_re.as_ref()) {
+            (Some(name), Some(re)) => Some(re.replace(title, name.as_str()).into_owned()),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the category was produced by this rule.
+    /// Only rules with a literal name or without a name can be resolved from the output.
+    pub fn is_output_match(&self, cluster: XtreamCluster, title: &str) -> bool {
+        if !self.has_cluster(cluster) {
+            return false;
+        }
+        match self.name.as_ref() {
+            Some(name) => !name.contains('$') && name == title,
+            None => self.t_re.as_ref().is_some_and(|re| re.is_match(title)),
+        }
+    }
+}
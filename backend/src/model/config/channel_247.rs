@@ -0,0 +1,64 @@
+use crate::foundation::filter::{apply_templates_to_pattern_single, PatternTemplate};
+use shared::error::{create_tuliprox_error_result, TuliproxError, TuliproxErrorKind};
+use shared::utils::default_as_true;
+
+pub const DEFAULT_CHANNEL_247_PATTERN: &str = r"(?i)(?:^|[^0-9])24\s*[/x|\-]\s*7(?:[^0-9]|$)";
+const DEFAULT_CHANNEL_247_GROUP: &str = "24/7";
+
+fn default_channel_247_group() -> String {
+    String::from(DEFAULT_CHANNEL_247_GROUP)
+}
+
+fn default_channel_247_separator() -> String {
+    String::from(" | ")
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigChannel247 {
+    // match against channel name and group title, default detects `24/7`, `24-7`, `24x7` and `24|7`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    // group name for detected channels
+    #[serde(default = "default_channel_247_group")]
+    pub group: String,
+    // split detected channels into A-Z sub groups
+    #[serde(default = "default_as_true")]
+    pub alphabetical: bool,
+    #[serde(default = "default_channel_247_separator")]
+    pub separator: String,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub t_re: Option<regex::Regex>,
+}
+
+impl ConfigChannel247 {
+    pub fn prepare(&mut self, templates: Option<&Vec<PatternTemplate>>) -> Result<(), TuliproxError> {
+        self.group = self.group.trim().to_string();
+        if self.group.is_empty() {
+            self.group = default_channel_247_group();
+        }
+        let pattern = match self.pattern.as_ref() {
+            Some(pattern) => apply_templates_to_pattern_single(pattern, templates)?,
+            None => DEFAULT_CHANNEL_247_PATTERN.to_string(),
+        };
+        match regex::Regex::new(&pattern) {
+            Ok(re) => {
+                self.t_re = Some(re);
+                Ok(())
+            }
+            Err(err) => create_tuliprox_error_result!(TuliproxErrorKind::Info, "cant parse regex: {} {err}", &pattern),
+        }
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        self.t_re.as_ref().is_some_and(|re| re.is_match(text))
+    }
+
+    /// Returns the channel name without the 24/7 marker.
+    pub fn strip_marker<'a>(&self, name: &'a str) -> std::borrow::Cow<'a, str> {
+        match self.t_re.as_ref() {
+            Some(re) => re.replace_all(name, " "),
+            None => std::borrow::Cow::Borrowed(name),
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use axum::http::HeaderValue;
+use shared::error::{info_err, TuliproxError, TuliproxErrorKind};
+use shared::utils::{default_client_error_content_type, default_client_error_expired, default_client_error_expired_channel, default_client_error_unauthorized};
+
+/// Explanatory responses of the playlist and guide endpoints, when the authentication fails or the account is expired.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientErrorConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    // content type of the messages, like `text/html; charset=utf-8`
+    #[serde(default = "default_client_error_content_type")]
+    pub content_type: String,
+    #[serde(default = "default_client_error_unauthorized")]
+    pub unauthorized: String,
+    #[serde(default = "default_client_error_expired")]
+    pub expired: String,
+    // name of the single channel of the m3u playlist of an expired account
+    #[serde(default = "default_client_error_expired_channel")]
+    pub expired_channel: String,
+}
+
+impl ClientErrorConfig {
+    pub fn prepare(&mut self) -> Result<(), TuliproxError> {
+        if self.enabled && HeaderValue::from_str(&self.content_type).is_err() {
+            return Err(info_err!(format!("Invalid client error content type {}", self.content_type)));
+        }
+        Ok(())
+    }
+}
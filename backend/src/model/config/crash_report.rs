@@ -0,0 +1,37 @@
+use path_clean::PathClean;
+use shared::error::{info_err, TuliproxError, TuliproxErrorKind};
+use shared::utils::default_crash_report_max_reports;
+use std::path::PathBuf;
+
+/// Opt-in crash reports written by the panic handler.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CrashReportConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    // directory of the reports, relative paths are resolved against the working dir, default `crash_reports`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+    // the oldest reports are deleted when more reports are written
+    #[serde(default = "default_crash_report_max_reports")]
+    pub max_reports: usize,
+    // sends the panic message as `error` message over messaging
+    #[serde(default)]
+    pub notify: bool,
+}
+
+impl CrashReportConfig {
+    pub fn prepare(&mut self, working_dir: &str) -> Result<(), TuliproxError> {
+        if self.max_reports == 0 {
+            return Err(info_err!("crash_report max_reports should be greater than 0".to_string()));
+        }
+        let work_path = PathBuf::from(working_dir);
+        let dir = match self.dir.as_ref() {
+            None => work_path.join("crash_reports"),
+            Some(dir) if PathBuf::from(dir).is_relative() => work_path.join(dir),
+            Some(dir) => PathBuf::from(dir),
+        };
+        self.dir = Some(dir.clean().to_string_lossy().to_string());
+        Ok(())
+    }
+}
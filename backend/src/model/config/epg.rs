@@ -1,7 +1,10 @@
 use shared::error::{create_tuliprox_error_result, info_err, TuliproxError, TuliproxErrorKind};
-use shared::utils::CONSTANTS;
+use shared::utils::{default_epg_download_timeout_secs, CONSTANTS};
 use log::warn;
 use regex::Regex;
+use std::collections::HashMap;
+use std::time::Duration;
+use crate::utils::parse_timeshift;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -32,6 +35,40 @@ pub enum EpgNamePrefix {
     Prefix(String),
 }
 
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EpgPhoneticAlgorithm {
+    Metaphone,
+    #[default]
+    DoubleMetaphone,
+    Cologne,
+    Soundex,
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EpgSimilarityMetric {
+    #[default]
+    JaroWinkler,
+    Jaro,
+    Levenshtein,
+    DamerauLevenshtein,
+    SorensenDice,
+}
+
+impl EpgSimilarityMetric {
+    /// Returns the similarity of both names between 0.0 and 1.0.
+    pub fn similarity(self, left: &str, right: &str) -> f64 {
+        match self {
+            EpgSimilarityMetric::JaroWinkler => strsim::jaro_winkler(left, right),
+            EpgSimilarityMetric::Jaro => strsim::jaro(left, right),
+            EpgSimilarityMetric::Levenshtein => strsim::normalized_levenshtein(left, right),
+            EpgSimilarityMetric::DamerauLevenshtein => strsim::normalized_damerau_levenshtein(left, right),
+            EpgSimilarityMetric::SorensenDice => strsim::sorensen_dice(left, right),
+        }
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct EpgSmartMatchConfig {
@@ -50,12 +87,20 @@ pub struct EpgSmartMatchConfig {
     pub match_threshold: u16,
     #[serde(default)]
     pub best_match_threshold: u16,
+    #[serde(default)]
+    pub phonetic_algorithm: EpgPhoneticAlgorithm,
+    #[serde(default)]
+    pub similarity_metric: EpgSimilarityMetric,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_languages: Option<Vec<String>>,
     #[serde(skip)]
     pub t_strip: Vec<String>,
     #[serde(skip)]
     pub t_normalize_regex: Option<Regex>,
     #[serde(skip)]
     pub t_name_prefix_separator: Vec<char>,
+    #[serde(skip)]
+    pub t_preferred_languages: Vec<String>,
 
 }
 
@@ -124,6 +169,10 @@ impl EpgSmartMatchConfig {
             Some(list) => self.t_strip = list.iter().map(|s| s.to_lowercase()).collect(),
             None => self.t_strip = ["3840p", "uhd", "fhd", "hd", "sd", "4k", "plus", "raw", "full hd"].iter().map(std::string::ToString::to_string).collect(),
         }
+
+        self.t_preferred_languages = self.preferred_languages.as_ref()
+            .map(|list| list.iter().map(|lang| lang.trim().to_lowercase()).filter(|lang| !lang.is_empty()).collect())
+            .unwrap_or_default();
         Ok(())
     }
 }
@@ -139,9 +188,13 @@ impl Default for EpgSmartMatchConfig {
             fuzzy_matching: false,
             match_threshold: 0,
             best_match_threshold: 0,
+            phonetic_algorithm: EpgPhoneticAlgorithm::default(),
+            similarity_metric: EpgSimilarityMetric::default(),
+            preferred_languages: None,
             t_strip: Vec::default(),
             t_normalize_regex: None,
             t_name_prefix_separator: Vec::default(),
+            t_preferred_languages: Vec::default(),
         };
         let _ = instance.prepare();
         instance
@@ -155,13 +208,31 @@ pub struct EpgConfig {
     pub sources: Option<Vec<EpgSource>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub smart_match: Option<EpgSmartMatchConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_shift: Option<String>,
+    // timeout of each source download, 0 disables it
+    #[serde(default = "default_epg_download_timeout_secs")]
+    pub download_timeout_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_download_size_mb: Option<u64>,
     #[serde(skip)]
     pub t_sources: Vec<EpgSource>,
     #[serde(skip)]
     pub t_smart_match: EpgSmartMatchConfig,
+    #[serde(skip)]
+    pub t_time_shift: Option<i32>, // minutes
 }
 
 impl EpgConfig {
+    pub fn get_download_timeout(&self) -> Option<Duration> {
+        (self.download_timeout_secs > 0).then(|| Duration::from_secs(self.download_timeout_secs))
+    }
+
+    // in bytes
+    pub fn get_max_download_size(&self) -> Option<u64> {
+        self.max_download_size_mb.filter(|size| *size > 0).map(|size| size * 1024 * 1024)
+    }
+
     pub fn prepare<F>(&mut self, create_auto_url: F, include_computed: bool) -> Result<(), TuliproxError>
     where
         F: Fn() -> Result<String, String>,
@@ -204,6 +275,232 @@ impl EpgConfig {
                 }
             };
         }
+        self.t_time_shift = parse_timeshift(self.time_shift.as_ref());
+        Ok(())
+    }
+}
+
+/// Pins channels matching the name `pattern` to the `epg_id`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EpgAlias {
+    pub pattern: String,
+    pub epg_id: String,
+    #[serde(skip)]
+    pub t_re: Option<Regex>,
+}
+
+/// The content of the `epg_aliases.yml` file, the aliases are consulted before the smart match.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EpgAliasesConfig {
+    #[serde(default)]
+    pub aliases: Vec<EpgAlias>,
+}
+
+impl EpgAliasesConfig {
+    pub fn prepare(&mut self) -> Result<(), TuliproxError> {
+        for alias in &mut self.aliases {
+            alias.epg_id = alias.epg_id.trim().to_string();
+            if alias.epg_id.is_empty() {
+                return create_tuliprox_error_result!(TuliproxErrorKind::Info, "epg alias {} has no epg_id", alias.pattern);
+            }
+            alias.t_re = Some(Regex::new(&alias.pattern).map_err(|err| info_err!(format!("Invalid epg alias regex {}: {err}", alias.pattern)))?);
+        }
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Returns the epg id of the first alias matching the channel name.
+    pub fn get_epg_id(&self, name: &str) -> Option<&str> {
+        self.aliases.iter()
+            .find(|alias| alias.t_re.as_ref().is_some_and(|re| re.is_match(name)))
+            .map(|alias| alias.epg_id.as_str())
+    }
+}
+
+const fn default_epg_schedule_hours() -> u32 { 48 }
+
+/// A programme of a static epg schedule, `duration` in minutes.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EpgScheduleProgramme {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc: Option<String>,
+    pub duration: u32,
+}
+
+/// The programmes repeated for the channels with the `epg_id`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EpgSchedule {
+    pub epg_id: String,
+    // display name of the generated channel, default is the channel name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub programmes: Vec<EpgScheduleProgramme>,
+}
+
+impl EpgSchedule {
+    /// The length of one repetition in seconds.
+    pub fn get_cycle_secs(&self) -> i64 {
+        self.programmes.iter().map(|programme| i64::from(programme.duration) * 60).sum()
+    }
+}
+
+/// The content of the `epg_schedules.yml` file, static programmes for channels without guide data.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EpgSchedulesConfig {
+    // hours covered from now on
+    #[serde(default = "default_epg_schedule_hours")]
+    pub hours: u32,
+    #[serde(default)]
+    pub schedules: Vec<EpgSchedule>,
+    #[serde(skip)]
+    pub t_schedules: HashMap<String, usize>,
+}
+
+impl EpgSchedulesConfig {
+    pub fn prepare(&mut self) -> Result<(), TuliproxError> {
+        if self.hours == 0 {
+            return create_tuliprox_error_result!(TuliproxErrorKind::Info, "epg schedule hours must be greater than 0");
+        }
+        self.t_schedules = HashMap::new();
+        for (index, schedule) in self.schedules.iter_mut().enumerate() {
+            schedule.epg_id = schedule.epg_id.trim().to_string();
+            if schedule.epg_id.is_empty() {
+                return create_tuliprox_error_result!(TuliproxErrorKind::Info, "epg schedule without epg_id");
+            }
+            if schedule.programmes.is_empty() || schedule.programmes.iter().any(|programme| programme.duration == 0 || programme.title.trim().is_empty()) {
+                return create_tuliprox_error_result!(TuliproxErrorKind::Info, "epg schedule {} needs programmes with a title and a duration greater than 0", schedule.epg_id);
+            }
+            if self.t_schedules.insert(schedule.epg_id.to_lowercase(), index).is_some() {
+                return create_tuliprox_error_result!(TuliproxErrorKind::Info, "epg schedule {} is defined twice", schedule.epg_id);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_schedule(&self, epg_id: &str) -> Option<&EpgSchedule> {
+        self.t_schedules.get(&epg_id.to_lowercase()).and_then(|index| self.schedules.get(*index))
+    }
+}
+
+/// Drops or keeps the programmes of the target epg by the `title`, `sub-title` and `category` texts.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EpgProgrammeFilter {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drop: Option<Vec<String>>,
+    #[serde(skip)]
+    pub t_keep: Vec<Regex>,
+    #[serde(skip)]
+    pub t_drop: Vec<Regex>,
+}
+
+impl EpgProgrammeFilter {
+    pub fn prepare(&mut self) -> Result<(), TuliproxError> {
+        let compile = |patterns: Option<&Vec<String>>| -> Result<Vec<Regex>, TuliproxError> {
+            patterns.into_iter().flatten()
+                .map(|pattern| Regex::new(pattern).map_err(|err| info_err!(format!("Invalid epg_filter regex {pattern}: {err}"))))
+                .collect()
+        };
+        self.t_keep = compile(self.keep.as_ref())?;
+        self.t_drop = compile(self.drop.as_ref())?;
+        Ok(())
+    }
+
+    /// A programme is dropped if a `drop` regex matches one of the texts,
+    /// with `keep` regexps it is only kept if one of them matches.
+    pub fn is_allowed(&self, texts: &[&str]) -> bool {
+        let matches = |regexps: &[Regex]| texts.iter().any(|text| regexps.iter().any(|re| re.is_match(text)));
+        !matches(&self.t_drop) && (self.t_keep.is_empty() || matches(&self.t_keep))
+    }
+}
+
+/// The time range of the programmes kept in the target epg, unix timestamps in seconds.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EpgTimeWindow {
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+}
+
+impl EpgTimeWindow {
+    /// A programme is inside when it ends after `from` and starts before `to`.
+    /// A programme without stop time is checked with its start time.
+    pub fn is_inside(&self, start: Option<i64>, stop: Option<i64>) -> bool {
+        let ends_after_from = self.from.is_none_or(|from| stop.or(start).is_none_or(|stop| stop >= from));
+        let starts_before_to = self.to.is_none_or(|to| start.is_none_or(|start| start <= to));
+        ends_after_from && starts_before_to
+    }
+}
+
+const DEFAULT_EPG_PLACEHOLDER_TITLE: &str = "{name}";
+
+fn default_epg_placeholder_title() -> String {
+    String::from(DEFAULT_EPG_PLACEHOLDER_TITLE)
+}
+
+const fn default_epg_placeholder_duration() -> u32 { 60 }
+
+const fn default_epg_placeholder_hours() -> u32 { 48 }
+
+/// Generates placeholder programmes for live channels without guide data.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EpgPlaceholder {
+    // programme title, `{name}`, `{title}` and `{group}` are replaced with the channel fields
+    #[serde(default = "default_epg_placeholder_title")]
+    pub title: String,
+    // programme duration in minutes
+    #[serde(default = "default_epg_placeholder_duration")]
+    pub duration: u32,
+    // hours covered from now on
+    #[serde(default = "default_epg_placeholder_hours")]
+    pub hours: u32,
+}
+
+impl EpgPlaceholder {
+    pub fn prepare(&mut self) -> Result<(), TuliproxError> {
+        if self.duration == 0 || self.hours == 0 {
+            return create_tuliprox_error_result!(TuliproxErrorKind::Info, "epg_placeholder duration and hours must be greater than 0");
+        }
+        if self.title.trim().is_empty() {
+            self.title = default_epg_placeholder_title();
+        }
+        Ok(())
+    }
+
+    pub fn get_title(&self, name: &str, title: &str, group: &str) -> String {
+        self.title.replace("{name}", name).replace("{title}", title).replace("{group}", group)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EpgAliasesConfig;
+
+    #[test]
+    fn test_epg_aliases() {
+        let content = r"
+aliases:
+  - { pattern: '(?i)^DE: Das Erste', epg_id: ' ard.de ' }
+  - { pattern: '(?i)^(DE|AT): ZDF$', epg_id: zdf.de }
+  - { pattern: '(?i)^DE: ', epg_id: fallback.de }
+";
+        let mut aliases: EpgAliasesConfig = serde_yaml::from_str(content).unwrap();
+        aliases.prepare().unwrap();
+        assert_eq!(aliases.get_epg_id("DE: Das Erste HD"), Some("ard.de"));
+        assert_eq!(aliases.get_epg_id("at: zdf"), Some("zdf.de"));
+        assert_eq!(aliases.get_epg_id("DE: ZDF Neo"), Some("fallback.de"));
+        assert_eq!(aliases.get_epg_id("FR: TF1"), None);
+
+        let mut invalid: EpgAliasesConfig = serde_yaml::from_str("aliases: [{ pattern: '(DE', epg_id: ard.de }]").unwrap();
+        assert!(invalid.prepare().is_err());
+        let mut empty: EpgAliasesConfig = serde_yaml::from_str("aliases: [{ pattern: 'DE', epg_id: '' }]").unwrap();
+        assert!(empty.prepare().is_err());
+    }
+}
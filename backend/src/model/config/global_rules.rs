@@ -0,0 +1,38 @@
+use crate::foundation::filter::{get_filter, Filter, PatternTemplate, ValueProvider};
+use crate::model::mapping::Mapping;
+use crate::model::ConfigRename;
+use arc_swap::ArcSwapOption;
+use shared::error::{handle_tuliprox_error_result_list, TuliproxError, TuliproxErrorKind};
+use std::sync::Arc;
+
+/// Filter, rename and mapping rules applied to all inputs before the targets are processed.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigGlobalRules {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub filter: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rename: Option<Vec<ConfigRename>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mapping: Option<Vec<String>>,
+    #[serde(skip)]
+    pub t_filter: Option<Filter>,
+    #[serde(skip)]
+    pub t_mapping: Arc<ArcSwapOption<Vec<Mapping>>>,
+}
+
+impl ConfigGlobalRules {
+    pub fn prepare(&mut self, templates: Option<&Vec<PatternTemplate>>) -> Result<(), TuliproxError> {
+        if let Some(filter) = self.filter.as_ref() {
+            self.t_filter = Some(get_filter(filter, templates)?);
+        }
+        if let Some(renames) = self.rename.as_mut() {
+            handle_tuliprox_error_result_list!(TuliproxErrorKind::Info, renames.iter_mut().map(|cr| cr.prepare(templates)));
+        }
+        Ok(())
+    }
+
+    pub fn filter(&self, provider: &ValueProvider) -> bool {
+        self.t_filter.as_ref().is_none_or(|filter| filter.filter(provider))
+    }
+}
@@ -1,15 +1,19 @@
 use shared::error::{create_tuliprox_error_result, handle_tuliprox_error_result_list, info_err, TuliproxError, TuliproxErrorKind};
 use crate::model::{EpgConfig};
-use shared::utils::default_as_true;
+use shared::utils::{default_as_true, default_auth_token_field, default_auth_token_refresh_mins};
 use shared::utils::get_trimmed_string;
 use crate::utils::request::{get_base_url_from_str, get_credentials_from_url, get_credentials_from_url_str, sanitize_sensitive_info};
 use enum_iterator::Sequence;
 use log::{debug};
 use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Arc;
 use url::Url;
 use crate::utils;
+use crate::utils::rate_limiter::ProviderRateLimiter;
+use crate::utils::input_auth::InputAuthToken;
 
 macro_rules! check_input_credentials {
     ($this:ident, $input_type:expr) => {
@@ -138,6 +142,114 @@ pub struct ConfigInputOptions {
     pub xtream_live_stream_use_prefix: bool,
     #[serde(default)]
     pub xtream_live_stream_without_extension: bool,
+    // fetch the provider short epg for live channels without xmltv programmes
+    #[serde(default)]
+    pub xtream_epg_fallback: bool,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigInputRateLimit {
+    #[serde(default)]
+    pub requests_per_second: u32,
+    #[serde(default)]
+    pub max_concurrent: u16,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigInputTls {
+    // pem file with additional root certificates, e.g. of a self-signed provider certificate
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_ca: Option<String>,
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+    // hostname sent as sni and checked against the certificate, the connection goes to the host of the input url
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_name: Option<String>,
+    #[serde(skip)]
+    pub t_host: Option<String>,
+}
+
+impl ConfigInputTls {
+    pub fn prepare(&mut self, input_name: &str, input_url: &str) -> Result<(), TuliproxError> {
+        self.root_ca = get_trimmed_string(&self.root_ca);
+        if let Some(root_ca) = self.root_ca.as_ref() {
+            if !PathBuf::from(root_ca).is_file() {
+                return Err(info_err!(format!("tls root_ca {root_ca} for input {input_name} does not exist")));
+            }
+        }
+        self.server_name = get_trimmed_string(&self.server_name);
+        self.t_host = None;
+        if self.server_name.is_some() {
+            match Url::parse(input_url).ok().and_then(|url| url.host_str().map(ToString::to_string)) {
+                Some(host) => self.t_host = Some(host),
+                None => return Err(info_err!(format!("tls server_name is set for input {input_name}, but the url host could not be parsed"))),
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root_ca.is_none() && !self.accept_invalid_certs && self.server_name.is_none()
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigInputBasicAuth {
+    pub username: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigInputTokenAuth {
+    pub login_url: String,
+    #[serde(default)]
+    pub login_method: InputFetchMethod,
+    // dotted path of the token in the json response, a plain text response is the token itself
+    #[serde(default = "default_auth_token_field")]
+    pub token_field: String,
+    // query parameter the token is added with to the download urls
+    #[serde(default = "default_auth_token_field")]
+    pub token_param: String,
+    // 0 keeps the token until the provider rejects it
+    #[serde(default = "default_auth_token_refresh_mins")]
+    pub refresh_mins: u32,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigInputAuth {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub basic: Option<ConfigInputBasicAuth>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<ConfigInputTokenAuth>,
+}
+
+impl ConfigInputAuth {
+    pub fn prepare(&mut self, input_name: &str) -> Result<(), TuliproxError> {
+        if let Some(basic) = self.basic.as_mut() {
+            basic.username = basic.username.trim().to_string();
+            if basic.username.is_empty() {
+                return Err(info_err!(format!("auth basic username for input {input_name} is mandatory")));
+            }
+        }
+        if let Some(token) = self.token.as_mut() {
+            token.login_url = token.login_url.trim().to_string();
+            if Url::parse(&token.login_url).is_err() {
+                return Err(info_err!(format!("auth token login_url for input {input_name} is invalid: {}", sanitize_sensitive_info(&token.login_url))));
+            }
+            token.token_field = token.token_field.trim().to_string();
+            token.token_param = token.token_param.trim().to_string();
+            if token.token_field.is_empty() || token.token_param.is_empty() {
+                return Err(info_err!(format!("auth token_field and token_param for input {input_name} can't be empty")));
+            }
+        }
+        Ok(())
+    }
 }
 
 pub struct InputUserInfo {
@@ -171,6 +283,17 @@ impl InputUserInfo {
         }
         None
     }
+
+    /// Replaces the base url and the credentials of this user info in `url` with the ones of `other`,
+    /// urls of other hosts are returned unchanged.
+    pub fn replace_in_url(&self, url: &str, other: &Self) -> String {
+        if !url.starts_with(&self.base_url) {
+            return url.to_string();
+        }
+        url.replace(&self.base_url, &other.base_url)
+            .replace(&self.username, &other.username)
+            .replace(&self.password, &other.password)
+    }
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
@@ -245,8 +368,21 @@ pub struct ConfigInput {
     pub max_connections: u16,
     #[serde(default)]
     pub method: InputFetchMethod,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<ConfigInputRateLimit>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<ConfigInputTls>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<ConfigInputAuth>,
     #[serde(skip)]
     pub t_base_url: String,
+    #[serde(skip)]
+    pub t_rate_limiter: Option<Arc<ProviderRateLimiter>>,
+    // client with the tls settings, created by the config
+    #[serde(skip)]
+    pub t_client: Option<Arc<reqwest::Client>>,
+    #[serde(skip)]
+    pub t_auth_token: Option<Arc<InputAuthToken>>,
 }
 
 impl ConfigInput {
@@ -268,6 +404,16 @@ impl ConfigInput {
         if let Some(base_url) = get_base_url_from_str(&self.url) {
             self.t_base_url = base_url;
         }
+        self.t_rate_limiter = self.rate_limit.as_ref()
+            .and_then(|rate_limit| ProviderRateLimiter::new(rate_limit.requests_per_second, rate_limit.max_concurrent))
+            .map(Arc::new);
+        if let Some(tls) = self.tls.as_mut() {
+            tls.prepare(&self.name, &self.url)?;
+        }
+        if let Some(auth) = self.auth.as_mut() {
+            auth.prepare(&self.name)?;
+        }
+        self.t_auth_token = self.auth.as_ref().and_then(|auth| auth.token.as_ref()).map(|_| Arc::new(InputAuthToken::default()));
 
         if let Some(epg) = self.epg.as_mut() {
             let create_auto_url = || {
@@ -357,6 +503,48 @@ impl ConfigInput {
         InputUserInfo::new(self.input_type, self.username.as_deref(), self.password.as_deref(), &self.url)
     }
 
+    pub fn get_alias_user_info(&self, alias: &ConfigInputAlias) -> Option<InputUserInfo> {
+        InputUserInfo::new(self.input_type, alias.username.as_deref(), alias.password.as_deref(), &alias.url)
+    }
+
+    /// The aliases ordered by priority, used to fetch the playlist and epg when the input fails.
+    pub fn get_aliases_by_priority(&self) -> Vec<&ConfigInputAlias> {
+        let mut aliases: Vec<&ConfigInputAlias> = self.aliases.iter().flatten().collect();
+        aliases.sort_by_key(|alias| alias.priority);
+        aliases
+    }
+
+    /// The input with the url and credentials of the alias, the name is kept.
+    pub fn with_alias(&self, alias: &ConfigInputAlias) -> Self {
+        Self {
+            url: alias.url.clone(),
+            username: alias.username.clone(),
+            password: alias.password.clone(),
+            t_base_url: alias.t_base_url.clone(),
+            epg: None,
+            aliases: None,
+            ..self.clone()
+        }
+    }
+
+    /// The client with the tls settings of this input, the given client if the input has none.
+    pub fn get_client(&self, client: &Arc<reqwest::Client>) -> Arc<reqwest::Client> {
+        self.t_client.as_ref().map_or_else(|| Arc::clone(client), Arc::clone)
+    }
+
+    /// Urls of the input host get the tls `server_name` as host, the client of the input resolves it to the input host.
+    pub fn get_tls_url(&self, url: &Url) -> Url {
+        if let Some((server_name, host)) = self.tls.as_ref().and_then(|tls| tls.server_name.as_ref().zip(tls.t_host.as_ref())) {
+            if url.host_str().is_some_and(|url_host| url_host.eq_ignore_ascii_case(host)) {
+                let mut tls_url = url.clone();
+                if tls_url.set_host(Some(server_name)).is_ok() {
+                    return tls_url;
+                }
+            }
+        }
+        url.clone()
+    }
+
     pub fn get_matched_config_by_url<'a>(&'a self, url: &str) -> Option<(&'a str, Option<&'a String>, Option<&'a String>)> {
         if url.starts_with(&self.t_base_url) {
             return Some((&self.t_base_url, self.username.as_ref(), self.password.as_ref()));
@@ -372,3 +560,43 @@ impl ConfigInput {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{ConfigInput, ConfigInputAlias, ConfigInputTls, InputType};
+    use url::Url;
+
+    fn alias(name: &str, priority: i16) -> ConfigInputAlias {
+        ConfigInputAlias { name: name.to_string(), url: format!("http://{name}.tv"), username: Some(format!("{name}_user")),
+            password: Some(format!("{name}_pass")), priority, t_base_url: format!("http://{name}.tv"), ..Default::default() }
+    }
+
+    #[test]
+    fn test_aliases() {
+        let input = ConfigInput { name: "input".to_string(), input_type: InputType::Xtream, url: "http://main.tv".to_string(),
+            username: Some("main_user".to_string()), password: Some("main_pass".to_string()),
+            aliases: Some(vec![alias("second", 2), alias("first", 1)]), ..Default::default() };
+        let aliases = input.get_aliases_by_priority();
+        assert_eq!(aliases.iter().map(|alias| alias.name.as_str()).collect::<Vec<_>>(), vec!["first", "second"]);
+
+        let alias_input = input.with_alias(aliases[0]);
+        assert_eq!((alias_input.name.as_str(), alias_input.url.as_str(), alias_input.username.as_deref()), ("input", "http://first.tv", Some("first_user")));
+        assert!(alias_input.aliases.is_none());
+
+        let input_info = input.get_user_info().unwrap();
+        let alias_info = input.get_alias_user_info(aliases[0]).unwrap();
+        assert_eq!(alias_info.replace_in_url("http://first.tv/live/first_user/first_pass/1.ts", &input_info), "http://main.tv/live/main_user/main_pass/1.ts");
+        assert_eq!(input_info.replace_in_url("http://epg.tv/main_user.xml", &alias_info), "http://epg.tv/main_user.xml");
+    }
+
+    #[test]
+    fn test_tls_url() {
+        let mut input = ConfigInput { name: "input".to_string(), url: "https://10.0.0.1:8443/get.php".to_string(),
+            tls: Some(ConfigInputTls { server_name: Some(" provider.tv ".to_string()), ..Default::default() }), ..Default::default() };
+        assert!(input.prepare(1, false).is_ok());
+        let url = Url::parse("https://10.0.0.1:8443/live/1.ts").unwrap();
+        assert_eq!(input.get_tls_url(&url).as_str(), "https://provider.tv:8443/live/1.ts");
+        let other_url = Url::parse("https://cdn.tv/live/1.ts").unwrap();
+        assert_eq!(input.get_tls_url(&other_url), other_url);
+    }
+}
@@ -1,17 +1,56 @@
 
 use shared::utils::default_as_true;
+use shared::error::{TuliproxError, TuliproxErrorKind, create_tuliprox_error_result};
+
+fn default_sanitize_replacement() -> String {
+    String::from("***")
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SanitizeRule {
+    pub pattern: String,
+    // can contain capture group variables $1, $2, ...
+    #[serde(default = "default_sanitize_replacement")]
+    pub replacement: String,
+    #[serde(skip_serializing, skip_deserializing)]
+    pub t_re: Option<regex::Regex>,
+}
+
+impl SanitizeRule {
+    pub fn prepare(&mut self) -> Result<(), TuliproxError> {
+        match regex::Regex::new(&self.pattern) {
+            Ok(re) => {
+                self.t_re = Some(re);
+                Ok(())
+            }
+            Err(err) => create_tuliprox_error_result!(TuliproxErrorKind::Info, "cant parse sanitize rule regex: {} {err}", &self.pattern),
+        }
+    }
+}
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct LogConfig {
     #[serde(default = "default_as_true")]
     pub sanitize_sensitive_info: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sanitize_rules: Vec<SanitizeRule>,
     #[serde(default)]
     pub log_active_user: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub log_level: Option<String>,
 }
 
+impl LogConfig {
+    pub fn prepare(&mut self) -> Result<(), TuliproxError> {
+        for rule in &mut self.sanitize_rules {
+            rule.prepare()?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 pub struct LogLevelConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -1,5 +1,19 @@
+use std::collections::HashMap;
+use crate::foundation::template::Template;
+use shared::error::TuliproxError;
 use shared::model::MsgKind;
 
+const DEFAULT_SMTP_PORT: u16 = 25;
+const DEFAULT_WELCOME_SUBJECT: &str = "Your IPTV account";
+
+fn default_smtp_port() -> u16 {
+    DEFAULT_SMTP_PORT
+}
+
+fn default_welcome_subject() -> String {
+    String::from(DEFAULT_WELCOME_SUBJECT)
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct TelegramMessagingConfig {
@@ -21,6 +35,36 @@ pub struct PushoverMessagingConfig {
     pub(crate) user: String,
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WelcomeEmailConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_welcome_subject")]
+    pub subject: String,
+    // message body template with variables like `{{user.username}}`, a default text is used if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmailMessagingConfig {
+    pub host: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    pub from: String,
+    // recipients of the notify_on messages
+    #[serde(default)]
+    pub to: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub welcome: Option<WelcomeEmailConfig>,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct MessagingConfig {
@@ -32,5 +76,26 @@ pub struct MessagingConfig {
     pub rest: Option<RestMessagingConfig>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pushover: Option<PushoverMessagingConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<EmailMessagingConfig>,
+    // templates per message kind with the variables `kind` and `message`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub templates: Option<HashMap<MsgKind, String>>,
+}
 
+impl MessagingConfig {
+    pub fn prepare(&self) -> Result<(), TuliproxError> {
+        if let Some(templates) = self.templates.as_ref() {
+            for template in templates.values() {
+                Template::parse(template)?;
+            }
+        }
+        if let Some(welcome) = self.email.as_ref().and_then(|email| email.welcome.as_ref()) {
+            Template::parse(&welcome.subject)?;
+            if let Some(template) = welcome.template.as_ref() {
+                Template::parse(template)?;
+            }
+        }
+        Ok(())
+    }
 }
\ No newline at end of file
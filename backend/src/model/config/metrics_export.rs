@@ -0,0 +1,32 @@
+use shared::error::{info_err, TuliproxError, TuliproxErrorKind};
+use shared::utils::default_metrics_export_interval_secs;
+use url::Url;
+
+/// Pushes the statistics in the influx line protocol to `url`.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetricsExportConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(default = "default_metrics_export_interval_secs")]
+    pub interval_secs: u64,
+}
+
+impl MetricsExportConfig {
+    pub fn prepare(&mut self) -> Result<(), TuliproxError> {
+        self.url = self.url.trim().to_string();
+        self.token = self.token.as_ref().map(|token| token.trim().to_string()).filter(|token| !token.is_empty());
+        if self.enabled {
+            if Url::parse(&self.url).is_err() {
+                return Err(info_err!(format!("Invalid metrics export url {}", self.url)));
+            }
+            if self.interval_secs == 0 {
+                return Err(info_err!("Metrics export interval_secs can't be 0".to_string()));
+            }
+        }
+        Ok(())
+    }
+}
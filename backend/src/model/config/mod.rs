@@ -20,6 +20,20 @@ mod proxy;
 mod schedule;
 mod api_proxy;
 mod rename;
+mod category;
+mod channel_247;
+mod adult;
+mod delivery;
+mod portal;
+mod provider_cache;
+mod metrics_export;
+mod replication;
+mod backup;
+mod user_cleanup;
+mod client_error;
+mod recording;
+mod crash_report;
+mod global_rules;
 
 mod healthcheck;
 
@@ -43,5 +57,19 @@ pub use rate_limit::*;
 pub use reverse_proxy::*;
 pub use proxy::*;
 pub use rename::*;
+pub use category::*;
+pub use channel_247::*;
+pub use adult::*;
+pub use delivery::*;
+pub use portal::*;
+pub use provider_cache::*;
+pub use metrics_export::*;
+pub use replication::*;
+pub use backup::*;
+pub use user_cleanup::*;
+pub use client_error::*;
+pub use recording::*;
+pub use crash_report::*;
+pub use global_rules::*;
 pub use trakt::*;
 pub use healthcheck::*;
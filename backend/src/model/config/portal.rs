@@ -0,0 +1,94 @@
+use crate::foundation::template::Template;
+use crate::utils;
+use shared::error::{create_tuliprox_error_result, TuliproxError, TuliproxErrorKind};
+
+const DEFAULT_PORTAL_PATH: &str = "portal";
+const DEFAULT_PORTAL_TITLE: &str = "tuliprox";
+
+fn default_portal_path() -> String {
+    String::from(DEFAULT_PORTAL_PATH)
+}
+
+fn default_portal_title() -> String {
+    String::from(DEFAULT_PORTAL_TITLE)
+}
+
+fn load_template(file: Option<&String>, working_dir: &str) -> Result<Option<Template>, TuliproxError> {
+    match file {
+        None => Ok(None),
+        Some(file) => {
+            let path = utils::make_absolute_path(file.trim(), working_dir);
+            match std::fs::read_to_string(&path) {
+                Ok(content) => Ok(Some(Template::parse(&content)?)),
+                Err(err) => create_tuliprox_error_result!(TuliproxErrorKind::Info, "Failed to read portal template {path}: {err}"),
+            }
+        }
+    }
+}
+
+/// Branding for requests with the `Host` of a server info.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PortalProfileConfig {
+    pub server: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub login_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_template: Option<String>,
+    #[serde(skip)]
+    pub t_login_template: Option<Template>,
+    #[serde(skip)]
+    pub t_account_template: Option<Template>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PortalConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_portal_path")]
+    pub path: String,
+    #[serde(default = "default_portal_title")]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub login_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<Vec<PortalProfileConfig>>,
+    #[serde(skip)]
+    pub t_login_template: Option<Template>,
+    #[serde(skip)]
+    pub t_account_template: Option<Template>,
+}
+
+impl PortalConfig {
+    pub fn prepare(&mut self, working_dir: &str) -> Result<(), TuliproxError> {
+        self.path = self.path.trim().trim_matches('/').to_string();
+        if self.path.is_empty() {
+            return create_tuliprox_error_result!(TuliproxErrorKind::Info, "portal path can't be empty");
+        }
+        if !self.enabled {
+            return Ok(());
+        }
+        self.t_login_template = load_template(self.login_template.as_ref(), working_dir)?;
+        self.t_account_template = load_template(self.account_template.as_ref(), working_dir)?;
+        if let Some(profiles) = self.profiles.as_mut() {
+            for profile in profiles {
+                profile.server = profile.server.trim().to_string();
+                if profile.server.is_empty() {
+                    return create_tuliprox_error_result!(TuliproxErrorKind::Info, "portal profile server can't be empty");
+                }
+                profile.t_login_template = load_template(profile.login_template.as_ref(), working_dir)?;
+                profile.t_account_template = load_template(profile.account_template.as_ref(), working_dir)?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get_profile(&self, server_name: Option<&str>) -> Option<&PortalProfileConfig> {
+        server_name.and_then(|name| self.profiles.as_ref()?.iter().find(|profile| profile.server == name))
+    }
+}
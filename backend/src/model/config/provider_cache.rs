@@ -0,0 +1,21 @@
+use shared::utils::default_provider_cache_max_age_secs;
+use std::time::Duration;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProviderCacheConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_provider_cache_max_age_secs")]
+    pub max_age_secs: u64,
+}
+
+impl ProviderCacheConfig {
+    pub fn get_max_age(&self) -> Option<Duration> {
+        if self.enabled && self.max_age_secs > 0 {
+            Some(Duration::from_secs(self.max_age_secs))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,41 @@
+use path_clean::PathClean;
+use shared::error::{info_err, TuliproxError, TuliproxErrorKind};
+use shared::utils::parse_size_base_2;
+use std::path::PathBuf;
+
+/// Recordings of live channels scheduled through the web ui api.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RecordingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    // directory of the recordings, relative paths are resolved against the working dir, default `recordings`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+    // maximum size of all recordings like `50GB`, unlimited if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub quota: Option<String>,
+    #[serde(skip)]
+    pub t_quota_bytes: Option<u64>,
+}
+
+impl RecordingConfig {
+    pub fn prepare(&mut self, working_dir: &str) -> Result<(), TuliproxError> {
+        let work_path = PathBuf::from(working_dir);
+        let dir = match self.dir.as_ref() {
+            None => work_path.join("recordings"),
+            Some(dir) if PathBuf::from(dir).is_relative() => work_path.join(dir),
+            Some(dir) => PathBuf::from(dir),
+        };
+        self.dir = Some(dir.clean().to_string_lossy().to_string());
+        self.t_quota_bytes = match self.quota.as_ref() {
+            None => None,
+            Some(quota) => match parse_size_base_2(quota) {
+                Ok(0) => return Err(info_err!(format!("Invalid recording quota {quota}"))),
+                Ok(bytes) => Some(bytes),
+                Err(err) => return Err(info_err!(format!("Failed to read recording quota: {err}"))),
+            },
+        };
+        Ok(())
+    }
+}
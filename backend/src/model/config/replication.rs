@@ -0,0 +1,45 @@
+use shared::error::{info_err, TuliproxError, TuliproxErrorKind};
+use shared::utils::default_replication_interval_secs;
+use url::Url;
+
+/// Replicates the config, users and processed playlists of a primary to a standby instance.
+/// The instance is a standby if `primary_url` is set, otherwise it serves the replication api.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReplicationConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    pub token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_url: Option<String>,
+    #[serde(default = "default_replication_interval_secs")]
+    pub interval_secs: u64,
+    // the standby promotes itself if the primary is unreachable for this time, 0 disables it
+    #[serde(default)]
+    pub promote_after_secs: u64,
+}
+
+impl ReplicationConfig {
+    pub fn prepare(&mut self) -> Result<(), TuliproxError> {
+        self.token = self.token.trim().to_string();
+        self.primary_url = self.primary_url.as_ref().map(|url| url.trim().trim_end_matches('/').to_string()).filter(|url| !url.is_empty());
+        if self.enabled {
+            if self.token.is_empty() {
+                return Err(info_err!("Replication token can't be empty".to_string()));
+            }
+            if let Some(url) = self.primary_url.as_ref() {
+                if Url::parse(url).is_err() {
+                    return Err(info_err!(format!("Invalid replication primary_url {url}")));
+                }
+            }
+            if self.interval_secs == 0 {
+                return Err(info_err!("Replication interval_secs can't be 0".to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_standby(&self) -> bool {
+        self.enabled && self.primary_url.is_some()
+    }
+}
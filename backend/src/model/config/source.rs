@@ -1,12 +1,15 @@
 use std::collections::{HashMap, HashSet};
 use crate::foundation::filter::{prepare_templates, PatternTemplate};
+use crate::tools::directed_graph::DirectedGraph;
 use shared::error::{TuliproxError, TuliproxErrorKind, handle_tuliprox_error_result_list, create_tuliprox_error_result};
-use crate::model::{ConfigInput, ConfigTarget, ProcessTargets};
+use crate::model::{ConfigGlobalRules, ConfigInput, ConfigTarget, ProcessTargets};
 use shared::utils::default_as_default;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ConfigSource {
+    // a source with only dependent targets needs no inputs
+    #[serde(default)]
     pub inputs: Vec<ConfigInput>,
     pub targets: Vec<ConfigTarget>,
 }
@@ -37,7 +40,13 @@ impl ConfigSource {
 pub struct SourcesConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub templates: Option<Vec<PatternTemplate>>,
+    // rules applied to all inputs before the targets are processed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global: Option<ConfigGlobalRules>,
     pub sources: Vec<ConfigSource>,
+    // targets with `depends_on`, ordered so that each target comes after its dependencies
+    #[serde(skip)]
+    pub t_dependency_order: Vec<String>,
 }
 
 impl SourcesConfig {
@@ -47,7 +56,11 @@ impl SourcesConfig {
 
     pub fn prepare(&mut self, include_computed: bool) -> Result<(), TuliproxError> {
         self.prepare_templates()?;
+        if let Some(global) = self.global.as_mut() {
+            global.prepare(self.templates.as_ref())?;
+        }
         self.prepare_sources(include_computed)?;
+        self.prepare_target_dependencies()?;
         Ok(())
     }
 
@@ -70,6 +83,39 @@ impl SourcesConfig {
         Ok(())
     }
 
+    fn prepare_target_dependencies(&mut self) -> Result<(), TuliproxError> {
+        let default_target_name = default_as_default();
+        let mut graph = DirectedGraph::<String>::new();
+        let mut dependent_targets = HashSet::new();
+        for target in self.sources.iter().flat_map(|source| source.targets.iter()) {
+            let Some(depends_on) = target.depends_on.as_ref() else { continue; };
+            graph.add_node(&target.name);
+            dependent_targets.insert(target.name.clone());
+            for dependency_name in depends_on {
+                if default_target_name.eq_ignore_ascii_case(dependency_name) {
+                    return create_tuliprox_error_result!(TuliproxErrorKind::Info, "target {} can't depend on a target without unique name", target.name);
+                }
+                let Some(dependency) = self.get_target_by_name(dependency_name) else {
+                    return create_tuliprox_error_result!(TuliproxErrorKind::Info, "target {} depends on unknown target {}", target.name, dependency_name);
+                };
+                if target.enabled && !dependency.enabled {
+                    return create_tuliprox_error_result!(TuliproxErrorKind::Info, "target {} depends on disabled target {}", target.name, dependency_name);
+                }
+                graph.add_node(dependency_name);
+                graph.add_edge(&target.name, dependency_name);
+            }
+        }
+        let cycles = graph.find_cycles();
+        if !cycles.is_empty() {
+            return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Cyclic target dependencies detected [{}]",
+                cycles.iter().map(|cycle| cycle.join(" -> ")).collect::<Vec<_>>().join(", "));
+        }
+        // the dependencies are sorted before their dependent targets
+        self.t_dependency_order = graph.topological_sort().unwrap_or_default()
+            .into_iter().filter(|name| dependent_targets.contains(name)).collect();
+        Ok(())
+    }
+
     fn prepare_templates(&mut self) -> Result<(), TuliproxError> {
         if let Some(templates) = &mut self.templates {
             match prepare_templates(templates) {
@@ -115,6 +161,10 @@ impl SourcesConfig {
         None
     }
 
+    pub fn get_target_by_name(&self, target_name: &str) -> Option<&ConfigTarget> {
+        self.sources.iter().flat_map(|source| source.targets.iter()).find(|target| target.name == target_name)
+    }
+
     /// Returns the targets that were specified as parameters.
     /// If invalid targets are found, the program will be terminated.
     /// The return value has `enabled` set to true, if selective targets should be processed, otherwise false.
@@ -151,6 +201,7 @@ impl SourcesConfig {
             if !missing_targets.is_empty() {
                 return create_tuliprox_error_result!(TuliproxErrorKind::Info, "No target found for {}", missing_targets.join(", "));
             }
+            self.add_target_dependencies(&mut inputs, &mut targets);
             // let processing_targets: Vec<String> = check_targets.iter().filter(|&(_, v)| *v != 0).map(|(k, _)| k.to_string()).collect();
             // info!("Processing targets {}", processing_targets.join(", "));
         } else {
@@ -163,4 +214,66 @@ impl SourcesConfig {
             targets,
         })
     }
-}
\ No newline at end of file
+
+    // The targets a selected target depends on are processed too.
+    fn add_target_dependencies(&self, inputs: &mut Vec<u16>, targets: &mut Vec<u16>) {
+        let mut pending = targets.clone();
+        while let Some(target_id) = pending.pop() {
+            let Some(depends_on) = self.get_target_by_id(target_id).and_then(|target| target.depends_on.as_ref()) else { continue; };
+            for source in &self.sources {
+                for target in &source.targets {
+                    if depends_on.contains(&target.name) && !targets.contains(&target.id) {
+                        targets.push(target.id);
+                        pending.push(target.id);
+                        for input in &source.inputs {
+                            if !inputs.contains(&input.id) {
+                                inputs.push(input.id);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_target(name: &str, depends_on: Option<Vec<&str>>) -> ConfigTarget {
+        ConfigTarget {
+            enabled: true,
+            name: name.to_string(),
+            depends_on: depends_on.map(|names| names.into_iter().map(ToString::to_string).collect()),
+            ..Default::default()
+        }
+    }
+
+    fn create_sources(targets: Vec<ConfigTarget>) -> SourcesConfig {
+        SourcesConfig { sources: vec![ConfigSource { inputs: vec![], targets }], ..Default::default() }
+    }
+
+    #[test]
+    fn test_target_dependency_order() {
+        let mut sources = create_sources(vec![
+            create_target("all", Some(vec!["merged", "prov_c"])),
+            create_target("merged", Some(vec!["prov_a", "prov_b"])),
+            create_target("prov_a", None),
+            create_target("prov_b", None),
+            create_target("prov_c", None),
+        ]);
+        assert!(sources.prepare_target_dependencies().is_ok());
+        assert_eq!(sources.t_dependency_order, vec!["merged".to_string(), "all".to_string()]);
+    }
+
+    #[test]
+    fn test_target_dependency_errors() {
+        let mut cyclic = create_sources(vec![
+            create_target("a", Some(vec!["b"])),
+            create_target("b", Some(vec!["a"])),
+        ]);
+        assert!(cyclic.prepare_target_dependencies().is_err());
+        let mut unknown = create_sources(vec![create_target("a", Some(vec!["missing"]))]);
+        assert!(unknown.prepare_target_dependencies().is_err());
+    }
+}
@@ -1,11 +1,16 @@
 use shared::utils::default_grace_period_millis;
 use shared::utils::default_grace_period_timeout_secs;
+use shared::utils::default_failover_timeout_secs;
 use shared::error::{TuliproxError, TuliproxErrorKind};
 use shared::info_err;
-use shared::utils::parse_to_kbps;
+use shared::utils::{parse_size_base_2, parse_to_kbps};
 use crate::api::model::streams::transport_stream_buffer::TransportStreamBuffer;
+use log::warn;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
 
 const STREAM_QUEUE_SIZE: usize = 1024; // mpsc channel holding messages. with 8192byte chunks and 2Mbit/s approx 8MB
+const DEFAULT_CHAOS_ERROR_STATUS: u16 = 503;
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 #[serde(deny_unknown_fields)]
@@ -24,6 +29,50 @@ impl StreamBufferConfig {
     }
 }
 
+// Fault injection for provider streams, only meant for test deployments.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct StreamChaosConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    // delay before each provider stream request
+    #[serde(default)]
+    pub latency_millis: u64,
+    // provider streams are closed after this time
+    #[serde(default)]
+    pub drop_after_secs: u32,
+    // every nth provider stream request (hls segment) fails with error_status
+    #[serde(default)]
+    pub error_every_nth: u32,
+    #[serde(default)]
+    pub error_status: u16,
+    #[serde(default, skip)]
+    pub t_request_counter: Arc<AtomicU32>,
+}
+
+impl StreamChaosConfig {
+    fn prepare(&mut self) -> Result<(), TuliproxError> {
+        if self.error_status == 0 {
+            self.error_status = DEFAULT_CHAOS_ERROR_STATUS;
+        } else if !(500..=599).contains(&self.error_status) {
+            return Err(info_err!(format!("Chaos error status {} should be a server error status 5xx", self.error_status)));
+        }
+        if self.enabled {
+            warn!("Stream chaos testing is enabled, provider streams will fail on purpose!");
+        }
+        Ok(())
+    }
+
+    /// Counts the provider request and returns the error status if this request should fail.
+    pub fn next_request_error_status(&self) -> Option<u16> {
+        if self.error_every_nth == 0 {
+            return None;
+        }
+        let count = self.t_request_counter.fetch_add(1, Ordering::SeqCst).wrapping_add(1);
+        count.is_multiple_of(self.error_every_nth).then_some(self.error_status)
+    }
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct StreamConfig {
@@ -33,14 +82,33 @@ pub struct StreamConfig {
     pub buffer: Option<StreamBufferConfig>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub throttle: Option<String>,
+    // unthrottled size at stream start, like `10MB`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub throttle_burst: Option<String>,
+    // throttle vod streams to their bitrate multiplied by this factor, 0 disables it
+    #[serde(default)]
+    pub throttle_bitrate_factor: f64,
     #[serde(default = "default_grace_period_millis")]
     pub grace_period_millis: u64,
     #[serde(default = "default_grace_period_timeout_secs")]
     pub grace_period_timeout_secs: u64,
+    // wait for a freed slot when max_connections is reached, known devices first, 0 disables it
+    #[serde(default)]
+    pub slot_wait_millis: u64,
     #[serde(default)]
     pub forced_retry_interval_secs: u32,
+    // a failover channel switches to the next url when the provider sends no data within this time, 0 disables it
+    #[serde(default = "default_failover_timeout_secs")]
+    pub failover_timeout_secs: u64,
+    // client streams are closed when the client read nothing within this time, 0 disables it
+    #[serde(default)]
+    pub idle_timeout_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chaos: Option<StreamChaosConfig>,
     #[serde(default, skip)]
     pub throttle_kbps: u64,
+    #[serde(default, skip)]
+    pub throttle_burst_bytes: u64,
 }
 
 impl StreamConfig {
@@ -48,9 +116,18 @@ impl StreamConfig {
         if let Some(buffer) = self.buffer.as_mut() {
             buffer.prepare();
         }
+        if let Some(chaos) = self.chaos.as_mut() {
+            chaos.prepare()?;
+        }
         if let Some(throttle) = &self.throttle {
             self.throttle_kbps = parse_to_kbps(throttle).map_err(|err| TuliproxError::new(TuliproxErrorKind::Info, err))?;
         }
+        if self.throttle_bitrate_factor < 0.0 {
+            return Err(info_err!(format!("Throttle bitrate factor {} should not be negative", self.throttle_bitrate_factor)));
+        }
+        if let Some(burst) = &self.throttle_burst {
+            self.throttle_burst_bytes = parse_size_base_2(burst).map_err(|err| TuliproxError::new(TuliproxErrorKind::Info, err))?;
+        }
 
         if self.grace_period_millis > 0 {
             if self.grace_period_timeout_secs == 0 {
@@ -77,4 +154,21 @@ pub struct CustomStreamResponse {
     pub provider_connections_exhausted: Option<TransportStreamBuffer>, // provider limit reached, has no more connections
     #[serde(default, skip)]
     pub user_account_expired: Option<TransportStreamBuffer>,
-}
\ No newline at end of file
+    #[serde(default, skip)]
+    pub maintenance: Option<TransportStreamBuffer>, // announcement of a planned maintenance
+}
+#[cfg(test)]
+mod tests {
+    use crate::model::StreamChaosConfig;
+
+    #[test]
+    fn test_chaos_error_every_nth() {
+        let mut chaos: StreamChaosConfig = serde_yaml::from_str("{enabled: true, error_every_nth: 3}").unwrap();
+        chaos.prepare().unwrap();
+        let statuses: Vec<Option<u16>> = (0..6).map(|_| chaos.next_request_error_status()).collect();
+        assert_eq!(statuses, vec![None, None, Some(503), None, None, Some(503)]);
+
+        let mut invalid: StreamChaosConfig = serde_yaml::from_str("{enabled: true, error_status: 404}").unwrap();
+        assert!(invalid.prepare().is_err());
+    }
+}
@@ -5,9 +5,10 @@ use shared::error::{create_tuliprox_error_result, handle_tuliprox_error_result_l
 use shared::utils::{default_as_default, default_as_true, default_resolve_delay_secs};
 use arc_swap::ArcSwapOption;
 use shared::model::{ClusterFlags, ProcessingOrder, StrmExportStyle, TargetType};
-use shared::model::PlaylistItemType;
+use shared::model::{PlaylistItemType, XtreamCluster};
+use std::collections::HashMap;
 use std::sync::Arc;
-use crate::model::{ConfigRename, ConfigSort};
+use crate::model::{ConfigAdultDetection, ConfigCategoryRule, ConfigChannel247, ConfigDelivery, ConfigRename, ConfigSort, EpgPlaceholder, EpgProgrammeFilter, EpgTimeWindow};
 
 
 #[derive(Clone, Debug)]
@@ -27,6 +28,7 @@ impl ProcessTargets {
     }
 }
 
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct ConfigTargetOptions {
@@ -36,8 +38,16 @@ pub struct ConfigTargetOptions {
     pub share_live_streams: bool,
     #[serde(default)]
     pub remove_duplicates: bool,
+    #[serde(default)]
+    pub remove_duplicate_titles: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub force_redirect: Option<ClusterFlags>,
+    #[serde(default)]
+    pub group_hierarchy: bool,
+    #[serde(default)]
+    pub compress_store: bool,
+    #[serde(default)]
+    pub channel_failover: bool,
 }
 
 #[allow(clippy::struct_excessive_bools)]
@@ -60,6 +70,10 @@ pub struct XtreamTargetOutput {
     pub resolve_vod_delay: u16,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub trakt: Option<TraktConfig>,
+    #[serde(default)]
+    pub continue_watching: bool,
+    #[serde(default)]
+    pub epg_json: bool,
 }
 
 impl XtreamTargetOutput {
@@ -70,6 +84,8 @@ impl XtreamTargetOutput {
     }
 }
 
+// the flags are independent options of the m3u output config
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct M3uTargetOutput {
@@ -79,6 +95,10 @@ pub struct M3uTargetOutput {
     pub include_type_in_url: bool,
     #[serde(default)]
     pub mask_redirect_url: bool,
+    #[serde(default)]
+    pub epg_json: bool,
+    #[serde(default)]
+    pub catchup: bool,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -145,12 +165,42 @@ pub struct ConfigTarget {
     pub output: Vec<TargetOutput>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rename: Option<Vec<ConfigRename>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Vec<ConfigCategoryRule>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel_247: Option<ConfigChannel247>,
+    // tags adult channels, see `hide_adult` of the user packages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adult_detection: Option<ConfigAdultDetection>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mapping: Option<Vec<String>>,
     #[serde(default)]
     pub processing_order: ProcessingOrder,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub watch: Option<Vec<String>>,
+    // locale -> (category name -> translated name), the locale is selected by the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locales: Option<HashMap<String, HashMap<String, String>>>,
+    // upload the generated m3u and xmltv after each update
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery: Option<Vec<ConfigDelivery>>,
+    // names of the targets whose processed playlists are the input of this target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depends_on: Option<Vec<String>>,
+    // values read by the mapper scripts with var("key")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mapper_vars: Option<HashMap<String, String>>,
+    // drops programmes of the generated epg
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_filter: Option<EpgProgrammeFilter>,
+    // placeholder programmes for channels without guide data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_placeholder: Option<EpgPlaceholder>,
+    // programmes outside of the days around the update are removed from the generated epg
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_days_past: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_days_future: Option<u16>,
     #[serde(skip)]
     pub t_watch_re: Option<Vec<regex::Regex>>,
     #[serde(skip)]
@@ -266,6 +316,25 @@ impl ConfigTarget {
             }
         }
 
+        if let Some(delivery) = self.delivery.as_mut() {
+            handle_tuliprox_error_result_list!(TuliproxErrorKind::Info, delivery.iter_mut().map(ConfigDelivery::prepare));
+        }
+
+        if let Some(epg_filter) = self.epg_filter.as_mut() {
+            epg_filter.prepare()?;
+        }
+        if let Some(epg_placeholder) = self.epg_placeholder.as_mut() {
+            epg_placeholder.prepare()?;
+        }
+        if let Some(depends_on) = self.depends_on.as_mut() {
+            for name in depends_on.iter_mut() {
+                *name = name.trim().to_string();
+            }
+            if depends_on.iter().any(|name| name.eq(&self.name)) {
+                return create_tuliprox_error_result!(TuliproxErrorKind::Info, "target can't depend on itself: {}", self.name);
+            }
+        }
+
         match get_filter(&self.filter, templates) {
             Ok(fltr) => {
                 // debug!("Filter: {}", fltr);
@@ -273,6 +342,15 @@ impl ConfigTarget {
                 if let Some(renames) = self.rename.as_mut() {
                     handle_tuliprox_error_result_list!(TuliproxErrorKind::Info, renames.iter_mut().map(|cr|cr.prepare(templates)));
                 }
+                if let Some(categories) = self.categories.as_mut() {
+                    handle_tuliprox_error_result_list!(TuliproxErrorKind::Info, categories.iter_mut().map(|cr|cr.prepare(templates)));
+                }
+                if let Some(channel_247) = self.channel_247.as_mut() {
+                    channel_247.prepare(templates)?;
+                }
+                if let Some(adult_detection) = self.adult_detection.as_mut() {
+                    adult_detection.prepare(templates)?;
+                }
                 if let Some(sort) = self.sort.as_mut() {
                     sort.prepare(templates)?;
                 }
@@ -333,10 +411,46 @@ impl ConfigTarget {
         false
     }
 
+    pub fn get_category_icon(&self, cluster: XtreamCluster, title: &str) -> Option<&str> {
+        self.categories.as_ref()?.iter()
+            .find(|rule| rule.icon.is_some() && rule.is_output_match(cluster, title))
+            .and_then(|rule| rule.icon.as_deref())
+    }
+
+    /// Returns the category name dictionary for the locale, `de-AT` falls back to `de`.
+    pub fn get_locale_dictionary(&self, locale: Option<&str>) -> Option<&HashMap<String, String>> {
+        let locale = locale?.trim();
+        let locales = self.locales.as_ref()?;
+        locales.get(locale).or_else(|| {
+            let (language, _) = locale.split_once(['-', '_'])?;
+            locales.get(language)
+        })
+    }
+
+    /// Returns the time window of the programmes kept in the target epg, `None` without `epg_days_past` and `epg_days_future`.
+    pub fn get_epg_time_window(&self, now: i64) -> Option<EpgTimeWindow> {
+        const SECS_PER_DAY: i64 = 86_400;
+        if self.epg_days_past.is_none() && self.epg_days_future.is_none() {
+            return None;
+        }
+        Some(EpgTimeWindow {
+            from: self.epg_days_past.map(|days| now - i64::from(days) * SECS_PER_DAY),
+            to: self.epg_days_future.map(|days| now + i64::from(days) * SECS_PER_DAY),
+        })
+    }
+
     pub fn is_force_redirect(&self, item_type: PlaylistItemType) -> bool {
         self.options
             .as_ref()
             .and_then(|options| options.force_redirect.as_ref())
             .is_some_and(|flags| flags.has_cluster(item_type))
     }
+
+    pub fn is_store_compressed(&self) -> bool {
+        self.options.as_ref().is_some_and(|options| options.compress_store)
+    }
+
+    pub fn is_channel_failover(&self) -> bool {
+        self.options.as_ref().is_some_and(|options| options.channel_failover)
+    }
 }
\ No newline at end of file
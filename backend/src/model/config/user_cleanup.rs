@@ -0,0 +1,42 @@
+use cron::Schedule;
+use shared::error::{info_err, TuliproxError, TuliproxErrorKind};
+use shared::model::UserCleanupAction;
+use shared::utils::default_user_cleanup_grace_days;
+use std::str::FromStr;
+
+/// Scheduled cleanup of users without activity, the users are disabled or deleted
+/// `grace_days` after the notification when they are still inactive.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserCleanupConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    pub schedule: String,
+    // users without activity for this number of days are cleaned up
+    pub inactive_days: u32,
+    #[serde(default)]
+    pub action: UserCleanupAction,
+    #[serde(default = "default_user_cleanup_grace_days")]
+    pub grace_days: u32,
+    // usernames which are never cleaned up
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<Vec<String>>,
+}
+
+impl UserCleanupConfig {
+    pub fn prepare(&mut self) -> Result<(), TuliproxError> {
+        if self.enabled {
+            if let Err(err) = Schedule::from_str(&self.schedule) {
+                return Err(info_err!(format!("Invalid user cleanup schedule {}: {err}", self.schedule)));
+            }
+            if self.inactive_days == 0 {
+                return Err(info_err!("User cleanup inactive_days must be greater than 0".to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn is_excluded(&self, username: &str) -> bool {
+        self.exclude.as_ref().is_some_and(|list| list.iter().any(|name| name == username))
+    }
+}
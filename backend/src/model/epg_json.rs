@@ -0,0 +1,113 @@
+use crate::model::{XmlTag, XmlTagIcon, EPG_ATTRIB_START, EPG_ATTRIB_STOP, EPG_TAG_CATEGORY, EPG_TAG_DESC, EPG_TAG_DISPLAY_NAME, EPG_TAG_ICON, EPG_TAG_SUB_TITLE, EPG_TAG_TITLE};
+use crate::utils::parse_xmltv_time;
+
+/// A programme of the json guide, the times are unix timestamps in seconds.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EpgJsonProgramme {
+    pub start: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stop: Option<i64>,
+    #[serde(default)]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub_title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub desc: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+}
+
+impl EpgJsonProgramme {
+    /// Converts a xmltv `programme` tag, programmes without a valid start time are skipped.
+    pub fn from_tag(tag: &XmlTag) -> Option<Self> {
+        let start = tag.get_attribute_value(EPG_ATTRIB_START).and_then(|start| parse_xmltv_time(start))?;
+        let children = tag.children.as_deref().unwrap_or_default();
+        let text = |name: &str| children.iter().find(|child| child.name == name).and_then(|child| child.value.clone());
+        Some(Self {
+            start,
+            stop: tag.get_attribute_value(EPG_ATTRIB_STOP).and_then(|stop| parse_xmltv_time(stop)),
+            title: text(EPG_TAG_TITLE).unwrap_or_default(),
+            sub_title: text(EPG_TAG_SUB_TITLE),
+            desc: text(EPG_TAG_DESC),
+            categories: children.iter().filter(|child| child.name == EPG_TAG_CATEGORY).filter_map(|child| child.value.clone()).collect(),
+        })
+    }
+}
+
+/// A channel of the json guide with its programmes sorted by start time.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EpgJsonChannel {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    pub programmes: Vec<EpgJsonProgramme>,
+}
+
+impl EpgJsonChannel {
+    /// The `channel` tag is `None` for programmes of channels the guide does not define.
+    pub fn new(id: &str, channel: Option<&XmlTag>, programmes: &[&XmlTag]) -> Self {
+        let children = channel.and_then(|tag| tag.children.as_deref()).unwrap_or_default();
+        let mut programmes: Vec<EpgJsonProgramme> = programmes.iter().filter_map(|tag| EpgJsonProgramme::from_tag(tag)).collect();
+        programmes.sort_by_key(|programme| programme.start);
+        Self {
+            id: id.to_string(),
+            name: children.iter().find(|child| child.name == EPG_TAG_DISPLAY_NAME).and_then(|child| child.value.clone()),
+            // like the xmltv output only prepared icons are used
+            icon: children.iter().find(|child| child.name == EPG_TAG_ICON && child.icon != XmlTagIcon::Undefined)
+                .and_then(|child| child.get_attribute_value("src").cloned()),
+            programmes,
+        }
+    }
+
+    pub fn shift(&mut self, seconds: i64) {
+        for programme in &mut self.programmes {
+            programme.start += seconds;
+            programme.stop = programme.stop.map(|stop| stop + seconds);
+        }
+    }
+}
+
+/// The json guide of a target, written alongside the xmltv file when the output option `epg_json` is set.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EpgJson {
+    pub channels: Vec<EpgJsonChannel>,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{EpgJsonChannel, XmlTag, XmlTagIcon};
+    use std::collections::HashMap;
+
+    fn tag(name: &str, value: Option<&str>, attributes: &[(&str, &str)], children: Vec<XmlTag>) -> XmlTag {
+        let mut tag = XmlTag::new(name.to_string(), Some(attributes.iter().map(|(k, v)| ((*k).to_string(), (*v).to_string())).collect::<HashMap<_, _>>()));
+        tag.value = value.map(ToString::to_string);
+        tag.children = (!children.is_empty()).then_some(children);
+        tag
+    }
+
+    #[test]
+    fn test_epg_json_channel() {
+        let mut icon = tag("icon", None, &[("src", "http://logo/zdf.png")], vec![]);
+        icon.icon = XmlTagIcon::Exists;
+        let channel = tag("channel", None, &[("id", "zdf.de")], vec![tag("display-name", Some("ZDF"), &[], vec![]), icon]);
+        let late = tag("programme", None, &[("channel", "zdf.de"), ("start", "20250601210000 +0000"), ("stop", "20250601220000 +0000")],
+                       vec![tag("title", Some("Late"), &[], vec![]), tag("category", Some("News"), &[], vec![]), tag("category", Some("Talk"), &[], vec![])]);
+        let early = tag("programme", None, &[("channel", "zdf.de"), ("start", "20250601220000 +0200")],
+                        vec![tag("title", Some("Early"), &[], vec![]), tag("desc", Some("Text"), &[], vec![])]);
+        let invalid = tag("programme", None, &[("channel", "zdf.de"), ("start", "tomorrow")], vec![]);
+
+        let mut json = EpgJsonChannel::new("zdf.de", Some(&channel), &[&late, &early, &invalid]);
+        assert_eq!(json.name.as_deref(), Some("ZDF"));
+        assert_eq!(json.icon.as_deref(), Some("http://logo/zdf.png"));
+        assert_eq!(json.programmes.iter().map(|p| p.title.as_str()).collect::<Vec<_>>(), vec!["Early", "Late"]);
+        assert_eq!((json.programmes[0].start, json.programmes[0].stop, json.programmes[0].desc.as_deref()), (1_748_808_000, None, Some("Text")));
+        assert_eq!(json.programmes[1].categories, vec!["News", "Talk"]);
+        json.shift(-3600);
+        assert_eq!((json.programmes[1].start, json.programmes[1].stop), (1_748_808_000, Some(1_748_811_600)));
+
+        let json = EpgJsonChannel::new("ard.de", None, &[]);
+        assert_eq!(serde_json::to_string(&json).unwrap(), r#"{"id":"ard.de","programmes":[]}"#);
+    }
+}
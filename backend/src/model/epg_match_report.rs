@@ -0,0 +1,110 @@
+use shared::error::{create_tuliprox_error_result, TuliproxError, TuliproxErrorKind};
+use std::str::FromStr;
+
+/// How the epg id of a live channel was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EpgMatchKind {
+    // the channel epg id exists in the guide
+    Id,
+    // the epg id is pinned by the epg aliases
+    Alias,
+    // the normalized channel name equals a normalized guide display name
+    Normalized,
+    // the normalized channel name is similar to a normalized guide display name
+    Fuzzy,
+    Unmatched,
+}
+
+impl FromStr for EpgMatchKind {
+    type Err = TuliproxError;
+
+    fn from_str(s: &str) -> Result<Self, TuliproxError> {
+        match s.trim().to_lowercase().as_str() {
+            "id" => Ok(Self::Id),
+            "alias" => Ok(Self::Alias),
+            "normalized" => Ok(Self::Normalized),
+            "fuzzy" => Ok(Self::Fuzzy),
+            "unmatched" => Ok(Self::Unmatched),
+            _ => create_tuliprox_error_result!(TuliproxErrorKind::Info, "Unknown epg match kind: {}", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EpgChannelMatch {
+    pub input: String,
+    pub group: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_id: Option<String>,
+    pub kind: EpgMatchKind,
+    // similarity in percent of fuzzy matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub score: Option<u16>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct EpgMatchSummary {
+    pub id: usize,
+    pub alias: usize,
+    pub normalized: usize,
+    pub fuzzy: usize,
+    pub unmatched: usize,
+}
+
+/// The epg matches of the live channels of the last playlist update of a target.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct EpgMatchReport {
+    // unix timestamp in seconds
+    pub ts: i64,
+    pub channels: Vec<EpgChannelMatch>,
+}
+
+impl EpgMatchReport {
+    pub fn summary(&self) -> EpgMatchSummary {
+        let mut summary = EpgMatchSummary::default();
+        for channel in &self.channels {
+            let count = match channel.kind {
+                EpgMatchKind::Id => &mut summary.id,
+                EpgMatchKind::Alias => &mut summary.alias,
+                EpgMatchKind::Normalized => &mut summary.normalized,
+                EpgMatchKind::Fuzzy => &mut summary.fuzzy,
+                EpgMatchKind::Unmatched => &mut summary.unmatched,
+            };
+            *count += 1;
+        }
+        summary
+    }
+
+    /// The channels with one of the `kinds`, all channels if `kinds` is empty.
+    pub fn query(&self, kinds: &[EpgMatchKind]) -> Vec<&EpgChannelMatch> {
+        self.channels.iter().filter(|channel| kinds.is_empty() || kinds.contains(&channel.kind)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{EpgChannelMatch, EpgMatchKind, EpgMatchReport, EpgMatchSummary};
+    use std::str::FromStr;
+
+    fn channel(name: &str, kind: EpgMatchKind) -> EpgChannelMatch {
+        EpgChannelMatch { input: "input".to_string(), group: "News".to_string(), name: name.to_string(), epg_id: None, kind, score: None }
+    }
+
+    #[test]
+    fn test_epg_match_report() {
+        let report = EpgMatchReport { ts: 0, channels: vec![
+            channel("Das Erste", EpgMatchKind::Id),
+            channel("ZDF", EpgMatchKind::Fuzzy),
+            channel("Sport", EpgMatchKind::Unmatched),
+            channel("News", EpgMatchKind::Unmatched),
+        ] };
+        assert_eq!(report.summary(), EpgMatchSummary { id: 1, alias: 0, normalized: 0, fuzzy: 1, unmatched: 2 });
+        assert_eq!(report.query(&[]).len(), 4);
+        let names: Vec<_> = report.query(&[EpgMatchKind::Unmatched, EpgMatchKind::Fuzzy]).iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["ZDF", "Sport", "News"]);
+        assert_eq!(EpgMatchKind::from_str(" Fuzzy").unwrap(), EpgMatchKind::Fuzzy);
+        assert!(EpgMatchKind::from_str("guess").is_err());
+    }
+}
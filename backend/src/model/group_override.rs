@@ -0,0 +1,170 @@
+use serde_json::Value;
+use shared::model::XtreamCluster;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GroupOverride {
+    pub cluster: XtreamCluster,
+    pub group: String,
+    #[serde(default)]
+    pub hidden: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rename: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<i16>,
+}
+
+impl GroupOverride {
+    fn new(cluster: XtreamCluster, group: &str) -> Self {
+        Self {
+            cluster,
+            group: group.to_string(),
+            hidden: false,
+            rename: None,
+            priority: None,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.hidden && self.rename.is_none() && self.priority.is_none()
+    }
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "action", rename_all = "lowercase")]
+pub enum GroupBulkOperation {
+    Hide { cluster: XtreamCluster, groups: Vec<String> },
+    Show { cluster: XtreamCluster, groups: Vec<String> },
+    Rename { cluster: XtreamCluster, group: String, name: Option<String> },
+    Priority { cluster: XtreamCluster, groups: Vec<String>, priority: Option<i16> },
+}
+
+/// Runtime group overrides of a target.
+/// They are applied when the target output is served and are kept across playlist updates.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct GroupOverrides {
+    pub groups: Vec<GroupOverride>,
+}
+
+impl GroupOverrides {
+    pub fn get(&self, cluster: XtreamCluster, group: &str) -> Option<&GroupOverride> {
+        self.groups.iter().find(|o| o.cluster == cluster && o.group == group)
+    }
+
+    fn get_or_insert(&mut self, cluster: XtreamCluster, group: &str) -> &mut GroupOverride {
+        let idx = if let Some(idx) = self.groups.iter().position(|o| o.cluster == cluster && o.group == group) { idx } else {
+            self.groups.push(GroupOverride::new(cluster, group));
+            self.groups.len() - 1
+        };
+        &mut self.groups[idx]
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    pub fn has_cluster(&self, cluster: XtreamCluster) -> bool {
+        self.groups.iter().any(|o| o.cluster == cluster)
+    }
+
+    pub fn is_hidden(&self, cluster: XtreamCluster, group: &str) -> bool {
+        self.get(cluster, group).is_some_and(|o| o.hidden)
+    }
+
+    pub fn get_priority(&self, cluster: XtreamCluster, group: &str) -> i16 {
+        self.get(cluster, group).and_then(|o| o.priority).unwrap_or_default()
+    }
+
+    pub fn has_priority(&self) -> bool {
+        self.groups.iter().any(|o| o.priority.is_some_and(|priority| priority != 0))
+    }
+
+    pub fn get_name(&self, cluster: XtreamCluster, group: &str) -> Option<&str> {
+        self.get(cluster, group).and_then(|o| o.rename.as_deref())
+    }
+
+    pub fn get_hidden_groups(&self, cluster: XtreamCluster) -> Vec<&str> {
+        self.groups.iter().filter(|o| o.cluster == cluster && o.hidden).map(|o| o.group.as_str()).collect()
+    }
+
+    pub fn apply_operation(&mut self, operation: GroupBulkOperation) {
+        match operation {
+            GroupBulkOperation::Hide { cluster, groups } => {
+                for group in &groups {
+                    self.get_or_insert(cluster, group).hidden = true;
+                }
+            }
+            GroupBulkOperation::Show { cluster, groups } => {
+                for group in &groups {
+                    self.get_or_insert(cluster, group).hidden = false;
+                }
+            }
+            GroupBulkOperation::Rename { cluster, group, name } => {
+                self.get_or_insert(cluster, &group).rename = name.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+            }
+            GroupBulkOperation::Priority { cluster, groups, priority } => {
+                for group in &groups {
+                    self.get_or_insert(cluster, group).priority = priority;
+                }
+            }
+        }
+        self.groups.retain(|o| !o.is_empty());
+    }
+
+    /// Applies the overrides to the xtream category documents of the given cluster.
+    /// Hidden categories are removed, renamed categories get the new name
+    /// and the categories are ordered by priority. Categories without priority have priority 0.
+    pub fn apply_to_categories(&self, cluster: XtreamCluster, categories: Vec<Value>) -> Vec<Value> {
+        let mut result: Vec<(i16, Value)> = categories.into_iter().filter_map(|mut category| {
+            let Some(name) = category.get(crate::model::XC_TAG_CATEGORY_NAME).and_then(Value::as_str).map(ToString::to_string) else {
+                return Some((0, category));
+            };
+            match self.get(cluster, &name) {
+                Some(group_override) => {
+                    if group_override.hidden {
+                        return None;
+                    }
+                    if let Some(new_name) = group_override.rename.as_ref() {
+                        category[crate::model::XC_TAG_CATEGORY_NAME] = Value::String(new_name.clone());
+                    }
+                    Some((group_override.priority.unwrap_or_default(), category))
+                }
+                None => Some((0, category))
+            }
+        }).collect();
+        result.sort_by_key(|(priority, _)| *priority);
+        result.into_iter().map(|(_, category)| category).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{GroupBulkOperation, GroupOverrides};
+    use serde_json::json;
+    use shared::model::XtreamCluster;
+
+    #[test]
+    fn test_group_overrides() {
+        let mut overrides = GroupOverrides::default();
+        overrides.apply_operation(GroupBulkOperation::Hide { cluster: XtreamCluster::Live, groups: vec!["News".to_string()] });
+        overrides.apply_operation(GroupBulkOperation::Rename { cluster: XtreamCluster::Live, group: "Sport".to_string(), name: Some("Sports".to_string()) });
+        overrides.apply_operation(GroupBulkOperation::Priority { cluster: XtreamCluster::Live, groups: vec!["Sport".to_string()], priority: Some(-1) });
+        assert!(overrides.is_hidden(XtreamCluster::Live, "News"));
+        assert!(!overrides.is_hidden(XtreamCluster::Video, "News"));
+
+        let categories = vec![
+            json!({"category_id": "1", "category_name": "News"}),
+            json!({"category_id": "2", "category_name": "Music"}),
+            json!({"category_id": "3", "category_name": "Sport"}),
+        ];
+        let result = overrides.apply_to_categories(XtreamCluster::Live, categories);
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[0]["category_name"], "Sports");
+        assert_eq!(result[1]["category_name"], "Music");
+
+        overrides.apply_operation(GroupBulkOperation::Show { cluster: XtreamCluster::Live, groups: vec!["News".to_string()] });
+        overrides.apply_operation(GroupBulkOperation::Rename { cluster: XtreamCluster::Live, group: "Sport".to_string(), name: None });
+        overrides.apply_operation(GroupBulkOperation::Priority { cluster: XtreamCluster::Live, groups: vec!["Sport".to_string()], priority: None });
+        assert!(overrides.is_empty());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::model::TargetFreshness;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 
@@ -24,4 +25,10 @@ pub struct StatusCheck {
     pub active_user_connections: usize,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub active_provider_connections: Option<BTreeMap<String, usize>>,
+    // targets serving their last playlist because inputs fail
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale_targets: Option<BTreeMap<String, TargetFreshness>>,
+    // stores written by another release which can't be read until the playlists are updated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reprocess_required: Option<Vec<String>>,
 }
\ No newline at end of file
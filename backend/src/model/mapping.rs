@@ -1,5 +1,6 @@
 use enum_iterator::Sequence;
 use std::fmt::Display;
+use std::path::Path;
 use std::str::FromStr;
 use std::sync::atomic::AtomicU32;
 use std::sync::Arc;
@@ -143,6 +144,8 @@ impl MapperOperation {
 pub struct Mapper {
     pub filter: String,
     pub script: String,
+    #[serde(default)]
+    pub trace: bool,
     #[serde(skip_serializing, skip_deserializing)]
     pub t_filter: Option<Filter>,
     #[serde(skip_serializing, skip_deserializing)]
@@ -153,7 +156,7 @@ impl Mapper {
     /// # Panics
     ///
     /// Will panic if default `RegEx` gets invalid
-    pub fn prepare(&mut self, templates: Option<&Vec<PatternTemplate>>) -> Result<(), TuliproxError> {
+    pub fn prepare(&mut self, templates: Option<&Vec<PatternTemplate>>, include_dir: Option<&Path>) -> Result<(), TuliproxError> {
         match get_filter(&self.filter, templates) {
             Ok(filter) => self.t_filter = Some(filter),
             Err(err) => return Err(err),
@@ -164,7 +167,7 @@ impl Mapper {
             self.script.to_string()
         };
         trace!("Mapper script: {script}");
-        self.t_script = Some(MapperScript::parse(&script, templates)?);
+        self.t_script = Some(MapperScript::parse_with_includes(&script, templates, include_dir)?);
         Ok(())
     }
 }
@@ -184,11 +187,13 @@ pub struct Mapping {
 }
 
 impl Mapping {
-    pub fn prepare(&mut self, templates: Option<&Vec<PatternTemplate>>) -> Result<(), TuliproxError> {
+    pub fn prepare(&mut self, templates: Option<&Vec<PatternTemplate>>, include_dir: Option<&Path>) -> Result<(), TuliproxError> {
         self.templates = templates.map(|t| t.iter().map(PatternTemplate::clone).collect::<Vec<_>>());
         if let Some(mapper_list) = &mut self.mapper {
-            for mapper in mapper_list {
-                mapper.prepare(templates)?;
+            for (index, mapper) in mapper_list.iter_mut().enumerate() {
+                // the script errors contain the line and column inside the script
+                mapper.prepare(templates, include_dir)
+                    .map_err(|err| info_err!(format!("Mapping {} mapper {}: {}", self.id, index + 1, err.message)))?;
             }
         }
 
@@ -226,7 +231,7 @@ pub struct MappingDefinition {
 }
 
 impl MappingDefinition {
-    pub fn prepare(&mut self) -> Result<(), TuliproxError> {
+    pub fn prepare(&mut self, include_dir: Option<&Path>) -> Result<(), TuliproxError> {
         if let Some(templates) = &mut self.templates {
             match prepare_templates(templates) {
                 Ok(tmplts) => {
@@ -237,7 +242,7 @@ impl MappingDefinition {
         }
         for mapping in &mut self.mapping {
             let template_list = self.templates.as_ref();
-            mapping.prepare(template_list)?;
+            mapping.prepare(template_list, include_dir)?;
         }
         Ok(())
     }
@@ -249,8 +254,9 @@ pub struct Mappings {
 }
 
 impl Mappings {
-    pub fn prepare(&mut self) -> Result<(), TuliproxError> {
-        self.mappings.prepare()
+    /// `include_dir` is the directory for the includes of mapper scripts.
+    pub fn prepare(&mut self, include_dir: Option<&Path>) -> Result<(), TuliproxError> {
+        self.mappings.prepare(include_dir)
     }
 
     pub fn get_mapping(&self, mapping_id: &str) -> Option<Mapping> {
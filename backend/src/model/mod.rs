@@ -7,6 +7,12 @@ mod healthcheck;
 mod playlist_categories;
 mod xtream_const;
 mod config;
+mod group_override;
+mod playlist_comparison;
+mod refresh_history;
+mod epg_match_report;
+mod epg_json;
+mod user_activity;
 
 pub use self::playlist::*;
 pub use self::mapping::*;
@@ -17,3 +23,9 @@ pub use self::healthcheck::*;
 pub use self::playlist_categories::*;
 pub use self::xtream_const::*;
 pub use self::config::*;
+pub use self::group_override::*;
+pub use self::playlist_comparison::*;
+pub use self::refresh_history::*;
+pub use self::epg_match_report::*;
+pub use self::epg_json::*;
+pub use self::user_activity::*;
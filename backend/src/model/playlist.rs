@@ -2,11 +2,13 @@ use crate::model::xtream::{xtream_playlistitem_to_document, XtreamMappingOptions
 use crate::model::xtream_const;
 use crate::model::{TVGuide, ProxyUserCredentials, ConfigInput, ConfigTargetOptions};
 use crate::utils::request::extract_extension_from_url;
-use crate::utils::{generate_playlist_uuid, get_provider_id};
+use crate::utils::{generate_playlist_uuid, get_provider_id, normalize_media_title, classify_channel};
 use crate::utils::{get_string_from_serde_value, get_u64_from_serde_value};
 use serde::{Deserialize, Serialize};
 use serde_json::{Map, Value};
 use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::fmt::Write;
 use shared::model::{PlaylistEntry, PlaylistItemType, UUIDType, XtreamCluster};
 // https://de.wikipedia.org/wiki/M3U
 // https://siptv.eu/howto/playlist.html
@@ -31,7 +33,15 @@ impl FetchedPlaylist<'_> {
 }
 
 
+/// Url of the same channel at another input, tried when the stream of the channel fails.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+pub struct ChannelFailover {
+    pub input_name: String,
+    pub url: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[serde(default)]
 pub struct PlaylistItemHeader {
     pub uuid: UUIDType, // calculated
     pub id: String, // provider id
@@ -50,11 +60,17 @@ pub struct PlaylistItemHeader {
     pub epg_channel_id: Option<String>,
     pub xtream_cluster: XtreamCluster,
     pub additional_properties: Option<Value>,
+    // extended m3u attributes like `catchup` or `tvg-shift`, which have no own field
+    #[serde(default)]
+    pub attributes: Option<BTreeMap<String, String>>,
     #[serde(default, skip_serializing, skip_deserializing)]
     pub item_type: PlaylistItemType,
     #[serde(default)]
     pub category_id: u32,
     pub input_name: String,
+    // the urls of the same channel at other inputs, by priority
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failover: Vec<ChannelFailover>,
 }
 
 impl PlaylistItemHeader {
@@ -102,6 +118,40 @@ impl PlaylistItemHeader {
             None => None
         }
     }
+
+    /// The archive days of xtream live channels with `tv_archive`.
+    pub fn get_catchup_days(&self) -> Option<u64> {
+        if self.get_additional_property_as_u64(xtream_const::XC_TAG_TV_ARCHIVE)? == 0 {
+            return None;
+        }
+        self.get_additional_property_as_u64(xtream_const::XC_TAG_TV_ARCHIVE_DURATION).filter(|days| *days > 0)
+    }
+
+    /// The `is_adult` flag of xtream providers.
+    pub fn is_provider_adult(&self) -> bool {
+        self.get_additional_property_as_u64(xtream_const::XC_TAG_IS_ADULT).is_some_and(|value| value > 0)
+    }
+
+    // xtream output reads the property, m3u output the attribute
+    pub fn set_adult(&mut self, adult: bool) {
+        let flag = u8::from(adult);
+        match self.additional_properties.as_mut() {
+            Some(Value::Object(props)) => {
+                props.insert(xtream_const::XC_TAG_IS_ADULT.to_string(), Value::from(flag));
+            }
+            _ if adult => {
+                let mut props = Map::new();
+                props.insert(xtream_const::XC_TAG_IS_ADULT.to_string(), Value::from(flag));
+                self.additional_properties = Some(Value::Object(props));
+            }
+            _ => {}
+        }
+        if adult {
+            self.attributes.get_or_insert_with(BTreeMap::new).insert(xtream_const::XC_TAG_IS_ADULT.to_string(), flag.to_string());
+        } else if let Some(attributes) = self.attributes.as_mut() {
+            attributes.remove(xtream_const::XC_TAG_IS_ADULT);
+        }
+    }
 }
 
 macro_rules! to_m3u_non_empty_fields {
@@ -124,6 +174,36 @@ macro_rules! to_m3u_resource_non_empty_fields {
     };
 }
 
+// Read only fields extracted from the caption by the media title normalization.
+fn get_media_title_field<'a>(header: &PlaylistItemHeader, field: &str) -> Option<Cow<'a, str>> {
+    let caption = if header.title.is_empty() { &header.name } else { &header.title };
+    let media_title = normalize_media_title(caption);
+    match field {
+        "clean_title" => Some(Cow::Owned(media_title.title)),
+        "year" => media_title.year.map(|v| Cow::Owned(v.to_string())),
+        "season" => media_title.season.map(|v| Cow::Owned(v.to_string())),
+        "episode" => media_title.episode.map(|v| Cow::Owned(v.to_string())),
+        "quality" => media_title.quality.map(Cow::Owned),
+        "language" => media_title.language.map(Cow::Owned),
+        _ => None,
+    }
+}
+
+// Read only fields `country` and `language` from the channel classification.
+// The provider attributes `tvg-country` and `tvg-language` take precedence.
+fn get_classification_field<'a>(header: &'a PlaylistItemHeader, field: &str) -> Option<Cow<'a, str>> {
+    let attribute = if field == "country" { "tvg-country" } else { "tvg-language" };
+    if let Some(value) = header.attributes.as_ref().and_then(|attributes| attributes.get(attribute)).filter(|value| !value.is_empty()) {
+        return Some(Cow::Borrowed(value.as_str()));
+    }
+    let caption = if header.title.is_empty() { &header.name } else { &header.title };
+    let classification = classify_channel(&[caption, &header.group], header.epg_channel_id.as_deref());
+    match field {
+        "country" => classification.country.map(Cow::Owned),
+        _ => classification.language.map(Cow::Owned).or_else(|| get_media_title_field(header, field)),
+    }
+}
+
 macro_rules! generate_field_accessor_impl_for_playlist_item_header {
     ($($prop:ident),*;) => {
         impl shared::model::FieldGetAccessor for PlaylistItemHeader {
@@ -137,7 +217,13 @@ macro_rules! generate_field_accessor_impl_for_playlist_item_header {
                     "type" => Some(Cow::Owned(self.item_type.to_string())),
                     "caption" =>  Some(if self.title.is_empty() { Cow::Borrowed(&self.name) } else { Cow::Borrowed(&self.title) }),
                     "epg_channel_id" | "epg_id" => self.epg_channel_id.as_ref().map(|s| Cow::Borrowed(s.as_str())),
-                    _ => None,
+                    "clean_title" | "year" | "season" | "episode" | "quality" => get_media_title_field(self, field.as_str()),
+                    "country" | "language" => get_classification_field(self, field.as_str()),
+                    // extended m3u attributes, then the provider properties of xtream items
+                    _ => match self.attributes.as_ref().and_then(|attributes| attributes.get(field.as_str())) {
+                        Some(value) => Some(Cow::Borrowed(value.as_str())),
+                        None => self.get_additional_property_as_str(field.as_str()).map(Cow::Owned),
+                    },
                 }
             }
          }
@@ -161,7 +247,11 @@ macro_rules! generate_field_accessor_impl_for_playlist_item_header {
                         self.epg_channel_id = Some(value.to_owned());
                         true
                     }
-                    _ => false,
+                    "input" | "type" | "clean_title" | "year" | "season" | "episode" | "quality" | "country" | "language" => false,
+                    _ => {
+                        self.attributes.get_or_insert_with(BTreeMap::new).insert(field, val);
+                        true
+                    }
                 }
             }
         }
@@ -188,6 +278,7 @@ pub struct M3uPlaylistItem {
     pub epg_channel_id: Option<String>,
     pub input_name: String,
     pub item_type: PlaylistItemType,
+    pub attributes: Option<BTreeMap<String, String>>,
     #[serde(skip)]
     pub t_stream_url: String,
     #[serde(skip)]
@@ -195,6 +286,10 @@ pub struct M3uPlaylistItem {
 }
 
 impl M3uPlaylistItem {
+    pub fn is_adult(&self) -> bool {
+        self.attributes.as_ref().and_then(|attributes| attributes.get(xtream_const::XC_TAG_IS_ADULT)).is_some_and(|flag| flag == "1")
+    }
+
     #[allow(clippy::missing_panics_doc)]
     pub fn to_m3u(&self, target_options: Option<&ConfigTargetOptions>, rewrite_urls: bool) -> String {
         let options = target_options.as_ref();
@@ -218,6 +313,12 @@ impl M3uPlaylistItem {
             (time_shift, "timeshift"),
             (rec, "tvg-rec"););
 
+        if let Some(attributes) = self.attributes.as_ref() {
+            for (key, value) in attributes {
+                line = format!("{line} {key}=\"{value}\"");
+            }
+        }
+
         let url = if self.t_stream_url.is_empty() { &self.url } else { &self.t_stream_url };
         format!("{line},{}\n{url}", self.title, )
     }
@@ -305,6 +406,10 @@ impl XtreamPlaylistItem {
         }
         None
     }
+
+    pub fn is_adult(&self) -> bool {
+        self.get_additional_property(xtream_const::XC_TAG_IS_ADULT).is_some_and(|value| get_u64_from_serde_value(&value).is_some_and(|flag| flag > 0))
+    }
 }
 
 impl PlaylistEntry for XtreamPlaylistItem {
@@ -422,11 +527,29 @@ impl PlaylistItem {
             epg_channel_id: header.epg_channel_id.clone(),
             input_name: header.input_name.to_string(),
             item_type: header.item_type,
+            attributes: header.attributes.clone(),
             t_stream_url: header.url.to_string(),
             t_resource_url: None,
         }
     }
 
+    /// Sets the m3u `catchup` attributes of xtream live channels with `tv_archive`, existing attributes are kept.
+    pub fn add_catchup_attributes(&self, m3u: &mut M3uPlaylistItem) {
+        if !matches!(self.header.item_type, PlaylistItemType::Live | PlaylistItemType::LiveHls) {
+            return;
+        }
+        let Some(days) = self.header.get_catchup_days() else { return };
+        let attributes = m3u.attributes.get_or_insert_with(BTreeMap::new);
+        if attributes.contains_key(M3U_ATTRIB_CATCHUP) {
+            return;
+        }
+        attributes.insert(M3U_ATTRIB_CATCHUP.to_string(), "default".to_string());
+        attributes.insert(M3U_ATTRIB_CATCHUP_DAYS.to_string(), days.to_string());
+        if let Some(source) = get_xtream_catchup_source(&self.header.url) {
+            attributes.insert(M3U_ATTRIB_CATCHUP_SOURCE.to_string(), source);
+        }
+    }
+
     pub fn to_xtream(&self) -> XtreamPlaylistItem {
         let header = &self.header;
         let provider_id = header.id.parse::<u32>().unwrap_or_default();
@@ -517,6 +640,60 @@ impl PlaylistEntry for PlaylistItem {
     }
 }
 
+/// Separator of the sub groups in a group title like `Sports / Football / England`.
+pub const GROUP_PATH_SEPARATOR: &str = " / ";
+
+pub const M3U_ATTRIB_CATCHUP: &str = "catchup";
+pub const M3U_ATTRIB_CATCHUP_DAYS: &str = "catchup-days";
+pub const M3U_ATTRIB_CATCHUP_SOURCE: &str = "catchup-source";
+// the placeholders of the catchup-source, they are replaced by the player
+pub const M3U_CATCHUP_SOURCE_TIMESHIFT: &str = "{duration:60}/{Y}-{m}-{d}:{H}-{M}";
+
+/// The timeshift url template of a xtream live url `<base>/live/<username>/<password>/<stream_id>.ts`,
+/// the `/live` prefix is optional.
+fn get_xtream_catchup_source(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url).ok()?;
+    let mut segments: Vec<&str> = parsed.path_segments()?.filter(|segment| !segment.is_empty()).collect();
+    let stream_id = segments.pop()?;
+    let stream_id = stream_id.split_once('.').map_or(stream_id, |(id, _)| id);
+    let password = segments.pop()?;
+    let username = segments.pop()?;
+    if stream_id.parse::<u32>().is_err() {
+        return None;
+    }
+    if segments.last().is_some_and(|segment| *segment == "live") {
+        segments.pop();
+    }
+    let prefix = segments.iter().fold(String::new(), |mut acc, segment| {
+        let _ = write!(acc, "/{segment}");
+        acc
+    });
+    Some(format!("{}{prefix}/timeshift/{username}/{password}/{M3U_CATCHUP_SOURCE_TIMESHIFT}/{stream_id}.ts", parsed.origin().ascii_serialization()))
+}
+
+/// Joins the trimmed, non-empty segments of a group path.
+pub fn join_group_path<I, S>(segments: I) -> String
+where
+    I: IntoIterator<Item=S>,
+    S: AsRef<str>,
+{
+    segments.into_iter()
+        .filter_map(|segment| Some(segment.as_ref().trim().to_string()).filter(|segment| !segment.is_empty()))
+        .collect::<Vec<_>>()
+        .join(GROUP_PATH_SEPARATOR)
+}
+
+/// Normalizes a group title with sub groups separated by `/` like `Sports/Football` into `Sports / Football`.
+pub fn normalize_group_path(title: &str) -> String {
+    let path = join_group_path(title.split('/'));
+    if path.is_empty() { title.to_string() } else { path }
+}
+
+/// Returns the parent of a normalized group path.
+pub fn get_parent_group_path(title: &str) -> Option<&str> {
+    title.rsplit_once(GROUP_PATH_SEPARATOR).map(|(parent, _)| parent)
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PlaylistGroup {
     pub id: u32,
@@ -542,3 +719,41 @@ impl PlaylistGroup {
         self.channels.iter().filter(|&c| filter(c)).count()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{get_xtream_catchup_source, PlaylistItem, PlaylistItemHeader, M3U_ATTRIB_CATCHUP_DAYS, M3U_ATTRIB_CATCHUP_SOURCE};
+    use shared::model::PlaylistItemType;
+
+    #[test]
+    fn test_xtream_catchup_source() {
+        assert_eq!(get_xtream_catchup_source("http://provider.tv:8080/live/user/pass/1234.ts").as_deref(),
+                   Some("http://provider.tv:8080/timeshift/user/pass/{duration:60}/{Y}-{m}-{d}:{H}-{M}/1234.ts"));
+        assert_eq!(get_xtream_catchup_source("http://provider.tv/user/pass/1234").as_deref(),
+                   Some("http://provider.tv/timeshift/user/pass/{duration:60}/{Y}-{m}-{d}:{H}-{M}/1234.ts"));
+        assert_eq!(get_xtream_catchup_source("http://provider.tv/stream.m3u8"), None);
+    }
+
+    #[test]
+    fn test_add_catchup_attributes() {
+        let mut pli = PlaylistItem {
+            header: PlaylistItemHeader {
+                url: "http://provider.tv/live/user/pass/1234.ts".to_string(),
+                item_type: PlaylistItemType::Live,
+                additional_properties: Some(serde_json::json!({"tv_archive": 1, "tv_archive_duration": "5"})),
+                ..Default::default()
+            },
+        };
+        let mut m3u = pli.to_m3u();
+        pli.add_catchup_attributes(&mut m3u);
+        let attributes = m3u.attributes.unwrap();
+        assert_eq!(attributes.get("catchup").map(String::as_str), Some("default"));
+        assert_eq!(attributes.get(M3U_ATTRIB_CATCHUP_DAYS).map(String::as_str), Some("5"));
+        assert!(attributes.contains_key(M3U_ATTRIB_CATCHUP_SOURCE));
+
+        pli.header.additional_properties = Some(serde_json::json!({"tv_archive": 0, "tv_archive_duration": 5}));
+        let mut m3u = pli.to_m3u();
+        pli.add_catchup_attributes(&mut m3u);
+        assert!(m3u.attributes.is_none_or(|attributes| attributes.is_empty()));
+    }
+}
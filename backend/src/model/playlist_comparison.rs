@@ -0,0 +1,115 @@
+use crate::model::PlaylistGroup;
+use crate::utils::normalize_title_for_matching;
+use shared::model::XtreamCluster;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct PlaylistClusterComparison {
+    pub left_count: usize,
+    pub right_count: usize,
+    pub common_count: usize,
+    /// Share of the left catalog which is also available in the right catalog.
+    pub left_overlap_percent: f64,
+    /// Share of the right catalog which is also available in the left catalog.
+    pub right_overlap_percent: f64,
+    pub left_exclusive: Vec<String>,
+    pub right_exclusive: Vec<String>,
+}
+
+/// Content comparison report of two inputs.
+/// Entries are compared by their normalized name, duplicates inside one input are counted once.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct PlaylistComparison {
+    pub left: String,
+    pub right: String,
+    pub live: PlaylistClusterComparison,
+    pub vod: PlaylistClusterComparison,
+    pub series: PlaylistClusterComparison,
+}
+
+fn collect_names(playlist: &[PlaylistGroup], cluster: XtreamCluster) -> BTreeMap<String, String> {
+    let mut names = BTreeMap::new();
+    for group in playlist.iter().filter(|group| group.xtream_cluster == cluster) {
+        for channel in &group.channels {
+            let name = if channel.header.name.is_empty() { &channel.header.title } else { &channel.header.name };
+            let normalized = normalize_title_for_matching(name);
+            if !normalized.is_empty() {
+                names.entry(normalized).or_insert_with(|| name.clone());
+            }
+        }
+    }
+    names
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn percent(count: usize, total: usize) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        ((count as f64 * 10_000.0) / total as f64).round() / 100.0
+    }
+}
+
+impl PlaylistClusterComparison {
+    fn new(left: &BTreeMap<String, String>, right: &BTreeMap<String, String>) -> Self {
+        let left_exclusive: Vec<String> = left.iter().filter(|(key, _)| !right.contains_key(*key)).map(|(_, name)| name.clone()).collect();
+        let right_exclusive: Vec<String> = right.iter().filter(|(key, _)| !left.contains_key(*key)).map(|(_, name)| name.clone()).collect();
+        let common_count = left.len() - left_exclusive.len();
+        Self {
+            left_count: left.len(),
+            right_count: right.len(),
+            common_count,
+            left_overlap_percent: percent(common_count, left.len()),
+            right_overlap_percent: percent(common_count, right.len()),
+            left_exclusive,
+            right_exclusive,
+        }
+    }
+}
+
+impl PlaylistComparison {
+    pub fn new(left_name: &str, left: &[PlaylistGroup], right_name: &str, right: &[PlaylistGroup]) -> Self {
+        let compare = |cluster| PlaylistClusterComparison::new(&collect_names(left, cluster), &collect_names(right, cluster));
+        Self {
+            left: left_name.to_string(),
+            right: right_name.to_string(),
+            live: compare(XtreamCluster::Live),
+            vod: compare(XtreamCluster::Video),
+            series: compare(XtreamCluster::Series),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{PlaylistComparison, PlaylistGroup, PlaylistItem, PlaylistItemHeader};
+    use shared::model::XtreamCluster;
+
+    fn create_group(cluster: XtreamCluster, names: &[&str]) -> PlaylistGroup {
+        PlaylistGroup {
+            id: 1,
+            title: "group".to_string(),
+            channels: names.iter().map(|name| PlaylistItem {
+                header: PlaylistItemHeader { name: (*name).to_string(), ..Default::default() },
+            }).collect(),
+            xtream_cluster: cluster,
+        }
+    }
+
+    #[test]
+    fn test_playlist_comparison() {
+        let left = vec![create_group(XtreamCluster::Live, &["DE: ARD HD", "ZDF", "Sky Sport 1", "zdf"])];
+        let right = vec![create_group(XtreamCluster::Live, &["de ard hd", "ZDF", "Eurosport"]),
+                         create_group(XtreamCluster::Video, &["Movie (2020)"])];
+        let report = PlaylistComparison::new("left", &left, "right", &right);
+        assert_eq!(report.live.left_count, 3);
+        assert_eq!(report.live.right_count, 3);
+        assert_eq!(report.live.common_count, 2);
+        assert_eq!(report.live.left_exclusive, vec!["Sky Sport 1".to_string()]);
+        assert_eq!(report.live.right_exclusive, vec!["Eurosport".to_string()]);
+        assert!((report.live.left_overlap_percent - 66.67).abs() < f64::EPSILON);
+        assert_eq!(report.vod.left_count, 0);
+        assert_eq!(report.vod.right_exclusive.len(), 1);
+        assert!(report.vod.right_overlap_percent.abs() < f64::EPSILON);
+    }
+}
@@ -0,0 +1,194 @@
+use shared::model::XtreamCluster;
+
+pub const DEFAULT_REFRESH_HISTORY_SIZE: usize = 50;
+
+/// The summary of a playlist refresh of a target.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct RefreshResult {
+    // unix timestamp in seconds
+    pub ts: i64,
+    pub success: bool,
+    // duration in seconds
+    pub duration: u64,
+    pub groups: usize,
+    pub live: usize,
+    pub vod: usize,
+    pub series: usize,
+    pub errors: usize,
+}
+
+impl RefreshResult {
+    pub fn add_group(&mut self, cluster: XtreamCluster, channel_count: usize) {
+        self.groups += 1;
+        match cluster {
+            XtreamCluster::Live => self.live += channel_count,
+            XtreamCluster::Video => self.vod += channel_count,
+            XtreamCluster::Series => self.series += channel_count,
+        }
+    }
+
+    pub const fn channels(&self) -> usize {
+        self.live + self.vod + self.series
+    }
+}
+
+/// The last refresh results of a target, the oldest entry comes first.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(transparent)]
+pub struct RefreshHistory {
+    pub entries: Vec<RefreshResult>,
+}
+
+impl RefreshHistory {
+    pub fn push(&mut self, result: RefreshResult, max_entries: usize) {
+        self.entries.push(result);
+        if self.entries.len() > max_entries {
+            self.entries.drain(..self.entries.len() - max_entries);
+        }
+    }
+
+    /// Returns the entries after `since` (unix timestamp in seconds), at most the last `limit` entries.
+    pub fn query(&self, since: Option<i64>, limit: Option<usize>) -> Vec<RefreshResult> {
+        let entries: Vec<RefreshResult> = self.entries.iter().filter(|entry| since.is_none_or(|ts| entry.ts > ts)).cloned().collect();
+        match limit {
+            Some(limit) if limit < entries.len() => entries[entries.len() - limit..].to_vec(),
+            _ => entries,
+        }
+    }
+}
+
+/// The freshness of the playlist a target serves. While inputs of its source fail,
+/// the target is stale and keeps serving the last successfully processed playlist.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TargetFreshness {
+    // unix timestamp in seconds of the last refresh with all inputs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_success: Option<i64>,
+    // unix timestamp in seconds of the first failed refresh after the last success
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale_since: Option<i64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failed_inputs: Vec<String>,
+    // the staleness notification was sent
+    #[serde(default)]
+    pub notified: bool,
+}
+
+impl TargetFreshness {
+    pub const fn is_stale(&self) -> bool {
+        self.stale_since.is_some()
+    }
+
+    pub fn set_success(&mut self, ts: i64) {
+        *self = Self { last_success: Some(ts), ..Self::default() };
+    }
+
+    /// Marks the target stale, returns `true` when the staleness exceeds `notify_after_secs` the first time.
+    pub fn set_stale(&mut self, ts: i64, failed_inputs: Vec<String>, notify_after_secs: Option<i64>) -> bool {
+        let stale_since = *self.stale_since.get_or_insert(ts);
+        self.failed_inputs = failed_inputs;
+        let notify = !self.notified && notify_after_secs.is_some_and(|secs| ts - stale_since >= secs);
+        self.notified |= notify;
+        notify
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct CountTrend {
+    pub first: usize,
+    pub last: usize,
+    pub min: usize,
+    pub max: usize,
+    // the difference between the last and the first count
+    pub change: i64,
+    // the change in percent of the first count, 0 if the first count is 0
+    pub change_percent: f64,
+}
+
+impl CountTrend {
+    #[allow(clippy::cast_precision_loss, clippy::cast_possible_wrap)]
+    fn new(counts: &[usize]) -> Option<Self> {
+        let (first, last) = (*counts.first()?, *counts.last()?);
+        let change = last as i64 - first as i64;
+        Some(Self {
+            first,
+            last,
+            min: counts.iter().copied().min().unwrap_or_default(),
+            max: counts.iter().copied().max().unwrap_or_default(),
+            change,
+            change_percent: if first == 0 { 0.0 } else { (change as f64 * 10_000.0 / first as f64).round() / 100.0 },
+        })
+    }
+}
+
+/// The channel count trend of the successful refreshes.
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct RefreshTrend {
+    pub runs: usize,
+    pub failures: usize,
+    pub live: CountTrend,
+    pub vod: CountTrend,
+    pub series: CountTrend,
+    pub channels: CountTrend,
+}
+
+impl RefreshTrend {
+    pub fn new(entries: &[RefreshResult]) -> Option<Self> {
+        let successful: Vec<&RefreshResult> = entries.iter().filter(|entry| entry.success).collect();
+        let counts = |count: fn(&RefreshResult) -> usize| successful.iter().map(|entry| count(entry)).collect::<Vec<usize>>();
+        Some(Self {
+            runs: entries.len(),
+            failures: entries.len() - successful.len(),
+            live: CountTrend::new(&counts(|entry| entry.live))?,
+            vod: CountTrend::new(&counts(|entry| entry.vod))?,
+            series: CountTrend::new(&counts(|entry| entry.series))?,
+            channels: CountTrend::new(&counts(RefreshResult::channels))?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{RefreshHistory, RefreshResult, RefreshTrend, TargetFreshness};
+
+    fn result(ts: i64, success: bool, live: usize, vod: usize) -> RefreshResult {
+        RefreshResult { ts, success, live, vod, ..RefreshResult::default() }
+    }
+
+    #[test]
+    fn test_refresh_history() {
+        let mut history = RefreshHistory::default();
+        for (ts, success, live, vod) in [(1, true, 100, 40), (2, true, 110, 40), (3, false, 0, 0), (4, true, 80, 50)] {
+            history.push(result(ts, success, live, vod), 3);
+        }
+        assert_eq!(history.entries.len(), 3);
+        assert_eq!(history.entries[0].ts, 2);
+        assert_eq!(history.query(Some(2), None).len(), 2);
+        assert_eq!(history.query(None, Some(1))[0].ts, 4);
+
+        let trend = RefreshTrend::new(&history.entries).unwrap();
+        assert_eq!(trend.runs, 3);
+        assert_eq!(trend.failures, 1);
+        assert_eq!((trend.live.first, trend.live.last, trend.live.min, trend.live.max), (110, 80, 80, 110));
+        assert_eq!(trend.live.change, -30);
+        assert!((trend.live.change_percent + 27.27).abs() < f64::EPSILON);
+        assert_eq!(trend.channels.change, -20);
+        assert!(RefreshTrend::new(&[result(1, false, 0, 0)]).is_none());
+    }
+
+    #[test]
+    fn test_target_freshness() {
+        let mut freshness = TargetFreshness::default();
+        assert!(!freshness.set_stale(100, vec!["provider".to_string()], Some(60)));
+        assert!(!freshness.set_stale(150, vec!["provider".to_string()], Some(60)));
+        // the notification is sent once
+        assert!(freshness.set_stale(160, vec!["provider".to_string()], Some(60)));
+        assert!(!freshness.set_stale(300, vec!["provider".to_string()], Some(60)));
+        assert_eq!((freshness.stale_since, freshness.last_success), (Some(100), None));
+        freshness.set_success(400);
+        assert!(!freshness.is_stale());
+        assert_eq!(freshness, TargetFreshness { last_success: Some(400), ..TargetFreshness::default() });
+        assert!(!freshness.set_stale(500, vec![], None));
+        assert!(freshness.is_stale());
+    }
+}
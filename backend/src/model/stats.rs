@@ -34,6 +34,9 @@ pub struct InputStats {
     pub name: String,
     #[serde(rename = "type")]
     pub input_type: InputType,
+    // the alias the playlist was fetched over when the input failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub alias: Option<String>,
     #[serde(rename = "errors")]
     pub error_count: usize,
     #[serde(rename = "raw")]
@@ -56,14 +59,20 @@ pub struct TargetStats {
     #[serde(rename = "target")]
     pub name: String,
     pub success: bool,
+    // the inputs failed and the last playlist is kept
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub stale: bool,
 }
 
 impl TargetStats {
     pub fn success(name: &str) -> Self {
-        Self  {name: name.to_string(), success: true}
+        Self  {name: name.to_string(), success: true, stale: false}
     }
     pub fn failure(name: &str) -> Self {
-        Self  {name: name.to_string(), success: false}
+        Self  {name: name.to_string(), success: false, stale: false}
+    }
+    pub fn stale(name: &str) -> Self {
+        Self  {name: name.to_string(), success: false, stale: true}
     }
 }
 
@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+
+// user agents not seen within this window are removed, 30 days
+pub const USER_ACTIVITY_WINDOW_SECS: u64 = 2_592_000;
+pub const DEFAULT_DORMANT_DAYS: u64 = 30;
+const USER_AGENT_LIMIT: usize = 20;
+
+/// A user agent a user was seen with.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct UserAgentActivity {
+    pub user_agent: String,
+    pub first_seen: u64,
+    pub last_seen: u64,
+}
+
+/// The last activity of a user, it is kept when the user disconnects.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct UserActivity {
+    // unix timestamp in seconds
+    pub last_seen: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_ip: Option<String>,
+    // distinct user agents seen within the rolling window, most recent first
+    #[serde(default)]
+    pub user_agents: Vec<UserAgentActivity>,
+    // unix timestamp of the inactivity notification of the user cleanup, reset by new activity
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cleanup_notified: Option<u64>,
+}
+
+impl UserActivity {
+    pub fn update(&mut self, ts: u64, client_ip: &str, user_agent: Option<&str>) {
+        self.last_seen = ts;
+        self.last_ip = Some(client_ip.to_string());
+        self.cleanup_notified = None;
+        if let Some(user_agent) = user_agent.map(str::trim).filter(|ua| !ua.is_empty()) {
+            if let Some(entry) = self.user_agents.iter_mut().find(|entry| entry.user_agent == user_agent) {
+                entry.last_seen = ts;
+            } else {
+                self.user_agents.push(UserAgentActivity { user_agent: user_agent.to_string(), first_seen: ts, last_seen: ts });
+            }
+        }
+        self.prune(ts);
+    }
+
+    /// Removes the user agents outside the rolling window, at most `USER_AGENT_LIMIT` user agents are kept.
+    pub fn prune(&mut self, now: u64) {
+        self.user_agents.retain(|entry| now.saturating_sub(entry.last_seen) < USER_ACTIVITY_WINDOW_SECS);
+        self.user_agents.sort_by_key(|entry| std::cmp::Reverse(entry.last_seen));
+        self.user_agents.truncate(USER_AGENT_LIMIT);
+    }
+
+    pub const fn is_dormant(&self, now: u64, dormant_secs: u64) -> bool {
+        now.saturating_sub(self.last_seen) >= dormant_secs
+    }
+}
+
+/// The activity of all users, keyed by username.
+pub type UserActivityMap = HashMap<String, UserActivity>;
+
+/// The activity of a configured user for the web ui, users without activity have never been seen.
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UserActivityInfo {
+    pub username: String,
+    pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub activity: Option<UserActivity>,
+    pub dormant: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UserActivity, USER_ACTIVITY_WINDOW_SECS};
+
+    #[test]
+    fn test_user_activity_update() {
+        let mut activity = UserActivity::default();
+        activity.update(100, "10.0.0.1", Some("VLC/3.0"));
+        activity.update(200, "10.0.0.2", Some("Kodi"));
+        activity.update(300, "10.0.0.2", Some("VLC/3.0"));
+        activity.update(400, "10.0.0.3", None);
+        assert_eq!(activity.last_seen, 400);
+        assert_eq!(activity.last_ip.as_deref(), Some("10.0.0.3"));
+        assert_eq!(activity.user_agents.len(), 2);
+        assert_eq!(activity.user_agents[0].user_agent, "VLC/3.0");
+        assert_eq!(activity.user_agents[0].first_seen, 100);
+        assert_eq!(activity.user_agents[0].last_seen, 300);
+
+        activity.update(300 + USER_ACTIVITY_WINDOW_SECS, "10.0.0.3", Some("Tivimate"));
+        let agents: Vec<&str> = activity.user_agents.iter().map(|entry| entry.user_agent.as_str()).collect();
+        assert_eq!(agents, vec!["Tivimate"]);
+        assert!(!activity.is_dormant(400 + USER_ACTIVITY_WINDOW_SECS, 86_400));
+        assert!(activity.is_dormant(300 + 2 * USER_ACTIVITY_WINDOW_SECS, 86_400));
+    }
+}
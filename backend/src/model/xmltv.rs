@@ -1,6 +1,6 @@
 use quick_xml::events::{BytesEnd, BytesStart, BytesText, Event};
 use quick_xml::{Error, Writer};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::path::PathBuf;
 use crate::model::xmltv::XmlTagIcon::Undefined;
 
@@ -9,8 +9,14 @@ pub const EPG_TAG_PROGRAMME: &str = "programme";
 pub const EPG_TAG_CHANNEL: &str = "channel";
 pub const EPG_ATTRIB_ID: &str = "id";
 pub const EPG_ATTRIB_CHANNEL: &str = "channel";
+pub const EPG_ATTRIB_START: &str = "start";
+pub const EPG_ATTRIB_STOP: &str = "stop";
 pub const EPG_TAG_DISPLAY_NAME: &str = "display-name";
 pub const EPG_TAG_ICON: &str = "icon";
+pub const EPG_TAG_TITLE: &str = "title";
+pub const EPG_TAG_SUB_TITLE: &str = "sub-title";
+pub const EPG_TAG_CATEGORY: &str = "category";
+pub const EPG_TAG_DESC: &str = "desc";
 
 // https://github.com/XMLTV/xmltv/blob/master/xmltv.dtd
 
@@ -95,6 +101,94 @@ impl Epg {
     }
 }
 
+/// Merged view of the epg sources of a target, which is written without copying the tags.
+/// A channel is taken from the source with the highest priority (lowest value),
+/// the programmes of a channel only from the sources with this priority.
+#[derive(Default)]
+pub struct MergedEpg<'a> {
+    attributes: Option<&'a HashMap<String, String>>,
+    guides: Vec<&'a Epg>,
+    channel_priority: HashMap<&'a str, i16>,
+    generated: Vec<Epg>,
+}
+
+impl<'a> MergedEpg<'a> {
+    pub fn new(tv_guides: &'a [Epg]) -> Option<Self> {
+        if tv_guides.is_empty() {
+            return None;
+        }
+        let mut guides: Vec<&Epg> = tv_guides.iter().collect();
+        // stable sort, sources with the same priority keep their order
+        guides.sort_by_key(|guide| guide.priority);
+        let mut channel_priority = HashMap::new();
+        for guide in &guides {
+            for tag in guide.children.iter().filter(|tag| tag.name == EPG_TAG_CHANNEL) {
+                if let Some(id) = tag.get_attribute_value(EPG_ATTRIB_ID) {
+                    channel_priority.entry(id.as_str()).or_insert(guide.priority);
+                }
+            }
+        }
+        Some(Self {
+            attributes: tv_guides.first().and_then(|guide| guide.attributes.as_ref()),
+            guides,
+            channel_priority,
+            generated: Vec::new(),
+        })
+    }
+
+    /// Generated channels and programmes like schedules and placeholders, written after the tags of the sources.
+    pub fn add_generated(&mut self, epg: Epg) {
+        self.generated.push(epg);
+    }
+
+    fn is_selected(&self, guide: &Epg, channel_id: Option<&String>) -> bool {
+        channel_id.and_then(|id| self.channel_priority.get(id.as_str())).is_some_and(|priority| *priority == guide.priority)
+    }
+
+    pub fn channels(&self) -> impl Iterator<Item = &XmlTag> {
+        let mut written = HashSet::new();
+        self.guides.iter()
+            .flat_map(|guide| guide.children.iter()
+                .filter(|tag| tag.name == EPG_TAG_CHANNEL && self.is_selected(guide, tag.get_attribute_value(EPG_ATTRIB_ID))))
+            .filter(move |tag| tag.get_attribute_value(EPG_ATTRIB_ID).is_some_and(|id| written.insert(id.as_str())))
+            .chain(self.generated.iter().flat_map(|epg| epg.children.iter().filter(|tag| tag.name == EPG_TAG_CHANNEL)))
+    }
+
+    pub fn programmes(&self) -> impl Iterator<Item = &XmlTag> {
+        self.guides.iter()
+            .flat_map(|guide| guide.children.iter()
+                .filter(|tag| tag.name == EPG_TAG_PROGRAMME && self.is_selected(guide, tag.get_attribute_value(EPG_ATTRIB_CHANNEL))))
+            .chain(self.generated.iter().flat_map(|epg| epg.children.iter().filter(|tag| tag.name == EPG_TAG_PROGRAMME)))
+    }
+
+    /// The channel icons of the sources with `logo_override`, served through the resource cache.
+    pub fn override_icons(&self) -> impl Iterator<Item = &String> {
+        self.guides.iter()
+            .filter(|guide| guide.logo_override)
+            .flat_map(|guide| guide.children.iter()
+                .filter(|tag| tag.name == EPG_TAG_CHANNEL && self.is_selected(guide, tag.get_attribute_value(EPG_ATTRIB_ID))))
+            .filter_map(|tag| match &tag.icon {
+                XmlTagIcon::Src(src) => Some(src),
+                _ => None,
+            })
+    }
+
+    /// Writes all channels followed by all programmes, the writer should be buffered.
+    pub fn write_to<W: std::io::Write>(&self, writer: &mut Writer<W>) -> Result<(), quick_xml::Error> {
+        let mut elem = BytesStart::new(EPG_TAG_TV);
+        if let Some(attribs) = self.attributes {
+            for (k, v) in attribs {
+                elem.push_attribute((k.as_str(), v.as_str()));
+            }
+        }
+        writer.write_event(Event::Start(elem))?;
+        for tag in self.channels().chain(self.programmes()) {
+            tag.write_to(writer)?;
+        }
+        Ok(writer.write_event(Event::End(BytesEnd::new(EPG_TAG_TV)))?)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct PersistedEpgSource {
     pub file_path: PathBuf,
@@ -15,10 +15,17 @@ pub const XC_ACTION_GET_ACCOUNT_INFO: &str = "get_account_info";
 pub const XC_ACTION_GET_EPG: &str = "get_epg";
 pub const XC_ACTION_GET_SHORT_EPG: &str = "get_short_epg";
 pub const XC_ACTION_GET_CATCHUP_TABLE: &str = "get_simple_data_table";
+pub const XC_ACTION_SET_PLAYBACK_POSITION: &str = "set_playback_position";
+pub const XC_ACTION_REMOVE_PLAYBACK_POSITION: &str = "remove_playback_position";
+pub const XC_ACTION_GET_PLAYBACK_POSITIONS: &str = "get_playback_positions";
 pub const XC_TAG_ID: &str = "id";
 pub const XC_TAG_CATEGORY_ID: &str = "category_id";
 pub const XC_TAG_STREAM_ID: &str = "stream_id";
 pub const XC_TAG_EPG_LISTINGS: &str = "epg_listings";
+pub const XC_TAG_EPG_START: &str = "start";
+pub const XC_TAG_EPG_END: &str = "end";
+pub const XC_TAG_EPG_START_TIMESTAMP: &str = "start_timestamp";
+pub const XC_TAG_EPG_STOP_TIMESTAMP: &str = "stop_timestamp";
 pub const XC_INFO_RESOURCE_PREFIX: &str = "nfo_";
 pub const XC_INFO_RESOURCE_PREFIX_EPISODE: &str = "nfo_ep_";
 pub const XC_SEASON_RESOURCE_PREFIX: &str = "ssn_";
@@ -26,6 +33,13 @@ pub const XC_PROP_BACKDROP_PATH: &str = "backdrop_path";
 pub const XC_PROP_COVER: &str = "cover";
 pub const XC_TAG_CATEGORY_IDS: &str = "category_ids";
 pub const XC_TAG_CATEGORY_NAME: &str = "category_name";
+pub const XC_TAG_CATEGORY_ICON: &str = "category_icon";
+pub const XC_TAG_IS_ADULT: &str = "is_adult";
+pub const XC_TAG_TV_ARCHIVE: &str = "tv_archive";
+pub const XC_TAG_TV_ARCHIVE_DURATION: &str = "tv_archive_duration";
+pub const XC_TAG_PLAYBACK_POSITION: &str = "playback_position";
+pub const XC_CONTINUE_WATCHING_CATEGORY_ID: u32 = 999_999;
+pub const XC_CONTINUE_WATCHING_CATEGORY_NAME: &str = "Continue Watching";
 pub const XC_TAG_DIRECT_SOURCE: &str = "direct_source";
 pub const XC_TAG_PARENT_ID: &str = "parent_id";
 pub const XC_TAG_MOVIE_DATA: &str = "movie_data";
@@ -15,6 +15,8 @@ macro_rules! include_modules {
         pub mod utils;
         pub mod tools;
         pub mod foundation;
+        #[cfg(feature = "mock-provider")]
+        pub mod mock;
     }
 }
 
@@ -1,4 +1,5 @@
 use std::borrow::BorrowMut;
+use std::collections::BTreeMap;
 use shared::model::{PlaylistItemType, XtreamCluster};
 use crate::model::{Config, ConfigInput};
 use crate::model::{PlaylistGroup, PlaylistItem, PlaylistItemHeader};
@@ -98,12 +99,12 @@ fn create_empty_playlistitem_header(input_name: &str, url: &str) -> PlaylistItem
 }
 
 macro_rules! process_header_fields {
-    ($header:expr, $token:expr, $(($prop:ident, $field:expr)),*; $val:expr) => {
+    ($header:expr, $token:expr, $(($prop:ident, $field:expr)),*; $val:expr; $other:expr) => {
         match $token {
             $(
                $field => $header.$prop = $val,
              )*
-            _ => {}
+            _ => $other,
         }
     };
 }
@@ -129,7 +130,8 @@ fn process_header(input_name: &str, video_suffixes: &[&str], content: &str, url:
                 let token = token_till(&mut stack, &mut it, '=', true);
                 if let Some(t) = token {
                     let value = token_value(&mut stack, &mut it);
-                    process_header_fields!(plih, t.to_lowercase().as_str(),
+                    let attribute = t.to_lowercase();
+                    process_header_fields!(plih, attribute.as_str(),
                         (id, "tvg-id"),
                         (group, "group-title"),
                         (name, "tvg-name"),
@@ -139,7 +141,11 @@ fn process_header(input_name: &str, video_suffixes: &[&str], content: &str, url:
                         (logo, "tvg-logo"),
                         (logo_small, "tvg-logo-small"),
                         (time_shift, "timeshift"),
-                        (rec, "tvg-rec"); value);
+                        (rec, "tvg-rec"); value;
+                        // extended attributes are kept and written to the target playlist
+                        {
+                            plih.attributes.get_or_insert_with(BTreeMap::new).insert(attribute.clone(), value);
+                        });
                 }
             }
             c = it.next();
@@ -254,6 +260,7 @@ where
 
 #[cfg(test)]
 mod test {
+    use crate::model::PlaylistItem;
     use crate::processing::parser::m3u::process_header;
 
     #[test]
@@ -285,4 +292,17 @@ mod test {
         assert_eq!(pli.chno, "7");
         assert_eq!(pli.group, "Sydney");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_process_header_attributes() {
+        let line = r#"#EXTINF:-1 tvg-id="seven" tvg-shift="-1" Catchup="shift" catchup-days="7" timeshift="2" group-title="Sydney",Seven"#;
+        let header = process_header("hello", &[], line, "http://hello.de/hello.ts");
+        assert_eq!(header.time_shift, "2");
+        let attributes = header.attributes.as_ref().unwrap();
+        assert_eq!(attributes.len(), 3);
+        assert_eq!(attributes.get("tvg-shift").map(String::as_str), Some("-1"));
+        assert_eq!(attributes.get("catchup").map(String::as_str), Some("shift"));
+        let m3u = PlaylistItem { header }.to_m3u().to_m3u(None, false);
+        assert!(m3u.contains(r#" timeshift="2" catchup="shift" catchup-days="7" tvg-shift="-1",Seven"#));
+    }
+}
@@ -1,19 +1,20 @@
-use crate::model::{Epg, TVGuide, XmlTag, XmlTagIcon, EPG_ATTRIB_CHANNEL, EPG_ATTRIB_ID, EPG_TAG_CHANNEL, EPG_TAG_DISPLAY_NAME, EPG_TAG_ICON, EPG_TAG_PROGRAMME, EPG_TAG_TV};
-use crate::model::{EpgNamePrefix, EpgSmartMatchConfig, PersistedEpgSource};
+use crate::model::{Epg, MergedEpg, TVGuide, XmlTag, XmlTagIcon, EPG_ATTRIB_CHANNEL, EPG_ATTRIB_ID, EPG_ATTRIB_START, EPG_ATTRIB_STOP, EPG_TAG_CHANNEL, EPG_TAG_CATEGORY, EPG_TAG_DESC, EPG_TAG_DISPLAY_NAME, EPG_TAG_ICON, EPG_TAG_SUB_TITLE, EPG_TAG_TITLE, EPG_TAG_PROGRAMME, EPG_TAG_TV};
+use crate::model::{EpgMatchKind, EpgNamePrefix, EpgPlaceholder, EpgSchedulesConfig, EpgSmartMatchConfig, EpgTimeWindow, PersistedEpgSource, PlaylistGroup};
 use crate::processing::processor::epg::EpgIdCache;
 use crate::utils::compressed_file_reader::CompressedFileReader;
+use crate::utils::{format_xmltv_time, parse_xmltv_time, time_correct};
+use chrono::{DateTime, TimeDelta, Utc};
 use shared::utils::CONSTANTS;
 use deunicode::deunicode;
+use log::debug;
+use shared::model::XtreamCluster;
 use quick_xml::events::{BytesStart, BytesText, Event};
 use quick_xml::Reader;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use std::borrow::Cow;
 use std::cmp::min;
-use std::collections::hash_map::Entry;
-use std::collections::HashMap;
-use std::mem;
-use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{Arc, Mutex, RwLock};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
 
 /// Splits a string at the first delimiter if the prefix matches a known country code.
 ///
@@ -91,6 +92,41 @@ pub fn normalize_channel_name(name: &str, normalize_config: &EpgSmartMatchConfig
 }
 
 
+const EPG_ATTRIB_LANG: &str = "lang";
+
+// the position of the display name language in the preferred languages, `de-AT` matches `de`
+fn display_name_lang_rank(tag: &XmlTag, preferred_languages: &[String]) -> Option<usize> {
+    let lang = tag.get_attribute_value(EPG_ATTRIB_LANG)?.trim().to_lowercase();
+    let primary = lang.split(['-', '_']).next().unwrap_or_default();
+    preferred_languages.iter().position(|preferred| *preferred == lang || preferred == primary)
+}
+
+/// Keeps only the display names of the preferred languages, ordered by preference.
+/// Channels without a display name in a preferred language keep all their display names.
+pub fn select_preferred_display_names(children: &mut Vec<XmlTag>, preferred_languages: &[String]) {
+    if preferred_languages.is_empty() {
+        return;
+    }
+    let ranked: Vec<Option<usize>> = children.iter()
+        .map(|child| if child.name == EPG_TAG_DISPLAY_NAME { display_name_lang_rank(child, preferred_languages) } else { None })
+        .collect();
+    if ranked.iter().all(Option::is_none) {
+        return;
+    }
+    let mut display_names = Vec::new();
+    let mut others = Vec::new();
+    for (child, rank) in children.drain(..).zip(ranked) {
+        if child.name != EPG_TAG_DISPLAY_NAME {
+            others.push(child);
+        } else if let Some(rank) = rank {
+            display_names.push((rank, child));
+        }
+    }
+    display_names.sort_by_key(|(rank, _)| *rank);
+    children.extend(display_names.into_iter().map(|(_, child)| child));
+    children.extend(others);
+}
+
 impl TVGuide {
     pub fn merge(mut epgs: Vec<Epg>) -> Option<Epg> {
         if let Some(first_epg) = epgs.get_mut(0) {
@@ -109,6 +145,9 @@ impl TVGuide {
 
     fn prepare_tag(id_cache: &mut EpgIdCache, tag: &mut XmlTag, smart_match: bool) {
         if let Some(children) = &mut tag.children {
+            if smart_match {
+                select_preferred_display_names(children, &id_cache.smart_match_config.t_preferred_languages);
+            }
             for child in children {
                 match child.name.as_str() {
                     EPG_TAG_DISPLAY_NAME => {
@@ -140,75 +179,72 @@ impl TVGuide {
             .as_ref()
             .is_some_and(|ids| id_cache.match_with_normalized(epg_id, ids));
         if !matched && fuzzy_matching {
-            let (fuzzy_matched, matched_normalized_name) = Self::find_best_fuzzy_match(id_cache, tag);
-            if fuzzy_matched {
-                let key = matched_normalized_name.unwrap();
+            if let Some((key, score)) = Self::find_best_fuzzy_match(id_cache, tag) {
                 let id = epg_id.to_string();
-                id_cache.normalized.entry(key).and_modify(|entry| {
+                if let Some(entry) = id_cache.normalized.get_mut(&key) {
                     entry.replace(id.clone());
                     id_cache.channel_epg_id.insert(Cow::Owned(id));
+                    id_cache.match_kinds.insert(key, (EpgMatchKind::Fuzzy, Some(score)));
                     matched = true;
-                });
+                }
             }
         }
         matched
     }
 
-    /// Finds the best fuzzy match for a channel's normalized EPG ID using phonetic encoding and Jaro-Winkler similarity.
+    /// Finds the best fuzzy match for a channel's normalized EPG ID using the configured phonetic encoding and similarity metric.
     ///
     /// Iterates over the tag's normalized EPG IDs, computes their phonetic codes, and searches for candidates in the phonetics map.
-    /// For each candidate, calculates the Jaro-Winkler similarity score and tracks the best match above the configured threshold.
-    /// Returns a tuple indicating whether a suitable match was found and the matched normalized EPG ID if available.
+    /// For each candidate, calculates the similarity score and tracks the best match above the configured threshold.
+    /// The search stops early when a candidate exceeds the best match threshold.
     ///
     /// # Returns
     ///
-    /// A tuple where the first element is `true` if a match above the threshold was found, and the second element is the matched normalized EPG ID.
+    /// The matched normalized EPG ID and its score in percent, if a match above the threshold was found.
     ///
     /// # Examples
     ///
     /// ```
-    /// let (found, matched) = find_best_fuzzy_match(&mut id_cache, &tag);
-    /// if found {
-    ///     println!("Best match: {:?}", matched);
+    /// if let Some((matched, score)) = find_best_fuzzy_match(&mut id_cache, &tag) {
+    ///     println!("Best match: {matched} {score}%");
     /// }
     /// ```
-    fn find_best_fuzzy_match(id_cache: &mut EpgIdCache, tag: &XmlTag) -> (bool, Option<String>) {
-        let early_exit_flag = Arc::new(AtomicBool::new(false));
+    fn find_best_fuzzy_match(id_cache: &mut EpgIdCache, tag: &XmlTag) -> Option<(String, u16)> {
         let data: Mutex<(u16, Option<Cow<str>>)> = Mutex::new((0, None));
 
         let match_threshold = id_cache.smart_match_config.match_threshold;
         let best_match_threshold = id_cache.smart_match_config.best_match_threshold;
+        let similarity_metric = id_cache.smart_match_config.similarity_metric;
 
         if let Some(normalized_epg_ids) = tag.normalized_epg_ids.as_ref() {
             for tag_normalized in normalized_epg_ids {
                 let tag_code = id_cache.phonetic(tag_normalized);
                 if let Some(normalized) = id_cache.phonetics.get(&tag_code) {
-                    normalized.par_iter().find_any(|norm_key| {
-                        let match_jw = strsim::jaro_winkler(norm_key, tag_normalized);
+                    let best_match_found = normalized.par_iter().find_any(|norm_key| {
+                        let similarity = similarity_metric.similarity(norm_key, tag_normalized);
                         #[allow(clippy::cast_possible_truncation)]
                         #[allow(clippy::cast_sign_loss)]
-                        let mjw = min(100, (match_jw * 100.0).round() as u16);
-                        if mjw >= match_threshold {
+                        let score = min(100, (similarity * 100.0).round() as u16);
+                        if score >= match_threshold {
                             let mut lock = data.lock().unwrap();
-                            if lock.0 < mjw {
-                                *lock = (mjw, Some(Cow::Borrowed(norm_key)));
+                            if lock.0 < score {
+                                *lock = (score, Some(Cow::Borrowed(norm_key)));
                             }
-                            if mjw > best_match_threshold {
-                                return true; // (true, matched_normalized_epg_id.map(|s| s.to_string()));
+                            if score > best_match_threshold {
+                                return true;
                             }
                         }
                         false
                     });
+                    if best_match_found.is_some() {
+                        break;
+                    }
                 }
             }
         }
-        // is there an early exit strategy ???
 
-        if early_exit_flag.load(Ordering::SeqCst) {
-            let result = data.lock().unwrap().1.take();
-            return (true, result.as_ref().map(std::string::ToString::to_string));
-        }
-        (false, None)
+        let (score, norm_key) = data.into_inner().unwrap();
+        norm_key.map(|key| (key.to_string(), score))
     }
 
     /// Parses and filters a compressed EPG XML file, extracting relevant channel and program tags based on smart and fuzzy matching criteria.
@@ -256,8 +292,14 @@ impl TVGuide {
                             if let Some(epg_id) = tag.get_attribute_value(EPG_ATTRIB_CHANNEL) {
                                 if id_cache.processed.contains(epg_id) {
                                     let borrowed_epg_id = Cow::Borrowed(epg_id.as_str());
-                                    if id_cache.channel_epg_id.contains(&borrowed_epg_id) {
-                                        children.push(tag);
+                                    if id_cache.channel_epg_id.contains(&borrowed_epg_id)
+                                        && id_cache.programme_filter.as_ref().is_none_or(|filter| filter.is_allowed(&get_programme_texts(&tag))) {
+                                        if let Some(minutes) = id_cache.get_time_shift(epg_id) {
+                                            shift_programme_time(&mut tag, minutes);
+                                        }
+                                        if id_cache.programme_window.as_ref().is_none_or(|window| is_programme_in_window(&tag, window)) {
+                                            children.push(tag);
+                                        }
                                     }
                                 }
                             }
@@ -299,6 +341,32 @@ impl TVGuide {
 }
 
 
+// The `title`, `sub-title` and `category` texts of a programme for the target `epg_filter`.
+fn get_programme_texts(tag: &XmlTag) -> Vec<&str> {
+    tag.children.iter().flatten()
+        .filter(|child| matches!(child.name.as_str(), EPG_TAG_TITLE | EPG_TAG_SUB_TITLE | EPG_TAG_CATEGORY))
+        .filter_map(|child| child.value.as_deref())
+        .collect()
+}
+
+// Checks the `start` and `stop` of a programme against the target `epg_days_past` and `epg_days_future`.
+fn is_programme_in_window(tag: &XmlTag, window: &EpgTimeWindow) -> bool {
+    let get_time = |attr| tag.get_attribute_value(attr).and_then(|value| parse_xmltv_time(value));
+    window.is_inside(get_time(EPG_ATTRIB_START), get_time(EPG_ATTRIB_STOP))
+}
+
+// Offsets the `start` and `stop` attributes of a programme.
+fn shift_programme_time(tag: &mut XmlTag, minutes: i32) {
+    let correction = TimeDelta::minutes(i64::from(minutes));
+    if let Some(attributes) = tag.attributes.as_mut() {
+        for attr in [EPG_ATTRIB_START, EPG_ATTRIB_STOP] {
+            if let Some(value) = attributes.get_mut(attr) {
+                *value = time_correct(value, &correction);
+            }
+        }
+    }
+}
+
 fn handle_tag_start<F>(callback: &mut F, stack: &mut Vec<XmlTag>, e: &BytesStart)
 where
     F: FnMut(XmlTag),
@@ -415,78 +483,142 @@ fn collect_tag_attributes(e: &BytesStart, is_channel: bool, is_program: bool) ->
     attributes
 }
 
-pub fn flatten_tvguide(tv_guides: &[Epg]) -> Option<Epg> {
-    if tv_guides.is_empty() {
-        None
-    } else {
-        let epg_children = Mutex::new(Vec::new());
-        let epg_attributes = tv_guides.first().and_then(|t| t.attributes.clone());
-        let count = tv_guides.iter().map(|tvg| tvg.children.len()).sum();
-        let channel_mapping: RwLock<HashMap<String, i16>> = RwLock::new(HashMap::with_capacity(count));
-
-        let mut sorted_guides = tv_guides.to_vec();
-        // sort by priority
-        sorted_guides.sort_by(|a, b| a.priority.cmp(&b.priority));
-        // if executed parallel it does not matter how we sort.
-        sorted_guides.par_iter().for_each(|guide| {
-            let mut children = vec![];
-            guide.children.iter().for_each(|c| {
-                if c.name.as_str() == EPG_TAG_CHANNEL {
-                    if let Some(chan_id) = c.get_attribute_value(EPG_ATTRIB_ID) {
-                        let should_add = {
-                            let channel_map = channel_mapping.read().unwrap();
-                            // if not stored
-                            !channel_map.contains_key(chan_id) ||
-                                // or if priority is higher (less means higher priority)
-                                channel_map.get(chan_id).is_none_or(|&priority| guide.priority < priority)
-                        };
-                        if should_add {
-                            let mut channel_map = channel_mapping.write().unwrap();
-                            match channel_map.entry(chan_id.to_string()) {
-                                Entry::Vacant(e) => {
-                                    e.insert(guide.priority);
-                                    children.push(c.clone());
-                                }
-                                Entry::Occupied(mut e) if guide.priority < *e.get() => {
-                                    e.insert(guide.priority);
-                                    children.push(c.clone());
-                                }
-                                Entry::Occupied(_) => {}
-                            }
-                        }
-                    }
-                }
-            });
-            guide.children.iter().for_each(|c| {
-                if c.name.as_str() == EPG_TAG_PROGRAMME {
-                    if let Some(chan_id) = c.get_attribute_value(EPG_ATTRIB_CHANNEL) {
-                        let channel_map = channel_mapping.read().unwrap();
-                        if let Some(&stored_priority) = channel_map.get(chan_id) {
-                            if stored_priority == guide.priority {
-                                children.push(c.clone());
-                            }
-                        }
+fn create_text_tag(name: &str, value: String) -> XmlTag {
+    let mut tag = XmlTag::new(name.to_string(), None);
+    tag.value = Some(value);
+    tag
+}
+
+// epg id for channels without one, derived from the channel name
+fn get_placeholder_epg_id(name: &str) -> Option<String> {
+    let id: String = name.chars().filter(|c| c.is_alphanumeric()).flat_map(char::to_lowercase).collect();
+    (!id.is_empty()).then(|| format!("{id}.placeholder"))
+}
+
+// the lower case epg ids of the channels and of the programmes in the epg
+fn get_epg_ids(epg: Option<&MergedEpg>) -> (HashSet<String>, HashSet<String>) {
+    let mut channel_ids = HashSet::new();
+    let mut programme_ids = HashSet::new();
+    if let Some(epg) = epg {
+        epg.channels().filter_map(|tag| tag.get_attribute_value(EPG_ATTRIB_ID)).for_each(|id| { channel_ids.insert(id.to_lowercase()); });
+        epg.programmes().filter_map(|tag| tag.get_attribute_value(EPG_ATTRIB_CHANNEL)).for_each(|id| { programme_ids.insert(id.to_lowercase()); });
+    }
+    (channel_ids, programme_ids)
+}
+
+fn create_channel_tag(epg_id: &str, name: String) -> XmlTag {
+    let mut channel_tag = XmlTag::new(EPG_TAG_CHANNEL.to_string(), Some(HashMap::from([(EPG_ATTRIB_ID.to_string(), epg_id.to_string())])));
+    channel_tag.children = Some(vec![create_text_tag(EPG_TAG_DISPLAY_NAME, name)]);
+    channel_tag
+}
+
+fn create_programme_tag(epg_id: &str, start: i64, stop: i64, children: Vec<XmlTag>) -> XmlTag {
+    let mut programme = XmlTag::new(EPG_TAG_PROGRAMME.to_string(), Some(HashMap::from([
+        (EPG_ATTRIB_CHANNEL.to_string(), epg_id.to_string()),
+        (EPG_ATTRIB_START.to_string(), format_xmltv_time(start).unwrap_or_default()),
+        (EPG_ATTRIB_STOP.to_string(), format_xmltv_time(stop).unwrap_or_default()),
+    ])));
+    programme.children = Some(children);
+    programme
+}
+
+/// Creates the programmes of the static schedules for the live channels whose epg id has a schedule
+/// and no programmes in the epg. The programmes repeat in cycles aligned to the cycle length.
+pub fn fill_epg_schedules(epg: Option<&MergedEpg>, playlist: &[PlaylistGroup], schedules: &EpgSchedulesConfig, now: DateTime<Utc>) -> Option<Epg> {
+    let (mut channel_ids, mut programme_ids) = get_epg_ids(epg);
+    let now = now.timestamp();
+    let end = now + i64::from(schedules.hours) * 3600;
+
+    let mut channel_tags = vec![];
+    let mut programme_tags = vec![];
+    for header in playlist.iter().flat_map(|group| &group.channels).map(|channel| &channel.header) {
+        if header.xtream_cluster != XtreamCluster::Live {
+            continue;
+        }
+        let Some(epg_id) = header.epg_channel_id.as_deref() else { continue };
+        let Some(schedule) = schedules.get_schedule(epg_id) else { continue };
+        if !programme_ids.insert(epg_id.to_lowercase()) {
+            continue;
+        }
+        if channel_ids.insert(epg_id.to_lowercase()) {
+            channel_tags.push(create_channel_tag(epg_id, schedule.name.clone().unwrap_or_else(|| header.name.clone())));
+        }
+        let mut start = now - now.rem_euclid(schedule.get_cycle_secs());
+        while start < end {
+            for programme in &schedule.programmes {
+                let stop = start + i64::from(programme.duration) * 60;
+                if stop > now && start < end {
+                    let mut children = vec![create_text_tag(EPG_TAG_TITLE, programme.title.clone())];
+                    if let Some(desc) = programme.desc.as_ref() {
+                        children.push(create_text_tag(EPG_TAG_DESC, desc.clone()));
                     }
+                    programme_tags.push(create_programme_tag(epg_id, start, stop, children));
                 }
-            });
-
-            epg_children.lock().unwrap().extend(children);
-        });
-        let epg = Epg {
-            logo_override: false,
-            priority: 0,
-            attributes: epg_attributes,
-            children: mem::take(&mut *epg_children.lock().unwrap()),
-        };
-        Some(epg)
+                start = stop;
+            }
+        }
     }
+    if !programme_tags.is_empty() {
+        debug!("Added {} scheduled programmes", programme_tags.len());
+    }
+    channel_tags.extend(programme_tags);
+    (!channel_tags.is_empty()).then_some(Epg { priority: 0, logo_override: false, attributes: None, children: channel_tags })
 }
 
+/// Creates placeholder programmes for the live channels of the playlist which have no programmes in the epg.
+/// Channels without an epg id get one derived from their name.
+pub fn fill_epg_placeholders(epg: Option<&MergedEpg>, playlist: &mut [PlaylistGroup], placeholder: &EpgPlaceholder, now: DateTime<Utc>) -> Option<Epg> {
+    let (mut channel_ids, mut programme_ids) = get_epg_ids(epg);
+
+    let duration = i64::from(placeholder.duration) * 60;
+    // aligned to the duration, the first programme covers now
+    let first_start = now.timestamp() - now.timestamp().rem_euclid(duration);
+    let end = now.timestamp() + i64::from(placeholder.hours) * 3600;
+
+    let mut channel_tags = vec![];
+    let mut programme_tags = vec![];
+    for group in playlist.iter_mut() {
+        for channel in &mut group.channels {
+            let header = &mut channel.header;
+            if header.xtream_cluster != XtreamCluster::Live {
+                continue;
+            }
+            if header.epg_channel_id.as_ref().is_none_or(|id| id.trim().is_empty()) {
+                header.epg_channel_id = get_placeholder_epg_id(&header.name);
+            }
+            let Some(epg_id) = header.epg_channel_id.clone() else { continue };
+            if !programme_ids.insert(epg_id.to_lowercase()) {
+                continue;
+            }
+            if channel_ids.insert(epg_id.to_lowercase()) {
+                channel_tags.push(create_channel_tag(&epg_id, header.name.clone()));
+            }
+            let title = placeholder.get_title(&header.name, &header.title, &header.group);
+            let mut start = first_start;
+            while start < end {
+                programme_tags.push(create_programme_tag(&epg_id, start, start + duration, vec![create_text_tag(EPG_TAG_TITLE, title.clone())]));
+                start += duration;
+            }
+        }
+    }
+    if !programme_tags.is_empty() {
+        debug!("Added {} placeholder programmes", programme_tags.len());
+    }
+    channel_tags.extend(programme_tags);
+    (!channel_tags.is_empty()).then_some(Epg { priority: 0, logo_override: false, attributes: None, children: channel_tags })
+}
 
 #[cfg(test)]
 mod tests {
     use crate::model::{EpgNamePrefix, EpgSmartMatchConfig};
-    use crate::processing::parser::xmltv::normalize_channel_name;
+    use crate::model::{Epg, EpgPlaceholder, EpgProgrammeFilter, EpgSchedulesConfig, MergedEpg, PlaylistGroup, PlaylistItem, PlaylistItemHeader, XmlTag, XmlTagIcon};
+    use crate::repository::epg_repository::{epg_json_write, xmltv_write};
+    use crate::processing::parser::xmltv::{fill_epg_placeholders, fill_epg_schedules, get_programme_texts, normalize_channel_name, parse_tvguide, select_preferred_display_names, shift_programme_time};
+    use crate::model::TVGuide;
+    use crate::processing::processor::epg::EpgIdCache;
+    use chrono::DateTime;
+    use shared::model::XtreamCluster;
+    use std::collections::{HashMap, HashSet};
 
     #[test]
     /// Tests normalization of a channel name using the default smart match configuration.
@@ -503,6 +635,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_select_preferred_display_names() {
+        let display_name = |name: &str, lang: Option<&str>| {
+            let attribs = lang.map(|lang| HashMap::from([("lang".to_string(), lang.to_string())]));
+            XmlTag { value: Some(name.to_string()), ..XmlTag::new("display-name".to_string(), attribs) }
+        };
+        let names = |children: &[XmlTag]| children.iter().map(|child| child.value.clone().unwrap_or_default()).collect::<Vec<String>>();
+        let preferred = vec!["de".to_string(), "en".to_string()];
+
+        let mut children = vec![display_name("Arte FR", Some("fr")), display_name("Arte EN", Some("en")),
+                                display_name("Arte", None), display_name("Arte DE", Some("de-AT")), XmlTag::new("icon".to_string(), None)];
+        select_preferred_display_names(&mut children, &preferred);
+        assert_eq!(names(&children), vec!["Arte DE", "Arte EN", ""]);
+
+        let mut children = vec![display_name("Arte FR", Some("fr")), display_name("Arte", None)];
+        select_preferred_display_names(&mut children, &preferred);
+        assert_eq!(names(&children), vec!["Arte FR", "Arte"]);
+    }
+
 
     // #[test]
     // fn parse_test() -> io::Result<()> {
@@ -577,4 +728,199 @@ mod tests {
         println!("{}", metaphone.encode(&normalize_channel_name("BU | ODISEA ᵁᴴᴰ ³⁸⁴⁰ᴾ", &epg_smart_cfg)));
         println!("{}", metaphone.encode(&normalize_channel_name("BG | ODISEA ᵁᴴᴰ ³⁸⁴⁰ᴾ", &epg_smart_cfg)));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_find_best_fuzzy_match() {
+        let mut id_cache = EpgIdCache::new(None);
+        id_cache.smart_match_config = EpgSmartMatchConfig { enabled: true, fuzzy_matching: true, match_threshold: 80, best_match_threshold: 99, ..Default::default() };
+        let code = id_cache.phonetic("lovenature");
+        id_cache.phonetics.insert(code, HashSet::from(["lovenatur".to_string()]));
+        let mut tag = XmlTag::new("channel".to_string(), None);
+        tag.normalized_epg_ids = Some(vec!["lovenature".to_string()]);
+        // a match below the best match threshold is returned too
+        assert_eq!(TVGuide::find_best_fuzzy_match(&mut id_cache, &tag), Some(("lovenatur".to_string(), 98)));
+
+        id_cache.smart_match_config.match_threshold = 100;
+        assert_eq!(TVGuide::find_best_fuzzy_match(&mut id_cache, &tag), None);
+    }
+
+    #[test]
+    fn test_shift_programme_time() {
+        let attributes = HashMap::from([
+            ("start".to_string(), "20250601233000 +0000".to_string()),
+            ("stop".to_string(), "20250602003000 +0000".to_string()),
+            ("channel".to_string(), "ard.de".to_string()),
+        ]);
+        let mut tag = XmlTag::new("programme".to_string(), Some(attributes));
+        shift_programme_time(&mut tag, -90);
+        assert_eq!(tag.get_attribute_value("start").map(String::as_str), Some("20250601220000 +0000"));
+        assert_eq!(tag.get_attribute_value("stop").map(String::as_str), Some("20250601230000 +0000"));
+        assert_eq!(tag.get_attribute_value("channel").map(String::as_str), Some("ard.de"));
+    }
+
+    #[test]
+    fn test_programme_filter() {
+        let xml = r#"<tv><programme channel="a" start="20250601200000 +0000"><title>Teleshopping</title><category>Shopping</category></programme>
+            <programme channel="a" start="20250601210000 +0000"><title>News</title><category>News</category></programme>
+            <programme channel="a" start="20250601220000 +0000"><title>Movie</title><sub-title>Adult</sub-title></programme></tv>"#;
+        let mut filter = EpgProgrammeFilter { drop: Some(vec!["(?i)shopping".to_string(), "^Adult$".to_string()]), ..Default::default() };
+        filter.prepare().unwrap();
+        let mut titles = vec![];
+        parse_tvguide(xml.as_bytes(), &mut |tag: XmlTag| {
+            let texts = get_programme_texts(&tag);
+            if tag.name == "programme" && filter.is_allowed(&texts) {
+                titles.push(texts[0].to_string());
+            }
+        });
+        assert_eq!(titles, vec!["News"]);
+
+        let mut filter = EpgProgrammeFilter { keep: Some(vec!["Movie".to_string()]), ..Default::default() };
+        filter.prepare().unwrap();
+        assert!(filter.is_allowed(&["Movie", "Adult"]));
+        assert!(!filter.is_allowed(&["News"]));
+        assert!(EpgProgrammeFilter { drop: Some(vec!["(".to_string()]), ..Default::default() }.prepare().is_err());
+    }
+
+    #[test]
+    fn test_merged_epg() {
+        let create_tag = |name: &str, attrib: &str, id: &str| XmlTag::new(name.to_string(), Some(HashMap::from([(attrib.to_string(), id.to_string())])));
+        let guides = vec![
+            Epg { priority: 1, logo_override: false, attributes: None, children: vec![
+                create_tag("channel", "id", "zdf.de"), create_tag("channel", "id", "arte.de"),
+                create_tag("programme", "channel", "zdf.de"), create_tag("programme", "channel", "arte.de"),
+            ] },
+            Epg { priority: 0, logo_override: false, attributes: None, children: vec![
+                create_tag("channel", "id", "zdf.de"), create_tag("programme", "channel", "zdf.de"), create_tag("programme", "channel", "zdf.de"),
+            ] },
+        ];
+        let mut merged = MergedEpg::new(&guides).unwrap();
+        assert_eq!(merged.channels().filter_map(|tag| tag.get_attribute_value("id")).collect::<Vec<_>>(), vec!["zdf.de", "arte.de"]);
+        // the programmes of zdf.de are only taken from the source with the higher priority
+        assert_eq!(merged.programmes().count(), 3);
+
+        let xml = String::from_utf8(xmltv_write(&merged, Vec::new()).unwrap()).unwrap();
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\" ?><!DOCTYPE tv SYSTEM \"xmltv.dtd\"><tv><channel id=\"zdf.de\"></channel><channel id=\"arte.de\">"));
+        assert!(xml.ends_with("<programme channel=\"arte.de\"></programme></tv>"));
+
+        // programmes without start are skipped, programmes of undefined channels follow the channels
+        let mut programme = create_tag("programme", "channel", "orf.at");
+        programme.attributes.as_mut().unwrap().insert("start".to_string(), "20250601203000 +0000".to_string());
+        merged.add_generated(Epg { priority: 0, logo_override: false, attributes: None, children: vec![programme] });
+        let json = String::from_utf8(epg_json_write(&merged, Vec::new()).unwrap()).unwrap();
+        assert_eq!(json, r#"{"channels":[{"id":"zdf.de","programmes":[]},{"id":"arte.de","programmes":[]},{"id":"orf.at","programmes":[{"start":1748809800,"title":""}]}]}"#);
+
+        // only the icons of the selected channels of guides with logo override are proxied
+        let create_channel = |id: &str, src: &str| {
+            let mut tag = create_tag("channel", "id", id);
+            tag.icon = XmlTagIcon::Src(src.to_string());
+            tag
+        };
+        let guides = vec![
+            Epg { priority: 0, logo_override: false, attributes: None, children: vec![create_channel("zdf.de", "http://logo/zdf0.png")] },
+            Epg { priority: 1, logo_override: true, attributes: None, children: vec![
+                create_channel("zdf.de", "http://logo/zdf1.png"), create_channel("arte.de", "http://logo/arte.png"),
+            ] },
+        ];
+        let merged = MergedEpg::new(&guides).unwrap();
+        assert_eq!(merged.override_icons().collect::<Vec<_>>(), vec!["http://logo/arte.png"]);
+    }
+
+    #[test]
+    fn test_fill_epg_placeholders() {
+        let create_channel = |name: &str, epg_id: Option<&str>| PlaylistItem { header: PlaylistItemHeader {
+            name: name.to_string(),
+            group: "News".to_string(),
+            epg_channel_id: epg_id.map(ToString::to_string),
+            xtream_cluster: XtreamCluster::Live,
+            ..Default::default()
+        } };
+        let mut playlist = vec![PlaylistGroup {
+            id: 1,
+            title: "News".to_string(),
+            channels: vec![create_channel("Das Erste", Some("daserste.de")), create_channel("Sport HD", None), create_channel("ZDF", Some("ZDF.de"))],
+            xtream_cluster: XtreamCluster::Live,
+        }];
+        let mut programme = XmlTag::new("programme".to_string(), Some(HashMap::from([("channel".to_string(), "zdf.de".to_string())])));
+        programme.children = Some(vec![]);
+        let channel = XmlTag::new("channel".to_string(), Some(HashMap::from([("id".to_string(), "zdf.de".to_string())])));
+        let epg = vec![Epg { priority: 0, logo_override: false, attributes: None, children: vec![channel, programme] }];
+        let merged = MergedEpg::new(&epg);
+        let placeholder = EpgPlaceholder { title: "{name} ({group})".to_string(), duration: 60, hours: 2 };
+        let now = DateTime::from_timestamp(1_748_808_000 + 1800, 0).unwrap(); // 2025-06-01 20:30:00
+
+        let epg = fill_epg_placeholders(merged.as_ref(), &mut playlist, &placeholder, now).unwrap();
+        assert_eq!(playlist[0].channels[1].header.epg_channel_id.as_deref(), Some("sporthd.placeholder"));
+        let channels: Vec<_> = epg.children.iter().filter(|tag| tag.name == "channel").filter_map(|tag| tag.get_attribute_value("id")).collect();
+        assert_eq!(channels, vec!["daserste.de", "sporthd.placeholder"]);
+        let programmes: Vec<_> = epg.children.iter().filter(|tag| tag.get_attribute_value("channel").is_some_and(|id| id == "daserste.de")).collect();
+        assert_eq!(programmes.len(), 3);
+        assert_eq!(programmes[0].get_attribute_value("start").unwrap(), "20250601200000 +0000");
+        assert_eq!(programmes[0].get_attribute_value("stop").unwrap(), "20250601210000 +0000");
+        assert_eq!(programmes[0].children.as_ref().unwrap()[0].value.as_deref(), Some("Das Erste (News)"));
+        // the channel with guide data is not touched
+        assert_eq!(epg.children.iter().filter(|tag| tag.get_attribute_value("channel").is_some_and(|id| id.eq_ignore_ascii_case("zdf.de"))).count(), 0);
+    }
+
+    #[test]
+    fn test_fill_epg_schedules() {
+        let create_channel = |name: &str, epg_id: &str| PlaylistItem { header: PlaylistItemHeader {
+            name: name.to_string(),
+            epg_channel_id: Some(epg_id.to_string()),
+            xtream_cluster: XtreamCluster::Live,
+            ..Default::default()
+        } };
+        let mut playlist = vec![PlaylistGroup {
+            id: 1,
+            title: "24/7".to_string(),
+            channels: vec![create_channel("Simpsons 24/7", "247.Simpsons"), create_channel("ZDF", "zdf.de"), create_channel("Sport HD", "sport.de")],
+            xtream_cluster: XtreamCluster::Live,
+        }];
+        let content = r"
+hours: 1
+schedules:
+  - epg_id: 247.simpsons
+    name: The Simpsons
+    programmes:
+      - { title: The Simpsons, desc: Episodes around the clock, duration: 25 }
+      - { title: Break, duration: 5 }
+  - epg_id: zdf.de
+    programmes: [{ title: ZDF, duration: 60 }]
+";
+        let mut schedule_config: EpgSchedulesConfig = serde_yaml::from_str(content).unwrap();
+        schedule_config.prepare().unwrap();
+        let mut programme = XmlTag::new("programme".to_string(), Some(HashMap::from([("channel".to_string(), "zdf.de".to_string())])));
+        programme.children = Some(vec![]);
+        let channel = XmlTag::new("channel".to_string(), Some(HashMap::from([("id".to_string(), "zdf.de".to_string())])));
+        let epg = vec![Epg { priority: 0, logo_override: false, attributes: None, children: vec![channel, programme] }];
+        let mut merged = MergedEpg::new(&epg);
+        let now = DateTime::from_timestamp(1_748_808_000 + 2400, 0).unwrap(); // 2025-06-01 20:40:00
+
+        let scheduled = fill_epg_schedules(merged.as_ref(), &playlist, &schedule_config, now).unwrap();
+        let channels: Vec<_> = scheduled.children.iter().filter(|tag| tag.name == "channel").collect();
+        assert_eq!(channels.len(), 1);
+        assert_eq!(channels[0].get_attribute_value("id").unwrap(), "247.Simpsons");
+        assert_eq!(channels[0].children.as_ref().unwrap()[0].value.as_deref(), Some("The Simpsons"));
+        // the cycle starts at 20:30, the programmes ending before now are skipped
+        let programmes: Vec<_> = scheduled.children.iter().filter(|tag| tag.name == "programme").collect();
+        let times: Vec<_> = programmes.iter().map(|tag| (tag.get_attribute_value("start").unwrap().as_str(), tag.get_attribute_value("stop").unwrap().as_str())).collect();
+        assert_eq!(times, vec![
+            ("20250601203000 +0000", "20250601205500 +0000"),
+            ("20250601205500 +0000", "20250601210000 +0000"),
+            ("20250601210000 +0000", "20250601212500 +0000"),
+            ("20250601212500 +0000", "20250601213000 +0000"),
+            ("20250601213000 +0000", "20250601215500 +0000"),
+        ]);
+        let children = programmes[0].children.as_ref().unwrap();
+        assert_eq!((children[0].value.as_deref(), children[1].value.as_deref()), (Some("The Simpsons"), Some("Episodes around the clock")));
+        assert_eq!(programmes[1].children.as_ref().unwrap().len(), 1);
+
+        // the scheduled channel gets no placeholders
+        merged.as_mut().unwrap().add_generated(scheduled);
+        let placeholder = EpgPlaceholder { title: "{name}".to_string(), duration: 60, hours: 1 };
+        let placeholders = fill_epg_placeholders(merged.as_ref(), &mut playlist, &placeholder, now).unwrap();
+        assert!(placeholders.children.iter().all(|tag| tag.get_attribute_value("channel").or(tag.get_attribute_value("id")).is_some_and(|id| id == "sport.de")));
+
+        let mut invalid: EpgSchedulesConfig = serde_yaml::from_str("schedules: [{ epg_id: a, programmes: [{ title: A, duration: 0 }] }]").unwrap();
+        assert!(invalid.prepare().is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use crate::model::{ConfigAdultDetection, ConfigTarget, PlaylistGroup, PlaylistItem};
+use crate::utils::debug_if_enabled;
+
+fn is_adult_channel(config: &ConfigAdultDetection, group_is_adult: bool, channel: &PlaylistItem) -> bool {
+    group_is_adult
+        || (config.provider_flag && channel.header.is_provider_adult())
+        || config.is_match(&channel.header.name)
+        || config.is_match(&channel.header.title)
+}
+
+// Adult channels are flagged the same way for all providers, the user packages with `hide_adult` skip them.
+// Providers flag adult content differently or not at all, therefore the channels are detected by name or group title.
+pub(in crate::processing::processor) fn tag_adult_channels(target: &ConfigTarget, playlist: &mut [PlaylistGroup]) {
+    let Some(config) = target.adult_detection.as_ref() else {
+        return;
+    };
+    let mut count = 0;
+    for group in playlist {
+        let group_is_adult = config.is_match(&group.title);
+        for channel in &mut group.channels {
+            let adult = is_adult_channel(config, group_is_adult, channel);
+            if adult {
+                count += 1;
+            }
+            channel.header.set_adult(adult);
+        }
+    }
+    debug_if_enabled!("Tagged {count} adult channels for {}", target.name);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{ConfigAdultDetection, ConfigTarget, PlaylistGroup, PlaylistItem, PlaylistItemHeader};
+    use crate::processing::processor::adult::tag_adult_channels;
+    use serde_json::json;
+    use shared::model::XtreamCluster;
+
+    fn create_group(title: &str, names: &[&str]) -> PlaylistGroup {
+        PlaylistGroup {
+            id: 1,
+            title: title.to_string(),
+            channels: names.iter().map(|name| PlaylistItem {
+                header: PlaylistItemHeader { name: (*name).to_string(), title: (*name).to_string(), group: title.to_string(), ..Default::default() }
+            }).collect(),
+            xtream_cluster: XtreamCluster::Live,
+        }
+    }
+
+    #[test]
+    fn test_tag_adult_channels() {
+        let mut config: ConfigAdultDetection = serde_json::from_str("{}").unwrap();
+        config.prepare(None).unwrap();
+        let target = ConfigTarget { adult_detection: Some(config), ..Default::default() };
+        let mut playlist = vec![
+            create_group("DE: Sport", &["Sky Sport 1", "Redlight HD", "Sport 18+", "Adultswim"]),
+            create_group("XXX | Movies", &["Private TV"]),
+        ];
+        playlist[0].channels[0].header.additional_properties = Some(json!({"is_adult": "1"}));
+        playlist[0].channels[3].header.additional_properties = Some(json!({"is_adult": 1}));
+        tag_adult_channels(&target, &mut playlist);
+        let flags: Vec<bool> = playlist.iter().flat_map(|g| &g.channels).map(|c| c.header.is_provider_adult()).collect();
+        assert_eq!(flags, vec![true, true, true, true, true]);
+
+        let mut config: ConfigAdultDetection = serde_json::from_str(r#"{"provider_flag": false}"#).unwrap();
+        config.prepare(None).unwrap();
+        let target = ConfigTarget { adult_detection: Some(config), ..Default::default() };
+        tag_adult_channels(&target, &mut playlist);
+        let flags: Vec<bool> = playlist.iter().flat_map(|g| &g.channels).map(|c| c.to_m3u().is_adult()).collect();
+        assert_eq!(flags, vec![false, true, true, false, true]);
+        assert_eq!(playlist[0].channels[0].header.get_additional_property("is_adult"), Some(&json!(0)));
+    }
+}
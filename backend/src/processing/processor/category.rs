@@ -0,0 +1,173 @@
+use crate::model::{normalize_group_path, ConfigCategoryRule, ConfigTarget, PlaylistGroup};
+use crate::processing::processor::sort::sort_group_channels;
+use crate::utils::debug_if_enabled;
+use shared::model::XtreamCluster;
+use std::collections::HashMap;
+
+fn find_rule<'a>(rules: &'a [ConfigCategoryRule], group: &PlaylistGroup) -> Option<&'a ConfigCategoryRule> {
+    rules.iter().find(|rule| rule.is_match(group.xtream_cluster, &group.title))
+}
+
+// Category rules are applied on the final groups.
+// Groups are renamed, groups with the same name and cluster are merged into the first occurrence
+// and the result is ordered by rule priority. Groups without a rule have priority 0.
+// The channels of renamed or merged groups are sorted again, the group order of the sorted playlist is kept.
+pub(in crate::processing::processor) fn apply_category_rules(target: &ConfigTarget, playlist: Vec<PlaylistGroup>) -> Vec<PlaylistGroup> {
+    let Some(rules) = target.categories.as_ref().filter(|r| !r.is_empty()) else {
+        return playlist;
+    };
+
+    let mut result: Vec<(i16, PlaylistGroup, bool)> = Vec::with_capacity(playlist.len());
+    let mut group_map: HashMap<(String, XtreamCluster), usize> = HashMap::new();
+    for mut group in playlist {
+        let mut changed = false;
+        let priority = match find_rule(rules, &group) {
+            Some(rule) => {
+                if let Some(new_title) = rule.apply(&group.title) {
+                    if new_title != group.title {
+                        changed = true;
+                        debug_if_enabled!("Category {} renamed to {} for {}", &group.title, &new_title, target.name);
+                        for channel in &mut group.channels {
+                            channel.header.group.clone_from(&new_title);
+                        }
+                        group.title = new_title;
+                    }
+                }
+                rule.priority
+            }
+            None => 0,
+        };
+
+        let key = (group.title.clone(), group.xtream_cluster);
+        match group_map.entry(key) {
+            std::collections::hash_map::Entry::Vacant(v) => {
+                v.insert(result.len());
+                result.push((priority, group, changed));
+            }
+            std::collections::hash_map::Entry::Occupied(o) => {
+                let (merged_priority, merged_group, merged_changed) = &mut result[*o.get()];
+                *merged_priority = (*merged_priority).min(priority);
+                *merged_changed = true;
+                merged_group.channels.extend(group.channels);
+            }
+        }
+    }
+
+    for (_, group, _) in result.iter_mut().filter(|(_, _, changed)| *changed) {
+        sort_group_channels(target, group);
+    }
+    // stable sort, keeps the existing order for groups with the same priority
+    result.sort_by_key(|(priority, _, _)| *priority);
+    result.into_iter().map(|(_, group, _)| group).collect()
+}
+
+// With the target option `group_hierarchy` sub groups like `Sports/Football` are written as `Sports / Football`,
+// groups with the same path are merged when the playlist is flattened.
+pub(in crate::processing::processor) fn normalize_group_hierarchy(target: &ConfigTarget, playlist: &mut [PlaylistGroup]) {
+    if !target.options.as_ref().is_some_and(|options| options.group_hierarchy) {
+        return;
+    }
+    for group in playlist {
+        let title = normalize_group_path(&group.title);
+        if title != group.title {
+            for channel in &mut group.channels {
+                channel.header.group.clone_from(&title);
+            }
+            group.title = title;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{ConfigCategoryRule, ConfigSort, ConfigSortChannel, ConfigTarget, ConfigTargetOptions, PlaylistGroup, PlaylistItem, PlaylistItemHeader, SortOrder};
+    use crate::processing::processor::category::{apply_category_rules, normalize_group_hierarchy};
+    use regex::Regex;
+    use shared::model::{ItemField, XtreamCluster};
+
+    fn create_group(id: u32, title: &str, cluster: XtreamCluster) -> PlaylistGroup {
+        PlaylistGroup {
+            id,
+            title: title.to_string(),
+            channels: vec![PlaylistItem { header: PlaylistItemHeader { group: title.to_string(), xtream_cluster: cluster, ..Default::default() } }],
+            xtream_cluster: cluster,
+        }
+    }
+
+    fn create_rule(pattern: &str, name: Option<&str>, priority: i16) -> ConfigCategoryRule {
+        let mut rule = ConfigCategoryRule {
+            pattern: pattern.to_string(),
+            cluster: None,
+            name: name.map(ToString::to_string),
+            icon: None,
+            priority,
+            t_re: None,
+        };
+        rule.prepare(None).unwrap();
+        rule
+    }
+
+    #[test]
+    fn test_category_rules() {
+        let target = ConfigTarget {
+            categories: Some(vec![
+                create_rule("^(DE|AT): Sport.*", Some("Sport"), 0),
+                create_rule("^UK: (.*)", Some("English $1"), 0),
+                create_rule("^News", None, -1),
+            ]),
+            ..Default::default()
+        };
+        let playlist = vec![
+            create_group(1, "DE: Sport HD", XtreamCluster::Live),
+            create_group(2, "UK: Movies", XtreamCluster::Live),
+            create_group(3, "AT: Sport", XtreamCluster::Live),
+            create_group(4, "News", XtreamCluster::Live),
+            create_group(5, "DE: Sport HD", XtreamCluster::Video),
+        ];
+        let result = apply_category_rules(&target, playlist);
+        let titles = result.iter().map(|g| (g.title.as_str(), g.channels.len())).collect::<Vec<_>>();
+        assert_eq!(titles, vec![("News", 1), ("Sport", 2), ("English Movies", 1), ("Sport", 1)]);
+        assert!(result[1].channels.iter().all(|c| c.header.group == "Sport"));
+    }
+
+    #[test]
+    fn test_category_rules_sort_merged_channels() {
+        let target = ConfigTarget {
+            categories: Some(vec![create_rule("^(DE|AT): Sport.*", Some("Sport"), 0)]),
+            sort: Some(ConfigSort {
+                channels: Some(vec![ConfigSortChannel {
+                    field: ItemField::Caption,
+                    group_pattern: "^Sport$".to_string(),
+                    order: SortOrder::Asc,
+                    sequence: None,
+                    t_re_sequence: None,
+                    t_re_group_pattern: Some(Regex::new("^Sport$").unwrap()),
+                }]),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut playlist = vec![create_group(1, "DE: Sport", XtreamCluster::Live), create_group(2, "AT: Sport", XtreamCluster::Live)];
+        playlist[0].channels[0].header.title = "ZDF".to_string();
+        playlist[1].channels[0].header.title = "ORF".to_string();
+        let result = apply_category_rules(&target, playlist);
+        let titles = result[0].channels.iter().map(|c| c.header.title.as_str()).collect::<Vec<_>>();
+        assert_eq!(titles, vec!["ORF", "ZDF"]);
+    }
+
+    #[test]
+    fn test_normalize_group_hierarchy() {
+        let mut playlist = vec![
+            create_group(1, "Sports/Football /England", XtreamCluster::Live),
+            create_group(2, "News", XtreamCluster::Live),
+        ];
+        normalize_group_hierarchy(&ConfigTarget::default(), &mut playlist);
+        assert_eq!(playlist[0].title, "Sports/Football /England");
+
+        let target = ConfigTarget { options: Some(ConfigTargetOptions { group_hierarchy: true, ..Default::default() }), ..Default::default() };
+        normalize_group_hierarchy(&target, &mut playlist);
+        assert_eq!(playlist[0].title, "Sports / Football / England");
+        assert_eq!(playlist[0].channels[0].header.group, "Sports / Football / England");
+        assert_eq!(playlist[1].title, "News");
+    }
+}
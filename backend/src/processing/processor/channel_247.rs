@@ -0,0 +1,111 @@
+use crate::model::{ConfigChannel247, ConfigTarget, PlaylistGroup, PlaylistItem};
+use crate::utils::debug_if_enabled;
+use deunicode::deunicode;
+use shared::model::XtreamCluster;
+use shared::utils::CONSTANTS;
+use std::collections::BTreeMap;
+
+// Provider names often start with a country prefix like `US: ` or `DE | `, which is skipped for the sub group letter.
+fn strip_country_prefix(name: &str) -> &str {
+    if let Some((prefix, rest)) = name.split_once([':', '|']) {
+        let prefix = prefix.trim();
+        if CONSTANTS.country_codes.contains(prefix.to_lowercase().as_str()) {
+            return rest;
+        }
+    }
+    name
+}
+
+fn get_sub_group_key(config: &ConfigChannel247, name: &str) -> String {
+    let stripped = config.strip_marker(name);
+    let normalized = deunicode(strip_country_prefix(stripped.trim()));
+    match normalized.chars().find(|c| c.is_alphanumeric()) {
+        Some(c) if c.is_ascii_digit() => String::from("0-9"),
+        Some(c) => c.to_ascii_uppercase().to_string(),
+        None => String::from("#"),
+    }
+}
+
+fn is_247_channel(config: &ConfigChannel247, group_is_247: bool, channel: &PlaylistItem) -> bool {
+    group_is_247 || config.is_match(&channel.header.name) || config.is_match(&channel.header.title)
+}
+
+// 24/7 channels are single show channels. They are detected by name or group title
+// and moved from their live groups into a dedicated group, optionally split into A-Z sub groups.
+pub(in crate::processing::processor) fn group_247_channels(target: &ConfigTarget, playlist: Vec<PlaylistGroup>) -> Vec<PlaylistGroup> {
+    let Some(config) = target.channel_247.as_ref() else {
+        return playlist;
+    };
+
+    let mut next_group_id = playlist.iter().map(|group| group.id).max().unwrap_or_default() + 1;
+    let mut detected: BTreeMap<String, Vec<PlaylistItem>> = BTreeMap::new();
+    let mut result: Vec<PlaylistGroup> = Vec::with_capacity(playlist.len());
+    for mut group in playlist {
+        if group.xtream_cluster != XtreamCluster::Live {
+            result.push(group);
+            continue;
+        }
+        let group_is_247 = config.is_match(&group.title);
+        let (channels_247, channels): (Vec<PlaylistItem>, Vec<PlaylistItem>) = group.channels.into_iter()
+            .partition(|channel| is_247_channel(config, group_is_247, channel));
+        for channel in channels_247 {
+            let key = if config.alphabetical { get_sub_group_key(config, &channel.header.name) } else { String::new() };
+            detected.entry(key).or_default().push(channel);
+        }
+        if !channels.is_empty() {
+            group.channels = channels;
+            result.push(group);
+        }
+    }
+
+    for (key, mut channels) in detected {
+        let title = if key.is_empty() { config.group.clone() } else { format!("{}{}{key}", config.group, config.separator) };
+        debug_if_enabled!("Moved {} channels into 24/7 group {} for {}", channels.len(), &title, target.name);
+        for channel in &mut channels {
+            channel.header.group.clone_from(&title);
+        }
+        result.push(PlaylistGroup {
+            id: next_group_id,
+            title,
+            channels,
+            xtream_cluster: XtreamCluster::Live,
+        });
+        next_group_id += 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{ConfigChannel247, ConfigTarget, PlaylistGroup, PlaylistItem, PlaylistItemHeader};
+    use crate::processing::processor::channel_247::group_247_channels;
+    use shared::model::XtreamCluster;
+
+    fn create_group(id: u32, title: &str, names: &[&str]) -> PlaylistGroup {
+        PlaylistGroup {
+            id,
+            title: title.to_string(),
+            channels: names.iter().map(|name| PlaylistItem {
+                header: PlaylistItemHeader { name: (*name).to_string(), title: (*name).to_string(), group: title.to_string(), ..Default::default() }
+            }).collect(),
+            xtream_cluster: XtreamCluster::Live,
+        }
+    }
+
+    #[test]
+    fn test_group_247_channels() {
+        let mut config: ConfigChannel247 = serde_json::from_str("{}").unwrap();
+        config.prepare(None).unwrap();
+        let target = ConfigTarget { channel_247: Some(config), ..Default::default() };
+        let playlist = vec![
+            create_group(1, "US: Entertainment", &["US: ABC", "US: 24/7 Friends", "Seinfeld 24-7", "Channel 247"]),
+            create_group(2, "Classics 24/7", &["US: Bonanza", "24x7 1000 Ways to Die"]),
+        ];
+        let result = group_247_channels(&target, playlist);
+        let titles: Vec<&str> = result.iter().map(|g| g.title.as_str()).collect();
+        assert_eq!(titles, vec!["US: Entertainment", "24/7 | 0-9", "24/7 | B", "24/7 | F", "24/7 | S"]);
+        assert_eq!(result[0].channels.len(), 2);
+        assert_eq!(result[2].channels[0].header.group, "24/7 | B");
+        assert_eq!(result[4].id, 6);
+    }
+}
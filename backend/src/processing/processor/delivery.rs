@@ -0,0 +1,80 @@
+use crate::model::{Config, ConfigDelivery, ConfigTarget, DeliveryProtocol};
+use crate::repository::m3u_repository::m3u_get_epg_file_path;
+use crate::repository::storage::get_target_storage_path;
+use crate::repository::xtream_repository::{xtream_get_epg_file_path, xtream_get_storage_path};
+use crate::utils;
+use crate::utils::ftp::ftp_upload;
+use crate::utils::request::sanitize_sensitive_info;
+use log::{info, warn};
+use shared::error::{notify_err, TuliproxError, TuliproxErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn get_m3u_artifact(cfg: &Config, target: &ConfigTarget, delivery: &ConfigDelivery) -> Option<(PathBuf, String)> {
+    let m3u_filename = target.get_m3u_output().and_then(|output| output.filename.as_ref());
+    let path = m3u_filename.and_then(|filename| utils::get_file_path(&cfg.working_dir, Some(PathBuf::from(filename))))
+        .filter(|path| path.exists());
+    let Some(path) = path else {
+        warn!("Delivery of target {} skips the m3u, it needs a m3u output with `filename`", target.name);
+        return None;
+    };
+    let remote_name = delivery.m3u_filename.clone()
+        .or_else(|| path.file_name().map(|name| name.to_string_lossy().to_string()))
+        .unwrap_or_else(|| format!("{}.m3u", target.name));
+    Some((path, remote_name))
+}
+
+fn get_xmltv_artifact(cfg: &Config, target: &ConfigTarget, delivery: &ConfigDelivery) -> Option<(PathBuf, String)> {
+    let m3u_epg_path = get_target_storage_path(cfg, &target.name).map(|path| m3u_get_epg_file_path(&path));
+    let xtream_epg_path = xtream_get_storage_path(cfg, &target.name).map(|path| xtream_get_epg_file_path(&path));
+    let path = [m3u_epg_path, xtream_epg_path].into_iter().flatten().find(|path| path.exists())?;
+    let remote_name = delivery.xmltv_filename.clone().unwrap_or_else(|| format!("{}.xml", target.name));
+    Some((path, remote_name))
+}
+
+async fn upload_file(client: &reqwest::Client, delivery: &ConfigDelivery, path: &Path, remote_name: &str) -> Result<(), String> {
+    let (Some(url), Some(protocol)) = (delivery.t_url.as_ref(), delivery.t_protocol) else {
+        return Err("delivery is not prepared".to_string());
+    };
+    let content = tokio::fs::read(path).await.map_err(|err| format!("can't read {}: {err}", path.display()))?;
+    let (username, password) = delivery.get_credentials();
+    match protocol {
+        DeliveryProtocol::Http => {
+            let file_url = url.join(remote_name).map_err(|err| err.to_string())?;
+            let mut request = client.put(file_url).body(content);
+            if let Some(username) = username {
+                request = request.basic_auth(username, password);
+            }
+            let response = request.send().await.map_err(|err| err.to_string())?;
+            if response.status().is_success() {
+                Ok(())
+            } else {
+                Err(format!("status {}", response.status()))
+            }
+        }
+        DeliveryProtocol::Ftp => {
+            ftp_upload(url, username.as_deref(), password.as_deref(), remote_name, &content).await.map_err(|err| err.to_string())
+        }
+    }
+}
+
+/// Uploads the generated m3u and xmltv files of the target to the configured deliveries.
+pub async fn deliver_playlist(client: &Arc<reqwest::Client>, cfg: &Config, target: &ConfigTarget) -> Vec<TuliproxError> {
+    let mut errors = vec![];
+    let Some(deliveries) = target.delivery.as_ref() else { return errors };
+    for delivery in deliveries {
+        let artifacts = [
+            if delivery.m3u { get_m3u_artifact(cfg, target, delivery) } else { None },
+            if delivery.xmltv { get_xmltv_artifact(cfg, target, delivery) } else { None },
+        ];
+        for (path, remote_name) in artifacts.into_iter().flatten() {
+            let destination = format!("{}/{remote_name}", delivery.url.trim_end_matches('/'));
+            let destination = sanitize_sensitive_info(&destination);
+            match upload_file(client, delivery, &path, &remote_name).await {
+                Ok(()) => info!("Delivered {} to {destination}", path.display()),
+                Err(err) => errors.push(notify_err!(format!("Failed to deliver {} to {destination}: {err}", path.display()))),
+            }
+        }
+    }
+    errors
+}
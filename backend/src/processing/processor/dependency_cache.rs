@@ -0,0 +1,63 @@
+use crate::model::{Config, ConfigTarget, FetchedPlaylist, PlaylistGroup, TVGuide};
+use shared::error::{notify_err, TuliproxError, TuliproxErrorKind};
+use std::collections::{HashMap, HashSet};
+use tokio::sync::Mutex;
+
+struct ProcessedPlaylist {
+    input_id: u16,
+    playlistgroups: Vec<PlaylistGroup>,
+    epg: Option<TVGuide>,
+}
+
+/// Keeps the processed playlists of the targets other targets depend on for one refresh cycle.
+/// The dependent targets are processed with these playlists instead of fetching the providers again.
+pub struct DependencyCache {
+    dependencies: HashSet<String>,
+    playlists: Mutex<HashMap<String, Vec<ProcessedPlaylist>>>,
+}
+
+impl DependencyCache {
+    pub fn new(cfg: &Config) -> Self {
+        let dependencies = cfg.sources.sources.iter()
+            .flat_map(|source| source.targets.iter())
+            .filter_map(|target| target.depends_on.as_ref())
+            .flatten()
+            .cloned()
+            .collect();
+        Self { dependencies, playlists: Mutex::new(HashMap::new()) }
+    }
+
+    /// Stores the playlists of a target after the processing pipe, if another target depends on it.
+    pub async fn store(&self, target: &ConfigTarget, playlists: &[FetchedPlaylist<'_>]) {
+        if self.dependencies.contains(&target.name) {
+            let processed = playlists.iter().map(|fpl| ProcessedPlaylist {
+                input_id: fpl.input.id,
+                playlistgroups: fpl.playlistgroups.clone(),
+                epg: fpl.epg.clone(),
+            }).collect();
+            self.playlists.lock().await.insert(target.name.clone(), processed);
+        }
+    }
+
+    /// Returns the playlists of all targets the given target depends on.
+    /// Fails if a dependency was not processed in this refresh cycle.
+    pub async fn get_playlists<'a>(&self, cfg: &'a Config, target: &ConfigTarget) -> Result<Vec<FetchedPlaylist<'a>>, TuliproxError> {
+        let playlists = self.playlists.lock().await;
+        let mut result = vec![];
+        for dependency_name in target.depends_on.iter().flatten() {
+            let Some(processed) = playlists.get(dependency_name) else {
+                return Err(notify_err!(format!("Target {} depends on {dependency_name}, which has no processed playlist", target.name)));
+            };
+            for ppl in processed {
+                if let Some(input) = cfg.get_input_by_id(ppl.input_id) {
+                    result.push(FetchedPlaylist {
+                        input,
+                        playlistgroups: ppl.playlistgroups.clone(),
+                        epg: ppl.epg.clone(),
+                    });
+                }
+            }
+        }
+        Ok(result)
+    }
+}
@@ -1,12 +1,47 @@
-use crate::model::{Epg, TVGuide, XmlTag, XmlTagIcon, EPG_ATTRIB_ID};
-use crate::model::{EpgConfig, EpgSmartMatchConfig};
-use crate::model::{FetchedPlaylist, PlaylistItem};
+use crate::model::{Epg, TVGuide, XmlTag, XmlTagIcon, EPG_ATTRIB_CHANNEL, EPG_ATTRIB_ID, EPG_ATTRIB_START, EPG_ATTRIB_STOP, EPG_TAG_CHANNEL, EPG_TAG_DESC, EPG_TAG_DISPLAY_NAME, EPG_TAG_PROGRAMME, EPG_TAG_TITLE};
+use crate::model::{Config, ConfigInput, ConfigTarget, EpgAliasesConfig, EpgConfig, EpgPhoneticAlgorithm, EpgProgrammeFilter, EpgSmartMatchConfig, EpgTimeWindow, InputType};
+use crate::model::{EpgChannelMatch, EpgMatchKind, FetchedPlaylist, PlaylistGroup, PlaylistItem, XC_ACTION_GET_CATCHUP_TABLE, XC_TAG_EPG_LISTINGS, XC_TAG_STREAM_ID};
 use crate::processing::parser::xmltv::normalize_channel_name;
-use log::{debug, trace};
-use rphonetic::{DoubleMetaphone, Encoder};
+use crate::utils::{format_xmltv_time, parse_timeshift};
+use crate::utils::request::{download_text_content, sanitize_sensitive_info};
+use crate::utils::xtream::get_xtream_player_api_action_url;
+use base64::engine::general_purpose;
+use base64::Engine;
+use log::{debug, info, trace};
+use serde_json::Value;
+use std::sync::Arc;
+use rphonetic::{Cologne, DoubleMetaphone, Encoder, Metaphone, Soundex};
 use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
-use shared::model::XtreamCluster;
+use shared::model::{UUIDType, XtreamCluster};
+
+/// The phonetic encoder used to group channel names for fuzzy matching.
+pub enum PhoneticEncoder {
+    Metaphone(Metaphone),
+    DoubleMetaphone(DoubleMetaphone),
+    Cologne(Cologne),
+    Soundex(Soundex),
+}
+
+impl PhoneticEncoder {
+    pub fn new(algorithm: EpgPhoneticAlgorithm) -> Self {
+        match algorithm {
+            EpgPhoneticAlgorithm::Metaphone => PhoneticEncoder::Metaphone(Metaphone::default()),
+            EpgPhoneticAlgorithm::DoubleMetaphone => PhoneticEncoder::DoubleMetaphone(DoubleMetaphone::default()),
+            EpgPhoneticAlgorithm::Cologne => PhoneticEncoder::Cologne(Cologne),
+            EpgPhoneticAlgorithm::Soundex => PhoneticEncoder::Soundex(Soundex::default()),
+        }
+    }
+
+    pub fn encode(&self, name: &str) -> String {
+        match self {
+            PhoneticEncoder::Metaphone(encoder) => encoder.encode(name),
+            PhoneticEncoder::DoubleMetaphone(encoder) => encoder.encode(name),
+            PhoneticEncoder::Cologne(encoder) => encoder.encode(name),
+            PhoneticEncoder::Soundex(encoder) => encoder.encode(name),
+        }
+    }
+}
 
 pub struct EpgIdCache<'a> {
     pub channel_epg_id: HashSet<Cow<'a, str>>,
@@ -14,9 +49,15 @@ pub struct EpgIdCache<'a> {
     pub phonetics: HashMap<String, HashSet<String>>,
     pub processed: HashSet<String>,
     pub smart_match_config: EpgSmartMatchConfig,
-    pub metaphone: DoubleMetaphone,
+    pub phonetic_encoder: PhoneticEncoder,
     pub smart_match_enabled: bool, // smart match is enabled, normalizing names
     pub fuzzy_match_enabled: bool, // fuzzy matching enabled
+    pub time_shift: Option<i32>, // minutes, the input epg time_shift
+    pub channel_time_shift: HashMap<String, i32>, // minutes, epg_id to the channel time_shift
+    pub programme_filter: Option<EpgProgrammeFilter>, // the target epg_filter
+    pub programme_window: Option<EpgTimeWindow>, // the target epg_days_past and epg_days_future
+    pub alias_channels: HashSet<UUIDType>, // channels pinned by the epg aliases
+    pub match_kinds: HashMap<String, (EpgMatchKind, Option<u16>)>, // normalized name to the match kind and fuzzy score
 }
 
 impl EpgIdCache<'_> {
@@ -37,11 +78,16 @@ impl EpgIdCache<'_> {
             normalized: HashMap::new(),
             phonetics: HashMap::new(),
             processed: HashSet::new(),
-            metaphone: DoubleMetaphone::default(),
+            phonetic_encoder: PhoneticEncoder::new(normalize_config.phonetic_algorithm),
             smart_match_enabled: normalize_config.enabled,
             fuzzy_match_enabled: normalize_config.enabled && normalize_config.fuzzy_matching,
             smart_match_config: normalize_config,
-
+            time_shift: epg_config.and_then(|epg_config| epg_config.t_time_shift),
+            channel_time_shift: HashMap::new(),
+            programme_filter: None,
+            programme_window: None,
+            alias_channels: HashSet::new(),
+            match_kinds: HashMap::new(),
         }
     }
 
@@ -82,20 +128,32 @@ impl EpgIdCache<'_> {
     }
 
     pub(crate) fn phonetic(&self, name: &str) -> String {
-        self.metaphone.encode(name)
+        self.phonetic_encoder.encode(name)
     }
 
-    pub fn collect_epg_id(&mut self, fp: &mut FetchedPlaylist) {
+    pub fn collect_epg_id(&mut self, fp: &mut FetchedPlaylist, epg_aliases: Option<&EpgAliasesConfig>) {
         let smart_match_enabled = self.smart_match_enabled;
         let fuzzy_matching = self.fuzzy_match_enabled;
 
-        for channel in fp.playlistgroups.iter().flat_map(|g| &g.channels) {
+        for channel in fp.playlistgroups.iter_mut().flat_map(|g| &mut g.channels) {
+            // the aliases pin the epg_id before any smart matching
+            if channel.header.xtream_cluster == XtreamCluster::Live {
+                if let Some(epg_id) = epg_aliases.and_then(|aliases| aliases.get_epg_id(&channel.header.name)) {
+                    trace!("Pinned channel {} to epg alias {epg_id}", channel.header.name);
+                    channel.header.epg_channel_id = Some(epg_id.to_string());
+                    self.alias_channels.insert(channel.header.uuid);
+                }
+            }
             let mut missing_epg_id = true;
             // insert epg_id to known channel epg_ids
             if let Some(id) = channel.header.epg_channel_id.as_deref() {
                 if !id.is_empty() {
                     missing_epg_id = false;
                     self.channel_epg_id.insert(Cow::Owned(id.to_string()));
+                    // the channel time_shift overrides the input time_shift
+                    if let Some(minutes) = parse_timeshift(Some(&channel.header.time_shift)) {
+                        self.channel_time_shift.insert(id.to_lowercase(), minutes);
+                    }
                 }
             }
 
@@ -110,11 +168,17 @@ impl EpgIdCache<'_> {
         }
     }
 
+    /// Returns the time shift in minutes for the programmes of the epg channel.
+    pub fn get_time_shift(&self, epg_id: &str) -> Option<i32> {
+        self.channel_time_shift.get(epg_id).copied().or(self.time_shift)
+    }
+
     pub fn match_with_normalized(&mut self, epg_id: &str, normalized_epg_ids: &[String]) -> bool {
         for key in normalized_epg_ids {
             if let Some(entry) = self.normalized.get_mut(key) {
                 entry.replace(epg_id.to_string());
                 self.channel_epg_id.insert(epg_id.to_string().into());
+                self.match_kinds.insert(key.clone(), (EpgMatchKind::Normalized, None));
                 return true;
             }
         }
@@ -132,13 +196,16 @@ impl EpgIdCache<'_> {
 /// let mut new_epg = Vec::new();
 /// let mut playlist = FetchedPlaylist::default();
 /// let mut id_cache = EpgIdCache::new(None);
-/// assign_channel_epg(&mut new_epg, &mut playlist, &mut id_cache);
+/// assign_channel_epg(&mut new_epg, &mut playlist, &mut id_cache, &mut matches);
 /// ```
-fn assign_channel_epg(new_epg: &mut Vec<Epg>, fp: &mut FetchedPlaylist, id_cache: &mut EpgIdCache) {
-    id_cache.normalized.retain(|_, v| v.is_some());
+fn assign_channel_epg(new_epg: &mut Vec<Epg>, fp: &mut FetchedPlaylist, id_cache: &mut EpgIdCache, matches: &mut Vec<EpgChannelMatch>) {
+    let mut guide_epg_ids = HashSet::new();
+    let mut smart_matched = HashSet::new();
     if let Some(tv_guide) = &fp.epg {
         let mut processed_epgs = vec![];
         if let Some(epg_sources) = tv_guide.filter(id_cache) {
+            // only the normalized names matched by the guide are assigned
+            id_cache.normalized.retain(|_, v| v.is_some());
             let mut icon_assigned = HashSet::new();
             for epg_source in epg_sources {
                 // icon tags
@@ -159,6 +226,7 @@ fn assign_channel_epg(new_epg: &mut Vec<Epg>, fp: &mut FetchedPlaylist, id_cache
                                 if epg_id.is_some() {
                                     trace!("Matched channel {} to epg {epg_id:?}", chan.header.name);
                                     chan.header.epg_channel_id.clone_from(epg_id);
+                                    smart_matched.insert(chan.header.uuid);
                                 }
                             }
                         }
@@ -191,10 +259,38 @@ fn assign_channel_epg(new_epg: &mut Vec<Epg>, fp: &mut FetchedPlaylist, id_cache
             }
         }
 
+        guide_epg_ids = processed_epgs.iter().flat_map(|epg| &epg.children)
+            .filter(|tag| tag.name == EPG_TAG_CHANNEL)
+            .filter_map(|tag| tag.get_attribute_value(EPG_ATTRIB_ID).cloned())
+            .collect();
         if let Some(epg) = TVGuide::merge(processed_epgs) {
             new_epg.push(epg);
         }
     }
+
+    for channel in fp.playlistgroups.iter().flat_map(|g| &g.channels).filter(|c| c.header.xtream_cluster == XtreamCluster::Live) {
+        let header = &channel.header;
+        let (kind, score) = match header.epg_channel_id.as_ref() {
+            Some(epg_id) if guide_epg_ids.contains(epg_id) => {
+                if smart_matched.contains(&header.uuid) {
+                    id_cache.match_kinds.get(&id_cache.normalize(&header.name)).copied().unwrap_or((EpgMatchKind::Normalized, None))
+                } else if id_cache.alias_channels.contains(&header.uuid) {
+                    (EpgMatchKind::Alias, None)
+                } else {
+                    (EpgMatchKind::Id, None)
+                }
+            }
+            _ => (EpgMatchKind::Unmatched, None),
+        };
+        matches.push(EpgChannelMatch {
+            input: fp.input.name.clone(),
+            group: header.group.clone(),
+            name: header.name.clone(),
+            epg_id: header.epg_channel_id.clone(),
+            kind,
+            score,
+        });
+    }
 }
 
 /// Processes a fetched playlist and assigns EPG data to its channels.
@@ -206,23 +302,122 @@ fn assign_channel_epg(new_epg: &mut Vec<Epg>, fp: &mut FetchedPlaylist, id_cache
 /// ```
 /// let mut playlist = FetchedPlaylist::default();
 /// let mut epg_data = Vec::new();
-/// process_playlist_epg(&mut playlist, &mut epg_data);
+/// process_playlist_epg(&mut playlist, &ConfigTarget::default(), None, &mut epg_data, &mut matches);
 /// ```
-pub fn process_playlist_epg(fp: &mut FetchedPlaylist, epg: &mut Vec<Epg>) {
+pub fn process_playlist_epg(fp: &mut FetchedPlaylist, target: &ConfigTarget, epg_aliases: Option<&EpgAliasesConfig>, epg: &mut Vec<Epg>, matches: &mut Vec<EpgChannelMatch>) {
     // collect all epg_channel ids
     let mut id_cache = EpgIdCache::new(fp.input.epg.as_ref());
-    id_cache.collect_epg_id(fp);
+    id_cache.programme_filter.clone_from(&target.epg_filter);
+    id_cache.programme_window = target.get_epg_time_window(chrono::Utc::now().timestamp());
+    id_cache.collect_epg_id(fp, epg_aliases);
 
     if id_cache.is_empty() && !id_cache.smart_match_enabled {
         debug!("No epg ids found");
-    } else {
-        assign_channel_epg(epg, fp, &mut id_cache);
     }
+    // without epg ids the guide is not read, the channels are reported as unmatched
+    assign_channel_epg(epg, fp, &mut id_cache, matches);
 }
 
+// Lowest priority, the xmltv sources win when the guides are merged.
+const XTREAM_EPG_FALLBACK_PRIORITY: i16 = i16::MAX;
+
+// The provider sends the texts base64 encoded.
+fn get_listing_text(listing: &Value, field: &str) -> Option<String> {
+    let value = listing.get(field).and_then(Value::as_str)?;
+    let text = general_purpose::STANDARD.decode(value).ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .unwrap_or_else(|| value.to_string());
+    Some(text).filter(|text| !text.trim().is_empty())
+}
+
+fn get_listing_timestamp(listing: &Value, field: &str) -> Option<i64> {
+    match listing.get(field)? {
+        Value::Number(value) => value.as_i64(),
+        Value::String(value) => value.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Creates the programme tags for `epg_id` from a provider `get_simple_data_table` or `get_short_epg` response.
+pub fn create_xtream_epg_programmes(epg_id: &str, content: &str, programme_filter: Option<&EpgProgrammeFilter>, programme_window: Option<&EpgTimeWindow>) -> Vec<XmlTag> {
+    let Ok(doc) = serde_json::from_str::<Value>(content) else { return vec![] };
+    let Some(listings) = doc.get(XC_TAG_EPG_LISTINGS).and_then(Value::as_array) else { return vec![] };
+    listings.iter().filter_map(|listing| {
+        let title = get_listing_text(listing, EPG_TAG_TITLE)?;
+        if programme_filter.is_some_and(|filter| !filter.is_allowed(&[title.as_str()])) {
+            return None;
+        }
+        let start_ts = get_listing_timestamp(listing, "start_timestamp")?;
+        let stop_ts = get_listing_timestamp(listing, "stop_timestamp")?;
+        if programme_window.is_some_and(|window| !window.is_inside(Some(start_ts), Some(stop_ts))) {
+            return None;
+        }
+        let start = format_xmltv_time(start_ts)?;
+        let stop = format_xmltv_time(stop_ts)?;
+        let mut programme = XmlTag::new(EPG_TAG_PROGRAMME.to_string(), Some(HashMap::from([
+            (EPG_ATTRIB_CHANNEL.to_string(), epg_id.to_string()),
+            (EPG_ATTRIB_START.to_string(), start),
+            (EPG_ATTRIB_STOP.to_string(), stop),
+        ])));
+        let mut children = vec![XmlTag { value: Some(title), ..XmlTag::new(EPG_TAG_TITLE.to_string(), None) }];
+        if let Some(description) = get_listing_text(listing, "description") {
+            children.push(XmlTag { value: Some(description), ..XmlTag::new(EPG_TAG_DESC.to_string(), None) });
+        }
+        programme.children = Some(children);
+        Some(programme)
+    }).collect()
+}
+
+/// Fetches the provider short epg for the live channels of inputs with `xtream_epg_fallback`,
+/// which have no programmes in the xmltv guides.
+/// Channels without an epg id get one from the input name and the stream id.
+pub async fn xtream_epg_fallback(client: &Arc<reqwest::Client>, cfg: &Config, target: &ConfigTarget, epg: &[Epg], playlist: &mut [PlaylistGroup]) -> Option<Epg> {
+    let is_enabled = |input: &ConfigInput| input.input_type == InputType::Xtream && input.options.as_ref().is_some_and(|options| options.xtream_epg_fallback);
+    let mut programme_ids: HashSet<String> = epg.iter().flat_map(|guide| &guide.children)
+        .filter(|tag| tag.name == EPG_TAG_PROGRAMME)
+        .filter_map(|tag| tag.get_attribute_value(EPG_ATTRIB_CHANNEL).map(|id| id.to_lowercase()))
+        .collect();
+    let programme_window = target.get_epg_time_window(chrono::Utc::now().timestamp());
+    let mut children = vec![];
+    for channel in playlist.iter_mut().flat_map(|group| &mut group.channels) {
+        let header = &mut channel.header;
+        if header.xtream_cluster != XtreamCluster::Live {
+            continue;
+        }
+        let Some(input) = cfg.get_input_by_name(&header.input_name).filter(|input| is_enabled(input)) else { continue };
+        let Some(provider_id) = header.get_provider_id() else { continue };
+        let epg_id = header.epg_channel_id.get_or_insert_with(|| format!("{provider_id}.{}", input.name.to_lowercase().replace(' ', "_"))).clone();
+        if programme_ids.contains(&epg_id.to_lowercase()) {
+            continue;
+        }
+        let Some(action_url) = get_xtream_player_api_action_url(input, XC_ACTION_GET_CATCHUP_TABLE) else { continue };
+        let url = format!("{action_url}&{XC_TAG_STREAM_ID}={provider_id}");
+        match download_text_content(Arc::clone(client), input, &url, None).await {
+            Ok((content, _)) => {
+                let programmes = create_xtream_epg_programmes(&epg_id, &content, target.epg_filter.as_ref(), programme_window.as_ref());
+                if !programmes.is_empty() {
+                    programme_ids.insert(epg_id.to_lowercase());
+                    let mut channel_tag = XmlTag::new(EPG_TAG_CHANNEL.to_string(), Some(HashMap::from([(EPG_ATTRIB_ID.to_string(), epg_id)])));
+                    channel_tag.children = Some(vec![XmlTag { value: Some(header.name.clone()), ..XmlTag::new(EPG_TAG_DISPLAY_NAME.to_string(), None) }]);
+                    children.push(channel_tag);
+                    children.extend(programmes);
+                }
+            }
+            Err(err) => debug!("Failed to download short epg for {}: {}", header.name, sanitize_sensitive_info(&err.to_string())),
+        }
+    }
+    if children.is_empty() {
+        None
+    } else {
+        info!("Added {} programmes from the provider short epg", children.iter().filter(|tag| tag.name == EPG_TAG_PROGRAMME).count());
+        Some(Epg { priority: XTREAM_EPG_FALLBACK_PRIORITY, logo_override: false, attributes: None, children })
+    }
+}
 
 #[cfg(test)]
 mod tests {
+    use super::{create_xtream_epg_programmes, PhoneticEncoder};
+    use crate::model::{EpgPhoneticAlgorithm, EpgProgrammeFilter, EpgSimilarityMetric, EpgTimeWindow};
     use rand::distr::Alphanumeric;
     use rand::Rng;
     use rphonetic::{DoubleMetaphone, Encoder};
@@ -252,4 +447,42 @@ mod tests {
         let elapsed = now.elapsed();
         println!("Elapsed time: {}.{:03} secs", elapsed.as_secs(), elapsed.subsec_millis());
     }
+
+    #[test]
+    fn test_phonetic_encoder() {
+        let metaphone = PhoneticEncoder::new(EpgPhoneticAlgorithm::Metaphone);
+        let cologne = PhoneticEncoder::new(EpgPhoneticAlgorithm::Cologne);
+        let soundex = PhoneticEncoder::new(EpgPhoneticAlgorithm::Soundex);
+        assert_ne!(metaphone.encode("meyer"), metaphone.encode("maier"));
+        assert_eq!(cologne.encode("meyer"), cologne.encode("maier"));
+        assert_eq!(soundex.encode("robert"), soundex.encode("rupert"));
+        assert_eq!(PhoneticEncoder::new(EpgPhoneticAlgorithm::DoubleMetaphone).encode("zdfneo"), "STFN");
+
+        assert!((EpgSimilarityMetric::Levenshtein.similarity("sat1gold", "sat1gold") - 1.0).abs() < f64::EPSILON);
+        assert!(EpgSimilarityMetric::JaroWinkler.similarity("prosieben", "prosiebenmaxx") > EpgSimilarityMetric::Levenshtein.similarity("prosieben", "prosiebenmaxx"));
+    }
+
+    #[test]
+    fn test_create_xtream_epg_programmes() {
+        // titles and descriptions are base64 encoded: "News", "Teleshopping", "Daily news"
+        let content = r#"{"epg_listings":[
+            {"title":"TmV3cw==","description":"RGFpbHkgbmV3cw==","start_timestamp":"1748808000","stop_timestamp":"1748809800"},
+            {"title":"VGVsZXNob3BwaW5n","description":"","start_timestamp":1748809800,"stop_timestamp":1748811600},
+            {"title":"TmV3cw==","start_timestamp":"invalid","stop_timestamp":"1748811600"}]}"#;
+        let programmes = create_xtream_epg_programmes("ard.de", content, None, None);
+        assert_eq!(programmes.len(), 2);
+        assert_eq!(programmes[0].get_attribute_value("channel").unwrap(), "ard.de");
+        assert_eq!(programmes[0].get_attribute_value("start").unwrap(), "20250601200000 +0000");
+        assert_eq!(programmes[0].get_attribute_value("stop").unwrap(), "20250601203000 +0000");
+        let children = programmes[0].children.as_ref().unwrap();
+        assert_eq!((children[0].value.as_deref(), children[1].value.as_deref()), (Some("News"), Some("Daily news")));
+
+        let mut filter = EpgProgrammeFilter { drop: Some(vec!["(?i)shopping".to_string()]), ..Default::default() };
+        filter.prepare().unwrap();
+        assert_eq!(create_xtream_epg_programmes("ard.de", content, Some(&filter), None).len(), 1);
+        assert!(create_xtream_epg_programmes("ard.de", "[]", None, None).is_empty());
+        // the first programme ends before the window
+        let window = EpgTimeWindow { from: Some(1_748_809_801), to: None };
+        assert_eq!(create_xtream_epg_programmes("ard.de", content, None, Some(&window)).len(), 1);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,107 @@
+use crate::model::{ChannelFailover, ConfigTarget, PlaylistGroup, PlaylistItem};
+use crate::utils::debug_if_enabled;
+use deunicode::deunicode;
+use shared::model::XtreamCluster;
+use std::collections::HashMap;
+
+// Channels are the same when they have the same epg id, otherwise the same name without case, spaces and punctuation.
+fn get_channel_key(channel: &PlaylistItem) -> String {
+    let header = &channel.header;
+    match header.epg_channel_id.as_deref().map(str::trim) {
+        Some(epg_id) if !epg_id.is_empty() => format!("epg:{}", epg_id.to_lowercase()),
+        _ => {
+            let name: String = deunicode(&header.name).chars()
+                .filter(char::is_ascii_alphanumeric)
+                .map(|c| c.to_ascii_lowercase())
+                .collect();
+            if name.is_empty() { name } else { format!("name:{name}") }
+        }
+    }
+}
+
+// The same live channel of several inputs is kept once, the first occurrence is kept like for `remove_duplicates`.
+// The urls of the removed channels are tried in playlist order when the stream of the kept channel fails.
+pub(in crate::processing::processor) fn merge_failover_channels(target: &ConfigTarget, playlist: &mut Vec<PlaylistGroup>) {
+    if !target.is_channel_failover() {
+        return;
+    }
+    // channel key -> (group index, channel index)
+    let mut primaries: HashMap<String, (usize, usize)> = HashMap::new();
+    let mut failover: Vec<((usize, usize), ChannelFailover)> = vec![];
+    let mut count = 0;
+    for (group_idx, group) in playlist.iter_mut().enumerate().filter(|(_, group)| group.xtream_cluster == XtreamCluster::Live) {
+        let mut channel_idx = 0;
+        group.channels.retain(|channel| {
+            let key = get_channel_key(channel);
+            let keep = if key.is_empty() {
+                true
+            } else {
+                match primaries.get(&key) {
+                    None => {
+                        primaries.insert(key, (group_idx, channel_idx));
+                        true
+                    }
+                    Some(primary) => {
+                        failover.push((*primary, ChannelFailover { input_name: channel.header.input_name.clone(), url: channel.header.url.clone() }));
+                        false
+                    }
+                }
+            };
+            if keep {
+                channel_idx += 1;
+            }
+            keep
+        });
+    }
+    for ((group_idx, channel_idx), channel_failover) in failover {
+        let header = &mut playlist[group_idx].channels[channel_idx].header;
+        // the same channel of the same input is only a duplicate
+        if header.input_name != channel_failover.input_name && header.url != channel_failover.url
+            && !header.failover.contains(&channel_failover) {
+            header.failover.push(channel_failover);
+            count += 1;
+        }
+    }
+    playlist.retain(|group| !group.channels.is_empty());
+    debug_if_enabled!("Merged {count} failover channels for {}", target.name);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::{ChannelFailover, ConfigTarget, ConfigTargetOptions, PlaylistGroup, PlaylistItem, PlaylistItemHeader};
+    use crate::processing::processor::failover::merge_failover_channels;
+    use shared::model::XtreamCluster;
+
+    fn create_group(id: u32, title: &str, channels: &[(&str, Option<&str>, &str)]) -> PlaylistGroup {
+        PlaylistGroup {
+            id,
+            title: title.to_string(),
+            channels: channels.iter().map(|(name, epg_id, input)| PlaylistItem {
+                header: PlaylistItemHeader {
+                    name: (*name).to_string(),
+                    epg_channel_id: epg_id.map(ToString::to_string),
+                    input_name: (*input).to_string(),
+                    url: format!("http://{input}/{name}"),
+                    ..Default::default()
+                }
+            }).collect(),
+            xtream_cluster: XtreamCluster::Live,
+        }
+    }
+
+    #[test]
+    fn test_merge_failover_channels() {
+        let target = ConfigTarget { options: Some(ConfigTargetOptions { channel_failover: true, ..Default::default() }), ..Default::default() };
+        let mut playlist = vec![
+            create_group(1, "News", &[("ZDF HD", Some("zdf.de"), "a"), ("CNN", None, "a")]),
+            create_group(2, "Provider B", &[("ZDF", Some("ZDF.de"), "b"), ("C.N.N", None, "b"), ("cnn", None, "c")]),
+            create_group(3, "Same input", &[("CNN", None, "a")]),
+        ];
+        merge_failover_channels(&target, &mut playlist);
+        assert_eq!(playlist.len(), 1);
+        let channels = &playlist[0].channels;
+        assert_eq!(channels.len(), 2);
+        assert_eq!(channels[0].header.failover, vec![ChannelFailover { input_name: "b".to_string(), url: "http://b/ZDF".to_string() }]);
+        assert_eq!(channels[1].header.failover.iter().map(|failover| failover.input_name.as_str()).collect::<Vec<_>>(), vec!["b", "c"]);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::model::{Config, ConfigInput, InputType, PlaylistGroup, ProcessTargets};
+use log::info;
+use shared::error::TuliproxError;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+struct SharedPlaylist {
+    // the number of inputs which still need the playlist in this cycle
+    remaining: usize,
+    input_name: String,
+    playlistgroups: Option<Vec<PlaylistGroup>>,
+}
+
+/// Shares the fetched and parsed provider playlist of an input across all sources of one refresh cycle.
+/// Only inputs which are used more than once are cached, the playlist is released after the last usage.
+pub struct FetchCache {
+    entries: HashMap<String, Arc<Mutex<SharedPlaylist>>>,
+}
+
+impl FetchCache {
+    pub fn new(cfg: &Config, user_targets: &ProcessTargets, is_input_enabled: fn(&ConfigInput, &ProcessTargets) -> bool) -> Self {
+        let mut usages = HashMap::<String, usize>::new();
+        for source in &cfg.sources.sources {
+            for input in &source.inputs {
+                if is_input_enabled(input, user_targets) {
+                    if let Some(key) = get_cache_key(input) {
+                        *usages.entry(key).or_default() += 1;
+                    }
+                }
+            }
+        }
+        let entries = usages.into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(key, count)| (key, Arc::new(Mutex::new(SharedPlaylist { remaining: count, input_name: String::new(), playlistgroups: None }))))
+            .collect();
+        Self { entries }
+    }
+
+    /// Returns the cached playlist of an identical input or executes the fetch.
+    /// A failed fetch is not cached, the next input with the same provider playlist fetches again.
+    pub async fn get_playlist<F>(&self, input: &ConfigInput, fetch: F) -> (Vec<PlaylistGroup>, Vec<TuliproxError>)
+    where
+        F: Future<Output=(Vec<PlaylistGroup>, Vec<TuliproxError>)>,
+    {
+        let Some(entry) = get_cache_key(input).and_then(|key| self.entries.get(&key)) else {
+            return fetch.await;
+        };
+        // the lock is held while fetching, an identical input processed in parallel waits for the result
+        let mut shared = entry.lock().await;
+        shared.remaining = shared.remaining.saturating_sub(1);
+        if shared.playlistgroups.is_some() {
+            info!("Using the playlist of input {} for input {}", shared.input_name, input.name);
+            let mut playlistgroups = if shared.remaining == 0 {
+                shared.playlistgroups.take().unwrap_or_default()
+            } else {
+                shared.playlistgroups.clone().unwrap_or_default()
+            };
+            if shared.input_name != input.name {
+                for group in &mut playlistgroups {
+                    for channel in &mut group.channels {
+                        channel.header.input_name.clone_from(&input.name);
+                    }
+                }
+            }
+            return (playlistgroups, vec![]);
+        }
+        let (playlistgroups, errors) = fetch.await;
+        if errors.is_empty() && !playlistgroups.is_empty() && shared.remaining > 0 {
+            shared.input_name.clone_from(&input.name);
+            shared.playlistgroups = Some(playlistgroups.clone());
+        }
+        (playlistgroups, errors)
+    }
+}
+
+// inputs with the same key download and parse the same provider playlist
+fn get_cache_key(input: &ConfigInput) -> Option<String> {
+    match input.input_type {
+        InputType::M3u | InputType::Xtream => {
+            let headers: BTreeMap<&String, &String> = input.headers.iter().collect();
+            Some(format!("{:?}|{}|{:?}|{:?}|{:?}|{:?}|{headers:?}|{:?}|{:?}", input.input_type, input.url, input.username,
+                         input.password, input.persist, input.method, input.options, input.auth))
+        }
+        InputType::M3uBatch | InputType::XtreamBatch => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{InputFetchMethod, PlaylistItem, PlaylistItemHeader};
+    use shared::model::XtreamCluster;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn create_config_input(name: &str, url: &str) -> ConfigInput {
+        ConfigInput {
+            id: 0,
+            name: name.to_string(),
+            url: url.to_string(),
+            epg: None,
+            username: None,
+            password: None,
+            persist: None,
+            enabled: true,
+            input_type: InputType::M3u,
+            max_connections: 0,
+            priority: 0,
+            aliases: None,
+            headers: HashMap::default(),
+            options: None,
+            method: InputFetchMethod::default(),
+            rate_limit: None,
+            tls: None,
+            auth: None,
+            t_base_url: String::default(),
+            t_rate_limiter: None,
+            t_client: None,
+            t_auth_token: None,
+        }
+    }
+
+    async fn fetch(input: &ConfigInput, counter: &AtomicUsize) -> (Vec<PlaylistGroup>, Vec<TuliproxError>) {
+        tokio::task::yield_now().await;
+        counter.fetch_add(1, Ordering::SeqCst);
+        let header = PlaylistItemHeader { title: "Sport".to_string(), input_name: input.name.clone(), ..Default::default() };
+        (vec![PlaylistGroup { id: 1, title: "DE".to_string(), channels: vec![PlaylistItem { header }], xtream_cluster: XtreamCluster::default() }], vec![])
+    }
+
+    #[tokio::test]
+    async fn test_fetch_cache() {
+        let inputs = [create_config_input("a", "http://provider/get.m3u"),
+            create_config_input("b", "http://provider/get.m3u"),
+            create_config_input("c", "http://other/get.m3u")];
+        let entries = inputs.iter().take(2).filter_map(get_cache_key).map(|key|
+            (key, Arc::new(Mutex::new(SharedPlaylist { remaining: 2, input_name: String::new(), playlistgroups: None })))).collect();
+        let cache = FetchCache { entries };
+        let counter = AtomicUsize::new(0);
+        for input in &inputs {
+            let (groups, errors) = cache.get_playlist(input, fetch(input, &counter)).await;
+            assert!(errors.is_empty());
+            assert_eq!(groups[0].channels[0].header.input_name, input.name);
+        }
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+        let shared = cache.entries.values().next().unwrap().lock().await;
+        assert_eq!(shared.remaining, 0);
+        assert!(shared.playlistgroups.is_none());
+    }
+}
@@ -5,6 +5,13 @@ mod xtream_vod;
 mod xtream_series;
 pub mod epg;
 mod sort;
+mod category;
+mod channel_247;
+mod adult;
+mod failover;
+mod delivery;
+mod fetch_cache;
+mod dependency_cache;
 pub mod trakt;
 
 #[macro_export]
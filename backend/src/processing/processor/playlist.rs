@@ -1,4 +1,4 @@
-use crate::model::{ConfigInput, ConfigRename};
+use crate::model::{ConfigGlobalRules, ConfigInput, ConfigRename};
 use crate::utils::epg;
 use crate::utils::m3u;
 use crate::utils::xtream;
@@ -10,16 +10,19 @@ use std::thread;
 use tokio::sync::Mutex;
 
 use crate::foundation::filter::{get_field_value, set_field_value, ValueProvider, ValueAccessor};
+use crate::foundation::mapper::MapperCounters;
 use crate::messaging::{send_message};
-use crate::model::{ConfigTarget, InputType, ProcessTargets};
+use crate::model::{ConfigSource, ConfigTarget, InputType, ProcessTargets};
 use crate::model::{CounterModifier, Mapping};
 use crate::model::{FetchedPlaylist,  PlaylistGroup, PlaylistItem};
 use shared::model::{FieldGetAccessor, FieldSetAccessor, ItemField, MsgKind, PlaylistEntry, ProcessingOrder, UUIDType, XtreamCluster};
-use crate::model::{InputStats, PlaylistStats, SourceStats, TargetStats};
+use crate::model::{InputStats, PlaylistStats, RefreshResult, SourceStats, TargetStats};
 use crate::processing::playlist_watch::process_group_watch;
 use crate::processing::processor::xtream_series::playlist_resolve_series;
 use crate::processing::processor::trakt::process_trakt_categories_for_target;
 use crate::repository::playlist_repository::persist_playlist;
+use crate::repository::refresh_history_repository::{append_refresh_result, load_target_freshness, update_target_freshness};
+use crate::repository::epg_match_report_repository::save_epg_match_report;
 use shared::error::{get_errors_notify_message, notify_err, TuliproxError, TuliproxErrorKind};
 use crate::utils::debug_if_enabled;
 use shared::utils::default_as_default;
@@ -27,12 +30,21 @@ use deunicode::deunicode;
 use log::{debug, error, info, log_enabled, trace, warn, Level};
 use std::time::Instant;
 use reqwest::Client;
-use crate::model::Epg;
-use crate::processing::parser::xmltv::flatten_tvguide;
-use crate::processing::processor::epg::process_playlist_epg;
+use crate::model::{Epg, EpgAliasesConfig, EpgMatchReport, MergedEpg};
+use crate::processing::parser::xmltv::{fill_epg_placeholders, fill_epg_schedules};
+use crate::processing::processor::epg::{process_playlist_epg, xtream_epg_fallback};
 use crate::processing::processor::xtream_vod::playlist_resolve_vod;
 use crate::processing::processor::sort::sort_playlist;
-use crate::utils::StepMeasure;
+use crate::processing::processor::category::{apply_category_rules, normalize_group_hierarchy};
+use crate::processing::processor::channel_247::group_247_channels;
+use crate::processing::processor::adult::tag_adult_channels;
+use crate::processing::processor::failover::merge_failover_channels;
+use crate::processing::processor::delivery::deliver_playlist;
+use crate::processing::processor::fetch_cache::FetchCache;
+use crate::processing::processor::dependency_cache::DependencyCache;
+use crate::utils::{StepMeasure, TargetProgress, REFRESH_PROGRESS};
+use crate::utils::normalize_media_title;
+use crate::utils::{read_epg_aliases, read_epg_schedules};
 
 fn is_valid(pli: &PlaylistItem, target: &ConfigTarget) -> bool {
     let provider = ValueProvider { pli };
@@ -96,25 +108,29 @@ fn exec_rename(pli: &mut PlaylistItem, rename: Option<&Vec<ConfigRename>>) {
     }
 }
 
+fn rename_groups(playlist: &[PlaylistGroup], renames: &Vec<ConfigRename>, name: &str) -> Vec<PlaylistGroup> {
+    let mut new_playlist: Vec<PlaylistGroup> = Vec::with_capacity(playlist.len());
+    for g in playlist {
+        let mut grp = g.clone();
+        for r in renames {
+            if matches!(r.field, ItemField::Group) {
+                let cap = r.re.as_ref().unwrap().replace_all(&grp.title, &r.new_name);
+                debug_if_enabled!("Renamed group {} to {} for {}", &grp.title, cap, name);
+                grp.title = cap.into_owned();
+            }
+        }
+
+        grp.channels.iter_mut().for_each(|pli| exec_rename(pli, Some(renames)));
+        new_playlist.push(grp);
+    }
+    new_playlist
+}
+
 fn rename_playlist(playlist: &mut [PlaylistGroup], target: &ConfigTarget) -> Option<Vec<PlaylistGroup>> {
     match &target.rename {
         Some(renames) => {
             if !renames.is_empty() {
-                let mut new_playlist: Vec<PlaylistGroup> = Vec::with_capacity(playlist.len());
-                for g in playlist {
-                    let mut grp = g.clone();
-                    for r in renames {
-                        if matches!(r.field, ItemField::Group) {
-                            let cap = r.re.as_ref().unwrap().replace_all(&grp.title, &r.new_name);
-                            debug_if_enabled!("Renamed group {} to {} for {}", &grp.title, cap, target.name);
-                            grp.title = cap.into_owned();
-                        }
-                    }
-
-                    grp.channels.iter_mut().for_each(|pli| exec_rename(pli, target.rename.as_ref()));
-                    new_playlist.push(grp);
-                }
-                return Some(new_playlist);
+                return Some(rename_groups(playlist, renames, &target.name));
             }
             None
         }
@@ -122,7 +138,8 @@ fn rename_playlist(playlist: &mut [PlaylistGroup], target: &ConfigTarget) -> Opt
     }
 }
 
-fn map_channel(mut channel: PlaylistItem, mapping: &Mapping) -> PlaylistItem {
+// returns None if a mapper script dropped the channel
+fn map_channel(mut channel: PlaylistItem, mapping: &Mapping, counters: &mut MapperCounters) -> Option<PlaylistItem> {
     if let Some(mapper) = &mapping.mapper {
         if !mapper.is_empty() {
             let header = &channel.header;
@@ -136,53 +153,89 @@ fn map_channel(mut channel: PlaylistItem, mapping: &Mapping) -> PlaylistItem {
                         let provider = ValueProvider { pli: ref_chan };
                         if filter.filter(&provider) {
                             let mut accessor = ValueAccessor { pli: ref_chan };
-                            script.eval(&mut accessor, templates);
+                            let dropped = if m.trace {
+                                let (trace, dropped) = script.eval_with_trace(&mut accessor, templates, counters);
+                                info!("Mapper trace for {}:\n{}", accessor.pli.header.title, trace.join("\n"));
+                                dropped
+                            } else {
+                                script.eval(&mut accessor, templates, counters)
+                            };
+                            if dropped {
+                                if log_enabled!(Level::Trace) { trace!("Mapper dropped channel {}", accessor.pli.header.title); }
+                                return None;
+                            }
                         }
                     }
                 }
             }
         }
     }
-    channel
+    Some(channel)
 }
 
-fn map_playlist(playlist: &mut [PlaylistGroup], target: &ConfigTarget) -> Option<Vec<PlaylistGroup>> {
-    if let Some(mappings) = target.t_mapping.load().as_ref() {
-        let new_playlist: Vec<PlaylistGroup> = playlist.iter().map(|playlist_group| {
-            let mut grp = playlist_group.clone();
-            mappings.iter().filter(|&mapping| mapping.mapper.as_ref().is_some_and(|v| !v.is_empty()))
-                .for_each(|mapping|
-                    grp.channels = grp.channels.drain(..).map(|chan| map_channel(chan, mapping)).collect());
-            grp
-        }).collect();
-
-        // if the group names are changed, restructure channels to the right groups
-        // we use
-        let mut new_groups: Vec<PlaylistGroup> = Vec::with_capacity(128);
-        let mut grp_id: u32 = 0;
-        for playlist_group in new_playlist {
-            for channel in &playlist_group.channels {
-                let cluster = &channel.header.xtream_cluster;
-                let title = &channel.header.group;
-                if let Some(grp) = new_groups.iter_mut().find(|x| *x.title == **title) {
-                    grp.channels.push(channel.clone());
-                } else {
-                    grp_id += 1;
-                    new_groups.push(PlaylistGroup {
-                        id: grp_id,
-                        title: title.to_string(),
-                        channels: vec![channel.clone()],
-                        xtream_cluster: *cluster,
-                    });
-                }
+fn map_groups(playlist: &[PlaylistGroup], mappings: &[Mapping], counters: &mut MapperCounters) -> Vec<PlaylistGroup> {
+    let new_playlist: Vec<PlaylistGroup> = playlist.iter().map(|playlist_group| {
+        let mut grp = playlist_group.clone();
+        mappings.iter().filter(|&mapping| mapping.mapper.as_ref().is_some_and(|v| !v.is_empty()))
+            .for_each(|mapping|
+                grp.channels = grp.channels.drain(..).filter_map(|chan| map_channel(chan, mapping, counters)).collect());
+        grp
+    }).collect();
+
+    // if the group names are changed, restructure channels to the right groups
+    // we use
+    let mut new_groups: Vec<PlaylistGroup> = Vec::with_capacity(128);
+    let mut grp_id: u32 = 0;
+    for playlist_group in new_playlist {
+        for channel in &playlist_group.channels {
+            let cluster = &channel.header.xtream_cluster;
+            let title = &channel.header.group;
+            if let Some(grp) = new_groups.iter_mut().find(|x| *x.title == **title) {
+                grp.channels.push(channel.clone());
+            } else {
+                grp_id += 1;
+                new_groups.push(PlaylistGroup {
+                    id: grp_id,
+                    title: title.to_string(),
+                    channels: vec![channel.clone()],
+                    xtream_cluster: *cluster,
+                });
             }
         }
-        Some(new_groups)
+    }
+    new_groups
+}
+
+fn map_playlist(playlist: &mut [PlaylistGroup], target: &ConfigTarget) -> Option<Vec<PlaylistGroup>> {
+    if let Some(mappings) = target.t_mapping.load().as_ref() {
+        // the counters of the mapper scripts continue over all groups of the target
+        let mut counters = MapperCounters::with_vars(target.mapper_vars.clone().unwrap_or_default());
+        Some(map_groups(playlist, mappings, &mut counters))
     } else {
         None
     }
 }
 
+// The global rules are applied once to the fetched playlists, before the targets are processed.
+fn apply_global_rules(global: &ConfigGlobalRules, playlists: &mut [FetchedPlaylist]) {
+    let mappings = global.t_mapping.load();
+    let mut counters = MapperCounters::default();
+    for fpl in playlists {
+        if global.t_filter.is_some() {
+            for group in &mut fpl.playlistgroups {
+                group.channels.retain(|pli| global.filter(&ValueProvider { pli }));
+            }
+            fpl.playlistgroups.retain(|group| !group.channels.is_empty());
+        }
+        if let Some(renames) = global.rename.as_ref().filter(|renames| !renames.is_empty()) {
+            fpl.playlistgroups = rename_groups(&fpl.playlistgroups, renames, "global rules");
+        }
+        if let Some(mappings) = mappings.as_ref() {
+            fpl.playlistgroups = map_groups(&fpl.playlistgroups, mappings, &mut counters);
+        }
+    }
+}
+
 fn map_playlist_counter(target: &ConfigTarget, playlist: &mut [PlaylistGroup]) {
     if target.t_mapping.load().is_some() {
         let guard = target.t_mapping.load();
@@ -233,25 +286,77 @@ fn is_target_enabled(target: &ConfigTarget, user_targets: &ProcessTargets) -> bo
     (!user_targets.enabled && target.enabled) || (user_targets.enabled && user_targets.has_target(target.id))
 }
 
-async fn process_source(client: Arc<reqwest::Client>, cfg: Arc<Config>, source_idx: usize, user_targets: Arc<ProcessTargets>) -> (Vec<InputStats>, Vec<TargetStats>, Vec<TuliproxError>) {
-    let source = cfg.sources.get_source_at(source_idx).unwrap();
-    let mut errors = vec![];
-    let mut input_stats = HashMap::<String, InputStats>::new();
-    let mut target_stats = Vec::<TargetStats>::new();
-    let mut source_playlists = Vec::with_capacity(128);
-    // Download the sources
+async fn fetch_input_playlist(client: &Arc<reqwest::Client>, cfg: &Config, input: &ConfigInput) -> (Vec<PlaylistGroup>, Vec<TuliproxError>) {
+    match input.input_type {
+        InputType::M3u => m3u::get_m3u_playlist(Arc::clone(client), cfg, input, &cfg.working_dir).await,
+        InputType::Xtream => xtream::get_xtream_playlist(cfg, Arc::clone(client), input, &cfg.working_dir).await,
+        InputType::M3uBatch | InputType::XtreamBatch => (vec![], vec![])
+    }
+}
+
+// When the playlist of the input can't be fetched, the aliases are tried by priority.
+// The stream urls of the alias playlist are changed to the input, the provider is selected when streaming.
+async fn fetch_playlist_over_aliases(client: &Arc<reqwest::Client>, cfg: &Config, input: &ConfigInput) -> Option<(String, Vec<PlaylistGroup>)> {
+    let input_user_info = input.get_user_info();
+    for alias in input.get_aliases_by_priority() {
+        let (mut playlistgroups, errors) = fetch_input_playlist(client, cfg, &input.with_alias(alias)).await;
+        if playlistgroups.is_empty() {
+            warn!("Failed to fetch playlist of input {} over alias {}: {}", input.name, alias.name,
+                errors.iter().map(|err| err.message.as_str()).collect::<Vec<_>>().join(", "));
+            continue;
+        }
+        if let (Some(input_info), Some(alias_info)) = (input_user_info.as_ref(), input.get_alias_user_info(alias)) {
+            for channel in playlistgroups.iter_mut().flat_map(|group| group.channels.iter_mut()) {
+                channel.header.url = alias_info.replace_in_url(&channel.header.url, input_info);
+            }
+        }
+        return Some((alias.name.clone(), playlistgroups));
+    }
+    None
+}
+
+// A hung provider download would block the schedule of the targets forever.
+// The fetch and processing of a source are aborted after the configured refresh timeout,
+// dropping the future cancels the running provider requests.
+fn get_refresh_deadline(cfg: &Config) -> Option<tokio::time::Instant> {
+    cfg.get_refresh_timeout().map(|timeout| tokio::time::Instant::now() + timeout)
+}
+
+fn is_deadline_expired(deadline: Option<tokio::time::Instant>) -> bool {
+    deadline.is_some_and(|deadline| tokio::time::Instant::now() >= deadline)
+}
+
+async fn until_deadline<F: std::future::Future>(deadline: Option<tokio::time::Instant>, future: F) -> Option<F::Output> {
+    match deadline {
+        Some(deadline) => tokio::time::timeout_at(deadline, future).await.ok(),
+        None => Some(future.await),
+    }
+}
+
+// Fetches the playlists of the enabled inputs, returns false if no input is enabled.
+#[allow(clippy::too_many_arguments)]
+async fn fetch_source_inputs<'a>(client: &Arc<reqwest::Client>, cfg: &Config, source: &'a ConfigSource, user_targets: &ProcessTargets,
+                                 fetch_cache: &FetchCache, source_playlists: &mut Vec<FetchedPlaylist<'a>>, failed_inputs: &mut Vec<String>,
+                                 input_stats: &mut HashMap<String, InputStats>, errors: &mut Vec<TuliproxError>) -> bool {
     let mut source_downloaded = false;
     for input in &source.inputs {
-        if is_input_enabled(input, &user_targets) {
+        if is_input_enabled(input, user_targets) {
             source_downloaded = true;
             let start_time = Instant::now();
-            let (mut playlistgroups, mut error_list) = match input.input_type {
-                InputType::M3u => m3u::get_m3u_playlist(Arc::clone(&client), &cfg, input, &cfg.working_dir).await,
-                InputType::Xtream => xtream::get_xtream_playlist(&cfg, Arc::clone(&client), input, &cfg.working_dir).await,
-                InputType::M3uBatch | InputType::XtreamBatch => (vec![], vec![])
-            };
+            let (mut playlistgroups, mut error_list) = fetch_cache.get_playlist(input, fetch_input_playlist(client, cfg, input)).await;
+            let mut alias_name = None;
+            if playlistgroups.is_empty() {
+                if let Some((name, alias_playlistgroups)) = fetch_playlist_over_aliases(client, cfg, input).await {
+                    for err in error_list.drain(..) {
+                        warn!("Input {}: {}", input.name, err.message);
+                    }
+                    info!("Playlist of input {} fetched over alias {name}", input.name);
+                    playlistgroups = alias_playlistgroups;
+                    alias_name = Some(name);
+                }
+            }
             let (tvguide, mut tvguide_errors) = if error_list.is_empty() {
-                epg::get_xmltv(Arc::clone(&client), &cfg, input, &cfg.working_dir).await
+                epg::get_xmltv(Arc::clone(client), cfg, input, &cfg.working_dir).await
             } else {
                 (None, vec![])
             };
@@ -265,6 +370,7 @@ async fn process_source(client: Arc<reqwest::Client>, cfg: Arc<Config>, source_i
             if playlistgroups.is_empty() {
                 info!("Source is empty {input_name}");
                 errors.push(notify_err!(format!("Source is empty {input_name}")));
+                failed_inputs.push(input_name.clone());
             } else {
                 playlistgroups.iter_mut().for_each(PlaylistGroup::on_load);
                 source_playlists.push(
@@ -277,37 +383,154 @@ async fn process_source(client: Arc<reqwest::Client>, cfg: Arc<Config>, source_i
             }
             let elapsed = start_time.elapsed().as_secs();
             input_stats.insert(input_name.to_string(), create_input_stat(group_count, channel_count, error_list.len(),
-                                                                         input.input_type, input_name, elapsed));
+                                                                         input.input_type, input_name, alias_name, elapsed));
         }
     }
+    source_downloaded
+}
+
+async fn process_source(client: Arc<reqwest::Client>, cfg: Arc<Config>, source_idx: usize, user_targets: Arc<ProcessTargets>,
+                        fetch_cache: Arc<FetchCache>, dependency_cache: Arc<DependencyCache>,
+                        deadline: Option<tokio::time::Instant>) -> (Vec<InputStats>, Vec<TargetStats>, Vec<TuliproxError>) {
+    let start_time = Instant::now();
+    let source = cfg.sources.get_source_at(source_idx).unwrap();
+    let mut errors = vec![];
+    let mut input_stats = HashMap::<String, InputStats>::new();
+    let mut target_stats = Vec::<TargetStats>::new();
+    let mut source_playlists = Vec::with_capacity(128);
+    let mut failed_inputs = vec![];
+    for target in source.targets.iter().filter(|target| target.depends_on.is_none() && is_target_enabled(target, &user_targets)) {
+        REFRESH_PROGRESS.update(TargetProgress::new(&target.name, "Fetching inputs", 0, 0));
+    }
+    // Download the sources, on a timeout an enabled input was being fetched
+    let download = fetch_source_inputs(&client, &cfg, source, &user_targets, &fetch_cache,
+                                       &mut source_playlists, &mut failed_inputs, &mut input_stats, &mut errors);
+    let (source_downloaded, downloaded) = until_deadline(deadline, Box::pin(download)).await
+        .map_or((true, false), |source_downloaded| (source_downloaded, true));
+    let mut aborted_targets = vec![];
     if source_downloaded {
-        if source_playlists.is_empty() {
+        if !downloaded {
+            debug!("Download of source at index {source_idx} aborted after refresh timeout");
+        } else if source_playlists.is_empty() {
             debug!("Source at index {source_idx} is empty");
             errors.push(notify_err!(format!("Source at {source_idx} is empty")));
         } else {
             debug_if_enabled!("Source has {} groups", source_playlists.iter().map(|fpl| fpl.playlistgroups.len()).sum::<usize>());
-            for target in &source.targets {
-                if is_target_enabled(target, &user_targets) {
-                    match process_playlist_for_target(Arc::clone(&client), &mut source_playlists, target, &cfg, &mut input_stats, &mut errors).await {
-                        Ok(()) => {
-                            target_stats.push(TargetStats::success(&target.name));
-                        }
-                        Err(mut err) => {
-                            target_stats.push(TargetStats::failure(&target.name));
-                            errors.append(&mut err);
+            if let Some(global) = cfg.sources.global.as_ref() {
+                apply_global_rules(global, &mut source_playlists);
+            }
+        }
+        for target in &source.targets {
+            // targets with dependencies are processed after all sources
+            if target.depends_on.is_none() && is_target_enabled(target, &user_targets) {
+                if !downloaded || is_deadline_expired(deadline) {
+                    let refresh_result = RefreshResult { ts: chrono::Utc::now().timestamp(), success: false, duration: start_time.elapsed().as_secs(), errors: 1, ..RefreshResult::default() };
+                    if let Err(err) = append_refresh_result(&cfg, &target.name, refresh_result).await {
+                        error!("Failed to store refresh result for target {}: {err}", target.name);
+                    }
+                    target_stats.push(TargetStats::failure(&target.name));
+                    aborted_targets.push(target.name.as_str());
+                    continue;
+                }
+                // Without all inputs the last processed playlist of the target is kept.
+                // A target without a successful refresh is processed with the remaining inputs.
+                if source_playlists.is_empty()
+                    || (!failed_inputs.is_empty() && load_target_freshness(&cfg, &target.name).await.last_success.is_some()) {
+                    warn!("Inputs {} failed, target {} keeps its last playlist", failed_inputs.join(", "), target.name);
+                    target_stats.push(TargetStats::stale(&target.name));
+                    set_target_freshness(&client, &cfg, &target.name, &failed_inputs).await;
+                    continue;
+                }
+                match process_playlist_for_target(Arc::clone(&client), &mut source_playlists, target, &cfg, &mut input_stats, &mut errors, &dependency_cache, deadline).await {
+                    Ok(()) => {
+                        target_stats.push(TargetStats::success(&target.name));
+                        set_target_freshness(&client, &cfg, &target.name, &failed_inputs).await;
+                    }
+                    Err(mut err) => {
+                        target_stats.push(TargetStats::failure(&target.name));
+                        errors.append(&mut err);
+                        if is_deadline_expired(deadline) {
+                            aborted_targets.push(target.name.as_str());
                         }
                     }
                 }
             }
         }
     }
+    if !aborted_targets.is_empty() {
+        let msg = format!("Refresh of source at index {source_idx} aborted after {} mins, targets {} failed",
+                          cfg.refresh_timeout_mins.unwrap_or_default(), aborted_targets.join(", "));
+        error!("{msg}");
+        send_message(&client, &MsgKind::Error, cfg.messaging.as_ref(), &msg);
+    }
+    // targets which were not processed are no longer shown as running
+    for target in &source.targets {
+        REFRESH_PROGRESS.finish(&target.name);
+    }
     (input_stats.into_values().collect(), target_stats, errors)
 }
 
-fn create_input_stat(group_count: usize, channel_count: usize, error_count: usize, input_type: InputType, input_name: &str, secs_took: u64) -> InputStats {
+// The dependent targets are processed in dependency order with the playlists of the targets they depend on.
+async fn process_dependent_targets(client: Arc<reqwest::Client>, cfg: Arc<Config>, user_targets: Arc<ProcessTargets>, dependency_cache: Arc<DependencyCache>) -> (Vec<TargetStats>, Vec<TuliproxError>) {
+    let mut errors = vec![];
+    let mut target_stats = vec![];
+    for target_name in &cfg.sources.t_dependency_order {
+        let Some(target) = cfg.sources.get_target_by_name(target_name) else { continue; };
+        if !is_target_enabled(target, &user_targets) {
+            continue;
+        }
+        let mut playlists = match dependency_cache.get_playlists(&cfg, target).await {
+            Ok(playlists) => playlists,
+            Err(err) => {
+                warn!("{}, target {} keeps its last playlist", err.message, target.name);
+                target_stats.push(TargetStats::stale(&target.name));
+                continue;
+            }
+        };
+        let mut input_stats = HashMap::new();
+        match process_playlist_for_target(Arc::clone(&client), &mut playlists, target, &cfg, &mut input_stats, &mut errors, &dependency_cache, None).await {
+            Ok(()) => {
+                target_stats.push(TargetStats::success(&target.name));
+                set_target_freshness(&client, &cfg, &target.name, &[]).await;
+            }
+            Err(mut err) => {
+                target_stats.push(TargetStats::failure(&target.name));
+                errors.append(&mut err);
+            }
+        }
+    }
+    (target_stats, errors)
+}
+
+// A target is fresh when all inputs were fetched, otherwise stale until the next complete refresh.
+async fn set_target_freshness(client: &Arc<reqwest::Client>, cfg: &Config, target_name: &str, failed_inputs: &[String]) {
+    let now = chrono::Utc::now().timestamp();
+    let result = update_target_freshness(cfg, target_name, |freshness| {
+        if failed_inputs.is_empty() {
+            freshness.set_success(now);
+            None
+        } else {
+            freshness.set_stale(now, failed_inputs.to_vec(), cfg.get_stale_notify_secs()).then_some(freshness.last_success)
+        }
+    }).await;
+    match result {
+        Ok(Some(last_success)) => {
+            let last_success = last_success.and_then(|ts| chrono::DateTime::from_timestamp(ts, 0))
+                .map_or_else(|| "never".to_string(), |datetime| datetime.format("%Y-%m-%d %H:%M:%S").to_string());
+            let msg = format!("Target {target_name} is stale, inputs {} failed, last success {last_success}", failed_inputs.join(", "));
+            warn!("{msg}");
+            send_message(client, &MsgKind::Error, cfg.messaging.as_ref(), &msg);
+        }
+        Ok(None) => {}
+        Err(err) => error!("Failed to store freshness for target {target_name}: {err}"),
+    }
+}
+
+fn create_input_stat(group_count: usize, channel_count: usize, error_count: usize, input_type: InputType, input_name: &str, alias: Option<String>, secs_took: u64) -> InputStats {
     InputStats {
         name: input_name.to_string(),
         input_type,
+        alias,
         error_count,
         raw_stats: PlaylistStats {
             group_count,
@@ -330,6 +553,8 @@ async fn process_sources(client: Arc<reqwest::Client>, config: Arc<Config>, user
     }
     let errors = Arc::new(Mutex::<Vec<TuliproxError>>::new(vec![]));
     let stats = Arc::new(Mutex::<Vec<SourceStats>>::new(vec![]));
+    let fetch_cache = Arc::new(FetchCache::new(&config, &user_targets, is_input_enabled));
+    let dependency_cache = Arc::new(DependencyCache::new(&config));
     for (index, _) in config.sources.sources.iter().enumerate() {
         // We're using the file lock this way on purpose
         let source_lock_path = PathBuf::from(format!("source_{index}"));
@@ -342,6 +567,8 @@ async fn process_sources(client: Arc<reqwest::Client>, config: Arc<Config>, user
         let shared_stats = stats.clone();
         let cfg = config.clone();
         let usr_trgts = user_targets.clone();
+        let shared_fetch_cache = Arc::clone(&fetch_cache);
+        let shared_dependency_cache = Arc::clone(&dependency_cache);
         if process_parallel {
             let http_client = Arc::clone(&client);
             let handles = &mut handle_list;
@@ -349,7 +576,8 @@ async fn process_sources(client: Arc<reqwest::Client>, config: Arc<Config>, user
                 // TODO better way ?
                 let rt = tokio::runtime::Runtime::new().unwrap();
                 rt.block_on(async {
-                    let (input_stats, target_stats, mut res_errors) = process_source(Arc::clone(&http_client), cfg, index, usr_trgts).await;
+                    let deadline = get_refresh_deadline(&cfg);
+                    let (input_stats, target_stats, mut res_errors) = Box::pin(process_source(Arc::clone(&http_client), cfg, index, usr_trgts, shared_fetch_cache, shared_dependency_cache, deadline)).await;
                     shared_errors.lock().await.append(&mut res_errors);
                     let process_stats = SourceStats::new(input_stats, target_stats);
                     shared_stats.lock().await.push(process_stats);
@@ -360,7 +588,7 @@ async fn process_sources(client: Arc<reqwest::Client>, config: Arc<Config>, user
                 handles.drain(..).for_each(|handle| { let _ = handle.join(); });
             }
         } else {
-            let (input_stats, target_stats, mut res_errors) = process_source(Arc::clone(&client), cfg, index, usr_trgts).await;
+            let (input_stats, target_stats, mut res_errors) = Box::pin(process_source(Arc::clone(&client), cfg, index, usr_trgts, shared_fetch_cache, shared_dependency_cache, get_refresh_deadline(&config))).await;
             shared_errors.lock().await.append(&mut res_errors);
             let process_stats = SourceStats::new(input_stats, target_stats);
             shared_stats.lock().await.push(process_stats);
@@ -370,6 +598,11 @@ async fn process_sources(client: Arc<reqwest::Client>, config: Arc<Config>, user
     for handle in handle_list {
         let _ = handle.join();
     }
+    if !config.sources.t_dependency_order.is_empty() {
+        let (target_stats, mut res_errors) = process_dependent_targets(Arc::clone(&client), Arc::clone(&config), user_targets, dependency_cache).await;
+        errors.lock().await.append(&mut res_errors);
+        stats.lock().await.push(SourceStats::new(vec![], target_stats));
+    }
     (Arc::try_unwrap(stats).unwrap().into_inner(), Arc::try_unwrap(errors).unwrap().into_inner())
 }
 
@@ -390,7 +623,8 @@ fn duplicate_hash(item: &PlaylistItem) -> UUIDType {
     item.get_uuid()
 }
 
-fn execute_pipe<'a>(target: &ConfigTarget, pipe: &ProcessingPipe, fpl: &FetchedPlaylist<'a>, duplicates: &mut HashSet<UUIDType>) -> FetchedPlaylist<'a> {
+fn execute_pipe<'a>(target: &ConfigTarget, pipe: &ProcessingPipe, fpl: &FetchedPlaylist<'a>,
+                    duplicates: &mut HashSet<UUIDType>, duplicate_titles: &mut HashSet<(XtreamCluster, String)>) -> FetchedPlaylist<'a> {
     let mut new_fpl = FetchedPlaylist {
         input: fpl.input,
         playlistgroups: fpl.playlistgroups.clone(), // we need to clone, because of multiple target definitions, we cant change the initial playlist.
@@ -408,9 +642,26 @@ fn execute_pipe<'a>(target: &ConfigTarget, pipe: &ProcessingPipe, fpl: &FetchedP
             new_fpl.playlistgroups = groups;
         }
     }
+    if target.options.as_ref().is_some_and(|opt| opt.remove_duplicate_titles) {
+        remove_duplicate_titles(&mut new_fpl.playlistgroups, duplicate_titles);
+    }
     new_fpl
 }
 
+// The same movie or episode is often offered by multiple providers with different names.
+// Vod and series entries are compared by their normalized title, the first occurrence is kept.
+fn remove_duplicate_titles(playlistgroups: &mut Vec<PlaylistGroup>, duplicate_titles: &mut HashSet<(XtreamCluster, String)>) {
+    for group in playlistgroups.iter_mut().filter(|group| group.xtream_cluster != XtreamCluster::Live) {
+        group.channels.retain(|item| {
+            let header = &item.header;
+            let caption = if header.title.is_empty() { &header.name } else { &header.title };
+            let key = normalize_media_title(caption).dedup_key();
+            key.is_empty() || duplicate_titles.insert((group.xtream_cluster, key))
+        });
+    }
+    playlistgroups.retain(|group| !group.channels.is_empty());
+}
+
 // This method is needed, because of duplicate group names in different inputs.
 // We merge the same group names considering cluster together.
 fn flatten_groups(playlistgroups: Vec<PlaylistGroup>) -> Vec<PlaylistGroup> {
@@ -433,25 +684,104 @@ fn flatten_groups(playlistgroups: Vec<PlaylistGroup>) -> Vec<PlaylistGroup> {
     sort_order
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn process_playlist_for_target(client: Arc<reqwest::Client>,
                                      playlists: &mut [FetchedPlaylist<'_>],
                                      target: &ConfigTarget,
                                      cfg: &Config,
                                      stats: &mut HashMap<String, InputStats>,
-                                     errors: &mut Vec<TuliproxError>) -> Result<(), Vec<TuliproxError>> {
+                                     errors: &mut Vec<TuliproxError>,
+                                     dependency_cache: &DependencyCache,
+                                     deadline: Option<tokio::time::Instant>) -> Result<(), Vec<TuliproxError>> {
+    let start_time = Instant::now();
+    let error_count = errors.len();
+    let mut refresh_result = RefreshResult { ts: chrono::Utc::now().timestamp(), ..RefreshResult::default() };
+    let result = process_target_playlist(client, playlists, target, cfg, stats, errors, &mut refresh_result, dependency_cache, deadline).await;
+    refresh_result.success = result.is_ok();
+    refresh_result.duration = start_time.elapsed().as_secs();
+    refresh_result.errors = errors.len() - error_count + result.as_ref().err().map_or(0, Vec::len);
+    if let Err(err) = append_refresh_result(cfg, &target.name, refresh_result).await {
+        error!("Failed to store refresh result for target {}: {err}", target.name);
+    }
+    result
+}
+
+// the measured steps of a target refresh, used for the progress percentage
+const TARGET_REFRESH_STEPS: usize = 17;
+
+#[allow(clippy::too_many_arguments)]
+async fn process_target_playlist(client: Arc<reqwest::Client>,
+                                 playlists: &mut [FetchedPlaylist<'_>],
+                                 target: &ConfigTarget,
+                                 cfg: &Config,
+                                 stats: &mut HashMap<String, InputStats>,
+                                 errors: &mut Vec<TuliproxError>,
+                                 refresh_result: &mut RefreshResult,
+                                 dependency_cache: &DependencyCache,
+                                 deadline: Option<tokio::time::Instant>) -> Result<(), Vec<TuliproxError>> {
+    let mut step = StepMeasure::with_progress("Pipes processed", &target.name, TARGET_REFRESH_STEPS);
+    let prepare = prepare_target_playlist(&client, playlists, target, cfg, stats, errors, refresh_result, dependency_cache, &mut step);
+    let Some(prepared) = until_deadline(deadline, prepare).await else {
+        step.stop();
+        return Err(vec![notify_err!(format!("Processing of target {} aborted after refresh timeout", target.name))]);
+    };
+    let Some((mut flat_new_playlist, new_epg)) = prepared else {
+        info!("Playlist is empty: {}", &target.name);
+        return Ok(());
+    };
+    // the persistence is not cancelled by the refresh timeout, a partially written store would be corrupt
+    step.tick("Persisting playlists");
+    let mut target_epg = MergedEpg::new(&new_epg);
+    let now = chrono::Utc::now();
+    // the scheduled channels are added first, they get no placeholders
+    match read_epg_schedules(cfg) {
+        Ok(Some(schedules)) => {
+            if let Some(scheduled) = fill_epg_schedules(target_epg.as_ref(), &flat_new_playlist, &schedules, now) {
+                target_epg.get_or_insert_with(MergedEpg::default).add_generated(scheduled);
+            }
+        }
+        Ok(None) => {}
+        Err(err) => errors.push(err),
+    }
+    if let Some(placeholder) = target.epg_placeholder.as_ref() {
+        if let Some(placeholders) = fill_epg_placeholders(target_epg.as_ref(), &mut flat_new_playlist, placeholder, now) {
+            target_epg.get_or_insert_with(MergedEpg::default).add_generated(placeholders);
+        }
+    }
+    let result = persist_playlist(&mut flat_new_playlist, target_epg.as_ref(), target, cfg).await;
+    if result.is_ok() && target.delivery.is_some() {
+        step.tick("Delivering playlists");
+        errors.extend(deliver_playlist(&client, cfg, target).await);
+    }
+    step.stop();
+    result
+}
+
+// Processes the fetched playlists of the target, returns the playlist and the epg sources or `None` for an empty playlist.
+#[allow(clippy::too_many_arguments)]
+async fn prepare_target_playlist(client: &Arc<reqwest::Client>,
+                                 playlists: &mut [FetchedPlaylist<'_>],
+                                 target: &ConfigTarget,
+                                 cfg: &Config,
+                                 stats: &mut HashMap<String, InputStats>,
+                                 errors: &mut Vec<TuliproxError>,
+                                 refresh_result: &mut RefreshResult,
+                                 dependency_cache: &DependencyCache,
+                                 step: &mut StepMeasure) -> Option<(Vec<PlaylistGroup>, Vec<Epg>)> {
     let pipe = get_processing_pipe(target);
     debug_if_enabled!("Processing order is {}", &target.processing_order);
 
     let mut duplicates: HashSet<UUIDType> = HashSet::new();
+    let mut duplicate_titles: HashSet<(XtreamCluster, String)> = HashSet::new();
     let mut processed_fetched_playlists: Vec<FetchedPlaylist> = vec![];
 
     debug!("Executing processing pipes");
 
-    let mut step = StepMeasure::new("Pipes processed");
+    let mut processed_items = 0;
     for provider_fpl in playlists.iter_mut() {
-        let mut processed_fpl = execute_pipe(target, &pipe, provider_fpl, &mut duplicates);
-        playlist_resolve_series(Arc::clone(&client), cfg, target, errors, &pipe, provider_fpl, &mut processed_fpl).await;
-        playlist_resolve_vod(Arc::clone(&client), cfg, target, errors, &mut processed_fpl).await;
+        let mut processed_fpl = execute_pipe(target, &pipe, provider_fpl, &mut duplicates, &mut duplicate_titles);
+        playlist_resolve_series(Arc::clone(client), cfg, target, errors, &pipe, provider_fpl, &mut processed_fpl).await;
+        playlist_resolve_vod(Arc::clone(client), cfg, target, errors, &mut processed_fpl).await;
         // stats
         let input_stats = stats.get_mut(&processed_fpl.input.name);
         if let Some(stat) = input_stats {
@@ -460,37 +790,60 @@ async fn process_playlist_for_target(client: Arc<reqwest::Client>,
                 .map(|group| group.channels.len())
                 .sum();
         }
+        processed_items += processed_fpl.playlistgroups.iter().map(|group| group.channels.len()).sum::<usize>();
+        step.set_items(processed_items);
         processed_fetched_playlists.push(processed_fpl);
     }
+    dependency_cache.store(target, &processed_fetched_playlists).await;
 
     step.tick("Processed epg");
-    let (new_epg, mut new_playlist) = process_epg(&mut processed_fetched_playlists);
+    let epg_aliases = read_epg_aliases(cfg).unwrap_or_else(|err| {
+        errors.push(err);
+        None
+    });
+    let (mut new_epg, mut new_playlist, epg_match_report) = process_epg(target, epg_aliases.as_ref(), &mut processed_fetched_playlists);
+    if let Err(err) = save_epg_match_report(cfg, &target.name, &epg_match_report).await {
+        error!("Failed to store epg match report for target {}: {err}", target.name);
+    }
+    step.tick("Fetched provider short epg");
+    if let Some(fallback_epg) = xtream_epg_fallback(client, cfg, target, &new_epg, &mut new_playlist).await {
+        new_epg.push(fallback_epg);
+    }
 
     if new_playlist.is_empty() {
-        info!("Playlist is empty: {}", &target.name);
-        Ok(())
+        None
     } else {
 
         // Process Trakt categories
         step.tick("Processing Trakt categories");
-        trakt_playlist(&client, target, errors, &mut new_playlist).await;
+        trakt_playlist(client, target, errors, &mut new_playlist).await;
 
+        step.tick("Normalized group hierarchy");
+        normalize_group_hierarchy(target, &mut new_playlist);
         step.tick("Merged playlists");
-        let mut flat_new_playlist = flatten_groups(new_playlist);
+        let flat_new_playlist = flatten_groups(new_playlist);
+        step.tick("Grouped 24/7 channels");
+        let mut flat_new_playlist = group_247_channels(target, flat_new_playlist);
+        step.tick("Merged failover channels");
+        merge_failover_channels(target, &mut flat_new_playlist);
 
         step.tick("Sorted playlists");
         sort_playlist(target, &mut flat_new_playlist);
+        step.tick("Applied category rules");
+        let mut flat_new_playlist = apply_category_rules(target, flat_new_playlist);
+        step.tick("Tagged adult channels");
+        tag_adult_channels(target, &mut flat_new_playlist);
         step.tick("Assigned channel number");
         assign_channel_no_playlist(&mut flat_new_playlist);
         step.tick("Assigned channel counter");
         map_playlist_counter(target, &mut flat_new_playlist);
 
         step.tick("Processed group watches");
-        process_watch(&client, target, cfg, &flat_new_playlist);
-        step.tick("Persisting playlists");
-        let result = persist_playlist(&mut flat_new_playlist, flatten_tvguide(&new_epg).as_ref(), target, cfg).await;
-        step.stop();
-        result
+        process_watch(client, target, cfg, &flat_new_playlist);
+        for group in &flat_new_playlist {
+            refresh_result.add_group(group.xtream_cluster, group.channels.len());
+        }
+        Some((flat_new_playlist, new_epg))
     }
 }
 
@@ -509,17 +862,18 @@ async fn trakt_playlist(client: &Arc<Client>, target: &ConfigTarget, errors: &mu
     }
 }
 
-fn process_epg(processed_fetched_playlists: &mut Vec<FetchedPlaylist>) -> (Vec<Epg>, Vec<PlaylistGroup>) {
+fn process_epg(target: &ConfigTarget, epg_aliases: Option<&EpgAliasesConfig>, processed_fetched_playlists: &mut Vec<FetchedPlaylist>) -> (Vec<Epg>, Vec<PlaylistGroup>, EpgMatchReport) {
     let mut new_playlist = vec![];
     let mut new_epg = vec![];
+    let mut epg_match_report = EpgMatchReport { ts: chrono::Utc::now().timestamp(), channels: vec![] };
 
     // each fetched playlist can have its own epgl url.
     // we need to process each input epg.
     for fp in processed_fetched_playlists {
-        process_playlist_epg(fp, &mut new_epg);
+        process_playlist_epg(fp, target, epg_aliases, &mut new_epg, &mut epg_match_report.channels);
         new_playlist.append(&mut fp.playlistgroups);
     }
-    (new_epg, new_playlist)
+    (new_epg, new_playlist, epg_match_report)
 }
 
 fn process_watch(client: &Arc<reqwest::Client>, target: &ConfigTarget, cfg: &Config, new_playlist: &Vec<PlaylistGroup>) {
@@ -562,6 +916,16 @@ pub async fn exec_processing(client: Arc<reqwest::Client>, cfg: Arc<Config>, tar
 
 #[cfg(test)]
 mod tests {
+    use super::{apply_global_rules, is_input_enabled, process_source};
+    use crate::model::{Config, ConfigGlobalRules, ConfigInput, FetchedPlaylist, PlaylistGroup, PlaylistItem, PlaylistItemHeader, ProcessTargets, SourcesConfig};
+    use crate::processing::processor::dependency_cache::DependencyCache;
+    use crate::processing::processor::fetch_cache::FetchCache;
+    use crate::repository::m3u_repository::m3u_get_file_paths;
+    use crate::repository::storage::ensure_target_storage_path;
+    use shared::model::XtreamCluster;
+    use std::sync::Arc;
+    use std::time::Duration;
+
     // #[test]
     // fn test_jaro_winkeler() {
     //     let data = [("yessport5", "heyessport5gold"), ("yessport5", "heyesport5gold")];
@@ -575,4 +939,68 @@ mod tests {
     //     // println!("sorensen dice {:?}", strsim::sorensen_dice(data.0, data.1));
     // }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_apply_global_rules() {
+        let input = ConfigInput::default();
+        let create_channel = |name: &str| PlaylistItem { header: PlaylistItemHeader { name: name.to_string(), group: "Sports".to_string(), ..Default::default() } };
+        let mut playlists = vec![FetchedPlaylist {
+            input: &input,
+            playlistgroups: vec![
+                PlaylistGroup { id: 1, title: "Sports".to_string(), channels: vec![create_channel("Sky Sport HD"), create_channel("Test Channel")], xtream_cluster: XtreamCluster::Live },
+                PlaylistGroup { id: 2, title: "Test".to_string(), channels: vec![create_channel("Test Feed")], xtream_cluster: XtreamCluster::Live },
+            ],
+            epg: None,
+        }];
+        let mut global: ConfigGlobalRules = serde_yaml::from_str(r#"
+filter: 'NOT(Name ~ "^Test")'
+rename:
+  - { field: name, pattern: '(?i)sky', new_name: 'SKY' }
+"#).unwrap();
+        global.prepare(None).unwrap();
+        apply_global_rules(&global, &mut playlists);
+        let groups = &playlists[0].playlistgroups;
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].channels.len(), 1);
+        assert_eq!(groups[0].channels[0].header.name, "SKY Sport HD");
+    }
+
+    #[tokio::test]
+    async fn test_refresh_timeout_keeps_stores() {
+        // the provider accepts the connection but never answers
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let mut connections = vec![];
+            while let Ok((stream, _)) = listener.accept().await {
+                connections.push(stream);
+            }
+        });
+        let mut sources: SourcesConfig = serde_yaml::from_str(&format!(r#"
+sources:
+  - inputs:
+      - {{ name: slow, type: m3u, url: 'http://127.0.0.1:{port}/playlist.m3u' }}
+    targets:
+      - {{ name: target, filter: 'Group ~ ".*"', output: [{{ type: m3u }}] }}
+"#)).unwrap();
+        sources.prepare(true).unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = Arc::new(Config { working_dir: dir.path().to_string_lossy().to_string(), sources, ..Default::default() });
+
+        let (m3u_path, idx_path) = m3u_get_file_paths(&ensure_target_storage_path(&cfg, "target").unwrap());
+        std::fs::write(&m3u_path, "previous playlist").unwrap();
+        std::fs::write(&idx_path, "previous index").unwrap();
+
+        let user_targets = Arc::new(ProcessTargets { enabled: false, inputs: vec![], targets: vec![] });
+        let fetch_cache = Arc::new(FetchCache::new(&cfg, &user_targets, is_input_enabled));
+        let dependency_cache = Arc::new(DependencyCache::new(&cfg));
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(200);
+        let (_, target_stats, _) = process_source(Arc::new(reqwest::Client::new()), Arc::clone(&cfg), 0, user_targets,
+                                                  fetch_cache, dependency_cache, Some(deadline)).await;
+        server.abort();
+
+        assert_eq!(target_stats.len(), 1);
+        assert!(!target_stats[0].success);
+        assert_eq!(std::fs::read_to_string(&m3u_path).unwrap(), "previous playlist");
+        assert_eq!(std::fs::read_to_string(&idx_path).unwrap(), "previous index");
+    }
+}
@@ -117,19 +117,25 @@ fn playlistitem_comparator(
 
 pub(in crate::processing::processor) fn sort_playlist(target: &ConfigTarget, new_playlist: &mut [PlaylistGroup]) {
     if let Some(sort) = &target.sort {
-        let match_as_ascii = sort.match_as_ascii;
         if let Some(group_sort) = &sort.groups {
-            new_playlist.sort_by(|a, b| playlistgroup_comparator(a, b, group_sort, match_as_ascii));
+            new_playlist.sort_by(|a, b| playlistgroup_comparator(a, b, group_sort, sort.match_as_ascii));
         }
-        if let Some(channel_sorts) = &sort.channels {
-            for channel_sort in channel_sorts {
-                let regexp = channel_sort.t_re_group_pattern.as_ref().unwrap();
-                for group in new_playlist.iter_mut() {
-                    let group_title = if match_as_ascii { deunicode(&group.title) } else { group.title.to_string() };
-                    if regexp.is_match(group_title.as_str()) {
-                        group.channels.sort_by(|chan1, chan2| playlistitem_comparator(chan1, chan2, channel_sort, match_as_ascii));
-                    }
-                }
+        for group in new_playlist.iter_mut() {
+            sort_group_channels(target, group);
+        }
+    }
+}
+
+// Sorts the channels of a group with the channel sort rules matching the group title.
+pub(in crate::processing::processor) fn sort_group_channels(target: &ConfigTarget, group: &mut PlaylistGroup) {
+    let Some(sort) = &target.sort else { return };
+    if let Some(channel_sorts) = &sort.channels {
+        let match_as_ascii = sort.match_as_ascii;
+        let group_title = if match_as_ascii { deunicode(&group.title) } else { group.title.to_string() };
+        for channel_sort in channel_sorts {
+            let regexp = channel_sort.t_re_group_pattern.as_ref().unwrap();
+            if regexp.is_match(group_title.as_str()) {
+                group.channels.sort_by(|chan1, chan2| playlistitem_comparator(chan1, chan2, channel_sort, match_as_ascii));
             }
         }
     }
@@ -137,7 +143,8 @@ pub(in crate::processing::processor) fn sort_playlist(target: &ConfigTarget, new
 
 #[cfg(test)]
 mod tests {
-    use crate::model::{ConfigSortChannel, ItemField, SortOrder};
+    use crate::model::{ConfigSortChannel, SortOrder};
+    use shared::model::ItemField;
     use crate::model::{PlaylistItem, PlaylistItemHeader};
     use crate::processing::processor::sort::playlistitem_comparator;
     use regex::Regex;
@@ -17,12 +17,14 @@ use crate::utils;
 use crate::utils::xtream;
 use serde_json::{from_str, to_string, Value};
 
-pub(in crate::processing) async fn playlist_resolve_download_playlist_item(client: Arc<reqwest::Client>, pli: &PlaylistItem, input: &ConfigInput, errors: &mut Vec<TuliproxError>, resolve_delay: u16, cluster: XtreamCluster) -> Option<String> {
+pub(in crate::processing) async fn playlist_resolve_download_playlist_item(cfg: &Config, client: Arc<reqwest::Client>, pli: &PlaylistItem, input: &ConfigInput, errors: &mut Vec<TuliproxError>, resolve_delay: u16, cluster: XtreamCluster) -> Option<String> {
     let mut result = None;
     let provider_id = pli.get_provider_id()?;
     if let Some(info_url) = xtream::get_xtream_player_api_info_url(input, cluster, provider_id) {
-        result = match xtream::get_xtream_stream_info_content(client, &info_url, input).await {
-            Ok(content) => Some(content),
+        result = match xtream::get_cached_xtream_stream_info_content(cfg, client, &info_url, input).await {
+            // no delay necessary, the provider was not requested
+            Ok((content, true)) => return Some(content),
+            Ok((content, false)) => Some(content),
             Err(err) => {
                 errors.push(info_err!(format!("{err}")));
                 None
@@ -90,7 +90,7 @@ pub async fn playlist_resolve_vod(client: Arc<reqwest::Client>, cfg: &Config, ta
     for pli in  vod_info_iter {
         let (should_update, _provider_id, _ts) = should_update_vod_info(pli, &processed_info_ids);
         if should_update {
-            if let Some(content) = playlist_resolve_download_playlist_item(Arc::clone(&client), pli, fpl.input, errors, resolve_delay, XtreamCluster::Video).await {
+            if let Some(content) = playlist_resolve_download_playlist_item(cfg, Arc::clone(&client), pli, fpl.input, errors, resolve_delay, XtreamCluster::Video).await {
                 let normalized_content = normalize_json_content(content);
                 if let Some((provider_id, info_record)) = extract_info_record_from_vod_info(&normalized_content) {
                     let ts = info_record.ts;
@@ -9,6 +9,7 @@ use ruzstd::decoding::StreamingDecoder;
 use ruzstd::encoding::{compress_to_vec, CompressionLevel};
 use serde::{Deserialize, Serialize};
 use tempfile::NamedTempFile;
+use crate::repository::store_version::{check_store_layout, clear_reprocess_required, decode_store_header, encode_store_header, STORE_HEADER_SIZE};
 use crate::utils;
 use crate::utils::{bincode_deserialize, bincode_serialize};
 
@@ -34,6 +35,22 @@ fn is_file_valid(file: File) -> io::Result<File> {
     Ok(file)
 }
 
+/// The first block holds the store version header followed by the root node.
+/// Trees of older releases have no header block, their root node is the first block.
+fn read_root_offset<R: Read + Seek>(file: &mut R, filepath: &Path) -> io::Result<u64> {
+    let mut header = [0u8; STORE_HEADER_SIZE];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_exact(&mut header)?;
+    match decode_store_header(&header) {
+        Some(version) => {
+            // the keys and values of the tree nodes can't be migrated
+            check_store_layout(filepath, version)?;
+            Ok(BLOCK_SIZE as u64)
+        }
+        None => Ok(0),
+    }
+}
+
 #[inline]
 fn u32_from_bytes(bytes: &[u8]) -> io::Result<u32> {
     Ok(u32::from_le_bytes(bytes.try_into().map_err(to_io_error)?))
@@ -476,13 +493,16 @@ where
             let tempfile = NamedTempFile::new()?;
             let mut file = utils::file_writer(&tempfile); //create_new_file_for_write(&tempfile)?);
             let mut buffer = vec![0u8; BLOCK_SIZE];
-            match self.root.serialize_to_block(&mut file, &mut buffer, 0u64) {
+            buffer[..STORE_HEADER_SIZE].copy_from_slice(&encode_store_header());
+            file.write_all(&buffer)?;
+            match self.root.serialize_to_block(&mut file, &mut buffer, BLOCK_SIZE as u64) {
                 Ok(result) => {
                     file.flush()?;
                     drop(file);
                     if let Err(err) = utils::rename_or_copy(tempfile.path(), filepath, false) {
                         return Err(str_to_io_error(&format!("Temp file rename/copy did not work {} {err}", tempfile.path().to_string_lossy())));
                     }
+                    clear_reprocess_required(filepath);
                     self.dirty = false;
                     Ok(result)
                 }
@@ -498,8 +518,9 @@ where
     pub fn load(filepath: &Path) -> io::Result<Self> {
         let file = is_file_valid(File::open(filepath)?)?;
         let mut reader = utils::file_reader(file);
+        let root_offset = read_root_offset(&mut reader, filepath)?;
         let mut buffer = vec![0u8; BLOCK_SIZE];
-        let (root, _) = BPlusTreeNode::deserialize_from_block(&mut reader, &mut buffer, 0, true)?;
+        let (root, _) = BPlusTreeNode::deserialize_from_block(&mut reader, &mut buffer, root_offset, true)?;
         Ok(Self::new_with_root(root))
     }
 
@@ -511,12 +532,12 @@ where
     }
 }
 
-fn query_tree<K, V, R: Read + Seek>(file: &mut R, key: &K) -> Option<V>
+fn query_tree<K, V, R: Read + Seek>(file: &mut R, root_offset: u64, key: &K) -> Option<V>
 where
     K: Ord + Serialize + for<'de> Deserialize<'de> + Clone,
     V: Serialize + for<'de> Deserialize<'de> + Clone,
 {
-    let mut offset = 0;
+    let mut offset = root_offset;
     let mut buffer = vec![0u8; BLOCK_SIZE];
     loop {
         match BPlusTreeNode::<K, V>::deserialize_from_block(file, &mut buffer, offset, false) {
@@ -570,6 +591,7 @@ where
 ///
 pub struct BPlusTreeQuery<K, V> {
     file: BufReader<File>,
+    root_offset: u64,
     _marker_k: PhantomData<K>,
     _marker_v: PhantomData<V>,
 }
@@ -579,10 +601,12 @@ where
     K: Ord + Serialize + for<'de> Deserialize<'de> + Clone,
     V: Serialize + for<'de> Deserialize<'de> + Clone,
 {
-    pub fn try_from_file(file: File) -> io::Result<Self> {
-        let file = is_file_valid(file)?;
+    pub fn try_from_file(filepath: &Path, file: File) -> io::Result<Self> {
+        let mut file = utils::file_reader(is_file_valid(file)?);
+        let root_offset = read_root_offset(&mut file, filepath)?;
         Ok(Self {
-            file: utils::file_reader(file),
+            file,
+            root_offset,
             _marker_k: PhantomData,
             _marker_v: PhantomData,
         })
@@ -590,11 +614,11 @@ where
 
 
     pub fn try_new(filepath: &Path) -> io::Result<Self> {
-        Self::try_from_file(File::open(filepath)?)
+        Self::try_from_file(filepath, File::open(filepath)?)
     }
 
     pub fn query(&mut self, key: &K) -> Option<V> {
-        query_tree(&mut self.file, key)
+        query_tree(&mut self.file, self.root_offset, key)
     }
 
     // pub fn traverse<F>(&mut self, mut visit: F)
@@ -607,6 +631,7 @@ where
 
 pub struct BPlusTreeUpdate<K, V> {
     file: File,
+    root_offset: u64,
     _marker_k: PhantomData<K>,
     _marker_v: PhantomData<V>,
 }
@@ -620,9 +645,11 @@ where
         if !filepath.exists() {
             return Err(io::Error::new(io::ErrorKind::NotFound, format!("File not found {}", filepath.to_str().unwrap_or("?"))));
         }
-        let file = is_file_valid(utils::open_read_write_file(filepath)?)?;
+        let mut file = is_file_valid(utils::open_read_write_file(filepath)?)?;
+        let root_offset = read_root_offset(&mut file, filepath)?;
         Ok(Self {
             file,
+            root_offset,
             _marker_k: PhantomData,
             _marker_v: PhantomData,
         })
@@ -630,7 +657,7 @@ where
 
     pub fn query(&mut self, key: &K) -> Option<V> {
         let mut reader = utils::file_reader(&mut self.file);
-        query_tree(&mut reader, key)
+        query_tree(&mut reader, self.root_offset, key)
     }
 
     fn serialize_node(&mut self, offset: u64, node: &BPlusTreeNode<K, V>) -> io::Result<u64> {
@@ -641,7 +668,7 @@ where
     }
 
     pub fn update(&mut self, key: &K, value: V) -> io::Result<u64> {
-        let mut offset = 0;
+        let mut offset = self.root_offset;
         let mut buffer = vec![0u8; BLOCK_SIZE];
         let mut reader = utils::file_reader(&mut self.file);
         loop {
@@ -784,7 +811,7 @@ mod tests {
     #[test]
     fn insert_test() -> io::Result<()> {
         let test_size = 500;
-        let content = crate::utils::generate_random_string(1024);
+        let content = shared::utils::generate_random_string(1024);
         let mut tree = BPlusTree::<u32, Record>::new();
         for i in 0u32..=test_size {
             tree.insert(i, Record {
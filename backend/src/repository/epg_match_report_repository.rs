@@ -0,0 +1,33 @@
+use crate::model::{Config, EpgMatchReport};
+use crate::repository::storage::{ensure_target_storage_path, get_target_storage_path};
+use crate::repository::storage_const;
+use crate::utils::json_write_documents_to_file;
+use shared::error::to_io_error;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+fn get_epg_match_report_file_path(target_path: &Path) -> PathBuf {
+    target_path.join(storage_const::FILE_EPG_MATCH_REPORT)
+}
+
+pub async fn load_epg_match_report(cfg: &Config, target_name: &str) -> EpgMatchReport {
+    let Some(target_path) = get_target_storage_path(cfg, target_name) else {
+        return EpgMatchReport::default();
+    };
+    let path = get_epg_match_report_file_path(&target_path);
+    if !path.exists() {
+        return EpgMatchReport::default();
+    }
+    let _file_lock = cfg.file_locks.read_lock(&path).await;
+    tokio::fs::read_to_string(&path).await.ok()
+        .and_then(|content| serde_json::from_str::<EpgMatchReport>(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Replaces the report of the previous playlist update.
+pub async fn save_epg_match_report(cfg: &Config, target_name: &str, report: &EpgMatchReport) -> Result<(), Error> {
+    let target_path = ensure_target_storage_path(cfg, target_name).map_err(to_io_error)?;
+    let path = get_epg_match_report_file_path(&target_path);
+    let _file_lock = cfg.file_locks.write_lock(&path).await;
+    json_write_documents_to_file(&path, report)
+}
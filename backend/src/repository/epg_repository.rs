@@ -1,57 +1,132 @@
 use shared::error::{notify_err, TuliproxError, TuliproxErrorKind};
 use crate::model::{Config, ConfigTarget, TargetOutput};
-use crate::model::Epg;
+use crate::model::{EpgJsonChannel, MergedEpg, XmlTag, EPG_ATTRIB_CHANNEL, EPG_ATTRIB_ID};
 use crate::repository::m3u_repository::m3u_get_epg_file_path;
+use crate::repository::storage::get_target_storage_path;
+use crate::repository::storage_const;
 use crate::repository::xtream_repository::{xtream_get_epg_file_path, xtream_get_storage_path};
-use crate::utils::debug_if_enabled;
+use crate::utils::{debug_if_enabled, json_write_documents_to_file, short_hash};
 use quick_xml::Writer;
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{Cursor, Write};
-use std::path::Path;
-
-fn epg_write_file(target: &ConfigTarget, epg: &Epg, path: &Path) -> Result<(), TuliproxError> {
-    let mut writer = Writer::new(Cursor::new(vec![]));
-    match epg.write_to(&mut writer) {
-        Ok(()) => {
-            let result = writer.into_inner().into_inner();
-            match File::create(path) {
-                Ok(mut epg_file) => {
-                    match epg_file.write_all("<?xml version=\"1.0\" encoding=\"utf-8\" ?><!DOCTYPE tv SYSTEM \"xmltv.dtd\">".as_bytes()) {
-                        Ok(()) => {}
-                        Err(err) => return Err(notify_err!(format!("failed to write epg: {} - {}", path.to_str().unwrap_or("?"), err))),
-                    }
-                    match epg_file.write_all(&result) {
-                        Ok(()) => {
-                            debug_if_enabled!("Epg for target {} written to {}", target.name, path.to_str().unwrap_or("?"));
-                        }
-                        Err(err) => return Err(notify_err!(format!("failed to write epg: {} - {}", path.to_str().unwrap_or("?"), err))),
-                    }
-                }
-                Err(err) => return Err(notify_err!(format!("failed to write epg: {} - {}", path.to_str().unwrap_or("?"), err))),
-            }
-        }
-        Err(err) => return Err(notify_err!(format!("failed to write epg: {} - {}", path.to_str().unwrap_or("?"), err))),
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const XMLTV_HEADER: &str = "<?xml version=\"1.0\" encoding=\"utf-8\" ?><!DOCTYPE tv SYSTEM \"xmltv.dtd\">";
+
+fn get_epg_icons_file_path(target_path: &Path) -> PathBuf {
+    target_path.join(storage_const::FILE_EPG_ICONS)
+}
+
+// The override icons are stored by their hash, the icon resource requests of the clients are resolved with it.
+fn epg_write_icons(target_path: &Path, epg: &MergedEpg) -> Result<(), TuliproxError> {
+    let path = get_epg_icons_file_path(target_path);
+    let icons: HashMap<String, &String> = epg.override_icons().map(|src| (short_hash(src), src)).collect();
+    let result = if icons.is_empty() {
+        if path.exists() { std::fs::remove_file(&path) } else { Ok(()) }
+    } else {
+        json_write_documents_to_file(&path, &icons)
+    };
+    result.map_err(|err| notify_err!(format!("failed to write epg icons: {} - {err}", path.to_str().unwrap_or("?"))))
+}
+
+/// The original urls of the override icons by their hash, empty if the target has none.
+pub async fn load_epg_icons(cfg: &Config, target_name: &str) -> HashMap<String, String> {
+    let Some(path) = get_target_storage_path(cfg, target_name).map(|target_path| get_epg_icons_file_path(&target_path)) else {
+        return HashMap::new();
+    };
+    if !path.exists() {
+        return HashMap::new();
+    }
+    tokio::fs::read_to_string(&path).await.ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// The json guide is stored next to the xmltv file.
+pub fn epg_get_json_file_path(epg_path: &Path) -> PathBuf {
+    epg_path.with_extension("json")
+}
+
+fn epg_write_file(target: &ConfigTarget, epg: &MergedEpg, path: &Path, epg_json: bool) -> Result<(), TuliproxError> {
+    let to_error = |path: &Path, err: &dyn std::fmt::Display| notify_err!(format!("failed to write epg: {} - {err}", path.to_str().unwrap_or("?")));
+    let epg_file = File::create(path).map_err(|err| to_error(path, &err))?;
+    // the tags are streamed into the file, large guides are never serialized into memory
+    xmltv_write(epg, BufWriter::new(epg_file)).map_err(|err| to_error(path, &err))?;
+    debug_if_enabled!("Epg for target {} written to {}", target.name, path.to_str().unwrap_or("?"));
+    if epg_json {
+        let json_path = epg_get_json_file_path(path);
+        let json_file = File::create(&json_path).map_err(|err| to_error(&json_path, &err))?;
+        epg_json_write(epg, BufWriter::new(json_file)).map_err(|err| to_error(&json_path, &err))?;
+        debug_if_enabled!("Json epg for target {} written to {}", target.name, json_path.to_str().unwrap_or("?"));
     }
     Ok(())
 }
 
-pub fn epg_write(target: &ConfigTarget, cfg: &Config, target_path: &Path, epg: Option<&Epg>, output: &TargetOutput) -> Result<(), TuliproxError> {
+/// Writes the xmltv document, a `GzEncoder` can be used as writer to write a compressed file.
+pub fn xmltv_write<W: Write>(epg: &MergedEpg, mut writer: W) -> std::io::Result<W> {
+    writer.write_all(XMLTV_HEADER.as_bytes())?;
+    let mut xml_writer = Writer::new(writer);
+    epg.write_to(&mut xml_writer).map_err(std::io::Error::other)?;
+    let mut writer = xml_writer.into_inner();
+    writer.flush()?;
+    Ok(writer)
+}
+
+fn epg_json_write_channel<W: Write>(writer: &mut W, first: &mut bool, channel: &EpgJsonChannel) -> std::io::Result<()> {
+    if !std::mem::take(first) {
+        writer.write_all(b",")?;
+    }
+    serde_json::to_writer(writer, channel).map_err(std::io::Error::other)
+}
+
+/// Writes the json guide `{"channels": [...]}` with the programmes of each channel, one channel is serialized at a time.
+/// Programmes of channels the guide does not define are written after the defined channels.
+pub fn epg_json_write<W: Write>(epg: &MergedEpg, mut writer: W) -> std::io::Result<W> {
+    let mut programmes: HashMap<&str, Vec<&XmlTag>> = HashMap::new();
+    for tag in epg.programmes() {
+        if let Some(channel_id) = tag.get_attribute_value(EPG_ATTRIB_CHANNEL) {
+            programmes.entry(channel_id.as_str()).or_default().push(tag);
+        }
+    }
+    writer.write_all(b"{\"channels\":[")?;
+    let mut first = true;
+    for channel in epg.channels() {
+        if let Some(id) = channel.get_attribute_value(EPG_ATTRIB_ID) {
+            let tags = programmes.remove(id.as_str()).unwrap_or_default();
+            epg_json_write_channel(&mut writer, &mut first, &EpgJsonChannel::new(id, Some(channel), &tags))?;
+        }
+    }
+    let mut remaining: Vec<(&str, Vec<&XmlTag>)> = programmes.into_iter().collect();
+    remaining.sort_by_key(|(id, _)| *id);
+    for (id, tags) in remaining {
+        epg_json_write_channel(&mut writer, &mut first, &EpgJsonChannel::new(id, None, &tags))?;
+    }
+    writer.write_all(b"]}")?;
+    writer.flush()?;
+    Ok(writer)
+}
+
+pub fn epg_write(target: &ConfigTarget, cfg: &Config, target_path: &Path, epg: Option<&MergedEpg>, output: &TargetOutput) -> Result<(), TuliproxError> {
     if let Some(epg_data) = epg {
+        if matches!(output, TargetOutput::Xtream(_) | TargetOutput::M3u(_)) {
+            epg_write_icons(target_path, epg_data)?;
+        }
         match output {
-            TargetOutput::Xtream(_) => {
+            TargetOutput::Xtream(xtream_output) => {
                 match xtream_get_storage_path(cfg, &target.name) {
                     Some(path) => {
                         let epg_path = xtream_get_epg_file_path(&path);
                         debug_if_enabled!("writing xtream epg to {}", epg_path.to_str().unwrap_or("?"));
-                        epg_write_file(target, epg_data, &epg_path)?;
+                        epg_write_file(target, epg_data, &epg_path, xtream_output.epg_json)?;
                     }
                     None => return Err(notify_err!(format!("failed to serialize epg for target: {}, storage path not found", target.name))),
                 }
             }
-            TargetOutput::M3u(_) => {
+            TargetOutput::M3u(m3u_output) => {
                 let path = m3u_get_epg_file_path(target_path);
                 debug_if_enabled!("writing m3u epg to {}", path.to_str().unwrap_or("?"));
-                epg_write_file(target, epg_data, &path)?;
+                epg_write_file(target, epg_data, &path, m3u_output.epg_json)?;
             }
             TargetOutput::Strm(_) | TargetOutput::HdHomeRun(_) => {}
         }
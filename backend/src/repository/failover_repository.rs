@@ -0,0 +1,52 @@
+use crate::model::{ChannelFailover, Config, PlaylistGroup};
+use crate::repository::bplustree::{BPlusTree, BPlusTreeQuery};
+use crate::repository::storage::get_target_storage_path;
+use crate::repository::storage_const;
+use log::error;
+use shared::model::XtreamCluster;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+fn get_failover_file_path(target_path: &Path) -> PathBuf {
+    target_path.join(storage_const::FILE_CHANNEL_FAILOVER)
+}
+
+/// Stores the failover urls of the live channels by virtual id, the virtual ids have to be assigned.
+pub(in crate::repository) async fn persist_channel_failover(cfg: &Config, target_path: &Path, playlist: &[PlaylistGroup]) -> Result<(), Error> {
+    let path = get_failover_file_path(target_path);
+    let _file_lock = cfg.file_locks.write_lock(&path).await;
+    let mut tree: BPlusTree<u32, Vec<ChannelFailover>> = BPlusTree::new();
+    let mut empty = true;
+    for channel in playlist.iter().filter(|group| group.xtream_cluster == XtreamCluster::Live).flat_map(|group| &group.channels) {
+        if !channel.header.failover.is_empty() {
+            tree.insert(channel.header.virtual_id, channel.header.failover.clone());
+            empty = false;
+        }
+    }
+    if empty {
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        return Ok(());
+    }
+    tree.store(&path).map(|_| ())
+}
+
+/// Returns the failover urls of the channel, empty if the channel has none.
+pub async fn load_channel_failover(cfg: &Config, target_name: &str, virtual_id: u32) -> Vec<ChannelFailover> {
+    let Some(target_path) = get_target_storage_path(cfg, target_name) else {
+        return vec![];
+    };
+    let path = get_failover_file_path(&target_path);
+    if !path.exists() {
+        return vec![];
+    }
+    let _file_lock = cfg.file_locks.read_lock(&path).await;
+    match BPlusTreeQuery::<u32, Vec<ChannelFailover>>::try_new(&path) {
+        Ok(mut tree) => tree.query(&virtual_id).unwrap_or_default(),
+        Err(err) => {
+            error!("Could not load failover for target {target_name}: {err}");
+            vec![]
+        }
+    }
+}
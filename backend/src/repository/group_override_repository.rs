@@ -0,0 +1,144 @@
+use crate::model::{Config, GroupOverrides};
+use crate::repository::storage::{ensure_target_storage_path, get_target_storage_path};
+use crate::repository::storage_const;
+use crate::utils::json_write_documents_to_file;
+use log::error;
+use shared::error::to_io_error;
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::SystemTime;
+
+// the parsed overrides with the modification time of their file
+type CachedGroupOverrides = (SystemTime, Option<GroupOverrides>);
+
+// the overrides are read for every playlist request, they are parsed again when the file changes
+static GROUP_OVERRIDES_CACHE: LazyLock<Mutex<HashMap<PathBuf, CachedGroupOverrides>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+// an update is a read-modify-write of the overrides file, updates of a target are serialized
+static GROUP_OVERRIDES_UPDATE_LOCKS: LazyLock<Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> = LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn get_group_overrides_update_lock(target_name: &str) -> Arc<tokio::sync::Mutex<()>> {
+    GROUP_OVERRIDES_UPDATE_LOCKS.lock()
+        .map_or_else(|_| Arc::default(), |mut locks| Arc::clone(locks.entry(target_name.to_string()).or_default()))
+}
+
+fn get_group_overrides_file_path(target_path: &Path) -> PathBuf {
+    target_path.join(storage_const::FILE_GROUP_OVERRIDES)
+}
+
+pub async fn load_group_overrides(cfg: &Config, target_name: &str) -> Option<GroupOverrides> {
+    match try_load_group_overrides(cfg, target_name).await {
+        Ok(overrides) => overrides,
+        Err(err) => {
+            error!("Failed to load group overrides for target {target_name}: {err}");
+            None
+        }
+    }
+}
+
+/// Loads the overrides of the target, `Ok(None)` if the target has none.
+pub async fn try_load_group_overrides(cfg: &Config, target_name: &str) -> Result<Option<GroupOverrides>, Error> {
+    let Some(target_path) = get_target_storage_path(cfg, target_name) else {
+        return Ok(None);
+    };
+    let path = get_group_overrides_file_path(&target_path);
+    let _file_lock = cfg.file_locks.read_lock(&path).await;
+    let modified = match tokio::fs::metadata(&path).await.and_then(|metadata| metadata.modified()) {
+        Ok(modified) => modified,
+        Err(err) if err.kind() == ErrorKind::NotFound => return Ok(None),
+        Err(err) => return Err(err),
+    };
+    if let Ok(cache) = GROUP_OVERRIDES_CACHE.lock() {
+        if let Some((_, overrides)) = cache.get(&path).filter(|(cached_modified, _)| *cached_modified == modified) {
+            return Ok(overrides.clone());
+        }
+    }
+    let content = tokio::fs::read_to_string(&path).await?;
+    let overrides = serde_json::from_str::<GroupOverrides>(&content).map_err(to_io_error)?;
+    let overrides = Some(overrides).filter(|overrides| !overrides.is_empty());
+    if let Ok(mut cache) = GROUP_OVERRIDES_CACHE.lock() {
+        cache.insert(path, (modified, overrides.clone()));
+    }
+    Ok(overrides)
+}
+
+async fn save_group_overrides(cfg: &Config, target_name: &str, overrides: &GroupOverrides) -> Result<(), Error> {
+    let target_path = ensure_target_storage_path(cfg, target_name).map_err(to_io_error)?;
+    let path = get_group_overrides_file_path(&target_path);
+    let _file_lock = cfg.file_locks.write_lock(&path).await;
+    // the modification time can be unchanged if the file is written twice within its resolution
+    if let Ok(mut cache) = GROUP_OVERRIDES_CACHE.lock() {
+        cache.remove(&path);
+    }
+    if overrides.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        Ok(())
+    } else {
+        json_write_documents_to_file(&path, overrides)
+    }
+}
+
+/// Applies `update` to the overrides of the target and saves them.
+/// Nothing is saved if the existing overrides can't be loaded.
+pub async fn update_group_overrides<F>(cfg: &Config, target_name: &str, update: F) -> Result<GroupOverrides, Error>
+where
+    F: FnOnce(&mut GroupOverrides),
+{
+    let update_lock = get_group_overrides_update_lock(target_name);
+    let _update_guard = update_lock.lock().await;
+    let mut overrides = try_load_group_overrides(cfg, target_name).await?.unwrap_or_default();
+    update(&mut overrides);
+    save_group_overrides(cfg, target_name, &overrides).await?;
+    Ok(overrides)
+}
+
+pub async fn reset_group_overrides(cfg: &Config, target_name: &str) -> Result<GroupOverrides, Error> {
+    let update_lock = get_group_overrides_update_lock(target_name);
+    let _update_guard = update_lock.lock().await;
+    let overrides = GroupOverrides::default();
+    save_group_overrides(cfg, target_name, &overrides).await?;
+    Ok(overrides)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::GroupBulkOperation;
+    use shared::model::XtreamCluster;
+
+    fn hide(group: &str) -> GroupBulkOperation {
+        GroupBulkOperation::Hide { cluster: XtreamCluster::Live, groups: vec![group.to_string()] }
+    }
+
+    #[tokio::test]
+    async fn test_update_group_overrides() {
+        let dir = tempfile::tempdir().unwrap();
+        let cfg = Arc::new(Config { working_dir: dir.path().to_string_lossy().to_string(), ..Default::default() });
+
+        let updates = (0..10).map(|index| {
+            let cfg = Arc::clone(&cfg);
+            tokio::spawn(async move {
+                update_group_overrides(&cfg, "target", |overrides| overrides.apply_operation(hide(&format!("group {index}")))).await
+            })
+        }).collect::<Vec<_>>();
+        for update in updates {
+            assert!(update.await.unwrap().is_ok());
+        }
+        let overrides = try_load_group_overrides(&cfg, "target").await.unwrap().unwrap();
+        assert!((0..10).all(|index| overrides.is_hidden(XtreamCluster::Live, &format!("group {index}"))), "no update is lost");
+
+        let path = get_group_overrides_file_path(&get_target_storage_path(&cfg, "target").unwrap());
+        std::fs::write(&path, "[{\"cluster\":").unwrap();
+        assert!(try_load_group_overrides(&cfg, "target").await.is_err());
+        assert!(update_group_overrides(&cfg, "target", |overrides| overrides.apply_operation(hide("other"))).await.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "[{\"cluster\":", "the unreadable overrides are kept");
+
+        assert!(reset_group_overrides(&cfg, "target").await.is_ok());
+        assert!(!path.exists());
+        assert!(try_load_group_overrides(&cfg, "target").await.unwrap().is_none());
+    }
+}
@@ -5,7 +5,10 @@ use std::marker::PhantomData;
 use std::path::{Path, PathBuf};
 
 use crate::repository::bplustree::{BPlusTree, BPlusTreeQuery};
+use crate::repository::store_version::{check_store_layout, clear_reprocess_required, decode_store_header, encode_store_header, get_record_migrations, migrate_record, RecordMigration, LEGACY_STORE_VERSION, STORE_HEADER_SIZE};
 use log::error;
+use ruzstd::decoding::StreamingDecoder;
+use ruzstd::encoding::{compress_to_vec, CompressionLevel};
 use serde::{Deserialize, Serialize};
 use tempfile::NamedTempFile;
 use shared::error::{str_to_io_error, to_io_error};
@@ -14,6 +17,10 @@ use crate::utils::{bincode_deserialize, bincode_serialize};
 
 const BLOCK_SIZE: usize = 4096;
 const LEN_SIZE: usize = 4;
+// flags of the first byte of the content file
+const FLAG_FRAGMENTED: u8 = 1;
+const FLAG_COMPRESSED: u8 = 2;
+const FLAG_SIZE: usize = 1;
 
 pub(in crate::repository) type OffsetPointer = u32;
 type SizeType = u32;
@@ -23,17 +30,62 @@ pub type IndexedDocumentIndex<K> = BPlusTree<K, OffsetPointer>;
 pub(in crate::repository) struct IndexedDocument {}
 
 impl IndexedDocument {
-    pub(in crate::repository) fn read_fragmentation<R: Read + Seek>(file: &mut R) -> std::io::Result<bool> {
+    fn read_flags<R: Read + Seek>(file: &mut R) -> std::io::Result<u8> {
         file.seek(SeekFrom::Start(0))?;
-        let mut bool_bytes = [0u8];
-        file.read_exact(&mut bool_bytes)?;
-        Ok(u8::from_le_bytes(bool_bytes) == 1)
+        let mut flag_bytes = [0u8];
+        file.read_exact(&mut flag_bytes)?;
+        Ok(u8::from_le_bytes(flag_bytes))
+    }
+
+    pub(in crate::repository) fn read_fragmentation<R: Read + Seek>(file: &mut R) -> std::io::Result<bool> {
+        Ok(Self::read_flags(file)? & FLAG_FRAGMENTED != 0)
+    }
+
+    pub(in crate::repository) fn read_compression<R: Read + Seek>(file: &mut R) -> std::io::Result<bool> {
+        Ok(Self::read_flags(file)? & FLAG_COMPRESSED != 0)
     }
 
-    pub(in crate::repository) fn write_fragmentation<W: Write + Seek>(file: &mut W, fragmented: bool) -> std::io::Result<()> {
+    /// Returns the version of the store header following the flags byte, `None` for stores of older releases.
+    fn read_store_header<R: Read + Seek>(file: &mut R) -> std::io::Result<Option<u32>> {
+        file.seek(SeekFrom::Start(FLAG_SIZE as u64))?;
+        let mut header = [0u8; STORE_HEADER_SIZE];
+        match file.read_exact(&mut header) {
+            Ok(()) => Ok(decode_store_header(&header)),
+            Err(err) if err.kind() == ErrorKind::UnexpectedEof => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn read_record_migrations<R: Read + Seek>(file: &mut R, main_path: &Path) -> std::io::Result<Vec<RecordMigration>> {
+        let version = Self::read_store_header(file)?.unwrap_or(LEGACY_STORE_VERSION);
+        get_record_migrations(main_path, version)
+    }
+
+    pub(in crate::repository) fn write_flags<W: Write + Seek>(file: &mut W, fragmented: bool, compressed: bool) -> std::io::Result<()> {
         file.seek(SeekFrom::Start(0))?;
-        let fragmented_byte = if fragmented { 1u8.to_le_bytes() } else { 0u8.to_le_bytes() };
-        file.write_all(&fragmented_byte)
+        let flags = (if fragmented { FLAG_FRAGMENTED } else { 0 }) | (if compressed { FLAG_COMPRESSED } else { 0 });
+        file.write_all(&flags.to_le_bytes())
+    }
+
+    fn encode_content<T: ?Sized + Serialize>(doc: &T, compressed: bool) -> Result<Vec<u8>, Error> {
+        let encoded_bytes = bincode_serialize(doc).map_err(|_| Error::new(ErrorKind::InvalidData, "Failed to serialize document"))?;
+        Ok(if compressed { compress_to_vec(&*encoded_bytes, CompressionLevel::Fastest) } else { encoded_bytes })
+    }
+
+    // compressed records are decompressed one by one, the stores are never decompressed into memory
+    fn decode_content<T: serde::de::DeserializeOwned>(content_bytes: &[u8], compressed: bool, migrations: &[RecordMigration]) -> Result<T, Error> {
+        if !compressed && migrations.is_empty() {
+            return bincode_deserialize::<T>(content_bytes).map_err(to_io_error);
+        }
+        let content = if compressed {
+            let mut decoder = StreamingDecoder::new(content_bytes).map_err(to_io_error)?;
+            let mut content = Vec::with_capacity(content_bytes.len() * 4);
+            decoder.read_to_end(&mut content)?;
+            content
+        } else {
+            content_bytes.to_vec()
+        };
+        bincode_deserialize::<T>(&migrate_record(content, migrations)?).map_err(to_io_error)
     }
 
     pub(in crate::repository) fn read_content_size<R: Read + Seek>(reader: &mut R) -> Result<usize, Error>
@@ -68,8 +120,10 @@ impl IndexedDocument {
  * - content
  * - index
  *
+ * The first byte of the content file holds the fragmentation and compression flags,
+ * followed by the store version header. Files of older releases have no version header.
  * Layout of content file record is:
- *   - content-size (u32) + content (deflate)
+ *   - content-size (u32) + content (bincode, zstd if compressed)
  *
  * index file is a bplustree
  */
@@ -84,14 +138,21 @@ where
     index_tree: IndexedDocumentIndex<K>,
     dirty: bool,
     fragmented: bool,
+    compressed: bool,
 }
 
 impl<K> IndexedDocumentWriter<K>
 where
     K: Ord + Serialize + for<'de> Deserialize<'de> + Clone + Debug,
 {
-    fn new_with_mode(main_path: PathBuf, index_path: PathBuf, append: bool) -> Result<Self, Error> {
-        let append_mode = append && main_path.exists();
+    fn new_with_mode(main_path: PathBuf, index_path: PathBuf, append: bool, compress: bool) -> Result<Self, Error> {
+        let mut append_mode = append && main_path.exists();
+        if append_mode {
+            // records can only be appended to stores with the current layout, others are written again
+            let mut main_file = utils::open_readonly_file(&main_path)?;
+            let version = IndexedDocument::read_store_header(&mut main_file).unwrap_or(None).unwrap_or(LEGACY_STORE_VERSION);
+            append_mode = check_store_layout(&main_path, version).is_ok();
+        }
         let mut main_file = if append_mode {
             utils::open_read_write_file(&main_path)
         } else {
@@ -105,11 +166,16 @@ where
             .unwrap_or(0);
 
         let mut fragmented = false;
+        let mut compressed = compress;
         if main_offset == 0 {
-            IndexedDocument::write_fragmentation(&mut main_file, false)?;
-            main_offset = 1;
+            IndexedDocument::write_flags(&mut main_file, false, compressed)?;
+            main_file.write_all(&encode_store_header())?;
+            main_offset = SizeType::try_from(FLAG_SIZE + STORE_HEADER_SIZE).map_err(to_io_error)?;
+            clear_reprocess_required(&main_path);
         } else {
+            // appended records keep the compression of the existing file
             fragmented = IndexedDocument::read_fragmentation(&mut main_file)?;
+            compressed = IndexedDocument::read_compression(&mut main_file)?;
         }
 
         // Initialize the index tree (BPlusTree) - either by deserializing an existing one or creating a new one
@@ -130,15 +196,17 @@ where
             index_tree,
             dirty: false,
             fragmented,
+            compressed,
         })
     }
 
-    pub fn new(main_path: PathBuf, index_path: PathBuf) -> Result<Self, Error> {
-        Self::new_with_mode(main_path, index_path, false)
+    /// Creates a store with zstd compressed records if `compress` is set.
+    pub fn new_with_compression(main_path: PathBuf, index_path: PathBuf, compress: bool) -> Result<Self, Error> {
+        Self::new_with_mode(main_path, index_path, false, compress)
     }
 
     pub fn new_append(main_path: PathBuf, index_path: PathBuf) -> Result<Self, Error> {
-        Self::new_with_mode(main_path, index_path, true)
+        Self::new_with_mode(main_path, index_path, true, false)
     }
 
     pub fn store(&mut self) -> std::io::Result<()> {
@@ -163,7 +231,7 @@ where
     where
         T: ?Sized + serde::Serialize,
     {
-        let encoded_bytes = bincode_serialize(doc).map_err(|_| Error::new(ErrorKind::InvalidData, "Failed to serialize document"))?;
+        let encoded_bytes = IndexedDocument::encode_content(doc, self.compressed)?;
         let mut new_record_appended = false; // do i need to change the index and set the new offset
         if let Some(&offset) = self.index_tree.query(&doc_id) {
             self.main_file.seek(SeekFrom::Start(u64::from(offset)))?;
@@ -183,7 +251,7 @@ where
                 // does not fit we need to append, file is fragmented
                 if !self.fragmented {
                     self.fragmented = true;
-                    IndexedDocument::write_fragmentation(&mut self.main_file, true)?;
+                    IndexedDocument::write_flags(&mut self.main_file, true, self.compressed)?;
                 }
                 self.main_file.seek(SeekFrom::End(0))?;
                 new_record_appended = true;
@@ -237,6 +305,8 @@ where
 {
     main_file: BufReader<File>,
     index_tree: IndexedDocumentIndex<K>,
+    compressed: bool,
+    migrations: Vec<RecordMigration>,
     t_type: PhantomData<T>,
 }
 
@@ -248,12 +318,16 @@ where
 {
     pub fn new(main_path: &Path, index_path: &Path) -> Result<Self, Error> {
         if main_path.exists() && index_path.exists() {
-            let main_file = utils::open_readonly_file(main_path)?;
+            let mut main_file = utils::file_reader(utils::open_readonly_file(main_path)?);
+            let compressed = IndexedDocument::read_compression(&mut main_file)?;
+            let migrations = IndexedDocument::read_record_migrations(&mut main_file, main_path)?;
             let index_tree = IndexedDocumentIndex::<K>::load(index_path)?;
 
             Ok(Self {
-                main_file: utils::file_reader(main_file),
+                main_file,
                 index_tree,
+                compressed,
+                migrations,
                 t_type: PhantomData,
             })
         } else {
@@ -266,7 +340,7 @@ where
             let buf_size = IndexedDocument::read_content_size(&mut self.main_file)?;
             let mut buffer: Vec<u8> = vec![0; buf_size];
             self.main_file.read_exact(&mut buffer)?;
-            if let Ok(item) = bincode_deserialize::<T>(&buffer) {
+            if let Ok(item) = IndexedDocument::decode_content::<T>(&buffer, self.compressed, &self.migrations) {
                 return Ok(item);
             }
         }
@@ -286,6 +360,8 @@ pub(in crate::repository) struct IndexedDocumentIterator<K, T> {
     offsets: Vec<OffsetPointer>,
     index: usize,
     failed: bool,
+    compressed: bool,
+    migrations: Vec<RecordMigration>,
     t_buffer: Vec<u8>,
     t_type: PhantomData<T>,
     k_type: PhantomData<K>,
@@ -306,12 +382,17 @@ where
             }
             match File::open(main_path) {
                 Ok(file) => {
+                    let mut main_file = utils::file_reader(file);
+                    let compressed = IndexedDocument::read_compression(&mut main_file)?;
+                    let migrations = IndexedDocument::read_record_migrations(&mut main_file, main_path)?;
                     Ok(Self {
                         main_path: main_path.to_path_buf(),
-                        main_file: utils::file_reader(file),
+                        main_file,
                         offsets,
                         index: 0,
                         failed: false,
+                        compressed,
+                        migrations,
                         t_buffer: Vec::with_capacity(BLOCK_SIZE),
                         t_type: PhantomData,
                         k_type: PhantomData,
@@ -352,7 +433,7 @@ where
         // read content
         self.main_file.read_exact(&mut self.t_buffer[0..buf_size])?;
         // deserialize buffer
-        match bincode_deserialize::<T>(&self.t_buffer[0..buf_size]) {
+        match IndexedDocument::decode_content::<T>(&self.t_buffer[0..buf_size], self.compressed, &self.migrations) {
             Ok(value) => Ok(Some((value, has_next))),
             Err(err) => {
                 self.failed = true;
@@ -396,11 +477,13 @@ impl IndexedDocumentDirectAccess {
             // get the offset from index
             let offset = IndexedDocument::get_offset(index_path, doc_id)?;
             let mut main_file = File::open(main_path)?;
+            let compressed = IndexedDocument::read_compression(&mut main_file)?;
+            let migrations = IndexedDocument::read_record_migrations(&mut main_file, main_path)?;
             main_file.seek(SeekFrom::Start(offset))?;
             let buf_size = IndexedDocument::read_content_size(&mut main_file)?;
             let mut buffer: Vec<u8> = vec![0; buf_size];
             main_file.read_exact(&mut buffer)?;
-            if let Ok(item) = bincode_deserialize::<T>(&buffer) {
+            if let Ok(item) = IndexedDocument::decode_content::<T>(&buffer, compressed, &migrations) {
                 return Ok(item);
             }
         }
@@ -459,6 +542,8 @@ where
         if !fragmented {
             return Ok(());
         }
+        let compressed = IndexedDocument::read_compression(&mut self.main_file)?;
+        let has_store_header = IndexedDocument::read_store_header(&mut self.main_file)?.is_some();
 
         let gc_file = NamedTempFile::new()?;
         let gc_path = gc_file.path();
@@ -469,10 +554,17 @@ where
             });
             let mut gc_writer = utils::file_writer(&gc_file);
 
-            let fragmented_byte = 0u8.to_le_bytes();
-            gc_writer.write_all(&fragmented_byte)?;
-
-            let mut gc_offset = 1usize; // offset is 1 because of fragment bit
+            // the records are copied as they are, the compression and the version are kept
+            let flags = if compressed { FLAG_COMPRESSED } else { 0 };
+            gc_writer.write_all(&flags.to_le_bytes())?;
+            let mut gc_offset = FLAG_SIZE;
+            if has_store_header {
+                let mut header = [0u8; STORE_HEADER_SIZE];
+                self.main_file.seek(SeekFrom::Start(FLAG_SIZE as u64))?;
+                self.main_file.read_exact(&mut header)?;
+                gc_writer.write_all(&header)?;
+                gc_offset += STORE_HEADER_SIZE;
+            }
             let mut buffer: Vec<u8> = Vec::with_capacity(BLOCK_SIZE);
             let mut size_bytes = [0u8; LEN_SIZE];
             for (key, offset) in key_offset {
@@ -511,10 +603,12 @@ mod tests {
     use std::path::PathBuf;
 
     use serde::{Deserialize, Serialize};
-    use crate::model::XtreamPlaylistItem;
+    use shared::model::PlaylistItemType;
+    use crate::model::{M3uPlaylistItem, XtreamPlaylistItem};
     // use crate::model::XtreamPlaylistItem;
-    use crate::repository::indexed_document::{IndexedDocumentGarbageCollector, IndexedDocumentIterator, IndexedDocumentWriter};
-    use crate::utils::resolve_env_var;
+    use crate::repository::indexed_document::{IndexedDocumentDirectAccess, IndexedDocumentGarbageCollector, IndexedDocumentIndex, IndexedDocumentIterator, IndexedDocumentWriter};
+    use crate::repository::store_version::get_reprocess_required_stores;
+    use crate::utils::{bincode_serialize, resolve_env_var};
 
     // Example usage with a simple struct
     #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
@@ -528,7 +622,7 @@ mod tests {
         let main_path = PathBuf::from("/tmp/main.iw");
         let index_path = PathBuf::from("/tmp/main.iw.idx");
         {
-            let mut idw = IndexedDocumentWriter::new(main_path.clone(), index_path.clone())?;
+            let mut idw = IndexedDocumentWriter::new_with_compression(main_path.clone(), index_path.clone(), false)?;
 
             for i in 0u32..=500 {
                 idw.write_doc(i, &Record {
@@ -586,6 +680,106 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn compressed_test() -> io::Result<()> {
+        let main_path = PathBuf::from("/tmp/main_compressed.iw");
+        let index_path = PathBuf::from("/tmp/main_compressed.iw.idx");
+        let record = |i: u32| Record { id: i, data: format!("Entry {i} ").repeat(20) };
+        {
+            let mut idw = IndexedDocumentWriter::new_with_compression(main_path.clone(), index_path.clone(), true)?;
+            for i in 0u32..=100 {
+                idw.write_doc(i, &record(i))?;
+            }
+            idw.store()?;
+        }
+        {
+            // appended records keep the compression
+            let mut idw = IndexedDocumentWriter::new_append(main_path.clone(), index_path.clone())?;
+            idw.write_doc(101, &record(101))?;
+            idw.store()?;
+        }
+        let size_compressed = std::fs::metadata(&main_path)?.len();
+        assert!(size_compressed < 102 * 200, "Failed, the records should be compressed");
+
+        let reader = IndexedDocumentIterator::<u32, Record>::new(&main_path, &index_path)?;
+        let docs: Vec<Record> = reader.map(|(doc, _)| doc).collect();
+        assert_eq!(docs.len(), 102);
+        assert!(docs.iter().enumerate().all(|(i, doc)| *doc == record(u32::try_from(i).unwrap())));
+        assert_eq!(IndexedDocumentDirectAccess::read_indexed_item::<u32, Record>(&main_path, &index_path, &42)?, record(42));
+        Ok(())
+    }
+
+    // the layout of `M3uPlaylistItem` in 3.1.4, without `attributes`
+    #[derive(Serialize)]
+    struct LegacyM3uPlaylistItem {
+        virtual_id: u32,
+        provider_id: String,
+        name: String,
+        chno: String,
+        logo: String,
+        logo_small: String,
+        group: String,
+        title: String,
+        parent_code: String,
+        audio_track: String,
+        time_shift: String,
+        rec: String,
+        url: String,
+        epg_channel_id: Option<String>,
+        input_name: String,
+        item_type: PlaylistItemType,
+    }
+
+    #[test]
+    fn legacy_store_test() -> io::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let main_path = dir.path().join("m3u.db");
+        let index_path = dir.path().join("m3u.idx");
+        // a store of 3.1.4 has the flags byte followed by the records, without version header
+        let mut content = vec![0u8];
+        let mut index = IndexedDocumentIndex::<u32>::new();
+        for virtual_id in 1u32..=3 {
+            let record = bincode_serialize(&LegacyM3uPlaylistItem {
+                virtual_id,
+                provider_id: virtual_id.to_string(),
+                name: format!("Channel {virtual_id}"),
+                chno: String::new(),
+                logo: String::new(),
+                logo_small: String::new(),
+                group: "News".to_string(),
+                title: format!("Channel {virtual_id}"),
+                parent_code: String::new(),
+                audio_track: String::new(),
+                time_shift: String::new(),
+                rec: String::new(),
+                url: format!("http://localhost/{virtual_id}.ts"),
+                epg_channel_id: None,
+                input_name: "input".to_string(),
+                item_type: PlaylistItemType::Live,
+            })?;
+            index.insert(virtual_id, u32::try_from(content.len()).unwrap());
+            content.extend_from_slice(&u32::try_from(record.len()).unwrap().to_le_bytes());
+            content.extend_from_slice(&record);
+        }
+        std::fs::write(&main_path, content)?;
+        index.store(&index_path)?;
+
+        // the store is not read with the current layout, it needs a reprocess
+        assert!(IndexedDocumentIterator::<u32, M3uPlaylistItem>::new(&main_path, &index_path).is_err());
+        assert!(get_reprocess_required_stores().contains(&main_path.to_string_lossy().to_string()));
+
+        // records are not appended to it, the store is written again
+        {
+            let mut idw = IndexedDocumentWriter::new_append(main_path.clone(), index_path.clone())?;
+            idw.write_doc(1, &Record { id: 1, data: "Entry 1".to_string() })?;
+            idw.store()?;
+        }
+        let docs: Vec<Record> = IndexedDocumentIterator::<u32, Record>::new(&main_path, &index_path)?.map(|(doc, _)| doc).collect();
+        assert_eq!(docs, vec![Record { id: 1, data: "Entry 1".to_string() }]);
+        assert!(!get_reprocess_required_stores().contains(&main_path.to_string_lossy().to_string()));
+        Ok(())
+    }
+
     #[test]
     fn test_read_xt() -> io::Result<()> {
         let main_path = PathBuf::from(resolve_env_var("${env:HOME}/projects/m3u-test/settings/alexyand/data/all_channels/xtream/live.db"));
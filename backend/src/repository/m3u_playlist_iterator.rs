@@ -2,19 +2,55 @@ use shared::error::info_err;
 use shared::error::{TuliproxError, TuliproxErrorKind};
 use crate::model::{ProxyUserCredentials};
 use crate::model::{Config, ConfigTarget, ConfigTargetOptions};
-use crate::model::{M3uPlaylistItem};
+use crate::model::{GroupOverrides, M3uPlaylistItem, M3U_ATTRIB_CATCHUP, M3U_ATTRIB_CATCHUP_DAYS, M3U_ATTRIB_CATCHUP_SOURCE, M3U_CATCHUP_SOURCE_TIMESHIFT};
 use shared::model::{PlaylistItemType, ProxyType, TargetType, XtreamCluster};
+use crate::repository::group_override_repository::load_group_overrides;
 use crate::repository::indexed_document::IndexedDocumentIterator;
 use crate::repository::m3u_repository::m3u_get_file_paths;
 use crate::repository::storage::ensure_target_storage_path;
 use crate::repository::storage_const;
 use crate::repository::user_repository::user_get_bouquet_filter;
 use crate::utils::FileReadGuard;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+enum M3uPlaylistReader {
+    Store(IndexedDocumentIterator<u32, M3uPlaylistItem>),
+    // the items are ordered by the priority of their group overrides
+    Ordered(std::vec::IntoIter<M3uPlaylistItem>),
+}
+
+impl M3uPlaylistReader {
+    fn new(reader: IndexedDocumentIterator<u32, M3uPlaylistItem>, overrides: Option<&GroupOverrides>) -> Self {
+        match overrides.filter(|o| o.has_priority()) {
+            Some(overrides) => {
+                let mut items: Vec<M3uPlaylistItem> = reader.map(|(item, _)| item).collect();
+                sort_by_group_priority(&mut items, overrides);
+                Self::Ordered(items.into_iter())
+            }
+            None => Self::Store(reader),
+        }
+    }
+}
+
+impl Iterator for M3uPlaylistReader {
+    type Item = (M3uPlaylistItem, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            Self::Store(reader) => reader.next(),
+            Self::Ordered(items) => items.next().map(|item| (item, items.len() > 0)),
+        }
+    }
+}
+
+/// Orders the items by the priority of their group, groups with equal priority keep their order.
+fn sort_by_group_priority(items: &mut [M3uPlaylistItem], overrides: &GroupOverrides) {
+    items.sort_by_key(|item| overrides.get_priority(get_cluster(item.item_type), &item.group));
+}
 
 #[allow(clippy::struct_excessive_bools)]
 pub struct M3uPlaylistIterator {
-    reader: IndexedDocumentIterator<u32, M3uPlaylistItem>,
+    reader: M3uPlaylistReader,
     base_url: String,
     username: String,
     password: String,
@@ -22,8 +58,14 @@ pub struct M3uPlaylistIterator {
     mask_redirect_url: bool,
     include_type_in_url: bool,
     rewrite_resource: bool,
+    // catchup attributes of proxied channels point to the timeshift api of the xtream output
+    catchup: bool,
+    has_xtream_output: bool,
     proxy_type: ProxyType,
     filter: Option<HashSet<String>>,
+    overrides: Option<GroupOverrides>,
+    hide_adult: bool,
+    dictionary: Option<HashMap<String, String>>,
     lookup_item: Option<(M3uPlaylistItem, bool)>,
     _file_lock: FileReadGuard,
 }
@@ -45,6 +87,9 @@ impl M3uPlaylistIterator {
                 .map_err(|err| info_err!(format!("Could not deserialize file {m3u_path:?} - {err}")))?;
 
         let filter = user_get_bouquet_filter(cfg, &user.username, None, TargetType::M3u, XtreamCluster::Live).await;
+        let overrides = load_group_overrides(cfg, &target.name).await;
+        let reader = M3uPlaylistReader::new(reader, overrides.as_ref());
+        let hide_adult = cfg.t_api_proxy.load().as_ref().is_some_and(|api_proxy| api_proxy.is_adult_hidden(user));
 
         let server_info = cfg.get_user_server_info(user);
         Ok(Self {
@@ -55,7 +100,12 @@ impl M3uPlaylistIterator {
             target_options: target.options.clone(),
             include_type_in_url: m3u_output.include_type_in_url,
             mask_redirect_url: m3u_output.mask_redirect_url,
+            catchup: m3u_output.catchup,
+            has_xtream_output: target.has_output(&TargetType::Xtream),
             filter,
+            overrides,
+            hide_adult,
+            dictionary: target.get_locale_dictionary(user.locale.as_deref()).cloned(),
             proxy_type: user.proxy.clone(),
             _file_lock: file_lock, // Save lock inside struct
             rewrite_resource: cfg.is_reverse_proxy_resource_rewrite_enabled(),
@@ -95,17 +145,38 @@ impl M3uPlaylistIterator {
         self.get_rewritten_url(m3u_pli, false, storage_const::M3U_RESOURCE_PATH)
     }
 
+    fn rewrite_catchup_source(&self, m3u_pli: &mut M3uPlaylistItem) {
+        let Some(attributes) = m3u_pli.attributes.as_mut() else { return };
+        if !attributes.contains_key(M3U_ATTRIB_CATCHUP_SOURCE) {
+            return;
+        }
+        if self.has_xtream_output {
+            attributes.insert(M3U_ATTRIB_CATCHUP_SOURCE.to_string(),
+                              format!("{}/timeshift/{}/{}/{M3U_CATCHUP_SOURCE_TIMESHIFT}/{}.ts", &self.base_url, &self.username, &self.password, m3u_pli.virtual_id));
+        } else {
+            for attribute in [M3U_ATTRIB_CATCHUP, M3U_ATTRIB_CATCHUP_DAYS, M3U_ATTRIB_CATCHUP_SOURCE] {
+                attributes.remove(attribute);
+            }
+        }
+    }
+
     fn get_next(&mut self) -> Option<(M3uPlaylistItem, bool)> {
-        let entry = if let Some(set) = &self.filter {
+        let entry = if self.filter.is_some() || self.overrides.is_some() || self.hide_adult {
+            let filter = self.filter.as_ref();
+            let overrides = self.overrides.as_ref();
+            let hide_adult = self.hide_adult;
+            let is_valid = |(pli, _): &(M3uPlaylistItem, bool)| filter.is_none_or(|set| set.contains(&pli.group.to_string()))
+                && overrides.is_none_or(|o| !o.is_hidden(get_cluster(pli.item_type), &pli.group))
+                && !(hide_adult && pli.is_adult());
             if let Some((current_item, _)) = self.lookup_item.take() {
-                let next_valid = self.reader.find(|(pli, _)| set.contains(&pli.group.to_string()));
+                let next_valid = self.reader.find(is_valid);
                 self.lookup_item = next_valid;
                 let has_next = self.lookup_item.is_some();
                 Some((current_item, has_next))
             } else {
-                let current_item = self.reader.find(|(item, _)| set.contains(&item.group.to_string()));
+                let current_item = self.reader.find(is_valid);
                 if let Some((item, _)) = current_item {
-                    self.lookup_item = self.reader.find(|(item, _)| set.contains(&item.group.to_string()));
+                    self.lookup_item = self.reader.find(is_valid);
                     let has_next = self.lookup_item.is_some();
                     Some((item, has_next))
                 } else {
@@ -131,11 +202,24 @@ impl M3uPlaylistIterator {
 
             m3u_pli.t_stream_url = stream_url.to_string();
             m3u_pli.t_resource_url = resource_url.map(|s| s.to_string());
+            if self.catchup && should_rewrite_urls {
+                self.rewrite_catchup_source(&mut m3u_pli);
+            }
+            if let Some(name) = self.overrides.as_ref().and_then(|o| o.get_name(get_cluster(m3u_pli.item_type), &m3u_pli.group)) {
+                m3u_pli.group = name.to_string();
+            }
+            if let Some(name) = self.dictionary.as_ref().and_then(|d| d.get(m3u_pli.group.as_str())) {
+                m3u_pli.group.clone_from(name);
+            }
             (m3u_pli, has_next)
         })
     }
 }
 
+fn get_cluster(item_type: PlaylistItemType) -> XtreamCluster {
+    XtreamCluster::try_from(item_type).unwrap_or(XtreamCluster::Live)
+}
+
 impl Iterator for M3uPlaylistIterator {
     type Item = (M3uPlaylistItem, bool);
 
@@ -179,3 +263,41 @@ impl Iterator for M3uPlaylistM3uTextIterator {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{GroupBulkOperation, PlaylistItem, PlaylistItemHeader};
+
+    fn create_item(group: &str, name: &str, item_type: PlaylistItemType) -> M3uPlaylistItem {
+        let header = PlaylistItemHeader { group: group.to_string(), name: name.to_string(), item_type, ..Default::default() };
+        PlaylistItem { header }.to_m3u()
+    }
+
+    #[test]
+    fn test_sort_by_group_priority() {
+        let mut overrides = GroupOverrides::default();
+        overrides.apply_operation(GroupBulkOperation::Priority { cluster: XtreamCluster::Live, groups: vec!["Sport".to_string()], priority: Some(-1) });
+        overrides.apply_operation(GroupBulkOperation::Priority { cluster: XtreamCluster::Live, groups: vec!["News".to_string()], priority: Some(1) });
+        overrides.apply_operation(GroupBulkOperation::Priority { cluster: XtreamCluster::Video, groups: vec!["Movies".to_string()], priority: Some(-2) });
+        assert!(overrides.has_priority());
+
+        let mut items = vec![
+            create_item("News", "News 1", PlaylistItemType::Live),
+            create_item("News", "News 2", PlaylistItemType::Live),
+            create_item("Music", "Music 1", PlaylistItemType::Live),
+            create_item("Sport", "Sport 1", PlaylistItemType::Live),
+            create_item("Sport", "Sport 2", PlaylistItemType::Live),
+            create_item("Movies", "Movie 1", PlaylistItemType::Video),
+            create_item("Sport", "Sport Movie", PlaylistItemType::Video),
+        ];
+        sort_by_group_priority(&mut items, &overrides);
+        let names: Vec<&str> = items.iter().map(|item| item.name.as_str()).collect();
+        assert_eq!(names, ["Movie 1", "Sport 1", "Sport 2", "Music 1", "Sport Movie", "News 1", "News 2"]);
+
+        let mut reader = M3uPlaylistReader::Ordered(items.into_iter());
+        assert!(reader.next().is_some_and(|(item, has_next)| item.name == "Movie 1" && has_next));
+        assert!(reader.nth(5).is_some_and(|(item, has_next)| item.name == "News 2" && !has_next));
+        assert!(reader.next().is_none());
+    }
+}
@@ -2,7 +2,7 @@ use shared::error::{create_tuliprox_error,info_err};
 use shared::error::{str_to_io_error, TuliproxError, TuliproxErrorKind};
 use crate::model::ProxyUserCredentials;
 use crate::model::{Config, ConfigTarget, M3uTargetOutput};
-use crate::model::{M3uPlaylistItem, PlaylistGroup, PlaylistItem};
+use crate::model::{M3uPlaylistItem, PlaylistGroup};
 use crate::repository::indexed_document::{IndexedDocumentDirectAccess, IndexedDocumentIterator, IndexedDocumentWriter};
 use crate::repository::m3u_playlist_iterator::{M3uPlaylistM3uTextIterator};
 use crate::repository::storage::{get_target_storage_path};
@@ -58,12 +58,18 @@ pub async fn m3u_write_playlist( cfg: &Config, target: &ConfigTarget, target_out
         let m3u_playlist = new_playlist.iter()
             .flat_map(|pg| &pg.channels)
             .filter(|&pli| pli.header.item_type != PlaylistItemType::SeriesInfo)
-            .map(PlaylistItem::to_m3u).collect::<Vec<M3uPlaylistItem>>();
+            .map(|pli| {
+                let mut m3u = pli.to_m3u();
+                if target_output.catchup {
+                    pli.add_catchup_attributes(&mut m3u);
+                }
+                m3u
+            }).collect::<Vec<M3uPlaylistItem>>();
 
         persist_m3u_playlist_as_text(cfg, target, target_output, &m3u_playlist);
         {
             let _file_lock = cfg.file_locks.write_lock(&m3u_path);
-            match IndexedDocumentWriter::new(m3u_path.clone(), idx_path) {
+            match IndexedDocumentWriter::new_with_compression(m3u_path.clone(), idx_path, target.is_store_compressed()) {
                 Ok(mut writer) => {
                     for m3u in m3u_playlist {
                         match writer.write_doc(m3u.virtual_id, &m3u) {
@@ -100,6 +106,18 @@ pub async fn m3u_get_item_for_stream_id(stream_id: u32, cfg: &Config, target: &C
     }
 }
 
+/// Returns `true` if the m3u store of the target was written by an older release with another record layout.
+/// It can't be read until the target is processed again.
+pub async fn m3u_store_needs_reprocess(cfg: &Config, target: &ConfigTarget) -> bool {
+    let Some(target_path) = get_target_storage_path(cfg, target.name.as_str()) else { return false };
+    let (m3u_path, idx_path) = m3u_get_file_paths(&target_path);
+    if !m3u_path.exists() || !idx_path.exists() {
+        return false;
+    }
+    let _file_lock = cfg.file_locks.read_lock(&m3u_path).await;
+    IndexedDocumentIterator::<u32, M3uPlaylistItem>::new(&m3u_path, &idx_path).is_err()
+}
+
 pub async fn iter_raw_m3u_playlist(config: &Arc<Config>, target: &ConfigTarget) -> Option<(utils::FileReadGuard, impl Iterator<Item=(M3uPlaylistItem, bool)>)> {
     let target_path = get_target_storage_path(config, target.name.as_str())?;
     let (m3u_path, idx_path) = m3u_get_file_paths(&target_path);
@@ -2,6 +2,7 @@ pub mod storage;
 pub mod target_id_mapping;
 pub mod bplustree;
 mod indexed_document;
+pub mod store_version;
 pub use indexed_document::IndexedDocumentReader;
 pub mod playlist_repository;
 pub mod m3u_repository;
@@ -11,5 +12,10 @@ pub mod strm_repository;
 pub mod m3u_playlist_iterator;
 pub mod xtream_playlist_iterator;
 pub mod user_repository;
+pub mod group_override_repository;
+pub mod refresh_history_repository;
+pub mod epg_match_report_repository;
+pub mod failover_repository;
+pub mod provider_cache_repository;
 pub mod storage_const;
 
@@ -3,8 +3,9 @@ use shared::error::{TuliproxError, TuliproxErrorKind};
 use crate::model::{Config, ConfigTarget, TargetOutput};
 use crate::model::{PlaylistGroup};
 use shared::model::{PlaylistItemType};
-use crate::model::Epg;
+use crate::model::MergedEpg;
 use crate::repository::epg_repository::epg_write;
+use crate::repository::failover_repository::persist_channel_failover;
 use crate::repository::strm_repository::write_strm_playlist;
 use crate::repository::m3u_repository::m3u_write_playlist;
 use crate::repository::storage::{ensure_target_storage_path, get_target_id_mapping_file};
@@ -14,7 +15,7 @@ use crate::utils::request::{is_dash_url, is_hls_url};
 use std::path::Path;
 use crate::utils;
 
-pub async fn persist_playlist(playlist: &mut [PlaylistGroup], epg: Option<&Epg>,
+pub async fn persist_playlist(playlist: &mut [PlaylistGroup], epg: Option<&MergedEpg<'_>>,
                               target: &ConfigTarget, cfg: &Config) -> Result<(), Vec<TuliproxError>> {
     let mut errors = vec![];
     let target_path = match ensure_target_storage_path(cfg, &target.name) {
@@ -48,6 +49,10 @@ pub async fn persist_playlist(playlist: &mut [PlaylistGroup], epg: Option<&Epg>,
         }
     }
 
+    if let Err(err) = persist_channel_failover(cfg, &target_path, playlist).await {
+        errors.push(info_err!(format!("Failed to store failover for target {}: {err}", target.name)));
+    }
+
     for output in &target.output {
         let result = match output {
             TargetOutput::Xtream(_xtream_output) => xtream_write_playlist(target, cfg, playlist).await,
@@ -0,0 +1,74 @@
+use crate::model::{Config, ConfigInput, ProviderCacheConfig};
+use crate::repository::storage::get_input_storage_path;
+use crate::repository::storage_const;
+use crate::utils::short_hash;
+use log::{debug, warn};
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+// the responses are stored per input, the file name is the hash of the request url
+fn get_provider_cache_file_path(cfg: &Config, input: &ConfigInput, url: &str) -> Option<PathBuf> {
+    get_input_storage_path(&input.name, &cfg.working_dir).ok()
+        .map(|path| path.join(storage_const::PATH_PROVIDER_CACHE).join(short_hash(url)))
+}
+
+fn get_max_age(cfg: &Config) -> Option<Duration> {
+    cfg.provider_cache.as_ref().and_then(ProviderCacheConfig::get_max_age)
+}
+
+/// Returns the cached provider response if it is not older than the configured `max_age_secs`.
+/// Expired responses are replaced with the next stored response.
+pub async fn load_provider_response(cfg: &Config, input: &ConfigInput, url: &str) -> Option<String> {
+    let max_age = get_max_age(cfg)?;
+    let path = get_provider_cache_file_path(cfg, input, url)?;
+    let modified = tokio::fs::metadata(&path).await.ok()?.modified().ok()?;
+    if SystemTime::now().duration_since(modified).unwrap_or_default() > max_age {
+        return None;
+    }
+    let _file_lock = cfg.file_locks.read_lock(&path).await;
+    tokio::fs::read_to_string(&path).await.ok()
+}
+
+pub async fn store_provider_response(cfg: &Config, input: &ConfigInput, url: &str, content: &str) {
+    if get_max_age(cfg).is_none() || content.is_empty() {
+        return;
+    }
+    let Some(path) = get_provider_cache_file_path(cfg, input, url) else { return };
+    if let Some(dir) = path.parent() {
+        if let Err(err) = tokio::fs::create_dir_all(dir).await {
+            warn!("Failed to create provider cache directory {}: {err}", dir.display());
+            return;
+        }
+    }
+    let _file_lock = cfg.file_locks.write_lock(&path).await;
+    match tokio::fs::write(&path, content).await {
+        Ok(()) => debug!("Stored provider response {}", path.display()),
+        Err(err) => warn!("Failed to store provider response {}: {err}", path.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_provider_cache() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut cfg = Config { working_dir: dir.path().to_string_lossy().to_string(), ..Default::default() };
+        let input = ConfigInput { name: "provider".to_string(), ..Default::default() };
+        let url = "http://provider/player_api.php?action=get_vod_categories";
+
+        store_provider_response(&cfg, &input, url, "[]").await;
+        assert!(load_provider_response(&cfg, &input, url).await.is_none(), "cache is disabled");
+
+        cfg.provider_cache = Some(ProviderCacheConfig { enabled: true, max_age_secs: 60 });
+        store_provider_response(&cfg, &input, url, "[]").await;
+        assert_eq!(load_provider_response(&cfg, &input, url).await.as_deref(), Some("[]"));
+        assert!(load_provider_response(&cfg, &input, "http://provider/other").await.is_none());
+
+        let path = get_provider_cache_file_path(&cfg, &input, url).unwrap();
+        let expired = SystemTime::now() - Duration::from_secs(90);
+        std::fs::File::options().write(true).open(&path).unwrap().set_modified(expired).unwrap();
+        assert!(load_provider_response(&cfg, &input, url).await.is_none(), "response is expired");
+    }
+}
@@ -0,0 +1,75 @@
+use crate::model::{Config, RefreshHistory, RefreshResult, TargetFreshness};
+use crate::repository::storage::{ensure_target_storage_path, get_target_storage_path};
+use crate::repository::storage_const;
+use crate::utils::json_write_documents_to_file;
+use shared::error::to_io_error;
+use std::io::Error;
+use std::path::{Path, PathBuf};
+
+fn get_refresh_history_file_path(target_path: &Path) -> PathBuf {
+    target_path.join(storage_const::FILE_REFRESH_HISTORY)
+}
+
+async fn read_refresh_history(path: &Path) -> RefreshHistory {
+    tokio::fs::read_to_string(path).await.ok()
+        .and_then(|content| serde_json::from_str::<RefreshHistory>(&content).ok())
+        .unwrap_or_default()
+}
+
+pub async fn load_refresh_history(cfg: &Config, target_name: &str) -> RefreshHistory {
+    let Some(target_path) = get_target_storage_path(cfg, target_name) else {
+        return RefreshHistory::default();
+    };
+    let path = get_refresh_history_file_path(&target_path);
+    if !path.exists() {
+        return RefreshHistory::default();
+    }
+    let _file_lock = cfg.file_locks.read_lock(&path).await;
+    read_refresh_history(&path).await
+}
+
+/// Appends the result and removes the oldest entries above the configured history size.
+pub async fn append_refresh_result(cfg: &Config, target_name: &str, result: RefreshResult) -> Result<(), Error> {
+    let max_entries = cfg.get_refresh_history_size();
+    if max_entries == 0 {
+        return Ok(());
+    }
+    let target_path = ensure_target_storage_path(cfg, target_name).map_err(to_io_error)?;
+    let path = get_refresh_history_file_path(&target_path);
+    let _file_lock = cfg.file_locks.write_lock(&path).await;
+    let mut history = if path.exists() { read_refresh_history(&path).await } else { RefreshHistory::default() };
+    history.push(result, max_entries);
+    json_write_documents_to_file(&path, &history)
+}
+
+fn get_target_freshness_file_path(target_path: &Path) -> PathBuf {
+    target_path.join(storage_const::FILE_TARGET_FRESHNESS)
+}
+
+async fn read_target_freshness(path: &Path) -> TargetFreshness {
+    tokio::fs::read_to_string(path).await.ok()
+        .and_then(|content| serde_json::from_str::<TargetFreshness>(&content).ok())
+        .unwrap_or_default()
+}
+
+pub async fn load_target_freshness(cfg: &Config, target_name: &str) -> TargetFreshness {
+    let Some(path) = get_target_storage_path(cfg, target_name).map(|target_path| get_target_freshness_file_path(&target_path)) else {
+        return TargetFreshness::default();
+    };
+    if !path.exists() {
+        return TargetFreshness::default();
+    }
+    let _file_lock = cfg.file_locks.read_lock(&path).await;
+    read_target_freshness(&path).await
+}
+
+/// Applies the `update` to the stored freshness of the target and returns its result.
+pub async fn update_target_freshness<R>(cfg: &Config, target_name: &str, update: impl FnOnce(&mut TargetFreshness) -> R) -> Result<R, Error> {
+    let target_path = ensure_target_storage_path(cfg, target_name).map_err(to_io_error)?;
+    let path = get_target_freshness_file_path(&target_path);
+    let _file_lock = cfg.file_locks.write_lock(&path).await;
+    let mut freshness = if path.exists() { read_target_freshness(&path).await } else { TargetFreshness::default() };
+    let result = update(&mut freshness);
+    json_write_documents_to_file(&path, &freshness)?;
+    Ok(result)
+}
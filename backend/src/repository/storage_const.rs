@@ -4,10 +4,18 @@ pub(in crate::repository) const FILE_SUFFIX_INDEX: &str = "idx";
 pub(in crate::repository) const FILE_ID_MAPPING: &str = "id_mapping.db";
 pub(in crate::repository) const FILE_STRM: &str = "strm";
 pub(in crate::repository) const FILE_M3U: &str = "m3u";
+pub(in crate::repository) const FILE_GROUP_OVERRIDES: &str = "group_overrides.json";
+pub(in crate::repository) const FILE_REFRESH_HISTORY: &str = "refresh_history.json";
+pub(in crate::repository) const FILE_TARGET_FRESHNESS: &str = "freshness.json";
+pub(in crate::repository) const FILE_EPG_MATCH_REPORT: &str = "epg_match_report.json";
+pub(in crate::repository) const FILE_EPG_ICONS: &str = "epg_icons.json";
+pub(in crate::repository) const FILE_CHANNEL_FAILOVER: &str = "failover.db";
+pub(in crate::repository) const PATH_PROVIDER_CACHE: &str = "provider_cache";
 
 pub const FILE_SUFFIX_WAL: &str = "wal";
 pub const M3U_STREAM_PATH: &str = "m3u-stream";
 pub const M3U_RESOURCE_PATH: &str = "resource/m3u";
+pub const EPG_RESOURCE_PATH: &str = "resource/epg";
 
 pub const COL_CAT_LIVE: &str = "cat_live";
 pub const COL_CAT_SERIES: &str = "cat_series";
@@ -17,6 +25,7 @@ pub(in crate::repository) const USER_LIVE_BOUQUET: &str = "live_bouquet.json";
 pub(in crate::repository) const USER_VOD_BOUQUET: &str = "vod_bouquet.json";
 pub(in crate::repository) const USER_SERIES_BOUQUET: &str = "series_bouquet.json";
 pub(in crate::repository) const API_USER_DB_FILE: &str = "api_user.db";
+pub(in crate::repository) const FILE_USER_ACTIVITY: &str = "user_activity.json";
 
 
 pub(in crate::repository) const FILE_SERIES_INFO: &str = "series_info";
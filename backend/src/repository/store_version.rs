@@ -0,0 +1,128 @@
+use log::warn;
+use std::collections::BTreeSet;
+use std::io;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+
+/// Version of the bincode layout of the persisted stores.
+/// Increase it when a persisted struct changes and register a `StoreMigration` from the previous version.
+pub(in crate::repository) const STORE_VERSION: u32 = 1;
+// stores of older releases have no header, they are version 0.
+// Their records have the layout of 3.1.4, `M3uPlaylistItem` has no `attributes`, they are written again.
+pub(in crate::repository) const LEGACY_STORE_VERSION: u32 = 0;
+const STORE_MAGIC: [u8; 4] = *b"TPST";
+pub(in crate::repository) const STORE_HEADER_SIZE: usize = 8;
+
+pub(in crate::repository) type RecordMigration = fn(Vec<u8>) -> io::Result<Vec<u8>>;
+
+/// Migrates the records of a store from `from_version` to the next version.
+struct StoreMigration {
+    from_version: u32,
+    // `None` if the records of both versions have the same layout
+    migrate_record: Option<RecordMigration>,
+}
+
+static STORE_MIGRATIONS: &[StoreMigration] = &[];
+
+// stores which can't be read after an upgrade until their targets or inputs are processed again
+static REPROCESS_REQUIRED: LazyLock<Mutex<BTreeSet<String>>> = LazyLock::new(|| Mutex::new(BTreeSet::new()));
+
+pub(in crate::repository) fn encode_store_header() -> [u8; STORE_HEADER_SIZE] {
+    let mut header = [0u8; STORE_HEADER_SIZE];
+    header[..STORE_MAGIC.len()].copy_from_slice(&STORE_MAGIC);
+    header[STORE_MAGIC.len()..].copy_from_slice(&STORE_VERSION.to_le_bytes());
+    header
+}
+
+/// Returns the version of the header, `None` for stores without header.
+pub(in crate::repository) fn decode_store_header(header: &[u8]) -> Option<u32> {
+    if header.len() < STORE_HEADER_SIZE || header[..STORE_MAGIC.len()] != STORE_MAGIC {
+        return None;
+    }
+    header[STORE_MAGIC.len()..STORE_HEADER_SIZE].try_into().ok().map(u32::from_le_bytes)
+}
+
+/// Returns the record migrations from `version` to the current version,
+/// or `None` if the store has to be written again.
+fn find_record_migrations(version: u32) -> Option<Vec<Option<RecordMigration>>> {
+    let mut migrations = Vec::new();
+    let mut current = version;
+    while current < STORE_VERSION {
+        let migration = STORE_MIGRATIONS.iter().find(|migration| migration.from_version == current)?;
+        migrations.push(migration.migrate_record);
+        current += 1;
+    }
+    (current == STORE_VERSION).then_some(migrations)
+}
+
+/// Returns the record migrations of the store `path` with `version`,
+/// an error with a clear message if the store is not readable by this release.
+pub(in crate::repository) fn get_record_migrations(path: &Path, version: u32) -> io::Result<Vec<RecordMigration>> {
+    match find_record_migrations(version) {
+        Some(migrations) => Ok(migrations.into_iter().flatten().collect()),
+        None => Err(reprocess_required(path, version)),
+    }
+}
+
+/// Returns `Ok` if the records of the store `path` with `version` have the current layout.
+/// Stores like the index trees can't migrate their records, they need a compatible layout.
+pub(in crate::repository) fn check_store_layout(path: &Path, version: u32) -> io::Result<()> {
+    match find_record_migrations(version) {
+        Some(migrations) if migrations.iter().all(Option::is_none) => Ok(()),
+        _ => Err(reprocess_required(path, version)),
+    }
+}
+
+pub(in crate::repository) fn migrate_record(mut content: Vec<u8>, migrations: &[RecordMigration]) -> io::Result<Vec<u8>> {
+    for migrate in migrations {
+        content = migrate(content)?;
+    }
+    Ok(content)
+}
+
+fn reprocess_required(path: &Path, version: u32) -> io::Error {
+    let path = path.to_string_lossy().to_string();
+    if let Ok(mut stores) = REPROCESS_REQUIRED.lock() {
+        if stores.insert(path.clone()) {
+            warn!("Store {path} has version {version}, this release reads version {STORE_VERSION}, reprocess required");
+        }
+    }
+    io::Error::new(io::ErrorKind::InvalidData, format!("Store {path} has version {version}, reprocess required"))
+}
+
+/// A store written again is readable.
+pub(in crate::repository) fn clear_reprocess_required(path: &Path) {
+    if let Ok(mut stores) = REPROCESS_REQUIRED.lock() {
+        stores.remove(path.to_string_lossy().as_ref());
+    }
+}
+
+/// The stores which need a playlist update after an upgrade.
+pub fn get_reprocess_required_stores() -> Vec<String> {
+    REPROCESS_REQUIRED.lock().map(|stores| stores.iter().cloned().collect()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_store_layout, decode_store_header, encode_store_header, get_record_migrations, get_reprocess_required_stores, LEGACY_STORE_VERSION, STORE_VERSION};
+    use std::path::Path;
+
+    #[test]
+    fn test_store_version() {
+        assert_eq!(decode_store_header(&encode_store_header()), Some(STORE_VERSION));
+        assert_eq!(decode_store_header(&[0u8; 9]), None);
+
+        // stores of the current version are readable
+        assert!(get_record_migrations(Path::new("/tmp/current.db"), STORE_VERSION).unwrap().is_empty());
+        assert!(check_store_layout(Path::new("/tmp/current.idx"), STORE_VERSION).is_ok());
+
+        // stores of older releases have another record layout
+        assert!(get_record_migrations(Path::new("/tmp/legacy.db"), LEGACY_STORE_VERSION).is_err());
+        assert!(get_reprocess_required_stores().contains(&"/tmp/legacy.db".to_string()));
+
+        // stores of newer releases need a reprocess
+        let future = Path::new("/tmp/future.db");
+        assert!(get_record_migrations(future, STORE_VERSION + 1).is_err());
+        assert!(get_reprocess_required_stores().contains(&"/tmp/future.db".to_string()));
+    }
+}
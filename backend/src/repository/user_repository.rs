@@ -1,5 +1,5 @@
-use crate::model::{ProxyUserCredentials, TargetUser};
-use crate::model::{Config};
+use crate::model::{ProxyUserCredentials, ProxyUserDevice, TargetUser};
+use crate::model::{Config, UserActivityMap};
 use shared::model::{ProxyType, ProxyUserStatus, TargetType, XtreamCluster};
 use crate::model::{PlaylistBouquetDto, TargetBouquetDto};
 use crate::model::PlaylistXtreamCategory;
@@ -45,6 +45,152 @@ impl StoredProxyUserCredentialsDeprecated {
             status: stored.status,
             ui_enabled: stored.ui_enabled,
             comment: None,
+            locale: None,
+            timezone: None,
+            email: None,
+            devices: None,
+            package: None,
+            t_device: None,
+            t_ipv6_client: false,
+        }
+    }
+}
+
+// Stored layout before the user locale, timezone and email were added.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct StoredProxyUserCredentialsV1 {
+    pub target: String,
+    pub username: String,
+    pub password: String,
+    pub token: Option<String>,
+    pub proxy: ProxyType,
+    pub server: Option<String>,
+    pub epg_timeshift: Option<String>,
+    pub created_at: Option<i64>,
+    pub exp_date: Option<i64>,
+    pub max_connections: Option<u32>,
+    pub status: Option<ProxyUserStatus>,
+    pub ui_enabled: bool,
+    pub comment: Option<String>,
+}
+
+impl StoredProxyUserCredentialsV1 {
+    fn to(stored: &StoredProxyUserCredentialsV1) -> ProxyUserCredentials {
+        ProxyUserCredentials {
+            username: stored.username.clone(),
+            password: stored.password.clone(),
+            token: stored.token.clone(),
+            proxy: stored.proxy.clone(),
+            server: stored.server.clone(),
+            epg_timeshift: stored.epg_timeshift.clone(),
+            created_at: stored.created_at,
+            exp_date: stored.exp_date,
+            max_connections: stored.max_connections.unwrap_or_default(),
+            status: stored.status,
+            ui_enabled: stored.ui_enabled,
+            comment: stored.comment.clone(),
+            locale: None,
+            timezone: None,
+            email: None,
+            devices: None,
+            package: None,
+            t_device: None,
+            t_ipv6_client: false,
+        }
+    }
+}
+
+// Stored layout before the user devices were added.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct StoredProxyUserCredentialsV2 {
+    pub target: String,
+    pub username: String,
+    pub password: String,
+    pub token: Option<String>,
+    pub proxy: ProxyType,
+    pub server: Option<String>,
+    pub epg_timeshift: Option<String>,
+    pub created_at: Option<i64>,
+    pub exp_date: Option<i64>,
+    pub max_connections: Option<u32>,
+    pub status: Option<ProxyUserStatus>,
+    pub ui_enabled: bool,
+    pub comment: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub email: Option<String>,
+}
+
+impl StoredProxyUserCredentialsV2 {
+    fn to(stored: &StoredProxyUserCredentialsV2) -> ProxyUserCredentials {
+        ProxyUserCredentials {
+            username: stored.username.clone(),
+            password: stored.password.clone(),
+            token: stored.token.clone(),
+            proxy: stored.proxy.clone(),
+            server: stored.server.clone(),
+            epg_timeshift: stored.epg_timeshift.clone(),
+            created_at: stored.created_at,
+            exp_date: stored.exp_date,
+            max_connections: stored.max_connections.unwrap_or_default(),
+            status: stored.status,
+            ui_enabled: stored.ui_enabled,
+            comment: stored.comment.clone(),
+            locale: stored.locale.clone(),
+            timezone: stored.timezone.clone(),
+            email: stored.email.clone(),
+            devices: None,
+            package: None,
+            t_device: None,
+            t_ipv6_client: false,
+        }
+    }
+}
+
+// Stored layout before the user packages were added.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct StoredProxyUserCredentialsV3 {
+    pub target: String,
+    pub username: String,
+    pub password: String,
+    pub token: Option<String>,
+    pub proxy: ProxyType,
+    pub server: Option<String>,
+    pub epg_timeshift: Option<String>,
+    pub created_at: Option<i64>,
+    pub exp_date: Option<i64>,
+    pub max_connections: Option<u32>,
+    pub status: Option<ProxyUserStatus>,
+    pub ui_enabled: bool,
+    pub comment: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub email: Option<String>,
+    pub devices: Option<Vec<ProxyUserDevice>>,
+}
+
+impl StoredProxyUserCredentialsV3 {
+    fn to(stored: &StoredProxyUserCredentialsV3) -> ProxyUserCredentials {
+        ProxyUserCredentials {
+            username: stored.username.clone(),
+            password: stored.password.clone(),
+            token: stored.token.clone(),
+            proxy: stored.proxy.clone(),
+            server: stored.server.clone(),
+            epg_timeshift: stored.epg_timeshift.clone(),
+            created_at: stored.created_at,
+            exp_date: stored.exp_date,
+            max_connections: stored.max_connections.unwrap_or_default(),
+            status: stored.status,
+            ui_enabled: stored.ui_enabled,
+            comment: stored.comment.clone(),
+            locale: stored.locale.clone(),
+            timezone: stored.timezone.clone(),
+            email: stored.email.clone(),
+            devices: stored.devices.clone(),
+            package: None,
+            t_device: None,
+            t_ipv6_client: false,
         }
     }
 }
@@ -68,6 +214,11 @@ struct StoredProxyUserCredentials {
     pub status: Option<ProxyUserStatus>,
     pub ui_enabled: bool,
     pub comment: Option<String>,
+    pub locale: Option<String>,
+    pub timezone: Option<String>,
+    pub email: Option<String>,
+    pub devices: Option<Vec<ProxyUserDevice>>,
+    pub package: Option<String>,
 }
 
 impl StoredProxyUserCredentials {
@@ -86,6 +237,11 @@ impl StoredProxyUserCredentials {
             status: proxy.status,
             ui_enabled: proxy.ui_enabled,
             comment: proxy.comment.clone(),
+            locale: proxy.locale.clone(),
+            timezone: proxy.timezone.clone(),
+            email: proxy.email.clone(),
+            devices: proxy.devices.clone(),
+            package: proxy.package.clone(),
         }
     }
 
@@ -103,6 +259,13 @@ impl StoredProxyUserCredentials {
             status: stored.status,
             ui_enabled: stored.ui_enabled,
             comment: stored.comment.clone(),
+            locale: stored.locale.clone(),
+            timezone: stored.timezone.clone(),
+            email: stored.email.clone(),
+            devices: stored.devices.clone(),
+            package: stored.package.clone(),
+            t_device: None,
+            t_ipv6_client: false,
         }
     }
 }
@@ -155,62 +318,102 @@ pub fn store_api_user(cfg: &Config, target_users: &[TargetUser]) -> Result<u64,
     user_tree.store(&path)
 }
 
-// TODO remove me if we get stable on user_db
-pub fn load_api_user_deprecated(cfg: &Config) -> Result<Vec<TargetUser>, Error> {
-    let path = get_api_user_db_path(cfg);
-    let lock = cfg.file_locks.read_lock(&path);
-    let user_tree = BPlusTree::<String, StoredProxyUserCredentialsDeprecated>::load(&path)?;
-    drop(lock);
+fn to_target_users<'a, T: 'a>(stored_users: impl Iterator<Item=(&'a String, &'a T)>, convert: fn(&T) -> (String, ProxyUserCredentials)) -> Vec<TargetUser> {
     let mut target_users: HashMap<String, TargetUser> = HashMap::new();
-    for (_uname, stored_user) in &user_tree {
-        let proxy_user: ProxyUserCredentials = StoredProxyUserCredentialsDeprecated::to(stored_user);
-        let target_name = stored_user.target.clone();
+    for (_uname, stored_user) in stored_users {
+        let (target_name, proxy_user) = convert(stored_user);
         match target_users.entry(target_name) {
             std::collections::hash_map::Entry::Occupied(mut entry) => {
                 let target = entry.get_mut();
                 target.credentials.push(proxy_user);
             }
             std::collections::hash_map::Entry::Vacant(entry) => {
+                let target = entry.key().clone();
                 entry.insert(TargetUser {
-                    target: stored_user.target.clone(),
+                    target,
                     credentials: vec![proxy_user],
                 });
             }
         }
     }
-    Ok(target_users.into_values().collect())
+    target_users.into_values().collect()
+}
+
+// TODO remove me if we get stable on user_db
+pub fn load_api_user_deprecated(cfg: &Config) -> Result<Vec<TargetUser>, Error> {
+    let path = get_api_user_db_path(cfg);
+    let lock = cfg.file_locks.read_lock(&path);
+    let user_tree = BPlusTree::<String, StoredProxyUserCredentialsDeprecated>::load(&path)?;
+    drop(lock);
+    Ok(to_target_users(user_tree.iter(), |stored| (stored.target.clone(), StoredProxyUserCredentialsDeprecated::to(stored))))
+}
+
+fn load_api_user_v1(cfg: &Config) -> Result<Vec<TargetUser>, Error> {
+    let path = get_api_user_db_path(cfg);
+    let lock = cfg.file_locks.read_lock(&path);
+    let Ok(user_tree) = BPlusTree::<String, StoredProxyUserCredentialsV1>::load(&path) else { return load_api_user_deprecated(cfg) };
+    drop(lock);
+    Ok(to_target_users(user_tree.iter(), |stored| (stored.target.clone(), StoredProxyUserCredentialsV1::to(stored))))
+}
+
+fn load_api_user_v2(cfg: &Config) -> Result<Vec<TargetUser>, Error> {
+    let path = get_api_user_db_path(cfg);
+    let lock = cfg.file_locks.read_lock(&path);
+    let Ok(user_tree) = BPlusTree::<String, StoredProxyUserCredentialsV2>::load(&path) else { return load_api_user_v1(cfg) };
+    drop(lock);
+    Ok(to_target_users(user_tree.iter(), |stored| (stored.target.clone(), StoredProxyUserCredentialsV2::to(stored))))
 }
 
+fn load_api_user_v3(cfg: &Config) -> Result<Vec<TargetUser>, Error> {
+    let path = get_api_user_db_path(cfg);
+    let lock = cfg.file_locks.read_lock(&path);
+    let Ok(user_tree) = BPlusTree::<String, StoredProxyUserCredentialsV3>::load(&path) else { return load_api_user_v2(cfg) };
+    drop(lock);
+    Ok(to_target_users(user_tree.iter(), |stored| (stored.target.clone(), StoredProxyUserCredentialsV3::to(stored))))
+}
 
 pub fn load_api_user(cfg: &Config) -> Result<Vec<TargetUser>, Error> {
     let path = get_api_user_db_path(cfg);
     let lock = cfg.file_locks.read_lock(&path);
-    let Ok(user_tree) = BPlusTree::<String, StoredProxyUserCredentials>::load(&path) else { return load_api_user_deprecated(cfg) };
+    let Ok(user_tree) = BPlusTree::<String, StoredProxyUserCredentials>::load(&path) else { return load_api_user_v3(cfg) };
     drop(lock);
-    let mut target_users: HashMap<String, TargetUser> = HashMap::new();
-    for (_uname, stored_user) in &user_tree {
-        let proxy_user: ProxyUserCredentials = StoredProxyUserCredentials::to(stored_user);
-        let target_name = stored_user.target.clone();
-        match target_users.entry(target_name) {
-            std::collections::hash_map::Entry::Occupied(mut entry) => {
-                let target = entry.get_mut();
-                target.credentials.push(proxy_user);
-            }
-            std::collections::hash_map::Entry::Vacant(entry) => {
-                entry.insert(TargetUser {
-                    target: stored_user.target.clone(),
-                    credentials: vec![proxy_user],
-                });
-            }
-        }
-    }
-    Ok(target_users.into_values().collect())
+    Ok(to_target_users(user_tree.iter(), |stored| (stored.target.clone(), StoredProxyUserCredentials::to(stored))))
 }
 
 pub fn get_user_storage_path(cfg: &Config, username: &str) -> Option<PathBuf> {
     cfg.user_config_dir.as_ref().and_then(|ucd| utils::get_file_path(ucd, Some(std::path::PathBuf::from(username))))
 }
 
+pub fn get_user_activity_file_path(cfg: &Config) -> Option<PathBuf> {
+    cfg.user_config_dir.as_ref().map(|ucd| PathBuf::from(ucd).join(storage_const::FILE_USER_ACTIVITY))
+}
+
+pub fn load_user_activity(path: &Path) -> UserActivityMap {
+    if !path.exists() {
+        return UserActivityMap::new();
+    }
+    match std::fs::read_to_string(path).map(|content| serde_json::from_str::<UserActivityMap>(&content)) {
+        Ok(Ok(activity)) => activity,
+        Ok(Err(err)) => {
+            error!("Failed to read user activity {}: {err}", path.display());
+            UserActivityMap::new()
+        }
+        Err(err) => {
+            error!("Failed to read user activity {}: {err}", path.display());
+            UserActivityMap::new()
+        }
+    }
+}
+
+pub fn save_user_activity(path: &Path, activity: &UserActivityMap) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    json_write_documents_to_file(path, activity)
+}
+
 fn ensure_user_storage_path(cfg: &Config, username: &str) -> Option<PathBuf> {
     if let Some(path) = get_user_storage_path(cfg, username) {
         if !path.exists() && std::fs::create_dir_all(&path).is_err() {
@@ -397,7 +600,7 @@ pub async fn user_get_bouquet_filter(config: &Config, username: &str, category_i
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::model::{ProxyType, ProxyUserStatus};
+    use shared::model::{ProxyType, ProxyUserStatus};
     use std::env::temp_dir;
 
 
@@ -420,6 +623,13 @@ mod tests {
                         status: Some(ProxyUserStatus::Active),
                         ui_enabled: true,
                         comment: None,
+                        locale: None,
+                        timezone: None,
+                        email: None,
+                        devices: None,
+                        package: None,
+                        t_device: None,
+                        t_ipv6_client: false,
                     },
                     ProxyUserCredentials {
                         username: "Test2".to_string(),
@@ -434,6 +644,13 @@ mod tests {
                         status: Some(ProxyUserStatus::Expired),
                         ui_enabled: true,
                         comment: None,
+                        locale: None,
+                        timezone: None,
+                        email: None,
+                        devices: None,
+                        package: None,
+                        t_device: None,
+                        t_ipv6_client: false,
                     },
                     ProxyUserCredentials {
                         username: "Test3".to_string(),
@@ -448,6 +665,13 @@ mod tests {
                         status: Some(ProxyUserStatus::Expired),
                         ui_enabled: true,
                         comment: None,
+                        locale: None,
+                        timezone: None,
+                        email: None,
+                        devices: None,
+                        package: None,
+                        t_device: None,
+                        t_ipv6_client: false,
                     },
                     ProxyUserCredentials {
                         username: "Test4".to_string(),
@@ -462,6 +686,13 @@ mod tests {
                         status: Some(ProxyUserStatus::Expired),
                         ui_enabled: true,
                         comment: None,
+                        locale: None,
+                        timezone: None,
+                        email: None,
+                        devices: None,
+                        package: None,
+                        t_device: None,
+                        t_ipv6_client: false,
                     }
                 ],
             };
@@ -476,4 +707,36 @@ mod tests {
         assert_eq!(user_list.as_ref().unwrap().len(), 1);
         assert_eq!(user_list.as_ref().unwrap().first().unwrap().credentials.len(), 4);
     }
+
+    #[test]
+    pub fn load_target_user_v1() {
+        let mut cfg = Config::default();
+        let config_path = temp_dir().join("tuliprox_user_v1");
+        std::fs::create_dir_all(&config_path).unwrap();
+        cfg.t_config_path = config_path.to_string_lossy().to_string();
+        let mut user_tree = BPlusTree::<String, StoredProxyUserCredentialsV1>::new();
+        user_tree.insert("Test".to_string(), StoredProxyUserCredentialsV1 {
+            target: "test".to_string(),
+            username: "Test".to_string(),
+            password: "Test".to_string(),
+            token: None,
+            proxy: ProxyType::Redirect,
+            server: None,
+            epg_timeshift: None,
+            created_at: None,
+            exp_date: None,
+            max_connections: None,
+            status: Some(ProxyUserStatus::Active),
+            ui_enabled: true,
+            comment: Some("comment".to_string()),
+        });
+        user_tree.store(&get_api_user_db_path(&cfg)).unwrap();
+
+        let user_list = load_api_user(&cfg).unwrap();
+        assert_eq!(user_list.len(), 1);
+        let user = user_list.first().unwrap().credentials.first().unwrap();
+        assert_eq!(user.comment.as_deref(), Some("comment"));
+        assert_eq!(user.locale, None);
+        assert_eq!(user.timezone, None);
+    }
 }
\ No newline at end of file
@@ -8,14 +8,17 @@ use crate::model::{Config, ConfigTarget};
 use crate::model::{XtreamPlaylistItem};
 use crate::model::XtreamMappingOptions;
 use crate::repository::indexed_document::{IndexedDocumentIterator};
+use crate::repository::group_override_repository::load_group_overrides;
 use crate::repository::user_repository::user_get_bouquet_filter;
-use crate::repository::xtream_repository::{xtream_get_file_paths, xtream_get_storage_path};
+use crate::repository::xtream_repository::{xtream_get_file_paths, xtream_get_playlist_categories, xtream_get_storage_path};
 use crate::utils::FileReadGuard;
 
 pub struct XtreamPlaylistIterator {
     reader: IndexedDocumentIterator<u32, XtreamPlaylistItem>,
     options: XtreamMappingOptions,
     filter: Option<HashSet<String>>,
+    hidden: Option<HashSet<u32>>,
+    hide_adult: bool,
     base_url: String,
     user: ProxyUserCredentials,
     lookup_item: Option<(XtreamPlaylistItem, bool)>,  // this is for filtered iteration
@@ -45,11 +48,15 @@ impl XtreamPlaylistIterator {
             let server_info = config.get_user_server_info(user);
 
             let filter = user_get_bouquet_filter(config, &user.username, category_id, TargetType::Xtream, cluster).await;
+            let hidden = get_hidden_category_ids(config, target, cluster).await;
+            let hide_adult = config.t_api_proxy.load().as_ref().is_some_and(|api_proxy| api_proxy.is_adult_hidden(user));
 
             Ok(Self {
                 reader,
                 options,
                 filter,
+                hidden,
+                hide_adult,
                 _file_lock: file_lock,
                 base_url: server_info.get_base_url(),
                 user: user.clone(),
@@ -65,16 +72,22 @@ impl XtreamPlaylistIterator {
             error!("Could not deserialize xtream item: {}", self.reader.get_path().display());
             return None;
         }
-        if let Some(set) = &self.filter {
+        if self.filter.is_some() || self.hidden.is_some() || self.hide_adult {
+            let filter = self.filter.as_ref();
+            let hidden = self.hidden.as_ref();
+            let hide_adult = self.hide_adult;
+            let is_valid = |(pli, _): &(XtreamPlaylistItem, bool)| filter.is_none_or(|set| set.contains(&pli.category_id.to_string()))
+                && hidden.is_none_or(|set| !set.contains(&pli.category_id))
+                && !(hide_adult && pli.is_adult());
             if let Some((current_item, _)) = self.lookup_item.take() {
-                let next_valid = self.reader.find(|(pli, _)| set.contains(&pli.category_id.to_string()));
+                let next_valid = self.reader.find(is_valid);
                 self.lookup_item = next_valid;
                 let has_next = self.lookup_item.is_some();
                 Some((current_item, has_next))
             } else {
-                let current_item = self.reader.find(|(item, _)| set.contains(&item.category_id.to_string()));
+                let current_item = self.reader.find(is_valid);
                 if let Some((item, _)) = current_item {
-                    self.lookup_item = self.reader.find(|(item, _)| set.contains(&item.category_id.to_string()));
+                    self.lookup_item = self.reader.find(is_valid);
                     let has_next = self.lookup_item.is_some();
                     Some((item, has_next))
                 } else {
@@ -88,6 +101,19 @@ impl XtreamPlaylistIterator {
 
 }
 
+async fn get_hidden_category_ids(config: &Config, target: &ConfigTarget, cluster: XtreamCluster) -> Option<HashSet<u32>> {
+    let overrides = load_group_overrides(config, &target.name).await?;
+    let hidden_groups = overrides.get_hidden_groups(cluster);
+    if hidden_groups.is_empty() {
+        return None;
+    }
+    let categories = xtream_get_playlist_categories(config, &target.name, cluster).await?;
+    Some(categories.iter()
+        .filter(|category| hidden_groups.contains(&category.name.as_str()))
+        .filter_map(|category| category.id.parse::<u32>().ok())
+        .collect())
+}
+
 impl Iterator for XtreamPlaylistIterator {
     type Item = (XtreamPlaylistItem, bool);
     fn next(&mut self) -> Option<Self::Item> {
@@ -1,7 +1,7 @@
 use shared::error::{create_tuliprox_error, create_tuliprox_error_result, info_err, notify_err, str_to_io_error, to_io_error, TuliproxError, TuliproxErrorKind};
 use crate::model::{ProxyUserCredentials};
 use crate::model::{Config, ConfigInput, ConfigTarget, XtreamTargetOutput};
-use crate::model::{PlaylistGroup, PlaylistItem, XtreamPlaylistItem};
+use crate::model::{get_parent_group_path, PlaylistGroup, PlaylistItem, XtreamPlaylistItem};
 use crate::model::{rewrite_doc_urls, PlaylistXtreamCategory, XtreamMappingOptions, XtreamSeriesEpisode};
 use crate::model::normalize_release_date;
 use crate::repository::bplustree::{BPlusTree, BPlusTreeQuery, BPlusTreeUpdate};
@@ -105,6 +105,7 @@ pub fn xtream_get_record_file_path(storage_path: &Path, item_type: PlaylistItemT
 }
 fn write_playlists_to_file(
     cfg: &Config,
+    target: &ConfigTarget,
     storage_path: &Path,
     collections: Vec<(XtreamCluster, &[&mut PlaylistItem])>,
 ) -> Result<(), TuliproxError> {
@@ -112,7 +113,7 @@ fn write_playlists_to_file(
         let (xtream_path, idx_path) = xtream_get_file_paths(storage_path, cluster);
         {
             let _file_lock = cfg.file_locks.write_lock(&xtream_path);
-            match IndexedDocumentWriter::new(xtream_path.clone(), idx_path) {
+            match IndexedDocumentWriter::new_with_compression(xtream_path.clone(), idx_path, target.is_store_compressed()) {
                 Ok(mut writer) => {
                     for item in playlist {
                         let xtream = item.to_xtream();
@@ -216,24 +217,39 @@ pub async fn xtream_write_playlist(
     // preserve category_ids
     let (max_cat_id, existing_cat_ids) = load_old_category_ids(&path);
     let mut cat_id_counter = max_cat_id;
+    let mut cat_ids: HashMap<(XtreamCluster, String), u32> = HashMap::new();
+    for plg in playlist.iter_mut().filter(|plg| !plg.channels.is_empty()) {
+        let cat_key = format!("{}{}", plg.xtream_cluster, &plg.title);
+        plg.id = existing_cat_ids.get(&cat_key).copied().unwrap_or_else(|| {
+            cat_id_counter += 1;
+            cat_id_counter
+        });
+        cat_ids.insert((plg.xtream_cluster, plg.title.clone()), plg.id);
+    }
+    let group_hierarchy = target.options.as_ref().is_some_and(|options| options.group_hierarchy);
     for plg in playlist.iter_mut() {
         if !&plg.channels.is_empty() {
-            let cat_key = format!("{}{}", plg.xtream_cluster, &plg.title);
-            let cat_id = existing_cat_ids.get(&cat_key).unwrap_or_else(|| {
-                cat_id_counter += 1;
-                &cat_id_counter
+            let cat_id = &plg.id;
+            // the parent of a sub group is only referenced if it is a category itself
+            let parent_id = get_parent_group_path(&plg.title)
+                .filter(|_| group_hierarchy)
+                .and_then(|parent| cat_ids.get(&(plg.xtream_cluster, parent.to_string())))
+                .copied()
+                .unwrap_or(0);
+
+            let mut category = json!({
+              crate::model::XC_TAG_CATEGORY_ID: format!("{}", &cat_id),
+              crate::model::XC_TAG_CATEGORY_NAME: plg.title.clone(),
+              crate::model::XC_TAG_PARENT_ID: parent_id
             });
-            plg.id = *cat_id;
-
+            if let Some(icon) = target.get_category_icon(plg.xtream_cluster, &plg.title) {
+                category[crate::model::XC_TAG_CATEGORY_ICON] = Value::String(icon.to_string());
+            }
             match &plg.xtream_cluster {
                 XtreamCluster::Live => &mut cat_live_col,
                 XtreamCluster::Series => &mut cat_series_col,
                 XtreamCluster::Video => &mut cat_vod_col,
-            }.push(json!({
-              crate::model::XC_TAG_CATEGORY_ID: format!("{}", &cat_id),
-              crate::model::XC_TAG_CATEGORY_NAME: plg.title.clone(),
-              crate::model::XC_TAG_PARENT_ID: 0
-            }));
+            }.push(category);
 
             for pli in &mut plg.channels {
                 let header = &mut pli.header;
@@ -263,6 +279,7 @@ pub async fn xtream_write_playlist(
 
     match write_playlists_to_file(
         cfg,
+        target,
         &path,
         vec![
             (XtreamCluster::Live, &live_col),
@@ -0,0 +1,105 @@
+use shared::utils::CONSTANTS;
+
+// Prefix codes which stand for a language and not for a country.
+const LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("en", "en"), ("eng", "en"), ("ger", "de"), ("deu", "de"), ("fre", "fr"), ("fra", "fr"),
+    ("spa", "es"), ("esp", "es"), ("ita", "it"), ("por", "pt"), ("rus", "ru"), ("tur", "tr"),
+    ("pol", "pl"), ("ar", "ar"), ("ara", "ar"), ("nld", "nl"), ("dut", "nl"), ("swe", "sv"),
+    ("nor", "no"), ("dan", "da"), ("fin", "fi"), ("hin", "hi"), ("jpn", "ja"), ("kor", "ko"),
+    ("chi", "zh"),
+];
+
+// Prefix codes which are commonly used instead of the iso country code.
+const COUNTRY_ALIASES: &[(&str, &str)] = &[("uk", "gb")];
+
+// The main language of a country, used when only the country is known.
+const COUNTRY_LANGUAGES: &[(&str, &str)] = &[
+    ("de", "de"), ("at", "de"), ("ch", "de"), ("fr", "fr"), ("it", "it"), ("es", "es"),
+    ("mx", "es"), ("co", "es"), ("cl", "es"), ("pe", "es"), ("pt", "pt"), ("br", "pt"),
+    ("nl", "nl"), ("pl", "pl"), ("tr", "tr"), ("gb", "en"), ("us", "en"), ("ca", "en"),
+    ("au", "en"), ("ie", "en"), ("nz", "en"), ("ru", "ru"), ("gr", "el"), ("ro", "ro"),
+    ("hu", "hu"), ("cz", "cs"), ("sk", "sk"), ("se", "sv"), ("no", "no"), ("dk", "da"),
+    ("fi", "fi"), ("rs", "sr"), ("hr", "hr"), ("si", "sl"), ("bg", "bg"), ("al", "sq"),
+    ("in", "hi"), ("jp", "ja"), ("kr", "ko"), ("cn", "zh"), ("il", "he"), ("ir", "fa"),
+    ("pk", "ur"), ("ua", "uk"), ("sa", "ar"), ("eg", "ar"), ("ae", "ar"), ("ma", "ar"),
+];
+
+fn lookup(table: &[(&str, &'static str)], code: &str) -> Option<&'static str> {
+    table.iter().find(|(key, _)| *key == code).map(|(_, value)| *value)
+}
+
+/// Country (ISO 3166) and language (ISO 639-1) of a channel, both upper case.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChannelClassification {
+    pub country: Option<String>,
+    pub language: Option<String>,
+}
+
+impl ChannelClassification {
+    fn is_complete(&self) -> bool {
+        self.country.is_some() && self.language.is_some()
+    }
+
+    fn add_code(&mut self, code: &str) {
+        let code = code.to_lowercase();
+        if let Some(language) = lookup(LANGUAGE_ALIASES, &code) {
+            self.language.get_or_insert_with(|| language.to_uppercase());
+            return;
+        }
+        let country = lookup(COUNTRY_ALIASES, &code).unwrap_or(code.as_str());
+        if CONSTANTS.country_codes.contains(country) {
+            self.country.get_or_insert_with(|| country.to_uppercase());
+            if let Some(language) = lookup(COUNTRY_LANGUAGES, country) {
+                self.language.get_or_insert_with(|| language.to_uppercase());
+            }
+        }
+    }
+}
+
+/// Classifies a channel by the country prefixes of the given texts like `DE: Das Erste`, `|UK| News` or just `FR`,
+/// in the given order, and by the country suffix of the epg id like `daserste.de`.
+pub fn classify_channel(texts: &[&str], epg_id: Option<&str>) -> ChannelClassification {
+    let mut result = ChannelClassification::default();
+    for text in texts {
+        let text = text.trim();
+        // a group name can be the code itself
+        let code = if (2..=3).contains(&text.len()) && text.chars().all(|c| c.is_ascii_alphabetic()) {
+            Some(text)
+        } else {
+            CONSTANTS.re_title_language_prefix.captures(text).and_then(|caps| caps.get(1)).map(|code| code.as_str())
+        };
+        if let Some(code) = code {
+            result.add_code(code);
+            if result.is_complete() {
+                return result;
+            }
+        }
+    }
+    if let Some((_, suffix)) = epg_id.and_then(|id| id.trim().rsplit_once('.')) {
+        result.add_code(suffix);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{classify_channel, ChannelClassification};
+
+    #[test]
+    fn test_classify_channel() {
+        let result = classify_channel(&["DE: Das Erste HD", "Germany"], None);
+        assert_eq!((result.country.as_deref(), result.language.as_deref()), (Some("DE"), Some("DE")));
+
+        let result = classify_channel(&["BBC One", "|UK| Entertainment"], None);
+        assert_eq!((result.country.as_deref(), result.language.as_deref()), (Some("GB"), Some("EN")));
+
+        let result = classify_channel(&["EN: Discovery", "Documentary"], Some("discovery.ch"));
+        assert_eq!((result.country.as_deref(), result.language.as_deref()), (Some("CH"), Some("EN")));
+
+        let result = classify_channel(&["Sport HD", "FR"], None);
+        assert_eq!((result.country.as_deref(), result.language.as_deref()), (Some("FR"), Some("FR")));
+
+        let result = classify_channel(&["Sky: Cinema", "Movies"], Some("skycinema"));
+        assert_eq!(result, ChannelClassification::default());
+    }
+}
@@ -1,9 +1,18 @@
-use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Seek, SeekFrom};
 use std::path::Path;
-use flate2::bufread::{GzDecoder, ZlibDecoder};
-use crate::utils::compression::compression_utils::{is_deflate, is_gzip};
+use flate2::bufread::{DeflateDecoder, GzDecoder, ZlibDecoder};
+use lzma_rust2::XzReader;
+use ruzstd::decoding::StreamingDecoder;
+use crate::utils::compression::compression_utils::{is_deflate, is_gzip, is_xz, is_zip, is_zstd};
 use crate::utils::{file_reader, open_readonly_file};
 
+const HEADER_SIZE: usize = 6;
+const ZIP_LOCAL_HEADER_SIZE: usize = 30;
+const ZIP_METHOD_STORED: u16 = 0;
+const ZIP_METHOD_DEFLATE: u16 = 8;
+const ZIP_FLAG_ENCRYPTED: u16 = 0x0001;
+const ZIP_FLAG_DATA_DESCRIPTOR: u16 = 0x0008;
+
 pub struct CompressedFileReader {
     reader: BufReader<Box<dyn Read>>,
 }
@@ -13,13 +22,20 @@ impl CompressedFileReader {
         let file = open_readonly_file(path)?;
 
         let mut buffered_file = file_reader(file);
-        let mut header = [0u8; 2];
-        buffered_file.read_exact(&mut header)?;
+        let mut header = [0u8; HEADER_SIZE];
+        let header_len = read_header(&mut buffered_file, &mut header)?;
+        let header = &header[..header_len];
         buffered_file.seek(SeekFrom::Start(0))?;
 
-        let reader: Box<dyn Read> = if is_gzip(&header) {
+        let reader: Box<dyn Read> = if is_gzip(header) {
             Box::new(GzDecoder::new(buffered_file))
-        } else if is_deflate(&header) {
+        } else if is_xz(header) {
+            Box::new(XzReader::new(buffered_file, true))
+        } else if is_zstd(header) {
+            Box::new(StreamingDecoder::new(buffered_file).map_err(|err| Error::new(ErrorKind::InvalidData, err.to_string()))?)
+        } else if is_zip(header) {
+            open_first_zip_entry(buffered_file)?
+        } else if header.len() >= 2 && is_deflate(header) {
             Box::new(ZlibDecoder::new(buffered_file))
         } else {
             Box::new(buffered_file)
@@ -31,6 +47,40 @@ impl CompressedFileReader {
     }
 }
 
+// Reads the magic bytes, files can be shorter than the header.
+fn read_header<R: Read>(reader: &mut R, header: &mut [u8]) -> std::io::Result<usize> {
+    let mut len = 0;
+    while len < header.len() {
+        match reader.read(&mut header[len..])? {
+            0 => break,
+            count => len += count,
+        }
+    }
+    Ok(len)
+}
+
+// Guides are distributed as zip archives with a single xml file, the first entry is read.
+fn open_first_zip_entry<R: BufRead + 'static>(mut reader: R) -> std::io::Result<Box<dyn Read>> {
+    let mut local_header = [0u8; ZIP_LOCAL_HEADER_SIZE];
+    reader.read_exact(&mut local_header)?;
+    let read_u16 = |offset: usize| u16::from_le_bytes([local_header[offset], local_header[offset + 1]]);
+    let flags = read_u16(6);
+    let method = read_u16(8);
+    let compressed_size = u32::from_le_bytes([local_header[18], local_header[19], local_header[20], local_header[21]]);
+    let name_len = u64::from(read_u16(26));
+    let extra_len = u64::from(read_u16(28));
+    if flags & ZIP_FLAG_ENCRYPTED != 0 {
+        return Err(Error::new(ErrorKind::Unsupported, "encrypted zip entries are not supported"));
+    }
+    // skip the file name and the extra field
+    std::io::copy(&mut (&mut reader).take(name_len + extra_len), &mut std::io::sink())?;
+    match method {
+        ZIP_METHOD_DEFLATE => Ok(Box::new(DeflateDecoder::new(reader))),
+        ZIP_METHOD_STORED if flags & ZIP_FLAG_DATA_DESCRIPTOR == 0 => Ok(Box::new(reader.take(u64::from(compressed_size)))),
+        _ => Err(Error::new(ErrorKind::Unsupported, format!("unsupported zip compression method {method}"))),
+    }
+}
+
 // Implement the Read trait for CompressedFileReader
 impl Read for CompressedFileReader {
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
@@ -62,3 +112,52 @@ impl Iterator for CompressedFileReader
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::compression::compressed_file_reader::CompressedFileReader;
+    use flate2::write::DeflateEncoder;
+    use flate2::Compression;
+    use std::io::{Read, Write};
+
+    const GUIDE: &str = "<?xml version=\"1.0\"?>\n<tv></tv>\n";
+
+    fn create_zip(method: u16, data: &[u8]) -> Vec<u8> {
+        let name = b"guide.xml";
+        let mut zip = b"PK\x03\x04".to_vec();
+        zip.extend_from_slice(&20u16.to_le_bytes()); // version
+        zip.extend_from_slice(&0u16.to_le_bytes()); // flags
+        zip.extend_from_slice(&method.to_le_bytes());
+        zip.extend_from_slice(&[0u8; 8]); // time, date, crc
+        zip.extend_from_slice(&u32::try_from(data.len()).unwrap().to_le_bytes());
+        zip.extend_from_slice(&u32::try_from(GUIDE.len()).unwrap().to_le_bytes());
+        zip.extend_from_slice(&u16::try_from(name.len()).unwrap().to_le_bytes());
+        zip.extend_from_slice(&0u16.to_le_bytes()); // extra field
+        zip.extend_from_slice(name);
+        zip.extend_from_slice(data);
+        zip.extend_from_slice(b"PK\x01\x02"); // central directory is not read
+        zip
+    }
+
+    fn read_file(content: &[u8]) -> String {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(content).unwrap();
+        let mut result = String::new();
+        CompressedFileReader::new(file.path()).unwrap().read_to_string(&mut result).unwrap();
+        result
+    }
+
+    #[test]
+    fn test_compressed_file_reader() {
+        assert_eq!(read_file(GUIDE.as_bytes()), GUIDE);
+        assert_eq!(read_file(b"<"), "<");
+
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(GUIDE.as_bytes()).unwrap();
+        assert_eq!(read_file(&create_zip(8, &encoder.finish().unwrap())), GUIDE);
+        assert_eq!(read_file(&create_zip(0, GUIDE.as_bytes())), GUIDE);
+
+        let zstd = ruzstd::encoding::compress_to_vec(GUIDE.as_bytes(), ruzstd::encoding::CompressionLevel::Fastest);
+        assert_eq!(read_file(&zstd), GUIDE);
+    }
+}
@@ -12,6 +12,21 @@ pub const fn is_deflate(bytes: &[u8]) -> bool {
     bytes[0] == 0x78 && (bytes[1] == 0x01 || bytes[1] == 0x9C || bytes[1] == 0xDA)
 }
 
+pub const fn is_zip(bytes: &[u8]) -> bool {
+    // Zip files start with the local file header signature PK\x03\x04
+    bytes.len() >= 4 && bytes[0] == 0x50 && bytes[1] == 0x4B && bytes[2] == 0x03 && bytes[3] == 0x04
+}
+
+pub const fn is_xz(bytes: &[u8]) -> bool {
+    // Xz files start with the bytes 0xFD '7zXZ' 0x00
+    bytes.len() >= 6 && bytes[0] == 0xFD && bytes[1] == 0x37 && bytes[2] == 0x7A && bytes[3] == 0x58 && bytes[4] == 0x5A && bytes[5] == 0x00
+}
+
+pub const fn is_zstd(bytes: &[u8]) -> bool {
+    // Zstandard frames start with the magic number 0xFD2FB528 in little endian
+    bytes.len() >= 4 && bytes[0] == 0x28 && bytes[1] == 0xB5 && bytes[2] == 0x2F && bytes[3] == 0xFD
+}
+
 pub fn compress_string(input: &str) -> std::io::Result<Vec<u8>> {
     let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
     encoder.write_all(input.as_bytes())?;
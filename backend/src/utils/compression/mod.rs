@@ -1,2 +1,3 @@
 pub mod compressed_file_reader;
 pub mod compression_utils;
+pub mod tar_archive;
@@ -0,0 +1,88 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{Error, ErrorKind, Write};
+
+const BLOCK_SIZE: usize = 512;
+const NAME_LEN: usize = 100;
+
+// Writes a gzip compressed ustar archive with regular files only.
+pub struct TarGzWriter<W: Write> {
+    encoder: GzEncoder<W>,
+    mtime: u64,
+}
+
+fn write_octal(field: &mut [u8], value: u64) {
+    // the last byte of a numeric field is a terminating NUL
+    let digits = format!("{value:0width$o}", width = field.len() - 1);
+    let len = digits.len();
+    field[..len].copy_from_slice(digits.as_bytes());
+    field[len..].fill(0);
+}
+
+impl<W: Write> TarGzWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            encoder: GzEncoder::new(writer, Compression::default()),
+            mtime: u64::try_from(chrono::Utc::now().timestamp()).unwrap_or_default(),
+        }
+    }
+
+    pub fn append(&mut self, name: &str, content: &[u8]) -> std::io::Result<()> {
+        if name.is_empty() || name.len() > NAME_LEN {
+            return Err(Error::new(ErrorKind::InvalidInput, format!("Invalid archive entry name {name}")));
+        }
+        let mut header = [0u8; BLOCK_SIZE];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        write_octal(&mut header[100..108], 0o644);
+        write_octal(&mut header[108..116], 0);
+        write_octal(&mut header[116..124], 0);
+        write_octal(&mut header[124..136], content.len() as u64);
+        write_octal(&mut header[136..148], self.mtime);
+        header[156] = b'0';
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[263..265].copy_from_slice(b"00");
+        // the checksum is calculated with the checksum field filled with spaces
+        header[148..156].fill(b' ');
+        let checksum: u64 = header.iter().map(|&b| u64::from(b)).sum();
+        write_octal(&mut header[148..155], checksum);
+
+        self.encoder.write_all(&header)?;
+        self.encoder.write_all(content)?;
+        let padding = (BLOCK_SIZE - content.len() % BLOCK_SIZE) % BLOCK_SIZE;
+        self.encoder.write_all(&[0u8; BLOCK_SIZE][..padding])
+    }
+
+    pub fn finish(mut self) -> std::io::Result<W> {
+        // an archive ends with two empty blocks
+        self.encoder.write_all(&[0u8; BLOCK_SIZE * 2])?;
+        self.encoder.finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::compression::tar_archive::TarGzWriter;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_tar_gz_writer() {
+        let mut writer = TarGzWriter::new(Vec::new());
+        writer.append("config/config.yml", b"api:\n  port: 8901\n").unwrap();
+        writer.append("version.json", &[b'x'; 600]).unwrap();
+        assert!(writer.append(&"a".repeat(101), b"").is_err());
+        let archive = writer.finish().unwrap();
+
+        let mut tar = Vec::new();
+        GzDecoder::new(archive.as_slice()).read_to_end(&mut tar).unwrap();
+        assert_eq!(tar.len(), 512 * 7);
+        assert!(tar.starts_with(b"config/config.yml\0"));
+        assert_eq!(&tar[124..136], b"00000000022\0");
+        assert_eq!(&tar[257..262], b"ustar");
+        assert_eq!(&tar[512..530], b"api:\n  port: 8901\n");
+        assert!(tar[1024..].starts_with(b"version.json\0"));
+        let checksum: u64 = tar[..512].iter().enumerate()
+            .map(|(i, &b)| if (148..156).contains(&i) { u64::from(b' ') } else { u64::from(b) }).sum();
+        assert_eq!(&tar[148..155], format!("{checksum:06o}\0").as_bytes());
+    }
+}
@@ -0,0 +1,160 @@
+use crate::messaging::send_message;
+use crate::model::{Config, CrashReportConfig, MessagingConfig};
+use crate::utils::request::{create_client, sanitize_sensitive_info};
+use crate::{BUILD_TIMESTAMP, VERSION};
+use log::error;
+use serde_json::json;
+use shared::model::MsgKind;
+use std::backtrace::Backtrace;
+use std::collections::BTreeMap;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const CRASH_REPORT_PREFIX: &str = "crash_report_";
+
+struct CrashReporter {
+    dir: PathBuf,
+    max_reports: usize,
+    // sanitized, without urls and credentials
+    config_summary: serde_json::Value,
+    messaging: Option<MessagingConfig>,
+    client: Arc<reqwest::Client>,
+}
+
+// Only counts and enabled features, the report should be safe to attach to a public issue.
+fn create_config_summary(cfg: &Config) -> serde_json::Value {
+    let mut input_types = BTreeMap::new();
+    let mut inputs = 0;
+    let mut targets = 0;
+    for source in &cfg.sources.sources {
+        targets += source.targets.len();
+        for input in source.inputs.iter().filter(|input| input.enabled) {
+            inputs += 1;
+            let input_type = serde_json::to_value(input.input_type).ok().and_then(|value| value.as_str().map(ToString::to_string)).unwrap_or_default();
+            *input_types.entry(input_type).or_insert(0usize) += 1;
+        }
+    }
+    json!({
+        "threads": cfg.threads,
+        "sources": cfg.sources.sources.len(),
+        "inputs": inputs,
+        "input_types": input_types,
+        "targets": targets,
+        "schedules": cfg.schedules.as_ref().map_or(0, Vec::len),
+        "web_ui": cfg.web_ui.as_ref().is_some_and(|web_ui| web_ui.enabled),
+        "reverse_proxy": cfg.reverse_proxy.is_some(),
+        "cache": cfg.reverse_proxy.as_ref().and_then(|reverse_proxy| reverse_proxy.cache.as_ref()).is_some_and(|cache| cache.enabled),
+        "hdhomerun": cfg.hdhomerun.as_ref().is_some_and(|hdhomerun| hdhomerun.enabled),
+        "messaging": cfg.messaging.is_some(),
+        "proxy": cfg.proxy.is_some(),
+        "recording": cfg.recording.as_ref().is_some_and(|recording| recording.enabled),
+    })
+}
+
+fn get_panic_message(info: &PanicHookInfo) -> String {
+    let payload = info.payload();
+    let message = payload.downcast_ref::<&str>().map(|msg| (*msg).to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    sanitize_sensitive_info(&message).into_owned()
+}
+
+fn create_crash_report(reporter: &CrashReporter, message: &str, info: &PanicHookInfo) -> serde_json::Value {
+    json!({
+        "time": chrono::Utc::now().to_rfc3339(),
+        "version": VERSION,
+        "build_time": BUILD_TIMESTAMP,
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "thread": std::thread::current().name().unwrap_or("unnamed"),
+        "message": message,
+        "location": info.location().map(ToString::to_string),
+        "backtrace": Backtrace::force_capture().to_string(),
+        "config": reporter.config_summary,
+    })
+}
+
+// keeps the newest `max_reports` reports, the names contain the time
+fn remove_old_crash_reports(dir: &Path, max_reports: usize) {
+    let Ok(entries) = std::fs::read_dir(dir) else { return };
+    let mut reports: Vec<PathBuf> = entries.filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.file_name().and_then(|name| name.to_str()).is_some_and(|name| name.starts_with(CRASH_REPORT_PREFIX)))
+        .collect();
+    if reports.len() > max_reports {
+        reports.sort();
+        for path in &reports[..reports.len() - max_reports] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+fn write_crash_report(reporter: &CrashReporter, report: &serde_json::Value) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(&reporter.dir)?;
+    let file_name = format!("{CRASH_REPORT_PREFIX}{}.json", chrono::Utc::now().format("%Y%m%d_%H%M%S_%3f"));
+    let path = reporter.dir.join(file_name);
+    std::fs::write(&path, serde_json::to_vec_pretty(report)?)?;
+    remove_old_crash_reports(&reporter.dir, reporter.max_reports);
+    Ok(path)
+}
+
+fn handle_panic(reporter: &CrashReporter, notify: bool, info: &PanicHookInfo) {
+    let message = get_panic_message(info);
+    let report = create_crash_report(reporter, &message, info);
+    match write_crash_report(reporter, &report) {
+        Ok(path) => error!("Crash report written to {}", path.display()),
+        Err(err) => error!("Failed to write crash report: {err}"),
+    }
+    // the messages are sent by the runtime, panics outside the runtime are only reported as file
+    if notify {
+        if let Ok(handle) = tokio::runtime::Handle::try_current() {
+            let _guard = handle.enter();
+            let location = info.location().map(|location| format!(" at {location}")).unwrap_or_default();
+            send_message(&reporter.client, &MsgKind::Error, reporter.messaging.as_ref(), &format!("tuliprox {VERSION} crashed{location}: {message}"));
+        }
+    }
+}
+
+/// Installs the panic handler which writes a crash report into the crash report directory, if enabled.
+/// The default handler is called afterward, the panic is not caught.
+pub fn init_crash_report(cfg: &Config) {
+    let Some(crash_report) = cfg.crash_report.as_ref().filter(|crash_report| crash_report.enabled) else { return };
+    let CrashReportConfig { dir, max_reports, notify, .. } = crash_report;
+    let reporter = CrashReporter {
+        dir: PathBuf::from(dir.as_deref().unwrap_or("crash_reports")),
+        max_reports: *max_reports,
+        config_summary: create_config_summary(cfg),
+        messaging: cfg.messaging.clone(),
+        client: Arc::new(create_client(cfg).build().unwrap_or_else(|_| reqwest::Client::new())),
+    };
+    let notify = *notify;
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        handle_panic(&reporter, notify, info);
+        default_hook(info);
+    }));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::crash_report::{remove_old_crash_reports, CRASH_REPORT_PREFIX};
+
+    #[test]
+    fn test_remove_old_crash_reports() {
+        let dir = tempfile::tempdir().unwrap();
+        for idx in 0..5 {
+            std::fs::write(dir.path().join(format!("{CRASH_REPORT_PREFIX}2025010{idx}_120000_000.json")), "{}").unwrap();
+        }
+        std::fs::write(dir.path().join("other.json"), "{}").unwrap();
+        remove_old_crash_reports(dir.path(), 2);
+        let mut names: Vec<String> = std::fs::read_dir(dir.path()).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string()).collect();
+        names.sort();
+        assert_eq!(names, vec![
+            format!("{CRASH_REPORT_PREFIX}20250103_120000_000.json"),
+            format!("{CRASH_REPORT_PREFIX}20250104_120000_000.json"),
+            "other.json".to_string(),
+        ]);
+    }
+}
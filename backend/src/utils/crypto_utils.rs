@@ -3,6 +3,10 @@ use base64::{engine::general_purpose, Engine as _};
 use openssl::symm::{Cipher, Crypter, Mode};
 use rand::Rng;
 
+// the header and defaults of `openssl enc -pbkdf2`
+const OPENSSL_SALT_MAGIC: &[u8] = b"Salted__";
+const OPENSSL_SALT_LEN: usize = 8;
+const OPENSSL_PBKDF2_ITERATIONS: usize = 10_000;
 
 fn encode_base64_string(input: &[u8]) -> String {
     general_purpose::URL_SAFE_NO_PAD.encode(input)
@@ -60,9 +64,41 @@ pub fn decrypt_text(secret: &[u8; 16], encrypted_text: &str) -> Result<String, T
     String::from_utf8(decrypted).map_err(|err| TuliproxError::new(TuliproxErrorKind::Info, err.to_string()))
 }
 
+fn derive_openssl_key(password: &str, salt: &[u8]) -> Result<[u8; 48], TuliproxError> {
+    // 32 bytes key followed by 16 bytes IV
+    let mut key_iv = [0u8; 48];
+    openssl::pkcs5::pbkdf2_hmac(password.as_bytes(), salt, OPENSSL_PBKDF2_ITERATIONS, openssl::hash::MessageDigest::sha256(), &mut key_iv)
+        .map_err(|err| TuliproxError::new(TuliproxErrorKind::Info, err.to_string()))?;
+    Ok(key_iv)
+}
+
+/// Encrypts like `openssl enc -aes-256-cbc -pbkdf2`, the result can be decrypted with
+/// `openssl enc -d -aes-256-cbc -pbkdf2 -in <file> -out <file>`.
+pub fn encrypt_with_password(password: &str, data: &[u8]) -> Result<Vec<u8>, TuliproxError> {
+    let salt: [u8; OPENSSL_SALT_LEN] = rand::rng().random();
+    let key_iv = derive_openssl_key(password, &salt)?;
+    let ciphertext = openssl::symm::encrypt(Cipher::aes_256_cbc(), &key_iv[..32], Some(&key_iv[32..]), data)
+        .map_err(|err| TuliproxError::new(TuliproxErrorKind::Info, err.to_string()))?;
+    let mut out = Vec::with_capacity(OPENSSL_SALT_MAGIC.len() + OPENSSL_SALT_LEN + ciphertext.len());
+    out.extend_from_slice(OPENSSL_SALT_MAGIC);
+    out.extend_from_slice(&salt);
+    out.extend(ciphertext);
+    Ok(out)
+}
+
+pub fn decrypt_with_password(password: &str, data: &[u8]) -> Result<Vec<u8>, TuliproxError> {
+    let Some(salted) = data.strip_prefix(OPENSSL_SALT_MAGIC).filter(|salted| salted.len() > OPENSSL_SALT_LEN) else {
+        return Err(TuliproxError::new(TuliproxErrorKind::Info, "Data is not encrypted with a password".to_string()));
+    };
+    let (salt, ciphertext) = salted.split_at(OPENSSL_SALT_LEN);
+    let key_iv = derive_openssl_key(password, salt)?;
+    openssl::symm::decrypt(Cipher::aes_256_cbc(), &key_iv[..32], Some(&key_iv[32..]), ciphertext)
+        .map_err(|err| TuliproxError::new(TuliproxErrorKind::Info, err.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::utils::crypto_utils::{decrypt_text, deobfuscate_text, encrypt_text, obfuscate_text};
+    use crate::utils::crypto_utils::{decrypt_text, decrypt_with_password, deobfuscate_text, encrypt_text, encrypt_with_password, obfuscate_text};
     use rand::Rng;
 
     #[test]
@@ -83,4 +119,14 @@ mod tests {
 
         assert_eq!(decrypted, plain);
     }
+
+    #[test]
+    fn test_encrypt_with_password() {
+        let plain = b"backup archive";
+        let encrypted = encrypt_with_password("secret", plain).unwrap();
+        assert!(encrypted.starts_with(b"Salted__"));
+        assert_eq!(decrypt_with_password("secret", &encrypted).unwrap(), plain);
+        // a wrong password fails on the padding, rarely it results in garbage
+        assert!(!decrypt_with_password("wrong", &encrypted).is_ok_and(|data| data == plain));
+    }
 }
\ No newline at end of file
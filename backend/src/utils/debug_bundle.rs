@@ -0,0 +1,166 @@
+use crate::model::{Healthcheck, HealthcheckConfig, LogLevelConfig, SanitizeRule};
+use crate::utils::request::{sanitize_sensitive_info, set_sanitize_rules, set_sanitize_sensitive_info};
+use crate::utils::tar_archive::TarGzWriter;
+use crate::utils::config_file_reader;
+use regex::Regex;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+use std::time::Duration;
+
+const DEFAULT_LOG_LINES: usize = 2000;
+const STATUS_TIMEOUT_SECS: u64 = 5;
+
+// yaml values of keys like `password`, `smtp_password`, `access_token` or `user` (pushover) are replaced.
+static RE_SENSITIVE_YAML_VALUE: LazyLock<Regex> = LazyLock::new(|| Regex::new(
+    r"(?i)^(\s*(?:-\s+)?(?:[\w-]*(?:password|username|token|secret|api_key|chat_ids|email)|user|key)\s*:\s*)\S.*$").unwrap());
+
+/// Arguments of the `debug-bundle` command.
+#[derive(Debug, Clone, clap::Args)]
+pub struct DebugBundleArgs {
+    /// The archive file, default is tuliprox-debug-<timestamp>.tar.gz in the current directory
+    #[arg(short = 'o', long)]
+    pub output: Option<String>,
+
+    /// A log file with the redirected tuliprox output to include
+    #[arg(long = "log-file")]
+    pub log_file: Option<String>,
+
+    /// The number of lines taken from the end of the log file
+    #[arg(long = "log-lines", default_value_t = DEFAULT_LOG_LINES)]
+    pub log_lines: usize,
+}
+
+fn redact_line(line: &str) -> String {
+    let line = RE_SENSITIVE_YAML_VALUE.replace(line, "$1***");
+    sanitize_sensitive_info(&line).into_owned()
+}
+
+fn redact_text<I: Iterator<Item=String>>(lines: I) -> String {
+    let mut result = String::new();
+    for line in lines {
+        result.push_str(&redact_line(&line));
+        result.push('\n');
+    }
+    result
+}
+
+fn read_redacted_file(path: &Path) -> std::io::Result<String> {
+    let reader = BufReader::new(File::open(path)?);
+    Ok(redact_text(reader.lines().map_while(Result::ok)))
+}
+
+fn read_redacted_log(path: &Path, max_lines: usize) -> std::io::Result<String> {
+    let reader = BufReader::new(File::open(path)?);
+    let mut lines = std::collections::VecDeque::with_capacity(max_lines.min(DEFAULT_LOG_LINES));
+    for line in reader.lines().map_while(Result::ok) {
+        if lines.len() == max_lines {
+            lines.pop_front();
+        }
+        if max_lines > 0 {
+            lines.push_back(line);
+        }
+    }
+    Ok(redact_text(lines.into_iter()))
+}
+
+// the mapping can be a single file or a directory with mapping files
+fn collect_config_files(config_files: &[&str]) -> Vec<PathBuf> {
+    let mut files = vec![];
+    for file in config_files {
+        let path = PathBuf::from(file);
+        if path.is_dir() {
+            if let Ok(entries) = std::fs::read_dir(&path) {
+                let mut dir_files: Vec<PathBuf> = entries.filter_map(Result::ok).map(|entry| entry.path())
+                    .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "yml" || ext == "yaml"))
+                    .collect();
+                dir_files.sort();
+                files.extend(dir_files);
+            }
+        } else if path.is_file() {
+            files.push(path);
+        }
+    }
+    files
+}
+
+fn create_version_info() -> serde_json::Value {
+    serde_json::json!({
+        "version": crate::VERSION,
+        "build_time": crate::api::api_utils::get_build_time(),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "created": crate::api::api_utils::get_server_time(),
+    })
+}
+
+// the custom redaction rules of the log config, invalid rules are reported by the server on start
+fn read_sanitize_rules(config_file: &str) -> Vec<SanitizeRule> {
+    File::open(config_file).ok()
+        .and_then(|file| serde_yaml::from_reader::<_, LogLevelConfig>(config_file_reader(file, true)).ok())
+        .and_then(|config| config.log)
+        .and_then(|mut log| log.prepare().ok().map(|()| log.sanitize_rules))
+        .unwrap_or_default()
+}
+
+// the status of a running server, same as the `--healthcheck` request
+fn create_status_snapshot(config_file: &str) -> serde_json::Value {
+    let port = File::open(config_file).ok()
+        .and_then(|file| serde_yaml::from_reader::<_, HealthcheckConfig>(config_file_reader(file, true)).ok())
+        .map(|config| config.api.port);
+    let Some(port) = port else {
+        return serde_json::json!({"server": null, "error": "Failed to read the api port from the config file"});
+    };
+    let client = reqwest::blocking::Client::builder().timeout(Duration::from_secs(STATUS_TIMEOUT_SECS)).build();
+    let response = client.map_err(|err| err.to_string())
+        .and_then(|client| client.get(format!("http://localhost:{port}/healthcheck")).send().map_err(|err| err.to_string()))
+        .and_then(|response| response.json::<Healthcheck>().map_err(|err| err.to_string()));
+    match response {
+        Ok(check) => serde_json::json!({"server": check}),
+        Err(err) => serde_json::json!({"server": null, "error": format!("Server not reachable: {}", sanitize_sensitive_info(&err))}),
+    }
+}
+
+/// Writes the archive with the redacted config files, the log tail, a status snapshot and the version info.
+pub fn create_debug_bundle(args: &DebugBundleArgs, config_file: &str, config_files: &[&str]) -> std::io::Result<PathBuf> {
+    // credentials are always removed, regardless of the log config
+    set_sanitize_sensitive_info(true);
+    set_sanitize_rules(read_sanitize_rules(config_file));
+
+    let output = PathBuf::from(args.output.clone().unwrap_or_else(||
+        format!("tuliprox-debug-{}.tar.gz", chrono::Local::now().format("%Y%m%d-%H%M%S"))));
+    let mut archive = TarGzWriter::new(File::create(&output)?);
+
+    archive.append("version.json", serde_json::to_string_pretty(&create_version_info())?.as_bytes())?;
+    archive.append("status.json", serde_json::to_string_pretty(&create_status_snapshot(config_file))?.as_bytes())?;
+
+    for path in collect_config_files(config_files) {
+        let name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        archive.append(&format!("config/{name}"), read_redacted_file(&path)?.as_bytes())?;
+    }
+
+    if let Some(log_file) = args.log_file.as_ref() {
+        archive.append("logs/tuliprox.log", read_redacted_log(Path::new(log_file), args.log_lines)?.as_bytes())?;
+    }
+
+    archive.finish()?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::debug_bundle::redact_line;
+    use crate::utils::request::set_sanitize_sensitive_info;
+
+    #[test]
+    fn test_redact_line() {
+        set_sanitize_sensitive_info(true);
+        assert_eq!(redact_line("      password: secret"), "      password: ***");
+        assert_eq!(redact_line("  - username: bob"), "  - username: ***");
+        assert_eq!(redact_line("    smtp_password: \"x y\""), "    smtp_password: ***");
+        assert_eq!(redact_line("  token:"), "  token:");
+        assert_eq!(redact_line("    max_connections: 2"), "    max_connections: 2");
+        assert_eq!(redact_line("    url: http://provider.tv/get.php?username=bob&password=pw"), "    url: http://***/get.php?username=***&password=***");
+    }
+}
@@ -1,4 +1,4 @@
-use crate::model::{ApiProxyConfig, SourcesConfig};
+use crate::model::{ApiProxyConfig, EpgAliasesConfig, EpgSchedulesConfig, SourcesConfig};
 use crate::model::{Config};
 use shared::error::{create_tuliprox_error,  info_err, to_io_error, TuliproxError, TuliproxErrorKind};
 use crate::utils::{open_file, EnvResolvingReader};
@@ -54,26 +54,26 @@ pub fn read_api_proxy_config(cfg: &Config) -> Result<(), TuliproxError> {
     }
 }
 
-pub fn read_sources(sources_file: &str, resolve_env: bool, include_computed: bool) -> Result<SourcesConfig, TuliproxError> {
-
+/// Reads the sources file without preparing the inputs and targets.
+pub fn read_sources_file(sources_file: &str, resolve_env: bool) -> Result<SourcesConfig, TuliproxError> {
     match open_file(&std::path::PathBuf::from(sources_file)) {
         Ok(file) => {
             let maybe_sources: Result<SourcesConfig, _> = serde_yaml::from_reader(config_file_reader(file, resolve_env));
-            match maybe_sources {
-                Ok(mut sources) => {
-                    if let Err(err) = sources.prepare(include_computed) {
-                        Err(info_err!(format!("Can't read the sources-config file: {sources_file}: {err}")))
-                    } else {
-                        Ok(sources)
-                    }
-                }
-                Err(err) => Err(info_err!(format!("Can't read the sources-config file: {sources_file}: {err}")))
-            }
+            maybe_sources.map_err(|err| info_err!(format!("Can't read the sources-config file: {sources_file}: {err}")))
         }
         Err(err) => Err(info_err!(format!("Can't read the sources-config file: {sources_file}: {err}")))
     }
 }
 
+pub fn read_sources(sources_file: &str, resolve_env: bool, include_computed: bool) -> Result<SourcesConfig, TuliproxError> {
+    let mut sources = read_sources_file(sources_file, resolve_env)?;
+    if let Err(err) = sources.prepare(include_computed) {
+        Err(info_err!(format!("Can't read the sources-config file: {sources_file}: {err}")))
+    } else {
+        Ok(sources)
+    }
+}
+
 pub fn read_config(config_path: &str, config_file: &str, sources_file: &str, api_proxy_file: &str, mappings_file: Option<String>, include_computed: bool) -> Result<Config, TuliproxError> {
 
     let resolve_env = true;
@@ -93,6 +93,12 @@ pub fn read_config(config_path: &str, config_file: &str, sources_file: &str, api
                         if config.t_mapping_file_path.is_empty() {
                             config.t_mapping_file_path = resolve_env_var(&mappings_file.unwrap_or_else(|| utils::get_default_mappings_path(config_path)));
                         }
+                        if config.t_epg_aliases_file_path.is_empty() {
+                            config.t_epg_aliases_file_path = utils::get_default_epg_aliases_path(config_path);
+                        }
+                        if config.t_epg_schedules_file_path.is_empty() {
+                            config.t_epg_schedules_file_path = utils::get_default_epg_schedules_path(config_path);
+                        }
                         Ok(config)
                     }
                 }
@@ -131,6 +137,34 @@ pub fn read_api_proxy(config: &Config, resolve_env: bool) -> Option<ApiProxyConf
     })
 }
 
+/// Reads the epg alias file, a missing file is no error.
+pub fn read_epg_aliases(cfg: &Config) -> Result<Option<EpgAliasesConfig>, TuliproxError> {
+    let aliases_file = cfg.t_epg_aliases_file_path.as_str();
+    let path = PathBuf::from(aliases_file);
+    if aliases_file.is_empty() || !path.is_file() {
+        return Ok(None);
+    }
+    let file = open_file(&path).map_err(|err| info_err!(format!("Can't read the epg aliases file: {aliases_file}: {err}")))?;
+    let mut aliases: EpgAliasesConfig = serde_yaml::from_reader(config_file_reader(file, true))
+        .map_err(|err| info_err!(format!("Can't read the epg aliases file: {aliases_file}: {err}")))?;
+    aliases.prepare().map_err(|err| info_err!(format!("Can't read the epg aliases file: {aliases_file}: {err}")))?;
+    Ok(Some(aliases))
+}
+
+/// Reads the epg schedule file, a missing file is no error.
+pub fn read_epg_schedules(cfg: &Config) -> Result<Option<EpgSchedulesConfig>, TuliproxError> {
+    let schedules_file = cfg.t_epg_schedules_file_path.as_str();
+    let path = PathBuf::from(schedules_file);
+    if schedules_file.is_empty() || !path.is_file() {
+        return Ok(None);
+    }
+    let file = open_file(&path).map_err(|err| info_err!(format!("Can't read the epg schedules file: {schedules_file}: {err}")))?;
+    let mut schedules: EpgSchedulesConfig = serde_yaml::from_reader(config_file_reader(file, true))
+        .map_err(|err| info_err!(format!("Can't read the epg schedules file: {schedules_file}: {err}")))?;
+    schedules.prepare().map_err(|err| info_err!(format!("Can't read the epg schedules file: {schedules_file}: {err}")))?;
+    Ok(Some(schedules))
+}
+
 fn write_config_file<T>(file_path: &str, backup_dir: &str, config: &T, default_name: &str) -> Result<(), TuliproxError>
 where
     T: ?Sized + Serialize,
@@ -7,7 +7,7 @@ use std::{env, fs};
 
 use shared::error::str_to_io_error;
 use crate::utils::debug_if_enabled;
-use shared::utils::{API_PROXY_FILE, CONFIG_FILE, CONFIG_PATH, MAPPING_FILE, SOURCE_FILE, USER_FILE};
+use shared::utils::{API_PROXY_FILE, CONFIG_FILE, CONFIG_PATH, EPG_ALIASES_FILE, EPG_SCHEDULES_FILE, MAPPING_FILE, SOURCE_FILE, USER_FILE};
 use log::{debug, error};
 use path_clean::PathClean;
 
@@ -84,6 +84,16 @@ pub fn get_default_mappings_path(config_path: &str) -> String {
     get_default_file_path(config_path, MAPPING_FILE)
 }
 
+#[inline]
+pub fn get_default_epg_aliases_path(config_path: &str) -> String {
+    get_default_file_path(config_path, EPG_ALIASES_FILE)
+}
+
+#[inline]
+pub fn get_default_epg_schedules_path(config_path: &str) -> String {
+    get_default_file_path(config_path, EPG_SCHEDULES_FILE)
+}
+
 #[inline]
 pub fn get_default_api_proxy_config_path(config_path: &str) -> String {
     get_default_file_path(config_path, API_PROXY_FILE)
@@ -13,12 +13,13 @@ fn read_mapping(mapping_file: &Path, resolve_var: bool, prepare_mappings: bool)
         return match maybe_mapping {
             Ok(mut mapping) => {
                 if prepare_mappings {
-                    mapping.prepare()?;
+                    mapping.prepare(mapping_file.parent())
+                        .map_err(|err| info_err!(format!("{}: {}", mapping_file.display(), err.message)))?;
                 }
                 Ok(Some(mapping))
             }
             Err(err) => {
-                Err(info_err!(err.to_string()))
+                Err(info_err!(format!("{}: {err}", mapping_file.display())))
             }
         };
     }
@@ -62,7 +63,7 @@ fn merge_mappings(mappings: Vec<Mapping>) -> Vec<Mapping> {
 
     map.into_values().collect()
 }
-fn merge_mapping_definitions(mappings: Vec<Mappings>) -> Result<Option<Mappings>, TuliproxError> {
+fn merge_mapping_definitions(mappings: Vec<Mappings>, include_dir: &Path) -> Result<Option<Mappings>, TuliproxError> {
     let mut merged_templates: Vec<PatternTemplate> = Vec::new();
     let mut merged_mapping: Vec<Mapping> = Vec::new();
 
@@ -80,11 +81,12 @@ fn merge_mapping_definitions(mappings: Vec<Mappings>) -> Result<Option<Mappings>
             mapping: merge_mappings(merged_mapping)
         }
     };
-    result.prepare()?;
+    result.prepare(Some(include_dir))?;
     Ok(Some(result))
 }
 
-fn read_mappings_from_directory(path: &Path, resolve_env: bool) -> Result<Option<Mappings>, TuliproxError> {
+// the yml files of a mapping directory sorted by name
+fn list_mapping_files(path: &Path) -> Result<Vec<PathBuf>, TuliproxError> {
     let mut files = vec![];
     let mut visit = |entry: &std::fs::DirEntry, metadata: &std::fs::Metadata| {
         if metadata.is_file() {
@@ -97,20 +99,23 @@ fn read_mappings_from_directory(path: &Path, resolve_env: bool) -> Result<Option
     traverse_dir(path, &mut visit).map_err(|err| TuliproxError::new(TuliproxErrorKind::Info, format!("Failed to read mappings {err}")))?;
 
     files.sort_by(|a,b| a.file_name().cmp(&b.file_name()));
+    Ok(files)
+}
 
+fn read_mappings_from_directory(path: &Path, resolve_env: bool) -> Result<Option<Mappings>, TuliproxError> {
     let mut mappings = vec![];
-    for file_path in files {
+    for file_path in list_mapping_files(path)? {
         match read_mapping(&file_path, resolve_env, false) {
             Ok(Some(mapping)) => mappings.push(mapping),
             Ok(None) => {}
-            Err(err) => return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Failed to read mapping file {file_path:?}: {err:?}"),
+            Err(err) => return create_tuliprox_error_result!(TuliproxErrorKind::Info, "Failed to read mapping file {}: {}", file_path.display(), err.message),
         }
     }
 
     if mappings.is_empty() {
         return Ok(None);
     }
-    merge_mapping_definitions(mappings)
+    merge_mapping_definitions(mappings, path)
 }
 
 pub fn read_mappings(mappings_file: &str, resolve_env: bool) -> Result<Option<Mappings>, TuliproxError> {
@@ -129,4 +134,44 @@ pub fn read_mappings(mappings_file: &str, resolve_env: bool) -> Result<Option<Ma
             Ok(None)
         }
     }
-}
\ No newline at end of file
+}
+/// Reads the mapping file or the files of the mapping directory without preparing the mappings,
+/// the scripts and filters are not parsed.
+pub fn read_mapping_files(mappings_file: &str, resolve_env: bool) -> Result<Vec<(PathBuf, Mappings)>, TuliproxError> {
+    let path = PathBuf::from(mappings_file);
+    let files = if path.is_dir() { list_mapping_files(&path)? } else if path.is_file() { vec![path] } else { vec![] };
+    let mut result = vec![];
+    for file_path in files {
+        if let Some(mappings) = read_mapping(&file_path, resolve_env, false)? {
+            result.push((file_path, mappings));
+        }
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::read_mappings;
+
+    #[test]
+    fn test_read_invalid_mapping() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("mapping.yml");
+        std::fs::write(&path, r#"
+mappings:
+  mapping:
+    - id: sports
+      mapper:
+        - filter: 'Group ~ ".*"'
+          script: '@Caption = uppercase(@Caption)'
+        - filter: 'Group ~ ".*"'
+          script: "@Group = \"Sport\"\n@Caption = concat(@Caption"
+"#).unwrap();
+        let err = read_mappings(path.to_str().unwrap(), false).unwrap_err();
+        assert!(err.message.starts_with(&format!("{}: Mapping sports mapper 2: ", path.display())), "{}", err.message);
+        assert!(err.message.contains("line 2:27: expected operator\n  @Caption = concat(@Caption\n"), "{}", err.message);
+
+        std::fs::write(&path, "mappings:\n  mapping:\n    - id: sports\n      mapper:\n        - filter: 'Group ~ \".*\"'\n          script: '@Caption = uppercase(@Caption)'\n").unwrap();
+        assert!(read_mappings(path.to_str().unwrap(), false).unwrap().is_some());
+    }
+}
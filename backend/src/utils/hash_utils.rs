@@ -85,4 +85,139 @@ pub fn base64_to_u32(encoded: &str) -> Option<u32> {
         .as_slice()
         .try_into().ok()?;
     Some(u32::from_be_bytes(arr))
-}
\ No newline at end of file
+}
+
+// Message padding of md5 and sha1, the bit length is appended in the byte order of the algorithm.
+fn pad_message(bytes: &[u8], big_endian: bool) -> Vec<u8> {
+    let bit_len = (bytes.len() as u64).wrapping_mul(8);
+    let mut message = bytes.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&if big_endian { bit_len.to_be_bytes() } else { bit_len.to_le_bytes() });
+    message
+}
+
+/// MD5 digest (RFC 1321), only for ids and checksums, it is not secure.
+#[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss, clippy::many_single_char_names)]
+pub fn md5(bytes: &[u8]) -> [u8; 16] {
+    const SHIFTS: [u32; 16] = [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+    let constants: Vec<u32> = (1..=64).map(|i| (f64::from(i).sin().abs() * 4_294_967_296.0) as u32).collect();
+    let mut state: [u32; 4] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476];
+    for chunk in pad_message(bytes, false).chunks_exact(64) {
+        let words: Vec<u32> = chunk.chunks_exact(4).map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]])).collect();
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let rotated = a.wrapping_add(f).wrapping_add(constants[i]).wrapping_add(words[g])
+                .rotate_left(SHIFTS[(i / 16) * 4 + i % 4]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(rotated);
+        }
+        for (value, add) in state.iter_mut().zip([a, b, c, d]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+    let mut digest = [0u8; 16];
+    for (target, value) in digest.chunks_exact_mut(4).zip(state) {
+        target.copy_from_slice(&value.to_le_bytes());
+    }
+    digest
+}
+
+/// SHA-1 digest (RFC 3174), only for ids and checksums, it is not secure.
+#[allow(clippy::many_single_char_names)]
+pub fn sha1(bytes: &[u8]) -> [u8; 20] {
+    let mut state: [u32; 5] = [0x6745_2301, 0xefcd_ab89, 0x98ba_dcfe, 0x1032_5476, 0xc3d2_e1f0];
+    for chunk in pad_message(bytes, true).chunks_exact(64) {
+        let mut words = [0u32; 80];
+        for (word, w) in words.iter_mut().zip(chunk.chunks_exact(4)) {
+            *word = u32::from_be_bytes([w[0], w[1], w[2], w[3]]);
+        }
+        for i in 16..80 {
+            words[i] = (words[i - 3] ^ words[i - 8] ^ words[i - 14] ^ words[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = state;
+        for (i, word) in words.iter().enumerate() {
+            let (f, k) = match i / 20 {
+                0 => ((b & c) | (!b & d), 0x5a82_7999),
+                1 => (b ^ c ^ d, 0x6ed9_eba1),
+                2 => ((b & c) | (b & d) | (c & d), 0x8f1b_bcdc),
+                _ => (b ^ c ^ d, 0xca62_c1d6),
+            };
+            let temp = a.rotate_left(5).wrapping_add(f).wrapping_add(e).wrapping_add(k).wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (value, add) in state.iter_mut().zip([a, b, c, d, e]) {
+            *value = value.wrapping_add(add);
+        }
+    }
+    let mut digest = [0u8; 20];
+    for (target, value) in digest.chunks_exact_mut(4).zip(state) {
+        target.copy_from_slice(&value.to_be_bytes());
+    }
+    digest
+}
+
+// The predefined namespaces of RFC 4122 can be used by name.
+fn parse_uuid_namespace(namespace: &str) -> Option<[u8; 16]> {
+    let namespace = match namespace.trim().to_lowercase().as_str() {
+        "dns" => "6ba7b810-9dad-11d1-80b4-00c04fd430c8".to_string(),
+        "url" => "6ba7b811-9dad-11d1-80b4-00c04fd430c8".to_string(),
+        "oid" => "6ba7b812-9dad-11d1-80b4-00c04fd430c8".to_string(),
+        "x500" => "6ba7b814-9dad-11d1-80b4-00c04fd430c8".to_string(),
+        other => other.to_string(),
+    };
+    let hex: String = namespace.chars().filter(|c| *c != '-').collect();
+    if hex.len() != 32 {
+        return None;
+    }
+    hex_decode(&hex).ok().and_then(|bytes| bytes.try_into().ok())
+}
+
+/// Name based UUID version 5 (RFC 4122), the namespace is a UUID or one of `dns`, `url`, `oid` and `x500`.
+pub fn uuid_v5(namespace: &str, name: &str) -> Option<String> {
+    let namespace = parse_uuid_namespace(namespace)?;
+    let mut data = namespace.to_vec();
+    data.extend_from_slice(name.as_bytes());
+    let mut uuid = [0u8; 16];
+    uuid.copy_from_slice(&sha1(&data)[..16]);
+    uuid[6] = (uuid[6] & 0x0f) | 0x50;
+    uuid[8] = (uuid[8] & 0x3f) | 0x80;
+    let hex = hex_encode(&uuid).to_lowercase();
+    Some(format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{hex_encode, md5, sha1, uuid_v5};
+
+    #[test]
+    fn test_digests() {
+        assert_eq!(hex_encode(&md5(b"")), "D41D8CD98F00B204E9800998ECF8427E");
+        assert_eq!(hex_encode(&md5(b"The quick brown fox jumps over the lazy dog")), "9E107D9D372BB6826BD81D3542A419D6");
+        assert_eq!(hex_encode(&sha1(b"")), "DA39A3EE5E6B4B0D3255BFEF95601890AFD80709");
+        assert_eq!(hex_encode(&sha1(b"abc")), "A9993E364706816ABA3E25717850C26C9CD0D89D");
+        // 56 bytes, the length needs an extra block
+        assert_eq!(hex_encode(&sha1(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")), "84983E441C3BD26EBAAE4AA1F95129E5E54670F1");
+    }
+
+    #[test]
+    fn test_uuid_v5() {
+        assert_eq!(uuid_v5("dns", "python.org").as_deref(), Some("886313e1-3b8a-5372-9b90-0c9aee199e5d"));
+        assert_eq!(uuid_v5("6ba7b810-9dad-11d1-80b4-00c04fd430c8", "python.org"), uuid_v5("DNS", "python.org"));
+        assert_eq!(uuid_v5("no uuid", "python.org"), None);
+    }
+}
@@ -1,8 +1,13 @@
+use std::borrow::Cow;
+use std::collections::VecDeque;
 use std::fs::File;
+use std::sync::{LazyLock, Mutex};
 use env_logger::{Builder, Target};
-use log::{error, info, LevelFilter};
+use log::{error, info, Level, LevelFilter, Log, Metadata, Record};
+use tokio::sync::broadcast;
 use crate::model::LogLevelConfig;
 use crate::utils::config_file_reader;
+use crate::utils::request::apply_sanitize_rules;
 
 const LOG_ERROR_LEVEL_MOD: &[&str] = &[
     "reqwest::async_impl::client",
@@ -10,6 +15,104 @@ const LOG_ERROR_LEVEL_MOD: &[&str] = &[
     "hyper_util::client",
 ];
 
+const LOG_BUFFER_SIZE: usize = 2000;
+const LOG_CHANNEL_SIZE: usize = 256;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct LogEntry {
+    // unix timestamp in milliseconds
+    pub ts: i64,
+    #[serde(serialize_with = "serialize_level")]
+    pub level: Level,
+    pub module: String,
+    pub message: String,
+}
+
+// serde passes the field by reference
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn serialize_level<S: serde::Serializer>(level: &Level, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(level.as_str())
+}
+
+// The last log entries are kept in memory, new entries are also sent to subscribers for the live tail.
+pub struct LogBuffer {
+    entries: Mutex<VecDeque<LogEntry>>,
+    sender: broadcast::Sender<LogEntry>,
+}
+
+impl LogBuffer {
+    fn new() -> Self {
+        Self {
+            entries: Mutex::new(VecDeque::with_capacity(LOG_BUFFER_SIZE)),
+            sender: broadcast::channel(LOG_CHANNEL_SIZE).0,
+        }
+    }
+
+    // the entry is sent while the lock is held, a subscriber gets every entry either from the buffer or the channel
+    fn push(&self, entry: LogEntry) {
+        if let Ok(mut entries) = self.entries.lock() {
+            if entries.len() == LOG_BUFFER_SIZE {
+                entries.pop_front();
+            }
+            entries.push_back(entry.clone());
+            // sending only fails without subscribers
+            let _ = self.sender.send(entry);
+        }
+    }
+
+    pub fn get_entries<F: Fn(&LogEntry) -> bool>(&self, filter: F) -> Vec<LogEntry> {
+        self.entries.lock().map(|entries| entries.iter().filter(|entry| filter(entry)).cloned().collect()).unwrap_or_default()
+    }
+
+    pub fn subscribe<F: Fn(&LogEntry) -> bool>(&self, filter: F) -> (Vec<LogEntry>, broadcast::Receiver<LogEntry>) {
+        match self.entries.lock() {
+            Ok(entries) => (entries.iter().filter(|entry| filter(entry)).cloned().collect(), self.sender.subscribe()),
+            Err(_) => (vec![], self.sender.subscribe()),
+        }
+    }
+}
+
+pub static LOG_BUFFER: LazyLock<LogBuffer> = LazyLock::new(LogBuffer::new);
+
+struct BufferedLogger {
+    inner: env_logger::Logger,
+}
+
+impl Log for BufferedLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.inner.matches(record) {
+            let message = record.args().to_string();
+            // the custom redaction rules apply to every log message
+            let message = if let Cow::Owned(redacted) = apply_sanitize_rules(&message) {
+                self.inner.log(&Record::builder()
+                    .args(format_args!("{redacted}"))
+                    .metadata(record.metadata().clone())
+                    .module_path(record.module_path())
+                    .file(record.file())
+                    .line(record.line())
+                    .build());
+                redacted
+            } else {
+                self.inner.log(record);
+                message
+            };
+            LOG_BUFFER.push(LogEntry {
+                ts: chrono::Utc::now().timestamp_millis(),
+                level: record.level(),
+                module: record.target().to_string(),
+                message,
+            });
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
 
 fn get_log_level(log_level: &str) -> LevelFilter {
     match log_level.to_lowercase().as_str() {
@@ -66,6 +169,10 @@ pub fn init_logger(user_log_level: Option<&String>, config_file: &str) {
     for module in LOG_ERROR_LEVEL_MOD {
         log_builder.filter_module(module, LevelFilter::Error);
     }
-    log_builder.init();
+    let logger = log_builder.build();
+    let max_level = logger.filter();
+    if log::set_boxed_logger(Box::new(BufferedLogger { inner: logger })).is_ok() {
+        log::set_max_level(max_level);
+    }
     info!("Log Level {}", &log_levels.join(", "));
 }
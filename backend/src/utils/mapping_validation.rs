@@ -0,0 +1,150 @@
+use crate::foundation::filter::{apply_templates_to_pattern_single, get_filter, prepare_templates, PatternTemplate};
+use crate::foundation::mapper::MapperScript;
+use crate::model::{Config, Mapping};
+use crate::utils::{config_file_reader, open_file, read_mapping_files, read_sources_file};
+use shared::error::TuliproxError;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// The errors and warnings of the mapper scripts and filters referenced by the targets.
+#[derive(Debug, Default)]
+pub struct MappingValidation {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl MappingValidation {
+    fn check_filter(&mut self, name: &str, filter: &str, templates: Option<&Vec<PatternTemplate>>) {
+        if let Err(err) = get_filter(filter, templates) {
+            self.errors.push(format!("{name} filter: {}", err.message));
+        }
+    }
+
+    fn check_templates(&mut self, name: &str, mut templates: Vec<PatternTemplate>) -> Option<Vec<PatternTemplate>> {
+        if templates.is_empty() {
+            return None;
+        }
+        match prepare_templates(&mut templates) {
+            Ok(prepared) => Some(prepared),
+            Err(err) => {
+                self.errors.push(format!("{name} templates: {}", err.message));
+                None
+            }
+        }
+    }
+
+    fn check_mapping(&mut self, file: &Path, mapping: &Mapping, templates: Option<&Vec<PatternTemplate>>, include_dir: Option<&Path>) {
+        for (index, mapper) in mapping.mapper.iter().flatten().enumerate() {
+            let name = format!("{}: Mapping {} mapper {}", file.display(), mapping.id, index + 1);
+            self.check_filter(&name, &mapper.filter, templates);
+            let script = match templates {
+                Some(_) => apply_templates_to_pattern_single(&mapper.script, templates),
+                None => Ok(mapper.script.clone()),
+            };
+            match script.and_then(|script| MapperScript::parse_with_includes(&script, templates, include_dir)) {
+                Ok(script) => self.warnings.extend(script.warnings().into_iter().map(|warning| format!("{name} script: {warning}"))),
+                Err(err) => self.errors.push(format!("{name} script: {}", err.message)),
+            }
+        }
+        for (index, counter) in mapping.counter.iter().flatten().enumerate() {
+            self.check_filter(&format!("{}: Mapping {} counter {}", file.display(), mapping.id, index + 1), &counter.filter, templates);
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// The `mapping_path` of the config file overrides the mapping file like when the config is read.
+pub fn get_validation_mapping_path(config_file: &str, mappings_file: String) -> String {
+    open_file(&PathBuf::from(config_file)).ok()
+        .and_then(|file| serde_yaml::from_reader::<_, Config>(config_file_reader(file, true)).ok())
+        .and_then(|config| config.mapping_path)
+        .unwrap_or(mappings_file)
+}
+
+/// Parses the filters of the targets and the global rules, and the mapper scripts and filters of the mappings
+/// referenced by them. All errors are collected, the mappings are not prepared.
+pub fn validate_mappings(sources_file: &str, mappings_file: &str) -> Result<MappingValidation, TuliproxError> {
+    let sources = read_sources_file(sources_file, true)?;
+    let mapping_files = read_mapping_files(mappings_file, true)?;
+    let mut validation = MappingValidation::default();
+
+    let source_templates = validation.check_templates("Sources", sources.templates.clone().unwrap_or_default());
+    // the referenced mapping ids with the name of the referencing target
+    let mut references = vec![];
+    if let Some(global) = sources.global.as_ref() {
+        if let Some(filter) = global.filter.as_ref() {
+            validation.check_filter("Global", filter, source_templates.as_ref());
+        }
+        references.extend(global.mapping.iter().flatten().map(|id| (id.as_str(), "Global".to_string())));
+    }
+    for target in sources.sources.iter().flat_map(|source| &source.targets) {
+        let name = format!("Target {}", target.name);
+        validation.check_filter(&name, &target.filter, source_templates.as_ref());
+        references.extend(target.mapping.iter().flatten().map(|id| (id.as_str(), name.clone())));
+    }
+
+    let defined: HashSet<&str> = mapping_files.iter().flat_map(|(_, mappings)| &mappings.mappings.mapping).map(|mapping| mapping.id.as_str()).collect();
+    for (id, name) in &references {
+        if !defined.contains(id) {
+            validation.errors.push(format!("{name}: Mapping {id} not found"));
+        }
+    }
+
+    // the templates of all files of a mapping directory are merged
+    let mapping_templates = validation.check_templates("Mapping",
+        mapping_files.iter().filter_map(|(_, mappings)| mappings.mappings.templates.clone()).flatten().collect());
+    let include_dir = if Path::new(mappings_file).is_dir() { Some(PathBuf::from(mappings_file)) } else { Path::new(mappings_file).parent().map(Path::to_path_buf) };
+    let mapping_ids: HashSet<&str> = references.iter().map(|(id, _)| *id).collect();
+    for (file, mappings) in &mapping_files {
+        for mapping in mappings.mappings.mapping.iter().filter(|mapping| mapping_ids.contains(mapping.id.as_str())) {
+            validation.check_mapping(file, mapping, mapping_templates.as_ref(), include_dir.as_deref());
+        }
+    }
+    Ok(validation)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::validate_mappings;
+
+    #[test]
+    fn test_validate_mappings() {
+        let dir = tempfile::tempdir().unwrap();
+        let sources = dir.path().join("source.yml");
+        let mapping = dir.path().join("mapping.yml");
+        std::fs::write(&sources, r#"
+sources:
+  - inputs:
+      - name: input
+        url: http://localhost/list.m3u
+    targets:
+      - name: all
+        filter: 'Group ~ ".*" AND'
+        mapping: [sports, news]
+"#).unwrap();
+        std::fs::write(&mapping, r#"
+mappings:
+  mapping:
+    - id: sports
+      mapper:
+        - filter: 'Group ~ ".*"'
+          script: "@Caption = uppercase(@Caption)\n@Group = concat(x, \"a\")"
+        - filter: 'Group ~ ".*"'
+          script: '@"tvg-foo" = @Caption'
+    - id: unused
+      mapper:
+        - filter: 'Group ~ ".*"'
+          script: "@Caption = nope("
+"#).unwrap();
+        let validation = validate_mappings(sources.to_str().unwrap(), mapping.to_str().unwrap()).unwrap();
+        assert_eq!(validation.errors.len(), 3, "{:?}", validation.errors);
+        assert!(validation.errors[0].starts_with("Target all filter: "));
+        assert_eq!(validation.errors[1], "Target all: Mapping news not found");
+        assert_eq!(validation.errors[2], format!("{}: Mapping sports mapper 1 script: line 2:1: Identifier unknown x", mapping.display()));
+        assert_eq!(validation.warnings, vec![format!("{}: Mapping sports mapper 2 script: line 1:1: Unknown field @tvg-foo, it is handled as extended attribute", mapping.display())]);
+        assert!(!validation.is_valid());
+    }
+}
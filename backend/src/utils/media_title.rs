@@ -0,0 +1,133 @@
+use deunicode::deunicode;
+use shared::utils::CONSTANTS;
+use std::borrow::Cow;
+use std::fmt::Write;
+
+// Language codes which are not part of the country codes.
+const LANGUAGE_CODES: &[&str] = &[
+    "en", "ar", "ja", "ko", "zh", "hi", "sv", "da", "cs", "el", "he", "fa", "ur", "uk",
+    "eng", "ger", "deu", "fre", "fra", "spa", "esp", "ita", "por", "rus", "tur", "pol",
+    "ara", "nld", "dut", "swe", "nor", "dan", "fin", "hin", "jpn", "kor", "chi", "lat",
+];
+
+fn is_language_code(code: &str) -> bool {
+    let code = code.to_lowercase();
+    CONSTANTS.country_codes.contains(code.as_str()) || LANGUAGE_CODES.contains(&code.as_str())
+}
+
+/// Structured information extracted from a vod or series title.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MediaTitle {
+    /// Title without language prefix, quality tags, year and season/episode.
+    pub title: String,
+    pub year: Option<u32>,
+    pub season: Option<u32>,
+    pub episode: Option<u32>,
+    pub quality: Option<String>,
+    pub language: Option<String>,
+}
+
+impl MediaTitle {
+    /// Returns a key to identify the same movie or episode across providers.
+    pub fn dedup_key(&self) -> String {
+        let mut key: String = deunicode(&self.title).chars()
+            .filter(|c| c.is_alphanumeric())
+            .map(|c| c.to_ascii_lowercase())
+            .collect();
+        if let Some(year) = self.year {
+            let _ = write!(key, "|{year}");
+        }
+        if let (Some(season), Some(episode)) = (self.season, self.episode) {
+            let _ = write!(key, "|s{season}e{episode}");
+        }
+        key
+    }
+}
+
+fn clean_separators(text: &str) -> String {
+    let text = CONSTANTS.re_title_brackets.replace_all(text, " ");
+    let text = CONSTANTS.re_whitespace.replace_all(&text, " ");
+    text.trim_matches(|c: char| c.is_whitespace() || matches!(c, '-' | '_' | '.' | '|' | ':' | ',')).to_string()
+}
+
+/// Normalizes a vod or series title.
+/// Strips language prefixes and quality tags, extracts the year and season/episode.
+/// Scene style titles like `The.Matrix.1999.1080p` are split at dots and underscores.
+pub fn normalize_media_title(title: &str) -> MediaTitle {
+    let mut result = MediaTitle::default();
+    let mut text: Cow<str> = Cow::Borrowed(title.trim());
+    if !text.contains(' ') {
+        text = Cow::Owned(text.replace(['.', '_'], " "));
+    }
+
+    if let Some(caps) = CONSTANTS.re_title_language_prefix.captures(&text) {
+        if let (Some(full), Some(code)) = (caps.get(0), caps.get(1)) {
+            if is_language_code(code.as_str()) && full.end() < text.len() {
+                result.language = Some(code.as_str().to_uppercase());
+                text = Cow::Owned(text[full.end()..].to_string());
+            }
+        }
+    }
+
+    if let Some(caps) = CONSTANTS.re_title_season_episode.captures(&text) {
+        let season = caps.get(1).or_else(|| caps.get(3)).and_then(|m| m.as_str().parse::<u32>().ok());
+        let episode = caps.get(2).or_else(|| caps.get(4)).and_then(|m| m.as_str().parse::<u32>().ok());
+        if let (Some(season), Some(episode), Some(full)) = (season, episode, caps.get(0)) {
+            result.season = Some(season);
+            result.episode = Some(episode);
+            // Everything after the episode marker is the episode title.
+            text = Cow::Owned(text[..full.start()].to_string());
+        }
+    }
+
+    if let Some(quality) = CONSTANTS.re_quality.find(&text) {
+        result.quality = Some(quality.as_str().to_uppercase());
+    }
+    text = Cow::Owned(CONSTANTS.re_quality.replace_all(&text, " ").to_string());
+
+    // The last year is used, a title which only consists of a year like `1917` is kept.
+    if let Some(caps) = CONSTANTS.re_title_year.captures_iter(&text).filter(|c| c.get(0).is_some_and(|m| m.start() > 0)).last() {
+        if let (Some(full), Some(year)) = (caps.get(0), caps.get(1)) {
+            result.year = year.as_str().parse::<u32>().ok();
+            text = Cow::Owned(format!("{} {}", &text[..full.start()], &text[full.end()..]));
+        }
+    }
+
+    result.title = clean_separators(&text);
+    if result.title.is_empty() {
+        result.title = clean_separators(title);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::normalize_media_title;
+
+    #[test]
+    fn test_normalize_media_title() {
+        let movie = normalize_media_title("DE: The Matrix (1999) [4K]");
+        assert_eq!(movie.title, "The Matrix");
+        assert_eq!(movie.year, Some(1999));
+        assert_eq!(movie.quality.as_deref(), Some("4K"));
+        assert_eq!(movie.language.as_deref(), Some("DE"));
+
+        let scene = normalize_media_title("The.Matrix.1999.1080p");
+        assert_eq!(scene.title, "The Matrix");
+        assert_eq!(scene.year, Some(1999));
+        assert_eq!(movie.dedup_key(), scene.dedup_key());
+
+        let episode = normalize_media_title("|EN| Breaking Bad S02E05 - Breakage");
+        assert_eq!(episode.title, "Breaking Bad");
+        assert_eq!((episode.season, episode.episode), (Some(2), Some(5)));
+        assert_eq!(episode.language.as_deref(), Some("EN"));
+
+        let year_title = normalize_media_title("1917 (2019)");
+        assert_eq!(year_title.title, "1917");
+        assert_eq!(year_title.year, Some(2019));
+
+        let plain = normalize_media_title("Up - Pixar Classics");
+        assert_eq!(plain.title, "Up - Pixar Classics");
+        assert_eq!(plain.language, None);
+    }
+}
@@ -7,13 +7,29 @@ mod network;
 mod bincode_utils;
 mod crypto_utils;
 mod step_measure;
+mod refresh_progress;
 mod logging;
 mod trakt;
 mod serde_utils;
+mod media_title;
+mod channel_classifier;
+mod time_utils;
+mod qr_code;
+mod debug_bundle;
+mod mapping_validation;
+mod crash_report;
+pub mod mpegts_muxer;
 
 pub use self::logging::*;
 pub use self::trakt::*;
 pub use self::serde_utils::*;
+pub use self::media_title::*;
+pub use self::channel_classifier::*;
+pub use self::time_utils::*;
+pub use self::qr_code::*;
+pub use self::debug_bundle::*;
+pub use self::mapping_validation::*;
+pub use self::crash_report::*;
 
 
 #[macro_export]
@@ -71,3 +87,4 @@ pub use self::network::*;
 pub use self::bincode_utils::*;
 pub use self::crypto_utils::*;
 pub use self::step_measure::*;
+pub use self::refresh_progress::*;
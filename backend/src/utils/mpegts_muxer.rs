@@ -0,0 +1,218 @@
+// Minimal MPEG-TS muxer for one program with a video and an optional audio elementary stream.
+// The timestamps are given in 90kHz units.
+
+const TS_PACKET_SIZE: usize = 188;
+const TS_HEADER_SIZE: usize = 4;
+const TS_PAYLOAD_SIZE: usize = TS_PACKET_SIZE - TS_HEADER_SIZE;
+const SYNC_BYTE: u8 = 0x47;
+const PAT_PID: u16 = 0;
+const PMT_PID: u16 = 0x1000;
+const VIDEO_PID: u16 = 0x100;
+const AUDIO_PID: u16 = 0x101;
+const PROGRAM_NUMBER: u16 = 1;
+const TRANSPORT_STREAM_ID: u16 = 1;
+const VIDEO_STREAM_ID: u8 = 0xE0;
+const AUDIO_STREAM_ID: u8 = 0xC0;
+const MAX_PTS: u64 = 1 << 33;
+// length byte, flags and 6 bytes pcr
+const PCR_ADAPTATION_SIZE: usize = 8;
+// the pcr runs ahead of the presentation time, clients buffer this delay
+const PCR_DELAY: u64 = 9000;
+const AUD_H264: &[u8] = &[0, 0, 0, 1, 0x09, 0xF0];
+const AUD_H265: &[u8] = &[0, 0, 0, 1, 0x46, 0x01, 0x50];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TsStreamType {
+    H264,
+    H265,
+    Aac,
+}
+
+impl TsStreamType {
+    const fn value(self) -> u8 {
+        match self {
+            Self::H264 => 0x1B,
+            Self::H265 => 0x24,
+            Self::Aac => 0x0F,
+        }
+    }
+}
+
+fn crc32_mpeg2(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFF_u32;
+    for &byte in data {
+        crc ^= u32::from(byte) << 24;
+        for _ in 0..8 {
+            crc = if crc & 0x8000_0000 == 0 { crc << 1 } else { (crc << 1) ^ 0x04C1_1DB7 };
+        }
+    }
+    crc
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn encode_pts(pts: u64) -> [u8; 5] {
+    [
+        0x20 | (((pts >> 30) & 0x07) as u8) << 1 | 1,
+        (pts >> 22) as u8,
+        (((pts >> 15) & 0x7F) as u8) << 1 | 1,
+        (pts >> 7) as u8,
+        ((pts & 0x7F) as u8) << 1 | 1,
+    ]
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn encode_pcr(pcr_base: u64) -> [u8; 6] {
+    [
+        (pcr_base >> 25) as u8,
+        (pcr_base >> 17) as u8,
+        (pcr_base >> 9) as u8,
+        (pcr_base >> 1) as u8,
+        (((pcr_base & 1) as u8) << 7) | 0x7E,
+        0,
+    ]
+}
+
+pub struct TsMuxer {
+    video: TsStreamType,
+    audio: Option<TsStreamType>,
+    pat_cc: u8,
+    pmt_cc: u8,
+    video_cc: u8,
+    audio_cc: u8,
+}
+
+impl TsMuxer {
+    pub const fn new(video: TsStreamType, audio: Option<TsStreamType>) -> Self {
+        Self { video, audio, pat_cc: 0, pmt_cc: 0, video_cc: 0, audio_cc: 0 }
+    }
+
+    fn write_section(out: &mut Vec<u8>, pid: u16, cc: &mut u8, section: &[u8]) {
+        out.push(SYNC_BYTE);
+        out.extend_from_slice(&(0x4000 | pid).to_be_bytes());
+        out.push(0x10 | *cc);
+        *cc = (*cc + 1) & 0x0F;
+        // pointer field
+        out.push(0);
+        out.extend_from_slice(section);
+        out.extend_from_slice(&crc32_mpeg2(section).to_be_bytes());
+        out.resize(out.len() + TS_PAYLOAD_SIZE - 1 - section.len() - 4, 0xFF);
+    }
+
+    /// Writes PAT and PMT, they are repeated before each key frame so clients can join at any key frame.
+    pub fn write_tables(&mut self, out: &mut Vec<u8>) {
+        let mut pat = vec![0x00, 0xB0, 13];
+        pat.extend_from_slice(&TRANSPORT_STREAM_ID.to_be_bytes());
+        pat.extend_from_slice(&[0xC1, 0, 0]);
+        pat.extend_from_slice(&PROGRAM_NUMBER.to_be_bytes());
+        pat.extend_from_slice(&(0xE000 | PMT_PID).to_be_bytes());
+        Self::write_section(out, PAT_PID, &mut self.pat_cc, &pat);
+
+        let streams: Vec<(TsStreamType, u16)> = std::iter::once((self.video, VIDEO_PID))
+            .chain(self.audio.map(|audio| (audio, AUDIO_PID))).collect();
+        let section_length = 13 + 5 * streams.len();
+        #[allow(clippy::cast_possible_truncation)]
+        let mut pmt = vec![0x02, 0xB0, section_length as u8];
+        pmt.extend_from_slice(&PROGRAM_NUMBER.to_be_bytes());
+        pmt.extend_from_slice(&[0xC1, 0, 0]);
+        pmt.extend_from_slice(&(0xE000 | VIDEO_PID).to_be_bytes());
+        pmt.extend_from_slice(&[0xF0, 0]);
+        for (stream_type, pid) in streams {
+            pmt.push(stream_type.value());
+            pmt.extend_from_slice(&(0xE000 | pid).to_be_bytes());
+            pmt.extend_from_slice(&[0xF0, 0]);
+        }
+        Self::write_section(out, PMT_PID, &mut self.pmt_cc, &pmt);
+    }
+
+    /// Writes an access unit in annex b format, the tables are written before key frames.
+    pub fn write_video(&mut self, out: &mut Vec<u8>, pts: u64, key_frame: bool, access_unit: &[u8]) {
+        if key_frame {
+            self.write_tables(out);
+        }
+        let aud = if self.video == TsStreamType::H265 { AUD_H265 } else { AUD_H264 };
+        let pes = Self::create_pes(VIDEO_STREAM_ID, pts, &[aud, access_unit], false);
+        let pcr = (pts + MAX_PTS - PCR_DELAY) % MAX_PTS;
+        let mut cc = self.video_cc;
+        Self::write_pes(out, VIDEO_PID, &mut cc, &pes, Some(pcr), key_frame);
+        self.video_cc = cc;
+    }
+
+    /// Writes an audio frame with adts header.
+    pub fn write_audio(&mut self, out: &mut Vec<u8>, pts: u64, frame: &[u8]) {
+        let pes = Self::create_pes(AUDIO_STREAM_ID, pts, &[frame], true);
+        let mut cc = self.audio_cc;
+        Self::write_pes(out, AUDIO_PID, &mut cc, &pes, None, false);
+        self.audio_cc = cc;
+    }
+
+    fn create_pes(stream_id: u8, pts: u64, payload: &[&[u8]], with_length: bool) -> Vec<u8> {
+        let payload_len: usize = payload.iter().map(|part| part.len()).sum();
+        let mut pes = Vec::with_capacity(14 + payload_len);
+        pes.extend_from_slice(&[0, 0, 1, stream_id]);
+        // the length is unbounded for video
+        let pes_len = if with_length { u16::try_from(payload_len + 8).unwrap_or(0) } else { 0 };
+        pes.extend_from_slice(&pes_len.to_be_bytes());
+        pes.extend_from_slice(&[0x80, 0x80, 5]);
+        pes.extend_from_slice(&encode_pts(pts % MAX_PTS));
+        for part in payload {
+            pes.extend_from_slice(part);
+        }
+        pes
+    }
+
+    fn write_pes(out: &mut Vec<u8>, pid: u16, cc: &mut u8, pes: &[u8], pcr: Option<u64>, random_access: bool) {
+        let mut offset = 0;
+        while offset < pes.len() {
+            let first = offset == 0;
+            let pcr = pcr.filter(|_| first);
+            let min_adaptation = if pcr.is_some() { PCR_ADAPTATION_SIZE } else { 0 };
+            let payload_len = (pes.len() - offset).min(TS_PAYLOAD_SIZE - min_adaptation);
+            let adaptation_len = TS_PAYLOAD_SIZE - payload_len;
+
+            out.push(SYNC_BYTE);
+            out.extend_from_slice(&(if first { 0x4000 } else { 0 } | pid).to_be_bytes());
+            out.push(if adaptation_len > 0 { 0x30 } else { 0x10 } | *cc);
+            *cc = (*cc + 1) & 0x0F;
+            if adaptation_len > 0 {
+                let start = out.len();
+                #[allow(clippy::cast_possible_truncation)]
+                out.push((adaptation_len - 1) as u8);
+                if adaptation_len > 1 {
+                    let random_access_flag = if random_access && first { 0x40 } else { 0 };
+                    out.push(if pcr.is_some() { 0x10 } else { 0 } | random_access_flag);
+                    if let Some(pcr) = pcr {
+                        out.extend_from_slice(&encode_pcr(pcr));
+                    }
+                    out.resize(start + adaptation_len, 0xFF);
+                }
+            }
+            out.extend_from_slice(&pes[offset..offset + payload_len]);
+            offset += payload_len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TsMuxer, TsStreamType, TS_PACKET_SIZE};
+
+    #[test]
+    fn test_ts_muxer() {
+        let mut muxer = TsMuxer::new(TsStreamType::H264, Some(TsStreamType::Aac));
+        let mut out = Vec::new();
+        muxer.write_tables(&mut out);
+        assert_eq!(out.len(), 2 * TS_PACKET_SIZE);
+        // the well known pat of a single program with the pmt on pid 0x1000
+        assert_eq!(&out[..21], &[0x47, 0x40, 0x00, 0x10, 0x00, 0x00, 0xB0, 0x0D, 0x00, 0x01, 0xC1, 0x00, 0x00, 0x00, 0x01, 0xF0, 0x00, 0x2A, 0xB1, 0x04, 0xB2]);
+
+        out.clear();
+        muxer.write_video(&mut out, 90_000, true, &[0, 0, 0, 1, 0x65, 1, 2, 3]);
+        muxer.write_audio(&mut out, 90_000, &vec![0xAA; 400]);
+        assert_eq!(out.len() % TS_PACKET_SIZE, 0);
+        assert!(out.chunks(TS_PACKET_SIZE).all(|packet| packet[0] == 0x47));
+        // video pes with random access and pcr after the tables
+        let video = &out[2 * TS_PACKET_SIZE..];
+        assert_eq!(&video[1..3], &[0x41, 0x00]);
+        assert_eq!(video[5] & 0x50, 0x50);
+    }
+}
@@ -0,0 +1,160 @@
+use chrono::{DateTime, Utc};
+use reqwest::header::{HeaderMap, HeaderValue, SET_COOKIE};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use url::Url;
+
+#[derive(Debug, Clone)]
+struct ProviderCookie {
+    name: String,
+    value: String,
+    // the host the cookie was received from or the domain attribute
+    domain: String,
+    include_subdomains: bool,
+    path: String,
+    secure: bool,
+    expires: Option<Instant>,
+}
+
+impl ProviderCookie {
+    /// Parses a `Set-Cookie` header, returns the cookie and whether it is already expired.
+    fn parse(url: &Url, set_cookie: &str, now: Instant) -> Option<(Self, bool)> {
+        let mut parts = set_cookie.split(';');
+        let (name, value) = parts.next()?.split_once('=')?;
+        let name = name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        let mut cookie = Self {
+            name: name.to_string(),
+            value: value.trim().trim_matches('"').to_string(),
+            domain: url.host_str()?.to_lowercase(),
+            include_subdomains: false,
+            path: "/".to_string(),
+            secure: false,
+            expires: None,
+        };
+        let mut expired = false;
+        let mut max_age = None;
+        for attribute in parts {
+            let (key, attr_value) = attribute.split_once('=').map_or((attribute.trim(), ""), |(key, value)| (key.trim(), value.trim()));
+            match key.to_lowercase().as_str() {
+                "domain" if !attr_value.is_empty() => {
+                    let domain = attr_value.trim_start_matches('.').to_lowercase();
+                    // a provider can only set cookies for its own domain
+                    if cookie.domain != domain && !cookie.domain.ends_with(&format!(".{domain}")) {
+                        return None;
+                    }
+                    cookie.domain = domain;
+                    cookie.include_subdomains = true;
+                }
+                "path" if attr_value.starts_with('/') => cookie.path = attr_value.to_string(),
+                "secure" => cookie.secure = true,
+                "max-age" => max_age = attr_value.parse::<i64>().ok(),
+                "expires" => {
+                    if let Ok(expires) = DateTime::parse_from_rfc2822(attr_value) {
+                        let seconds = (expires.with_timezone(&Utc) - Utc::now()).num_seconds();
+                        expired = seconds <= 0;
+                        cookie.expires = u64::try_from(seconds).ok().map(|secs| now + Duration::from_secs(secs));
+                    }
+                }
+                _ => {}
+            }
+        }
+        // max-age has precedence over expires
+        if let Some(seconds) = max_age {
+            expired = seconds <= 0;
+            cookie.expires = u64::try_from(seconds).ok().map(|secs| now + Duration::from_secs(secs));
+        }
+        Some((cookie, expired))
+    }
+
+    fn is_same(&self, other: &Self) -> bool {
+        self.name == other.name && self.domain == other.domain && self.path == other.path
+    }
+
+    fn matches(&self, url: &Url, now: Instant) -> bool {
+        let host = url.host_str().unwrap_or_default().to_lowercase();
+        (host == self.domain || (self.include_subdomains && host.ends_with(&format!(".{}", self.domain))))
+            && url.path().starts_with(&self.path)
+            && (!self.secure || url.scheme() == "https")
+            && self.expires.is_none_or(|expires| expires > now)
+    }
+}
+
+/// Keeps the session cookies a provider sets on stream responses and sends them with the following stream requests.
+/// Each provider connection (input or alias) has its own jar.
+#[derive(Debug, Default)]
+pub struct ProviderCookieJar {
+    cookies: Mutex<Vec<ProviderCookie>>,
+}
+
+impl ProviderCookieJar {
+    /// Stores the `Set-Cookie` headers of a provider response, expired cookies are removed.
+    pub fn store(&self, url: &Url, headers: &HeaderMap) {
+        let now = Instant::now();
+        let mut cookies = self.cookies.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        for (cookie, expired) in headers.get_all(SET_COOKIE).iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| ProviderCookie::parse(url, value, now)) {
+            cookies.retain(|existing| !existing.is_same(&cookie));
+            if !expired {
+                cookies.push(cookie);
+            }
+        }
+        cookies.retain(|cookie| cookie.expires.is_none_or(|expires| expires > now));
+    }
+
+    /// The `Cookie` header value for a request to the `url`, `None` if no cookie matches.
+    pub fn get_cookie_header(&self, url: &Url) -> Option<HeaderValue> {
+        let now = Instant::now();
+        let cookies = self.cookies.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let value = cookies.iter()
+            .filter(|cookie| cookie.matches(url, now))
+            .map(|cookie| format!("{}={}", cookie.name, cookie.value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if value.is_empty() { None } else { HeaderValue::from_str(&value).ok() }
+    }
+
+    /// Removes all cookies, returns `false` if the jar was empty.
+    pub fn clear(&self) -> bool {
+        let mut cookies = self.cookies.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let had_cookies = !cookies.is_empty();
+        cookies.clear();
+        had_cookies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append(SET_COOKIE, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn test_cookie_jar() {
+        let jar = ProviderCookieJar::default();
+        let url = Url::parse("http://cdn.provider.tv/live/user/pass/1.ts").unwrap();
+        jar.store(&url, &headers(&["session=abc; Path=/; HttpOnly", "lb=2; Domain=.provider.tv; Max-Age=3600",
+            "secure=1; Secure", "other=1; Domain=evil.tv", "gone=1; Max-Age=0"]));
+        assert_eq!(jar.get_cookie_header(&url).unwrap(), "session=abc; lb=2");
+        assert_eq!(jar.get_cookie_header(&Url::parse("http://www.provider.tv/").unwrap()).unwrap(), "lb=2");
+        assert!(jar.get_cookie_header(&Url::parse("http://other.tv/").unwrap()).is_none());
+        assert_eq!(jar.get_cookie_header(&Url::parse("https://cdn.provider.tv/").unwrap()).unwrap(), "session=abc; lb=2; secure=1");
+
+        // a new value replaces the cookie, an expired cookie removes it
+        jar.store(&url, &headers(&["session=def", "lb=; Domain=provider.tv; Expires=Thu, 01 Jan 1970 00:00:00 GMT"]));
+        assert_eq!(jar.get_cookie_header(&url).unwrap(), "session=def");
+
+        assert!(jar.clear());
+        assert!(!jar.clear());
+        assert!(jar.get_cookie_header(&url).is_none());
+    }
+}
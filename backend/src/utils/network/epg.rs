@@ -1,22 +1,62 @@
-use shared::error::TuliproxError;
+use shared::error::{notify_err, TuliproxError, TuliproxErrorKind};
 use crate::model::{Config, ConfigInput, PersistedEpgSource};
 use crate::model::TVGuide;
 use crate::utils::{add_prefix_to_filename, cleanup_unlisted_files_with_suffix, prepare_file_path, short_hash};
 use crate::utils::request;
-use log::debug;
+use log::{debug, info, warn};
+use futures::future::join_all;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 use crate::utils::request::sanitize_sensitive_info;
 
-async fn download_epg_file(url: &str, client: &Arc<reqwest::Client>, input: &ConfigInput, working_dir: &str) -> Result<PathBuf, TuliproxError> {
+// the file name is derived from `source_url`, an epg downloaded over an alias replaces the file of the input
+async fn download_epg_file(url: &str, source_url: &str, client: &Arc<reqwest::Client>, input: &ConfigInput, working_dir: &str) -> Result<PathBuf, TuliproxError> {
     debug!("Getting epg file path for url: {}", sanitize_sensitive_info(url));
-    let file_prefix = short_hash(url);
+    let file_prefix = short_hash(source_url);
     let persist_file_path = prepare_file_path(input.persist.as_deref(), working_dir, "")
         .map(|path| add_prefix_to_filename(&path, format!("{file_prefix}_epg_").as_str(), Some("xml")));
 
     request::get_input_epg_content_as_file(Arc::clone(client), input, working_dir, url, persist_file_path).await
 }
 
+// Epg sources of the input provider are tried with the aliases by priority when the download fails.
+async fn download_epg_file_over_aliases(url: &str, client: &Arc<reqwest::Client>, input: &ConfigInput, working_dir: &str) -> Option<PathBuf> {
+    let input_user_info = input.get_user_info()?;
+    for alias in input.get_aliases_by_priority() {
+        let Some(alias_user_info) = input.get_alias_user_info(alias) else { continue };
+        let alias_url = input_user_info.replace_in_url(url, &alias_user_info);
+        if alias_url == url {
+            continue;
+        }
+        match download_epg_file(&alias_url, url, client, input, working_dir).await {
+            Ok(file_path) => {
+                info!("Epg of input {} downloaded over alias {}", input.name, alias.name);
+                return Some(file_path);
+            }
+            Err(err) => warn!("Failed to download epg of input {} over alias {}: {}", input.name, alias.name, err.message),
+        }
+    }
+    None
+}
+
+// The download is aborted after the timeout, the aliases are tried within the same timeout.
+async fn download_epg_source(url: &str, client: &Arc<reqwest::Client>, input: &ConfigInput, working_dir: &str, timeout: Option<Duration>) -> Result<PathBuf, TuliproxError> {
+    let download = async {
+        match download_epg_file(url, url, client, input, working_dir).await {
+            Err(err) => download_epg_file_over_aliases(url, client, input, working_dir).await.ok_or(err),
+            result => result,
+        }
+    };
+    match timeout {
+        None => download.await,
+        Some(duration) => tokio::time::timeout(duration, download).await.unwrap_or_else(|_| {
+            warn!("Epg download of input {} timed out after {} secs: {}", input.name, duration.as_secs(), sanitize_sensitive_info(url));
+            Err(notify_err!(format!("Epg download timed out {}", sanitize_sensitive_info(url))))
+        }),
+    }
+}
+
 pub async fn get_xmltv(client: Arc<reqwest::Client>, _cfg: &Config, input: &ConfigInput, working_dir: &str) -> (Option<TVGuide>, Vec<TuliproxError>) {
     match &input.epg {
         None => (None, vec![]),
@@ -25,8 +65,12 @@ pub async fn get_xmltv(client: Arc<reqwest::Client>, _cfg: &Config, input: &Conf
             let mut file_paths = vec![];
             let mut stored_file_paths = vec![];
 
-            for epg_source in &epg_config.t_sources {
-                match download_epg_file(&epg_source.url, &client, input, working_dir).await {
+            // all sources are downloaded concurrently, a slow guide server does not delay the others
+            let timeout = epg_config.get_download_timeout();
+            let results = join_all(epg_config.t_sources.iter()
+                .map(|epg_source| download_epg_source(&epg_source.url, &client, input, working_dir, timeout))).await;
+            for (epg_source, result) in epg_config.t_sources.iter().zip(results) {
+                match result {
                     Ok(file_path) => {
                         stored_file_paths.push(file_path.clone());
                         file_paths.push(PersistedEpgSource {file_path, priority: epg_source.priority, logo_override: epg_source.logo_override});
@@ -38,6 +82,8 @@ pub async fn get_xmltv(client: Arc<reqwest::Client>, _cfg: &Config, input: &Conf
             }
 
             let _ = cleanup_unlisted_files_with_suffix(&stored_file_paths, "_epg.xml");
+            let validator_paths = stored_file_paths.iter().map(|path| request::get_download_validators_path(path)).collect();
+            let _ = cleanup_unlisted_files_with_suffix(&validator_paths, "_epg.validators.json");
 
             if file_paths.is_empty() {
                 (None, errors)
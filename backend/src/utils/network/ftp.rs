@@ -0,0 +1,116 @@
+use crate::utils::text_protocol::TextProtocolConnection;
+use std::io::{Error, ErrorKind};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use url::Url;
+
+const FTP_DEFAULT_PORT: u16 = 21;
+const FTP_ANONYMOUS_USER: &str = "anonymous";
+const FTP_TIMEOUT: Duration = Duration::from_mins(2);
+
+async fn login(control: &mut TextProtocolConnection, username: &str, password: &str) -> Result<(), Error> {
+    let (code, _) = control.command(&format!("USER {username}"), &[230, 331]).await?;
+    if code == 331 {
+        control.command(&format!("PASS {password}"), &[230, 202]).await?;
+    }
+    Ok(())
+}
+
+// the address of the passive reply is ignored, the control peer is used because of NAT
+async fn open_passive_data(control: &mut TextProtocolConnection) -> Result<TcpStream, Error> {
+    let (_, message) = control.command("PASV", &[227]).await?;
+    let port = parse_passive_port(&message)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("invalid ftp passive reply: {message}")))?;
+    TcpStream::connect(SocketAddr::new(control.peer().ip(), port)).await
+}
+
+fn parse_passive_port(message: &str) -> Option<u16> {
+    let start = message.find('(')?;
+    let end = message[start..].find(')')? + start;
+    let numbers: Vec<u16> = message[start + 1..end].split(',').filter_map(|n| n.trim().parse().ok()).collect();
+    if numbers.len() == 6 {
+        Some(numbers[4] * 256 + numbers[5])
+    } else {
+        None
+    }
+}
+
+async fn upload(url: &Url, username: Option<&str>, password: Option<&str>, filename: &str, content: &[u8]) -> Result<(), Error> {
+    let host = url.host_str().ok_or_else(|| Error::new(ErrorKind::InvalidInput, "ftp url without host"))?;
+    let mut control = TextProtocolConnection::connect(host, url.port().unwrap_or(FTP_DEFAULT_PORT), &[220]).await?;
+    login(&mut control, username.unwrap_or(FTP_ANONYMOUS_USER), password.unwrap_or_default()).await?;
+    control.command("TYPE I", &[200]).await?;
+    let mut data = open_passive_data(&mut control).await?;
+    let path = format!("{}{filename}", url.path());
+    control.command(&format!("STOR {path}"), &[125, 150]).await?;
+    data.write_all(content).await?;
+    data.shutdown().await?;
+    drop(data);
+    control.expect_reply(&[226, 250]).await?;
+    let _ = control.command("QUIT", &[221]).await;
+    Ok(())
+}
+
+/// Uploads the content as `filename` into the directory of the `ftp://` url with passive mode.
+pub async fn ftp_upload(url: &Url, username: Option<&str>, password: Option<&str>, filename: &str, content: &[u8]) -> Result<(), Error> {
+    tokio::time::timeout(FTP_TIMEOUT, upload(url, username, password, filename, content)).await
+        .map_err(|_| Error::new(ErrorKind::TimedOut, "ftp upload timed out"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::ftp::ftp_upload;
+    use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_ftp_upload() {
+        let control_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let control_addr = control_listener.local_addr().unwrap();
+        let server = tokio::spawn(async move {
+            let (stream, _) = control_listener.accept().await.unwrap();
+            let (read_half, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(read_half);
+            writer.write_all(b"220-Welcome\r\n220 Ready\r\n").await.unwrap();
+            let data_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let data_port = data_listener.local_addr().unwrap().port();
+            let mut commands = vec![];
+            let mut uploaded = vec![];
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).await.unwrap() == 0 {
+                    break;
+                }
+                let command = line.trim().to_string();
+                commands.push(command.clone());
+                let reply = match command.split(' ').next().unwrap_or_default() {
+                    "USER" => "331 Password required".to_string(),
+                    "PASS" => "230 Logged in".to_string(),
+                    "TYPE" => "200 Type set".to_string(),
+                    "PASV" => format!("227 Entering Passive Mode (10,0,0,1,{},{})", data_port / 256, data_port % 256),
+                    "STOR" => {
+                        writer.write_all(b"150 Ok to send data\r\n").await.unwrap();
+                        let (mut data, _) = data_listener.accept().await.unwrap();
+                        data.read_to_end(&mut uploaded).await.unwrap();
+                        "226 Transfer complete".to_string()
+                    }
+                    "QUIT" => {
+                        writer.write_all(b"221 Bye\r\n").await.unwrap();
+                        break;
+                    }
+                    _ => "502 Not implemented".to_string(),
+                };
+                writer.write_all(format!("{reply}\r\n").as_bytes()).await.unwrap();
+            }
+            (commands, uploaded)
+        });
+
+        let url = url::Url::parse(&format!("ftp://{control_addr}/upload/")).unwrap();
+        ftp_upload(&url, Some("user"), Some("secret"), "playlist.m3u", b"#EXTM3U\n").await.unwrap();
+        let (commands, uploaded) = server.await.unwrap();
+        assert_eq!(commands, vec!["USER user", "PASS secret", "TYPE I", "PASV", "STOR /upload/playlist.m3u", "QUIT"]);
+        assert_eq!(uploaded, b"#EXTM3U\n");
+    }
+}
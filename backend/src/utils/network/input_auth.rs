@@ -0,0 +1,104 @@
+use crate::model::{ConfigInput, ConfigInputTokenAuth};
+use crate::utils::rate_limiter::acquire_provider_permit;
+use crate::utils::request::{get_client_request, sanitize_sensitive_info};
+use log::debug;
+use shared::error::str_to_io_error;
+use std::io::Error;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use url::Url;
+
+/// Login token of an input, requested from the `login_url` and kept until it is refreshed or rejected.
+#[derive(Debug, Default)]
+pub struct InputAuthToken {
+    // token and login time, the lock is held during the login so concurrent downloads log in once
+    token: Mutex<Option<(String, Instant)>>,
+}
+
+// The token is the value at the dotted `token_field` path of a json response, otherwise the plain text response.
+fn extract_token(content: &str, token_field: &str) -> Option<String> {
+    let token = match serde_json::from_str::<serde_json::Value>(content) {
+        Ok(json) => {
+            let value = token_field.split('.').try_fold(&json, |value, key| value.get(key))?;
+            match value {
+                serde_json::Value::String(token) => token.trim().to_string(),
+                serde_json::Value::Number(token) => token.to_string(),
+                _ => return None,
+            }
+        }
+        Err(_) => content.trim().to_string(),
+    };
+    (!token.is_empty() && !token.contains(char::is_whitespace)).then_some(token)
+}
+
+async fn login(client: &Arc<reqwest::Client>, input: &ConfigInput, token_auth: &ConfigInputTokenAuth) -> Result<String, Error> {
+    let login_url = Url::parse(&token_auth.login_url).map_err(|err| str_to_io_error(&format!("Invalid login url for input {}: {err}", input.name)))?;
+    let login_url = input.get_tls_url(&login_url);
+    let request = apply_basic_auth(input, get_client_request(client, token_auth.login_method, Some(&input.headers), &login_url, None));
+    let _permit = acquire_provider_permit(input.t_rate_limiter.as_ref()).await;
+    let response = request.send().await
+        .map_err(|err| str_to_io_error(&format!("Login failed for input {}: {} {err}", input.name, sanitize_sensitive_info(&token_auth.login_url))))?;
+    if !response.status().is_success() {
+        return Err(str_to_io_error(&format!("Login failed for input {} with status {}", input.name, response.status())));
+    }
+    let content = response.text().await
+        .map_err(|err| str_to_io_error(&format!("Login failed for input {}: {err}", input.name)))?;
+    extract_token(&content, &token_auth.token_field)
+        .ok_or_else(|| str_to_io_error(&format!("Login response for input {} contains no token `{}`", input.name, token_auth.token_field)))
+}
+
+/// Adds the basic auth header of the input auth to the request.
+pub fn apply_basic_auth(input: &ConfigInput, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+    match input.auth.as_ref().and_then(|auth| auth.basic.as_ref()) {
+        Some(basic) => request.basic_auth(&basic.username, basic.password.as_ref()),
+        None => request,
+    }
+}
+
+/// Adds the login token of the input auth to the url, the token is requested when it is missing or outdated.
+pub async fn get_auth_url(client: &Arc<reqwest::Client>, input: &ConfigInput, url: &Url) -> Result<Url, Error> {
+    let (Some(token_auth), Some(auth_token)) = (input.auth.as_ref().and_then(|auth| auth.token.as_ref()), input.t_auth_token.as_ref()) else {
+        return Ok(url.clone());
+    };
+    let mut guard = auth_token.token.lock().await;
+    let refresh = Duration::from_secs(u64::from(token_auth.refresh_mins) * 60);
+    let token = match guard.as_ref() {
+        Some((token, login_time)) if refresh.is_zero() || login_time.elapsed() < refresh => token.clone(),
+        _ => {
+            let token = login(client, input, token_auth).await?;
+            debug!("Logged in for input {}", input.name);
+            *guard = Some((token.clone(), Instant::now()));
+            token
+        }
+    };
+    drop(guard);
+    let mut auth_url = url.clone();
+    let query: Vec<(String, String)> = url.query_pairs()
+        .filter(|(key, _)| key != token_auth.token_param.as_str())
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    auth_url.query_pairs_mut().clear().extend_pairs(query).append_pair(&token_auth.token_param, &token);
+    Ok(auth_url)
+}
+
+/// Drops the login token after the provider rejected it, the next download logs in again.
+pub async fn invalidate_auth_token(input: &ConfigInput) {
+    if let Some(auth_token) = input.t_auth_token.as_ref() {
+        *auth_token.token.lock().await = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::input_auth::extract_token;
+
+    #[test]
+    fn test_extract_token() {
+        assert_eq!(extract_token(r#"{"data":{"token":"abc123"}}"#, "data.token").as_deref(), Some("abc123"));
+        assert_eq!(extract_token(r#"{"token":42}"#, "token").as_deref(), Some("42"));
+        assert_eq!(extract_token(r#"{"error":"denied"}"#, "token"), None);
+        assert_eq!(extract_token(" abc123\n", "token").as_deref(), Some("abc123"));
+        assert_eq!(extract_token("<html>access denied</html>", "token"), None);
+    }
+}
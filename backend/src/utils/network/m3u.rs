@@ -6,7 +6,15 @@ use crate::processing::parser::m3u;
 use crate::utils::prepare_file_path;
 use crate::utils::request;
 
+// A camera or headend encoder url is a single live channel named like the input.
+fn create_rtsp_playlist(input: &ConfigInput) -> String {
+    format!("#EXTM3U\n#EXTINF:-1 tvg-name=\"{0}\" group-title=\"{0}\",{0}\n{1}\n", input.name, input.url)
+}
+
 pub async fn get_m3u_playlist(client: Arc<reqwest::Client>, cfg: &Config, input: &ConfigInput, working_dir: &str) -> (Vec<PlaylistGroup>, Vec<TuliproxError>) {
+    if input.url.to_lowercase().starts_with("rtsp://") {
+        return (m3u::parse_m3u(cfg, input, create_rtsp_playlist(input).lines()), vec![]);
+    }
     let url = input.url.clone();
     let persist_file_path = prepare_file_path(input.persist.as_deref(), working_dir, "");
     match request::get_input_text_content(client, input, working_dir, &url, persist_file_path).await {
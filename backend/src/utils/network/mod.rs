@@ -2,4 +2,12 @@ pub mod request;
 pub mod xtream;
 pub mod m3u;
 pub mod epg;
-pub mod ip_checker;
\ No newline at end of file
+pub mod ip_checker;
+pub mod ftp;
+pub mod smtp;
+pub mod text_protocol;
+pub mod rate_limiter;
+pub mod s3;
+pub mod cookie_jar;
+pub mod rtsp;
+pub mod input_auth;
@@ -0,0 +1,102 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::time::Instant;
+
+/// Limits the outgoing requests of an input to its provider.
+/// Requests are spread evenly with `requests_per_second` and at most `max_concurrent` requests are in flight.
+#[derive(Debug)]
+pub struct ProviderRateLimiter {
+    interval: Option<Duration>,
+    next_slot: Mutex<Instant>,
+    semaphore: Option<Arc<Semaphore>>,
+}
+
+/// Keeps the concurrency slot of a provider request until it is dropped.
+pub struct ProviderRequestPermit {
+    _permit: Option<OwnedSemaphorePermit>,
+}
+
+impl ProviderRateLimiter {
+    /// Returns `None` if both limits are `0` (unlimited).
+    pub fn new(requests_per_second: u32, max_concurrent: u16) -> Option<Self> {
+        if requests_per_second == 0 && max_concurrent == 0 {
+            return None;
+        }
+        Some(Self {
+            interval: (requests_per_second > 0).then(|| Duration::from_secs(1) / requests_per_second),
+            next_slot: Mutex::new(Instant::now()),
+            semaphore: (max_concurrent > 0).then(|| Arc::new(Semaphore::new(usize::from(max_concurrent)))),
+        })
+    }
+
+    pub async fn acquire(&self) -> ProviderRequestPermit {
+        // the concurrency slot is taken first, waiting requests should not reserve time slots
+        let permit = match self.semaphore.as_ref() {
+            Some(semaphore) => Arc::clone(semaphore).acquire_owned().await.ok(),
+            None => None,
+        };
+        if let Some(interval) = self.interval {
+            let slot = {
+                let mut next_slot = self.next_slot.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+                let slot = (*next_slot).max(Instant::now());
+                *next_slot = slot + interval;
+                slot
+            };
+            tokio::time::sleep_until(slot).await;
+        }
+        ProviderRequestPermit { _permit: permit }
+    }
+}
+
+pub async fn acquire_provider_permit(rate_limiter: Option<&Arc<ProviderRateLimiter>>) -> Option<ProviderRequestPermit> {
+    match rate_limiter {
+        Some(limiter) => Some(limiter.acquire().await),
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_unlimited() {
+        assert!(ProviderRateLimiter::new(0, 0).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_requests_per_second() {
+        let limiter = ProviderRateLimiter::new(20, 0).unwrap();
+        let start = Instant::now();
+        for _ in 0..4 {
+            let _permit = limiter.acquire().await;
+        }
+        // the first request is sent immediately, the others are spread by 50ms
+        assert!(start.elapsed() >= Duration::from_millis(150));
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent() {
+        let limiter = Arc::new(ProviderRateLimiter::new(0, 2).unwrap());
+        let running = Arc::new(AtomicUsize::new(0));
+        let max_running = Arc::new(AtomicUsize::new(0));
+        let tasks: Vec<_> = (0..6).map(|_| {
+            let limiter = Arc::clone(&limiter);
+            let running = Arc::clone(&running);
+            let max_running = Arc::clone(&max_running);
+            tokio::spawn(async move {
+                let _permit = limiter.acquire().await;
+                let current = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(current, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+            })
+        }).collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        assert_eq!(max_running.load(Ordering::SeqCst), 2);
+    }
+}
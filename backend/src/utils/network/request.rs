@@ -5,23 +5,26 @@ use std::fs::File;
 use std::io::{BufWriter, Error, ErrorKind, Read, Write};
 use std::path::{Path, PathBuf};
 use std::sync::atomic::Ordering;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 use std::time::{Duration, Instant};
 
+use arc_swap::ArcSwap;
 use flate2::read::{GzDecoder, ZlibDecoder};
 use futures::StreamExt;
 use log::{debug, error, log_enabled, trace, Level};
-use reqwest::header::CONTENT_ENCODING;
+use reqwest::header::{CONTENT_ENCODING, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
 use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
 use url::Url;
 
 use shared::error::create_tuliprox_error_result;
 use shared::error::{str_to_io_error, TuliproxError, TuliproxErrorKind};
 use crate::model::{format_elapsed_time, Config};
-use crate::model::{ConfigInput, InputFetchMethod};
+use crate::model::{ConfigInput, EpgConfig, InputFetchMethod, SanitizeRule};
 use crate::repository::storage::{get_input_storage_path};
 use crate::repository::storage_const;
 use crate::utils::compression::compression_utils::{is_deflate, is_gzip};
+use crate::utils::rate_limiter::acquire_provider_permit;
+use crate::utils::input_auth::{apply_basic_auth, get_auth_url, invalidate_auth_token};
 use crate::utils::{debug_if_enabled, short_hash};
 use shared::utils::{filter_request_header};
 use crate::utils::{get_file_path, persist_file};
@@ -193,19 +196,113 @@ pub fn get_local_file_content(file_path: &PathBuf) -> Result<String, Error> {
 }
 
 
-async fn get_remote_content_as_file(client: Arc<reqwest::Client>, input: &ConfigInput, url: &Url, file_path: &Path) -> Result<PathBuf, std::io::Error> {
+/// `ETag` and `Last-Modified` of a downloaded file, stored next to it for conditional requests.
+#[derive(Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+struct DownloadValidators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_modified: Option<String>,
+}
+
+impl DownloadValidators {
+    fn from_headers(headers: &HeaderMap) -> Self {
+        let get_header = |name: HeaderName| headers.get(name).and_then(|value| value.to_str().ok()).map(ToString::to_string);
+        Self {
+            etag: get_header(ETAG),
+            last_modified: get_header(LAST_MODIFIED),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+
+    fn get_path(file_path: &Path) -> PathBuf {
+        file_path.with_extension("validators.json")
+    }
+
+    // validators are only valid if the downloaded file still exists
+    fn load(file_path: &Path) -> Option<Self> {
+        if !file_path.exists() {
+            return None;
+        }
+        fs::read_to_string(Self::get_path(file_path)).ok()
+            .and_then(|content| serde_json::from_str::<Self>(&content).ok())
+            .filter(|validators| !validators.is_empty())
+    }
+
+    fn store(&self, file_path: &Path) {
+        let path = Self::get_path(file_path);
+        if self.is_empty() {
+            let _ = fs::remove_file(path);
+        } else if let Err(err) = serde_json::to_string(self).map_err(Error::other).and_then(|content| fs::write(&path, content)) {
+            error!("Failed to write download validators {}: {err}", path.display());
+        }
+    }
+
+    fn apply(&self, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if let Some(etag) = self.etag.as_deref() {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = self.last_modified.as_deref() {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+        request
+    }
+}
+
+/// The file with the `ETag` and `Last-Modified` validators of a downloaded file.
+pub fn get_download_validators_path(file_path: &Path) -> PathBuf {
+    DownloadValidators::get_path(file_path)
+}
+
+fn is_auth_rejected(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN
+}
+
+// Existing downloads are requested with `If-None-Match`/`If-Modified-Since`,
+// the file is kept when the provider answers with `304 Not Modified`.
+// Downloads larger than `max_size` bytes are aborted.
+async fn get_remote_content_as_file(client: Arc<reqwest::Client>, input: &ConfigInput, url: &Url, file_path: &Path, max_size: Option<u64>) -> Result<PathBuf, std::io::Error> {
     let start_time = Instant::now();
-    let request = get_client_request(&client, input.method, Some(&input.headers), url, None);
+    let client = input.get_client(&client);
+    let url = &get_auth_url(&client, input, &input.get_tls_url(url)).await?;
+    let mut request = apply_basic_auth(input, get_client_request(&client, input.method, Some(&input.headers), url, None));
+    if let Some(validators) = DownloadValidators::load(file_path) {
+        request = validators.apply(request);
+    }
+    let _permit = acquire_provider_permit(input.t_rate_limiter.as_ref()).await;
     match request.send().await {
         Ok(response) => {
-            if response.status().is_success() {
+            if is_auth_rejected(response.status()) {
+                invalidate_auth_token(input).await;
+            }
+            if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+                debug!("File not modified, using {}", file_path.display());
+                Ok(file_path.to_path_buf())
+            } else if response.status().is_success() {
+                let exceeds_max_size = |size: u64| max_size.is_some_and(|max_size| size > max_size);
+                if response.content_length().is_some_and(exceeds_max_size) {
+                    return Err(str_to_io_error(&format!("Download exceeds the max size {}", sanitize_sensitive_info(url.as_str()))));
+                }
+                let validators = DownloadValidators::from_headers(response.headers());
+                // a partial download must not be validated by the old validators
+                let _ = fs::remove_file(DownloadValidators::get_path(file_path));
                 // Open a file in write mode
                 let mut file = BufWriter::with_capacity(8192, File::create(file_path)?);
                 // Stream the response body in chunks
                 let mut stream = response.bytes_stream();
+                let mut size = 0;
                 while let Some(chunk) = stream.next().await {
                     match chunk {
                         Ok(bytes) => {
+                            size += bytes.len() as u64;
+                            if exceeds_max_size(size) {
+                                drop(file);
+                                let _ = fs::remove_file(file_path);
+                                return Err(str_to_io_error(&format!("Download exceeds the max size {}", sanitize_sensitive_info(url.as_str()))));
+                            }
                             file.write_all(&bytes)?;
                         }
                         Err(err) => {
@@ -215,6 +312,7 @@ async fn get_remote_content_as_file(client: Arc<reqwest::Client>, input: &Config
                 }
 
                 file.flush()?;
+                validators.store(file_path);
                 let elapsed = start_time.elapsed().as_secs();
                 debug!("File downloaded successfully to {}, took:{}", file_path.display(), format_elapsed_time(elapsed));
                 Ok(file_path.to_path_buf())
@@ -228,9 +326,15 @@ async fn get_remote_content_as_file(client: Arc<reqwest::Client>, input: &Config
 
 async fn get_remote_content(client: Arc<reqwest::Client>, input: &ConfigInput, url: &Url) -> Result<(String, String), Error> {
     let start_time = Instant::now();
-    let request = get_client_request(&client, input.method, Some(&input.headers), url, None);
+    let client = input.get_client(&client);
+    let url = &get_auth_url(&client, input, &input.get_tls_url(url)).await?;
+    let request = apply_basic_auth(input, get_client_request(&client, input.method, Some(&input.headers), url, None));
+    let _permit = acquire_provider_permit(input.t_rate_limiter.as_ref()).await;
     match request.send().await {
         Ok(response) => {
+            if is_auth_rejected(response.status()) {
+                invalidate_auth_token(input).await;
+            }
             let is_success = response.status().is_success();
             if is_success {
                 let response_url = response.url().to_string();
@@ -312,7 +416,10 @@ async fn download_epg_content_as_file(client: Arc<reqwest::Client>, input: &Conf
                 Err(err) => Err(err)
             }, Ok);
             match file_path {
-                Ok(persist_path) => get_remote_content_as_file(client, input, &url, &persist_path).await,
+                Ok(persist_path) => {
+                    let max_size = input.epg.as_ref().and_then(EpgConfig::get_max_download_size);
+                    get_remote_content_as_file(client, input, &url, &persist_path, max_size).await
+                }
                 Err(err) => Err(err)
             }
         }
@@ -365,9 +472,34 @@ pub async fn get_input_json_content(client: Arc<reqwest::Client>, input: &Config
     }
 }
 
+// custom redaction rules from the log config, applied after the built-in rules
+static SANITIZE_RULES: LazyLock<ArcSwap<Vec<SanitizeRule>>> = LazyLock::new(|| ArcSwap::from_pointee(vec![]));
+
 pub fn set_sanitize_sensitive_info(value: bool) {
     CONSTANTS.sanitize.store(value, Ordering::SeqCst);
 }
+
+pub fn set_sanitize_rules(rules: Vec<SanitizeRule>) {
+    SANITIZE_RULES.store(Arc::new(rules));
+}
+
+/// Applies only the custom redaction rules, used for messages where the built-in rules are not wanted.
+pub fn apply_sanitize_rules(text: &str) -> Cow<'_, str> {
+    let rules = SANITIZE_RULES.load();
+    if rules.is_empty() || !CONSTANTS.sanitize.load(Ordering::SeqCst) {
+        return Cow::Borrowed(text);
+    }
+    let mut result = Cow::Borrowed(text);
+    for rule in rules.iter() {
+        if let Some(re) = rule.t_re.as_ref() {
+            if let Cow::Owned(replaced) = re.replace_all(&result, rule.replacement.as_str()) {
+                result = Cow::Owned(replaced);
+            }
+        }
+    }
+    result
+}
+
 pub fn sanitize_sensitive_info(query: &str) -> Cow<str> {
     if !CONSTANTS.sanitize.load(Ordering::SeqCst) {
         return Cow::Borrowed(query);
@@ -384,6 +516,9 @@ pub fn sanitize_sensitive_info(query: &str) -> Cow<str> {
     ] {
         result = re.replace_all(&result, *replacement).into_owned();
     }
+    if let Cow::Owned(redacted) = apply_sanitize_rules(&result) {
+        result = redacted;
+    }
     Cow::Owned(result)
 }
 
@@ -509,6 +644,15 @@ pub fn get_base_url_from_str(url: &str) -> Option<String> {
     }
 }
 
+/// Encloses ipv6 literals in brackets, other hosts are returned unchanged.
+pub fn format_url_host(host: &str) -> String {
+    if host.parse::<std::net::Ipv6Addr>().is_ok() {
+        format!("[{host}]")
+    } else {
+        host.to_string()
+    }
+}
+
 pub fn create_client(cfg: &Config) -> reqwest::ClientBuilder {
     let mut client = reqwest::Client::builder()
         .redirect(reqwest::redirect::Policy::limited(10))
@@ -545,9 +689,56 @@ pub fn create_client(cfg: &Config) -> reqwest::ClientBuilder {
     client
 }
 
+// Resolves the tls `server_name` of an input to the addresses of the input host.
+struct ServerNameResolver {
+    server_name: String,
+    host: String,
+}
+
+impl reqwest::dns::Resolve for ServerNameResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        let host = if name.as_str().eq_ignore_ascii_case(&self.server_name) { self.host.clone() } else { name.as_str().to_string() };
+        Box::pin(async move {
+            let addrs: Vec<std::net::SocketAddr> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            let addrs: reqwest::dns::Addrs = Box::new(addrs.into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Creates a client with the tls settings of the input, `None` if the input has no tls settings.
+pub fn create_input_client(cfg: &Config, input: &ConfigInput) -> Result<Option<reqwest::Client>, TuliproxError> {
+    let Some(tls) = input.tls.as_ref().filter(|tls| !tls.is_empty()) else {
+        return Ok(None);
+    };
+    let mut builder = create_client(cfg).http1_only(); // because of RAII connection dropping
+    if cfg.connect_timeout_secs > 0 {
+        builder = builder.connect_timeout(Duration::from_secs(u64::from(cfg.connect_timeout_secs)));
+    }
+    if let Some(root_ca) = tls.root_ca.as_ref() {
+        let certificates = fs::read(root_ca)
+            .map_err(|err| err.to_string())
+            .and_then(|pem| reqwest::Certificate::from_pem_bundle(&pem).map_err(|err| err.to_string()))
+            .map_err(|err| TuliproxError::new(TuliproxErrorKind::Info, format!("Failed to read tls root_ca {root_ca} for input {}: {err}", input.name)))?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+    if tls.accept_invalid_certs {
+        builder = builder.danger_accept_invalid_certs(true);
+    }
+    if let (Some(server_name), Some(host)) = (tls.server_name.as_ref(), tls.t_host.as_ref()) {
+        builder = builder.dns_resolver(Arc::new(ServerNameResolver { server_name: server_name.clone(), host: host.clone() }));
+    }
+    builder.build()
+        .map(Some)
+        .map_err(|err| TuliproxError::new(TuliproxErrorKind::Info, format!("Failed to create client for input {}: {err}", input.name)))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::utils::request::{get_base_url_from_str, replace_url_extension, sanitize_sensitive_info};
+    use crate::model::SanitizeRule;
+    use crate::utils::request::{get_base_url_from_str, replace_url_extension, sanitize_sensitive_info, set_sanitize_rules, set_sanitize_sensitive_info};
 
     #[test]
     fn test_url_mask() {
@@ -557,6 +748,22 @@ mod tests {
         println!("{masked}");
     }
 
+    #[test]
+    fn test_sanitize_rules() {
+        set_sanitize_sensitive_info(true);
+        let mut rules = vec![
+            SanitizeRule { pattern: r"[\w.-]+\.corp\.internal".to_string(), replacement: "***".to_string(), t_re: None },
+            SanitizeRule { pattern: r"([?&]sig=)[^&]+".to_string(), replacement: "$1***".to_string(), t_re: None },
+        ];
+        for rule in &mut rules {
+            rule.prepare().unwrap();
+        }
+        set_sanitize_rules(rules);
+        assert_eq!(sanitize_sensitive_info("Failed to reach cdn1.corp.internal"), "Failed to reach ***");
+        assert_eq!(sanitize_sensitive_info("stream /live/1.ts?sig=abc&x=1"), "stream /live/1.ts?sig=***&x=1");
+        assert!(SanitizeRule { pattern: "(".to_string(), replacement: String::new(), t_re: None }.prepare().is_err());
+    }
+
     #[test]
     fn test_replace_ext() {
         let tests = [
@@ -0,0 +1,689 @@
+use base64::{engine::general_purpose, Engine as _};
+use openssl::hash::{hash, MessageDigest};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::{Error, ErrorKind};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+use url::Url;
+
+const RTSP_DEFAULT_PORT: u16 = 554;
+const RTSP_VERSION: &str = "RTSP/1.0";
+const INTERLEAVED_MARKER: u8 = b'$';
+const RTP_VERSION: u8 = 2;
+const RTP_HEADER_SIZE: usize = 12;
+const MAX_HEADER_LINES: usize = 64;
+const ANNEXB_START_CODE: &[u8] = &[0, 0, 0, 1];
+const AAC_SAMPLES_PER_FRAME: u32 = 1024;
+
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] == b'%' {
+            if let Some(byte) = value.get(idx + 1..idx + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                idx += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[idx]);
+        idx += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
+
+fn md5_hex(value: &str) -> Result<String, Error> {
+    let digest = hash(MessageDigest::md5(), value.as_bytes()).map_err(|err| Error::other(err.to_string()))?;
+    Ok(digest.iter().fold(String::with_capacity(32), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    }))
+}
+
+// key="value", key=value pairs of the authenticate header
+fn parse_auth_params(params: &str) -> HashMap<String, String> {
+    let mut result = HashMap::new();
+    let mut rest = params.trim();
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim().trim_start_matches(',').trim().to_lowercase();
+        rest = rest[eq + 1..].trim_start();
+        let value = if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            let value = &quoted[..end];
+            rest = quoted.get(end + 1..).unwrap_or_default();
+            value
+        } else {
+            let end = rest.find(',').unwrap_or(rest.len());
+            let value = &rest[..end];
+            rest = &rest[end..];
+            value
+        };
+        result.insert(key, value.trim().to_string());
+        rest = rest.trim_start().trim_start_matches(',');
+    }
+    result
+}
+
+#[derive(Debug, Clone)]
+enum RtspAuth {
+    Basic,
+    Digest { realm: String, nonce: String, opaque: Option<String>, qop: bool },
+}
+
+impl RtspAuth {
+    // digest is preferred, cameras offer both
+    fn from_headers<'a, I: Iterator<Item = &'a str>>(headers: I) -> Option<Self> {
+        let mut auth = None;
+        for header in headers {
+            let (scheme, params) = header.trim().split_once(' ').unwrap_or((header.trim(), ""));
+            if scheme.eq_ignore_ascii_case("digest") {
+                let params = parse_auth_params(params);
+                return Some(Self::Digest {
+                    realm: params.get("realm").cloned().unwrap_or_default(),
+                    nonce: params.get("nonce").cloned().unwrap_or_default(),
+                    opaque: params.get("opaque").cloned(),
+                    qop: params.get("qop").is_some_and(|qop| qop.split(',').any(|value| value.trim() == "auth")),
+                });
+            }
+            if scheme.eq_ignore_ascii_case("basic") {
+                auth = Some(Self::Basic);
+            }
+        }
+        auth
+    }
+
+    fn authorization(&self, method: &str, uri: &str, username: &str, password: &str, nonce_count: u32) -> Result<String, Error> {
+        match self {
+            Self::Basic => Ok(format!("Basic {}", general_purpose::STANDARD.encode(format!("{username}:{password}")))),
+            Self::Digest { realm, nonce, opaque, qop } => {
+                let ha1 = md5_hex(&format!("{username}:{realm}:{password}"))?;
+                let ha2 = md5_hex(&format!("{method}:{uri}"))?;
+                let mut header = format!("Digest username=\"{username}\", realm=\"{realm}\", nonce=\"{nonce}\", uri=\"{uri}\"");
+                let response = if *qop {
+                    let cnonce = format!("{:016x}", rand::random::<u64>());
+                    let nc = format!("{nonce_count:08x}");
+                    let _ = write!(header, ", qop=auth, nc={nc}, cnonce=\"{cnonce}\"");
+                    md5_hex(&format!("{ha1}:{nonce}:{nc}:{cnonce}:auth:{ha2}"))?
+                } else {
+                    md5_hex(&format!("{ha1}:{nonce}:{ha2}"))?
+                };
+                let _ = write!(header, ", response=\"{response}\"");
+                if let Some(opaque) = opaque {
+                    let _ = write!(header, ", opaque=\"{opaque}\"");
+                }
+                Ok(header)
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RtspResponse {
+    pub status: u16,
+    headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl RtspResponse {
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+
+    fn headers<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.headers.iter().filter(move |(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, value)| value.as_str())
+    }
+}
+
+pub enum RtspMessage {
+    Response(RtspResponse),
+    // rtp and rtcp packets are interleaved on the control connection
+    Interleaved(u8, Vec<u8>),
+}
+
+/// RTSP control connection with TCP interleaved transport, the url credentials are used for basic and digest authentication.
+pub struct RtspConnection {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+    // the url without credentials
+    url: Url,
+    credentials: Option<(String, String)>,
+    auth: Option<RtspAuth>,
+    nonce_count: u32,
+    cseq: u32,
+    session: Option<String>,
+    user_agent: String,
+}
+
+impl RtspConnection {
+    pub async fn connect(url: &Url, user_agent: &str) -> Result<Self, Error> {
+        let host = url.host_str().ok_or_else(|| Error::new(ErrorKind::InvalidInput, "rtsp url without host"))?;
+        let stream = TcpStream::connect((host, url.port().unwrap_or(RTSP_DEFAULT_PORT))).await?;
+        stream.set_nodelay(true)?;
+        let (read_half, writer) = stream.into_split();
+        let credentials = (!url.username().is_empty())
+            .then(|| (percent_decode(url.username()), percent_decode(url.password().unwrap_or_default())));
+        let mut url = url.clone();
+        let _ = url.set_username("");
+        let _ = url.set_password(None);
+        Ok(Self {
+            reader: BufReader::new(read_half),
+            writer,
+            url,
+            credentials,
+            auth: None,
+            nonce_count: 0,
+            cseq: 0,
+            session: None,
+            user_agent: user_agent.to_string(),
+        })
+    }
+
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+
+    fn create_request(&mut self, method: &str, uri: &str, headers: &[(&str, &str)]) -> Result<String, Error> {
+        self.cseq += 1;
+        let mut request = format!("{method} {uri} {RTSP_VERSION}\r\nCSeq: {}\r\nUser-Agent: {}\r\n", self.cseq, self.user_agent);
+        if let (Some(auth), Some((username, password))) = (self.auth.as_ref(), self.credentials.as_ref()) {
+            self.nonce_count += 1;
+            let _ = write!(request, "Authorization: {}\r\n", auth.authorization(method, uri, username, password, self.nonce_count)?);
+        }
+        if let Some(session) = self.session.as_ref() {
+            let _ = write!(request, "Session: {session}\r\n");
+        }
+        for (key, value) in headers {
+            let _ = write!(request, "{key}: {value}\r\n");
+        }
+        request.push_str("\r\n");
+        Ok(request)
+    }
+
+    /// Sends the request without waiting for the response, used for keep alive requests while streaming.
+    pub async fn send(&mut self, method: &str, uri: &str, headers: &[(&str, &str)]) -> Result<(), Error> {
+        let request = self.create_request(method, uri, headers)?;
+        self.writer.write_all(request.as_bytes()).await
+    }
+
+    /// Sends the request and returns the response, a `401` is answered once with the credentials of the url.
+    pub async fn request(&mut self, method: &str, uri: &str, headers: &[(&str, &str)]) -> Result<RtspResponse, Error> {
+        let mut response = self.request_once(method, uri, headers).await?;
+        if response.status == 401 && self.credentials.is_some() {
+            if let Some(auth) = RtspAuth::from_headers(response.headers("WWW-Authenticate")) {
+                self.auth = Some(auth);
+                self.nonce_count = 0;
+                response = self.request_once(method, uri, headers).await?;
+            }
+        }
+        if response.status != 200 {
+            return Err(Error::other(format!("rtsp {method} failed with status {}", response.status)));
+        }
+        if let Some(session) = response.header("Session") {
+            // the session id is followed by the timeout `12345678;timeout=60`
+            self.session = session.split(';').next().map(|id| id.trim().to_string());
+        }
+        Ok(response)
+    }
+
+    async fn request_once(&mut self, method: &str, uri: &str, headers: &[(&str, &str)]) -> Result<RtspResponse, Error> {
+        self.send(method, uri, headers).await?;
+        loop {
+            if let RtspMessage::Response(response) = self.read_message().await? {
+                return Ok(response);
+            }
+        }
+    }
+
+    async fn read_line(&mut self) -> Result<String, Error> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line).await? == 0 {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "rtsp connection closed"));
+        }
+        Ok(line.trim_end().to_string())
+    }
+
+    pub async fn read_message(&mut self) -> Result<RtspMessage, Error> {
+        if self.reader.fill_buf().await?.first() == Some(&INTERLEAVED_MARKER) {
+            let mut header = [0u8; 4];
+            self.reader.read_exact(&mut header).await?;
+            let mut data = vec![0u8; usize::from(u16::from_be_bytes([header[2], header[3]]))];
+            self.reader.read_exact(&mut data).await?;
+            return Ok(RtspMessage::Interleaved(header[1], data));
+        }
+        let status_line = self.read_line().await?;
+        let status = status_line.strip_prefix(RTSP_VERSION)
+            .and_then(|rest| rest.trim_start().get(..3))
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("invalid rtsp response: {status_line}")))?;
+        let mut headers = Vec::new();
+        loop {
+            let line = self.read_line().await?;
+            if line.is_empty() {
+                break;
+            }
+            if headers.len() >= MAX_HEADER_LINES {
+                return Err(Error::new(ErrorKind::InvalidData, "too many rtsp headers"));
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                headers.push((key.trim().to_string(), value.trim().to_string()));
+            }
+        }
+        let mut response = RtspResponse { status, headers, body: Vec::new() };
+        let content_length = response.header("Content-Length").and_then(|len| len.parse::<usize>().ok()).unwrap_or(0);
+        if content_length > 0 {
+            response.body = vec![0u8; content_length];
+            self.reader.read_exact(&mut response.body).await?;
+        }
+        Ok(RtspMessage::Response(response))
+    }
+}
+
+/// A media description of the session description.
+#[derive(Debug, Clone, Default)]
+pub struct SdpMedia {
+    pub media: String,
+    pub payload_type: u8,
+    // upper case encoding name like `H264`
+    pub codec: String,
+    pub clock_rate: u32,
+    pub fmtp: HashMap<String, String>,
+    pub control: Option<String>,
+}
+
+pub fn parse_sdp(sdp: &str) -> Vec<SdpMedia> {
+    let mut medias: Vec<SdpMedia> = Vec::new();
+    for line in sdp.lines().map(str::trim) {
+        if let Some(media) = line.strip_prefix("m=") {
+            let mut parts = media.split_whitespace();
+            let name = parts.next().unwrap_or_default().to_string();
+            let payload_type = parts.nth(2).and_then(|pt| pt.parse::<u8>().ok()).unwrap_or_default();
+            medias.push(SdpMedia { media: name, payload_type, ..SdpMedia::default() });
+            continue;
+        }
+        let Some(current) = medias.last_mut() else { continue };
+        if let Some(rtpmap) = line.strip_prefix("a=rtpmap:") {
+            if let Some((pt, encoding)) = rtpmap.split_once(' ') {
+                if pt.trim().parse::<u8>().ok() == Some(current.payload_type) {
+                    let mut encoding = encoding.trim().split('/');
+                    current.codec = encoding.next().unwrap_or_default().to_uppercase();
+                    current.clock_rate = encoding.next().and_then(|rate| rate.parse().ok()).unwrap_or_default();
+                }
+            }
+        } else if let Some(fmtp) = line.strip_prefix("a=fmtp:") {
+            if let Some((pt, params)) = fmtp.split_once(' ') {
+                if pt.trim().parse::<u8>().ok() == Some(current.payload_type) {
+                    current.fmtp = params.split(';')
+                        .filter_map(|param| param.trim().split_once('='))
+                        .map(|(key, value)| (key.trim().to_lowercase(), value.trim().to_string()))
+                        .collect();
+                }
+            }
+        } else if let Some(control) = line.strip_prefix("a=control:") {
+            current.control = Some(control.trim().to_string());
+        }
+    }
+    medias
+}
+
+/// Returns the url of the track for the setup request.
+pub fn get_track_url(base: &str, control: Option<&str>) -> String {
+    match control {
+        None | Some("*") => base.to_string(),
+        Some(control) if control.to_lowercase().starts_with("rtsp://") => control.to_string(),
+        Some(control) if base.ends_with('/') => format!("{base}{control}"),
+        Some(control) => format!("{base}/{control}"),
+    }
+}
+
+pub struct RtpPacket<'a> {
+    pub marker: bool,
+    pub payload_type: u8,
+    pub sequence: u16,
+    pub timestamp: u32,
+    pub payload: &'a [u8],
+}
+
+pub fn parse_rtp(data: &[u8]) -> Option<RtpPacket<'_>> {
+    if data.len() < RTP_HEADER_SIZE || data[0] >> 6 != RTP_VERSION {
+        return None;
+    }
+    let mut offset = RTP_HEADER_SIZE + usize::from(data[0] & 0x0F) * 4;
+    if data[0] & 0x10 != 0 {
+        let ext = data.get(offset + 2..offset + 4)?;
+        offset += 4 + usize::from(u16::from_be_bytes([ext[0], ext[1]])) * 4;
+    }
+    let padding = if data[0] & 0x20 == 0 { 0 } else { usize::from(*data.last()?) };
+    let end = data.len().checked_sub(padding)?;
+    Some(RtpPacket {
+        marker: data[1] & 0x80 != 0,
+        payload_type: data[1] & 0x7F,
+        sequence: u16::from_be_bytes([data[2], data[3]]),
+        timestamp: u32::from_be_bytes([data[4], data[5], data[6], data[7]]),
+        payload: data.get(offset..end)?,
+    })
+}
+
+/// A complete video frame in annex b format.
+pub struct AccessUnit {
+    pub timestamp: u32,
+    pub key_frame: bool,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VideoCodec {
+    H264,
+    H265,
+}
+
+/// Reassembles the access units of H.264 (RFC 6184) and H.265 (RFC 7798) rtp packets.
+pub struct VideoDepacketizer {
+    codec: VideoCodec,
+    // parameter sets of the session description, written before key frames without parameter sets
+    parameter_sets: Vec<Vec<u8>>,
+    nals: Vec<Vec<u8>>,
+    fragment: Option<Vec<u8>>,
+    timestamp: Option<u32>,
+    sequence: Option<u16>,
+}
+
+impl VideoDepacketizer {
+    pub fn new(codec: VideoCodec, media: &SdpMedia) -> Self {
+        let keys: &[&str] = match codec {
+            VideoCodec::H264 => &["sprop-parameter-sets"],
+            VideoCodec::H265 => &["sprop-vps", "sprop-sps", "sprop-pps"],
+        };
+        let parameter_sets = keys.iter()
+            .filter_map(|key| media.fmtp.get(*key))
+            .flat_map(|sets| sets.split(','))
+            .filter_map(|set| general_purpose::STANDARD.decode(set.trim()).ok())
+            .filter(|set| !set.is_empty())
+            .collect();
+        Self { codec, parameter_sets, nals: Vec::new(), fragment: None, timestamp: None, sequence: None }
+    }
+
+    fn nal_type(&self, nal: &[u8]) -> u8 {
+        match self.codec {
+            VideoCodec::H264 => nal[0] & 0x1F,
+            VideoCodec::H265 => (nal[0] >> 1) & 0x3F,
+        }
+    }
+
+    fn is_key_frame_nal(&self, nal_type: u8) -> bool {
+        match self.codec {
+            VideoCodec::H264 => nal_type == 5,
+            VideoCodec::H265 => (16..=21).contains(&nal_type),
+        }
+    }
+
+    fn is_parameter_set_nal(&self, nal_type: u8) -> bool {
+        match self.codec {
+            VideoCodec::H264 => nal_type == 7 || nal_type == 8,
+            VideoCodec::H265 => (32..=34).contains(&nal_type),
+        }
+    }
+
+    fn flush(&mut self) -> Option<AccessUnit> {
+        let timestamp = self.timestamp.take()?;
+        let nals = std::mem::take(&mut self.nals);
+        if nals.is_empty() {
+            return None;
+        }
+        let types: Vec<u8> = nals.iter().map(|nal| self.nal_type(nal)).collect();
+        let key_frame = types.iter().any(|nal_type| self.is_key_frame_nal(*nal_type));
+        let with_parameter_sets = types.iter().any(|nal_type| self.is_parameter_set_nal(*nal_type));
+        let mut data = Vec::with_capacity(nals.iter().map(|nal| nal.len() + 4).sum());
+        if key_frame && !with_parameter_sets {
+            for set in &self.parameter_sets {
+                data.extend_from_slice(ANNEXB_START_CODE);
+                data.extend_from_slice(set);
+            }
+        }
+        for nal in nals {
+            data.extend_from_slice(ANNEXB_START_CODE);
+            data.extend_from_slice(&nal);
+        }
+        Some(AccessUnit { timestamp, key_frame, data })
+    }
+
+    // aggregation packets hold nal units with a 16 bit size prefix
+    fn push_aggregated(&mut self, mut payload: &[u8]) {
+        while payload.len() > 2 {
+            let size = usize::from(u16::from_be_bytes([payload[0], payload[1]]));
+            let Some(nal) = payload.get(2..2 + size) else { break };
+            if !nal.is_empty() {
+                self.nals.push(nal.to_vec());
+            }
+            payload = &payload[2 + size..];
+        }
+    }
+
+    fn push_fragment(&mut self, header: &[u8], fragment: &[u8], start: bool, end: bool) {
+        if start {
+            let mut nal = header.to_vec();
+            nal.extend_from_slice(fragment);
+            self.fragment = Some(nal);
+        } else if let Some(nal) = self.fragment.as_mut() {
+            nal.extend_from_slice(fragment);
+        }
+        if end {
+            if let Some(nal) = self.fragment.take() {
+                self.nals.push(nal);
+            }
+        }
+    }
+
+    /// Returns the completed access units, the marker bit or a new timestamp completes an access unit.
+    pub fn push(&mut self, packet: &RtpPacket) -> Vec<AccessUnit> {
+        let mut units = Vec::new();
+        // a lost packet corrupts the fragmented nal unit
+        if self.sequence.is_some_and(|sequence| sequence.wrapping_add(1) != packet.sequence) {
+            self.fragment = None;
+        }
+        self.sequence = Some(packet.sequence);
+        if self.timestamp.is_some_and(|timestamp| timestamp != packet.timestamp) {
+            units.extend(self.flush());
+        }
+        self.timestamp = Some(packet.timestamp);
+        let payload = packet.payload;
+        match self.codec {
+            VideoCodec::H264 if !payload.is_empty() => match payload[0] & 0x1F {
+                1..=23 => self.nals.push(payload.to_vec()),
+                // STAP-A
+                24 => self.push_aggregated(&payload[1..]),
+                // FU-A
+                28 if payload.len() > 2 => {
+                    let fu_header = payload[1];
+                    let nal_header = (payload[0] & 0xE0) | (fu_header & 0x1F);
+                    self.push_fragment(&[nal_header], &payload[2..], fu_header & 0x80 != 0, fu_header & 0x40 != 0);
+                }
+                _ => {}
+            },
+            VideoCodec::H265 if payload.len() > 2 => match (payload[0] >> 1) & 0x3F {
+                0..=47 => self.nals.push(payload.to_vec()),
+                // aggregation packet
+                48 => self.push_aggregated(&payload[2..]),
+                // fragmentation unit
+                49 if payload.len() > 3 => {
+                    let fu_header = payload[2];
+                    let nal_header = [(payload[0] & 0x81) | ((fu_header & 0x3F) << 1), payload[1]];
+                    self.push_fragment(&nal_header, &payload[3..], fu_header & 0x80 != 0, fu_header & 0x40 != 0);
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+        if packet.marker {
+            units.extend(self.flush());
+        }
+        units
+    }
+}
+
+/// Extracts the AAC frames of `mpeg4-generic` rtp packets (RFC 3640) and adds an adts header.
+pub struct AacDepacketizer {
+    profile: u8,
+    frequency_index: u8,
+    channels: u8,
+    size_length: u32,
+    index_length: u32,
+}
+
+impl AacDepacketizer {
+    /// Returns `None` if the stream is not AAC with an adts compatible config.
+    pub fn new(media: &SdpMedia) -> Option<Self> {
+        let config = media.fmtp.get("config")?;
+        let config = (0..config.len()).step_by(2)
+            .map(|idx| config.get(idx..idx + 2).and_then(|hex| u8::from_str_radix(hex, 16).ok()))
+            .collect::<Option<Vec<u8>>>()?;
+        if config.len() < 2 {
+            return None;
+        }
+        let object_type = config[0] >> 3;
+        let frequency_index = ((config[0] & 0x07) << 1) | (config[1] >> 7);
+        let channels = (config[1] >> 3) & 0x0F;
+        // adts supports the object types 1-4 and no explicit frequency
+        if !(1..=4).contains(&object_type) || frequency_index > 12 {
+            return None;
+        }
+        Some(Self {
+            profile: object_type - 1,
+            frequency_index,
+            channels,
+            size_length: media.fmtp.get("sizelength").and_then(|len| len.parse().ok()).unwrap_or(13),
+            index_length: media.fmtp.get("indexlength").and_then(|len| len.parse().ok()).unwrap_or(3),
+        })
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn adts_header(&self, frame_len: usize) -> [u8; 7] {
+        let len = frame_len + 7;
+        [
+            0xFF,
+            0xF1,
+            (self.profile << 6) | (self.frequency_index << 2) | (self.channels >> 2),
+            ((self.channels & 0x03) << 6) | ((len >> 11) & 0x03) as u8,
+            (len >> 3) as u8,
+            (((len & 0x07) as u8) << 5) | 0x1F,
+            0xFC,
+        ]
+    }
+
+    /// Returns the frames with their rtp timestamp.
+    pub fn push(&self, packet: &RtpPacket) -> Vec<(u32, Vec<u8>)> {
+        let payload = packet.payload;
+        if payload.len() < 2 {
+            return Vec::new();
+        }
+        let header_bits = u32::from(u16::from_be_bytes([payload[0], payload[1]]));
+        let header_bytes = header_bits.div_ceil(8) as usize;
+        let au_header_bits = self.size_length + self.index_length;
+        let Some(headers) = payload.get(2..2 + header_bytes) else { return Vec::new() };
+        let mut data = &payload[2 + header_bytes..];
+        let mut frames = Vec::new();
+        let mut bit_pos = 0;
+        let mut timestamp = packet.timestamp;
+        while au_header_bits > 0 && bit_pos + au_header_bits <= header_bits {
+            let size = read_bits(headers, bit_pos, self.size_length) as usize;
+            bit_pos += au_header_bits;
+            let Some(frame) = data.get(..size) else { break };
+            let mut adts = self.adts_header(size).to_vec();
+            adts.extend_from_slice(frame);
+            frames.push((timestamp, adts));
+            data = &data[size..];
+            timestamp = timestamp.wrapping_add(AAC_SAMPLES_PER_FRAME);
+        }
+        frames
+    }
+}
+
+fn read_bits(data: &[u8], start: u32, count: u32) -> u32 {
+    (start..start + count).fold(0, |value, bit| {
+        let byte = data.get((bit / 8) as usize).copied().unwrap_or_default();
+        (value << 1) | u32::from((byte >> (7 - bit % 8)) & 1)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_auth_params, parse_rtp, parse_sdp, get_track_url, AacDepacketizer, RtpPacket, VideoCodec, VideoDepacketizer};
+
+    const SDP: &str = "v=0\r\no=- 0 0 IN IP4 127.0.0.1\r\ns=Camera\r\nt=0 0\r\na=control:*\r\n\
+        m=video 0 RTP/AVP 96\r\na=rtpmap:96 H264/90000\r\na=fmtp:96 packetization-mode=1;sprop-parameter-sets=Z0IAKeKQFAe2AtwEBAaQeJEV,aM48gA==\r\na=control:trackID=1\r\n\
+        m=audio 0 RTP/AVP 97\r\na=rtpmap:97 MPEG4-GENERIC/48000/2\r\na=fmtp:97 streamtype=5;mode=AAC-hbr;config=1190;sizelength=13;indexlength=3;indexdeltalength=3\r\na=control:trackID=2\r\n";
+
+    fn packet(marker: bool, sequence: u16, timestamp: u32, payload: &[u8]) -> RtpPacket<'_> {
+        RtpPacket { marker, payload_type: 96, sequence, timestamp, payload }
+    }
+
+    #[test]
+    fn test_parse_sdp() {
+        let medias = parse_sdp(SDP);
+        assert_eq!(medias.len(), 2);
+        assert_eq!(medias[0].codec, "H264");
+        assert_eq!(medias[0].clock_rate, 90000);
+        assert_eq!(medias[1].codec, "MPEG4-GENERIC");
+        assert_eq!(medias[1].fmtp.get("config").map(String::as_str), Some("1190"));
+        assert_eq!(get_track_url("rtsp://cam/live", medias[0].control.as_deref()), "rtsp://cam/live/trackID=1");
+        assert_eq!(get_track_url("rtsp://cam/live/", Some("rtsp://cam/live/track2")), "rtsp://cam/live/track2");
+    }
+
+    #[test]
+    fn test_parse_auth_params() {
+        let params = parse_auth_params("realm=\"IP Camera\", nonce=\"abc,def\", stale=FALSE");
+        assert_eq!(params.get("realm").map(String::as_str), Some("IP Camera"));
+        assert_eq!(params.get("nonce").map(String::as_str), Some("abc,def"));
+        assert_eq!(params.get("stale").map(String::as_str), Some("FALSE"));
+    }
+
+    #[test]
+    fn test_parse_rtp() {
+        let data = [0x80, 0xE0, 0x00, 0x02, 0, 0, 0x01, 0x00, 0, 0, 0, 1, 0x65, 0xAA];
+        let rtp = parse_rtp(&data).unwrap();
+        assert!(rtp.marker);
+        assert_eq!(rtp.payload_type, 96);
+        assert_eq!(rtp.sequence, 2);
+        assert_eq!(rtp.timestamp, 256);
+        assert_eq!(rtp.payload, &[0x65, 0xAA]);
+    }
+
+    #[test]
+    fn test_h264_depacketizer() {
+        let medias = parse_sdp(SDP);
+        let mut depacketizer = VideoDepacketizer::new(VideoCodec::H264, &medias[0]);
+        // FU-A idr slice in two packets
+        assert!(depacketizer.push(&packet(false, 1, 3000, &[0x7C, 0x85, 1, 2])).is_empty());
+        let units = depacketizer.push(&packet(true, 2, 3000, &[0x7C, 0x45, 3])).into_iter().collect::<Vec<_>>();
+        assert_eq!(units.len(), 1);
+        assert!(units[0].key_frame);
+        // the parameter sets of the sdp are written before the key frame
+        assert!(units[0].data.starts_with(&[0, 0, 0, 1, 0x67]));
+        assert!(units[0].data.ends_with(&[0, 0, 0, 1, 0x65, 1, 2, 3]));
+
+        // STAP-A with two slices
+        let units = depacketizer.push(&packet(true, 3, 6000, &[0x18, 0, 2, 0x41, 9, 0, 2, 0x41, 8]));
+        assert!(!units[0].key_frame);
+        assert_eq!(units[0].data, vec![0, 0, 0, 1, 0x41, 9, 0, 0, 0, 1, 0x41, 8]);
+
+        // a lost fragment drops the nal unit
+        assert!(depacketizer.push(&packet(false, 4, 9000, &[0x7C, 0x81, 1])).is_empty());
+        assert!(depacketizer.push(&packet(true, 6, 9000, &[0x7C, 0x41, 3])).is_empty());
+    }
+
+    #[test]
+    fn test_aac_depacketizer() {
+        let medias = parse_sdp(SDP);
+        let depacketizer = AacDepacketizer::new(&medias[1]).unwrap();
+        // one au header with size 3 and index 0
+        let frames = depacketizer.push(&packet(true, 1, 1000, &[0x00, 0x10, 0x00, 0x18, 1, 2, 3]));
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].0, 1000);
+        assert_eq!(&frames[0].1[..2], &[0xFF, 0xF1]);
+        assert_eq!(&frames[0].1[7..], &[1, 2, 3]);
+        // aac lc, 48kHz, 2 channels
+        assert_eq!(frames[0].1[2], 0x4C);
+    }
+}
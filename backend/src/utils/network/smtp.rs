@@ -0,0 +1,125 @@
+use crate::model::EmailMessagingConfig;
+use crate::utils::text_protocol::TextProtocolConnection;
+use base64::engine::general_purpose;
+use base64::Engine;
+use std::io::{Error, ErrorKind};
+use std::time::Duration;
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+const SMTP_CLIENT_NAME: &str = "tuliprox";
+
+// non ascii subjects need the encoded word syntax of RFC 2047
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        value.to_string()
+    } else {
+        format!("=?UTF-8?B?{}?=", general_purpose::STANDARD.encode(value))
+    }
+}
+
+/// Creates the message with headers, line endings are normalized to CRLF and lines starting with a dot are escaped.
+fn create_message(from: &str, to: &[String], subject: &str, body: &str) -> String {
+    let mut message = format!(
+        "From: {from}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMIME-Version: 1.0\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Transfer-Encoding: 8bit\r\n\r\n",
+        to.join(", "), encode_header(subject), chrono::Utc::now().to_rfc2822());
+    for line in body.lines() {
+        if line.starts_with('.') {
+            message.push('.');
+        }
+        message.push_str(line);
+        message.push_str("\r\n");
+    }
+    message.push_str(".\r\n");
+    message
+}
+
+async fn send(config: &EmailMessagingConfig, to: &[String], subject: &str, body: &str) -> Result<(), Error> {
+    let mut connection = TextProtocolConnection::connect(&config.host, config.port, &[220]).await?;
+    connection.command(&format!("EHLO {SMTP_CLIENT_NAME}"), &[250]).await?;
+    if let Some(username) = config.username.as_ref() {
+        let credentials = format!("\0{username}\0{}", config.password.as_deref().unwrap_or_default());
+        connection.command(&format!("AUTH PLAIN {}", general_purpose::STANDARD.encode(credentials)), &[235]).await?;
+    }
+    connection.command(&format!("MAIL FROM:<{}>", config.from), &[250]).await?;
+    for recipient in to {
+        connection.command(&format!("RCPT TO:<{recipient}>"), &[250, 251]).await?;
+    }
+    connection.command("DATA", &[354]).await?;
+    connection.write_all(create_message(&config.from, to, subject, body).as_bytes()).await?;
+    connection.expect_reply(&[250]).await?;
+    let _ = connection.command("QUIT", &[221]).await;
+    Ok(())
+}
+
+/// Sends a plain text mail. The connection is not encrypted, use a local relay for remote mail servers.
+pub async fn smtp_send(config: &EmailMessagingConfig, to: &[String], subject: &str, body: &str) -> Result<(), Error> {
+    if to.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidInput, "no mail recipients"));
+    }
+    tokio::time::timeout(SMTP_TIMEOUT, send(config, to, subject, body)).await
+        .map_err(|_| Error::new(ErrorKind::TimedOut, "smtp send timed out"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::EmailMessagingConfig;
+    use crate::utils::smtp::smtp_send;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_smtp_send() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read_half, mut writer) = stream.into_split();
+            let mut reader = BufReader::new(read_half);
+            writer.write_all(b"220 mail.local ESMTP\r\n").await.unwrap();
+            let mut commands = vec![];
+            let mut data = String::new();
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).await.unwrap() == 0 {
+                    break;
+                }
+                if in_data {
+                    if line == ".\r\n" {
+                        in_data = false;
+                        writer.write_all(b"250 Queued\r\n").await.unwrap();
+                    } else {
+                        data.push_str(&line);
+                    }
+                    continue;
+                }
+                let command = line.trim().to_string();
+                commands.push(command.clone());
+                let reply = match command.split([' ', ':']).next().unwrap_or_default() {
+                    "EHLO" => "250-mail.local\r\n250 AUTH PLAIN",
+                    "AUTH" => "235 Authenticated",
+                    "MAIL" | "RCPT" => "250 Ok",
+                    "DATA" => {
+                        in_data = true;
+                        "354 Send data"
+                    }
+                    "QUIT" => {
+                        writer.write_all(b"221 Bye\r\n").await.unwrap();
+                        break;
+                    }
+                    _ => "502 Not implemented",
+                };
+                writer.write_all(format!("{reply}\r\n").as_bytes()).await.unwrap();
+            }
+            (commands, data)
+        });
+
+        let config: EmailMessagingConfig = serde_yaml::from_str(&format!(
+            "{{host: 127.0.0.1, port: {port}, username: user, password: secret, from: tuliprox@local}}")).unwrap();
+        smtp_send(&config, &["user@local".to_string()], "Willkommen ä", "Hello\n.hidden\nBye").await.unwrap();
+        let (commands, data) = server.await.unwrap();
+        assert_eq!(commands, vec!["EHLO tuliprox", "AUTH PLAIN AHVzZXIAc2VjcmV0", "MAIL FROM:<tuliprox@local>", "RCPT TO:<user@local>", "DATA", "QUIT"]);
+        assert!(data.contains("Subject: =?UTF-8?B?V2lsbGtvbW1lbiDDpA==?=\r\n"));
+        assert!(data.ends_with("\r\n\r\nHello\r\n..hidden\r\nBye\r\n"));
+    }
+}
@@ -0,0 +1,72 @@
+use std::io::{Error, ErrorKind};
+use std::net::SocketAddr;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::net::TcpStream;
+
+/// Control connection of line based protocols like FTP and SMTP, replies start with a 3 digit code.
+pub struct TextProtocolConnection {
+    reader: BufReader<OwnedReadHalf>,
+    writer: OwnedWriteHalf,
+    peer: SocketAddr,
+}
+
+impl TextProtocolConnection {
+    /// Connects and expects the greeting reply.
+    pub async fn connect(host: &str, port: u16, greeting_codes: &[u16]) -> Result<Self, Error> {
+        let stream = TcpStream::connect((host, port)).await?;
+        let peer = stream.peer_addr()?;
+        let (read_half, writer) = stream.into_split();
+        let mut connection = Self { reader: BufReader::new(read_half), writer, peer };
+        connection.expect_reply(greeting_codes).await?;
+        Ok(connection)
+    }
+
+    pub fn peer(&self) -> SocketAddr {
+        self.peer
+    }
+
+    async fn read_line(&mut self) -> Result<String, Error> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line).await? == 0 {
+            return Err(Error::new(ErrorKind::UnexpectedEof, "connection closed"));
+        }
+        Ok(line)
+    }
+
+    // multi line replies start with `123-` and end with `123 `
+    pub async fn read_reply(&mut self) -> Result<(u16, String), Error> {
+        let mut line = self.read_line().await?;
+        let code = line.get(..3).and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("invalid reply: {}", line.trim())))?;
+        if line.as_bytes().get(3) == Some(&b'-') {
+            let end = format!("{code} ");
+            loop {
+                let next = self.read_line().await?;
+                if next.starts_with(&end) {
+                    line = next;
+                    break;
+                }
+            }
+        }
+        Ok((code, line.trim().to_string()))
+    }
+
+    pub async fn expect_reply(&mut self, codes: &[u16]) -> Result<(u16, String), Error> {
+        let (code, message) = self.read_reply().await?;
+        if codes.contains(&code) {
+            Ok((code, message))
+        } else {
+            Err(Error::other(format!("unexpected reply: {message}")))
+        }
+    }
+
+    pub async fn write_all(&mut self, content: &[u8]) -> Result<(), Error> {
+        self.writer.write_all(content).await
+    }
+
+    pub async fn command(&mut self, command: &str, codes: &[u16]) -> Result<(u16, String), Error> {
+        self.writer.write_all(format!("{command}\r\n").as_bytes()).await?;
+        self.expect_reply(codes).await
+    }
+}
@@ -2,6 +2,7 @@ use crate::model::ProxyUserCredentials;
 use crate::model::{Config, ConfigInput, ConfigTarget};
 use crate::model::{PlaylistGroup, XtreamPlaylistItem};
 use crate::processing::parser::xtream;
+use crate::repository::provider_cache_repository::{load_provider_response, store_provider_response};
 use crate::repository::xtream_repository;
 use crate::repository::xtream_repository::{rewrite_xtream_series_info_content, rewrite_xtream_vod_info_content, xtream_get_input_info};
 use shared::error::{str_to_io_error, TuliproxError};
@@ -12,6 +13,7 @@ use chrono::{DateTime};
 use log::{info, warn};
 use std::cmp::Ordering;
 use std::io::Error;
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -54,6 +56,28 @@ pub async fn get_xtream_stream_info_content(client: Arc<reqwest::Client>, info_u
     }
 }
 
+/// Returns the info content from the provider cache or downloads and caches it.
+/// The flag is true if the content was taken from the cache.
+pub async fn get_cached_xtream_stream_info_content(cfg: &Config, client: Arc<reqwest::Client>, info_url: &str, input: &ConfigInput) -> Result<(String, bool), Error> {
+    if let Some(content) = load_provider_response(cfg, input, info_url).await {
+        return Ok((content, true));
+    }
+    let content = get_xtream_stream_info_content(client, info_url, input).await?;
+    store_provider_response(cfg, input, info_url, &content).await;
+    Ok((content, false))
+}
+
+async fn get_cached_json_content(cfg: &Config, client: Arc<reqwest::Client>, input: &ConfigInput, url: &str, persist_filepath: Option<PathBuf>) -> Result<serde_json::Value, TuliproxError> {
+    if let Some(value) = load_provider_response(cfg, input, url).await.and_then(|content| serde_json::from_str(&content).ok()) {
+        return Ok(value);
+    }
+    let value = request::get_input_json_content(client, input, url, persist_filepath).await?;
+    if let Ok(content) = serde_json::to_string(&value) {
+        store_provider_response(cfg, input, url, &content).await;
+    }
+    Ok(value)
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn get_xtream_stream_info<P>(client: Arc<reqwest::Client>,
                                        config: &Config,
@@ -97,7 +121,7 @@ where
         }
     }
 
-    if let Ok(content) = get_xtream_stream_info_content(client, info_url, input).await {
+    if let Ok((content, _)) = get_cached_xtream_stream_info_content(config, client, info_url, input).await {
         return match cluster {
             XtreamCluster::Live => Ok(content),
             XtreamCluster::Video => xtream_repository::write_and_get_xtream_vod_info(config, target, xtream_output, pli, user, &content).await,
@@ -210,7 +234,7 @@ pub async fn get_xtream_playlist(cfg: &Config, client: Arc<reqwest::Client>, inp
             let stream_file_path = crate::utils::prepare_file_path(input.persist.as_deref(), working_dir, format!("{stream}_").as_str());
 
             match futures::join!(
-                request::get_input_json_content(Arc::clone(&client), input, category_url.as_str(), category_file_path),
+                get_cached_json_content(cfg, Arc::clone(&client), input, category_url.as_str(), category_file_path),
                 request::get_input_json_content(Arc::clone(&client), input, stream_url.as_str(), stream_file_path)
             ) {
                 (Ok(category_content), Ok(stream_content)) => {
@@ -0,0 +1,558 @@
+// QR code encoder for byte mode with error correction level M, rendered as svg or png.
+// The implementation follows ISO/IEC 18004, versions are chosen automatically.
+use std::fmt::Write;
+use std::io::Write as IoWrite;
+
+const MIN_VERSION: usize = 1;
+const MAX_VERSION: usize = 40;
+const QUIET_ZONE: usize = 4;
+// error correction level M
+const FORMAT_ECC_BITS: u32 = 0;
+const ECC_CODEWORDS_PER_BLOCK: [usize; 41] = [0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28];
+const NUM_ERROR_CORRECTION_BLOCKS: [usize; 41] = [0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23, 25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49];
+const FINDER_LIKE_PATTERN: [bool; 11] = [true, false, true, true, true, false, true, false, false, false, false];
+
+fn get_bit(value: u32, index: usize) -> bool {
+    (value >> index) & 1 != 0
+}
+
+fn get_num_raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let num_align = version / 7 + 2;
+        result -= (25 * num_align - 10) * num_align - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn get_num_data_codewords(version: usize) -> usize {
+    get_num_raw_data_modules(version) / 8 - ECC_CODEWORDS_PER_BLOCK[version] * NUM_ERROR_CORRECTION_BLOCKS[version]
+}
+
+fn get_alignment_pattern_positions(version: usize) -> Vec<usize> {
+    if version == 1 {
+        return vec![];
+    }
+    let size = version * 4 + 17;
+    let num_align = version / 7 + 2;
+    let step = (version * 8 + num_align * 3 + 5) / (num_align * 4 - 4) * 2;
+    let mut result: Vec<usize> = (0..num_align - 1).map(|i| size - 7 - i * step).collect();
+    result.push(6);
+    result.reverse();
+    result
+}
+
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u8 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x1D);
+        z ^= ((y >> i) & 1) * x;
+    }
+    z
+}
+
+fn reed_solomon_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0u8; degree];
+    result[degree - 1] = 1;
+    let mut root: u8 = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn reed_solomon_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0u8; divisor.len()];
+    for byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (value, coefficient) in result.iter_mut().zip(divisor) {
+            *value ^= gf_multiply(*coefficient, factor);
+        }
+    }
+    result
+}
+
+struct BitBuffer(Vec<bool>);
+
+impl BitBuffer {
+    fn append(&mut self, value: u32, len: usize) {
+        for i in (0..len).rev() {
+            self.0.push(get_bit(value, i));
+        }
+    }
+}
+
+fn encode_data(data: &[u8], version: usize) -> Vec<u8> {
+    let capacity_bits = get_num_data_codewords(version) * 8;
+    let mut bits = BitBuffer(Vec::with_capacity(capacity_bits));
+    // byte mode indicator and character count
+    bits.append(0b0100, 4);
+    bits.append(u32::try_from(data.len()).unwrap_or_default(), if version <= 9 { 8 } else { 16 });
+    for byte in data {
+        bits.append(u32::from(*byte), 8);
+    }
+    bits.append(0, (capacity_bits - bits.0.len()).min(4));
+    bits.append(0, (8 - bits.0.len() % 8) % 8);
+    for pad in [0xEC, 0x11].iter().cycle() {
+        if bits.0.len() >= capacity_bits {
+            break;
+        }
+        bits.append(*pad, 8);
+    }
+    bits.0.chunks(8).map(|chunk| chunk.iter().fold(0u8, |acc, bit| (acc << 1) | u8::from(*bit))).collect()
+}
+
+fn add_ecc_and_interleave(data: &[u8], version: usize) -> Vec<u8> {
+    let num_blocks = NUM_ERROR_CORRECTION_BLOCKS[version];
+    let block_ecc_len = ECC_CODEWORDS_PER_BLOCK[version];
+    let raw_codewords = get_num_raw_data_modules(version) / 8;
+    let num_short_blocks = num_blocks - raw_codewords % num_blocks;
+    let short_block_len = raw_codewords / num_blocks;
+
+    let divisor = reed_solomon_divisor(block_ecc_len);
+    let mut blocks: Vec<Vec<u8>> = Vec::with_capacity(num_blocks);
+    let mut offset = 0;
+    for i in 0..num_blocks {
+        let len = short_block_len - block_ecc_len + usize::from(i >= num_short_blocks);
+        let mut block = data[offset..offset + len].to_vec();
+        offset += len;
+        let ecc = reed_solomon_remainder(&block, &divisor);
+        if i < num_short_blocks {
+            block.push(0);
+        }
+        block.extend(ecc);
+        blocks.push(block);
+    }
+
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..blocks[0].len() {
+        for (j, block) in blocks.iter().enumerate() {
+            // skip the padding byte of the short blocks
+            if i != short_block_len - block_ecc_len || j >= num_short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+struct QrCode {
+    size: usize,
+    modules: Vec<Vec<bool>>,
+    is_function: Vec<Vec<bool>>,
+}
+
+impl QrCode {
+    fn new(version: usize) -> Self {
+        let size = version * 4 + 17;
+        Self {
+            size,
+            modules: vec![vec![false; size]; size],
+            is_function: vec![vec![false; size]; size],
+        }
+    }
+
+    fn set_function_module(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y][x] = dark;
+        self.is_function[y][x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        for i in 0..self.size {
+            self.set_function_module(6, i, i.is_multiple_of(2));
+            self.set_function_module(i, 6, i.is_multiple_of(2));
+        }
+        self.draw_finder_pattern(3, 3);
+        self.draw_finder_pattern(self.size - 4, 3);
+        self.draw_finder_pattern(3, self.size - 4);
+
+        let positions = get_alignment_pattern_positions(version);
+        let num_align = positions.len();
+        for (i, x) in positions.iter().enumerate() {
+            for (j, y) in positions.iter().enumerate() {
+                // the corners are occupied by the finder patterns
+                let is_finder_corner = (i == 0 && (j == 0 || j == num_align - 1)) || (i == num_align - 1 && j == 0);
+                if !is_finder_corner {
+                    self.draw_alignment_pattern(*x, *y);
+                }
+            }
+        }
+        self.draw_format_bits(0);
+        self.draw_version(version);
+    }
+
+    fn draw_finder_pattern(&mut self, x: usize, y: usize) {
+        for yy in y.saturating_sub(4)..=(y + 4).min(self.size - 1) {
+            for xx in x.saturating_sub(4)..=(x + 4).min(self.size - 1) {
+                let dist = x.abs_diff(xx).max(y.abs_diff(yy));
+                self.set_function_module(xx, yy, dist != 2 && dist != 4);
+            }
+        }
+    }
+
+    fn draw_alignment_pattern(&mut self, x: usize, y: usize) {
+        for yy in y - 2..=y + 2 {
+            for xx in x - 2..=x + 2 {
+                self.set_function_module(xx, yy, x.abs_diff(xx).max(y.abs_diff(yy)) != 1);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, mask: u32) {
+        let data = (FORMAT_ECC_BITS << 3) | mask;
+        let mut rem = data;
+        for _ in 0..10 {
+            rem = (rem << 1) ^ ((rem >> 9) * 0x537);
+        }
+        let bits = ((data << 10) | rem) ^ 0x5412;
+        let size = self.size;
+
+        for i in 0..=5 {
+            self.set_function_module(8, i, get_bit(bits, i));
+        }
+        self.set_function_module(8, 7, get_bit(bits, 6));
+        self.set_function_module(8, 8, get_bit(bits, 7));
+        self.set_function_module(7, 8, get_bit(bits, 8));
+        for i in 9..15 {
+            self.set_function_module(14 - i, 8, get_bit(bits, i));
+        }
+
+        for i in 0..8 {
+            self.set_function_module(size - 1 - i, 8, get_bit(bits, i));
+        }
+        for i in 8..15 {
+            self.set_function_module(8, size - 15 + i, get_bit(bits, i));
+        }
+        self.set_function_module(8, size - 8, true);
+    }
+
+    fn draw_version(&mut self, version: usize) {
+        if version < 7 {
+            return;
+        }
+        let version = u32::try_from(version).unwrap_or_default();
+        let mut rem = version;
+        for _ in 0..12 {
+            rem = (rem << 1) ^ ((rem >> 11) * 0x1F25);
+        }
+        let bits = (version << 12) | rem;
+        for i in 0..18 {
+            let bit = get_bit(bits, i);
+            let a = self.size - 11 + i % 3;
+            let b = i / 3;
+            self.set_function_module(a, b, bit);
+            self.set_function_module(b, a, bit);
+        }
+    }
+
+    fn draw_codewords(&mut self, data: &[u8]) {
+        let mut index = 0;
+        let mut right = self.size - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..self.size {
+                for j in 0..2 {
+                    let x = right - j;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward { self.size - 1 - vert } else { vert };
+                    if !self.is_function[y][x] && index < data.len() * 8 {
+                        self.modules[y][x] = get_bit(u32::from(data[index >> 3]), 7 - (index & 7));
+                        index += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    fn apply_mask(&mut self, mask: u32) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                let invert = match mask {
+                    0 => (x + y) % 2 == 0,
+                    1 => y % 2 == 0,
+                    2 => x % 3 == 0,
+                    3 => (x + y) % 3 == 0,
+                    4 => (x / 3 + y / 2) % 2 == 0,
+                    5 => x * y % 2 + x * y % 3 == 0,
+                    6 => (x * y % 2 + x * y % 3) % 2 == 0,
+                    _ => ((x + y) % 2 + x * y % 3) % 2 == 0,
+                };
+                if invert && !self.is_function[y][x] {
+                    self.modules[y][x] = !self.modules[y][x];
+                }
+            }
+        }
+    }
+
+    fn get_line(&self, index: usize, horizontal: bool) -> Vec<bool> {
+        (0..self.size).map(|i| if horizontal { self.modules[index][i] } else { self.modules[i][index] }).collect()
+    }
+
+    fn get_penalty_score(&self) -> usize {
+        let mut result = 0;
+        for index in 0..self.size {
+            for horizontal in [true, false] {
+                let line = self.get_line(index, horizontal);
+                // runs of the same color
+                let mut run_len = 1;
+                for i in 1..=line.len() {
+                    if i < line.len() && line[i] == line[i - 1] {
+                        run_len += 1;
+                    } else {
+                        if run_len >= 5 {
+                            result += run_len - 2;
+                        }
+                        run_len = 1;
+                    }
+                }
+                // patterns which look like finder patterns
+                for window in line.windows(FINDER_LIKE_PATTERN.len()) {
+                    if window == FINDER_LIKE_PATTERN || window.iter().rev().eq(FINDER_LIKE_PATTERN.iter()) {
+                        result += 40;
+                    }
+                }
+            }
+        }
+        for y in 0..self.size - 1 {
+            for x in 0..self.size - 1 {
+                let color = self.modules[y][x];
+                if color == self.modules[y][x + 1] && color == self.modules[y + 1][x] && color == self.modules[y + 1][x + 1] {
+                    result += 3;
+                }
+            }
+        }
+        let total = self.size * self.size;
+        let dark = self.modules.iter().flatten().filter(|dark| **dark).count();
+        let deviation = (dark * 100 / total).abs_diff(50);
+        result + deviation / 5 * 10
+    }
+
+    fn encode(text: &str) -> Option<Self> {
+        let data = text.as_bytes();
+        let version = (MIN_VERSION..=MAX_VERSION).find(|version| {
+            let count_bits = if *version <= 9 { 8 } else { 16 };
+            data.len() < (1 << count_bits) && 4 + count_bits + data.len() * 8 <= get_num_data_codewords(*version) * 8
+        })?;
+        let codewords = add_ecc_and_interleave(&encode_data(data, version), version);
+
+        let mut qr = Self::new(version);
+        qr.draw_function_patterns(version);
+        qr.draw_codewords(&codewords);
+
+        let mut best_mask = 0;
+        let mut min_penalty = usize::MAX;
+        for mask in 0..8 {
+            qr.apply_mask(mask);
+            qr.draw_format_bits(mask);
+            let penalty = qr.get_penalty_score();
+            if penalty < min_penalty {
+                best_mask = mask;
+                min_penalty = penalty;
+            }
+            // xor again to undo the mask
+            qr.apply_mask(mask);
+        }
+        qr.apply_mask(best_mask);
+        qr.draw_format_bits(best_mask);
+        Some(qr)
+    }
+
+    fn to_svg(&self) -> String {
+        let dimension = self.size + QUIET_ZONE * 2;
+        let mut path = String::new();
+        for (y, row) in self.modules.iter().enumerate() {
+            for (x, dark) in row.iter().enumerate() {
+                if *dark {
+                    let _ = write!(path, "M{},{}h1v1h-1z", x + QUIET_ZONE, y + QUIET_ZONE);
+                }
+            }
+        }
+        format!(r##"<svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 {dimension} {dimension}" shape-rendering="crispEdges"><rect width="100%" height="100%" fill="#FFFFFF"/><path d="{path}" fill="#000000"/></svg>"##)
+    }
+
+    // 1 bit grayscale png, each module is scale x scale pixels
+    fn to_png(&self, scale: usize) -> Option<Vec<u8>> {
+        let dimension = (self.size + QUIET_ZONE * 2) * scale;
+        let row_len = dimension.div_ceil(8);
+        let mut raw = Vec::with_capacity((row_len + 1) * dimension);
+        for y in 0..dimension {
+            // filter type none
+            raw.push(0);
+            let mut row = vec![0xFFu8; row_len];
+            let module_y = (y / scale).checked_sub(QUIET_ZONE).filter(|my| *my < self.size);
+            if let Some(my) = module_y {
+                for x in 0..dimension {
+                    let dark = (x / scale).checked_sub(QUIET_ZONE).is_some_and(|mx| mx < self.size && self.modules[my][mx]);
+                    if dark {
+                        row[x / 8] &= !(0x80 >> (x % 8));
+                    }
+                }
+            }
+            raw.extend_from_slice(&row);
+        }
+        let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&raw).ok()?;
+        let data = encoder.finish().ok()?;
+
+        let size = u32::try_from(dimension).ok()?;
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&size.to_be_bytes());
+        header.extend_from_slice(&size.to_be_bytes());
+        // bit depth 1, grayscale, deflate, no filter, no interlace
+        header.extend_from_slice(&[1, 0, 0, 0, 0]);
+
+        let mut png = vec![0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1A, b'\n'];
+        write_png_chunk(&mut png, *b"IHDR", &header)?;
+        write_png_chunk(&mut png, *b"IDAT", &data)?;
+        write_png_chunk(&mut png, *b"IEND", &[])?;
+        Some(png)
+    }
+}
+
+fn write_png_chunk(png: &mut Vec<u8>, kind: [u8; 4], data: &[u8]) -> Option<()> {
+    png.extend_from_slice(&u32::try_from(data.len()).ok()?.to_be_bytes());
+    png.extend_from_slice(&kind);
+    png.extend_from_slice(data);
+    let mut crc = flate2::Crc::new();
+    crc.update(&kind);
+    crc.update(data);
+    png.extend_from_slice(&crc.sum().to_be_bytes());
+    Some(())
+}
+
+/// Encodes the text as QR code svg image, returns `None` if the text is too long.
+pub fn qr_code_svg(text: &str) -> Option<String> {
+    QrCode::encode(text).map(|qr| qr.to_svg())
+}
+
+/// Encodes the text as QR code png image with `scale` pixels per module, returns `None` if the text is too long.
+pub fn qr_code_png(text: &str, scale: usize) -> Option<Vec<u8>> {
+    QrCode::encode(text).and_then(|qr| qr.to_png(scale.max(1)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // reads the codewords back like a decoder, checks the error correction and returns the data codewords
+    fn decode(qr: &mut QrCode, version: usize, mask: u32) -> Vec<u8> {
+        qr.apply_mask(mask);
+        let mut bits = vec![];
+        let mut right = qr.size - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vert in 0..qr.size {
+                for j in 0..2 {
+                    let x = right - j;
+                    let y = if (right + 1) & 2 == 0 { qr.size - 1 - vert } else { vert };
+                    if !qr.is_function[y][x] {
+                        bits.push(qr.modules[y][x]);
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+        let codewords: Vec<u8> = bits.chunks_exact(8).map(|c| c.iter().fold(0u8, |acc, bit| (acc << 1) | u8::from(*bit))).collect();
+        assert_eq!(codewords.len(), get_num_raw_data_modules(version) / 8);
+
+        let num_blocks = NUM_ERROR_CORRECTION_BLOCKS[version];
+        let num_short_blocks = num_blocks - codewords.len() % num_blocks;
+        let short_data_len = codewords.len() / num_blocks - ECC_CODEWORDS_PER_BLOCK[version];
+        let mut blocks: Vec<Vec<u8>> = vec![vec![]; num_blocks];
+        let mut iter = codewords.iter();
+        for i in 0..=short_data_len {
+            for (j, block) in blocks.iter_mut().enumerate() {
+                if i < short_data_len || j >= num_short_blocks {
+                    block.push(*iter.next().unwrap());
+                }
+            }
+        }
+        let ecc_codewords: Vec<u8> = iter.copied().collect();
+        let divisor = reed_solomon_divisor(ECC_CODEWORDS_PER_BLOCK[version]);
+        for (j, block) in blocks.iter().enumerate() {
+            let ecc: Vec<u8> = ecc_codewords.iter().skip(j).step_by(num_blocks).copied().collect();
+            assert_eq!(reed_solomon_remainder(block, &divisor), ecc);
+        }
+        blocks.concat()
+    }
+
+    #[test]
+    fn test_format_bits() {
+        let mut qr = QrCode::new(1);
+        qr.draw_format_bits(0);
+        let bits: String = (0..8).map(|i| if qr.modules[8][qr.size - 1 - i] { '1' } else { '0' }).collect();
+        // format information of level M and mask 0 is 101010000010010, bit 0 is drawn first
+        assert_eq!(bits, "01001000");
+    }
+
+    #[test]
+    fn test_qr_code() {
+        for text in ["http://tv.local:8901/get.php?username=bob&password=secret&type=m3u_plus", "A", &"x".repeat(300)] {
+            let mut qr = QrCode::encode(text).unwrap();
+            let version = (qr.size - 17) / 4;
+            let mask = (0..8).find(|mask| {
+                let mut probe = QrCode::new(version);
+                probe.draw_format_bits(*mask);
+                (0..8).all(|i| probe.modules[8][probe.size - 1 - i] == qr.modules[8][qr.size - 1 - i])
+            }).unwrap();
+            let data = decode(&mut qr, version, mask);
+            let bits: Vec<bool> = data.iter().flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 != 0)).collect();
+            let count_bits = if version <= 9 { 8 } else { 16 };
+            let read = |offset: usize, len: usize| bits[offset..offset + len].iter().fold(0usize, |acc, bit| (acc << 1) | usize::from(*bit));
+            assert_eq!(read(0, 4), 0b0100);
+            assert_eq!(read(4, count_bits), text.len());
+            let payload: Vec<u8> = (0..text.len()).map(|i| u8::try_from(read(4 + count_bits + i * 8, 8)).unwrap()).collect();
+            assert_eq!(payload, text.as_bytes());
+        }
+        assert!(qr_code_svg("hello").unwrap().starts_with("<svg"));
+        assert!(qr_code_svg(&"x".repeat(3000)).is_none());
+    }
+
+    #[test]
+    fn test_qr_code_png() {
+        let qr = QrCode::encode("hello").unwrap();
+        let scale = 3;
+        let png = qr_code_png("hello", scale).unwrap();
+        assert_eq!(&png[0..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        let dimension = (qr.size + QUIET_ZONE * 2) * scale;
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()) as usize, dimension);
+        let idat_len = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&png[37..41], b"IDAT");
+        let mut decoder = flate2::write::ZlibDecoder::new(Vec::new());
+        decoder.write_all(&png[41..41 + idat_len]).unwrap();
+        let raw = decoder.finish().unwrap();
+        let row_len = dimension.div_ceil(8) + 1;
+        assert_eq!(raw.len(), row_len * dimension);
+        let pixel = |x: usize, y: usize| raw[y * row_len + 1 + x / 8] & (0x80 >> (x % 8)) == 0;
+        for y in 0..qr.size {
+            for x in 0..qr.size {
+                let (px, py) = ((x + QUIET_ZONE) * scale + 1, (y + QUIET_ZONE) * scale + 1);
+                assert_eq!(pixel(px, py), qr.modules[y][x]);
+            }
+        }
+        assert!(!pixel(0, 0));
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xAE, 0x42, 0x60, 0x82]));
+    }
+}
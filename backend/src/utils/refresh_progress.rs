@@ -0,0 +1,105 @@
+use std::collections::BTreeMap;
+use std::sync::{LazyLock, Mutex};
+use tokio::sync::broadcast;
+
+const PROGRESS_CHANNEL_SIZE: usize = 64;
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct TargetProgress {
+    pub target: String,
+    pub stage: String,
+    pub percent: u8,
+    // channels processed by the target pipes
+    pub items: usize,
+    pub finished: bool,
+    // unix timestamp in milliseconds
+    pub ts: i64,
+}
+
+impl TargetProgress {
+    pub fn new(target: &str, stage: &str, percent: u8, items: usize) -> Self {
+        Self {
+            target: target.to_string(),
+            stage: stage.to_string(),
+            percent,
+            items,
+            finished: false,
+            ts: chrono::Utc::now().timestamp_millis(),
+        }
+    }
+}
+
+// The progress of the running target refreshes, every update is also sent to the subscribers.
+pub struct RefreshProgress {
+    targets: Mutex<BTreeMap<String, TargetProgress>>,
+    sender: broadcast::Sender<TargetProgress>,
+}
+
+impl RefreshProgress {
+    fn new() -> Self {
+        Self {
+            targets: Mutex::new(BTreeMap::new()),
+            sender: broadcast::channel(PROGRESS_CHANNEL_SIZE).0,
+        }
+    }
+
+    // the update is sent while the lock is held, a subscriber gets every update either from the snapshot or the channel
+    pub fn update(&self, progress: TargetProgress) {
+        if let Ok(mut targets) = self.targets.lock() {
+            if progress.finished {
+                targets.remove(&progress.target);
+            } else {
+                targets.insert(progress.target.clone(), progress.clone());
+            }
+            // sending only fails without subscribers
+            let _ = self.sender.send(progress);
+        }
+    }
+
+    /// Marks a running target as finished, nothing is sent if the target is not running.
+    pub fn finish(&self, target: &str) {
+        if let Ok(mut targets) = self.targets.lock() {
+            if let Some(mut progress) = targets.remove(target) {
+                progress.percent = 100;
+                progress.finished = true;
+                progress.ts = chrono::Utc::now().timestamp_millis();
+                let _ = self.sender.send(progress);
+            }
+        }
+    }
+
+    pub fn get_running(&self) -> Vec<TargetProgress> {
+        self.targets.lock().map(|targets| targets.values().cloned().collect()).unwrap_or_default()
+    }
+
+    pub fn subscribe(&self) -> (Vec<TargetProgress>, broadcast::Receiver<TargetProgress>) {
+        match self.targets.lock() {
+            Ok(targets) => (targets.values().cloned().collect(), self.sender.subscribe()),
+            Err(_) => (vec![], self.sender.subscribe()),
+        }
+    }
+}
+
+pub static REFRESH_PROGRESS: LazyLock<RefreshProgress> = LazyLock::new(RefreshProgress::new);
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::refresh_progress::{RefreshProgress, TargetProgress};
+
+    #[test]
+    fn test_refresh_progress() {
+        let progress = RefreshProgress::new();
+        progress.update(TargetProgress::new("sport", "Fetching inputs", 0, 0));
+        let (running, mut receiver) = progress.subscribe();
+        assert_eq!(running.len(), 1);
+        progress.update(TargetProgress::new("sport", "Sorted playlists", 40, 120));
+        assert_eq!(progress.get_running()[0].percent, 40);
+        progress.finish("sport");
+        progress.finish("sport");
+        assert!(progress.get_running().is_empty());
+        assert_eq!(receiver.try_recv().unwrap().stage, "Sorted playlists");
+        let finished = receiver.try_recv().unwrap();
+        assert!(finished.finished && finished.percent == 100);
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -1,5 +1,6 @@
 use log::{debug, log_enabled, Level};
 use std::time::{Duration, Instant};
+use crate::utils::{TargetProgress, REFRESH_PROGRESS};
 
 fn format_duration(duration: Duration) -> String {
     let millis = duration.as_millis();
@@ -17,10 +18,29 @@ fn format_duration(duration: Duration) -> String {
     }
 }
 
+// The steps of a target refresh are published as progress.
+struct StepProgress {
+    target: String,
+    steps: usize,
+    step: usize,
+    stage: String,
+    items: usize,
+}
+
+impl StepProgress {
+    #[allow(clippy::cast_possible_truncation)]
+    fn publish(&self) {
+        // 100 is sent when the refresh is finished
+        let percent = (self.step * 100 / self.steps.max(1)).min(99) as u8;
+        REFRESH_PROGRESS.update(TargetProgress::new(&self.target, &self.stage, percent, self.items));
+    }
+}
+
 pub struct StepMeasure {
     enabled: bool,
     msg: String,
     start: Instant,
+    progress: Option<StepProgress>,
 }
 
 impl StepMeasure {
@@ -29,10 +49,32 @@ impl StepMeasure {
             enabled: log_enabled!(Level::Debug),
             msg: msg.to_string(),
             start: Instant::now(),
+            progress: None,
+        }
+    }
+
+    /// Measures the refresh of a target and publishes each step as progress of the target.
+    pub fn with_progress(msg: &str, target: &str, steps: usize) -> Self {
+        let progress = StepProgress { target: target.to_string(), steps, step: 0, stage: msg.to_string(), items: 0 };
+        progress.publish();
+        let mut measure = Self::new(msg);
+        measure.progress = Some(progress);
+        measure
+    }
+
+    pub fn set_items(&mut self, items: usize) {
+        if let Some(progress) = self.progress.as_mut() {
+            progress.items = items;
+            progress.publish();
         }
     }
 
     pub fn tick(&mut self, msg: &str) {
+        if let Some(progress) = self.progress.as_mut() {
+            progress.step += 1;
+            progress.stage = msg.to_string();
+            progress.publish();
+        }
         if self.enabled {
             debug!("{} in {}", self.msg, format_duration(self.start.elapsed()));
             self.msg = msg.to_string();
@@ -41,6 +83,9 @@ impl StepMeasure {
     }
 
     pub fn stop(&mut self) {
+        if let Some(progress) = self.progress.take() {
+            REFRESH_PROGRESS.finish(&progress.target);
+        }
         if self.enabled && !self.msg.is_empty() {
             debug!("{} in {}", self.msg, format_duration( self.start.elapsed()));
             self.enabled = false;
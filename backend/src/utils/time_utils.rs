@@ -0,0 +1,133 @@
+use chrono::{DateTime, FixedOffset, Local, NaiveDateTime, Offset, TimeDelta};
+
+const TIMESHIFT_START_FORMAT: &str = "%Y-%m-%d:%H-%M";
+const CATCHUP_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Parses a fixed utc offset like `UTC`, `+02:00`, `-0530`, `+2` or `UTC+1`.
+/// Named time zones like `Europe/Berlin` are not supported, they need a time zone database.
+pub fn parse_utc_offset(timezone: &str) -> Option<FixedOffset> {
+    let timezone = timezone.trim().to_uppercase();
+    let offset = timezone.strip_prefix("UTC")
+        .or_else(|| timezone.strip_prefix("GMT"))
+        .unwrap_or(&timezone).trim();
+    if offset.is_empty() || offset == "Z" {
+        return FixedOffset::east_opt(0);
+    }
+    let (sign, value) = match offset.as_bytes().first()? {
+        b'+' => (1, &offset[1..]),
+        b'-' => (-1, &offset[1..]),
+        _ => return None,
+    };
+    // only ascii digits, the split below is at a byte index
+    if !value.bytes().all(|b| b.is_ascii_digit() || b == b':') {
+        return None;
+    }
+    let (hours, minutes) = match value.split_once(':') {
+        Some((hours, minutes)) => (hours, minutes),
+        None if value.len() == 4 => value.split_at(2),
+        None => (value, "0"),
+    };
+    let hours: i32 = hours.parse().ok()?;
+    let minutes: i32 = minutes.parse().ok()?;
+    if hours > 14 || minutes > 59 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Returns the offset of the server timezone, the host timezone is used if it can't be parsed.
+pub fn get_server_utc_offset(timezone: &str) -> FixedOffset {
+    parse_utc_offset(timezone).unwrap_or_else(|| Local::now().offset().fix())
+}
+
+/// Converts a timeshift start `YYYY-MM-DD:HH-MM` from one utc offset into another.
+pub fn convert_timeshift_start(start: &str, from: FixedOffset, to: FixedOffset) -> Option<String> {
+    let start_time = NaiveDateTime::parse_from_str(start.trim(), TIMESHIFT_START_FORMAT).ok()?;
+    let utc = start_time.and_local_timezone(from).single()?;
+    Some(utc.with_timezone(&to).format(TIMESHIFT_START_FORMAT).to_string())
+}
+
+/// Formats a unix timestamp like the catchup table `start` and `end` fields.
+pub fn format_catchup_time(timestamp: i64, offset: FixedOffset) -> Option<String> {
+    DateTime::from_timestamp(timestamp, 0).map(|dt| dt.with_timezone(&offset).format(CATCHUP_TIME_FORMAT).to_string())
+}
+
+// `-2:30`(-2h30m), `1:45` (1h45m), `+0:15` (15m), `2` (2h), `:30` (30m), `:3` (3m), `2:` (3h)
+pub fn parse_timeshift(time_shift: Option<&String>) -> Option<i32> {
+    time_shift.and_then(|offset| {
+        let sign_factor = if offset.starts_with('-') { -1 } else { 1 };
+        let offset = offset.trim_start_matches(&['-', '+'][..]);
+
+        let parts: Vec<&str> = offset.split(':').collect();
+        let hours: i32 = parts.first().and_then(|h| h.parse().ok()).unwrap_or(0);
+        let minutes: i32 = parts.get(1).and_then(|m| m.parse().ok()).unwrap_or(0);
+
+        let total_minutes = hours * 60 + minutes;
+        (total_minutes > 0).then_some(sign_factor * total_minutes)
+    })
+}
+
+/// Formats a unix timestamp as xmltv time `20250601203000 +0000`.
+pub fn format_xmltv_time(timestamp: i64) -> Option<String> {
+    DateTime::from_timestamp(timestamp, 0).map(|dt| dt.format("%Y%m%d%H%M%S +0000").to_string())
+}
+
+/// Parses a xmltv time `20250601203000 +0000` into a unix timestamp, a time without offset is utc.
+pub fn parse_xmltv_time(date_time: &str) -> Option<i64> {
+    let date_time = date_time.trim();
+    DateTime::parse_from_str(date_time, "%Y%m%d%H%M%S %z").map(|dt| dt.timestamp())
+        .or_else(|_| NaiveDateTime::parse_from_str(date_time, "%Y%m%d%H%M%S").map(|dt| dt.and_utc().timestamp()))
+        .ok()
+}
+
+/// Shifts a xmltv time `20250601203000 +0000` by the `correction`, the timezone is kept.
+pub fn time_correct(date_time: &str, correction: &TimeDelta) -> String {
+    // Split the dateTime string into date and time parts
+    let date_time_split: Vec<&str> = date_time.split(' ').collect();
+    if date_time_split.len() != 2 {
+        return date_time.to_string();
+    }
+
+    // Parse the datetime string
+    NaiveDateTime::parse_from_str(date_time_split[0], "%Y%m%d%H%M%S").map_or_else(|_| date_time.to_string(), |native_dt| {
+        let corrected_dt = native_dt + *correction;
+        // Format the corrected datetime back to string
+        let formatted_dt = corrected_dt.format("%Y%m%d%H%M%S").to_string();
+        let result = format!("{} {}", formatted_dt, date_time_split[1]);
+        result
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::utils::{convert_timeshift_start, format_catchup_time, parse_utc_offset, parse_xmltv_time};
+
+    #[test]
+    fn test_parse_xmltv_time() {
+        assert_eq!(parse_xmltv_time("20250601203000 +0000"), Some(1_748_809_800));
+        assert_eq!(parse_xmltv_time("20250601223000 +0200"), Some(1_748_809_800));
+        assert_eq!(parse_xmltv_time("20250601203000"), Some(1_748_809_800));
+        assert_eq!(parse_xmltv_time("2025-06-01"), None);
+    }
+
+    #[test]
+    fn test_parse_utc_offset() {
+        assert_eq!(parse_utc_offset("UTC").map(|o| o.local_minus_utc()), Some(0));
+        assert_eq!(parse_utc_offset("+02:00").map(|o| o.local_minus_utc()), Some(7200));
+        assert_eq!(parse_utc_offset("utc-5").map(|o| o.local_minus_utc()), Some(-18000));
+        assert_eq!(parse_utc_offset("GMT+0530").map(|o| o.local_minus_utc()), Some(19800));
+        assert_eq!(parse_utc_offset("Europe/Berlin"), None);
+        assert_eq!(parse_utc_offset("+25"), None);
+        assert_eq!(parse_utc_offset("+1é1"), None);
+        assert_eq!(parse_utc_offset("-é:00"), None);
+    }
+
+    #[test]
+    fn test_convert_timeshift_start() {
+        let user = parse_utc_offset("-05:00").unwrap();
+        let server = parse_utc_offset("+01:00").unwrap();
+        assert_eq!(convert_timeshift_start("2025-06-01:20-30", user, server).as_deref(), Some("2025-06-02:02-30"));
+        assert_eq!(convert_timeshift_start("invalid", user, server), None);
+        assert_eq!(format_catchup_time(1_748_809_800, user).as_deref(), Some("2025-06-01 15:30:00"));
+    }
+}
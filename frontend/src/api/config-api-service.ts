@@ -1,11 +1,12 @@
 import ApiService, {DefaultApiService} from "./api-service";
 import {Observable} from "rxjs";
-import ServerConfig, {ApiProxyServerInfo, ServerMainConfig, TargetUser} from "../model/server-config";
+import ServerConfig, {ApiProxyServerInfo, QrCodeFormat, QrCodeKind, ServerMainConfig, TargetUser} from "../model/server-config";
 
 const SERVER_CONFIG_API_PATH = 'config';
 const SERVER_CONFIG_MAIN_API_PATH = 'config/main';
 const SERVER_CONFIG_TARGET_USER_API_PATH = 'config/user';
 const SERVER_CONFIG_SERVER_INFO_API_PATH = 'config/apiproxy';
+const QR_CODE_API_PATH = 'qrcode';
 
 export default interface ConfigApiService extends ApiService {
     getServerConfig(): Observable<ServerConfig>;
@@ -15,6 +16,8 @@ export default interface ConfigApiService extends ApiService {
     saveTargetUser(targetUser: TargetUser[]): Observable<any>;
 
     saveApiProxyConfig(serverInfo: ApiProxyServerInfo[]): Observable<any>;
+
+    getQrCodeUrl(username: string, kind: QrCodeKind, format?: QrCodeFormat, ttlMins?: number): Observable<string>;
 }
 
 export class DefaultConfigApiService extends DefaultApiService implements ConfigApiService {
@@ -34,4 +37,8 @@ export class DefaultConfigApiService extends DefaultApiService implements Config
         return this.post<ServerConfig>(SERVER_CONFIG_SERVER_INFO_API_PATH, serverInfo);
     }
 
+    getQrCodeUrl(username: string, kind: QrCodeKind, format?: QrCodeFormat, ttlMins?: number): Observable<string> {
+        return this.post<string>(QR_CODE_API_PATH, {username, kind, format, ttl_mins: ttlMins});
+    }
+
 }
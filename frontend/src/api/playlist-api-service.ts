@@ -1,11 +1,13 @@
 import ApiService, {DefaultApiService} from "./api-service";
 import {Observable, throwError} from "rxjs";
 import {PlaylistRequest} from "../model/playlist-request";
-import {PlaylistCategories, PlaylistItem} from "../model/playlist";
+import {EpgMatchKind, EpgMatchReport, PlaylistCategories, PlaylistItem, RefreshHistory} from "../model/playlist";
 
 const PLAYLIST_API_PATH = 'playlist';
 const TARGET_UPDATE_API_PATH = 'playlist/update';
 const WEBPLAYER_URL_API_PATH = 'playlist/webplayer';
+const REFRESH_HISTORY_API_PATH = 'playlist/history';
+const EPG_MATCH_API_PATH = 'playlist/epg_match';
 
 export default interface PlaylistApiService extends ApiService {
     getPlaylist(req: PlaylistRequest): Observable<PlaylistCategories>;
@@ -13,6 +15,10 @@ export default interface PlaylistApiService extends ApiService {
     updateTargets(targets: string[]): Observable<any>;
 
     getWebPlayerUrl(item: PlaylistItem, req: PlaylistRequest): Observable<string>;
+
+    getRefreshHistory(targetId: number, limit?: number): Observable<RefreshHistory>;
+
+    getEpgMatchReport(targetId: number, kinds?: EpgMatchKind[]): Observable<EpgMatchReport>;
 }
 
 export class DefaultPlaylistApiService extends DefaultApiService implements PlaylistApiService {
@@ -32,4 +38,14 @@ export class DefaultPlaylistApiService extends DefaultApiService implements Play
         return this.post(WEBPLAYER_URL_API_PATH + '/' + req.sourceId, item);
     }
 
+    getRefreshHistory(targetId: number, limit?: number): Observable<RefreshHistory> {
+        const query = limit !== undefined ? '?limit=' + limit : '';
+        return this.get<RefreshHistory>(REFRESH_HISTORY_API_PATH + '/' + targetId + query);
+    }
+
+    getEpgMatchReport(targetId: number, kinds?: EpgMatchKind[]): Observable<EpgMatchReport> {
+        const query = kinds?.length ? '?kind=' + kinds.join(',') : '';
+        return this.get<EpgMatchReport>(EPG_MATCH_API_PATH + '/' + targetId + query);
+    }
+
 }
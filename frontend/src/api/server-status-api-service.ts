@@ -1,14 +1,17 @@
 import {Observable} from "rxjs";
 import ApiService, {DefaultApiService} from "./api-service";
-import {ServerIpCheck, ServerStatus} from "../model/server-status";
+import {LogEntry, ServerIpCheck, ServerStatus} from "../model/server-status";
 
 const STATUS_PATH = "status";
 const IPCHECK_PATH = "ipinfo";
+const LOGS_PATH = "logs";
 
 export default interface ServerStatusApiService extends ApiService {
     getServerStatus(): Observable<ServerStatus>;
 
     getServerIpCheck(): Observable<ServerIpCheck>;
+
+    getLogs(level: string, since?: number): Observable<LogEntry[]>;
 }
 
 export class DefaultServerStatusApiService extends DefaultApiService implements ServerStatusApiService {
@@ -20,4 +23,12 @@ export class DefaultServerStatusApiService extends DefaultApiService implements
     getServerIpCheck(): Observable<ServerIpCheck> {
         return this.get<ServerIpCheck>(IPCHECK_PATH);
     }
+
+    getLogs(level: string, since?: number): Observable<LogEntry[]> {
+        const params = new URLSearchParams({level});
+        if (since !== undefined) {
+            params.set('since', String(since));
+        }
+        return this.get<LogEntry[]>(LOGS_PATH + '?' + params.toString());
+    }
 }
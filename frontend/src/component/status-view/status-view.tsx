@@ -1,13 +1,15 @@
 import {useServices} from "../../provider/service-provider";
 import useTranslator from "../../hook/use-translator";
 import React, {useCallback, useEffect, useState} from "react";
-import {ServerIpCheck, ServerStatus} from "../../model/server-status";
+import {LogEntry, ServerIpCheck, ServerStatus} from "../../model/server-status";
 import {interval} from "rxjs";
 import {first} from "rxjs/operators";
 import './status-view.scss';
 import {getIconByName} from "../../icons/icons";
 
 const REQUEST_INTERVAL = 5000;
+const MAX_LOG_ENTRIES = 500;
+const LOG_LEVELS = ["error", "warn", "info", "debug", "trace"];
 const STATUS_COLUMNS = [
     "status",
     "version",
@@ -17,13 +19,17 @@ const STATUS_COLUMNS = [
     "cache",
     "active_users",
     "active_user_connections",
-    "active_provider_connections"];
+    "active_provider_connections",
+    "stale_targets"];
+const JSON_COLUMNS = ["active_provider_connections", "stale_targets"];
 
 export default function StatusView() {
     const services = useServices();
     const translate = useTranslator();
     const [status, setStatus] = useState<ServerStatus>();
     const [ipCheck, setIpCheck] = useState<any>();
+    const [logLevel, setLogLevel] = useState<string>("info");
+    const [logs, setLogs] = useState<LogEntry[]>([]);
 
     const checkIp = useCallback(() => {
         services.status().getIpCheck().pipe(first()).subscribe({
@@ -46,6 +52,27 @@ export default function StatusView() {
         return () => sub.unsubscribe();
     }, [services, checkIp]);
 
+    useEffect(() => {
+        // only the entries after the last received entry are requested
+        let since: number | undefined = undefined;
+        setLogs([]);
+        const fetchLogs = () => services.status().getLogs(logLevel, since).pipe(first()).subscribe({
+            next: (entries: LogEntry[]) => {
+                if (entries.length) {
+                    since = entries[entries.length - 1].ts;
+                    setLogs(current => current.concat(entries).slice(-MAX_LOG_ENTRIES));
+                }
+            },
+            error: () => {
+            }
+        });
+        fetchLogs();
+        const sub = interval(REQUEST_INTERVAL).subscribe(fetchLogs);
+        return () => sub.unsubscribe();
+    }, [services, logLevel]);
+
+    const handleLogLevel = useCallback((evt: any) => setLogLevel(evt.target.value), []);
+
     return <div className={'status-view'}>
         {ipCheck && <>
             <div className={'status-view__section-title'}>{translate("LABEL.IP")} <button onClick={checkIp}>{getIconByName('Refresh')}</button></div>
@@ -61,10 +88,20 @@ export default function StatusView() {
             {status && STATUS_COLUMNS.map(col => <React.Fragment key={'status.' + col}>
                     <div className={'status-view__col-label'}>{translate('LABEL.' + col.toUpperCase())}</div>
                     <div className={'status-view__col-value'}>{
-                        (col === "active_provider_connections") ? JSON.stringify((status as any)?.[col]) : (status as any)?.[col]
+                        JSON_COLUMNS.includes(col) ? JSON.stringify((status as any)?.[col]) : (status as any)?.[col]
                     }</div>
                 </React.Fragment>
             )}
         </div>
+        <div className={'status-view__section-title'}>{translate("LABEL.LOGS")}
+            <select value={logLevel} onChange={handleLogLevel}>
+                {LOG_LEVELS.map(level => <option key={level} value={level}>{level}</option>)}
+            </select>
+        </div>
+        <div className={'status-view__logs'}>
+            {logs.map((entry, idx) => <div key={entry.ts + '.' + idx} className={'status-view__log status-view__log-' + entry.level.toLowerCase()}>
+                {new Date(entry.ts).toLocaleString()} {entry.level} {entry.module} {entry.message}
+            </div>)}
+        </div>
     </div>
 }
\ No newline at end of file
@@ -11,6 +11,7 @@ import DateUtils from "../../utils/date-utils";
 import PlaylistFilter from "../playlist-filter/playlist-filter";
 import UserEditor, {STATUS_OPTIONS} from "../user-editor/user-editor";
 import TextGenerator from "../../utils/text-generator";
+import {first, forkJoin} from "rxjs";
 
 const renderExpDate = (value: any, hidden?: boolean) => {
     if (!value) {
@@ -172,6 +173,7 @@ export default function UserView(props: UserViewProps) {
     const [activeTarget, setActiveTarget] = useState<string>(undefined);
     const [tabs, setTabs] = useState<TabSetTab[]>([]);
     const [showHiddenFields, setShowHiddenFields] = useState<Record<string, boolean>>({});
+    const [qrCodes, setQrCodes] = useState<{ username: string, m3u: string, xtream: string }>(undefined);
     const [filteredUser, setFilteredUser] = useState<Record<string, {
         filter: string,
         regexp: boolean,
@@ -258,6 +260,20 @@ export default function UserView(props: UserViewProps) {
         }
     }, [targets]);
 
+    const handleUserQrCode = useCallback((evt: any) => {
+        const username = evt.target.dataset.user;
+        forkJoin({
+            m3u: services.config().getQrCodeUrl(username, 'm3u'),
+            xtream: services.config().getQrCodeUrl(username, 'xtream'),
+        }).pipe(first()).subscribe({
+            next: (urls) => {
+                setQrCodes({username, ...urls});
+                (document.getElementById("qrcode-dialog") as any).showModal();
+            },
+            error: () => enqueueSnackbar(translate('MESSAGES.QR_CODE_FAIL'), {variant: 'error'})
+        });
+    }, [services, enqueueSnackbar, translate]);
+
     const handleSave = useCallback(() => {
         const usernames: any = {};
         const tokens: any = {};
@@ -466,6 +482,10 @@ export default function UserView(props: UserViewProps) {
                                                       onClick={handleUserEdit}>
                                                     {getIconByName('Edit')}
                                                 </span>
+                                                <span data-target={target.target} data-user={usr.username}
+                                                      onClick={handleUserQrCode}>
+                                                    {getIconByName('QrCode')}
+                                                </span>
                                             </div>
                                         </div>
                                         {COLUMNS.map(c => <div
@@ -500,5 +520,16 @@ export default function UserView(props: UserViewProps) {
             <button className={'button'}
                     onClick={() => (document.getElementById("comment-dialog") as any).close()}>{translate('LABEL.CLOSE')}</button>
         </dialog>
+        <dialog id="qrcode-dialog" className={'qrcode-dialog'}>
+            <label>{qrCodes?.username}</label>
+            <div className={'qrcode-dialog__codes'}>
+                {qrCodes && <figure><img src={qrCodes.m3u} alt={'m3u'}/>
+                    <figcaption>{translate('LABEL.M3U')}</figcaption></figure>}
+                {qrCodes && <figure><img src={qrCodes.xtream} alt={'xtream'}/>
+                    <figcaption>{translate('LABEL.XTREAM_CODES')}</figcaption></figure>}
+            </div>
+            <button className={'button'}
+                    onClick={() => (document.getElementById("qrcode-dialog") as any).close()}>{translate('LABEL.CLOSE')}</button>
+        </dialog>
     </div>
 }
\ No newline at end of file
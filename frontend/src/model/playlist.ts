@@ -78,4 +78,65 @@ export interface UserPlaylistTargetCategories {
 export interface UserPlaylistCategories {
     xtream: UserPlaylistTargetCategories,
     m3u: UserPlaylistTargetCategories,
-}
\ No newline at end of file
+}
+
+export interface RefreshResult {
+    ts: number,
+    success: boolean,
+    duration: number,
+    groups: number,
+    live: number,
+    vod: number,
+    series: number,
+    errors: number,
+}
+
+export interface CountTrend {
+    first: number,
+    last: number,
+    min: number,
+    max: number,
+    change: number,
+    change_percent: number,
+}
+
+export interface RefreshTrend {
+    runs: number,
+    failures: number,
+    live: CountTrend,
+    vod: CountTrend,
+    series: CountTrend,
+    channels: CountTrend,
+}
+
+export interface RefreshHistory {
+    target: string,
+    entries: RefreshResult[],
+    trend?: RefreshTrend,
+}
+
+export type EpgMatchKind = 'id' | 'alias' | 'normalized' | 'fuzzy' | 'unmatched';
+
+export interface EpgChannelMatch {
+    input: string,
+    group: string,
+    name: string,
+    epg_id?: string,
+    kind: EpgMatchKind,
+    score?: number,
+}
+
+export interface EpgMatchSummary {
+    id: number,
+    alias: number,
+    normalized: number,
+    fuzzy: number,
+    unmatched: number,
+}
+
+export interface EpgMatchReport {
+    target: string,
+    ts: number,
+    summary: EpgMatchSummary,
+    channels: EpgChannelMatch[],
+}
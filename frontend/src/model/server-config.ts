@@ -44,6 +44,29 @@ export interface InputConfig {
         xtream_skip_live: boolean,
         xtream_skip_vod: boolean,
         xtream_skip_series: boolean,
+        xtream_epg_fallback?: boolean,
+    },
+    rate_limit?: {
+        requests_per_second: number,
+        max_concurrent: number,
+    },
+    tls?: {
+        root_ca?: string,
+        accept_invalid_certs: boolean,
+        server_name?: string,
+    },
+    auth?: {
+        basic?: {
+            username: string,
+            password?: string,
+        },
+        token?: {
+            login_url: string,
+            login_method?: string,
+            token_field?: string,
+            token_param?: string,
+            refresh_mins?: number,
+        },
     },
 }
 
@@ -54,7 +77,9 @@ export interface TargetConfig {
         ignore_logo: boolean,
         share_live_streams: boolean,
         remove_duplicates: boolean,
+        remove_duplicate_titles: boolean,
         force_redirect?: string,
+        group_hierarchy: boolean,
     },
     sort: {
         match_as_ascii: boolean,
@@ -84,6 +109,9 @@ export interface TargetConfig {
             resolve_series_delay: number,
             resolve_vod: boolean,
             resolve_vod_delay: number,
+            continue_watching: boolean,
+            // xtream & m3u
+            epg_json: boolean,
             // strm
             style: ExportStyle,
             flat?: boolean,
@@ -108,7 +136,31 @@ export interface TargetConfig {
     ],
     mapping: string[],
     processing_order: ProcessingOrder,
-    watch: string[]
+    watch: string[],
+    locales?: Record<string, Record<string, string>>,
+    delivery?: {
+        url: string,
+        username?: string,
+        password?: string,
+        m3u?: boolean,
+        xmltv?: boolean,
+        m3u_filename?: string,
+        xmltv_filename?: string,
+    }[],
+    mapper_vars?: Record<string, string>,
+    epg_filter?: {
+        keep?: string[],
+        drop?: string[],
+    },
+    adult_detection?: {
+        pattern?: string,
+        provider_flag?: boolean,
+    },
+    epg_placeholder?: {
+        title?: string,
+        duration?: number,
+        hours?: number,
+    },
 }
 
 export interface SourceConfig {
@@ -144,15 +196,39 @@ export interface PushoverConfig {
     user: string;
 }
 
+export interface WelcomeEmailConfig {
+    enabled: boolean;
+    subject?: string;
+    template?: string;
+}
+
+export interface EmailConfig {
+    host: string;
+    port?: number;
+    username?: string;
+    password?: string;
+    from: string;
+    to: string[];
+    welcome?: WelcomeEmailConfig;
+}
+
 export interface MessagingConfig {
     notify_on: string[];
     telegram?: TelegramConfig;
     rest?: RestConfig;
     pushover?: PushoverConfig;
+    email?: EmailConfig;
+    templates?: Record<string, string>;
+}
+
+export interface SanitizeRule {
+    pattern: string,
+    replacement?: string,
 }
 
 export interface LogConfig {
     sanitize_sensitive_info: boolean,
+    sanitize_rules?: SanitizeRule[],
     log_active_user: boolean,
     log_level?: string,
 }
@@ -169,6 +245,17 @@ export interface Credentials {
     status: string,
     ui_enabled: boolean,
     comment: string,
+    locale?: string,
+    timezone?: string,
+    email?: string,
+    devices?: UserDevice[],
+    package?: string,
+}
+
+export interface UserDevice {
+    name: string,
+    token: string,
+    enabled?: boolean,
 }
 
 export interface TargetUser {
@@ -176,19 +263,32 @@ export interface TargetUser {
     credentials: Credentials[];
 }
 
+export type QrCodeKind = 'm3u' | 'xtream';
+export type QrCodeFormat = 'svg' | 'png';
+
 export interface ApiProxyServerInfo {
     name: string;
     protocol: string;
     host: string;
+    host_v6?: string;
     port: string;
     timezone: string;
     message: string;
     path: string;
 }
 
+export interface ApiProxyUserPackage {
+    name: string;
+    live?: string[];
+    vod?: string[];
+    series?: string[];
+    hide_adult?: boolean;
+}
+
 export interface ApiProxyConfig {
     server: ApiProxyServerInfo[];
     user: TargetUser[];
+    packages?: ApiProxyUserPackage[];
 }
 
 export interface ServerApiConfig {
@@ -214,12 +314,25 @@ export interface StreamBufferConfig {
     size: number,
 }
 
+export interface StreamChaosConfig {
+    enabled: boolean,
+    latency_millis?: number,
+    drop_after_secs?: number,
+    error_every_nth?: number,
+    error_status?: number,
+}
+
 export interface StreamConfig {
     retry: boolean,
     buffer?: StreamBufferConfig,
     throttle?: string,
+    throttle_burst?: string,
+    throttle_bitrate_factor?: number,
     grace_period_millis?: number,
     grace_period_timeout_secs?: number,
+    failover_timeout_secs?: number,
+    idle_timeout_secs?: number,
+    chaos?: StreamChaosConfig,
 }
 
 export interface CacheConfig {
@@ -263,11 +376,67 @@ export interface IpCheckConfig {
     pattern_ipv6?: string,
 }
 
+export interface PortalProfileConfig {
+    server: string,
+    title?: string,
+    login_template?: string,
+    account_template?: string,
+}
+
+export interface PortalConfig {
+    enabled: boolean,
+    path?: string,
+    title?: string,
+    login_template?: string,
+    account_template?: string,
+    profiles?: PortalProfileConfig[],
+}
+
+export interface ProviderCacheConfig {
+    enabled: boolean,
+    max_age_secs: number,
+}
+
+export interface MetricsExportConfig {
+    enabled: boolean,
+    url: string,
+    token?: string,
+    interval_secs: number,
+}
+
+export interface ReplicationConfig {
+    enabled: boolean,
+    token: string,
+    primary_url?: string,
+    interval_secs: number,
+    promote_after_secs: number,
+}
+
+export interface BackupUploadConfig {
+    url: string,
+    username?: string,
+    password?: string,
+    access_key?: string,
+    secret_key?: string,
+    region?: string,
+    endpoint?: string,
+}
+
+export interface BackupConfig {
+    enabled: boolean,
+    schedule: string,
+    retention: number,
+    encryption_password?: string,
+    upload?: BackupUploadConfig[],
+}
+
 export interface ServerMainConfig {
     threads: number;
     api: ServerApiConfig;
     working_dir: string;
     backup_dir: string;
+    epg_aliases_path?: string;
+    epg_schedules_path?: string;
     schedules?: Schedule[];
     messaging?: MessagingConfig;
     video?: VideoConfig;
@@ -277,9 +446,25 @@ export interface ServerMainConfig {
     reverse_proxy?: ReverseProxyConfig,
     proxy?: ProxyConfig,
     ipcheck?: IpCheckConfig,
+    portal?: PortalConfig,
+    provider_cache?: ProviderCacheConfig,
+    metrics_export?: MetricsExportConfig,
+    replication?: ReplicationConfig,
+    backup?: BackupConfig,
+}
+
+export interface GlobalRulesConfig {
+    filter?: string,
+    rename?: {
+        field: string,
+        pattern: string,
+        new_name: string
+    }[],
+    mapping?: string[],
 }
 
 export default interface ServerConfig extends ServerMainConfig {
+    global?: GlobalRulesConfig;
     sources: SourceConfig[];
     api_proxy?: ApiProxyConfig;
 }
\ No newline at end of file
@@ -1,3 +1,10 @@
+export interface TargetFreshness {
+    last_success?: number,
+    stale_since?: number,
+    failed_inputs?: string[],
+    notified: boolean,
+}
+
 export interface ServerStatus {
     status: string,
     version: string,
@@ -8,6 +15,14 @@ export interface ServerStatus {
     active_user: number,
     active_user_connections: number,
     active_provider_connections: Record<string, number>,
+    stale_targets?: Record<string, TargetFreshness>,
+}
+
+export interface LogEntry {
+    ts: number,
+    level: string,
+    module: string,
+    message: string,
 }
 
 export interface ServerIpCheck {
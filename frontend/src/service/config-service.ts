@@ -1,6 +1,6 @@
 import ConfigApiService, {DefaultConfigApiService} from "../api/config-api-service";
 import {Observable} from "rxjs";
-import ServerConfig, {ApiProxyServerInfo, ServerMainConfig, TargetUser} from "../model/server-config";
+import ServerConfig, {ApiProxyServerInfo, QrCodeFormat, QrCodeKind, ServerMainConfig, TargetUser} from "../model/server-config";
 import {DefaultUiConfig, UiConfig} from "../model/ui-config";
 
 export default class ConfigService {
@@ -35,4 +35,8 @@ export default class ConfigService {
     saveApiProxyConfig(serverInfo: ApiProxyServerInfo[]) {
         return this.configApiService.saveApiProxyConfig(serverInfo);
     }
+
+    getQrCodeUrl(username: string, kind: QrCodeKind, format?: QrCodeFormat, ttlMins?: number): Observable<string> {
+        return this.configApiService.getQrCodeUrl(username, kind, format, ttlMins);
+    }
 }
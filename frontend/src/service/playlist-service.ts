@@ -2,7 +2,7 @@ import {Observable} from "rxjs";
 import PlaylistApiService, {DefaultPlaylistApiService} from "../api/playlist-api-service";
 import {first} from "rxjs/operators";
 import {PlaylistRequest} from "../model/playlist-request";
-import {PlaylistCategories, PlaylistGroup, PlaylistItem} from "../model/playlist";
+import {PlaylistCategories, PlaylistGroup, PlaylistItem, RefreshHistory} from "../model/playlist";
 
 // const mergeCategory = (groups: PlaylistGroup[], channels: PlaylistItem[]) => {
 //     if (groups?.length  && channels?.length) {
@@ -59,4 +59,8 @@ export default class PlaylistService {
         return this.playlistApiService.getWebPlayerUrl(item, req);
     }
 
+    getRefreshHistory(targetId: number, limit?: number): Observable<RefreshHistory> {
+        return this.playlistApiService.getRefreshHistory(targetId, limit);
+    }
+
 }
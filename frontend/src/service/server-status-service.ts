@@ -1,5 +1,5 @@
 import {Observable} from "rxjs";
-import {ServerIpCheck, ServerStatus} from "../model/server-status";
+import {LogEntry, ServerIpCheck, ServerStatus} from "../model/server-status";
 import ServerStatusApiService, {DefaultServerStatusApiService} from "../api/server-status-api-service";
 
 export default class ServerStatusService {
@@ -13,4 +13,7 @@ export default class ServerStatusService {
     getIpCheck(): Observable<ServerIpCheck> {
         return this.serverStatusApiService.getServerIpCheck();
     }
+    getLogs(level: string, since?: number): Observable<LogEntry[]> {
+        return this.serverStatusApiService.getLogs(level, since);
+    }
 }
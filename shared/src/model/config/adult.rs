@@ -0,0 +1,10 @@
+use crate::utils::default_as_true;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigAdultDetectionDto {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default = "default_as_true")]
+    pub provider_flag: bool,
+}
@@ -13,6 +13,8 @@ pub struct ApiProxyServerInfoDto {
     pub protocol: String,
     pub host: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_v6: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub port: Option<String>,
     pub timezone: String,
     pub message: String,
@@ -20,6 +22,20 @@ pub struct ApiProxyServerInfoDto {
     pub path: Option<String>,
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiProxyUserPackageDto {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub live: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vod: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub series: Vec<String>,
+    #[serde(default)]
+    pub hide_adult: bool,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ApiProxyConfigDto {
@@ -27,4 +43,6 @@ pub struct ApiProxyConfigDto {
     pub user: Vec<TargetUserDto>,
     #[serde(default)]
     pub use_user_db: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub packages: Option<Vec<ApiProxyUserPackageDto>>,
 }
@@ -176,6 +176,15 @@ impl FromStr for ProxyUserStatus {
     }
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProxyUserDeviceDto {
+    pub name: String,
+    pub token: String,
+    #[serde(default = "default_as_true")]
+    pub enabled: bool,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ProxyUserCredentialsDto {
@@ -201,4 +210,14 @@ pub struct ProxyUserCredentialsDto {
     pub ui_enabled: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub comment: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub devices: Option<Vec<ProxyUserDeviceDto>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
 }
@@ -1,4 +1,4 @@
-use crate::model::{WebUiConfigDto, MessagingConfigDto, IpCheckConfigDto, HdHomeRunConfigDto, VideoConfigDto, ScheduleConfigDto, LogConfigDto, ReverseProxyConfigDto, ProxyConfigDto};
+use crate::model::{PortalConfigDto, ProviderCacheConfigDto, MetricsExportConfigDto, ReplicationConfigDto, BackupConfigDto, UserCleanupConfigDto, ClientErrorConfigDto, RecordingConfigDto, CrashReportConfigDto, WebUiConfigDto, MessagingConfigDto, IpCheckConfigDto, HdHomeRunConfigDto, VideoConfigDto, ScheduleConfigDto, LogConfigDto, ReverseProxyConfigDto, ProxyConfigDto};
 use crate::utils::{default_connect_timeout_secs};
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
@@ -25,6 +25,10 @@ pub struct ConfigDto {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub mapping_path: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_aliases_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_schedules_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub custom_stream_response_path: Option<String>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub video: Option<VideoConfigDto>,
@@ -38,6 +42,12 @@ pub struct ConfigDto {
     pub connect_timeout_secs: u32,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub sleep_timer_mins: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_history_size: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stale_notify_mins: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_timeout_mins: Option<u32>,
     #[serde(default)]
     pub update_on_boot: bool,
     #[serde(default)]
@@ -54,6 +64,24 @@ pub struct ConfigDto {
     pub proxy: Option<ProxyConfigDto>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub ipcheck: Option<IpCheckConfigDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub portal: Option<PortalConfigDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_cache: Option<ProviderCacheConfigDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metrics_export: Option<MetricsExportConfigDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replication: Option<ReplicationConfigDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backup: Option<BackupConfigDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_cleanup: Option<UserCleanupConfigDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_error: Option<ClientErrorConfigDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recording: Option<RecordingConfigDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crash_report: Option<CrashReportConfigDto>,
 }
 
 impl ConfigDto {
@@ -0,0 +1,22 @@
+use crate::utils::default_as_true;
+
+fn default_channel_247_group() -> String {
+    String::from("24/7")
+}
+
+fn default_channel_247_separator() -> String {
+    String::from(" | ")
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigChannel247Dto {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    #[serde(default = "default_channel_247_group")]
+    pub group: String,
+    #[serde(default = "default_as_true")]
+    pub alphabetical: bool,
+    #[serde(default = "default_channel_247_separator")]
+    pub separator: String,
+}
@@ -0,0 +1,16 @@
+use crate::utils::{default_client_error_content_type, default_client_error_expired, default_client_error_expired_channel, default_client_error_unauthorized};
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientErrorConfigDto {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_client_error_content_type")]
+    pub content_type: String,
+    #[serde(default = "default_client_error_unauthorized")]
+    pub unauthorized: String,
+    #[serde(default = "default_client_error_expired")]
+    pub expired: String,
+    #[serde(default = "default_client_error_expired_channel")]
+    pub expired_channel: String,
+}
@@ -0,0 +1,14 @@
+use crate::utils::default_crash_report_max_reports;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CrashReportConfigDto {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<String>,
+    #[serde(default = "default_crash_report_max_reports")]
+    pub max_reports: usize,
+    #[serde(default)]
+    pub notify: bool,
+}
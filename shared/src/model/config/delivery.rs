@@ -0,0 +1,19 @@
+use crate::utils::default_as_true;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigDeliveryDto {
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    #[serde(default = "default_as_true")]
+    pub m3u: bool,
+    #[serde(default = "default_as_true")]
+    pub xmltv: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub m3u_filename: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub xmltv_filename: Option<String>,
+}
@@ -1,3 +1,5 @@
+use crate::utils::default_epg_download_timeout_secs;
+
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -19,6 +21,27 @@ pub enum EpgNamePrefix {
     Prefix(String),
 }
 
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EpgPhoneticAlgorithm {
+    Metaphone,
+    #[default]
+    DoubleMetaphone,
+    Cologne,
+    Soundex,
+}
+
+#[derive(Debug, Clone, Copy, serde::Serialize, serde::Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EpgSimilarityMetric {
+    #[default]
+    JaroWinkler,
+    Jaro,
+    Levenshtein,
+    DamerauLevenshtein,
+    SorensenDice,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct EpgSmartMatchConfigDto {
@@ -37,6 +60,12 @@ pub struct EpgSmartMatchConfigDto {
     pub match_threshold: u16,
     #[serde(default)]
     pub best_match_threshold: u16,
+    #[serde(default)]
+    pub phonetic_algorithm: EpgPhoneticAlgorithm,
+    #[serde(default)]
+    pub similarity_metric: EpgSimilarityMetric,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preferred_languages: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -46,4 +75,38 @@ pub struct EpgConfigDto {
     pub sources: Option<Vec<EpgSourceDto>>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub smart_match: Option<EpgSmartMatchConfigDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_shift: Option<String>,
+    #[serde(default = "default_epg_download_timeout_secs")]
+    pub download_timeout_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_download_size_mb: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EpgProgrammeFilterDto {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub keep: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drop: Option<Vec<String>>,
+}
+
+fn default_epg_placeholder_title() -> String {
+    String::from("{name}")
+}
+
+const fn default_epg_placeholder_duration() -> u32 { 60 }
+
+const fn default_epg_placeholder_hours() -> u32 { 48 }
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EpgPlaceholderDto {
+    #[serde(default = "default_epg_placeholder_title")]
+    pub title: String,
+    #[serde(default = "default_epg_placeholder_duration")]
+    pub duration: u32,
+    #[serde(default = "default_epg_placeholder_hours")]
+    pub hours: u32,
 }
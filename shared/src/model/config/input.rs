@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use enum_iterator::Sequence;
 use crate::model::{EpgConfigDto};
-use crate::utils::{default_as_true};
+use crate::utils::{default_as_true, default_auth_token_field, default_auth_token_refresh_mins};
 
 #[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize, Sequence,
     PartialEq, Eq, Default)]
@@ -31,6 +31,8 @@ pub struct ConfigInputOptionsDto {
     pub xtream_live_stream_use_prefix: bool,
     #[serde(default)]
     pub xtream_live_stream_without_extension: bool,
+    #[serde(default)]
+    pub xtream_epg_fallback: bool,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
@@ -49,6 +51,57 @@ pub struct ConfigInputAliasDto {
     pub max_connections: u16,
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigInputRateLimitDto {
+    #[serde(default)]
+    pub requests_per_second: u32,
+    #[serde(default)]
+    pub max_concurrent: u16,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigInputTlsDto {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub root_ca: Option<String>,
+    #[serde(default)]
+    pub accept_invalid_certs: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub server_name: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigInputBasicAuthDto {
+    pub username: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigInputTokenAuthDto {
+    pub login_url: String,
+    #[serde(default)]
+    pub login_method: InputFetchMethod,
+    #[serde(default = "default_auth_token_field")]
+    pub token_field: String,
+    #[serde(default = "default_auth_token_field")]
+    pub token_param: String,
+    #[serde(default = "default_auth_token_refresh_mins")]
+    pub refresh_mins: u32,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigInputAuthDto {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub basic: Option<ConfigInputBasicAuthDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<ConfigInputTokenAuthDto>,
+}
+
 #[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize, Sequence,
     PartialEq, Eq, Default)]
 pub enum InputFetchMethod {
@@ -88,4 +141,10 @@ pub struct ConfigInputDto {
     pub max_connections: u16,
     #[serde(default)]
     pub method: InputFetchMethod,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<ConfigInputRateLimitDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<ConfigInputTlsDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<ConfigInputAuthDto>,
 }
@@ -1,10 +1,24 @@
 use crate::utils::{default_as_true};
 
+fn default_sanitize_replacement() -> String {
+    String::from("***")
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SanitizeRuleDto {
+    pub pattern: String,
+    #[serde(default = "default_sanitize_replacement")]
+    pub replacement: String,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct LogConfigDto {
     #[serde(default = "default_as_true")]
     pub sanitize_sensitive_info: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sanitize_rules: Vec<SanitizeRuleDto>,
     #[serde(default)]
     pub log_active_user: bool,
     #[serde(default, skip_serializing_if = "Option::is_none")]
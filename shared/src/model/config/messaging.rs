@@ -1,5 +1,14 @@
+use std::collections::HashMap;
 use crate::model::MsgKind;
 
+fn default_smtp_port() -> u16 {
+    25
+}
+
+fn default_welcome_subject() -> String {
+    String::from("Your IPTV account")
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct TelegramMessagingConfigDto {
@@ -21,6 +30,34 @@ pub struct PushoverMessagingConfigDto {
     pub user: String,
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WelcomeEmailConfigDto {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_welcome_subject")]
+    pub subject: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmailMessagingConfigDto {
+    pub host: String,
+    #[serde(default = "default_smtp_port")]
+    pub port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password: Option<String>,
+    pub from: String,
+    #[serde(default)]
+    pub to: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub welcome: Option<WelcomeEmailConfigDto>,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct MessagingConfigDto {
@@ -32,5 +69,8 @@ pub struct MessagingConfigDto {
     pub rest: Option<RestMessagingConfigDto>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub pushover: Option<PushoverMessagingConfigDto>,
-
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<EmailMessagingConfigDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub templates: Option<HashMap<MsgKind, String>>,
 }
\ No newline at end of file
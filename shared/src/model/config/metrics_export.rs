@@ -0,0 +1,13 @@
+use crate::utils::default_metrics_export_interval_secs;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetricsExportConfigDto {
+    #[serde(default)]
+    pub enabled: bool,
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(default = "default_metrics_export_interval_secs")]
+    pub interval_secs: u64,
+}
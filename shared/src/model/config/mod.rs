@@ -18,6 +18,20 @@ mod cache;
 mod rate_limit;
 mod proxy;
 mod rename;
+mod category;
+mod channel_247;
+mod adult;
+mod delivery;
+mod portal;
+mod provider_cache;
+mod metrics_export;
+mod replication;
+mod backup;
+mod user_cleanup;
+mod client_error;
+mod recording;
+mod crash_report;
+mod global_rules;
 mod api_proxy;
 mod api_user;
 
@@ -43,3 +57,17 @@ pub use reverse_proxy::*;
 pub use proxy::*;
 pub use trakt::*;
 pub use rename::*;
+pub use category::*;
+pub use channel_247::*;
+pub use adult::*;
+pub use delivery::*;
+pub use portal::*;
+pub use provider_cache::*;
+pub use metrics_export::*;
+pub use replication::*;
+pub use backup::*;
+pub use user_cleanup::*;
+pub use client_error::*;
+pub use recording::*;
+pub use crash_report::*;
+pub use global_rules::*;
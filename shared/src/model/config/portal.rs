@@ -0,0 +1,36 @@
+fn default_portal_path() -> String {
+    String::from("portal")
+}
+
+fn default_portal_title() -> String {
+    String::from("tuliprox")
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PortalProfileConfigDto {
+    pub server: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub login_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_template: Option<String>,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PortalConfigDto {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_portal_path")]
+    pub path: String,
+    #[serde(default = "default_portal_title")]
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub login_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_template: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub profiles: Option<Vec<PortalProfileConfigDto>>,
+}
@@ -0,0 +1,10 @@
+use crate::utils::default_provider_cache_max_age_secs;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProviderCacheConfigDto {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_provider_cache_max_age_secs")]
+    pub max_age_secs: u64,
+}
@@ -0,0 +1,15 @@
+use crate::utils::default_replication_interval_secs;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReplicationConfigDto {
+    #[serde(default)]
+    pub enabled: bool,
+    pub token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub primary_url: Option<String>,
+    #[serde(default = "default_replication_interval_secs")]
+    pub interval_secs: u64,
+    #[serde(default)]
+    pub promote_after_secs: u64,
+}
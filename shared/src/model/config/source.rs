@@ -1,4 +1,4 @@
-use crate::model::{ConfigInputDto};
+use crate::model::{ConfigGlobalRulesDto, ConfigInputDto};
 use crate::model::config::target::ConfigTargetDto;
 
 
@@ -20,6 +20,7 @@ pub struct PatternTemplateDto {
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ConfigSourceDto {
+    #[serde(default)]
     pub inputs: Vec<ConfigInputDto>,
     pub targets: Vec<ConfigTargetDto>,
 }
@@ -29,5 +30,7 @@ pub struct ConfigSourceDto {
 pub struct SourcesConfigDto {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub templates: Option<Vec<PatternTemplateDto>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub global: Option<ConfigGlobalRulesDto>,
     pub sources: Vec<ConfigSourceDto>,
 }
\ No newline at end of file
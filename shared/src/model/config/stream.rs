@@ -1,4 +1,4 @@
-use crate::utils::{default_grace_period_millis, default_grace_period_timeout_secs};
+use crate::utils::{default_failover_timeout_secs, default_grace_period_millis, default_grace_period_timeout_secs};
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 #[serde(deny_unknown_fields)]
@@ -9,6 +9,21 @@ pub struct StreamBufferConfigDto {
     pub size: usize,
 }
 
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct StreamChaosConfigDto {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub latency_millis: u64,
+    #[serde(default)]
+    pub drop_after_secs: u32,
+    #[serde(default)]
+    pub error_every_nth: u32,
+    #[serde(default)]
+    pub error_status: u16,
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct StreamConfigDto {
@@ -18,12 +33,31 @@ pub struct StreamConfigDto {
     pub buffer: Option<StreamBufferConfigDto>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub throttle: Option<String>,
+    // unthrottled size at stream start, like `10MB`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub throttle_burst: Option<String>,
+    // throttle vod streams to their bitrate multiplied by this factor, 0 disables it
+    #[serde(default)]
+    pub throttle_bitrate_factor: f64,
     #[serde(default = "default_grace_period_millis")]
     pub grace_period_millis: u64,
     #[serde(default = "default_grace_period_timeout_secs")]
     pub grace_period_timeout_secs: u64,
+    // wait for a freed slot when max_connections is reached, known devices first, 0 disables it
+    #[serde(default)]
+    pub slot_wait_millis: u64,
     #[serde(default)]
     pub forced_retry_interval_secs: u32,
+    // a failover channel switches to the next url when the provider sends no data within this time, 0 disables it
+    #[serde(default = "default_failover_timeout_secs")]
+    pub failover_timeout_secs: u64,
+    // client streams are closed when the client read nothing within this time, 0 disables it
+    #[serde(default)]
+    pub idle_timeout_secs: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub chaos: Option<StreamChaosConfigDto>,
     #[serde(default, skip)]
     pub throttle_kbps: u64,
+    #[serde(default, skip)]
+    pub throttle_burst_bytes: u64,
 }
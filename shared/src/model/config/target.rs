@@ -1,5 +1,7 @@
-use crate::model::{ClusterFlags, ConfigRenameDto, ConfigSortDto, ProcessingOrder, StrmExportStyle, TargetType, TraktConfigDto};
+use crate::model::{ClusterFlags, ConfigAdultDetectionDto, ConfigCategoryRuleDto, ConfigChannel247Dto, ConfigDeliveryDto, ConfigRenameDto, ConfigSortDto, EpgPlaceholderDto, EpgProgrammeFilterDto, ProcessingOrder, StrmExportStyle, TargetType, TraktConfigDto};
 use crate::utils::{default_as_true, default_resolve_delay_secs, default_as_default};
+use std::collections::HashMap;
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
 #[serde(deny_unknown_fields)]
 pub struct ConfigTargetOptionsDto {
@@ -9,8 +11,16 @@ pub struct ConfigTargetOptionsDto {
     pub share_live_streams: bool,
     #[serde(default)]
     pub remove_duplicates: bool,
+    #[serde(default)]
+    pub remove_duplicate_titles: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub force_redirect: Option<ClusterFlags>,
+    #[serde(default)]
+    pub group_hierarchy: bool,
+    #[serde(default)]
+    pub compress_store: bool,
+    #[serde(default)]
+    pub channel_failover: bool,
 }
 
 #[allow(clippy::struct_excessive_bools)]
@@ -33,8 +43,14 @@ pub struct XtreamTargetOutputDto {
     pub resolve_vod_delay: u16,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub trakt: Option<TraktConfigDto>,
+    #[serde(default)]
+    pub continue_watching: bool,
+    #[serde(default)]
+    pub epg_json: bool,
 }
 
+// the flags are independent options of the m3u output config
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct M3uTargetOutputDto {
@@ -44,6 +60,10 @@ pub struct M3uTargetOutputDto {
     pub include_type_in_url: bool,
     #[serde(default)]
     pub mask_redirect_url: bool,
+    #[serde(default)]
+    pub epg_json: bool,
+    #[serde(default)]
+    pub catchup: bool,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -100,12 +120,38 @@ pub struct ConfigTargetDto {
     pub output: Vec<TargetOutputDto>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub rename: Option<Vec<ConfigRenameDto>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub categories: Option<Vec<ConfigCategoryRuleDto>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub channel_247: Option<ConfigChannel247Dto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adult_detection: Option<ConfigAdultDetectionDto>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub mapping: Option<Vec<String>>,
     #[serde(default)]
     pub processing_order: ProcessingOrder,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub watch: Option<Vec<String>>,
+    // locale -> (category name -> translated name), the locale is selected by the user
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locales: Option<HashMap<String, HashMap<String, String>>>,
+    // upload the generated m3u and xmltv after each update
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delivery: Option<Vec<ConfigDeliveryDto>>,
+    // names of the targets whose processed playlists are the input of this target
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub depends_on: Option<Vec<String>>,
+    // values read by the mapper scripts with var("key")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mapper_vars: Option<HashMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_filter: Option<EpgProgrammeFilterDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_placeholder: Option<EpgPlaceholderDto>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_days_past: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_days_future: Option<u16>,
 }
 
 
@@ -0,0 +1,24 @@
+use crate::utils::default_user_cleanup_grace_days;
+
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UserCleanupAction {
+    #[default]
+    Disable,
+    Delete,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserCleanupConfigDto {
+    #[serde(default)]
+    pub enabled: bool,
+    pub schedule: String,
+    pub inactive_days: u32,
+    #[serde(default)]
+    pub action: UserCleanupAction,
+    #[serde(default = "default_user_cleanup_grace_days")]
+    pub grace_days: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exclude: Option<Vec<String>>,
+}
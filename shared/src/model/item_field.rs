@@ -20,6 +20,10 @@ pub enum ItemField {
     Type,
     #[serde(rename = "caption")]
     Caption,
+    #[serde(rename = "country")]
+    Country,
+    #[serde(rename = "language")]
+    Language,
 }
 
 impl ItemField {
@@ -30,6 +34,8 @@ impl ItemField {
     const INPUT: &'static str = "Input";
     const TYPE: &'static str = "Type";
     const CAPTION: &'static str = "Caption";
+    const COUNTRY: &'static str = "Country";
+    const LANGUAGE: &'static str = "Language";
 
     pub fn as_str(&self) -> &'static str {
         match *self {
@@ -40,6 +46,8 @@ impl ItemField {
             Self::Input => Self::INPUT,
             Self::Type => Self::TYPE,
             Self::Caption => Self::CAPTION,
+            Self::Country => Self::COUNTRY,
+            Self::Language => Self::LANGUAGE,
         }
     }
 }
@@ -54,6 +62,8 @@ impl Display for ItemField {
             Self::Input => Self::INPUT,
             Self::Type => Self::TYPE,
             Self::Caption => Self::CAPTION,
+            Self::Country => Self::COUNTRY,
+            Self::Language => Self::LANGUAGE,
         })
     }
 }
@@ -76,6 +86,10 @@ impl FromStr for ItemField {
             Ok(Self::Input)
         } else if s.eq_ignore_ascii_case(Self::TYPE) {
             Ok(Self::Type)
+        } else if s.eq_ignore_ascii_case(Self::COUNTRY) {
+            Ok(Self::Country)
+        } else if s.eq_ignore_ascii_case(Self::LANGUAGE) {
+            Ok(Self::Language)
         } else {
             create_tuliprox_error_result!(TuliproxErrorKind::Info, "Unknown InputType: {}", s)
         }
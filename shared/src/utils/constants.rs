@@ -10,6 +10,8 @@ pub const CONFIG_PATH: &str = "config";
 pub const CONFIG_FILE: &str = "config.yml";
 pub const SOURCE_FILE: &str = "source.yml";
 pub const MAPPING_FILE: &str = "mapping.yml";
+pub const EPG_ALIASES_FILE: &str = "epg_aliases.yml";
+pub const EPG_SCHEDULES_FILE: &str = "epg_schedules.yml";
 pub const API_PROXY_FILE: &str = "api-proxy.yml";
 
 
@@ -92,6 +94,10 @@ pub struct Constants {
     pub allowed_output_formats: Vec<String>,
     pub re_trakt_year:  Regex,
     pub re_quality:  Regex,
+    pub re_title_season_episode: Regex,
+    pub re_title_year: Regex,
+    pub re_title_language_prefix: Regex,
+    pub re_title_brackets: Regex,
 }
 
 pub static CONSTANTS: LazyLock<Constants> = LazyLock::new(||
@@ -139,5 +145,9 @@ pub static CONSTANTS: LazyLock<Constants> = LazyLock::new(||
         ].into_iter().collect::<HashSet<&str>>(),
         re_trakt_year: Regex::new(r"\(?(\d{4})\)?$").unwrap(),
         re_quality: Regex::new(r"(?i)\b(4K|UHD|8K|2160p?|1080p?|720p?|480p?|BLURAY|HDTV|DVDRIP|CAM|TS|HDR|DV|SDR)\b").unwrap(),
+        re_title_season_episode: Regex::new(r"(?i)\bS(\d{1,2})\s*E(\d{1,4})\b|\b(\d{1,2})x(\d{2,4})\b").unwrap(),
+        re_title_year: Regex::new(r"[(\[]?\b((?:19|20)\d{2})\b[)\]]?").unwrap(),
+        re_title_language_prefix: Regex::new(r"^\s*[\[(|]?\s*([A-Za-z]{2,3})\s*(?:[\])|:]|\s-)\s*-?\s*").unwrap(),
+        re_title_brackets: Regex::new(r"[(\[]\s*[)\]]").unwrap(),
     }
 );
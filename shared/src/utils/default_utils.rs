@@ -10,4 +10,18 @@ pub const fn default_resolve_delay_secs() -> u16 { 2 }
 // helping avoid triggering hard max_connection enforcement.
 pub const fn default_grace_period_millis() -> u64 { 400 }
 pub const fn default_grace_period_timeout_secs() -> u64 { 2 }
-pub const fn default_connect_timeout_secs() -> u32 { 6 }
\ No newline at end of file
+pub const fn default_failover_timeout_secs() -> u64 { 5 }
+pub const fn default_connect_timeout_secs() -> u32 { 6 }
+pub const fn default_provider_cache_max_age_secs() -> u64 { 3600 }
+pub const fn default_metrics_export_interval_secs() -> u64 { 60 }
+pub const fn default_replication_interval_secs() -> u64 { 60 }
+pub const fn default_backup_retention() -> usize { 7 }
+pub const fn default_user_cleanup_grace_days() -> u32 { 7 }
+pub const fn default_crash_report_max_reports() -> usize { 10 }
+pub const fn default_epg_download_timeout_secs() -> u64 { 300 }
+pub const fn default_auth_token_refresh_mins() -> u32 { 60 }
+pub fn default_auth_token_field() -> String { String::from("token") }
+pub fn default_client_error_content_type() -> String { String::from("text/plain; charset=utf-8") }
+pub fn default_client_error_unauthorized() -> String { String::from("Access denied, please check your username and password.") }
+pub fn default_client_error_expired() -> String { String::from("Your account has expired or is disabled, please contact your provider.") }
+pub fn default_client_error_expired_channel() -> String { String::from("Account expired") }
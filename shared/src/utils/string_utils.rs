@@ -55,7 +55,7 @@ pub fn get_non_empty_str<'a>(first: &'a str, second: &'a str, third: &'a str) ->
 #[cfg(test)]
 mod test {
     use std::collections::HashSet;
-    use super::generate_random_string;
+    use super::{generate_random_string, Capitalize};
 
     #[test]
     fn test_generate_random_string() {